@@ -1,6 +1,9 @@
 use crate::chunking::sentence_boundary::SentenceBoundaryDetector;
 use async_trait::async_trait;
+use runar::{HeuristicTokenizer, Tokenizer, TokenizerRegistry};
+use std::sync::Arc;
 use thiserror::Error;
+use tracing::warn;
 
 #[derive(Debug, Error)]
 pub enum ChunkingError {
@@ -27,6 +30,7 @@ pub struct SemanticChunker {
     chunk_size: u64,
     overlap_size: u64,
     sentence_detector: SentenceBoundaryDetector,
+    tokenizer: Arc<dyn Tokenizer>,
 }
 
 impl SemanticChunker {
@@ -35,14 +39,39 @@ impl SemanticChunker {
             chunk_size,
             overlap_size,
             sentence_detector: SentenceBoundaryDetector::new(),
+            tokenizer: Arc::new(HeuristicTokenizer),
         }
     }
 
-    /// Count tokens in text (simplified - would use tiktoken in real implementation)
+    /// Use the tokenizer of the embedding/LLM model for chunk sizes
+    /// (e.g. `TokenizerRegistry::for_model`); defaults to the chars/4 heuristic.
+    pub fn with_tokenizer(mut self, tokenizer: Arc<dyn Tokenizer>) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// Chunker measuring sizes with `model`'s tokenizer from `tokenizers`; models without a
+    /// loaded vocabulary fall back to the chars/4 heuristic.
+    pub fn for_model(chunk_size: u64, overlap_size: u64, tokenizers: &TokenizerRegistry, model: &str) -> Self {
+        if tokenizers.get(model).is_none() {
+            warn!("No tokenizer for {}, chunk sizes use the chars/4 heuristic", model);
+        }
+        Self::new(chunk_size, overlap_size).with_tokenizer(tokenizers.for_model(model))
+    }
+
+    /// Count tokens in text
     fn count_tokens(&self, text: &str) -> u64 {
-        // Simplified token counting - split by whitespace
-        // In real implementation, would use tiktoken
-        text.split_whitespace().count() as u64
+        self.tokenizer.count(text) as u64
+    }
+
+    /// Trailing words of a chunk that fit into the overlap size
+    fn overlap_words(&self, chunk_text: &str) -> Vec<String> {
+        let words: Vec<&str> = chunk_text.split_whitespace().collect();
+        let mut start = words.len();
+        while start > 0 && self.count_tokens(&words[start - 1..].join(" ")) <= self.overlap_size {
+            start -= 1;
+        }
+        words[start..].iter().map(|s| s.to_string()).collect()
     }
 
     /// Create chunks with overlap
@@ -62,12 +91,7 @@ impl SemanticChunker {
                 chunks.push(chunk_text.clone());
                 
                 // Create overlap buffer from end of chunk
-                let overlap_tokens = self.overlap_size.min(current_tokens);
-                let words: Vec<&str> = chunk_text.split_whitespace().collect();
-                let overlap_word_count = (overlap_tokens as usize).min(words.len());
-                overlap_buffer = words[words.len().saturating_sub(overlap_word_count)..].iter()
-                    .map(|s| s.to_string())
-                    .collect();
+                overlap_buffer = self.overlap_words(&chunk_text);
                 
                 // Start new chunk with overlap
                 current_chunk = overlap_buffer.clone();
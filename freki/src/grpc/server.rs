@@ -41,6 +41,24 @@ impl FrekiServiceImpl {
             audit_logger,
         }
     }
+
+    /// Chunks indexed documents with `chunker` (sizes in the embedding model's tokens)
+    pub fn with_chunker(mut self, chunker: Arc<dyn crate::chunking::DocumentChunker>) -> Self {
+        self.document_indexer = Arc::new(
+            crate::indexing::DocumentIndexer::new((*self.vector_db).clone(), self.collection_name.clone())
+                .with_chunker(chunker),
+        );
+        self
+    }
+}
+
+/// Odin's request id from the trace context, so Freki's logs and audit entries match the
+/// request; calls without one get a fresh id.
+fn request_id() -> String {
+    vegvisir::baggage(&tracing::Span::current())
+        .get(vegvisir::REQUEST_ID)
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string())
 }
 
 #[tonic::async_trait]
@@ -49,7 +67,7 @@ impl FrekiService for FrekiServiceImpl {
         &self,
         request: Request<freki::IndexDocumentRequest>,
     ) -> Result<Response<freki::IndexDocumentResponse>, Status> {
-        let request_id = Uuid::new_v4().to_string();
+        let request_id = request_id();
         let (document, embedding, document_id, document_indexer, audit_logger) = {
             let _guard = info_span!("index_document", request_id = %request_id).entered();
             let req = request.into_inner();
@@ -83,7 +101,7 @@ impl FrekiService for FrekiServiceImpl {
         &self,
         request: Request<freki::RetrieveContextRequest>,
     ) -> Result<Response<freki::RetrieveContextResponse>, Status> {
-        let request_id = Uuid::new_v4().to_string();
+        let request_id = request_id();
         let (query_embedding, limit, context_retriever, audit_logger) = {
             let _guard = info_span!("retrieve_context", request_id = %request_id).entered();
             let req = request.into_inner();
@@ -123,6 +141,7 @@ pub struct GrpcServerDependencies {
     pub vector_db: Arc<crate::vector_db::VectorDbClient>,
     pub collection_name: String,
     pub audit_logger: Arc<crate::utils::AuditLogger>,
+    pub chunker: Arc<dyn crate::chunking::DocumentChunker>,
 }
 
 pub async fn start_grpc_server(
@@ -135,9 +154,11 @@ pub async fn start_grpc_server(
         deps.vector_db,
         deps.collection_name,
         deps.audit_logger,
-    );
+    )
+    .with_chunker(deps.chunker);
 
     Server::builder()
+        .layer(vegvisir::ServerTraceLayer)
         .add_service(FrekiServiceServer::new(freki_service))
         .serve(addr)
         .await?;
@@ -6,7 +6,7 @@ use std::sync::Arc;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    logging::init_logging()?;
+    let trace_exporter = logging::init_logging()?;
 
     info!("Freki RAG Service starting...");
 
@@ -17,6 +17,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     let settings = settings_manager.get().await;
     info!("Configuration loaded");
+    trace_exporter.start(&settings.trace_export);
 
     // Initialize Qdrant client
     let vector_db = Arc::new(freki::vector_db::VectorDbClient::new(&settings.qdrant_url).await?);
@@ -29,6 +30,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         info!("Collection '{}' already exists or creation failed", collection_name);
     }
 
+    // Chunk sizes in tokens of the embedding model; the heuristic only without its vocabulary
+    let mut tokenizers = runar::TokenizerRegistry::new();
+    if let Some(dir) = &settings.chunking.tokenizers_dir {
+        if let Err(e) = tokenizers.load_dir(dir) {
+            tracing::warn!("Failed to load tokenizers from {}: {}", dir, e);
+        }
+    }
+    let chunker = Arc::new(freki::chunking::SemanticChunker::for_model(
+        settings.chunking.chunk_size,
+        settings.chunking.overlap_size,
+        &tokenizers,
+        &settings.embedding_model,
+    ));
+
     // Start gRPC server
     let addr = std::net::SocketAddr::from(([0, 0, 0, 0], settings.grpc_port));
     let audit_logger = Arc::new(freki::utils::AuditLogger::with_tracing());
@@ -36,6 +51,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         vector_db,
         collection_name,
         audit_logger,
+        chunker,
     };
     let server_handle = tokio::spawn(async move {
         if let Err(e) = freki::grpc::start_grpc_server(addr, deps).await {
@@ -14,6 +14,31 @@ pub enum SettingsError {
     EmptyQdrantUrl,
     #[error("embedding_model must be non-empty")]
     EmptyEmbeddingModel,
+    #[error("Invalid trace export configuration: {0}")]
+    InvalidTraceExport(String),
+    #[error("chunking.chunk_size must be non-zero and larger than chunking.overlap_size")]
+    InvalidChunking,
+}
+
+/// Chunking beim Indizieren; Größen in Tokens des Embedding-Models.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChunkingConfig {
+    pub chunk_size: u64,
+    pub overlap_size: u64,
+    /// Verzeichnis mit `tokenizer.json`-Vokabularen (wie Geris Models-Verzeichnis); ohne Vokabular
+    /// für `embedding_model` wird mit ~4 Zeichen pro Token gerechnet.
+    pub tokenizers_dir: Option<String>,
+}
+
+impl Default for ChunkingConfig {
+    fn default() -> Self {
+        Self {
+            chunk_size: 1000,
+            overlap_size: 100,
+            tokenizers_dir: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +46,11 @@ pub struct FrekiSettings {
     pub grpc_port: u16,
     pub qdrant_url: String,
     pub embedding_model: String,
+    #[serde(default)]
+    pub chunking: ChunkingConfig,
+    /// Span-Export (OTLP-Collector und/oder rollierende JSON-Datei), siehe Vegvisir.
+    #[serde(default)]
+    pub trace_export: vegvisir::TraceExportConfig,
 }
 
 impl FrekiSettings {
@@ -34,6 +64,10 @@ impl FrekiSettings {
         if self.embedding_model.trim().is_empty() {
             return Err(SettingsError::EmptyEmbeddingModel);
         }
+        if self.chunking.chunk_size == 0 || self.chunking.overlap_size >= self.chunking.chunk_size {
+            return Err(SettingsError::InvalidChunking);
+        }
+        self.trace_export.validate().map_err(SettingsError::InvalidTraceExport)?;
         Ok(())
     }
 }
@@ -44,6 +78,8 @@ impl Default for FrekiSettings {
             grpc_port: 50053,
             qdrant_url: "http://localhost:6333".to_string(),
             embedding_model: "all-MiniLM-L6-v2".to_string(),
+            chunking: ChunkingConfig::default(),
+            trace_export: vegvisir::TraceExportConfig::default(),
         }
     }
 }
@@ -148,4 +184,11 @@ mod tests {
         s.embedding_model = "   ".to_string();
         assert!(matches!(s.validate(), Err(SettingsError::EmptyEmbeddingModel)));
     }
+
+    #[test]
+    fn test_validate_overlap_not_smaller_than_chunk() {
+        let mut s = FrekiSettings::default();
+        s.chunking.overlap_size = s.chunking.chunk_size;
+        assert!(matches!(s.validate(), Err(SettingsError::InvalidChunking)));
+    }
 }
@@ -3,6 +3,7 @@
 //! Log-Levels: trace, debug, info, warn, error (via RUST_LOG, z.B. `RUST_LOG=freki=debug`).
 //! Context-Tracking: request_id in Spans (gRPC-Handler); alle Logs innerhalb eines Spans erben die Felder.
 //! Log-Rotation: optional via FREKI_LOG_FILE (z.B. `/var/log/freki/freki.log`) mit täglicher Rotation.
+//! Distributed Tracing: Spans gehen an Vegvisir (Export über `trace_export` in den Settings).
 
 use tracing_subscriber::{
     fmt,
@@ -17,12 +18,15 @@ use std::path::Path;
 /// - **RUST_LOG**: Filter (Default: `info`). Beispiele: `debug`, `freki=debug`, `freki=info,warn`.
 /// - **FREKI_LOG_JSON**: `1` = JSON-Format (für zentrale Log-Aggregation).
 /// - **FREKI_LOG_FILE**: Pfad für Log-Datei → tägliche Rotation (z.B. `freki.log` → `freki.log.2025-01-31`).
-pub fn init_logging() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+///
+/// Der zurückgegebene Exporter wird nach dem Laden der Settings gestartet.
+pub fn init_logging() -> Result<vegvisir::TraceExporter, Box<dyn std::error::Error + Send + Sync>> {
     let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
     let json = std::env::var("FREKI_LOG_JSON").map(|v| v == "1" || v.eq_ignore_ascii_case("true")).unwrap_or(false);
     let log_file = std::env::var("FREKI_LOG_FILE").ok();
 
-    let base = tracing_subscriber::registry().with(env_filter);
+    let (trace_layer, trace_exporter) = vegvisir::layer("freki");
+    let base = tracing_subscriber::registry().with(env_filter).with(trace_layer);
 
     if let Some(ref path) = log_file {
         let directory = Path::new(path).parent().unwrap_or(Path::new("."));
@@ -75,5 +79,5 @@ pub fn init_logging() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         base.with(layer).init();
     }
 
-    Ok(())
+    Ok(trace_exporter)
 }
@@ -1,8 +1,24 @@
 #[cfg(test)]
 mod tests {
     use freki::chunking::{DocumentChunker, SemanticChunker};
+    use runar::{Tokenizer, TokenizerRegistry};
+    use std::sync::Arc;
     use std::time::Duration;
 
+    /// Test tokenizer: every character is one token
+    #[derive(Debug)]
+    struct CharTokenizer;
+
+    impl Tokenizer for CharTokenizer {
+        fn name(&self) -> &str {
+            "chars"
+        }
+
+        fn count(&self, text: &str) -> usize {
+            text.trim().chars().count()
+        }
+    }
+
     #[tokio::test]
     async fn test_semantic_chunker_creation() {
         // Test semantic chunker creation
@@ -54,4 +70,32 @@ mod tests {
             assert!(chunks.len() >= 1);
         }
     }
+
+    #[tokio::test]
+    async fn test_chunk_sizes_follow_tokenizer() {
+        let document = "Sentence one. Sentence two. Sentence three. Sentence four. Sentence five.";
+
+        let heuristic = SemanticChunker::new(20, 0);
+        let chunks = heuristic.chunk_document(document).await.unwrap();
+        assert_eq!(chunks.len(), 1);
+
+        let per_char = SemanticChunker::new(20, 0).with_tokenizer(Arc::new(CharTokenizer));
+        let chunks = per_char.chunk_document(document).await.unwrap();
+        assert_eq!(chunks.len(), 5);
+        assert!(chunks.iter().all(|c| c.chars().count() <= 20));
+    }
+
+    #[tokio::test]
+    async fn test_chunker_for_model_uses_registered_vocabulary() {
+        let document = "Sentence one. Sentence two. Sentence three. Sentence four. Sentence five.";
+        let mut tokenizers = TokenizerRegistry::new();
+        tokenizers.register("all-MiniLM-L6-v2", Arc::new(CharTokenizer));
+
+        let chunker = SemanticChunker::for_model(20, 0, &tokenizers, "all-MiniLM-L6-v2");
+        assert_eq!(chunker.chunk_document(document).await.unwrap().len(), 5);
+
+        // Without a vocabulary for the model the heuristic applies
+        let fallback = SemanticChunker::for_model(20, 0, &tokenizers, "bge-small-en");
+        assert_eq!(fallback.chunk_document(document).await.unwrap().len(), 1);
+    }
 }
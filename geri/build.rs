@@ -3,5 +3,19 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .build_server(true)
         .build_client(false)
         .compile(&["proto/geri.proto"], &["proto"])?;
+
+    // Heimdall secret service: provider API keys and the key-store key (client only)
+    tonic_build::configure()
+        .build_server(false)
+        .build_client(true)
+        .compile(&["proto/heimdall/secrets.proto"], &["proto"])?;
+
+    // Heimdall token validation for the OpenAI-compatible HTTP API (client only)
+    if std::env::var_os("CARGO_FEATURE_OPENAI_API").is_some() {
+        tonic_build::configure()
+            .build_server(false)
+            .build_client(true)
+            .compile(&["proto/heimdall/token.proto"], &["proto"])?;
+    }
     Ok(())
 }
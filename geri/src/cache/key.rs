@@ -0,0 +1,141 @@
+//! Cache-Key (Phase 11.1.2): stabiler SHA-256 über den normalisierten Request, Model-ID und User.
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::llm::{ChatRole, PromptRequest, ResponseFormat, ToolCall, ToolDefinition, ToolResult};
+
+/// Erhöhen, wenn sich die Normalisierung ändert (alte Einträge werden dann nicht mehr getroffen).
+const KEY_VERSION: u32 = 2;
+
+/// Schlüssel eines Cache-Eintrags.
+///
+/// `hash` deckt den kompletten Request ab; `scope` alles außer dem aktuellen User-Turn
+/// (Model, User, System-Prompt, Kontext, Verlauf, Tools, Response-Format) und begrenzt die Ähnlichkeitssuche.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheKey {
+    hash: String,
+    scope: String,
+    prompt: String,
+    user_id: Option<String>,
+    embedding: Option<Vec<f32>>,
+}
+
+#[derive(Serialize)]
+struct NormalizedMessage<'a> {
+    role: ChatRole,
+    content: String,
+    name: Option<&'a str>,
+    tool_call_id: Option<&'a str>,
+    tool_calls: &'a [ToolCall],
+}
+
+#[derive(Serialize)]
+struct NormalizedRequest<'a> {
+    version: u32,
+    model_id: &'a str,
+    user_id: Option<&'a str>,
+    system_prompt: String,
+    context: String,
+    max_tokens: Option<u32>,
+    messages: Vec<NormalizedMessage<'a>>,
+    tools: &'a [ToolDefinition],
+    tool_calls: &'a [ToolCall],
+    tool_results: &'a [ToolResult],
+    response_format: Option<&'a ResponseFormat>,
+}
+
+impl CacheKey {
+    /// Key für den Request an das angegebene Model.
+    pub fn new(request: &PromptRequest, model_id: &str) -> Self {
+        let conversation = request.conversation();
+        let mut messages: Vec<NormalizedMessage> = conversation
+            .iter()
+            .map(|m| NormalizedMessage {
+                role: m.role,
+                content: normalize(&m.content),
+                name: m.name.as_deref(),
+                tool_call_id: m.tool_call_id.as_deref(),
+                tool_calls: &m.tool_calls,
+            })
+            .collect();
+        let mut normalized = NormalizedRequest {
+            version: KEY_VERSION,
+            model_id,
+            user_id: request.user_id.as_deref(),
+            system_prompt: normalize(request.system_prompt.as_deref().unwrap_or("")),
+            context: normalize(request.context.as_deref().unwrap_or("")),
+            max_tokens: request.max_tokens,
+            messages: Vec::new(),
+            tools: &request.tools,
+            tool_calls: &request.tool_calls,
+            tool_results: &request.tool_results,
+            response_format: request.response_format.as_ref(),
+        };
+
+        // The current user turn is what the similarity lookup compares
+        let prompt = match messages.last() {
+            Some(last) if last.role == ChatRole::User => messages.pop().map(|m| m.content).unwrap_or_default(),
+            _ => String::new(),
+        };
+        normalized.messages = messages;
+        let scope = digest(&normalized);
+        if !prompt.is_empty() {
+            normalized.messages.push(NormalizedMessage {
+                role: ChatRole::User,
+                content: prompt.clone(),
+                name: conversation.last().and_then(|m| m.name.as_deref()),
+                tool_call_id: None,
+                tool_calls: &[],
+            });
+        }
+
+        Self {
+            hash: digest(&normalized),
+            scope,
+            prompt,
+            user_id: request.user_id.clone(),
+            embedding: None,
+        }
+    }
+
+    /// Hängt das Embedding des aktuellen User-Turns an (für die Ähnlichkeitssuche).
+    pub fn with_embedding(mut self, embedding: Vec<f32>) -> Self {
+        self.embedding = Some(embedding);
+        self
+    }
+
+    /// Hex-kodierter SHA-256 des kompletten Requests.
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    /// Hash aller Request-Teile außer dem aktuellen User-Turn.
+    pub fn scope(&self) -> &str {
+        &self.scope
+    }
+
+    /// Normalisierter aktueller User-Turn (leer, wenn der Request nicht mit einem User-Turn endet).
+    pub fn prompt(&self) -> &str {
+        &self.prompt
+    }
+
+    pub fn user_id(&self) -> Option<&str> {
+        self.user_id.as_deref()
+    }
+
+    pub fn embedding(&self) -> Option<&[f32]> {
+        self.embedding.as_deref()
+    }
+}
+
+/// Whitespace-Normalisierung: Leerzeichen am Rand entfernen, Folgen von Whitespace zusammenfassen.
+fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn digest<T: Serialize>(value: &T) -> String {
+    // serde_json serialisiert Maps (Tool-Schemas, Argumente) mit sortierten Keys, daher stabil
+    let bytes = serde_json::to_vec(value).unwrap_or_default();
+    Sha256::digest(&bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
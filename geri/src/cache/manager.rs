@@ -1,64 +1,289 @@
-//! Cache-Manager (Phase 11.1.1): Response-Caching, Cache-Key (Prompt-Hash), TTL-Expiration.
+//! Cache-Manager (Phase 11.1.1): Response-Caching mit stabilem Request-Key, TTL, LRU-Größenlimit
+//! und optionaler Persistenz auf der Platte.
 
 use std::collections::HashMap;
-use std::hash::{Hash, Hasher};
-use std::time::{Duration, Instant};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-/// Cached-Eintrag: Response und Einfügezeit für TTL-Prüfung.
-#[derive(Debug, Clone)]
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::cache::CacheKey;
+use crate::llm::PromptResponse;
+
+/// Konfiguration des Response-Caches.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    pub enabled: bool,
+    /// Lebensdauer eines Eintrags in Sekunden.
+    pub ttl_secs: u64,
+    /// Verzeichnis für persistente Einträge; ohne Verzeichnis nur im Speicher.
+    pub dir: Option<String>,
+    /// Maximale Anzahl Einträge (LRU-Verdrängung).
+    pub max_entries: usize,
+    /// Maximale Gesamtgröße der Einträge in Bytes (LRU-Verdrängung).
+    pub max_bytes: u64,
+    /// Cosinus-Ähnlichkeit (0.0–1.0), ab der ein ähnlicher Prompt als Treffer gilt; ohne Wert nur exakte Treffer.
+    pub similarity_threshold: Option<f32>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_secs: 3600,
+            dir: None,
+            max_entries: 10_000,
+            max_bytes: 64 * 1024 * 1024,
+            similarity_threshold: None,
+        }
+    }
+}
+
+/// Cached-Eintrag; wird als JSON-Datei `<hash>.json` persistiert.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct CacheEntry {
-    response: String,
-    inserted_at: Instant,
+    hash: String,
+    scope: String,
+    user_id: Option<String>,
+    response: PromptResponse,
+    embedding: Option<Vec<f32>>,
+    /// Unix-Zeit in Sekunden (TTL über Neustarts hinweg).
+    created_at: u64,
+    #[serde(skip)]
+    last_access: u64,
+    #[serde(skip)]
+    size: u64,
 }
 
-/// Cached LLM-Responses für Prompts (Cache-Key = Hash des Prompts, TTL-basierte Expiration).
+/// Cached LLM-Responses pro Request (Key = SHA-256 über normalisierten Request, Model und User).
 #[derive(Debug, Clone)]
 pub struct CacheManager {
-    entries: HashMap<u64, CacheEntry>,
+    entries: HashMap<String, CacheEntry>,
     ttl: Duration,
+    dir: Option<PathBuf>,
+    max_entries: usize,
+    max_bytes: u64,
+    similarity_threshold: Option<f32>,
+    total_bytes: u64,
+    /// Logische Uhr für die LRU-Reihenfolge.
+    clock: u64,
 }
 
 impl CacheManager {
-    /// Erstellt einen Cache mit der angegebenen TTL (z. B. 60s).
+    /// Erstellt einen reinen In-Memory-Cache mit der angegebenen TTL (z. B. 60s).
     pub fn new(ttl: Duration) -> Self {
+        let config = CacheConfig::default();
         Self {
             entries: HashMap::new(),
             ttl,
+            dir: None,
+            max_entries: config.max_entries,
+            max_bytes: config.max_bytes,
+            similarity_threshold: None,
+            total_bytes: 0,
+            clock: 0,
         }
     }
 
-    /// Liefert den Cache-Key für einen Prompt (deterministischer Hash).
-    fn cache_key(prompt: &str) -> u64 {
-        let mut hasher = std::collections::hash_map::DefaultHasher::new();
-        prompt.hash(&mut hasher);
-        hasher.finish()
+    /// Erstellt den Cache aus der Konfiguration und lädt vorhandene Einträge aus `dir`.
+    pub fn open(config: &CacheConfig) -> io::Result<Self> {
+        let mut cache = Self::new(Duration::from_secs(config.ttl_secs));
+        cache.max_entries = config.max_entries.max(1);
+        cache.max_bytes = config.max_bytes;
+        cache.similarity_threshold = config.similarity_threshold;
+        if let Some(dir) = &config.dir {
+            let dir = PathBuf::from(dir);
+            std::fs::create_dir_all(&dir)?;
+            cache.load_dir(&dir)?;
+            cache.dir = Some(dir);
+            cache.evict();
+        }
+        Ok(cache)
     }
 
-    /// Liefert die gecachte Response für den Prompt, oder None bei Miss/Expiration.
-    pub fn get(&mut self, prompt: &str) -> Option<String> {
-        let key = Self::cache_key(prompt);
-        let entry = self.entries.get(&key)?;
-        if entry.inserted_at.elapsed() >= self.ttl {
-            self.entries.remove(&key);
+    /// `true`, wenn Einträge auf der Platte liegen (Zugriffe machen dann Datei-I/O).
+    pub fn is_persistent(&self) -> bool {
+        self.dir.is_some()
+    }
+
+    /// Ähnlichkeits-Schwelle; `None` = nur exakte Treffer.
+    pub fn similarity_threshold(&self) -> Option<f32> {
+        self.similarity_threshold
+    }
+
+    /// Liefert die gecachte Response für den Key, oder None bei Miss/Expiration.
+    pub fn get(&mut self, key: &CacheKey) -> Option<PromptResponse> {
+        let hash = key.hash();
+        if self.is_expired(self.entries.get(hash)?) {
+            self.remove(hash);
             return None;
         }
-        Some(entry.response.clone())
+        self.touch(hash)
     }
 
-    /// Speichert die Response für den Prompt (überschreibt bei gleichem Key).
-    pub fn insert(&mut self, prompt: &str, response: String) {
-        let key = Self::cache_key(prompt);
-        self.entries.insert(
-            key,
-            CacheEntry {
-                response,
-                inserted_at: Instant::now(),
-            },
-        );
+    /// Sucht im selben Scope (Model, User, Verlauf) den ähnlichsten Prompt per Cosinus-Ähnlichkeit
+    /// des Embeddings im Key; Treffer nur ab der konfigurierten Schwelle.
+    pub fn get_similar(&mut self, key: &CacheKey) -> Option<PromptResponse> {
+        let threshold = self.similarity_threshold?;
+        let embedding = key.embedding()?;
+        let best = self
+            .entries
+            .values()
+            .filter(|e| e.scope == key.scope() && !self.is_expired(e))
+            .filter_map(|e| Some((e.hash.clone(), cosine_similarity(embedding, e.embedding.as_deref()?))))
+            .filter(|(_, similarity)| *similarity >= threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1))?;
+        self.touch(&best.0)
+    }
+
+    /// Speichert die Response für den Key (überschreibt bei gleichem Key) und verdrängt
+    /// bei Überschreitung der Limits die am längsten nicht genutzten Einträge.
+    pub fn insert(&mut self, key: &CacheKey, response: PromptResponse) {
+        self.remove(key.hash());
+        let mut entry = CacheEntry {
+            hash: key.hash().to_string(),
+            scope: key.scope().to_string(),
+            user_id: key.user_id().map(str::to_string),
+            response,
+            embedding: key.embedding().map(<[f32]>::to_vec),
+            created_at: unix_now(),
+            last_access: 0,
+            size: 0,
+        };
+        let bytes = serde_json::to_vec(&entry).unwrap_or_default();
+        entry.size = bytes.len() as u64;
+        if entry.size > self.max_bytes {
+            return;
+        }
+        if let Some(dir) = &self.dir {
+            if let Err(e) = std::fs::write(entry_path(dir, &entry.hash), &bytes) {
+                warn!("Failed to persist cache entry: {}", e);
+            }
+        }
+        self.clock += 1;
+        entry.last_access = self.clock;
+        self.total_bytes += entry.size;
+        self.entries.insert(entry.hash.clone(), entry);
+        self.evict();
+    }
+
+    /// Entfernt alle Einträge eines Users (z. B. bei Löschanfrage).
+    pub fn invalidate_user(&mut self, user_id: &str) {
+        let hashes: Vec<String> = self
+            .entries
+            .values()
+            .filter(|e| e.user_id.as_deref() == Some(user_id))
+            .map(|e| e.hash.clone())
+            .collect();
+        for hash in hashes {
+            self.remove(&hash);
+        }
     }
 
     /// Entfernt alle Einträge (für Event-/Timeout-Invalidation).
     pub fn invalidate_all(&mut self) {
-        self.entries.clear();
+        let hashes: Vec<String> = self.entries.keys().cloned().collect();
+        for hash in hashes {
+            self.remove(&hash);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Gesamtgröße aller Einträge in Bytes.
+    pub fn size_bytes(&self) -> u64 {
+        self.total_bytes
+    }
+
+    fn is_expired(&self, entry: &CacheEntry) -> bool {
+        unix_now().saturating_sub(entry.created_at) >= self.ttl.as_secs()
+    }
+
+    fn touch(&mut self, hash: &str) -> Option<PromptResponse> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(hash)?;
+        entry.last_access = self.clock;
+        Some(entry.response.clone())
+    }
+
+    fn remove(&mut self, hash: &str) {
+        if let Some(entry) = self.entries.remove(hash) {
+            self.total_bytes -= entry.size;
+            if let Some(dir) = &self.dir {
+                let _ = std::fs::remove_file(entry_path(dir, hash));
+            }
+        }
+    }
+
+    fn evict(&mut self) {
+        while self.entries.len() > self.max_entries || self.total_bytes > self.max_bytes {
+            let Some(oldest) = self.entries.values().min_by_key(|e| e.last_access).map(|e| e.hash.clone()) else {
+                break;
+            };
+            self.remove(&oldest);
+        }
+    }
+
+    /// Lädt persistierte Einträge; LRU-Reihenfolge nach Dateialter, defekte oder abgelaufene Dateien werden entfernt.
+    fn load_dir(&mut self, dir: &Path) -> io::Result<()> {
+        let mut loaded = Vec::new();
+        for file in std::fs::read_dir(dir)? {
+            let path = file?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let parsed = std::fs::read(&path)
+                .ok()
+                .and_then(|bytes| Some((serde_json::from_slice::<CacheEntry>(&bytes).ok()?, bytes.len() as u64)));
+            match parsed {
+                Some((mut entry, size)) if !self.is_expired(&entry) => {
+                    entry.size = size;
+                    let modified = std::fs::metadata(&path).and_then(|m| m.modified()).unwrap_or(UNIX_EPOCH);
+                    loaded.push((modified, entry));
+                }
+                _ => {
+                    let _ = std::fs::remove_file(&path);
+                }
+            }
+        }
+        loaded.sort_by_key(|(modified, _)| *modified);
+        for (_, mut entry) in loaded {
+            self.clock += 1;
+            entry.last_access = self.clock;
+            self.total_bytes += entry.size;
+            self.entries.insert(entry.hash.clone(), entry);
+        }
+        Ok(())
+    }
+}
+
+fn entry_path(dir: &Path, hash: &str) -> PathBuf {
+    dir.join(format!("{}.json", hash))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+/// Cosinus-Ähnlichkeit zweier Vektoren; 0.0 bei unterschiedlicher Länge oder Nullvektor.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
     }
+    dot / (norm_a * norm_b)
 }
@@ -1,6 +1,10 @@
-//! Caching-System (Phase 11.1, 11.2): Response-Caching, Invalidation.
+//! Caching-System (Phase 11.1, 11.2): Response-Caching mit stabilem Request-Key, Persistenz, Invalidation.
 
 mod invalidator;
+mod key;
 mod manager;
+mod response_cache;
 pub use invalidator::{CacheInvalidator, InvalidationEvent};
-pub use manager::CacheManager;
+pub use key::CacheKey;
+pub use manager::{cosine_similarity, CacheConfig, CacheManager};
+pub use response_cache::{PromptEmbedder, ResponseCache};
@@ -0,0 +1,117 @@
+//! Response-Cache (Phase 11.1.3): thread-sicherer Cache für Engine und gRPC, optional mit
+//! Embedding-Ähnlichkeitssuche für fast gleiche Prompts.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use async_trait::async_trait;
+use tracing::{debug, warn};
+
+use crate::cache::{CacheKey, CacheManager};
+use crate::llm::{PromptRequest, PromptResponse};
+
+/// Erzeugt Embeddings für die Ähnlichkeitssuche.
+#[async_trait]
+pub trait PromptEmbedder: Send + Sync {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>, String>;
+}
+
+/// Gemeinsam genutzter Response-Cache.
+pub struct ResponseCache {
+    manager: Arc<Mutex<CacheManager>>,
+    /// Der Manager liest und schreibt Dateien; Zugriffe laufen dann im Blocking-Pool.
+    persistent: bool,
+    embedder: Option<Arc<dyn PromptEmbedder>>,
+}
+
+impl ResponseCache {
+    pub fn new(manager: CacheManager) -> Self {
+        Self {
+            persistent: manager.is_persistent(),
+            manager: Arc::new(Mutex::new(manager)),
+            embedder: None,
+        }
+    }
+
+    /// Aktiviert die Ähnlichkeitssuche (nur wirksam mit `similarity_threshold`).
+    pub fn with_embedder(mut self, embedder: Arc<dyn PromptEmbedder>) -> Self {
+        self.embedder = Some(embedder);
+        self
+    }
+
+    /// Key für den Request an das Model; `None`, wenn der Request den Cache umgeht.
+    pub async fn key(&self, request: &PromptRequest, model_id: &str) -> Option<CacheKey> {
+        if request.no_cache {
+            return None;
+        }
+        let key = CacheKey::new(request, model_id);
+        let similarity_enabled = lock(&self.manager).similarity_threshold().is_some();
+        match &self.embedder {
+            Some(embedder) if similarity_enabled && !key.prompt().is_empty() => {
+                match embedder.embed(key.prompt()).await {
+                    Ok(embedding) => Some(key.with_embedding(embedding)),
+                    Err(e) => {
+                        warn!("Prompt embedding for cache lookup failed: {}", e);
+                        Some(key)
+                    }
+                }
+            }
+            _ => Some(key),
+        }
+    }
+
+    /// Exakter Treffer, sonst ähnlichster Prompt im selben Scope.
+    pub async fn get(&self, key: &CacheKey) -> Option<PromptResponse> {
+        let key = key.clone();
+        self.with_manager(move |manager| {
+            if let Some(response) = manager.get(&key) {
+                debug!("Response cache hit");
+                return Some(response);
+            }
+            let response = manager.get_similar(&key);
+            if response.is_some() {
+                debug!("Response cache hit (similar prompt)");
+            }
+            response
+        })
+        .await
+    }
+
+    /// Speichert die Response; Tool-Call-Antworten werden nicht gecacht (Tools haben Seiteneffekte).
+    pub async fn insert(&self, key: &CacheKey, response: &PromptResponse) {
+        if !response.tool_calls.is_empty() {
+            return;
+        }
+        let (key, response) = (key.clone(), response.clone());
+        self.with_manager(move |manager| manager.insert(&key, response)).await;
+    }
+
+    /// Entfernt alle Einträge eines Users.
+    pub async fn invalidate_user(&self, user_id: &str) {
+        let user_id = user_id.to_string();
+        self.with_manager(move |manager| manager.invalidate_user(&user_id)).await;
+    }
+
+    pub async fn invalidate_all(&self) {
+        self.with_manager(CacheManager::invalidate_all).await;
+    }
+
+    /// Führt `f` auf dem Manager aus; mit Persistenz per `spawn_blocking`, damit Datei-I/O
+    /// unter dem Lock keine Runtime-Threads blockiert.
+    async fn with_manager<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut CacheManager) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        if !self.persistent {
+            return f(&mut lock(&self.manager));
+        }
+        let manager = self.manager.clone();
+        tokio::task::spawn_blocking(move || f(&mut lock(&manager)))
+            .await
+            .expect("response cache task panicked")
+    }
+}
+
+fn lock(manager: &Mutex<CacheManager>) -> MutexGuard<'_, CacheManager> {
+    manager.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -1,5 +1,12 @@
-use std::sync::Arc;
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
+use tracing::info;
+
+use crate::cost::{UsageFilter, UsageLedger, UsageRecord, UsageSummary};
+use crate::fallback::BudgetResetListener;
 
 /// Alert bei Budget-Überschreitung oder nahe am Limit.
 #[derive(Debug, Clone, PartialEq)]
@@ -51,29 +58,229 @@ impl BudgetManager {
     }
 }
 
+/// Abrechnungszeitraum eines Budget-Limits; Perioden beginnen um 00:00 UTC bzw. am Monatsersten (UTC).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetPeriod {
+    Daily,
+    Monthly,
+    /// Ohne Reset.
+    Total,
+}
+
+impl BudgetPeriod {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "daily" | "day" => Some(Self::Daily),
+            "monthly" | "month" => Some(Self::Monthly),
+            "total" => Some(Self::Total),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Monthly => "monthly",
+            Self::Total => "total",
+        }
+    }
+
+    /// Beginn der Periode, in der `now` liegt; `None` für `Total`.
+    pub fn start(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Daily => Utc.with_ymd_and_hms(now.year(), now.month(), now.day(), 0, 0, 0).single(),
+            Self::Monthly => Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0).single(),
+            Self::Total => None,
+        }
+    }
+
+    /// Beginn der nächsten Periode (Zeitpunkt des Resets); `None` für `Total`.
+    pub fn next_start(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = self.start(now)?;
+        match self {
+            Self::Daily => Some(start + Duration::days(1)),
+            Self::Monthly => {
+                let (year, month) = if start.month() == 12 { (start.year() + 1, 1) } else { (start.year(), start.month() + 1) };
+                Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()
+            }
+            Self::Total => None,
+        }
+    }
+}
+
+/// Budget-Limit (Dollar pro Periode) für einen Scope: ohne `user_id`/`device_id` global, sonst
+/// für den User, das Device oder den User auf dem Device.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BudgetLimit {
+    #[serde(default)]
+    pub user_id: Option<String>,
+    #[serde(default)]
+    pub device_id: Option<String>,
+    pub period: BudgetPeriod,
+    pub limit: f64,
+}
+
+impl BudgetLimit {
+    /// Gilt das Limit für Requests dieses Users/Devices?
+    pub fn applies_to(&self, user_id: Option<&str>, device_id: Option<&str>) -> bool {
+        let scope_matches = |scope: &Option<String>, value: Option<&str>| scope.is_none() || scope.as_deref() == value;
+        scope_matches(&self.user_id, user_id) && scope_matches(&self.device_id, device_id)
+    }
+
+    /// Ledger-Einträge, die in der aktuellen Periode auf das Limit zählen.
+    fn filter(&self, now: DateTime<Utc>) -> UsageFilter {
+        UsageFilter {
+            user_id: self.user_id.clone(),
+            device_id: self.device_id.clone(),
+            since: self.period.start(now),
+            until: None,
+        }
+    }
+}
+
+/// Konfiguration der Budgets und des Usage-Ledgers.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BudgetConfig {
+    /// JSON-Lines-Datei des Usage-Ledgers; ohne Pfad nur im Speicher.
+    pub ledger_path: Option<String>,
+    pub limits: Vec<BudgetLimit>,
+}
+
+/// Stand eines Budget-Limits in der aktuellen Periode.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetStatus {
+    pub limit: BudgetLimit,
+    pub used: f64,
+    /// Nächster Reset; `None` für `Total`.
+    pub resets_at: Option<DateTime<Utc>>,
+}
+
+impl BudgetStatus {
+    pub fn is_over_limit(&self) -> bool {
+        self.used > self.limit.limit
+    }
+}
+
+/// Budget-Tracking für Engine und gRPC: globales Limit (`BudgetManager`), Limits pro User/Device
+/// und Periode sowie der Usage-Ledger, aus dem die Perioden-Verbräuche berechnet werden.
 pub struct BudgetTracker {
     manager: RwLock<BudgetManager>,
+    limits: Vec<BudgetLimit>,
+    ledger: RwLock<UsageLedger>,
+    /// Periodenbeginn beim letzten Reset-Check; ein Wechsel löst den Budget-Reset aus.
+    period_starts: RwLock<HashMap<BudgetPeriod, DateTime<Utc>>>,
+    reset_listener: Option<BudgetResetListener>,
 }
 
 impl BudgetTracker {
     pub fn new(limit: f64) -> Self {
         Self {
             manager: RwLock::new(BudgetManager::new(limit)),
+            limits: Vec::new(),
+            ledger: RwLock::new(UsageLedger::in_memory()),
+            period_starts: RwLock::new(HashMap::new()),
+            reset_listener: None,
         }
     }
 
+    /// Limits pro User/Device und Periode.
+    pub fn with_limits(mut self, limits: Vec<BudgetLimit>) -> Self {
+        let now = Utc::now();
+        let starts = limits
+            .iter()
+            .filter_map(|limit| Some((limit.period, limit.period.start(now)?)))
+            .collect();
+        self.period_starts = RwLock::new(starts);
+        self.limits = limits;
+        self
+    }
+
+    /// Schreibt jeden abgerechneten Request in den Ledger (z. B. aus `UsageLedger::open`).
+    pub fn with_ledger(mut self, ledger: UsageLedger) -> Self {
+        self.ledger = RwLock::new(ledger);
+        self
+    }
+
+    /// Wird bei jedem Periodenwechsel benachrichtigt (Rückkehr zu Cloud-LLMs).
+    pub fn with_reset_listener(mut self, listener: BudgetResetListener) -> Self {
+        self.reset_listener = Some(listener);
+        self
+    }
+
     pub async fn add_usage(&self, amount: f64) {
         let mut mgr = self.manager.write().await;
         mgr.add_usage(amount);
     }
 
+    /// Rechnet einen Request ab: globales Limit und Ledger.
+    pub async fn record(&self, record: UsageRecord) {
+        self.add_usage(record.cost).await;
+        self.ledger.write().await.append(record);
+    }
+
     pub async fn is_over_limit(&self) -> bool {
         let mgr = self.manager.read().await;
         mgr.is_over_limit()
     }
 
+    /// Globales Limit oder eines der Limits für User/Device in der aktuellen Periode überschritten?
+    pub async fn is_over_limit_for(&self, user_id: Option<&str>, device_id: Option<&str>) -> bool {
+        if self.is_over_limit().await {
+            return true;
+        }
+        self.statuses_at(user_id, device_id, Utc::now())
+            .await
+            .iter()
+            .any(BudgetStatus::is_over_limit)
+    }
+
     pub async fn get_usage_info(&self) -> (f64, f64, bool) {
         let mgr = self.manager.read().await;
         (mgr.get_usage(), mgr.get_limit(), mgr.is_over_limit())
     }
+
+    /// Stand der Limits, die für User/Device gelten, zum Zeitpunkt `now`.
+    pub async fn statuses_at(&self, user_id: Option<&str>, device_id: Option<&str>, now: DateTime<Utc>) -> Vec<BudgetStatus> {
+        let ledger = self.ledger.read().await;
+        self.limits
+            .iter()
+            .filter(|limit| limit.applies_to(user_id, device_id))
+            .map(|limit| BudgetStatus {
+                limit: limit.clone(),
+                used: ledger.cost(&limit.filter(now)),
+                resets_at: limit.period.next_start(now),
+            })
+            .collect()
+    }
+
+    /// Verbrauch laut Ledger, nach Model aufgeschlüsselt.
+    pub async fn usage(&self, filter: &UsageFilter) -> UsageSummary {
+        self.ledger.read().await.summarize(filter)
+    }
+
+    /// Prüft auf einen Periodenwechsel der konfigurierten Limits und benachrichtigt dann den
+    /// Reset-Listener; regelmäßig aufzurufen. Liefert `true` bei einem Reset.
+    pub async fn check_period_reset(&self, now: DateTime<Utc>) -> bool {
+        let mut reset = false;
+        let mut starts = self.period_starts.write().await;
+        for (period, start) in starts.iter_mut() {
+            let Some(current) = period.start(now) else {
+                continue;
+            };
+            if current > *start {
+                info!("Budget period '{}' started, usage counters reset", period.as_str());
+                *start = current;
+                reset = true;
+            }
+        }
+        drop(starts);
+        if reset {
+            if let Some(listener) = &self.reset_listener {
+                listener.notify_reset();
+            }
+        }
+        reset
+    }
 }
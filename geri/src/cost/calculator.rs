@@ -1,10 +1,35 @@
 //! Cost-Calculator (Phase 9.2.1): Cost pro Token pro Provider, Input + Output, Total-Cost.
 
+use crate::llm::PromptRequest;
+use crate::model::ModelInfo;
+use crate::prompt::TokenCounter;
+
 /// Berechnet Gesamtkosten aus Input- und Output-Tokens pro Provider/Model.
 #[derive(Debug, Clone, Default)]
-pub struct CostCalculator;
+pub struct CostCalculator {
+    token_counter: TokenCounter,
+}
 
 impl CostCalculator {
+    /// Erstellt einen Cost-Calculator, der Input-Tokens mit dem angegebenen Token-Counter zählt.
+    pub fn new(token_counter: TokenCounter) -> Self {
+        Self { token_counter }
+    }
+
+    /// Zählt die Input-Tokens eines Requests (System-Prompt, Context, Verlauf, Prompt) für das Model.
+    pub fn input_tokens(&self, request: &PromptRequest, model: &ModelInfo) -> u32 {
+        let fixed = [request.system_prompt.as_deref(), request.context.as_deref()]
+            .into_iter()
+            .flatten()
+            .map(|text| self.token_counter.count_for_model_info(text, model))
+            .sum::<u32>();
+        request
+            .conversation()
+            .iter()
+            .map(|message| self.token_counter.count_for_model_info(&message.content, model))
+            .fold(fixed, u32::saturating_add)
+    }
+
     /// Berechnet die Gesamtkosten (in Dollar) für die angegebenen Input- und Output-Tokens.
     /// Unbekannte Provider/Model liefern 0.0.
     pub fn total_cost(
@@ -0,0 +1,159 @@
+//! Usage-Ledger (Phase 9.4.1): persistierte Abrechnung pro Request (User, Device, Model,
+//! Input-/Output-Tokens, Kosten) als JSON-Lines-Datei.
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Ein abgerechneter Request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageRecord {
+    pub timestamp: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+    pub provider: String,
+    pub model: String,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    /// Kosten in Dollar.
+    pub cost: f64,
+}
+
+/// Filter für Ledger-Abfragen; leere Felder schränken nicht ein.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageFilter {
+    pub user_id: Option<String>,
+    pub device_id: Option<String>,
+    /// Einschließlich.
+    pub since: Option<DateTime<Utc>>,
+    /// Ausschließlich.
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl UsageFilter {
+    pub fn matches(&self, record: &UsageRecord) -> bool {
+        let field_matches = |filter: &Option<String>, value: &Option<String>| match filter {
+            Some(filter) => value.as_deref() == Some(filter.as_str()),
+            None => true,
+        };
+        field_matches(&self.user_id, &record.user_id)
+            && field_matches(&self.device_id, &record.device_id)
+            && self.since.is_none_or(|since| record.timestamp >= since)
+            && self.until.is_none_or(|until| record.timestamp < until)
+    }
+}
+
+/// Verbrauch eines Models innerhalb einer Abfrage.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelUsage {
+    pub provider: String,
+    pub model: String,
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost: f64,
+}
+
+/// Summierter Verbrauch, aufgeschlüsselt nach Model (teuerstes zuerst).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UsageSummary {
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost: f64,
+    pub models: Vec<ModelUsage>,
+}
+
+/// Usage-Ledger; mit Datei wird jeder Eintrag sofort angehängt und beim Öffnen wieder eingelesen.
+#[derive(Debug, Default)]
+pub struct UsageLedger {
+    path: Option<PathBuf>,
+    records: Vec<UsageRecord>,
+}
+
+impl UsageLedger {
+    /// Ledger nur im Speicher (Tests, keine Persistenz konfiguriert).
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Öffnet bzw. erstellt die Ledger-Datei; unlesbare Zeilen werden übersprungen.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut records = Vec::new();
+        match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                for (line_no, line) in content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+                    match serde_json::from_str(line) {
+                        Ok(record) => records.push(record),
+                        Err(e) => warn!("Skipping invalid usage ledger line {} in {}: {}", line_no + 1, path.display(), e),
+                    }
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        Ok(Self { path: Some(path), records })
+    }
+
+    /// Nimmt den Eintrag auf; Schreibfehler werden geloggt, der Eintrag bleibt im Speicher.
+    pub fn append(&mut self, record: UsageRecord) {
+        if let Some(path) = &self.path {
+            if let Err(e) = Self::write_line(path, &record) {
+                warn!("Failed to persist usage record to {}: {}", path.display(), e);
+            }
+        }
+        self.records.push(record);
+    }
+
+    pub fn records(&self) -> &[UsageRecord] {
+        &self.records
+    }
+
+    /// Summe der Kosten aller passenden Einträge.
+    pub fn cost(&self, filter: &UsageFilter) -> f64 {
+        self.records.iter().filter(|r| filter.matches(r)).map(|r| r.cost).sum()
+    }
+
+    /// Verbrauch der passenden Einträge, nach Model aufgeschlüsselt.
+    pub fn summarize(&self, filter: &UsageFilter) -> UsageSummary {
+        let mut summary = UsageSummary::default();
+        let mut models: BTreeMap<(&str, &str), ModelUsage> = BTreeMap::new();
+        for record in self.records.iter().filter(|r| filter.matches(r)) {
+            summary.requests += 1;
+            summary.input_tokens += record.input_tokens as u64;
+            summary.output_tokens += record.output_tokens as u64;
+            summary.cost += record.cost;
+            let usage = models
+                .entry((record.provider.as_str(), record.model.as_str()))
+                .or_insert_with(|| ModelUsage {
+                    provider: record.provider.clone(),
+                    model: record.model.clone(),
+                    ..Default::default()
+                });
+            usage.requests += 1;
+            usage.input_tokens += record.input_tokens as u64;
+            usage.output_tokens += record.output_tokens as u64;
+            usage.cost += record.cost;
+        }
+        summary.models = models.into_values().collect();
+        summary.models.sort_by(|a, b| b.cost.total_cmp(&a.cost));
+        summary
+    }
+
+    fn write_line(path: &Path, record: &UsageRecord) -> io::Result<()> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(line.as_bytes())
+    }
+}
@@ -1,8 +1,10 @@
-//! Cost management: token counting, cost calculation, budget (Phase 1.1.2, 9.1.1, 9.2.1, 9.3.1).
+//! Cost management: token counting, cost calculation, budget, usage ledger (Phase 1.1.2, 9.1.1, 9.2.1, 9.3.1, 9.4.1).
 
 mod calculator;
 mod budget;
+mod ledger;
 mod provider_token_counter;
 pub use calculator::CostCalculator;
-pub use budget::{BudgetAlert, BudgetManager, BudgetTracker};
+pub use budget::{BudgetAlert, BudgetConfig, BudgetLimit, BudgetManager, BudgetPeriod, BudgetStatus, BudgetTracker};
+pub use ledger::{ModelUsage, UsageFilter, UsageLedger, UsageRecord, UsageSummary};
 pub use provider_token_counter::{AnthropicTokenCounter, OpenAITokenCounter};
@@ -0,0 +1,263 @@
+//! Circuit-Breaker (Phase 16.3): pro Provider/Model Closed → Open → Half-Open, gespeist vom PerformanceTracker.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::performance::ProviderMetrics;
+
+/// Zustand eines Circuit-Breakers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Requests laufen normal.
+    Closed,
+    /// Provider gilt als ausgefallen; Requests werden abgewiesen, bis `open_duration` verstrichen ist.
+    Open,
+    /// Probe-Phase: wenige Requests dürfen durch; Erfolg schließt, Fehler öffnet erneut.
+    HalfOpen,
+}
+
+impl std::fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CircuitState::Closed => write!(f, "closed"),
+            CircuitState::Open => write!(f, "open"),
+            CircuitState::HalfOpen => write!(f, "half_open"),
+        }
+    }
+}
+
+/// Schwellwerte des Circuit-Breakers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CircuitBreakerConfig {
+    /// Anzahl aufeinanderfolgender Fehler, ab der geöffnet wird.
+    pub failure_threshold: u32,
+    /// Fehlerquote (0.0–1.0) im PerformanceTracker-Fenster (letzte Minute), ab der geöffnet wird.
+    pub failure_rate_threshold: f64,
+    /// Mindestanzahl Requests im Fenster, bevor die Fehlerquote berücksichtigt wird.
+    pub min_requests: u64,
+    /// Wartezeit im Zustand Open, bevor Probe-Requests erlaubt werden.
+    pub open_duration_ms: u64,
+    /// Gleichzeitige Probe-Requests im Zustand Half-Open.
+    pub half_open_max_requests: u32,
+    /// Erfolgreiche Probes, nach denen wieder geschlossen wird.
+    pub success_threshold: u32,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            failure_rate_threshold: 0.5,
+            min_requests: 10,
+            open_duration_ms: 30_000,
+            half_open_max_requests: 1,
+            success_threshold: 1,
+        }
+    }
+}
+
+/// Zähler für Zustandswechsel und abgewiesene Requests eines Breakers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CircuitBreakerMetrics {
+    pub opened: u64,
+    pub half_opened: u64,
+    pub closed: u64,
+    /// Requests, die wegen offenem Breaker nicht an den Provider gingen.
+    pub rejected: u64,
+}
+
+/// Zustand und Metriken eines Breakers (für Monitoring).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CircuitBreakerStatus {
+    pub provider: String,
+    pub model: String,
+    pub state: CircuitState,
+    pub metrics: CircuitBreakerMetrics,
+}
+
+#[derive(Debug)]
+struct Breaker {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    probes_in_flight: u32,
+    probe_successes: u32,
+    metrics: CircuitBreakerMetrics,
+}
+
+impl Default for Breaker {
+    fn default() -> Self {
+        Self {
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            opened_at: None,
+            probes_in_flight: 0,
+            probe_successes: 0,
+            metrics: CircuitBreakerMetrics::default(),
+        }
+    }
+}
+
+impl Breaker {
+    fn transition(&mut self, key: &str, to: CircuitState) {
+        if self.state == to {
+            return;
+        }
+        match to {
+            CircuitState::Open => {
+                self.metrics.opened += 1;
+                self.opened_at = Some(Instant::now());
+                warn!(circuit = key, from = %self.state, "Circuit breaker opened");
+            }
+            CircuitState::HalfOpen => {
+                self.metrics.half_opened += 1;
+                info!(circuit = key, "Circuit breaker half-open, probing provider");
+            }
+            CircuitState::Closed => {
+                self.metrics.closed += 1;
+                self.opened_at = None;
+                info!(circuit = key, "Circuit breaker closed");
+            }
+        }
+        self.state = to;
+        self.consecutive_failures = 0;
+        self.probes_in_flight = 0;
+        self.probe_successes = 0;
+    }
+}
+
+/// Circuit-Breaker pro Provider/Model.
+#[derive(Debug, Default)]
+pub struct CircuitBreakerRegistry {
+    config: CircuitBreakerConfig,
+    breakers: RwLock<HashMap<String, Breaker>>,
+}
+
+impl CircuitBreakerRegistry {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            breakers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn key(provider: &str, model: &str) -> String {
+        format!("{}:{}", provider, model)
+    }
+
+    /// Prüft, ob ein Request an den Provider gehen darf; reserviert im Half-Open-Zustand einen Probe-Slot.
+    pub async fn allow(&self, provider: &str, model: &str) -> bool {
+        let key = Self::key(provider, model);
+        let mut breakers = self.breakers.write().await;
+        let breaker = breakers.entry(key.clone()).or_default();
+
+        if breaker.state == CircuitState::Open {
+            let elapsed = breaker.opened_at.map(|t| t.elapsed()).unwrap_or_default();
+            if elapsed < Duration::from_millis(self.config.open_duration_ms) {
+                breaker.metrics.rejected += 1;
+                return false;
+            }
+            breaker.transition(&key, CircuitState::HalfOpen);
+        }
+        if breaker.state == CircuitState::HalfOpen {
+            if breaker.probes_in_flight >= self.config.half_open_max_requests.max(1) {
+                breaker.metrics.rejected += 1;
+                return false;
+            }
+            breaker.probes_in_flight += 1;
+        }
+        true
+    }
+
+    /// Erfolgreicher Request: setzt Fehlerzähler zurück bzw. schließt nach genügend erfolgreichen Probes.
+    pub async fn record_success(&self, provider: &str, model: &str) {
+        let key = Self::key(provider, model);
+        let mut breakers = self.breakers.write().await;
+        let breaker = breakers.entry(key.clone()).or_default();
+        match breaker.state {
+            CircuitState::Closed => breaker.consecutive_failures = 0,
+            CircuitState::HalfOpen => {
+                breaker.probes_in_flight = breaker.probes_in_flight.saturating_sub(1);
+                breaker.probe_successes += 1;
+                if breaker.probe_successes >= self.config.success_threshold.max(1) {
+                    breaker.transition(&key, CircuitState::Closed);
+                }
+            }
+            CircuitState::Open => {}
+        }
+    }
+
+    /// Fehlgeschlagener Request; `metrics` sind die aktuellen Fenster-Metriken aus dem PerformanceTracker.
+    pub async fn record_failure(&self, provider: &str, model: &str, metrics: Option<&ProviderMetrics>) {
+        let key = Self::key(provider, model);
+        let mut breakers = self.breakers.write().await;
+        let breaker = breakers.entry(key.clone()).or_default();
+        match breaker.state {
+            CircuitState::Closed => {
+                breaker.consecutive_failures += 1;
+                let rate_exceeded = metrics.is_some_and(|m| {
+                    m.total_requests >= self.config.min_requests
+                        && 1.0 - m.success_rate() >= self.config.failure_rate_threshold
+                });
+                if breaker.consecutive_failures >= self.config.failure_threshold.max(1) || rate_exceeded {
+                    breaker.transition(&key, CircuitState::Open);
+                }
+            }
+            CircuitState::HalfOpen => breaker.transition(&key, CircuitState::Open),
+            CircuitState::Open => {}
+        }
+    }
+
+    /// Gibt einen Probe-Slot ohne Ergebnis frei (z. B. wenn der Aufrufer den Stream abbricht).
+    pub async fn release(&self, provider: &str, model: &str) {
+        let mut breakers = self.breakers.write().await;
+        if let Some(breaker) = breakers.get_mut(&Self::key(provider, model)) {
+            if breaker.state == CircuitState::HalfOpen {
+                breaker.probes_in_flight = breaker.probes_in_flight.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Aktueller Zustand (Closed für unbekannte Provider).
+    pub async fn state(&self, provider: &str, model: &str) -> CircuitState {
+        let breakers = self.breakers.read().await;
+        breakers
+            .get(&Self::key(provider, model))
+            .map(|b| b.state)
+            .unwrap_or(CircuitState::Closed)
+    }
+
+    /// Metriken eines Breakers (leer für unbekannte Provider).
+    pub async fn metrics(&self, provider: &str, model: &str) -> CircuitBreakerMetrics {
+        let breakers = self.breakers.read().await;
+        breakers
+            .get(&Self::key(provider, model))
+            .map(|b| b.metrics)
+            .unwrap_or_default()
+    }
+
+    /// Zustand und Metriken aller bekannten Breaker, sortiert nach Provider/Model.
+    pub async fn snapshot(&self) -> Vec<CircuitBreakerStatus> {
+        let breakers = self.breakers.read().await;
+        let mut statuses: Vec<_> = breakers
+            .iter()
+            .map(|(key, breaker)| {
+                let (provider, model) = key.split_once(':').unwrap_or((key.as_str(), ""));
+                CircuitBreakerStatus {
+                    provider: provider.to_string(),
+                    model: model.to_string(),
+                    state: breaker.state,
+                    metrics: breaker.metrics,
+                }
+            })
+            .collect();
+        statuses.sort_by(|a, b| (&a.provider, &a.model).cmp(&(&b.provider, &b.model)));
+        statuses
+    }
+}
@@ -1,6 +1,10 @@
-//! Error-Handling (Phase 16.1, 16.2): Provider-Fehler, gRPC-Status-Codes, Retry.
+//! Error-Handling (Phase 16.1, 16.2, 16.3): Provider-Fehler, gRPC-Status-Codes, Retry, Circuit-Breaker.
 
+mod circuit_breaker;
 mod provider_handler;
 mod retry;
+pub use circuit_breaker::{
+    CircuitBreakerConfig, CircuitBreakerMetrics, CircuitBreakerRegistry, CircuitBreakerStatus, CircuitState,
+};
 pub use provider_handler::{GrpcStatusCode, ProviderErrorHandler};
 pub use retry::RetryManager;
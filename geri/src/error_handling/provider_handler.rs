@@ -9,7 +9,7 @@ pub enum GrpcStatusCode {
     Internal,
     /// Ressource nicht verfügbar (z. B. ModelNotAvailable).
     Unavailable,
-    /// Ungültige Argumente (für zukünftige Nutzung).
+    /// Ungültige Argumente (z. B. Embedding-Input zu lang).
     InvalidArgument,
 }
 
@@ -21,8 +21,9 @@ impl ProviderErrorHandler {
     /// Behandelt einen LLM-Provider-Fehler; liefert (Code, Nachricht) für gRPC/Logging.
     pub fn handle_llm(&self, err: &LLMError) -> (GrpcStatusCode, String) {
         let (code, msg) = match err {
-            LLMError::ModelNotAvailable(m) => (GrpcStatusCode::Unavailable, m.clone()),
+            LLMError::ModelNotAvailable(m) | LLMError::Timeout(m) => (GrpcStatusCode::Unavailable, m.clone()),
             LLMError::ProcessingFailed(m) => (GrpcStatusCode::Internal, m.clone()),
+            LLMError::InvalidInput(m) => (GrpcStatusCode::InvalidArgument, m.clone()),
         };
         (code, msg)
     }
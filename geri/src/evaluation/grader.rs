@@ -0,0 +1,118 @@
+//! Scoring of model answers against a case's grader.
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::suite::{EvalCase, Grader};
+use crate::llm::{PromptRequest, ResponseFormat};
+
+/// Score of one answer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Grade {
+    /// 0.0 (wrong) to 1.0 (correct)
+    pub score: f64,
+    pub passed: bool,
+    /// Why the answer failed, or the judge's reasoning
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl Grade {
+    pub fn pass() -> Self {
+        Self { score: 1.0, passed: true, detail: None }
+    }
+
+    pub fn fail(detail: impl Into<String>) -> Self {
+        Self {
+            score: 0.0,
+            passed: false,
+            detail: Some(detail.into()),
+        }
+    }
+}
+
+/// Grade an answer with the deterministic graders; `None` for `llm_judge`, which needs a model
+/// (see [`judge_request`] and [`parse_judge_answer`]).
+pub fn grade_answer(case: &EvalCase, answer: &str) -> Option<Grade> {
+    let grade = match &case.grader {
+        Grader::ExactMatch { case_sensitive } => {
+            let expected = case.expected.as_deref().unwrap_or_default().trim();
+            let answer = answer.trim();
+            let matches = if *case_sensitive {
+                answer == expected
+            } else {
+                answer.to_lowercase() == expected.to_lowercase()
+            };
+            if matches {
+                Grade::pass()
+            } else {
+                Grade::fail(format!("expected '{}'", expected))
+            }
+        }
+        Grader::Regex { pattern } => match regex::Regex::new(pattern) {
+            Ok(re) if re.is_match(answer) => Grade::pass(),
+            Ok(_) => Grade::fail(format!("answer does not match /{}/", pattern)),
+            Err(e) => Grade::fail(format!("invalid pattern: {}", e)),
+        },
+        Grader::JsonSchema { schema } => match ResponseFormat::new("evaluation", schema.clone()).parse(answer) {
+            Ok(_) => Grade::pass(),
+            Err(errors) => Grade::fail(errors.join("; ")),
+        },
+        Grader::LlmJudge { .. } => return None,
+    };
+    Some(grade)
+}
+
+/// Schema of the judge's verdict
+fn verdict_format() -> ResponseFormat {
+    ResponseFormat::new(
+        "verdict",
+        json!({
+            "type": "object",
+            "properties": {
+                "score": { "type": "number", "minimum": 0, "maximum": 1 },
+                "reason": { "type": "string" }
+            },
+            "required": ["score"]
+        }),
+    )
+}
+
+/// Request asking the judge model to rate `answer`
+pub fn judge_request(case: &EvalCase, rubric: Option<&str>, answer: &str) -> PromptRequest {
+    let mut prompt = format!("Question:\n{}\n\nAnswer to rate:\n{}\n", case.prompt, answer);
+    if let Some(expected) = &case.expected {
+        prompt.push_str(&format!("\nReference answer:\n{}\n", expected));
+    }
+    if let Some(rubric) = rubric {
+        prompt.push_str(&format!("\nCriteria:\n{}\n", rubric));
+    }
+    prompt.push_str(&format!("\n{}", verdict_format().render_instructions()));
+    PromptRequest {
+        prompt,
+        system_prompt: Some(
+            "You are a strict grader. Rate how well the answer solves the question from 0.0 (wrong) \
+             to 1.0 (fully correct)."
+                .to_string(),
+        ),
+        max_tokens: Some(256),
+        response_format: Some(verdict_format()),
+        no_cache: true,
+        ..Default::default()
+    }
+}
+
+/// Grade from the judge's verdict; an unparsable verdict fails the case.
+pub fn parse_judge_answer(text: &str, pass_threshold: f64) -> Grade {
+    match verdict_format().parse(text) {
+        Ok(verdict) => {
+            let score = verdict["score"].as_f64().unwrap_or_default();
+            Grade {
+                score,
+                passed: score >= pass_threshold,
+                detail: verdict["reason"].as_str().map(str::to_string),
+            }
+        }
+        Err(errors) => Grade::fail(format!("invalid judge verdict: {}", errors.join("; "))),
+    }
+}
@@ -1,3 +1,14 @@
+//! Model evaluation: name-based `ModelEvaluator` and offline benchmark suites (runner, graders,
+//! stored results feeding the quality input of the model selection).
+
 pub mod evaluator;
+mod grader;
+mod runner;
+mod store;
+mod suite;
 
 pub use evaluator::*;
+pub use grader::{grade_answer, judge_request, parse_judge_answer, Grade};
+pub use runner::{CaseResult, EvalReport, EvalRunner, DEFAULT_EVAL_CONCURRENCY};
+pub use store::EvaluationStore;
+pub use suite::{EvalCase, EvalSuite, EvaluationError, Grader};
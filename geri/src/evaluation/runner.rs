@@ -0,0 +1,188 @@
+//! Runs a suite against an `LLMProvider` and collects the graded results.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+
+use super::grader::{grade_answer, judge_request, parse_judge_answer, Grade};
+use super::suite::{EvalCase, EvalSuite, Grader};
+use crate::llm::{LLMProvider, PromptRequest, PromptResponse, ProviderFactory};
+
+/// Cases evaluated at the same time
+pub const DEFAULT_EVAL_CONCURRENCY: usize = 2;
+
+/// Result of one case
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaseResult {
+    pub case_id: String,
+    /// The model's answer; `None` if the request failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub grade: Grade,
+    pub latency_ms: u64,
+    pub tokens_used: u32,
+}
+
+/// Results of one suite run against one model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalReport {
+    pub suite: String,
+    /// See [`EvalSuite::fingerprint`]
+    pub suite_fingerprint: String,
+    pub model: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    /// In suite order
+    pub results: Vec<CaseResult>,
+}
+
+impl EvalReport {
+    pub fn passed(&self) -> usize {
+        self.results.iter().filter(|r| r.grade.passed).count()
+    }
+
+    pub fn pass_rate(&self) -> f64 {
+        if self.results.is_empty() {
+            return 0.0;
+        }
+        self.passed() as f64 / self.results.len() as f64
+    }
+
+    /// Mean score over all cases (0.0–1.0); the quality input of the model selection
+    pub fn mean_score(&self) -> f64 {
+        if self.results.is_empty() {
+            return 0.0;
+        }
+        self.results.iter().map(|r| r.grade.score).sum::<f64>() / self.results.len() as f64
+    }
+
+    pub fn mean_latency_ms(&self) -> f64 {
+        if self.results.is_empty() {
+            return 0.0;
+        }
+        self.results.iter().map(|r| r.latency_ms as f64).sum::<f64>() / self.results.len() as f64
+    }
+}
+
+/// Runs suites with a bounded number of concurrent requests; `llm_judge` cases are rated by the
+/// judge model looked up in `judges`.
+pub struct EvalRunner {
+    concurrency: usize,
+    judges: Option<Arc<ProviderFactory>>,
+    request_timeout: Option<Duration>,
+}
+
+impl EvalRunner {
+    pub fn new() -> Self {
+        Self {
+            concurrency: DEFAULT_EVAL_CONCURRENCY,
+            judges: None,
+            request_timeout: None,
+        }
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Registered models available as `llm_judge`
+    pub fn with_judges(mut self, judges: Arc<ProviderFactory>) -> Self {
+        self.judges = Some(judges);
+        self
+    }
+
+    /// Per-request timeout for the evaluated model and the judge; a timeout fails the case.
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Run every case of `suite` against `provider`, reported as `model`.
+    pub async fn run(&self, suite: &EvalSuite, model: &str, provider: Arc<dyn LLMProvider>) -> EvalReport {
+        let started_at = Utc::now();
+        let start = Instant::now();
+        let results = futures::stream::iter(suite.cases.iter())
+            .map(|case| self.run_case(case, provider.clone()))
+            .buffered(self.concurrency)
+            .collect()
+            .await;
+        tracing::info!("Evaluated suite '{}' against {} in {:?}", suite.name, model, start.elapsed());
+        EvalReport {
+            suite: suite.name.clone(),
+            suite_fingerprint: suite.fingerprint(),
+            model: model.to_string(),
+            started_at,
+            duration_ms: start.elapsed().as_millis() as u64,
+            results,
+        }
+    }
+
+    async fn run_case(&self, case: &EvalCase, provider: Arc<dyn LLMProvider>) -> CaseResult {
+        let start = Instant::now();
+        let response = self.call(provider.as_ref(), case.to_prompt_request()).await;
+        let latency_ms = start.elapsed().as_millis() as u64;
+        match response {
+            Ok(response) => {
+                let grade = match grade_answer(case, &response.text) {
+                    Some(grade) => grade,
+                    None => self.judge(case, &response.text).await,
+                };
+                CaseResult {
+                    case_id: case.id.clone(),
+                    answer: Some(response.text),
+                    error: None,
+                    grade,
+                    latency_ms,
+                    tokens_used: response.tokens_used,
+                }
+            }
+            Err(e) => CaseResult {
+                case_id: case.id.clone(),
+                answer: None,
+                error: Some(e.clone()),
+                grade: Grade::fail(e),
+                latency_ms,
+                tokens_used: 0,
+            },
+        }
+    }
+
+    async fn judge(&self, case: &EvalCase, answer: &str) -> Grade {
+        let Grader::LlmJudge { model, rubric, pass_threshold } = &case.grader else {
+            return Grade::fail("not an llm_judge case");
+        };
+        let judge = match &self.judges {
+            Some(judges) => judges.get(model).await,
+            None => None,
+        };
+        let Some(judge) = judge else {
+            return Grade::fail(format!("judge model '{}' is not registered", model));
+        };
+        match self.call(judge.as_ref(), judge_request(case, rubric.as_deref(), answer)).await {
+            Ok(verdict) => parse_judge_answer(&verdict.text, *pass_threshold),
+            Err(e) => Grade::fail(format!("judge failed: {}", e)),
+        }
+    }
+
+    async fn call(&self, provider: &dyn LLMProvider, request: PromptRequest) -> Result<PromptResponse, String> {
+        let response = match self.request_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, provider.process_prompt(request))
+                .await
+                .map_err(|_| format!("timed out after {:?}", timeout))?,
+            None => provider.process_prompt(request).await,
+        };
+        response.map_err(|e| e.to_string())
+    }
+}
+
+impl Default for EvalRunner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
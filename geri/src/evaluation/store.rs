@@ -0,0 +1,96 @@
+//! Stored evaluation reports (JSON lines); the latest run per suite gives a model's quality score.
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use super::runner::EvalReport;
+use super::suite::EvaluationError;
+
+/// Evaluation reports; with a file every report is appended and read back on open.
+#[derive(Debug, Default)]
+pub struct EvaluationStore {
+    path: Option<PathBuf>,
+    reports: RwLock<Vec<EvalReport>>,
+}
+
+impl EvaluationStore {
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Open or create the results file; unreadable lines are skipped.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, EvaluationError> {
+        let path = path.as_ref().to_path_buf();
+        let store_error = |e: std::io::Error| EvaluationError::Store(format!("{}: {}", path.display(), e));
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir).map_err(store_error)?;
+        }
+        let mut reports = Vec::new();
+        match std::fs::read_to_string(&path) {
+            Ok(content) => {
+                for (line_no, line) in content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+                    match serde_json::from_str(line) {
+                        Ok(report) => reports.push(report),
+                        Err(e) => tracing::warn!("Skipping invalid evaluation report line {} in {}: {}", line_no + 1, path.display(), e),
+                    }
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(store_error(e)),
+        }
+        Ok(Self {
+            path: Some(path),
+            reports: RwLock::new(reports),
+        })
+    }
+
+    /// Store a report; it stays in memory even if it could not be written.
+    pub fn append(&self, report: EvalReport) -> Result<(), EvaluationError> {
+        let written = match &self.path {
+            Some(path) => Self::write_line(path, &report)
+                .map_err(|e| EvaluationError::Store(format!("{}: {}", path.display(), e))),
+            None => Ok(()),
+        };
+        self.reports.write().unwrap_or_else(|e| e.into_inner()).push(report);
+        written
+    }
+
+    pub fn reports(&self) -> Vec<EvalReport> {
+        self.reports.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Most recent report of `model` per suite
+    pub fn latest(&self, model: &str) -> Vec<EvalReport> {
+        let reports = self.reports.read().unwrap_or_else(|e| e.into_inner());
+        let mut latest: HashMap<&str, &EvalReport> = HashMap::new();
+        for report in reports.iter().filter(|r| r.model == model) {
+            match latest.get(report.suite.as_str()) {
+                Some(existing) if existing.started_at > report.started_at => {}
+                _ => {
+                    latest.insert(report.suite.as_str(), report);
+                }
+            }
+        }
+        let mut latest: Vec<EvalReport> = latest.into_values().cloned().collect();
+        latest.sort_by(|a, b| a.suite.cmp(&b.suite));
+        latest
+    }
+
+    /// Quality score 0.0–1.0: mean score of the latest run per suite; `None` if never evaluated.
+    pub fn quality_score(&self, model: &str) -> Option<f64> {
+        let latest = self.latest(model);
+        if latest.is_empty() {
+            return None;
+        }
+        Some(latest.iter().map(EvalReport::mean_score).sum::<f64>() / latest.len() as f64)
+    }
+
+    fn write_line(path: &Path, report: &EvalReport) -> std::io::Result<()> {
+        let mut line = serde_json::to_string(report)?;
+        line.push('\n');
+        let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(line.as_bytes())
+    }
+}
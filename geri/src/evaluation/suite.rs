@@ -0,0 +1,165 @@
+//! Benchmark suites: prompts with expected answers and the grader that scores each answer.
+//! Suites are YAML/JSON documents or JSONL files with one case per line.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::llm::PromptRequest;
+
+#[derive(Debug, Error)]
+pub enum EvaluationError {
+    #[error("Failed to read suite {path}: {source}")]
+    Io { path: String, source: std::io::Error },
+    #[error("Failed to parse suite {path}: {message}")]
+    Parse { path: String, message: String },
+    #[error("Invalid suite {suite}: {message}")]
+    InvalidSuite { suite: String, message: String },
+    #[error("Failed to store evaluation results: {0}")]
+    Store(String),
+}
+
+/// How an answer is scored
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Grader {
+    /// Answer equals `expected` after trimming whitespace
+    ExactMatch {
+        #[serde(default)]
+        case_sensitive: bool,
+    },
+    /// Answer matches the regular expression (anywhere unless anchored)
+    Regex { pattern: String },
+    /// Answer is JSON that validates against the schema
+    JsonSchema { schema: Value },
+    /// Another registered model rates the answer from 0.0 to 1.0
+    LlmJudge {
+        model: String,
+        /// What the judge should look for; `expected` is shown as reference answer
+        #[serde(default)]
+        rubric: Option<String>,
+        #[serde(default = "default_pass_threshold")]
+        pass_threshold: f64,
+    },
+}
+
+impl Default for Grader {
+    fn default() -> Self {
+        Grader::ExactMatch { case_sensitive: false }
+    }
+}
+
+fn default_pass_threshold() -> f64 {
+    0.5
+}
+
+/// One prompt of a suite
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalCase {
+    pub id: String,
+    pub prompt: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    /// Reference answer; required by `exact_match`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    #[serde(default)]
+    pub grader: Grader,
+}
+
+impl EvalCase {
+    /// Request sent to the evaluated model; suite runs never use the response cache
+    pub fn to_prompt_request(&self) -> PromptRequest {
+        PromptRequest {
+            prompt: self.prompt.clone(),
+            system_prompt: self.system_prompt.clone(),
+            max_tokens: self.max_tokens,
+            no_cache: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// A named list of cases
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EvalSuite {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub cases: Vec<EvalCase>,
+}
+
+impl EvalSuite {
+    /// Load and validate a suite; `.jsonl` files hold one case per line and are named after the file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, EvaluationError> {
+        let path = path.as_ref();
+        let display = path.display().to_string();
+        let content = std::fs::read_to_string(path).map_err(|source| EvaluationError::Io {
+            path: display.clone(),
+            source,
+        })?;
+        let parse_error = |message: String| EvaluationError::Parse {
+            path: display.clone(),
+            message,
+        };
+        let suite = match path.extension().and_then(|e| e.to_str()) {
+            Some("jsonl") => {
+                let mut cases = Vec::new();
+                for (line_no, line) in content.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+                    let case = serde_json::from_str(line).map_err(|e| parse_error(format!("line {}: {}", line_no + 1, e)))?;
+                    cases.push(case);
+                }
+                let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("suite").to_string();
+                EvalSuite { name, description: None, cases }
+            }
+            Some("json") => serde_json::from_str(&content).map_err(|e| parse_error(e.to_string()))?,
+            _ => serde_yaml::from_str(&content).map_err(|e| parse_error(e.to_string()))?,
+        };
+        suite.validate()?;
+        Ok(suite)
+    }
+
+    /// Non-empty, unique case ids, compilable regexes and a reference answer for exact matches.
+    pub fn validate(&self) -> Result<(), EvaluationError> {
+        let invalid = |message: String| EvaluationError::InvalidSuite {
+            suite: self.name.clone(),
+            message,
+        };
+        if self.cases.is_empty() {
+            return Err(invalid("suite has no cases".to_string()));
+        }
+        let mut ids = HashSet::new();
+        for case in &self.cases {
+            if !ids.insert(case.id.as_str()) {
+                return Err(invalid(format!("duplicate case id '{}'", case.id)));
+            }
+            match &case.grader {
+                Grader::ExactMatch { .. } if case.expected.is_none() => {
+                    return Err(invalid(format!("case '{}' uses exact_match without 'expected'", case.id)));
+                }
+                Grader::Regex { pattern } => {
+                    regex::Regex::new(pattern).map_err(|e| invalid(format!("case '{}': {}", case.id, e)))?;
+                }
+                Grader::LlmJudge { pass_threshold, .. } if !(0.0..=1.0).contains(pass_threshold) => {
+                    return Err(invalid(format!("case '{}': pass_threshold must be within 0.0-1.0", case.id)));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// SHA-256 over the cases, stored with every result so runs of different suite versions are
+    /// never compared.
+    pub fn fingerprint(&self) -> String {
+        let canonical = serde_json::to_string(&self.cases).unwrap_or_default();
+        let digest = Sha256::digest(canonical.as_bytes());
+        digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
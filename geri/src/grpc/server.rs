@@ -1,3 +1,4 @@
+use futures::StreamExt;
 use tonic::{transport::Server, Request, Response, Status};
 use tracing::info;
 use std::net::SocketAddr;
@@ -11,32 +12,140 @@ use geri::geri_service_server::{GeriService, GeriServiceServer};
 
 pub struct GeriServiceImpl {
     model_registry: Arc<crate::model::ModelRegistry>,
-    llm_provider: Arc<dyn crate::llm::LLMProvider>,
+    engine: Arc<crate::llm::GeriEngine>,
     vision_processor: Arc<crate::vision::VisionProcessor>,
+    token_counter: crate::prompt::TokenCounter,
+    embeddings: Option<Arc<crate::llm::EmbeddingService>>,
+    budget_tracker: Option<Arc<crate::cost::BudgetTracker>>,
+    vision_engine: Option<Arc<crate::llm::GeriEngine>>,
 }
 
 impl GeriServiceImpl {
+    /// Prompts are answered by `engine` (model selection, failover, circuit breakers, timeouts,
+    /// budgets, cache, structured output and safety filters).
     pub fn new(
         model_registry: Arc<crate::model::ModelRegistry>,
-        llm_provider: Arc<dyn crate::llm::LLMProvider>,
+        engine: Arc<crate::llm::GeriEngine>,
         vision_processor: Arc<crate::vision::VisionProcessor>,
     ) -> Self {
         Self {
             model_registry,
-            llm_provider,
+            engine,
             vision_processor,
+            token_counter: crate::prompt::TokenCounter::default(),
+            embeddings: None,
+            budget_tracker: None,
+            vision_engine: None,
         }
     }
+
+    /// Uses the given token counter (model tokenizers) for context-window trimming.
+    pub fn with_token_counter(mut self, token_counter: crate::prompt::TokenCounter) -> Self {
+        self.token_counter = token_counter;
+        self
+    }
+
+    /// Serves the `Embed` RPC from the given embedding models.
+    pub fn with_embeddings(mut self, embeddings: Arc<crate::llm::EmbeddingService>) -> Self {
+        self.embeddings = Some(embeddings);
+        self
+    }
+
+    /// Serves the `GetUsage` RPC from the usage ledger (answered requests are recorded by the engine).
+    pub fn with_budget_tracker(mut self, budget_tracker: Arc<crate::cost::BudgetTracker>) -> Self {
+        self.budget_tracker = Some(budget_tracker);
+        self
+    }
+
+    /// Routes `ProcessVision` through the engine's vision models (local llama.cpp models,
+    /// enforced for `privacy_sensitive` requests) instead of the default vision processor.
+    pub fn with_vision_engine(mut self, vision_engine: Arc<crate::llm::GeriEngine>) -> Self {
+        self.vision_engine = Some(vision_engine);
+        self
+    }
 }
 
-#[tonic::async_trait]
-impl GeriService for GeriServiceImpl {
-    async fn process_prompt(
-        &self,
-        request: Request<geri::ProcessPromptRequest>,
-    ) -> Result<Response<geri::ProcessPromptResponse>, Status> {
-        let req = request.into_inner();
-        
+impl From<crate::llm::ToolCall> for geri::ToolCall {
+    fn from(call: crate::llm::ToolCall) -> Self {
+        Self {
+            id: call.id,
+            name: call.name,
+            arguments_json: call.arguments.to_string(),
+        }
+    }
+}
+
+impl From<crate::safety::FilterDecision> for geri::SafetyDecision {
+    fn from(decision: crate::safety::FilterDecision) -> Self {
+        Self {
+            filter: decision.filter,
+            stage: decision.stage.as_str().to_string(),
+            action: decision.action.as_str().to_string(),
+            detail: decision.detail,
+        }
+    }
+}
+
+impl From<geri::ToolCall> for crate::llm::ToolCall {
+    fn from(call: geri::ToolCall) -> Self {
+        Self {
+            arguments: crate::llm::tools::parse_arguments(&call.arguments_json),
+            id: call.id,
+            name: call.name,
+        }
+    }
+}
+
+impl From<geri::ChatMessage> for crate::llm::ChatMessage {
+    fn from(message: geri::ChatMessage) -> Self {
+        Self {
+            role: crate::llm::ChatRole::parse(&message.role),
+            content: message.content,
+            name: if message.name.is_empty() { None } else { Some(message.name) },
+            tool_call_id: if message.tool_call_id.is_empty() { None } else { Some(message.tool_call_id) },
+            tool_calls: message.tool_calls.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// Context window assumed for models without registry entry
+const DEFAULT_CONTEXT_WINDOW: u32 = 4096;
+
+impl GeriServiceImpl {
+    /// Trims `request.messages` to the context window of the model the engine picks first (after
+    /// system prompt, RAG context and current prompt); returns the number of dropped messages.
+    async fn trim_conversation(&self, request: &mut crate::llm::PromptRequest, options: &crate::selection::SelectionOptions) -> u32 {
+        if request.messages.is_empty() {
+            return 0;
+        }
+        let model = self.engine.first_choice(request, options).await;
+        let model_name = model.as_ref().map(|m| m.id.as_str()).unwrap_or_default();
+        let model_limit = model.as_ref().and_then(|m| m.context_window).unwrap_or(DEFAULT_CONTEXT_WINDOW);
+
+        let counter = &self.token_counter;
+        let window = crate::prompt::ContextWindowManager::new(counter.clone());
+        let fixed = counter.count_for_model(request.system_prompt.as_deref().unwrap_or(""), model_name)
+            + counter.count_for_model(request.context.as_deref().unwrap_or(""), model_name)
+            + counter.count_for_model(&request.prompt, model_name);
+        let budget = window.max_context_tokens(model_limit).saturating_sub(fixed);
+
+        let kept = window.trim_messages(&request.messages, budget, model_name);
+        let trimmed = (request.messages.len() - kept.len()) as u32;
+        request.messages = kept;
+        trimmed
+    }
+
+    /// Converts the proto request; fails if `response_format.schema_json` is not valid JSON.
+    fn to_prompt_request(req: geri::ProcessPromptRequest) -> Result<crate::llm::PromptRequest, String> {
+        let response_format = match req.response_format {
+            Some(format) => {
+                let schema = serde_json::from_str(&format.schema_json)
+                    .map_err(|e| format!("response_format.schema_json is not valid JSON: {}", e))?;
+                let name = if format.name.is_empty() { "response".to_string() } else { format.name };
+                Some(crate::llm::ResponseFormat::new(name, schema).with_strict(format.strict))
+            }
+            None => None,
+        };
         let system_prompt = if req.system_prompt.is_empty() {
             "You are a helpful assistant in the Edda platform.".to_string()
         } else {
@@ -46,23 +155,183 @@ impl GeriService for GeriServiceImpl {
         // Mandatory XML Protocol Injection
         let injected_system = crate::prompt::inject_xml_protocol(&system_prompt);
 
-        let prompt_request = crate::llm::PromptRequest {
+        Ok(crate::llm::PromptRequest {
             prompt: req.prompt,
             system_prompt: Some(injected_system),
             context: if req.context.is_empty() { None } else { Some(req.context) },
             max_tokens: if req.max_tokens == 0 { None } else { Some(req.max_tokens) },
+            tools: req.tools.into_iter().map(|tool| crate::llm::ToolDefinition {
+                parameters: parse_json_or_empty_object(&tool.parameters_json),
+                name: tool.name,
+                description: tool.description,
+            }).collect(),
+            tool_calls: req.tool_calls.into_iter().map(Into::into).collect(),
+            tool_results: req.tool_results.into_iter().map(|result| crate::llm::ToolResult {
+                tool_call_id: result.tool_call_id,
+                name: result.name,
+                content: result.content,
+                is_error: result.is_error,
+            }).collect(),
+            messages: req.messages.into_iter().map(Into::into).collect(),
+            user_id: if req.user_id.is_empty() { None } else { Some(req.user_id) },
+            device_id: if req.device_id.is_empty() { None } else { Some(req.device_id) },
+            // Set from the request metadata, see `request_priority`
+            priority: crate::queue::RequestPriority::default(),
+            no_cache: req.no_cache,
+            response_format,
+        })
+    }
+}
+
+impl GeriServiceImpl {
+    /// Prompt request and model options of a `ProcessPrompt` call, with the conversation trimmed
+    /// to the context window; also returns the number of dropped messages.
+    async fn prepare(
+        &self,
+        request: Request<geri::ProcessPromptRequest>,
+    ) -> Result<(crate::llm::PromptRequest, crate::selection::SelectionOptions, u32), Status> {
+        let priority = request_priority(request.metadata());
+        let req = request.into_inner();
+        let options = crate::selection::SelectionOptions {
+            user_preferred_model_id: Some(req.model_name.clone()).filter(|name| !name.is_empty()),
+            ..Default::default()
         };
+        let mut prompt_request = Self::to_prompt_request(req).map_err(Status::invalid_argument)?;
+        prompt_request.priority = priority;
+        let trimmed_messages = self.trim_conversation(&mut prompt_request, &options).await;
+        Ok((prompt_request, options, trimmed_messages))
+    }
+}
+
+/// Maps LLM errors onto gRPC status codes via `ProviderErrorHandler`
+fn llm_status(error: &crate::llm::LLMError) -> Status {
+    use crate::error_handling::GrpcStatusCode;
+    let (code, message) = crate::error_handling::ProviderErrorHandler.handle_llm(error);
+    match code {
+        GrpcStatusCode::Internal => Status::internal(message),
+        GrpcStatusCode::Unavailable => Status::unavailable(message),
+        GrpcStatusCode::InvalidArgument => Status::invalid_argument(message),
+    }
+}
+
+/// gRPC metadata key carrying the request priority (`voice`, `chat`, `background`)
+pub const PRIORITY_METADATA_KEY: &str = "x-request-priority";
+
+/// Scheduling priority from the request metadata; chat if missing or unknown
+fn request_priority(metadata: &tonic::metadata::MetadataMap) -> crate::queue::RequestPriority {
+    metadata
+        .get(PRIORITY_METADATA_KEY)
+        .and_then(|value| value.to_str().ok())
+        .and_then(crate::queue::RequestPriority::parse)
+        .unwrap_or_default()
+}
 
-        let response = self.llm_provider.process_prompt(prompt_request).await
-            .map_err(|e| Status::internal(format!("LLM processing failed: {}", e)))?;
+/// Tool schemas without parameters may be sent with an empty `parameters_json`
+fn parse_json_or_empty_object(json: &str) -> serde_json::Value {
+    if json.trim().is_empty() {
+        return serde_json::json!({ "type": "object", "properties": {} });
+    }
+    crate::llm::tools::parse_arguments(json)
+}
+
+#[tonic::async_trait]
+impl GeriService for GeriServiceImpl {
+    async fn process_prompt(
+        &self,
+        request: Request<geri::ProcessPromptRequest>,
+    ) -> Result<Response<geri::ProcessPromptResponse>, Status> {
+        let (prompt_request, options, trimmed_messages) = self.prepare(request).await?;
+        let (response, model) = self.engine.process_with_model(prompt_request, options).await.map_err(|e| llm_status(&e))?;
 
         Ok(Response::new(geri::ProcessPromptResponse {
             text: response.text,
             tokens_used: response.tokens_used,
-            model_used: self.llm_provider.model_name().to_string(),
+            model_used: model.id,
+            tool_calls: response.tool_calls.into_iter().map(Into::into).collect(),
+            trimmed_messages,
+            structured_json: response.structured.map(|value| value.to_string()).unwrap_or_default(),
+            safety_decisions: response.safety.into_iter().map(Into::into).collect(),
         }))
     }
 
+    type ProcessPromptStreamStream = tokio_stream::wrappers::ReceiverStream<Result<geri::ProcessPromptStreamChunk, Status>>;
+
+    async fn process_prompt_stream(
+        &self,
+        request: Request<geri::ProcessPromptRequest>,
+    ) -> Result<Response<Self::ProcessPromptStreamStream>, Status> {
+        let (prompt_request, options, trimmed_messages) = self.prepare(request).await?;
+
+        // A structured answer is validated before it is sent, so it arrives in one delta, together
+        // with the parsed JSON
+        if prompt_request.response_format.is_some() {
+            let (response, model) = self.engine.process_with_model(prompt_request, options).await.map_err(|e| llm_status(&e))?;
+            let (tx, rx) = tokio::sync::mpsc::channel(2 + response.tool_calls.len());
+            let finish_reason = if response.tool_calls.is_empty() { "stop" } else { "tool_calls" };
+            let mut chunks = Vec::new();
+            if !response.text.is_empty() {
+                chunks.push(geri::process_prompt_stream_chunk::Chunk::Delta(geri::PromptDelta { text: response.text }));
+            }
+            chunks.extend(response.tool_calls.into_iter().map(|call| geri::process_prompt_stream_chunk::Chunk::ToolCall(call.into())));
+            chunks.push(geri::process_prompt_stream_chunk::Chunk::Done(geri::PromptStreamDone {
+                tokens_used: response.tokens_used,
+                finish_reason: finish_reason.to_string(),
+                model_used: model.id,
+                trimmed_messages,
+                structured_json: response.structured.map(|value| value.to_string()).unwrap_or_default(),
+                safety_decisions: response.safety.into_iter().map(Into::into).collect(),
+            }));
+            for chunk in chunks {
+                // Capacity covers all chunks, so this never waits
+                let _ = tx.send(Ok(geri::ProcessPromptStreamChunk { chunk: Some(chunk) })).await;
+            }
+            return Ok(Response::new(tokio_stream::wrappers::ReceiverStream::new(rx)));
+        }
+
+        let (mut chunks, model) = self.engine.process_stream_with_model(prompt_request, options).await.map_err(|e| llm_status(&e))?;
+        let model_used = model.id;
+
+        let (tx, rx) = tokio::sync::mpsc::channel(64);
+        tokio::spawn(async move {
+            loop {
+                // Client disconnected: dropping `chunks` aborts the engine's stream (and frees the
+                // model's slot) without waiting for the next chunk
+                let chunk = tokio::select! {
+                    chunk = chunks.next() => match chunk {
+                        Some(chunk) => chunk,
+                        None => return,
+                    },
+                    _ = tx.closed() => return,
+                };
+                let message = match chunk {
+                    Ok(crate::llm::PromptStreamChunk::Delta(text)) => Ok(geri::ProcessPromptStreamChunk {
+                        chunk: Some(geri::process_prompt_stream_chunk::Chunk::Delta(geri::PromptDelta { text })),
+                    }),
+                    Ok(crate::llm::PromptStreamChunk::ToolCall(call)) => Ok(geri::ProcessPromptStreamChunk {
+                        chunk: Some(geri::process_prompt_stream_chunk::Chunk::ToolCall(call.into())),
+                    }),
+                    Ok(crate::llm::PromptStreamChunk::Done { tokens_used, finish_reason, safety }) => Ok(geri::ProcessPromptStreamChunk {
+                        chunk: Some(geri::process_prompt_stream_chunk::Chunk::Done(geri::PromptStreamDone {
+                            tokens_used,
+                            finish_reason,
+                            model_used: model_used.clone(),
+                            trimmed_messages,
+                            structured_json: String::new(),
+                            safety_decisions: safety.into_iter().map(Into::into).collect(),
+                        })),
+                    }),
+                    Err(e) => Err(llm_status(&e)),
+                };
+                let is_error = message.is_err();
+                if tx.send(message).await.is_err() || is_error {
+                    return;
+                }
+            }
+        });
+
+        Ok(Response::new(tokio_stream::wrappers::ReceiverStream::new(rx)))
+    }
+
     async fn process_vision(
         &self,
         request: Request<geri::ProcessVisionRequest>,
@@ -71,15 +340,32 @@ impl GeriService for GeriServiceImpl {
         let vision_req = crate::vision::VisionRequest {
             image_data: req.image_data,
             prompt: if req.prompt.is_empty() { None } else { Some(req.prompt) },
+            privacy_sensitive: req.privacy_sensitive,
+        };
+        let (result, model_used) = match &self.vision_engine {
+            Some(engine) => {
+                let options = crate::selection::SelectionOptions {
+                    user_preferred_model_id: Some(req.model_name).filter(|name| !name.is_empty()),
+                    ..Default::default()
+                };
+                let (result, model) = engine.process_vision(vision_req, options).await.map_err(|e| match e {
+                    crate::llm::LLMError::ModelNotAvailable(msg) => Status::failed_precondition(msg),
+                    e => Status::internal(format!("Vision processing failed: {}", e)),
+                })?;
+                (result, model.id)
+            }
+            None => {
+                let result = self.vision_processor.process(vision_req).await
+                    .map_err(|e| Status::internal(format!("Vision processing failed: {}", e)))?;
+                (result, self.vision_processor.model_name().to_string())
+            }
         };
-        let result = self.vision_processor.process(vision_req).await
-            .map_err(|e| Status::internal(format!("Vision processing failed: {}", e)))?;
         let analysis_data = serde_json::to_vec(&result.analysis)
             .map_err(|e| Status::internal(format!("Vision analysis serialization failed: {}", e)))?;
         Ok(Response::new(geri::ProcessVisionResponse {
             description: result.description,
             analysis_data,
-            model_used: self.vision_processor.model_name().to_string(),
+            model_used,
         }))
     }
 
@@ -115,6 +401,7 @@ impl GeriService for GeriServiceImpl {
                 parameter_count: model.parameter_count.unwrap_or(0),
                 hardware_requirements: model.hardware_requirements.clone().unwrap_or_default(),
                 context_window: model.context_window.unwrap_or(0),
+                embedding_dimension: model.embedding_dimension.unwrap_or(0),
             }
         }).collect();
         
@@ -141,18 +428,102 @@ impl GeriService for GeriServiceImpl {
             parameter_count: model.parameter_count.unwrap_or(0),
             hardware_requirements: model.hardware_requirements.clone().unwrap_or_default(),
             context_window: model.context_window.unwrap_or(0),
+            embedding_dimension: model.embedding_dimension.unwrap_or(0),
         };
         
         Ok(Response::new(geri::GetModelInfoResponse {
             model: Some(proto_model),
         }))
     }
+
+    async fn embed(
+        &self,
+        request: Request<geri::EmbedRequest>,
+    ) -> Result<Response<geri::EmbedResponse>, Status> {
+        let req = request.into_inner();
+        let embeddings = self
+            .embeddings
+            .as_ref()
+            .ok_or_else(|| Status::unimplemented("No embedding model configured"))?;
+
+        let model = if req.model_name.is_empty() { None } else { Some(req.model_name.as_str()) };
+        let result = embeddings.embed(model, &req.inputs).await.map_err(|e| llm_status(&e))?;
+
+        Ok(Response::new(geri::EmbedResponse {
+            embeddings: result.vectors.into_iter().map(|values| geri::Embedding { values }).collect(),
+            model_used: result.model,
+            dimension: result.dimension,
+            tokens_used: result.tokens_used,
+        }))
+    }
+
+    async fn get_usage(
+        &self,
+        request: Request<geri::GetUsageRequest>,
+    ) -> Result<Response<geri::GetUsageResponse>, Status> {
+        use crate::cost::{BudgetPeriod, UsageFilter};
+
+        let req = request.into_inner();
+        let budget_tracker = self
+            .budget_tracker
+            .as_ref()
+            .ok_or_else(|| Status::unimplemented("Usage tracking is not configured"))?;
+        let period = if req.period.is_empty() {
+            BudgetPeriod::Monthly
+        } else {
+            BudgetPeriod::parse(&req.period)
+                .ok_or_else(|| Status::invalid_argument(format!("Unknown period '{}' (daily, monthly, total)", req.period)))?
+        };
+        let user_id = if req.user_id.is_empty() { None } else { Some(req.user_id) };
+        let device_id = if req.device_id.is_empty() { None } else { Some(req.device_id) };
+
+        let now = chrono::Utc::now();
+        let since = period.start(now);
+        let filter = UsageFilter {
+            user_id: user_id.clone(),
+            device_id: device_id.clone(),
+            since,
+            until: None,
+        };
+        let summary = budget_tracker.usage(&filter).await;
+        let budgets = budget_tracker.statuses_at(user_id.as_deref(), device_id.as_deref(), now).await;
+
+        Ok(Response::new(geri::GetUsageResponse {
+            period: period.as_str().to_string(),
+            period_start_unix: since.map(|start| start.timestamp()).unwrap_or(0),
+            request_count: summary.requests,
+            input_tokens: summary.input_tokens,
+            output_tokens: summary.output_tokens,
+            total_cost: summary.cost,
+            models: summary.models.into_iter().map(|usage| geri::ModelUsage {
+                model: usage.model,
+                provider: usage.provider,
+                request_count: usage.requests,
+                input_tokens: usage.input_tokens,
+                output_tokens: usage.output_tokens,
+                cost: usage.cost,
+            }).collect(),
+            budgets: budgets.into_iter().map(|status| geri::BudgetStatus {
+                over_limit: status.is_over_limit(),
+                user_id: status.limit.user_id.unwrap_or_default(),
+                device_id: status.limit.device_id.unwrap_or_default(),
+                period: status.limit.period.as_str().to_string(),
+                limit: status.limit.limit,
+                used: status.used,
+                resets_at_unix: status.resets_at.map(|at| at.timestamp()).unwrap_or(0),
+            }).collect(),
+        }))
+    }
 }
 
 pub struct GrpcServerDependencies {
     pub model_registry: Arc<crate::model::ModelRegistry>,
-    pub llm_provider: Arc<dyn crate::llm::LLMProvider>,
+    pub engine: Arc<crate::llm::GeriEngine>,
     pub vision_processor: Arc<crate::vision::VisionProcessor>,
+    pub token_counter: crate::prompt::TokenCounter,
+    pub embeddings: Option<Arc<crate::llm::EmbeddingService>>,
+    pub budget_tracker: Option<Arc<crate::cost::BudgetTracker>>,
+    pub vision_engine: Option<Arc<crate::llm::GeriEngine>>,
 }
 
 pub async fn start_grpc_server(
@@ -161,13 +532,24 @@ pub async fn start_grpc_server(
 ) -> Result<(), Box<dyn std::error::Error>> {
     info!("Starting Geri gRPC server on {}", addr);
 
-    let geri_service = GeriServiceImpl::new(
+    let mut geri_service = GeriServiceImpl::new(
         deps.model_registry,
-        deps.llm_provider,
+        deps.engine,
         deps.vision_processor,
-    );
+    )
+    .with_token_counter(deps.token_counter);
+    if let Some(embeddings) = deps.embeddings {
+        geri_service = geri_service.with_embeddings(embeddings);
+    }
+    if let Some(budget_tracker) = deps.budget_tracker {
+        geri_service = geri_service.with_budget_tracker(budget_tracker);
+    }
+    if let Some(vision_engine) = deps.vision_engine {
+        geri_service = geri_service.with_vision_engine(vision_engine);
+    }
 
     Server::builder()
+        .layer(vegvisir::ServerTraceLayer)
         .add_service(GeriServiceServer::new(geri_service))
         .serve(addr)
         .await?;
@@ -0,0 +1,89 @@
+//! Bearer-token authentication of the OpenAI HTTP API against Heimdall.
+
+use async_trait::async_trait;
+use thiserror::Error;
+use tonic::transport::Channel;
+
+pub mod heimdall_token {
+    tonic::include_proto!("heimdall.token");
+}
+
+use heimdall_token::token_service_client::TokenServiceClient;
+use heimdall_token::ValidateTokenRequest;
+
+/// Header with which clients bind a request to their device (optional, checked by Heimdall)
+pub const DEVICE_ID_HEADER: &str = "x-device-id";
+
+#[derive(Debug, Error)]
+pub enum AuthError {
+    #[error("Missing bearer token")]
+    MissingToken,
+    #[error("Invalid token: {0}")]
+    InvalidToken(String),
+    #[error("Heimdall unavailable: {0}")]
+    Unavailable(String),
+}
+
+/// Identity behind a validated token; added to the request extensions for handlers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthenticatedUser {
+    pub user_id: String,
+    pub device_id: String,
+    pub permissions: Vec<String>,
+}
+
+#[async_trait]
+pub trait TokenValidator: Send + Sync {
+    async fn validate(&self, token: &str, device_id: Option<&str>) -> Result<AuthenticatedUser, AuthError>;
+}
+
+/// Validates tokens via Heimdall's `TokenService.ValidateToken`
+#[derive(Clone)]
+pub struct HeimdallTokenValidator {
+    client: TokenServiceClient<vegvisir::TracedChannel>,
+}
+
+impl HeimdallTokenValidator {
+    /// Connects lazily, so Geri starts even if Heimdall is not up yet
+    pub fn new(heimdall_url: &str) -> Result<Self, AuthError> {
+        let channel = Channel::from_shared(heimdall_url.to_string())
+            .map_err(|e| AuthError::Unavailable(format!("Invalid Heimdall URL: {}", e)))?
+            .connect_lazy();
+        Ok(Self {
+            client: TokenServiceClient::new(vegvisir::traced(channel)),
+        })
+    }
+}
+
+#[async_trait]
+impl TokenValidator for HeimdallTokenValidator {
+    async fn validate(&self, token: &str, device_id: Option<&str>) -> Result<AuthenticatedUser, AuthError> {
+        let response = self
+            .client
+            .clone()
+            .validate_token(ValidateTokenRequest {
+                token: token.to_string(),
+                device_id: device_id.unwrap_or_default().to_string(),
+            })
+            .await
+            .map_err(|e| AuthError::Unavailable(e.message().to_string()))?
+            .into_inner();
+
+        if !response.valid || response.is_revoked {
+            let reason = if response.reason.is_empty() { "token rejected".to_string() } else { response.reason };
+            return Err(AuthError::InvalidToken(reason));
+        }
+        Ok(AuthenticatedUser {
+            user_id: response.user_id,
+            device_id: response.device_id,
+            permissions: response.permissions,
+        })
+    }
+}
+
+/// Extracts the token from an `Authorization: Bearer <token>` header value
+pub fn bearer_token(header: &str) -> Option<&str> {
+    let (scheme, token) = header.trim().split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
+}
@@ -0,0 +1,9 @@
+//! OpenAI-compatible HTTP API (feature `openai-api`): `/v1/chat/completions` (incl. SSE streaming),
+//! `/v1/models` and `/v1/embeddings` on top of `GeriEngine`, authenticated with Heimdall tokens.
+
+pub mod auth;
+pub mod server;
+pub mod types;
+
+pub use auth::{AuthError, AuthenticatedUser, HeimdallTokenValidator, TokenValidator};
+pub use server::{router, start_openai_server, OpenAiApiState};
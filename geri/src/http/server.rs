@@ -0,0 +1,434 @@
+//! axum router translating the OpenAI endpoints onto `GeriEngine`.
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{Path, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use futures::{Stream, StreamExt};
+use tracing::{info, warn};
+
+use super::auth::{bearer_token, AuthError, AuthenticatedUser, TokenValidator, DEVICE_ID_HEADER};
+use super::types::{
+    finish_reason, AssistantMessage, ChatCompletionChoice, ChatCompletionChunk, ChatCompletionRequest,
+    ChatCompletionResponse, ChunkChoice, ChunkDelta, ChunkToolCall, EmbeddingObject, EmbeddingsRequest,
+    EmbeddingsResponse, EmbeddingsUsage, ErrorResponse, ModelList, ModelObject, Usage,
+};
+use crate::llm::{EmbeddingService, GeriEngine, LLMError, PromptRequest, PromptStreamChunk};
+use crate::model::{ModelRegistryTrait, ModelType};
+use crate::prompt::TokenCounter;
+use crate::selection::SelectionOptions;
+
+/// Shared state of the HTTP handlers
+#[derive(Clone)]
+pub struct OpenAiApiState {
+    engine: Arc<GeriEngine>,
+    registry: Arc<dyn ModelRegistryTrait>,
+    token_counter: TokenCounter,
+    validator: Option<Arc<dyn TokenValidator>>,
+    embeddings: Option<Arc<EmbeddingService>>,
+}
+
+impl OpenAiApiState {
+    /// State without authentication; use `with_validator` to require Heimdall tokens.
+    pub fn new(engine: Arc<GeriEngine>, registry: Arc<dyn ModelRegistryTrait>) -> Self {
+        Self {
+            engine,
+            registry,
+            token_counter: TokenCounter::default(),
+            validator: None,
+            embeddings: None,
+        }
+    }
+
+    /// Uses the given token counter (model tokenizers) for the `usage` prompt/completion split.
+    pub fn with_token_counter(mut self, token_counter: TokenCounter) -> Self {
+        self.token_counter = token_counter;
+        self
+    }
+
+    /// Serves `/v1/embeddings`; without it the endpoint answers 501.
+    pub fn with_embeddings(mut self, embeddings: Arc<EmbeddingService>) -> Self {
+        self.embeddings = Some(embeddings);
+        self
+    }
+
+    /// Requires a valid bearer token on every request.
+    pub fn with_validator(mut self, validator: Arc<dyn TokenValidator>) -> Self {
+        self.validator = Some(validator);
+        self
+    }
+
+    fn prompt_tokens(&self, request: &PromptRequest, model: &str) -> u32 {
+        let counter = &self.token_counter;
+        let system = counter.count_for_model(request.system_prompt.as_deref().unwrap_or(""), model);
+        request
+            .conversation()
+            .iter()
+            .fold(system, |sum, message| sum + counter.count_for_model(&message.content, model))
+    }
+}
+
+/// Router with `/v1/models`, `/v1/chat/completions` and `/v1/embeddings`
+pub fn router(state: OpenAiApiState) -> Router {
+    Router::new()
+        .route("/v1/models", get(list_models))
+        .route("/v1/models/:model", get(get_model))
+        .route("/v1/chat/completions", post(chat_completions))
+        .route("/v1/embeddings", post(embeddings))
+        .layer(middleware::from_fn_with_state(state.clone(), authenticate))
+        .with_state(state)
+}
+
+pub async fn start_openai_server(
+    addr: SocketAddr,
+    state: OpenAiApiState,
+) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Starting Geri OpenAI-compatible HTTP server on {}", addr);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}
+
+/// Error in OpenAI's JSON envelope
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    body: ErrorResponse,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, kind: &str, code: Option<&str>, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: ErrorResponse::new(kind, code, message),
+        }
+    }
+
+    fn invalid_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "invalid_request_error", None, message)
+    }
+
+    fn model_not_found(model: &str) -> Self {
+        Self::new(
+            StatusCode::NOT_FOUND,
+            "invalid_request_error",
+            Some("model_not_found"),
+            format!("The model '{}' does not exist", model),
+        )
+    }
+}
+
+impl From<LLMError> for ApiError {
+    fn from(error: LLMError) -> Self {
+        match error {
+            LLMError::ModelNotAvailable(_) => {
+                Self::new(StatusCode::SERVICE_UNAVAILABLE, "server_error", Some("model_not_available"), error.to_string())
+            }
+            LLMError::ProcessingFailed(_) => Self::new(StatusCode::BAD_GATEWAY, "server_error", None, error.to_string()),
+            LLMError::Timeout(_) => Self::new(StatusCode::GATEWAY_TIMEOUT, "server_error", Some("timeout"), error.to_string()),
+            LLMError::InvalidInput(_) => Self::invalid_request(error.to_string()),
+        }
+    }
+}
+
+impl From<AuthError> for ApiError {
+    fn from(error: AuthError) -> Self {
+        match error {
+            AuthError::Unavailable(_) => {
+                Self::new(StatusCode::SERVICE_UNAVAILABLE, "server_error", None, error.to_string())
+            }
+            AuthError::MissingToken | AuthError::InvalidToken(_) => {
+                Self::new(StatusCode::UNAUTHORIZED, "invalid_request_error", Some("invalid_api_key"), error.to_string())
+            }
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(self.body)).into_response()
+    }
+}
+
+/// `Cache-Control: no-cache` / `no-store` skips the response cache for this request
+fn bypasses_cache(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|directive| matches!(directive.trim().to_ascii_lowercase().as_str(), "no-cache" | "no-store"))
+}
+
+/// `X-Request-Priority: voice|chat|background` sets the scheduling priority; chat otherwise
+fn request_priority(headers: &HeaderMap) -> crate::queue::RequestPriority {
+    headers
+        .get("x-request-priority")
+        .and_then(|value| value.to_str().ok())
+        .and_then(crate::queue::RequestPriority::parse)
+        .unwrap_or_default()
+}
+
+async fn authenticate(State(state): State<OpenAiApiState>, mut request: Request, next: Next) -> Response {
+    let Some(validator) = state.validator.as_ref() else {
+        return next.run(request).await;
+    };
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(bearer_token);
+    let Some(token) = token else {
+        return ApiError::from(AuthError::MissingToken).into_response();
+    };
+    let device_id = request
+        .headers()
+        .get(DEVICE_ID_HEADER)
+        .and_then(|value| value.to_str().ok());
+
+    match validator.validate(token, device_id).await {
+        Ok(user) => {
+            request.extensions_mut().insert(user);
+            next.run(request).await
+        }
+        Err(e) => {
+            warn!("Rejected OpenAI API request: {}", e);
+            ApiError::from(e).into_response()
+        }
+    }
+}
+
+async fn list_models(State(state): State<OpenAiApiState>) -> Result<Json<ModelList>, ApiError> {
+    let models = state
+        .registry
+        .list_all()
+        .await
+        .map_err(|e| LLMError::ProcessingFailed(e.to_string()))?;
+    let mut data: Vec<ModelObject> = models.iter().map(ModelObject::from).collect();
+    data.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(Json(ModelList {
+        object: "list".to_string(),
+        data,
+    }))
+}
+
+async fn get_model(State(state): State<OpenAiApiState>, Path(model): Path<String>) -> Result<Json<ModelObject>, ApiError> {
+    let info = state
+        .registry
+        .get_by_id(&model)
+        .await
+        .map_err(|e| LLMError::ProcessingFailed(e.to_string()))?
+        .ok_or_else(|| ApiError::model_not_found(&model))?;
+    Ok(Json(ModelObject::from(&info)))
+}
+
+async fn chat_completions(
+    State(state): State<OpenAiApiState>,
+    user: Option<Extension<AuthenticatedUser>>,
+    headers: HeaderMap,
+    body: Result<Json<ChatCompletionRequest>, JsonRejection>,
+) -> Result<Response, ApiError> {
+    let Json(request) = body.map_err(|e| ApiError::invalid_request(e.body_text()))?;
+    if request.messages.is_empty() {
+        return Err(ApiError::invalid_request("'messages' must not be empty"));
+    }
+
+    let mut options = SelectionOptions::default();
+    if !request.is_auto_model() {
+        let known = state
+            .registry
+            .get_by_id(&request.model)
+            .await
+            .map_err(|e| LLMError::ProcessingFailed(e.to_string()))?;
+        match known {
+            None => return Err(ApiError::model_not_found(&request.model)),
+            Some(model) if model.model_type == ModelType::Embedding => {
+                return Err(ApiError::invalid_request(format!("'{}' is an embedding model", model.id)));
+            }
+            Some(_) => {}
+        }
+        options.user_preferred_model_id = Some(request.model.clone());
+    }
+
+    let model = if request.is_auto_model() { "auto".to_string() } else { request.model.clone() };
+    let mut prompt_request = request.to_prompt_request();
+    // Heimdall identity wins over the client-supplied `user`, so cached answers stay per user
+    prompt_request.device_id = user.as_ref().map(|Extension(user)| user.device_id.clone()).filter(|id| !id.is_empty());
+    prompt_request.user_id = user.map(|Extension(user)| user.user_id).or_else(|| request.user.clone());
+    prompt_request.no_cache = bypasses_cache(&headers);
+    prompt_request.priority = request_priority(&headers);
+    let prompt_tokens = state.prompt_tokens(&prompt_request, &model);
+    let id = format!("chatcmpl-{}", uuid::Uuid::new_v4().simple());
+    let created = chrono::Utc::now().timestamp();
+
+    if request.stream {
+        let stream = state.engine.process_stream(prompt_request, options).await?;
+        let chunks = ChunkBuilder {
+            id,
+            created,
+            model,
+            prompt_tokens,
+            include_usage: request.stream_options.as_ref().is_some_and(|o| o.include_usage),
+            tool_calls: 0,
+        };
+        return Ok(Sse::new(sse_events(chunks, stream)).keep_alive(KeepAlive::default()).into_response());
+    }
+
+    let response = state.engine.process(prompt_request, options).await?;
+    let has_tool_calls = !response.tool_calls.is_empty();
+    let content = if response.text.is_empty() && has_tool_calls { None } else { Some(response.text) };
+    Ok(Json(ChatCompletionResponse {
+        id,
+        object: "chat.completion".to_string(),
+        created,
+        model,
+        choices: vec![ChatCompletionChoice {
+            index: 0,
+            message: AssistantMessage {
+                role: "assistant".to_string(),
+                content,
+                tool_calls: response.tool_calls.into_iter().map(Into::into).collect(),
+            },
+            finish_reason: finish_reason("stop", has_tool_calls),
+        }],
+        usage: Usage::from_total(prompt_tokens, response.tokens_used),
+    })
+    .into_response())
+}
+
+/// Embeddings need an embedding model, which Geri does not serve yet.
+async fn embeddings(
+    State(state): State<OpenAiApiState>,
+    body: Result<Json<EmbeddingsRequest>, JsonRejection>,
+) -> Result<Json<EmbeddingsResponse>, ApiError> {
+    let Some(service) = state.embeddings.as_ref() else {
+        return Err(ApiError::new(
+            StatusCode::NOT_IMPLEMENTED,
+            "invalid_request_error",
+            Some("unsupported_endpoint"),
+            "Embeddings are not supported by this Geri instance",
+        ));
+    };
+    let Json(request) = body.map_err(|e| ApiError::invalid_request(e.body_text()))?;
+    let base64 = match request.encoding_format.as_deref() {
+        None | Some("float") => false,
+        Some("base64") => true,
+        Some(other) => return Err(ApiError::invalid_request(format!("Unsupported encoding_format '{}'", other))),
+    };
+
+    let model = if request.model.is_empty() || request.model.eq_ignore_ascii_case("auto") {
+        None
+    } else {
+        let known = state
+            .registry
+            .get_by_id(&request.model)
+            .await
+            .map_err(|e| LLMError::ProcessingFailed(e.to_string()))?;
+        if known.is_none() {
+            return Err(ApiError::model_not_found(&request.model));
+        }
+        Some(request.model.as_str())
+    };
+    let result = service.embed(model, &request.input.into_inputs()).await?;
+
+    Ok(Json(EmbeddingsResponse {
+        object: "list".to_string(),
+        data: result
+            .vectors
+            .into_iter()
+            .enumerate()
+            .map(|(index, vector)| EmbeddingObject::new(index, vector, base64))
+            .collect(),
+        model: result.model,
+        usage: EmbeddingsUsage {
+            prompt_tokens: result.tokens_used,
+            total_tokens: result.tokens_used,
+        },
+    }))
+}
+
+/// Turns engine stream frames into `chat.completion.chunk` events
+struct ChunkBuilder {
+    id: String,
+    created: i64,
+    model: String,
+    prompt_tokens: u32,
+    include_usage: bool,
+    tool_calls: u32,
+}
+
+impl ChunkBuilder {
+    fn chunk(&self, delta: ChunkDelta, finish_reason: Option<String>) -> ChatCompletionChunk {
+        ChatCompletionChunk {
+            id: self.id.clone(),
+            object: "chat.completion.chunk".to_string(),
+            created: self.created,
+            model: self.model.clone(),
+            choices: vec![ChunkChoice {
+                index: 0,
+                delta,
+                finish_reason,
+            }],
+            usage: None,
+        }
+    }
+
+    fn events(&mut self, frame: Result<PromptStreamChunk, LLMError>) -> Vec<Event> {
+        match frame {
+            Ok(PromptStreamChunk::Delta(text)) if text.is_empty() => Vec::new(),
+            Ok(PromptStreamChunk::Delta(text)) => {
+                vec![json_event(&self.chunk(ChunkDelta { content: Some(text), ..Default::default() }, None))]
+            }
+            Ok(PromptStreamChunk::ToolCall(call)) => {
+                let delta = ChunkDelta {
+                    tool_calls: vec![ChunkToolCall {
+                        index: self.tool_calls,
+                        call: call.into(),
+                    }],
+                    ..Default::default()
+                };
+                self.tool_calls += 1;
+                vec![json_event(&self.chunk(delta, None))]
+            }
+            Ok(PromptStreamChunk::Done { tokens_used, finish_reason: reason, .. }) => {
+                let reason = finish_reason(&reason, self.tool_calls > 0);
+                let mut events = vec![json_event(&self.chunk(ChunkDelta::default(), Some(reason)))];
+                if self.include_usage {
+                    let mut usage = self.chunk(ChunkDelta::default(), None);
+                    usage.choices.clear();
+                    usage.usage = Some(Usage::from_total(self.prompt_tokens, tokens_used));
+                    events.push(json_event(&usage));
+                }
+                events
+            }
+            Err(e) => vec![json_event(&ApiError::from(e).body)],
+        }
+    }
+}
+
+fn sse_events(
+    mut builder: ChunkBuilder,
+    stream: crate::llm::PromptStream,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    let role = ChunkDelta {
+        role: Some("assistant".to_string()),
+        ..Default::default()
+    };
+    let first = json_event(&builder.chunk(role, None));
+    futures::stream::once(async move { first })
+        .chain(stream.flat_map(move |frame| futures::stream::iter(builder.events(frame))))
+        .chain(futures::stream::once(async { Event::default().data("[DONE]") }))
+        .map(Ok)
+}
+
+fn json_event<T: serde::Serialize>(value: &T) -> Event {
+    Event::default().data(serde_json::to_string(value).unwrap_or_default())
+}
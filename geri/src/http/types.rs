@@ -0,0 +1,449 @@
+//! Request/response bodies of the OpenAI HTTP API (the subset Geri serves).
+
+use serde::{Deserialize, Serialize};
+
+use crate::llm::{ChatMessage, ChatRole, PromptRequest, ResponseFormat, ToolCall, ToolDefinition};
+
+/// `POST /v1/chat/completions`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChatCompletionRequest {
+    /// Model id from `/v1/models`; empty or `auto` lets Geri's `ModelSelector` choose
+    #[serde(default)]
+    pub model: String,
+    pub messages: Vec<OpenAiMessage>,
+    #[serde(default)]
+    pub stream: bool,
+    #[serde(default)]
+    pub stream_options: Option<StreamOptions>,
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+    /// Newer name of `max_tokens`
+    #[serde(default)]
+    pub max_completion_tokens: Option<u32>,
+    #[serde(default)]
+    pub tools: Vec<OpenAiTool>,
+    /// End-user id; only used when the request is not authenticated via Heimdall
+    #[serde(default)]
+    pub user: Option<String>,
+    #[serde(default)]
+    pub response_format: Option<OpenAiResponseFormat>,
+}
+
+/// `response_format`: free text, any JSON object, or JSON matching a schema
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OpenAiResponseFormat {
+    Text,
+    JsonObject,
+    JsonSchema { json_schema: OpenAiJsonSchema },
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OpenAiJsonSchema {
+    pub name: String,
+    #[serde(default)]
+    pub schema: Option<serde_json::Value>,
+    #[serde(default)]
+    pub strict: Option<bool>,
+}
+
+impl OpenAiResponseFormat {
+    /// The schema Geri validates the answer against; `None` for free text
+    pub fn to_response_format(&self) -> Option<ResponseFormat> {
+        match self {
+            OpenAiResponseFormat::Text => None,
+            OpenAiResponseFormat::JsonObject => {
+                Some(ResponseFormat::new("json_object", serde_json::json!({ "type": "object" })))
+            }
+            OpenAiResponseFormat::JsonSchema { json_schema } => Some(
+                ResponseFormat::new(
+                    json_schema.name.clone(),
+                    json_schema.schema.clone().unwrap_or_else(|| serde_json::json!({})),
+                )
+                .with_strict(json_schema.strict.unwrap_or(false)),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StreamOptions {
+    #[serde(default)]
+    pub include_usage: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OpenAiMessage {
+    pub role: String,
+    #[serde(default)]
+    pub content: Option<MessageContent>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub tool_call_id: Option<String>,
+    #[serde(default)]
+    pub tool_calls: Vec<OpenAiToolCall>,
+}
+
+/// Message content: a plain string or a list of content parts
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum MessageContent {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContentPart {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub text: Option<String>,
+}
+
+impl MessageContent {
+    /// Concatenated text; non-text parts (images, audio) are ignored
+    pub fn text(&self) -> String {
+        match self {
+            MessageContent::Text(text) => text.clone(),
+            MessageContent::Parts(parts) => parts
+                .iter()
+                .filter(|part| part.kind == "text")
+                .filter_map(|part| part.text.as_deref())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OpenAiTool {
+    #[serde(rename = "type", default = "function_type")]
+    pub kind: String,
+    pub function: OpenAiFunction,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OpenAiFunction {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub parameters: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiToolCall {
+    pub id: String,
+    #[serde(rename = "type", default = "function_type")]
+    pub kind: String,
+    pub function: OpenAiFunctionCall,
+}
+
+/// Function call with JSON-encoded arguments, as OpenAI sends them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAiFunctionCall {
+    pub name: String,
+    pub arguments: String,
+}
+
+fn function_type() -> String {
+    "function".to_string()
+}
+
+impl From<ToolCall> for OpenAiToolCall {
+    fn from(call: ToolCall) -> Self {
+        Self {
+            id: call.id,
+            kind: function_type(),
+            function: OpenAiFunctionCall {
+                name: call.name,
+                arguments: call.arguments.to_string(),
+            },
+        }
+    }
+}
+
+impl From<OpenAiToolCall> for ToolCall {
+    fn from(call: OpenAiToolCall) -> Self {
+        Self {
+            id: call.id,
+            arguments: crate::llm::tools::parse_arguments(&call.function.arguments),
+            name: call.function.name,
+        }
+    }
+}
+
+impl ChatCompletionRequest {
+    /// Whether the caller lets Geri pick the model
+    pub fn is_auto_model(&self) -> bool {
+        self.model.is_empty() || self.model.eq_ignore_ascii_case("auto")
+    }
+
+    /// Maps the OpenAI conversation onto a `PromptRequest`: system messages become the system
+    /// prompt, all other turns (including the last user turn) go into `messages`.
+    pub fn to_prompt_request(&self) -> PromptRequest {
+        let mut system = Vec::new();
+        let mut messages: Vec<ChatMessage> = Vec::new();
+        for message in &self.messages {
+            let content = message.content.as_ref().map(MessageContent::text).unwrap_or_default();
+            let role = ChatRole::parse(&message.role);
+            if role == ChatRole::System || message.role == "developer" {
+                system.push(content);
+                continue;
+            }
+            let mut chat = ChatMessage::new(role, content);
+            chat.tool_calls = message.tool_calls.iter().cloned().map(Into::into).collect();
+            chat.tool_call_id = message.tool_call_id.clone();
+            chat.name = message.name.clone();
+            if role == ChatRole::Tool && chat.name.is_none() {
+                // OpenAI tool turns only carry the call id; the tool name comes from the assistant turn
+                chat.name = messages
+                    .iter()
+                    .flat_map(|m| m.tool_calls.iter())
+                    .find(|call| Some(&call.id) == message.tool_call_id.as_ref())
+                    .map(|call| call.name.clone());
+            }
+            messages.push(chat);
+        }
+
+        PromptRequest {
+            prompt: String::new(),
+            system_prompt: if system.is_empty() { None } else { Some(system.join("\n\n")) },
+            context: None,
+            max_tokens: self.max_completion_tokens.or(self.max_tokens),
+            messages,
+            tools: self
+                .tools
+                .iter()
+                .filter(|tool| tool.kind == "function")
+                .map(|tool| ToolDefinition {
+                    name: tool.function.name.clone(),
+                    description: tool.function.description.clone(),
+                    parameters: tool
+                        .function
+                        .parameters
+                        .clone()
+                        .unwrap_or_else(|| serde_json::json!({ "type": "object", "properties": {} })),
+                })
+                .collect(),
+            response_format: self.response_format.as_ref().and_then(OpenAiResponseFormat::to_response_format),
+            ..Default::default()
+        }
+    }
+}
+
+/// Non-streaming `chat.completion` response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatCompletionResponse {
+    pub id: String,
+    pub object: String,
+    pub created: i64,
+    pub model: String,
+    pub choices: Vec<ChatCompletionChoice>,
+    pub usage: Usage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatCompletionChoice {
+    pub index: u32,
+    pub message: AssistantMessage,
+    pub finish_reason: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssistantMessage {
+    pub role: String,
+    pub content: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<OpenAiToolCall>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Usage {
+    pub prompt_tokens: u32,
+    pub completion_tokens: u32,
+    pub total_tokens: u32,
+}
+
+impl Usage {
+    /// Splits the provider's total token count into prompt and completion share
+    pub fn from_total(prompt_tokens: u32, total_tokens: u32) -> Self {
+        let prompt_tokens = prompt_tokens.min(total_tokens);
+        Self {
+            prompt_tokens,
+            completion_tokens: total_tokens - prompt_tokens,
+            total_tokens,
+        }
+    }
+}
+
+/// Streaming `chat.completion.chunk`, sent as one SSE `data:` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatCompletionChunk {
+    pub id: String,
+    pub object: String,
+    pub created: i64,
+    pub model: String,
+    pub choices: Vec<ChunkChoice>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkChoice {
+    pub index: u32,
+    pub delta: ChunkDelta,
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChunkDelta {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ChunkToolCall>,
+}
+
+/// Tool call inside a stream delta; Geri sends each call complete in a single delta
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkToolCall {
+    pub index: u32,
+    #[serde(flatten)]
+    pub call: OpenAiToolCall,
+}
+
+/// `GET /v1/models`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelList {
+    pub object: String,
+    pub data: Vec<ModelObject>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelObject {
+    pub id: String,
+    pub object: String,
+    pub created: i64,
+    pub owned_by: String,
+}
+
+impl From<&crate::model::ModelInfo> for ModelObject {
+    fn from(model: &crate::model::ModelInfo) -> Self {
+        Self {
+            id: model.id.clone(),
+            object: "model".to_string(),
+            created: 0,
+            owned_by: model.provider.clone(),
+        }
+    }
+}
+
+/// `POST /v1/embeddings`
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmbeddingsRequest {
+    /// Embedding model id; empty or `auto` uses Geri's default embedding model
+    #[serde(default)]
+    pub model: String,
+    pub input: EmbeddingInput,
+    /// `float` (default) or `base64` (little-endian f32, as sent by the official SDKs)
+    #[serde(default)]
+    pub encoding_format: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum EmbeddingInput {
+    Single(String),
+    Batch(Vec<String>),
+}
+
+impl EmbeddingInput {
+    pub fn into_inputs(self) -> Vec<String> {
+        match self {
+            EmbeddingInput::Single(input) => vec![input],
+            EmbeddingInput::Batch(inputs) => inputs,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingsResponse {
+    pub object: String,
+    pub data: Vec<EmbeddingObject>,
+    pub model: String,
+    pub usage: EmbeddingsUsage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingObject {
+    pub object: String,
+    pub index: usize,
+    /// Array of floats, or a base64 string for `encoding_format: base64`
+    pub embedding: serde_json::Value,
+}
+
+impl EmbeddingObject {
+    pub fn new(index: usize, vector: Vec<f32>, base64: bool) -> Self {
+        let embedding = if base64 {
+            use base64::Engine;
+            let bytes: Vec<u8> = vector.iter().flat_map(|value| value.to_le_bytes()).collect();
+            serde_json::Value::String(base64::engine::general_purpose::STANDARD.encode(bytes))
+        } else {
+            serde_json::json!(vector)
+        };
+        Self {
+            object: "embedding".to_string(),
+            index,
+            embedding,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingsUsage {
+    pub prompt_tokens: u32,
+    pub total_tokens: u32,
+}
+
+/// OpenAI error envelope: `{"error": {...}}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub error: ErrorBody,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorBody {
+    pub message: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub code: Option<String>,
+}
+
+impl ErrorResponse {
+    pub fn new(kind: &str, code: Option<&str>, message: impl Into<String>) -> Self {
+        Self {
+            error: ErrorBody {
+                message: message.into(),
+                kind: kind.to_string(),
+                code: code.map(str::to_string),
+            },
+        }
+    }
+}
+
+/// Maps provider finish reasons (Anthropic, Gemini, llama.cpp, ...) onto OpenAI's vocabulary
+pub fn finish_reason(reason: &str, has_tool_calls: bool) -> String {
+    if has_tool_calls {
+        return "tool_calls".to_string();
+    }
+    match reason.to_lowercase().as_str() {
+        "length" | "max_tokens" => "length",
+        "tool_calls" | "tool_use" => "tool_calls",
+        "content_filter" | "safety" => "content_filter",
+        _ => "stop",
+    }
+    .to_string()
+}
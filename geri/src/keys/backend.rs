@@ -1,4 +1,4 @@
-//! Backend für Secure-Key-Storage (OS-spezifisch oder In-Memory für Tests).
+//! Backend für Secure-Key-Storage (OS-spezifisch, verschlüsselte Datei, Heimdall oder In-Memory für Tests).
 
 use std::collections::HashMap;
 
@@ -0,0 +1,227 @@
+//! Verschlüsselter Datei-Key-Store für Server ohne OS-Keychain (headless Asgard).
+//!
+//! Jeder Eintrag ist einzeln mit AES-256-GCM verschlüsselt (zufällige Nonce, Key-ID als
+//! Associated Data). Der Schlüssel wird per Argon2id aus einer Passphrase abgeleitet oder von
+//! Heimdall bereitgestellt. Die Datei wird bei jedem Zugriff gelesen, damit Rotationen durch
+//! andere Prozesse sofort sichtbar sind, und atomar geschrieben.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use crate::keys::{KeyStorageError, SecureKeyBackend};
+
+const FORMAT_VERSION: u32 = 1;
+/// Prüfwert, mit dem beim Öffnen eine falsche Passphrase erkannt wird.
+const VERIFIER_ID: &str = "__geri_key_store__";
+const VERIFIER_PLAINTEXT: &[u8] = b"geri-key-store";
+
+/// Argon2id-Parameter, in der Datei gespeichert.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KdfParams {
+    salt: String,
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EncryptedEntry {
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KeyStoreFile {
+    version: u32,
+    /// Fehlt, wenn der Schlüssel direkt übergeben wurde (Heimdall).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    kdf: Option<KdfParams>,
+    verifier: EncryptedEntry,
+    #[serde(default)]
+    entries: BTreeMap<String, EncryptedEntry>,
+}
+
+/// Key-Backend auf einer verschlüsselten JSON-Datei.
+pub struct EncryptedFileKeyBackend {
+    path: PathBuf,
+    cipher: Aes256Gcm,
+}
+
+impl EncryptedFileKeyBackend {
+    /// Öffnet (oder legt an) die Datei mit einem per Argon2id aus der Passphrase abgeleiteten Schlüssel.
+    pub fn open_with_passphrase(path: impl Into<PathBuf>, passphrase: &str) -> Result<Self, KeyStorageError> {
+        let path = path.into();
+        if passphrase.is_empty() {
+            return Err(KeyStorageError::Crypto("Passphrase must not be empty".to_string()));
+        }
+        let (kdf, existing) = match read_file(&path)? {
+            Some(file) => {
+                let kdf = file.kdf.clone().ok_or_else(|| {
+                    KeyStorageError::Crypto("Key store was created with a raw key, not a passphrase".to_string())
+                })?;
+                (kdf, Some(file))
+            }
+            None => {
+                let mut salt = [0u8; 16];
+                OsRng.fill_bytes(&mut salt);
+                let params = argon2::Params::default();
+                let kdf = KdfParams {
+                    salt: BASE64.encode(salt),
+                    m_cost: params.m_cost(),
+                    t_cost: params.t_cost(),
+                    p_cost: params.p_cost(),
+                };
+                (kdf, None)
+            }
+        };
+        let key = derive_key(passphrase, &kdf)?;
+        Self::open(path, key, Some(kdf), existing)
+    }
+
+    /// Öffnet (oder legt an) die Datei mit einem 256-Bit-Schlüssel, z. B. von Heimdall.
+    pub fn open_with_key(path: impl Into<PathBuf>, key: [u8; 32]) -> Result<Self, KeyStorageError> {
+        let path = path.into();
+        let existing = read_file(&path)?;
+        if existing.as_ref().is_some_and(|file| file.kdf.is_some()) {
+            return Err(KeyStorageError::Crypto("Key store was created with a passphrase, not a raw key".to_string()));
+        }
+        Self::open(path, key, None, existing)
+    }
+
+    fn open(path: PathBuf, key: [u8; 32], kdf: Option<KdfParams>, existing: Option<KeyStoreFile>) -> Result<Self, KeyStorageError> {
+        let backend = Self {
+            path,
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)),
+        };
+        match existing {
+            Some(file) => {
+                if file.version != FORMAT_VERSION {
+                    return Err(KeyStorageError::Backend(format!("Unsupported key store version {}", file.version)));
+                }
+                let verifier = backend
+                    .decrypt(VERIFIER_ID, &file.verifier)
+                    .map_err(|_| KeyStorageError::Crypto("Wrong passphrase or key for key store".to_string()))?;
+                if verifier != VERIFIER_PLAINTEXT {
+                    return Err(KeyStorageError::Crypto("Wrong passphrase or key for key store".to_string()));
+                }
+            }
+            None => {
+                let file = KeyStoreFile {
+                    version: FORMAT_VERSION,
+                    kdf,
+                    verifier: backend.encrypt(VERIFIER_ID, VERIFIER_PLAINTEXT)?,
+                    entries: BTreeMap::new(),
+                };
+                write_file(&backend.path, &file)?;
+            }
+        }
+        Ok(backend)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// IDs aller gespeicherten Keys.
+    pub fn key_ids(&self) -> Result<Vec<String>, KeyStorageError> {
+        Ok(self.read()?.entries.into_keys().collect())
+    }
+
+    fn read(&self) -> Result<KeyStoreFile, KeyStorageError> {
+        read_file(&self.path)?.ok_or_else(|| KeyStorageError::Backend(format!("Key store {} was removed", self.path.display())))
+    }
+
+    fn encrypt(&self, key_id: &str, plaintext: &[u8]) -> Result<EncryptedEntry, KeyStorageError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, Payload { msg: plaintext, aad: key_id.as_bytes() })
+            .map_err(|_| KeyStorageError::Crypto(format!("Encrypting {} failed", key_id)))?;
+        Ok(EncryptedEntry {
+            nonce: BASE64.encode(nonce),
+            ciphertext: BASE64.encode(ciphertext),
+        })
+    }
+
+    fn decrypt(&self, key_id: &str, entry: &EncryptedEntry) -> Result<Vec<u8>, KeyStorageError> {
+        let invalid = || KeyStorageError::Crypto(format!("Entry {} is corrupted or was encrypted with another key", key_id));
+        let nonce = BASE64.decode(&entry.nonce).map_err(|_| invalid())?;
+        let ciphertext = BASE64.decode(&entry.ciphertext).map_err(|_| invalid())?;
+        if nonce.len() != 12 {
+            return Err(invalid());
+        }
+        self.cipher
+            .decrypt(Nonce::from_slice(&nonce), Payload { msg: &ciphertext, aad: key_id.as_bytes() })
+            .map_err(|_| invalid())
+    }
+}
+
+impl SecureKeyBackend for EncryptedFileKeyBackend {
+    fn store(&mut self, key_id: &str, value: &[u8]) -> Result<(), KeyStorageError> {
+        let mut file = self.read()?;
+        let entry = self.encrypt(key_id, value)?;
+        file.entries.insert(key_id.to_string(), entry);
+        write_file(&self.path, &file)
+    }
+
+    fn load(&self, key_id: &str) -> Result<Option<Vec<u8>>, KeyStorageError> {
+        let file = self.read()?;
+        file.entries.get(key_id).map(|entry| self.decrypt(key_id, entry)).transpose()
+    }
+
+    fn delete(&mut self, key_id: &str) -> Result<(), KeyStorageError> {
+        let mut file = self.read()?;
+        if file.entries.remove(key_id).is_some() {
+            write_file(&self.path, &file)?;
+        }
+        Ok(())
+    }
+}
+
+fn derive_key(passphrase: &str, kdf: &KdfParams) -> Result<[u8; 32], KeyStorageError> {
+    let salt = BASE64
+        .decode(&kdf.salt)
+        .map_err(|e| KeyStorageError::Crypto(format!("Invalid salt: {}", e)))?;
+    let params = argon2::Params::new(kdf.m_cost, kdf.t_cost, kdf.p_cost, Some(32))
+        .map_err(|e| KeyStorageError::Crypto(format!("Invalid Argon2 parameters: {}", e)))?;
+    let argon2 = argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+    let mut key = [0u8; 32];
+    argon2
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+        .map_err(|e| KeyStorageError::Crypto(format!("Key derivation failed: {}", e)))?;
+    Ok(key)
+}
+
+fn read_file(path: &Path) -> Result<Option<KeyStoreFile>, KeyStorageError> {
+    match std::fs::read(path) {
+        Ok(bytes) => serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| KeyStorageError::Backend(format!("Invalid key store {}: {}", path.display(), e))),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(KeyStorageError::Backend(format!("Reading {} failed: {}", path.display(), e))),
+    }
+}
+
+/// Schreibt über eine temporäre Datei und Rename (nur für den Besitzer lesbar).
+fn write_file(path: &Path, file: &KeyStoreFile) -> Result<(), KeyStorageError> {
+    let io_error = |e: std::io::Error| KeyStorageError::Backend(format!("Writing {} failed: {}", path.display(), e));
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(io_error)?;
+    }
+    let json = serde_json::to_vec_pretty(file).map_err(|e| KeyStorageError::Backend(e.to_string()))?;
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, json).map_err(io_error)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&tmp, std::fs::Permissions::from_mode(0o600)).map_err(io_error)?;
+    }
+    std::fs::rename(&tmp, path).map_err(io_error)
+}
@@ -8,4 +8,6 @@ pub enum KeyStorageError {
     Backend(String),
     #[error("Invalid UTF-8 in stored key")]
     InvalidUtf8,
+    #[error("Key store encryption error: {0}")]
+    Crypto(String),
 }
@@ -0,0 +1,146 @@
+//! Provider-Keys von Heimdall (`SecretService`): Heimdall verwaltet und rotiert die Keys, Geri
+//! hält eine lokale Kopie, die regelmäßig aktualisiert wird.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+use tonic::transport::Channel;
+
+use crate::keys::{KeyStorageError, SecureKeyBackend};
+
+pub mod heimdall_secrets {
+    tonic::include_proto!("heimdall.secrets");
+}
+
+use heimdall_secrets::secret_service_client::SecretServiceClient;
+use heimdall_secrets::{GetKeyStoreKeyRequest, GetProviderKeysRequest};
+
+/// Quelle für Provider-Keys und den Schlüssel des lokalen Key-Stores.
+#[async_trait]
+pub trait RemoteKeySource: Send + Sync {
+    /// Aktuelle Keys der Provider (Provider-ID → API-Key); fehlende Provider fehlen in der Map.
+    async fn provider_keys(&self, provider_ids: &[String]) -> Result<HashMap<String, String>, KeyStorageError>;
+
+    /// 256-Bit-Schlüssel für den verschlüsselten Datei-Key-Store.
+    async fn key_store_key(&self) -> Result<[u8; 32], KeyStorageError>;
+}
+
+/// Holt die Keys über Heimdalls `SecretService` (authentifiziert mit dem Service-Token).
+#[derive(Clone)]
+pub struct HeimdallKeySource {
+    client: SecretServiceClient<vegvisir::TracedChannel>,
+    service_id: String,
+    service_token: String,
+}
+
+impl HeimdallKeySource {
+    /// Verbindet lazy, damit Geri auch startet, wenn Heimdall noch nicht läuft.
+    pub fn new(heimdall_url: &str, service_id: &str, service_token: &str) -> Result<Self, KeyStorageError> {
+        let channel = Channel::from_shared(heimdall_url.to_string())
+            .map_err(|e| KeyStorageError::Backend(format!("Invalid Heimdall URL: {}", e)))?
+            .connect_lazy();
+        Ok(Self {
+            client: SecretServiceClient::new(vegvisir::traced(channel)),
+            service_id: service_id.to_string(),
+            service_token: service_token.to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl RemoteKeySource for HeimdallKeySource {
+    async fn provider_keys(&self, provider_ids: &[String]) -> Result<HashMap<String, String>, KeyStorageError> {
+        let response = self
+            .client
+            .clone()
+            .get_provider_keys(GetProviderKeysRequest {
+                service_id: self.service_id.clone(),
+                service_token: self.service_token.clone(),
+                provider_ids: provider_ids.to_vec(),
+            })
+            .await
+            .map_err(|e| KeyStorageError::Backend(format!("Heimdall: {}", e.message())))?
+            .into_inner();
+        Ok(response
+            .keys
+            .into_iter()
+            .filter(|key| !key.api_key.is_empty())
+            .map(|key| (key.provider_id, key.api_key))
+            .collect())
+    }
+
+    async fn key_store_key(&self) -> Result<[u8; 32], KeyStorageError> {
+        let response = self
+            .client
+            .clone()
+            .get_key_store_key(GetKeyStoreKeyRequest {
+                service_id: self.service_id.clone(),
+                service_token: self.service_token.clone(),
+            })
+            .await
+            .map_err(|e| KeyStorageError::Backend(format!("Heimdall: {}", e.message())))?
+            .into_inner();
+        response
+            .key
+            .try_into()
+            .map_err(|key: Vec<u8>| KeyStorageError::Crypto(format!("Heimdall key store key has {} bytes, expected 32", key.len())))
+    }
+}
+
+/// Key-Backend mit den Keys aus einer `RemoteKeySource`. Lesend aus dem lokalen Cache;
+/// `refresh` holt die aktuellen Keys. Klone teilen sich den Cache.
+#[derive(Clone)]
+pub struct HeimdallKeyBackend {
+    source: Arc<dyn RemoteKeySource>,
+    provider_ids: Vec<String>,
+    cache: Arc<RwLock<HashMap<String, String>>>,
+}
+
+impl HeimdallKeyBackend {
+    pub fn new(source: Arc<dyn RemoteKeySource>, provider_ids: &[&str]) -> Self {
+        Self {
+            source,
+            provider_ids: provider_ids.iter().map(|id| id.to_string()).collect(),
+            cache: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Holt die aktuellen Keys; liefert die Provider-IDs, deren Key sich geändert hat.
+    /// Bei einem Fehler bleiben die zuletzt geholten Keys aktiv.
+    pub async fn refresh(&self) -> Result<Vec<String>, KeyStorageError> {
+        let keys = self.source.provider_keys(&self.provider_ids).await?;
+        let mut cache = self.cache.write().expect("key cache lock poisoned");
+        let mut changed: Vec<String> = keys
+            .iter()
+            .filter(|(provider_id, api_key)| cache.get(*provider_id) != Some(*api_key))
+            .map(|(provider_id, _)| provider_id.clone())
+            .collect();
+        changed.sort();
+        cache.extend(keys);
+        Ok(changed)
+    }
+}
+
+impl SecureKeyBackend for HeimdallKeyBackend {
+    /// Keys werden in Heimdall verwaltet; lokale Änderungen gelten nur bis zum nächsten `refresh`.
+    fn store(&mut self, key_id: &str, value: &[u8]) -> Result<(), KeyStorageError> {
+        let value = String::from_utf8(value.to_vec()).map_err(|_| KeyStorageError::InvalidUtf8)?;
+        self.cache.write().expect("key cache lock poisoned").insert(key_id.to_string(), value);
+        Ok(())
+    }
+
+    fn load(&self, key_id: &str) -> Result<Option<Vec<u8>>, KeyStorageError> {
+        Ok(self
+            .cache
+            .read()
+            .expect("key cache lock poisoned")
+            .get(key_id)
+            .map(|value| value.as_bytes().to_vec()))
+    }
+
+    fn delete(&mut self, key_id: &str) -> Result<(), KeyStorageError> {
+        self.cache.write().expect("key cache lock poisoned").remove(key_id);
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+//! Live-Keys: aktuelle API-Keys der Provider, geteilt zwischen Key-Storage und laufenden Clients.
+//! Rotierte Keys werden hier ausgetauscht und gelten ab dem nächsten Request, ohne Neustart.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use crate::keys::{KeyStorageError, SecureKeyStorage};
+
+/// Provider-IDs der Cloud-Provider, deren Keys Geri verwaltet.
+pub const CLOUD_PROVIDER_IDS: &[&str] = &["openai", "anthropic", "google"];
+
+/// Aktueller API-Key eines Providers; Klone sehen jeden Austausch.
+#[derive(Clone, Default)]
+pub struct LiveApiKey {
+    key: Arc<RwLock<String>>,
+}
+
+impl LiveApiKey {
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            key: Arc::new(RwLock::new(api_key.into())),
+        }
+    }
+
+    /// Der aktuelle Key (leer, wenn noch keiner gesetzt ist).
+    pub fn get(&self) -> String {
+        self.key.read().expect("live key lock poisoned").clone()
+    }
+
+    /// Tauscht den Key aus; `true`, wenn er sich geändert hat.
+    pub fn set(&self, api_key: &str) -> bool {
+        let mut key = self.key.write().expect("live key lock poisoned");
+        if *key == api_key {
+            return false;
+        }
+        *key = api_key.to_string();
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.key.read().expect("live key lock poisoned").is_empty()
+    }
+}
+
+impl fmt::Debug for LiveApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("LiveApiKey(***)")
+    }
+}
+
+/// Live-Keys aller Provider; Klone teilen sich die Keys.
+#[derive(Debug, Clone, Default)]
+pub struct LiveKeys {
+    keys: Arc<RwLock<HashMap<String, LiveApiKey>>>,
+}
+
+impl LiveKeys {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle für einen Provider (wird bei Bedarf leer angelegt), z. B. für einen Client.
+    pub fn handle(&self, provider_id: &str) -> LiveApiKey {
+        self.keys
+            .write()
+            .expect("live keys lock poisoned")
+            .entry(provider_id.to_string())
+            .or_default()
+            .clone()
+    }
+
+    /// Handle nur, wenn für den Provider ein Key vorliegt.
+    pub fn get(&self, provider_id: &str) -> Option<LiveApiKey> {
+        self.keys
+            .read()
+            .expect("live keys lock poisoned")
+            .get(provider_id)
+            .filter(|key| !key.is_empty())
+            .cloned()
+    }
+
+    /// Setzt den Key eines Providers in allen Clients; `true`, wenn er sich geändert hat.
+    pub fn apply(&self, provider_id: &str, api_key: &str) -> bool {
+        self.handle(provider_id).set(api_key)
+    }
+
+    /// Übernimmt die Keys der Provider aus dem Storage; liefert die geänderten Provider-IDs.
+    /// Fehlt ein Key im Storage, bleibt der bisherige Key aktiv.
+    pub fn sync_from(&self, storage: &SecureKeyStorage, provider_ids: &[&str]) -> Result<Vec<String>, KeyStorageError> {
+        let mut changed = Vec::new();
+        for provider_id in provider_ids {
+            if let Some(api_key) = storage.load_key(provider_id)? {
+                if self.apply(provider_id, &api_key) {
+                    changed.push(provider_id.to_string());
+                }
+            }
+        }
+        Ok(changed)
+    }
+}
@@ -1,10 +1,17 @@
-//! API-Key-Management (Phase 14.1, 14.2): Secure Storage, Key-Rotation.
+//! API-Key-Management (Phase 14.1, 14.2): Secure Storage, Key-Rotation, verschlüsselter
+//! Datei-Store, Keys von Heimdall und Hot-Swap rotierter Keys in laufende Clients.
 
 mod backend;
+mod encrypted_file;
 mod error;
+mod heimdall;
+mod live;
 mod rotation;
 mod storage;
 pub use backend::{InMemoryKeyBackend, SecureKeyBackend};
+pub use encrypted_file::EncryptedFileKeyBackend;
 pub use error::KeyStorageError;
+pub use heimdall::{heimdall_secrets, HeimdallKeyBackend, HeimdallKeySource, RemoteKeySource};
+pub use live::{LiveApiKey, LiveKeys, CLOUD_PROVIDER_IDS};
 pub use rotation::KeyRotationManager;
 pub use storage::SecureKeyStorage;
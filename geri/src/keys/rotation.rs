@@ -1,6 +1,6 @@
 //! Key-Rotation-Manager (Phase 14.2.1): Alte Keys entfernen, neue hinzufügen, Rotation-Workflow.
 
-use crate::keys::{KeyStorageError, SecureKeyStorage};
+use crate::keys::{KeyStorageError, LiveKeys, SecureKeyStorage};
 
 /// Führt den Rotation-Workflow für API-Keys durch (alt entfernen, neu speichern).
 #[derive(Debug, Clone, Copy, Default)]
@@ -17,4 +17,20 @@ impl KeyRotationManager {
         let _ = storage.remove_key(provider_id);
         storage.store_key(provider_id, new_api_key)
     }
+
+    /// Rotiert wie `rotate` und tauscht den Key danach in den laufenden Clients aus
+    /// (gilt ab dem nächsten Request, ohne Neustart).
+    pub fn rotate_live(
+        self,
+        storage: &mut SecureKeyStorage,
+        live_keys: &LiveKeys,
+        provider_id: &str,
+        new_api_key: &str,
+    ) -> Result<(), KeyStorageError> {
+        self.rotate(storage, provider_id, new_api_key)?;
+        if live_keys.apply(provider_id, new_api_key) {
+            tracing::info!(provider = %provider_id, "API key rotated in running clients");
+        }
+        Ok(())
+    }
 }
@@ -1,7 +1,10 @@
 pub mod llm;
 pub mod vision;
 pub mod evaluation;
+pub mod safety;
 pub mod grpc;
+#[cfg(feature = "openai-api")]
+pub mod http;
 pub mod utils;
 pub mod model;
 pub mod prompt;
@@ -1,8 +1,14 @@
 //! Anthropic API Client for Claude LLM and Vision requests
 
+use std::pin::Pin;
+
+use futures::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::llm::messages::{ChatMessage, ChatRole};
+use crate::llm::tools::{ToolCall, ToolDefinition, ToolResult};
+
 #[derive(Debug, Clone)]
 pub struct AnthropicConfig {
     pub api_key: String,
@@ -22,10 +28,28 @@ pub enum AnthropicError {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ContentBlock {
-    #[serde(rename = "type")]
-    pub content_type: String, // "text"
-    pub text: String,
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text { text: String },
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        is_error: bool,
+    },
+}
+
+/// Entry of the request `tools` array
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnthropicTool {
+    pub name: String,
+    pub description: String,
+    pub input_schema: serde_json::Value,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -43,6 +67,10 @@ pub struct MessagesRequest {
     pub system: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<AnthropicTool>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,8 +84,28 @@ pub struct MessagesResponse {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseContent {
     #[serde(rename = "type")]
-    pub content_type: String,
+    pub content_type: String, // "text" | "tool_use"
     pub text: Option<String>,
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub input: Option<serde_json::Value>,
+}
+
+impl ResponseContent {
+    /// The tool call carried by a `tool_use` block
+    pub fn tool_call(&self) -> Option<ToolCall> {
+        if self.content_type != "tool_use" {
+            return None;
+        }
+        Some(ToolCall {
+            id: self.id.clone()?,
+            name: self.name.clone()?,
+            arguments: self.input.clone().unwrap_or_else(|| serde_json::json!({})),
+        })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,6 +114,82 @@ pub struct Usage {
     pub output_tokens: u32,
 }
 
+/// Server-sent event of a streamed Messages API call
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamEvent {
+    MessageStart { message: StreamMessageStart },
+    ContentBlockStart {
+        index: usize,
+        content_block: StreamContentBlock,
+    },
+    ContentBlockDelta {
+        #[serde(default)]
+        index: usize,
+        delta: StreamDelta,
+    },
+    ContentBlockStop { index: usize },
+    MessageDelta {
+        delta: StreamMessageDelta,
+        #[serde(default)]
+        usage: Option<StreamUsage>,
+    },
+    MessageStop,
+    Error { error: StreamErrorBody },
+    /// `ping` and future event types
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StreamMessageStart {
+    pub usage: StreamUsage,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StreamContentBlock {
+    #[serde(rename = "type")]
+    pub block_type: String, // "text" | "tool_use"
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StreamDelta {
+    #[serde(rename = "type")]
+    pub delta_type: String, // "text_delta" | "input_json_delta"
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub partial_json: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StreamMessageDelta {
+    #[serde(default)]
+    pub stop_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StreamUsage {
+    #[serde(default)]
+    pub input_tokens: Option<u32>,
+    #[serde(default)]
+    pub output_tokens: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct StreamErrorBody {
+    #[serde(rename = "type")]
+    pub error_type: String,
+    pub message: String,
+}
+
+/// Stream of parsed events returned by [`AnthropicClient::messages_stream`]
+pub type MessagesStream = Pin<Box<dyn Stream<Item = Result<StreamEvent, AnthropicError>> + Send>>;
+
 // Vision-specific content blocks
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -97,9 +221,24 @@ pub struct VisionRequest {
     pub max_tokens: u32,
 }
 
+/// Append content blocks, merging into the previous message if it has the same role
+fn push_blocks(messages: &mut Vec<Message>, role: &str, blocks: Vec<ContentBlock>) {
+    if blocks.is_empty() {
+        return;
+    }
+    match messages.last_mut() {
+        Some(last) if last.role == role => last.content.extend(blocks),
+        _ => messages.push(Message {
+            role: role.to_string(),
+            content: blocks,
+        }),
+    }
+}
+
 pub struct AnthropicClient {
     config: AnthropicConfig,
     client: reqwest::Client,
+    live_key: Option<crate::keys::LiveApiKey>,
 }
 
 impl AnthropicClient {
@@ -109,7 +248,21 @@ impl AnthropicClient {
             .build()
             .expect("Failed to build HTTP client");
         
-        Self { config, client }
+        Self { config, client, live_key: None }
+    }
+
+    /// Reads the API key from `live_key` on every request, so rotated keys apply without a restart
+    pub fn with_live_key(mut self, live_key: crate::keys::LiveApiKey) -> Self {
+        self.live_key = Some(live_key);
+        self
+    }
+
+    /// The current API key: the live key if set, otherwise the configured one
+    pub fn api_key(&self) -> String {
+        match &self.live_key {
+            Some(live_key) => live_key.get(),
+            None => self.config.api_key.clone(),
+        }
     }
 
     pub fn base_url(&self) -> &str {
@@ -117,10 +270,11 @@ impl AnthropicClient {
     }
 
     pub async fn validate(&self) -> Result<(), AnthropicError> {
-        if self.config.api_key.is_empty() {
+        let api_key = self.api_key();
+        if api_key.is_empty() {
             return Err(AnthropicError::InvalidConfig("API key is empty".to_string()));
         }
-        if !self.config.api_key.starts_with("sk-ant-") {
+        if !api_key.starts_with("sk-ant-") {
             return Err(AnthropicError::InvalidConfig("API key must start with 'sk-ant-'".to_string()));
         }
         Ok(())
@@ -134,15 +288,67 @@ impl AnthropicClient {
         context: Option<&str>,
         max_tokens: Option<u32>,
     ) -> MessagesRequest {
-        let messages = vec![Message {
-            role: "user".to_string(),
-            content: vec![ContentBlock {
-                content_type: "text".to_string(),
-                text: prompt.to_string(),
-            }],
-        }];
-        
+        self.build_conversation_request(
+            model,
+            &[ChatMessage::user(prompt)],
+            system_prompt,
+            context,
+            max_tokens,
+        )
+    }
+
+    /// Messages request for a multi-turn conversation.
+    ///
+    /// System turns are merged into `system`, tool turns become `tool_result` blocks on a user
+    /// message, and consecutive turns of the same role are merged as the API requires alternation.
+    pub fn build_conversation_request(
+        &self,
+        model: &str,
+        conversation: &[ChatMessage],
+        system_prompt: Option<&str>,
+        context: Option<&str>,
+        max_tokens: Option<u32>,
+    ) -> MessagesRequest {
+        let mut messages = Vec::new();
         let mut system_content = system_prompt.unwrap_or("").to_string();
+
+        for message in conversation {
+            match message.role {
+                ChatRole::System => {
+                    if !system_content.is_empty() {
+                        system_content.push_str("\n\n");
+                    }
+                    system_content.push_str(&message.content);
+                }
+                ChatRole::User => push_blocks(
+                    &mut messages,
+                    "user",
+                    vec![ContentBlock::Text { text: message.content.clone() }],
+                ),
+                ChatRole::Assistant => {
+                    let mut blocks = Vec::new();
+                    if !message.content.is_empty() {
+                        blocks.push(ContentBlock::Text { text: message.content.clone() });
+                    }
+                    blocks.extend(message.tool_calls.iter().map(|call| ContentBlock::ToolUse {
+                        id: call.id.clone(),
+                        name: call.name.clone(),
+                        input: call.arguments.clone(),
+                    }));
+                    push_blocks(&mut messages, "assistant", blocks);
+                }
+                ChatRole::Tool => push_blocks(
+                    &mut messages,
+                    "user",
+                    vec![ContentBlock::ToolResult {
+                        tool_use_id: message.tool_call_id.clone().unwrap_or_default(),
+                        content: message.content.clone(),
+                        is_error: false,
+                    }],
+                ),
+            }
+        }
+        
         if let Some(ctx) = context {
             if !system_content.is_empty() {
                 system_content.push_str("\n\nContext:\n");
@@ -164,9 +370,68 @@ impl AnthropicClient {
             max_tokens: max_tokens.unwrap_or(1024),
             system,
             temperature: None,
+            stream: None,
+            tools: None,
+        }
+    }
+
+    /// Add tool declarations and the previous tool-call round (assistant `tool_use`
+    /// blocks followed by a user message with the matching `tool_result` blocks)
+    pub fn apply_tools(
+        &self,
+        request: &mut MessagesRequest,
+        tools: &[ToolDefinition],
+        tool_calls: &[ToolCall],
+        tool_results: &[ToolResult],
+    ) {
+        if !tools.is_empty() {
+            request.tools = Some(
+                tools
+                    .iter()
+                    .map(|tool| AnthropicTool {
+                        name: tool.name.clone(),
+                        description: tool.description.clone(),
+                        input_schema: tool.parameters.clone(),
+                    })
+                    .collect(),
+            );
+        }
+        if !tool_calls.is_empty() {
+            push_blocks(
+                &mut request.messages,
+                "assistant",
+                tool_calls
+                    .iter()
+                    .map(|call| ContentBlock::ToolUse {
+                        id: call.id.clone(),
+                        name: call.name.clone(),
+                        input: call.arguments.clone(),
+                    })
+                    .collect(),
+            );
+        }
+        if !tool_results.is_empty() {
+            push_blocks(
+                &mut request.messages,
+                "user",
+                tool_results
+                    .iter()
+                    .map(|result| ContentBlock::ToolResult {
+                        tool_use_id: result.tool_call_id.clone(),
+                        content: result.content.clone(),
+                        is_error: result.is_error,
+                    })
+                    .collect(),
+            );
         }
     }
 
+    /// Parse a single SSE payload of a streamed Messages API call
+    pub fn parse_stream_event(data: &str) -> Result<StreamEvent, AnthropicError> {
+        serde_json::from_str(data)
+            .map_err(|e| AnthropicError::RequestFailed(format!("Failed to parse stream event: {}", e)))
+    }
+
     pub fn build_vision_request(
         &self,
         model: &str,
@@ -208,7 +473,7 @@ impl AnthropicClient {
         let response = self
             .client
             .post(&url)
-            .header("x-api-key", &self.config.api_key)
+            .header("x-api-key", self.api_key())
             .header("anthropic-version", &self.config.anthropic_version)
             .header("Content-Type", "application/json")
             .json(&request)
@@ -228,13 +493,43 @@ impl AnthropicClient {
             .map_err(|e| AnthropicError::RequestFailed(format!("Failed to parse response: {}", e)))
     }
 
+    /// Send a messages request with `stream: true` and yield the events as they arrive
+    pub async fn messages_stream(&self, mut request: MessagesRequest) -> Result<MessagesStream, AnthropicError> {
+        request.stream = Some(true);
+        let url = format!("{}/messages", self.config.base_url);
+
+        let response = self
+            .client
+            .post(&url)
+            .header("x-api-key", self.api_key())
+            .header("anthropic-version", &self.config.anthropic_version)
+            .header("Content-Type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| AnthropicError::RequestFailed(e.to_string()))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(AnthropicError::APIError(format!("HTTP {}: {}", status, error_text)));
+        }
+
+        let events = crate::streaming::sse_events(response).map(|event| {
+            event
+                .map_err(|e| AnthropicError::RequestFailed(e.to_string()))
+                .and_then(|data| Self::parse_stream_event(&data))
+        });
+        Ok(Box::pin(events))
+    }
+
     pub async fn vision_messages(&self, request: VisionRequest) -> Result<MessagesResponse, AnthropicError> {
         let url = format!("{}/messages", self.config.base_url);
         
         let response = self
             .client
             .post(&url)
-            .header("x-api-key", &self.config.api_key)
+            .header("x-api-key", self.api_key())
             .header("anthropic-version", &self.config.anthropic_version)
             .header("Content-Type", "application/json")
             .json(&request)
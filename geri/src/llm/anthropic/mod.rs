@@ -1,5 +1,5 @@
 pub mod client;
 pub mod provider;
 
-pub use client::{AnthropicClient, AnthropicConfig, AnthropicError, MessagesRequest, Message, ContentBlock, VisionContentBlock};
+pub use client::{AnthropicClient, AnthropicConfig, AnthropicError, MessagesRequest, MessagesStream, Message, ContentBlock, StreamEvent, VisionContentBlock};
 pub use provider::AnthropicLLMProvider;
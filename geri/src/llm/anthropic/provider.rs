@@ -1,8 +1,12 @@
 //! Anthropic LLM Provider implementing LLMProvider trait
 
+use std::collections::BTreeMap;
+
 use async_trait::async_trait;
-use super::client::{AnthropicClient, AnthropicConfig};
-use crate::llm::{LLMError, LLMProvider, PromptRequest, PromptResponse};
+use futures::StreamExt;
+use super::client::{AnthropicClient, AnthropicConfig, MessagesRequest, StreamEvent};
+use crate::llm::tools::parse_arguments;
+use crate::llm::{LLMError, LLMProvider, PromptRequest, PromptResponse, PromptStream, PromptStreamChunk, ToolCall};
 
 pub struct AnthropicLLMProvider {
     client: AnthropicClient,
@@ -14,6 +18,33 @@ impl AnthropicLLMProvider {
         let client = AnthropicClient::new(config);
         Self { client, model_name }
     }
+
+    /// Uses the live API key, which key rotation swaps without a restart
+    pub fn with_live_key(mut self, live_key: crate::keys::LiveApiKey) -> Self {
+        self.client = self.client.with_live_key(live_key);
+        self
+    }
+
+    fn build_request(&self, request: &PromptRequest) -> MessagesRequest {
+        // No native JSON Schema mode: the schema goes into the system prompt and the answer is
+        // validated afterwards
+        let system_prompt = match &request.response_format {
+            Some(format) => Some(match request.system_prompt.as_deref() {
+                Some(system) if !system.is_empty() => format!("{}\n\n{}", system, format.render_instructions()),
+                _ => format.render_instructions(),
+            }),
+            None => request.system_prompt.clone(),
+        };
+        let mut messages_request = self.client.build_conversation_request(
+            &self.model_name,
+            &request.conversation(),
+            system_prompt.as_deref(),
+            request.context.as_deref(),
+            request.max_tokens,
+        );
+        self.client.apply_tools(&mut messages_request, &request.tools, &request.tool_calls, &request.tool_results);
+        messages_request
+    }
 }
 
 #[async_trait]
@@ -24,13 +55,7 @@ impl LLMProvider for AnthropicLLMProvider {
 
     async fn process_prompt(&self, request: PromptRequest) -> Result<PromptResponse, LLMError> {
         // Build Anthropic messages request
-        let messages_request = self.client.build_messages_request(
-            &self.model_name,
-            &request.prompt,
-            request.system_prompt.as_deref(),
-            request.context.as_deref(),
-            request.max_tokens,
-        );
+        let messages_request = self.build_request(&request);
 
         // Send request to Anthropic API
         let response = self
@@ -39,12 +64,21 @@ impl LLMProvider for AnthropicLLMProvider {
             .await
             .map_err(|e| LLMError::ProcessingFailed(e.to_string()))?;
 
-        // Extract response text from first content block
-        let text = response
+        // Collect text blocks and tool_use blocks
+        let text: String = response
+            .content
+            .iter()
+            .filter_map(|c| c.text.as_deref())
+            .collect();
+        let tool_calls: Vec<ToolCall> = response
             .content
-            .first()
-            .and_then(|c| c.text.clone())
-            .ok_or_else(|| LLMError::ProcessingFailed("No content in response".to_string()))?;
+            .iter()
+            .filter_map(|c| c.tool_call())
+            .collect();
+
+        if text.is_empty() && tool_calls.is_empty() {
+            return Err(LLMError::ProcessingFailed("No content in response".to_string()));
+        }
 
         // Calculate total tokens
         let tokens_used = response.usage.input_tokens + response.usage.output_tokens;
@@ -52,6 +86,96 @@ impl LLMProvider for AnthropicLLMProvider {
         Ok(PromptResponse {
             text,
             tokens_used,
+            tool_calls,
+            structured: None,
+            safety: Vec::new(),
         })
     }
+
+    async fn stream_prompt(&self, request: PromptRequest) -> Result<PromptStream, LLMError> {
+        let messages_request = self.build_request(&request);
+
+        let mut events = self
+            .client
+            .messages_stream(messages_request)
+            .await
+            .map_err(|e| LLMError::ProcessingFailed(e.to_string()))?;
+
+        let (tx, rx) = tokio::sync::mpsc::channel(64);
+        tokio::spawn(async move {
+            let mut input_tokens = 0;
+            let mut output_tokens = 0;
+            let mut finish_reason = "end_turn".to_string();
+            // Open tool_use blocks by index: (id, name, partial JSON input)
+            let mut pending_calls: BTreeMap<usize, (String, String, String)> = BTreeMap::new();
+            while let Some(event) = events.next().await {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(e) => {
+                        let _ = tx.send(Err(LLMError::ProcessingFailed(e.to_string()))).await;
+                        return;
+                    }
+                };
+                match event {
+                    StreamEvent::MessageStart { message } => {
+                        input_tokens = message.usage.input_tokens.unwrap_or(0);
+                        output_tokens = message.usage.output_tokens.unwrap_or(0);
+                    }
+                    StreamEvent::ContentBlockStart { index, content_block } => {
+                        if content_block.block_type == "tool_use" {
+                            pending_calls.insert(index, (
+                                content_block.id.unwrap_or_default(),
+                                content_block.name.unwrap_or_default(),
+                                String::new(),
+                            ));
+                        }
+                    }
+                    StreamEvent::ContentBlockDelta { index, delta } => {
+                        if let Some(json) = delta.partial_json {
+                            if let Some(call) = pending_calls.get_mut(&index) {
+                                call.2.push_str(&json);
+                            }
+                        }
+                        if let Some(text) = delta.text.filter(|t| !t.is_empty()) {
+                            if tx.send(Ok(PromptStreamChunk::Delta(text))).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    StreamEvent::ContentBlockStop { index } => {
+                        if let Some((id, name, input)) = pending_calls.remove(&index) {
+                            let call = ToolCall { id, name, arguments: parse_arguments(&input) };
+                            if tx.send(Ok(PromptStreamChunk::ToolCall(call))).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    StreamEvent::MessageDelta { delta, usage } => {
+                        if let Some(reason) = delta.stop_reason {
+                            finish_reason = reason;
+                        }
+                        if let Some(tokens) = usage.and_then(|u| u.output_tokens) {
+                            output_tokens = tokens;
+                        }
+                    }
+                    StreamEvent::MessageStop => break,
+                    StreamEvent::Error { error } => {
+                        let message = format!("{}: {}", error.error_type, error.message);
+                        let _ = tx.send(Err(LLMError::ProcessingFailed(message))).await;
+                        return;
+                    }
+                    StreamEvent::Other => {}
+                }
+            }
+            let _ = tx
+                .send(Ok(PromptStreamChunk::Done {
+                    tokens_used: input_tokens + output_tokens,
+                    finish_reason,
+                    safety: Vec::new(),
+                }))
+                .await;
+        });
+
+        Ok(Box::pin(tokio_stream::wrappers::ReceiverStream::new(rx)))
+    }
 }
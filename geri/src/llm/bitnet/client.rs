@@ -1,3 +1,6 @@
+use std::pin::Pin;
+
+use futures::Stream;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -11,6 +14,9 @@ pub enum BitNetError {
     InvalidConfig(String),
 }
 
+/// Stream of generated text pieces returned by `generate_stream`
+pub type TokenStream = Pin<Box<dyn Stream<Item = Result<String, BitNetError>> + Send>>;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BitNetConfig {
     /// Path to the BitNet model file
@@ -136,11 +142,36 @@ impl BitNetClient {
         ))
     }
     
+    /// Generate text from a prompt, yielding pieces as they are produced
+    /// 
+    /// # Arguments
+    /// 
+    /// * `prompt` - The input prompt
+    /// * `max_tokens` - Maximum number of tokens to generate
+    /// 
+    /// # Note
+    /// 
+    /// Like `generate` this is a stub: the stub output is split into word pieces.
+    /// With the BitNet.cpp FFI bindings each piece will be one decoded token.
+    pub async fn generate_stream(&self, prompt: &str, max_tokens: u32) -> Result<TokenStream, BitNetError> {
+        let text = self.generate(prompt, max_tokens).await?;
+        let pieces: Vec<Result<String, BitNetError>> = text
+            .split_inclusive(' ')
+            .map(|piece| Ok(piece.to_string()))
+            .collect();
+        Ok(Box::pin(futures::stream::iter(pieces)))
+    }
+    
     /// Get the model name
     pub fn model_name(&self) -> &str {
         &self.model_name
     }
     
+    /// Get the model file path
+    pub fn model_path(&self) -> &str {
+        &self.config.model_path
+    }
+    
     /// Get the context size
     pub fn context_size(&self) -> u32 {
         self.config.n_ctx
@@ -1,5 +1,5 @@
 pub mod client;
 pub mod provider;
 
-pub use client::{BitNetClient, BitNetConfig, BitNetError};
+pub use client::{BitNetClient, BitNetConfig, BitNetError, TokenStream};
 pub use provider::BitNetLLMProvider;
@@ -1,5 +1,8 @@
 use async_trait::async_trait;
-use crate::llm::provider::{LLMProvider, LLMError, PromptRequest, PromptResponse};
+use futures::StreamExt;
+use crate::llm::provider::{LLMProvider, LLMError, PromptRequest, PromptResponse, PromptStream, PromptStreamChunk};
+use crate::llm::tools::parse_tool_calls;
+use crate::prompt::ChatTemplate;
 use super::client::BitNetClient;
 
 /// LLM Provider implementation for BitNet.cpp
@@ -14,6 +17,7 @@ use super::client::BitNetClient;
 /// - Maintained quality through specialized 1-bit training
 pub struct BitNetLLMProvider {
     client: BitNetClient,
+    chat_template: ChatTemplate,
 }
 
 impl BitNetLLMProvider {
@@ -22,8 +26,11 @@ impl BitNetLLMProvider {
     /// # Arguments
     /// 
     /// * `client` - The configured BitNet.cpp client
+    /// 
+    /// The chat template is detected from the GGUF metadata or the model name.
     pub fn new(client: BitNetClient) -> Self {
-        Self { client }
+        let chat_template = ChatTemplate::resolve(None, client.model_path());
+        Self { client, chat_template }
     }
     
     /// Get the underlying client (for advanced usage)
@@ -47,6 +54,25 @@ impl BitNetLLMProvider {
     pub fn bit_depth(&self) -> u8 {
         self.client.bit_depth()
     }
+    
+    /// Use the given chat template instead of the auto-detected one
+    pub fn with_chat_template(mut self, chat_template: ChatTemplate) -> Self {
+        self.chat_template = chat_template;
+        self
+    }
+    
+    /// Get the chat template used to format prompts
+    pub fn chat_template(&self) -> ChatTemplate {
+        self.chat_template
+    }
+    
+    /// Build the full prompt in the model family's chat template
+    /// 
+    /// Tools are JSON-prompted: instructions go into the system prompt, previous tool calls
+    /// and results become assistant/tool turns.
+    fn build_prompt(&self, request: &PromptRequest) -> String {
+        self.chat_template.render_request(request)
+    }
 }
 
 #[async_trait]
@@ -56,12 +82,7 @@ impl LLMProvider for BitNetLLMProvider {
     }
     
     async fn process_prompt(&self, request: PromptRequest) -> Result<PromptResponse, LLMError> {
-        // Build the full prompt with context if provided
-        let full_prompt = if let Some(context) = &request.context {
-            format!("Context: {}\n\nPrompt: {}", context, request.prompt)
-        } else {
-            request.prompt.clone()
-        };
+        let full_prompt = self.build_prompt(&request);
         
         // Use max_tokens from request or default to 512
         let max_tokens = request.max_tokens.unwrap_or(512);
@@ -72,6 +93,8 @@ impl LLMProvider for BitNetLLMProvider {
             .generate(&full_prompt, max_tokens)
             .await
             .map_err(|e| LLMError::ProcessingFailed(e.to_string()))?;
+        // Drop anything after the end-of-turn marker (no role markers in the answer)
+        let generated_text = self.chat_template.trim_output(&generated_text).to_string();
         
         // Estimate token usage (simplified - would use actual tokenizer in production)
         // Count input tokens
@@ -80,11 +103,93 @@ impl LLMProvider for BitNetLLMProvider {
         let output_tokens = generated_text.split_whitespace().count() as u32;
         let total_tokens = input_tokens + output_tokens;
         
+        // A JSON answer naming a known tool is a tool call, not text for the user
+        if let Some(tool_calls) = parse_tool_calls(&generated_text, &request.tools) {
+            return Ok(PromptResponse {
+                text: String::new(),
+                tokens_used: total_tokens,
+                tool_calls,
+                structured: None,
+                safety: Vec::new(),
+            });
+        }
+        
         Ok(PromptResponse {
             text: generated_text,
             tokens_used: total_tokens,
+            tool_calls: Vec::new(),
+            structured: None,
+            safety: Vec::new(),
         })
     }
+    
+    async fn stream_prompt(&self, request: PromptRequest) -> Result<PromptStream, LLMError> {
+        let full_prompt = self.build_prompt(&request);
+        let max_tokens = request.max_tokens.unwrap_or(512);
+        
+        let mut pieces = self.client
+            .generate_stream(&full_prompt, max_tokens)
+            .await
+            .map_err(|e| LLMError::ProcessingFailed(e.to_string()))?;
+        
+        let input_tokens = full_prompt.split_whitespace().count() as u32;
+        let tools = request.tools;
+        let chat_template = self.chat_template;
+        let (tx, rx) = tokio::sync::mpsc::channel(64);
+        tokio::spawn(async move {
+            let mut output_tokens = 0u32;
+            // With tools the answer may be a JSON tool call, so it is buffered until complete
+            let mut buffered = String::new();
+            let mut generated = String::new();
+            while let Some(piece) = pieces.next().await {
+                match piece {
+                    Ok(text) => {
+                        output_tokens += 1;
+                        // Stop at the end-of-turn marker of the chat template
+                        let start = generated.len();
+                        generated.push_str(&text);
+                        let end = chat_template.trim_output(&generated).len();
+                        let stopped = end < generated.len();
+                        let text = generated[start.min(end)..end].to_string();
+                        if !tools.is_empty() {
+                            buffered.push_str(&text);
+                        } else if !text.is_empty()
+                            && tx.send(Ok(PromptStreamChunk::Delta(text))).await.is_err()
+                        {
+                            return;
+                        }
+                        if stopped {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        let _ = tx.send(Err(LLMError::ProcessingFailed(e.to_string()))).await;
+                        return;
+                    }
+                }
+            }
+            let mut finish_reason = if output_tokens >= max_tokens { "length" } else { "stop" };
+            if let Some(tool_calls) = parse_tool_calls(&buffered, &tools) {
+                finish_reason = "tool_calls";
+                for call in tool_calls {
+                    if tx.send(Ok(PromptStreamChunk::ToolCall(call))).await.is_err() {
+                        return;
+                    }
+                }
+            } else if !buffered.is_empty()
+                && tx.send(Ok(PromptStreamChunk::Delta(buffered))).await.is_err()
+            {
+                return;
+            }
+            let _ = tx.send(Ok(PromptStreamChunk::Done {
+                tokens_used: input_tokens + output_tokens,
+                finish_reason: finish_reason.to_string(),
+                safety: Vec::new(),
+            })).await;
+        });
+        
+        Ok(Box::pin(tokio_stream::wrappers::ReceiverStream::new(rx)))
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,183 @@
+//! Text embeddings: one `EmbeddingProvider` per embedding model, served through `EmbeddingService`
+//! so every Edda service (Freki RAG, response cache, HTTP API) gets vectors from the same model.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+
+use crate::cache::PromptEmbedder;
+use crate::llm::LLMError;
+use crate::model::{ModelInfo, ModelRegistryTrait, ModelType};
+use crate::prompt::TokenCounter;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddingResponse {
+    /// One vector per input, in input order
+    pub embeddings: Vec<Vec<f32>>,
+    /// Tokens billed by the provider; 0 if the provider does not report usage
+    pub tokens_used: u32,
+}
+
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    fn model_name(&self) -> &str;
+
+    async fn embed(&self, inputs: &[String]) -> Result<EmbeddingResponse, LLMError>;
+}
+
+/// Vectors returned by `EmbeddingService::embed`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Embeddings {
+    /// Id of the model that produced the vectors
+    pub model: String,
+    pub dimension: u32,
+    pub vectors: Vec<Vec<f32>>,
+    pub tokens_used: u32,
+}
+
+/// Embedding models registered in the model registry (type `Embedding`, with dimension and
+/// max input tokens as `context_window`) together with their providers.
+pub struct EmbeddingService {
+    registry: Arc<dyn ModelRegistryTrait>,
+    providers: RwLock<HashMap<String, Arc<dyn EmbeddingProvider>>>,
+    default_model: RwLock<Option<String>>,
+    token_counter: TokenCounter,
+}
+
+impl EmbeddingService {
+    pub fn new(registry: Arc<dyn ModelRegistryTrait>) -> Self {
+        Self {
+            registry,
+            providers: RwLock::new(HashMap::new()),
+            default_model: RwLock::new(None),
+            token_counter: TokenCounter::default(),
+        }
+    }
+
+    /// Uses the given token counter (model tokenizers) for the max-input check.
+    pub fn with_token_counter(mut self, token_counter: TokenCounter) -> Self {
+        self.token_counter = token_counter;
+        self
+    }
+
+    /// Registers the model in the registry and serves it with `provider`; the first registered
+    /// model becomes the default.
+    pub async fn register(&self, model: ModelInfo, provider: Arc<dyn EmbeddingProvider>) -> Result<(), LLMError> {
+        if model.model_type != ModelType::Embedding {
+            return Err(LLMError::InvalidInput(format!("{} is not an embedding model", model.id)));
+        }
+        if model.embedding_dimension.unwrap_or(0) == 0 {
+            return Err(LLMError::InvalidInput(format!("{} has no embedding dimension", model.id)));
+        }
+        let id = model.id.clone();
+        self.registry
+            .register(model)
+            .await
+            .map_err(|e| LLMError::ProcessingFailed(e.to_string()))?;
+        self.providers.write().await.insert(id.clone(), provider);
+        self.default_model.write().await.get_or_insert(id);
+        Ok(())
+    }
+
+    /// Model used when the caller names none.
+    pub async fn set_default_model(&self, model_id: &str) -> Result<(), LLMError> {
+        if !self.providers.read().await.contains_key(model_id) {
+            return Err(LLMError::ModelNotAvailable(model_id.to_string()));
+        }
+        *self.default_model.write().await = Some(model_id.to_string());
+        Ok(())
+    }
+
+    pub async fn default_model(&self) -> Option<String> {
+        self.default_model.read().await.clone()
+    }
+
+    pub fn registry(&self) -> &Arc<dyn ModelRegistryTrait> {
+        &self.registry
+    }
+
+    /// Embeds `inputs` with `model` (or the default model). Inputs longer than the model's max
+    /// input are rejected instead of silently truncated; the vector dimension is checked against
+    /// the registered one so callers can rely on it.
+    pub async fn embed(&self, model: Option<&str>, inputs: &[String]) -> Result<Embeddings, LLMError> {
+        if inputs.is_empty() {
+            return Err(LLMError::InvalidInput("no input to embed".to_string()));
+        }
+        let model_id = match model.filter(|m| !m.is_empty()) {
+            Some(model) => model.to_string(),
+            None => self
+                .default_model()
+                .await
+                .ok_or_else(|| LLMError::ModelNotAvailable("no embedding model registered".to_string()))?,
+        };
+        let info = self
+            .registry
+            .get_by_id(&model_id)
+            .await
+            .map_err(|e| LLMError::ProcessingFailed(e.to_string()))?
+            .filter(|m| m.model_type == ModelType::Embedding)
+            .ok_or_else(|| LLMError::ModelNotAvailable(format!("{} is not a registered embedding model", model_id)))?;
+        let provider = self
+            .providers
+            .read()
+            .await
+            .get(&model_id)
+            .cloned()
+            .ok_or_else(|| LLMError::ModelNotAvailable(model_id.clone()))?;
+
+        let mut input_tokens = 0;
+        for (index, input) in inputs.iter().enumerate() {
+            let tokens = self.token_counter.count_for_model(input, &model_id);
+            if let Some(max) = info.context_window.filter(|max| tokens > *max) {
+                return Err(LLMError::InvalidInput(format!(
+                    "input {} has {} tokens, {} accepts at most {}",
+                    index, tokens, model_id, max
+                )));
+            }
+            input_tokens += tokens;
+        }
+
+        let response = provider.embed(inputs).await?;
+        let dimension = info.embedding_dimension.unwrap_or(0);
+        if response.embeddings.len() != inputs.len() {
+            return Err(LLMError::ProcessingFailed(format!(
+                "{} returned {} embeddings for {} inputs",
+                model_id,
+                response.embeddings.len(),
+                inputs.len()
+            )));
+        }
+        if let Some(vector) = response.embeddings.iter().find(|v| v.len() != dimension as usize) {
+            return Err(LLMError::ProcessingFailed(format!(
+                "{} returned a {}-dimensional vector, expected {}",
+                model_id,
+                vector.len(),
+                dimension
+            )));
+        }
+
+        Ok(Embeddings {
+            model: model_id,
+            dimension,
+            vectors: response.embeddings,
+            tokens_used: if response.tokens_used > 0 { response.tokens_used } else { input_tokens },
+        })
+    }
+}
+
+/// The response cache compares prompts with the default embedding model.
+#[async_trait]
+impl PromptEmbedder for EmbeddingService {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>, String> {
+        let embeddings = EmbeddingService::embed(self, None, &[text.to_string()])
+            .await
+            .map_err(|e| e.to_string())?;
+        embeddings
+            .vectors
+            .into_iter()
+            .next()
+            .ok_or_else(|| "no embedding returned".to_string())
+    }
+}
@@ -1,11 +1,23 @@
 use std::sync::Arc;
-use crate::llm::{LLMProvider, LLMError, PromptRequest, PromptResponse};
-use crate::model::{ModelInfo, ModelRegistryTrait};
+use std::time::Duration;
+use futures::StreamExt;
+use crate::llm::{LLMProvider, LLMError, PromptRequest, PromptResponse, PromptStream, PromptStreamChunk};
+use crate::model::{ModelInfo, ModelRegistryTrait, ModelType};
 use crate::selection::{ModelSelector, SelectionOptions, EfficiencyInput, EfficiencyScoreCalculator, EfficiencyWeights};
-use crate::performance::PerformanceTracker;
+use crate::performance::{PerformanceTracker, PerformanceWindow};
 use crate::fallback::{FallbackManager, CloudLimitDetector};
+use crate::error_handling::{CircuitBreakerRegistry, RetryManager};
 use crate::llm::ProviderFactory;
-use crate::cost::{BudgetTracker, CostCalculator};
+use crate::cost::{BudgetTracker, CostCalculator, UsageRecord};
+use crate::cache::{CacheKey, ResponseCache};
+use crate::evaluation::EvaluationStore;
+use crate::safety::SafetyPipeline;
+use crate::vision::{VisionError, VisionProvider, VisionRequest, VisionResponse};
+use crate::llm::structured::{enforce_response_format, DEFAULT_MAX_REPAIR_ATTEMPTS};
+
+/// Failover attempts after the first model failed (next-best candidate per attempt)
+const DEFAULT_FAILOVER_RETRIES: u32 = 2;
+const DEFAULT_FAILOVER_DELAY_MS: u64 = 50;
 
 pub struct GeriEngine {
     registry: Arc<dyn ModelRegistryTrait>,
@@ -14,6 +26,13 @@ pub struct GeriEngine {
     budget_tracker: Arc<BudgetTracker>,
     cost_calculator: CostCalculator,
     selector: ModelSelector,
+    circuit_breaker: Arc<CircuitBreakerRegistry>,
+    retry_manager: RetryManager,
+    request_timeout: Option<Duration>,
+    response_cache: Option<Arc<ResponseCache>>,
+    max_repair_attempts: u32,
+    evaluations: Option<Arc<EvaluationStore>>,
+    safety: SafetyPipeline,
 }
 
 impl GeriEngine {
@@ -33,19 +52,436 @@ impl GeriEngine {
             budget_tracker,
             cost_calculator: CostCalculator::default(),
             selector,
+            circuit_breaker: Arc::new(CircuitBreakerRegistry::default()),
+            retry_manager: RetryManager::new(DEFAULT_FAILOVER_RETRIES, DEFAULT_FAILOVER_DELAY_MS),
+            request_timeout: None,
+            response_cache: None,
+            max_repair_attempts: DEFAULT_MAX_REPAIR_ATTEMPTS,
+            evaluations: None,
+            safety: SafetyPipeline::default(),
         }
     }
 
-    pub async fn process(&self, request: PromptRequest, mut options: SelectionOptions) -> Result<PromptResponse, LLMError> {
+    /// Uses the given cost calculator (e.g. with model tokenizers for input-token counting).
+    pub fn with_cost_calculator(mut self, cost_calculator: CostCalculator) -> Self {
+        self.cost_calculator = cost_calculator;
+        self
+    }
+
+    /// Uses the given circuit breakers (e.g. with configured thresholds or shared for monitoring).
+    pub fn with_circuit_breaker(mut self, circuit_breaker: Arc<CircuitBreakerRegistry>) -> Self {
+        self.circuit_breaker = circuit_breaker;
+        self
+    }
+
+    /// Number of failover attempts and the backoff between them.
+    pub fn with_retry_manager(mut self, retry_manager: RetryManager) -> Self {
+        self.retry_manager = retry_manager;
+        self
+    }
+
+    /// Treats providers that do not answer (or start streaming) within `timeout` as failed.
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Answers repeated requests from the cache (keyed per model and user, see `PromptRequest::no_cache`).
+    pub fn with_response_cache(mut self, response_cache: Arc<ResponseCache>) -> Self {
+        self.response_cache = Some(response_cache);
+        self
+    }
+
+    /// How often an answer that does not match the request's `response_format` is sent back for repair.
+    pub fn with_max_repair_attempts(mut self, max_repair_attempts: u32) -> Self {
+        self.max_repair_attempts = max_repair_attempts;
+        self
+    }
+
+    /// Weighs in each model's quality score from stored evaluation runs when selecting candidates.
+    pub fn with_evaluation_store(mut self, evaluations: Arc<EvaluationStore>) -> Self {
+        self.evaluations = Some(evaluations);
+        self
+    }
+
+    /// Runs every request and answer through the safety filters (per selected model, so secrets
+    /// are only redacted for cloud models); the decisions are returned in `PromptResponse::safety`.
+    pub fn with_safety_pipeline(mut self, safety: SafetyPipeline) -> Self {
+        self.safety = safety;
+        self
+    }
+
+    pub fn circuit_breaker(&self) -> &Arc<CircuitBreakerRegistry> {
+        &self.circuit_breaker
+    }
+
+    /// Processes the request on the best model; if the provider errors or times out, the
+    /// next-best candidate is tried (skipping providers whose circuit breaker is open).
+    /// Once the request's user or device is over budget, only local models are used.
+    /// With a `response_format` the answer is validated and repaired on the same model; if it
+    /// still does not match, the next candidate is tried. Request and answer pass the safety
+    /// pipeline; a request blocked by a filter fails without trying other models.
+    pub async fn process(&self, request: PromptRequest, options: SelectionOptions) -> Result<PromptResponse, LLMError> {
+        self.process_with_model(request, options).await.map(|(response, _)| response)
+    }
+
+    /// Like `process`; also returns the model that answered.
+    pub async fn process_with_model(&self, request: PromptRequest, options: SelectionOptions) -> Result<(PromptResponse, ModelInfo), LLMError> {
+        let mut failover = self.failover(&request, options).await?;
+        let mut attempt = 0;
+        loop {
+            let (selected_model, provider) = failover.next(self).await?;
+            let (cache_key, cached) = self.cache_lookup(&request, &selected_model).await;
+            if let Some(cached) = cached {
+                return Ok((cached, selected_model));
+            }
+            let (filtered, decisions) = self.filter_request(&request, &selected_model).await?;
+
+            self.performance_tracker.record_request_start(&selected_model.provider, &selected_model.id).await;
+            let start_time = std::time::Instant::now();
+            let input_tokens = self.cost_calculator.input_tokens(&filtered, &selected_model);
+
+            let response = self.with_timeout(&selected_model, async {
+                let response = provider.process_prompt(filtered.clone()).await?;
+                enforce_response_format(provider.as_ref(), &filtered, response, self.max_repair_attempts).await
+            }).await;
+
+            let latency_ms = start_time.elapsed().as_millis() as u64;
+
+            match response {
+                Ok(resp) => {
+                    Self::record_success(
+                        &self.performance_tracker, &self.budget_tracker, &self.cost_calculator, &self.circuit_breaker,
+                        &selected_model, &request, latency_ms, input_tokens, resp.tokens_used,
+                    ).await;
+                    let resp = self.safety.filter_response(&filtered, resp, decisions, &selected_model.id);
+                    if let (Some(cache), Some(key)) = (&self.response_cache, &cache_key) {
+                        cache.insert(key, &resp).await;
+                    }
+                    return Ok((resp, selected_model));
+                }
+                Err(e) => {
+                    Self::record_failure(&self.performance_tracker, &self.circuit_breaker, &selected_model, &e).await;
+                    match self.before_failover(attempt, &selected_model, &e).await {
+                        Some(next) => {
+                            attempt = next;
+                            failover.last_error = Some(e);
+                        }
+                        None => return Err(e),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Streaming variant of `process`: same model selection, budget and failover rules (failover
+    /// only before the first frame); performance and cost are recorded when the provider's final
+    /// `Done` frame passes through. Requests with a `response_format`, and requests with untrusted
+    /// content while safety filters are active, are answered in one piece, as the answer has to be
+    /// validated before it is sent. The safety decisions arrive in the final `Done` frame.
+    pub async fn process_stream(&self, request: PromptRequest, options: SelectionOptions) -> Result<PromptStream, LLMError> {
+        self.process_stream_with_model(request, options).await.map(|(stream, _)| stream)
+    }
+
+    /// Like `process_stream`; also returns the model that answers.
+    pub async fn process_stream_with_model(&self, request: PromptRequest, options: SelectionOptions) -> Result<(PromptStream, ModelInfo), LLMError> {
+        if request.response_format.is_some() || self.safety.checks_response(&request) {
+            let (response, model) = self.process_with_model(request, options).await?;
+            return Ok((Self::replay(response), model));
+        }
+        let mut failover = self.failover(&request, options).await?;
+        let mut attempt = 0;
+        let (selected_model, mut stream, start_time, input_tokens, cache_key, mut decisions) = loop {
+            let (selected_model, provider) = failover.next(self).await?;
+            let (cache_key, cached) = self.cache_lookup(&request, &selected_model).await;
+            if let Some(cached) = cached {
+                return Ok((Self::replay(cached), selected_model));
+            }
+            let (filtered, decisions) = self.filter_request(&request, &selected_model).await?;
+
+            self.performance_tracker.record_request_start(&selected_model.provider, &selected_model.id).await;
+            let start_time = std::time::Instant::now();
+            let input_tokens = self.cost_calculator.input_tokens(&filtered, &selected_model);
+
+            match self.with_timeout(&selected_model, provider.stream_prompt(filtered)).await {
+                Ok(stream) => break (selected_model, stream, start_time, input_tokens, cache_key, decisions),
+                Err(e) => {
+                    Self::record_failure(&self.performance_tracker, &self.circuit_breaker, &selected_model, &e).await;
+                    match self.before_failover(attempt, &selected_model, &e).await {
+                        Some(next) => {
+                            attempt = next;
+                            failover.last_error = Some(e);
+                        }
+                        None => return Err(e),
+                    }
+                }
+            }
+        };
+
+        let performance_tracker = self.performance_tracker.clone();
+        let budget_tracker = self.budget_tracker.clone();
+        let cost_calculator = self.cost_calculator.clone();
+        let circuit_breaker = self.circuit_breaker.clone();
+        let response_cache = self.response_cache.clone();
+        let model = selected_model.clone();
+        let (tx, rx) = tokio::sync::mpsc::channel(64);
+        tokio::spawn(async move {
+            let mut text = String::new();
+            let mut has_tool_calls = false;
+            let mut finished = false;
+            while let Some(mut chunk) = stream.next().await {
+                match &mut chunk {
+                    Ok(PromptStreamChunk::Done { tokens_used, safety, .. }) => {
+                        finished = true;
+                        // Request-stage decisions travel in the final frame, like `PromptResponse::safety`
+                        safety.splice(0..0, std::mem::take(&mut decisions));
+                        let latency_ms = start_time.elapsed().as_millis() as u64;
+                        Self::record_success(
+                            &performance_tracker, &budget_tracker, &cost_calculator, &circuit_breaker,
+                            &selected_model, &request, latency_ms, input_tokens, *tokens_used,
+                        ).await;
+                        if let (Some(cache), Some(key), false) = (&response_cache, &cache_key, has_tool_calls) {
+                            let response = PromptResponse {
+                                text: std::mem::take(&mut text),
+                                tokens_used: *tokens_used,
+                                tool_calls: Vec::new(),
+                                structured: None,
+                                safety: Vec::new(),
+                            };
+                            cache.insert(key, &response).await;
+                        }
+                    }
+                    Ok(PromptStreamChunk::Delta(delta)) => text.push_str(delta),
+                    Ok(PromptStreamChunk::ToolCall(_)) => has_tool_calls = true,
+                    Err(e) => {
+                        finished = true;
+                        Self::record_failure(&performance_tracker, &circuit_breaker, &selected_model, e).await;
+                    }
+                }
+                if tx.send(chunk).await.is_err() {
+                    performance_tracker.record_request_failure(
+                        &selected_model.provider, &selected_model.id, "stream cancelled by caller"
+                    ).await;
+                    // Not the provider's fault: free a half-open probe slot without a verdict
+                    circuit_breaker.release(&selected_model.provider, &selected_model.id).await;
+                    return;
+                }
+            }
+            if !finished {
+                // A stream cut off before `Done` counts as a failure of the provider
+                let error = LLMError::ProcessingFailed("stream ended without a Done frame".to_string());
+                Self::record_failure(&performance_tracker, &circuit_breaker, &selected_model, &error).await;
+                let _ = tx.send(Err(error)).await;
+            }
+        });
+
+        Ok((Box::pin(tokio_stream::wrappers::ReceiverStream::new(rx)), model))
+    }
+
+    /// Model a request with these options is sent to first (without reserving it), e.g. to fit
+    /// the conversation into its context window beforehand; `None` if no model is available.
+    pub async fn first_choice(&self, request: &PromptRequest, options: &SelectionOptions) -> Option<ModelInfo> {
+        let failover = self.failover(request, options.clone()).await.ok()?;
+        self.selector.select(&failover.candidates, &failover.options)
+    }
+
+    /// Analyzes the image on the best vision model (`ModelType::Vision` with a provider registered
+    /// via `ProviderFactory::register_vision`), failing over like `process`. Privacy-sensitive
+    /// requests only ever reach local models, as do all requests once the budget is exceeded.
+    /// Returns the response and the model that produced it.
+    pub async fn process_vision(&self, request: VisionRequest, mut options: SelectionOptions) -> Result<(VisionResponse, ModelInfo), LLMError> {
+        let models = self.registry.list_all().await
+            .map_err(|e| LLMError::ProcessingFailed(e.to_string()))?;
+        let mut candidates = self.prepare_candidates(&models, ModelType::Vision).await;
+        if request.privacy_sensitive || self.budget_tracker.is_over_limit().await {
+            candidates.retain(|(m, _)| m.is_local);
+            if let Some(preferred) = &options.user_preferred_model_id {
+                if !candidates.iter().any(|(m, _)| &m.id == preferred) {
+                    tracing::info!("Ignoring preferred vision model {}: only local models are allowed", preferred);
+                    options.user_preferred_model_id = None;
+                }
+            }
+        }
+
+        let mut attempt = 0;
+        let mut last_error = None;
+        loop {
+            let Some(selected_model) = self.selector.select(&candidates, &options) else {
+                return Err(last_error.unwrap_or_else(|| {
+                    LLMError::ModelNotAvailable(if request.privacy_sensitive {
+                        "No local vision model available for privacy-sensitive request".to_string()
+                    } else {
+                        "No suitable vision model found".to_string()
+                    })
+                }));
+            };
+            candidates.retain(|(m, _)| m.id != selected_model.id);
+            options.user_preferred_model_id = None;
+
+            let Some(provider) = self.factory.get_vision(&selected_model.id).await else {
+                last_error.get_or_insert(LLMError::ModelNotAvailable(format!("Vision provider for model {} not registered", selected_model.id)));
+                continue;
+            };
+            if !self.circuit_breaker.allow(&selected_model.provider, &selected_model.id).await {
+                last_error.get_or_insert(LLMError::ModelNotAvailable(format!("Circuit breaker open for model {}", selected_model.id)));
+                continue;
+            }
+
+            self.performance_tracker.record_request_start(&selected_model.provider, &selected_model.id).await;
+            let start_time = std::time::Instant::now();
+            let response = self.with_timeout(&selected_model, async {
+                provider.process(request.clone()).await.map_err(|e| LLMError::ProcessingFailed(e.to_string()))
+            }).await;
+            match response {
+                Ok(response) => {
+                    let latency_ms = start_time.elapsed().as_millis() as u64;
+                    self.performance_tracker.record_request_success(&selected_model.provider, &selected_model.id, latency_ms, 0).await;
+                    self.circuit_breaker.record_success(&selected_model.provider, &selected_model.id).await;
+                    return Ok((response, selected_model));
+                }
+                Err(e) => {
+                    Self::record_failure(&self.performance_tracker, &self.circuit_breaker, &selected_model, &e).await;
+                    match self.before_failover(attempt, &selected_model, &e).await {
+                        Some(next) => {
+                            attempt = next;
+                            last_error = Some(e);
+                        }
+                        None => return Err(e),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Cache key for the request on `model` and the cached response, if any. A hit frees the
+    /// breaker slot reserved by the failover, as the provider is not called.
+    async fn cache_lookup(&self, request: &PromptRequest, model: &ModelInfo) -> (Option<CacheKey>, Option<PromptResponse>) {
+        let Some(cache) = &self.response_cache else {
+            return (None, None);
+        };
+        let Some(key) = cache.key(request, &model.id).await else {
+            return (None, None);
+        };
+        let cached = cache.get(&key).await;
+        if cached.is_some() {
+            self.circuit_breaker.release(&model.provider, &model.id).await;
+        }
+        (Some(key), cached)
+    }
+
+    /// Request as sent to `model` after the safety filters; a blocked request frees the breaker
+    /// slot reserved by the failover, as the provider is not called.
+    async fn filter_request(&self, request: &PromptRequest, model: &ModelInfo) -> Result<(PromptRequest, Vec<crate::safety::FilterDecision>), LLMError> {
+        match self.safety.filter_request(request, model) {
+            Ok(filtered) => Ok(filtered),
+            Err(e) => {
+                self.circuit_breaker.release(&model.provider, &model.id).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Replays a complete (e.g. cached) response as a stream.
+    fn replay(response: PromptResponse) -> PromptStream {
+        let mut chunks = Vec::new();
+        if !response.text.is_empty() {
+            chunks.push(Ok(PromptStreamChunk::Delta(response.text)));
+        }
+        let finish_reason = if response.tool_calls.is_empty() { "stop" } else { "tool_calls" };
+        chunks.extend(response.tool_calls.into_iter().map(|call| Ok(PromptStreamChunk::ToolCall(call))));
+        chunks.push(Ok(PromptStreamChunk::Done {
+            tokens_used: response.tokens_used,
+            finish_reason: finish_reason.to_string(),
+            safety: response.safety,
+        }));
+        Box::pin(futures::stream::iter(chunks))
+    }
+
+    async fn with_timeout<T>(
+        &self,
+        model: &ModelInfo,
+        call: impl std::future::Future<Output = Result<T, LLMError>>,
+    ) -> Result<T, LLMError> {
+        let Some(timeout) = self.request_timeout else {
+            return call.await;
+        };
+        tokio::time::timeout(timeout, call).await.unwrap_or_else(|_| {
+            Err(LLMError::Timeout(format!("{} did not respond within {} ms", model.id, timeout.as_millis())))
+        })
+    }
+
+    /// Waits before the next failover attempt; `None` once the retries are used up.
+    async fn before_failover(&self, attempt: u32, failed: &ModelInfo, error: &LLMError) -> Option<u32> {
+        if !self.retry_manager.should_retry(attempt) {
+            return None;
+        }
+        tracing::warn!("Model {} failed ({}), failing over to next candidate", failed.id, error);
+        tokio::time::sleep(self.retry_manager.delay_for_attempt(attempt)).await;
+        Some(attempt + 1)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn record_success(
+        performance_tracker: &PerformanceTracker,
+        budget_tracker: &BudgetTracker,
+        cost_calculator: &CostCalculator,
+        circuit_breaker: &CircuitBreakerRegistry,
+        model: &ModelInfo,
+        request: &PromptRequest,
+        latency_ms: u64,
+        input_tokens: u32,
+        tokens_used: u32,
+    ) {
+        // Track performance
+        performance_tracker.record_request_success(
+            &model.provider, &model.id, latency_ms, tokens_used as u64
+        ).await;
+        circuit_breaker.record_success(&model.provider, &model.id).await;
+
+        // Track cost: providers report total tokens, the input share is counted locally
+        let input_tokens = input_tokens.min(tokens_used);
+        let output_tokens = tokens_used - input_tokens;
+        let cost = cost_calculator.total_cost(input_tokens, output_tokens, &model.provider, &model.id);
+        budget_tracker.record(UsageRecord {
+            timestamp: chrono::Utc::now(),
+            user_id: request.user_id.clone(),
+            device_id: request.device_id.clone(),
+            provider: model.provider.clone(),
+            model: model.id.clone(),
+            input_tokens,
+            output_tokens,
+            cost,
+        }).await;
+    }
+
+    /// Records the failure and feeds the provider's recent failure rate into its circuit breaker.
+    async fn record_failure(
+        performance_tracker: &PerformanceTracker,
+        circuit_breaker: &CircuitBreakerRegistry,
+        model: &ModelInfo,
+        error: &LLMError,
+    ) {
+        performance_tracker.record_request_failure(&model.provider, &model.id, &error.to_string()).await;
+        let recent = performance_tracker
+            .get_windowed_metrics(&model.provider, &model.id, PerformanceWindow::Last1Min)
+            .await;
+        circuit_breaker.record_failure(&model.provider, &model.id, recent.as_ref()).await;
+    }
+
+    /// Candidates for a request, honouring the global budget and those of the request's user and device.
+    async fn failover(&self, request: &PromptRequest, options: SelectionOptions) -> Result<Failover, LLMError> {
         // 1. Get all candidates
         let models = self.registry.list_all().await
             .map_err(|e| LLMError::ProcessingFailed(e.to_string()))?;
 
         // 2. Initial selection
-        let mut candidates = self.prepare_candidates(&models).await;
+        let mut candidates = self.prepare_candidates(&models, ModelType::Llm).await;
         
         // Check budget and adjust selection if needed
-        let is_over_limit = self.budget_tracker.is_over_limit().await;
+        let is_over_limit = self
+            .budget_tracker
+            .is_over_limit_for(request.user_id.as_deref(), request.device_id.as_deref())
+            .await;
         
         if is_over_limit && options.user_preferred_model_id.is_none() {
             // Force local models if budget is exceeded
@@ -53,60 +489,19 @@ impl GeriEngine {
             candidates.retain(|(m, _)| m.is_local);
         }
 
-        let mut selected_model = self.selector.select(&candidates, &options)
-            .ok_or_else(|| LLMError::ModelNotAvailable("No suitable model found (check budget/availability)".to_string()))?;
-
-        // 3. Fallback logic (if selected is cloud but we're over budget - double check)
-        if !selected_model.is_local && is_over_limit {
-             let local_candidates: Vec<_> = candidates.iter().filter(|(m, _)| m.is_local).cloned().collect();
-             if let Some(fallback) = self.selector.select(&local_candidates, &SelectionOptions::default()) {
-                 tracing::info!("Falling back to local model: {}", fallback.id);
-                 selected_model = fallback;
-             } else {
-                 return Err(LLMError::ModelNotAvailable("Budget exceeded and no local fallback available".to_string()));
-             }
-        }
-
-        // 4. Execution
-        let provider = self.factory.get(&selected_model.id).await
-            .ok_or_else(|| LLMError::ModelNotAvailable(format!("Provider for model {} not registered", selected_model.id)))?;
-        
-        self.performance_tracker.record_request_start(&selected_model.provider, &selected_model.id).await;
-        let start_time = std::time::Instant::now();
-        
-        let response = provider.process_prompt(request).await;
-        
-        let latency_ms = start_time.elapsed().as_millis() as u64;
-
-        match &response {
-            Ok(resp) => {
-                // Track performance
-                self.performance_tracker.record_request_success(
-                    &selected_model.provider, &selected_model.id, latency_ms, resp.tokens_used as u64
-                ).await;
-                
-                // Track cost
-                let cost = self.cost_calculator.total_cost(
-                    0, // We need to estimate input tokens or get them from provider
-                    resp.tokens_used, 
-                    &selected_model.provider, 
-                    &selected_model.id
-                );
-                self.budget_tracker.add_usage(cost).await;
-            }
-            Err(e) => {
-                self.performance_tracker.record_request_failure(
-                    &selected_model.provider, &selected_model.id, &e.to_string()
-                ).await;
-            }
-        }
-
-        response
+        Ok(Failover {
+            candidates,
+            options,
+            is_over_limit,
+            last_error: None,
+        })
     }
 
-    async fn prepare_candidates(&self, models: &[ModelInfo]) -> Vec<(ModelInfo, EfficiencyInput)> {
+    /// Registry models of `model_type` with their efficiency inputs (embedding and vision models
+    /// share the registry but cannot answer prompts).
+    async fn prepare_candidates(&self, models: &[ModelInfo], model_type: ModelType) -> Vec<(ModelInfo, EfficiencyInput)> {
         let mut candidates = Vec::new();
-        for model in models {
+        for model in models.iter().filter(|m| m.model_type == model_type) {
             let metrics = self.performance_tracker.get_metrics(&model.provider, &model.id).await;
             
             let input = EfficiencyInput {
@@ -122,9 +517,77 @@ impl GeriEngine {
                 is_local: model.is_local,
                 cost_per_token: model.cost_per_token_input,
                 max_cost_per_token: 0.0001,
+                quality_score: self.evaluations.as_ref().and_then(|store| store.quality_score(&model.id)),
             };
             candidates.push((model.clone(), input));
         }
         candidates
     }
 }
+
+/// Lets the engine serve as vision backend (e.g. for `VisionFrameAnalyzer`); the model is selected
+/// per request by `process_vision`.
+impl VisionProvider for GeriEngine {
+    fn model_name(&self) -> &str {
+        "auto"
+    }
+
+    fn process(&self, request: VisionRequest) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<VisionResponse, VisionError>> + Send + '_>> {
+        Box::pin(async move {
+            self.process_vision(request, SelectionOptions::default())
+                .await
+                .map(|(response, _)| response)
+                .map_err(|e| VisionError::ProcessingFailed(e.to_string()))
+        })
+    }
+}
+
+/// Remaining candidates of one request; hands out the next-best model on each failover.
+struct Failover {
+    candidates: Vec<(ModelInfo, EfficiencyInput)>,
+    options: SelectionOptions,
+    is_over_limit: bool,
+    /// Why the first failed or skipped candidate did not answer; reported if no candidate is left
+    last_error: Option<LLMError>,
+}
+
+impl Failover {
+    async fn next(&mut self, engine: &GeriEngine) -> Result<(ModelInfo, Arc<dyn LLMProvider>), LLMError> {
+        loop {
+            let mut selected_model = match engine.selector.select(&self.candidates, &self.options) {
+                Some(model) => model,
+                None => {
+                    return Err(self.last_error.take().unwrap_or_else(|| {
+                        LLMError::ModelNotAvailable("No suitable model found (check budget/availability)".to_string())
+                    }))
+                }
+            };
+
+            // Fallback logic (if selected is cloud but we're over budget - double check)
+            if !selected_model.is_local && self.is_over_limit {
+                let local_candidates: Vec<_> = self.candidates.iter().filter(|(m, _)| m.is_local).cloned().collect();
+                if let Some(fallback) = engine.selector.select(&local_candidates, &SelectionOptions::default()) {
+                    tracing::info!("Falling back to local model: {}", fallback.id);
+                    selected_model = fallback;
+                } else {
+                    return Err(LLMError::ModelNotAvailable("Budget exceeded and no local fallback available".to_string()));
+                }
+            }
+
+            // The preferred model only applies to the first pick; failover selects automatically
+            self.candidates.retain(|(m, _)| m.id != selected_model.id);
+            self.options.user_preferred_model_id = None;
+
+            let Some(provider) = engine.factory.get(&selected_model.id).await else {
+                self.last_error.get_or_insert(LLMError::ModelNotAvailable(format!("Provider for model {} not registered", selected_model.id)));
+                continue;
+            };
+            if !engine.circuit_breaker.allow(&selected_model.provider, &selected_model.id).await {
+                tracing::debug!("Circuit breaker open for {}, skipping", selected_model.id);
+                self.last_error.get_or_insert(LLMError::ModelNotAvailable(format!("Circuit breaker open for model {}", selected_model.id)));
+                continue;
+            }
+            return Ok((selected_model, provider));
+        }
+    }
+}
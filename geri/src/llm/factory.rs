@@ -4,21 +4,44 @@ use tokio::sync::RwLock;
 use crate::llm::{LLMProvider, LLMError};
 use crate::llm::openai::{OpenAILLMProvider, OpenAIConfig};
 use crate::llm::anthropic::{AnthropicLLMProvider, AnthropicConfig};
+use crate::llm::google::{GoogleLLMProvider, GoogleConfig};
 use crate::llm::llamacpp::{LlamaCppLLMProvider, LlamaCppClient, LlamaCppConfig};
 use crate::llm::bitnet::{BitNetLLMProvider, BitNetClient, BitNetConfig};
+use crate::keys::{LiveApiKey, LiveKeys};
+use crate::vision::VisionProvider;
 
 /// Factory and registry for LLM providers
 pub struct ProviderFactory {
     providers: Arc<RwLock<HashMap<String, Arc<dyn LLMProvider>>>>,
+    vision_providers: Arc<RwLock<HashMap<String, Arc<dyn VisionProvider>>>>,
+    live_keys: Option<LiveKeys>,
 }
 
 impl ProviderFactory {
     pub fn new() -> Self {
         Self {
             providers: Arc::new(RwLock::new(HashMap::new())),
+            vision_providers: Arc::new(RwLock::new(HashMap::new())),
+            live_keys: None,
         }
     }
 
+    /// Cloud providers created by the factory read their API key from `live_keys`, so rotated
+    /// keys apply to running clients without a restart
+    pub fn with_live_keys(mut self, live_keys: LiveKeys) -> Self {
+        self.live_keys = Some(live_keys);
+        self
+    }
+
+    /// Live key for a cloud provider; keys from the key store win over the configured key
+    fn live_key(&self, provider_id: &str, configured_key: &str) -> Option<LiveApiKey> {
+        let live_key = self.live_keys.as_ref()?.handle(provider_id);
+        if live_key.is_empty() && !configured_key.is_empty() {
+            live_key.set(configured_key);
+        }
+        Some(live_key)
+    }
+
     /// Register an existing provider instance
     pub async fn register(&self, model_id: &str, provider: Arc<dyn LLMProvider>) {
         let mut providers = self.providers.write().await;
@@ -31,16 +54,46 @@ impl ProviderFactory {
         providers.get(model_id).cloned()
     }
 
+    /// Register a vision provider for a `ModelType::Vision` model
+    pub async fn register_vision(&self, model_id: &str, provider: Arc<dyn VisionProvider>) {
+        let mut providers = self.vision_providers.write().await;
+        providers.insert(model_id.to_string(), provider);
+    }
+
+    /// Get a vision provider by model ID
+    pub async fn get_vision(&self, model_id: &str) -> Option<Arc<dyn VisionProvider>> {
+        let providers = self.vision_providers.read().await;
+        providers.get(model_id).cloned()
+    }
+
     /// Create and register an OpenAI provider
     pub async fn add_openai(&self, model_id: &str, config: OpenAIConfig) {
-        let provider = Arc::new(OpenAILLMProvider::new(config, model_id.to_string()));
-        self.register(model_id, provider).await;
+        let live_key = self.live_key("openai", &config.api_key);
+        let mut provider = OpenAILLMProvider::new(config, model_id.to_string());
+        if let Some(live_key) = live_key {
+            provider = provider.with_live_key(live_key);
+        }
+        self.register(model_id, Arc::new(provider)).await;
     }
 
     /// Create and register an Anthropic provider
     pub async fn add_anthropic(&self, model_id: &str, config: AnthropicConfig) {
-        let provider = Arc::new(AnthropicLLMProvider::new(config, model_id.to_string()));
-        self.register(model_id, provider).await;
+        let live_key = self.live_key("anthropic", &config.api_key);
+        let mut provider = AnthropicLLMProvider::new(config, model_id.to_string());
+        if let Some(live_key) = live_key {
+            provider = provider.with_live_key(live_key);
+        }
+        self.register(model_id, Arc::new(provider)).await;
+    }
+
+    /// Create and register a Google provider
+    pub async fn add_google(&self, model_id: &str, config: GoogleConfig) {
+        let live_key = self.live_key("google", &config.api_key);
+        let mut provider = GoogleLLMProvider::new(config, model_id.to_string());
+        if let Some(live_key) = live_key {
+            provider = provider.with_live_key(live_key);
+        }
+        self.register(model_id, Arc::new(provider)).await;
     }
 
     /// Create and register a llama.cpp provider
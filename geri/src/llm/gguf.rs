@@ -0,0 +1,118 @@
+//! Minimal GGUF metadata reader
+//!
+//! Reads the key/value header of a GGUF model file without loading tensors,
+//! e.g. to pick the chat template (`tokenizer.chat_template`) for llama.cpp models.
+
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek};
+use std::path::Path;
+
+const GGUF_MAGIC: &[u8; 4] = b"GGUF";
+
+/// Metadata key holding the Jinja chat template of the model
+pub const CHAT_TEMPLATE_KEY: &str = "tokenizer.chat_template";
+
+/// Read a string metadata value from a GGUF file
+///
+/// Returns `Ok(None)` if the file is not a GGUF file or the key is missing.
+pub fn read_string_metadata(path: &Path, key: &str) -> io::Result<Option<String>> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != GGUF_MAGIC {
+        return Ok(None);
+    }
+    let version = read_u32(&mut reader)?;
+    // GGUF v1 used 32-bit counts and string lengths, v2+ use 64-bit
+    let wide = version >= 2;
+    let _tensor_count = read_len(&mut reader, wide)?;
+    let kv_count = read_len(&mut reader, wide)?;
+
+    for _ in 0..kv_count {
+        let name = read_string(&mut reader, wide)?;
+        let value_type = read_u32(&mut reader)?;
+        if name == key && value_type == TYPE_STRING {
+            return Ok(Some(read_string(&mut reader, wide)?));
+        }
+        skip_value(&mut reader, value_type, wide)?;
+    }
+    Ok(None)
+}
+
+/// Read the Jinja chat template (`tokenizer.chat_template`) of a GGUF model
+pub fn read_chat_template(path: &Path) -> io::Result<Option<String>> {
+    read_string_metadata(path, CHAT_TEMPLATE_KEY)
+}
+
+const TYPE_STRING: u32 = 8;
+const TYPE_ARRAY: u32 = 9;
+
+fn scalar_size(value_type: u32) -> io::Result<u64> {
+    match value_type {
+        0 | 1 | 7 => Ok(1),   // u8, i8, bool
+        2 | 3 => Ok(2),       // u16, i16
+        4..=6 => Ok(4),       // u32, i32, f32
+        10..=12 => Ok(8),     // u64, i64, f64
+        other => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Unknown GGUF value type: {}", other),
+        )),
+    }
+}
+
+fn skip_value<R: Read + Seek>(reader: &mut R, value_type: u32, wide: bool) -> io::Result<()> {
+    match value_type {
+        TYPE_STRING => {
+            let len = read_len(reader, wide)?;
+            skip(reader, len)
+        }
+        TYPE_ARRAY => {
+            let item_type = read_u32(reader)?;
+            let count = read_len(reader, wide)?;
+            if item_type == TYPE_STRING || item_type == TYPE_ARRAY {
+                for _ in 0..count {
+                    skip_value(reader, item_type, wide)?;
+                }
+                Ok(())
+            } else {
+                let bytes = count
+                    .checked_mul(scalar_size(item_type)?)
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "GGUF array too large"))?;
+                skip(reader, bytes)
+            }
+        }
+        other => skip(reader, scalar_size(other)?),
+    }
+}
+
+fn skip<R: Seek>(reader: &mut R, bytes: u64) -> io::Result<()> {
+    let offset = i64::try_from(bytes).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "GGUF value too large"))?;
+    reader.seek(io::SeekFrom::Current(offset))?;
+    Ok(())
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_len<R: Read>(reader: &mut R, wide: bool) -> io::Result<u64> {
+    if !wide {
+        return read_u32(reader).map(u64::from);
+    }
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_string<R: Read>(reader: &mut R, wide: bool) -> io::Result<String> {
+    let len = read_len(reader, wide)?;
+    let mut buf = Vec::new();
+    reader.take(len).read_to_end(&mut buf)?;
+    if buf.len() as u64 != len {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Truncated GGUF string"));
+    }
+    String::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
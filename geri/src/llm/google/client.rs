@@ -1,7 +1,13 @@
 //! Google Gemini API Client
 
+use futures::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::pin::Pin;
+
+use crate::llm::messages::{ChatMessage, ChatRole};
+use crate::llm::structured::ResponseFormat;
+use crate::llm::tools::{ToolCall, ToolDefinition, ToolResult};
 
 #[derive(Debug, Clone)]
 pub struct GoogleConfig {
@@ -39,12 +45,43 @@ impl fmt::Display for GoogleError {
 
 impl std::error::Error for GoogleError {}
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Part {
-    #[serde(skip_serializing_if = "String::is_empty")]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub text: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub inline_data: Option<InlineData>,
+    #[serde(default, rename = "functionCall", skip_serializing_if = "Option::is_none")]
+    pub function_call: Option<FunctionCall>,
+    #[serde(default, rename = "functionResponse", skip_serializing_if = "Option::is_none")]
+    pub function_response: Option<FunctionResponse>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionCall {
+    pub name: String,
+    #[serde(default)]
+    pub args: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionResponse {
+    pub name: String,
+    pub response: serde_json::Value,
+}
+
+/// Entry of the request `tools` array
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoogleTool {
+    #[serde(rename = "functionDeclarations")]
+    pub function_declarations: Vec<FunctionDeclaration>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FunctionDeclaration {
+    pub name: String,
+    pub description: String,
+    pub parameters: serde_json::Value,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,33 +97,48 @@ pub struct Content {
     pub role: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GenerationConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_output_tokens: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f32>,
+    #[serde(rename = "responseMimeType", skip_serializing_if = "Option::is_none")]
+    pub response_mime_type: Option<String>,
+    #[serde(rename = "responseSchema", skip_serializing_if = "Option::is_none")]
+    pub response_schema: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GenerateContentRequest {
     pub contents: Vec<Content>,
+    #[serde(rename = "systemInstruction", skip_serializing_if = "Option::is_none")]
+    pub system_instruction: Option<Content>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub generation_config: Option<GenerationConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<GoogleTool>>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct GenerateContentResponse {
+    #[serde(default)]
     pub candidates: Vec<Candidate>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none", alias = "usageMetadata")]
     pub usage_metadata: Option<UsageMetadata>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Candidate {
     pub content: Content,
+    #[serde(default, rename = "finishReason")]
+    pub finish_reason: Option<String>,
 }
 
+/// Stream of partial responses returned by [`GoogleClient::stream_generate_content`]
+pub type GenerateContentStream =
+    Pin<Box<dyn Stream<Item = Result<GenerateContentResponse, GoogleError>> + Send>>;
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct UsageMetadata {
@@ -95,9 +147,37 @@ pub struct UsageMetadata {
     pub total_token_count: u32,
 }
 
+/// Request body of `models/{model}:batchEmbedContents`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchEmbedContentsRequest {
+    pub requests: Vec<EmbedContentRequest>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbedContentRequest {
+    /// `models/{model}`
+    pub model: String,
+    pub content: Content,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_dimensionality: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchEmbedContentsResponse {
+    #[serde(default)]
+    pub embeddings: Vec<ContentEmbedding>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContentEmbedding {
+    pub values: Vec<f32>,
+}
+
 pub struct GoogleClient {
     config: GoogleConfig,
     client: reqwest::Client,
+    live_key: Option<crate::keys::LiveApiKey>,
 }
 
 impl GoogleClient {
@@ -105,6 +185,21 @@ impl GoogleClient {
         Self {
             config,
             client: reqwest::Client::new(),
+            live_key: None,
+        }
+    }
+
+    /// Reads the API key from `live_key` on every request, so rotated keys apply without a restart
+    pub fn with_live_key(mut self, live_key: crate::keys::LiveApiKey) -> Self {
+        self.live_key = Some(live_key);
+        self
+    }
+
+    /// The current API key: the live key if set, otherwise the configured one
+    pub fn api_key(&self) -> String {
+        match &self.live_key {
+            Some(live_key) => live_key.get(),
+            None => self.config.api_key.clone(),
         }
     }
 
@@ -114,32 +209,165 @@ impl GoogleClient {
 
     pub fn build_generate_content_request(
         &self,
-        _model: &str,
+        model: &str,
         prompt: &str,
         context: Option<&str>,
         max_tokens: Option<u32>,
     ) -> GenerateContentRequest {
-        let text = if let Some(ctx) = context {
-            format!("Context: {}\n\nPrompt: {}", ctx, prompt)
-        } else {
-            prompt.to_string()
-        };
+        self.build_conversation_request(model, &[ChatMessage::user(prompt)], None, context, max_tokens)
+    }
+
+    /// Request for a multi-turn conversation: assistant turns become `model` contents, tool turns
+    /// `function` contents with `functionResponse` parts, system turns go to `systemInstruction`.
+    /// RAG context is prepended to the last user turn.
+    pub fn build_conversation_request(
+        &self,
+        _model: &str,
+        conversation: &[ChatMessage],
+        system_prompt: Option<&str>,
+        context: Option<&str>,
+        max_tokens: Option<u32>,
+    ) -> GenerateContentRequest {
+        let mut system_parts: Vec<Part> = system_prompt
+            .filter(|s| !s.is_empty())
+            .map(|s| Part { text: s.to_string(), ..Default::default() })
+            .into_iter()
+            .collect();
+        let last_user = conversation.iter().rposition(|m| m.role == ChatRole::User);
+
+        let mut contents: Vec<Content> = Vec::new();
+        for (i, message) in conversation.iter().enumerate() {
+            let (role, parts) = match message.role {
+                ChatRole::System => {
+                    system_parts.push(Part { text: message.content.clone(), ..Default::default() });
+                    continue;
+                }
+                ChatRole::User => {
+                    let text = match context {
+                        Some(ctx) if Some(i) == last_user => {
+                            format!("Context: {}\n\nPrompt: {}", ctx, message.content)
+                        }
+                        _ => message.content.clone(),
+                    };
+                    ("user", vec![Part { text, ..Default::default() }])
+                }
+                ChatRole::Assistant => {
+                    let mut parts = Vec::new();
+                    if !message.content.is_empty() {
+                        parts.push(Part { text: message.content.clone(), ..Default::default() });
+                    }
+                    parts.extend(message.tool_calls.iter().map(|call| Part {
+                        function_call: Some(FunctionCall {
+                            name: call.name.clone(),
+                            args: call.arguments.clone(),
+                        }),
+                        ..Default::default()
+                    }));
+                    ("model", parts)
+                }
+                ChatRole::Tool => (
+                    "function",
+                    vec![Part {
+                        function_response: Some(FunctionResponse {
+                            name: message.name.clone().unwrap_or_default(),
+                            response: serde_json::json!({ "result": message.content }),
+                        }),
+                        ..Default::default()
+                    }],
+                ),
+            };
+            // Consecutive turns of the same role are merged into one content
+            match contents.last_mut() {
+                Some(last) if last.role.as_deref() == Some(role) => last.parts.extend(parts),
+                _ => contents.push(Content {
+                    parts,
+                    role: Some(role.to_string()),
+                }),
+            }
+        }
 
         GenerateContentRequest {
-            contents: vec![Content {
-                parts: vec![Part {
-                    text,
-                    inline_data: None,
-                }],
-                role: Some("user".to_string()),
-            }],
+            contents,
+            system_instruction: if system_parts.is_empty() {
+                None
+            } else {
+                Some(Content { parts: system_parts, role: None })
+            },
             generation_config: Some(GenerationConfig {
                 max_output_tokens: max_tokens,
-                temperature: None,
+                ..Default::default()
             }),
+            tools: None,
         }
     }
 
+    /// Add function declarations and the previous tool-call round (model `functionCall`
+    /// parts followed by a `function` turn with the matching `functionResponse` parts)
+    pub fn apply_tools(
+        &self,
+        request: &mut GenerateContentRequest,
+        tools: &[ToolDefinition],
+        tool_calls: &[ToolCall],
+        tool_results: &[ToolResult],
+    ) {
+        if !tools.is_empty() {
+            request.tools = Some(vec![GoogleTool {
+                function_declarations: tools
+                    .iter()
+                    .map(|tool| FunctionDeclaration {
+                        name: tool.name.clone(),
+                        description: tool.description.clone(),
+                        parameters: tool.parameters.clone(),
+                    })
+                    .collect(),
+            }]);
+        }
+        if !tool_calls.is_empty() {
+            request.contents.push(Content {
+                parts: tool_calls
+                    .iter()
+                    .map(|call| Part {
+                        function_call: Some(FunctionCall {
+                            name: call.name.clone(),
+                            args: call.arguments.clone(),
+                        }),
+                        ..Default::default()
+                    })
+                    .collect(),
+                role: Some("model".to_string()),
+            });
+        }
+        if !tool_results.is_empty() {
+            request.contents.push(Content {
+                parts: tool_results
+                    .iter()
+                    .map(|result| {
+                        // Gemini expects a JSON object as function response
+                        let key = if result.is_error { "error" } else { "result" };
+                        Part {
+                            function_response: Some(FunctionResponse {
+                                name: result.name.clone(),
+                                response: serde_json::json!({ key: result.content }),
+                            }),
+                            ..Default::default()
+                        }
+                    })
+                    .collect(),
+                role: Some("function".to_string()),
+            });
+        }
+    }
+
+    /// Constrain the answer to the JSON Schema (`responseMimeType` + `responseSchema`)
+    pub fn apply_response_format(&self, request: &mut GenerateContentRequest, response_format: Option<&ResponseFormat>) {
+        let Some(format) = response_format else {
+            return;
+        };
+        let config = request.generation_config.get_or_insert_with(GenerationConfig::default);
+        config.response_mime_type = Some("application/json".to_string());
+        config.response_schema = Some(format.to_gemini_schema());
+    }
+
     pub fn build_vision_request(
         &self,
         _model: &str,
@@ -156,27 +384,29 @@ impl GoogleClient {
         if let Some(p) = prompt {
             parts.push(Part {
                 text: p.to_string(),
-                inline_data: None,
+                ..Default::default()
             });
         }
 
         parts.push(Part {
-            text: String::new(),
             inline_data: Some(InlineData {
                 mime_type: "image/jpeg".to_string(),
                 data: base64_image,
             }),
+            ..Default::default()
         });
 
         GenerateContentRequest {
+            system_instruction: None,
             contents: vec![Content {
                 parts,
                 role: Some("user".to_string()),
             }],
             generation_config: Some(GenerationConfig {
                 max_output_tokens: Some(300),
-                temperature: None,
+                ..Default::default()
             }),
+            tools: None,
         }
     }
 
@@ -194,7 +424,7 @@ impl GoogleClient {
         let response = self
             .client
             .post(&url)
-            .header("x-goog-api-key", &self.config.api_key)
+            .header("x-goog-api-key", self.api_key())
             .header("Content-Type", "application/json")
             .json(&request)
             .send()
@@ -220,4 +450,100 @@ impl GoogleClient {
 
         Ok(response_body)
     }
+
+    /// One embedding request per text, answered in input order
+    pub fn build_embed_request(&self, model: &str, texts: &[String], dimensions: Option<u32>) -> BatchEmbedContentsRequest {
+        BatchEmbedContentsRequest {
+            requests: texts
+                .iter()
+                .map(|text| EmbedContentRequest {
+                    model: format!("models/{}", model),
+                    content: Content {
+                        parts: vec![Part { text: text.clone(), ..Default::default() }],
+                        role: None,
+                    },
+                    output_dimensionality: dimensions,
+                })
+                .collect(),
+        }
+    }
+
+    pub async fn batch_embed_contents(
+        &self,
+        model: &str,
+        request: BatchEmbedContentsRequest,
+    ) -> Result<BatchEmbedContentsResponse, GoogleError> {
+        let url = format!("{}/models/{}:batchEmbedContents", self.config.base_url, model);
+
+        let response = self
+            .client
+            .post(&url)
+            .header("x-goog-api-key", self.api_key())
+            .header("Content-Type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| GoogleError::HttpError(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(GoogleError::ApiError {
+                code: status.as_u16(),
+                message: error_text,
+            });
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| GoogleError::ParseError(e.to_string()))
+    }
+
+    /// Stream a generation via `streamGenerateContent?alt=sse`; each event is a partial response
+    pub async fn stream_generate_content(
+        &self,
+        model: &str,
+        request: GenerateContentRequest,
+    ) -> Result<GenerateContentStream, GoogleError> {
+        let url = format!(
+            "{}/models/{}:streamGenerateContent?alt=sse",
+            self.config.base_url, model
+        );
+
+        let response = self
+            .client
+            .post(&url)
+            .header("x-goog-api-key", self.api_key())
+            .header("Content-Type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| GoogleError::HttpError(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(GoogleError::ApiError {
+                code: status.as_u16(),
+                message: error_text,
+            });
+        }
+
+        let chunks = crate::streaming::sse_events(response).map(|event| {
+            event
+                .map_err(|e| GoogleError::HttpError(e.to_string()))
+                .and_then(|data| {
+                    serde_json::from_str::<GenerateContentResponse>(&data)
+                        .map_err(|e| GoogleError::ParseError(e.to_string()))
+                })
+        });
+        Ok(Box::pin(chunks))
+    }
 }
@@ -0,0 +1,56 @@
+//! Google Gemini embeddings (`batchEmbedContents`) implementing EmbeddingProvider
+
+use async_trait::async_trait;
+
+use super::client::{GoogleClient, GoogleConfig};
+use crate::llm::{EmbeddingProvider, EmbeddingResponse, LLMError};
+
+pub struct GoogleEmbeddingProvider {
+    client: GoogleClient,
+    model_name: String,
+    dimensions: Option<u32>,
+}
+
+impl GoogleEmbeddingProvider {
+    pub fn new(config: GoogleConfig, model_name: String) -> Self {
+        Self {
+            client: GoogleClient::new(config),
+            model_name,
+            dimensions: None,
+        }
+    }
+
+    /// Requests shortened vectors (`outputDimensionality`)
+    pub fn with_dimensions(mut self, dimensions: u32) -> Self {
+        self.dimensions = Some(dimensions);
+        self
+    }
+
+    /// Uses the live API key, which key rotation swaps without a restart
+    pub fn with_live_key(mut self, live_key: crate::keys::LiveApiKey) -> Self {
+        self.client = self.client.with_live_key(live_key);
+        self
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for GoogleEmbeddingProvider {
+    fn model_name(&self) -> &str {
+        &self.model_name
+    }
+
+    async fn embed(&self, inputs: &[String]) -> Result<EmbeddingResponse, LLMError> {
+        let request = self.client.build_embed_request(&self.model_name, inputs, self.dimensions);
+        let response = self
+            .client
+            .batch_embed_contents(&self.model_name, request)
+            .await
+            .map_err(|e| LLMError::ProcessingFailed(e.to_string()))?;
+
+        // Gemini reports no token usage for embeddings
+        Ok(EmbeddingResponse {
+            embeddings: response.embeddings.into_iter().map(|embedding| embedding.values).collect(),
+            tokens_used: 0,
+        })
+    }
+}
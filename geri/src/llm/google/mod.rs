@@ -1,5 +1,7 @@
 pub mod client;
+pub mod embedding;
 pub mod provider;
 
-pub use client::{GoogleClient, GoogleConfig, GoogleError, GenerateContentRequest, GenerateContentResponse};
+pub use client::{GoogleClient, GoogleConfig, GoogleError, GenerateContentRequest, GenerateContentResponse, GenerateContentStream, BatchEmbedContentsRequest, BatchEmbedContentsResponse};
+pub use embedding::GoogleEmbeddingProvider;
 pub use provider::GoogleLLMProvider;
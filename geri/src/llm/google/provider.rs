@@ -1,7 +1,8 @@
 //! Google LLM Provider implementation
 
-use crate::llm::{LLMProvider, LLMError, PromptRequest, PromptResponse};
-use super::client::{GoogleClient, GoogleConfig};
+use futures::StreamExt;
+use crate::llm::{LLMProvider, LLMError, PromptRequest, PromptResponse, PromptStream, PromptStreamChunk, ToolCall};
+use super::client::{GenerateContentRequest, GoogleClient, GoogleConfig, Part};
 
 pub struct GoogleLLMProvider {
     client: GoogleClient,
@@ -15,6 +16,39 @@ impl GoogleLLMProvider {
             model_name,
         }
     }
+
+    /// Uses the live API key, which key rotation swaps without a restart
+    pub fn with_live_key(mut self, live_key: crate::keys::LiveApiKey) -> Self {
+        self.client = self.client.with_live_key(live_key);
+        self
+    }
+
+    fn build_request(&self, request: &PromptRequest) -> GenerateContentRequest {
+        let mut generate_request = self.client.build_conversation_request(
+            &self.model_name,
+            &request.conversation(),
+            request.system_prompt.as_deref(),
+            request.context.as_deref(),
+            request.max_tokens,
+        );
+        self.client.apply_tools(
+            &mut generate_request,
+            &request.tools,
+            &request.tool_calls,
+            &request.tool_results,
+        );
+        self.client.apply_response_format(&mut generate_request, request.response_format.as_ref());
+        generate_request
+    }
+}
+
+/// Gemini assigns no call ids, so a fresh id is generated per `functionCall` part
+fn tool_calls_from_parts(parts: &[Part]) -> Vec<ToolCall> {
+    parts
+        .iter()
+        .filter_map(|part| part.function_call.as_ref())
+        .map(|call| ToolCall::new(call.name.clone(), call.args.clone()))
+        .collect()
 }
 
 #[async_trait::async_trait]
@@ -24,12 +58,7 @@ impl LLMProvider for GoogleLLMProvider {
     }
 
     async fn process_prompt(&self, request: PromptRequest) -> Result<PromptResponse, LLMError> {
-        let generate_request = self.client.build_generate_content_request(
-            &self.model_name,
-            &request.prompt,
-            request.context.as_deref(),
-            request.max_tokens,
-        );
+        let generate_request = self.build_request(&request);
 
         let response = self
             .client
@@ -37,20 +66,71 @@ impl LLMProvider for GoogleLLMProvider {
             .await
             .map_err(|e| LLMError::ProcessingFailed(e.to_string()))?;
 
-        // Extract text from first candidate
-        let text = response
+        // Extract text and function calls from first candidate
+        let parts = response
             .candidates
             .first()
-            .and_then(|candidate| {
-                candidate.content.parts.first().map(|part| part.text.clone())
-            })
-            .ok_or_else(|| LLMError::ProcessingFailed("No response from model".to_string()))?;
+            .map(|candidate| candidate.content.parts.as_slice())
+            .unwrap_or_default();
+        let text: String = parts.iter().map(|part| part.text.as_str()).collect();
+        let tool_calls = tool_calls_from_parts(parts);
+        if text.is_empty() && tool_calls.is_empty() {
+            return Err(LLMError::ProcessingFailed("No response from model".to_string()));
+        }
 
         let tokens_used = response
             .usage_metadata
             .map(|usage| usage.total_token_count)
             .unwrap_or(0);
 
-        Ok(PromptResponse { text, tokens_used })
+        Ok(PromptResponse { text, tokens_used, tool_calls, structured: None, safety: Vec::new() })
+    }
+
+    async fn stream_prompt(&self, request: PromptRequest) -> Result<PromptStream, LLMError> {
+        let generate_request = self.build_request(&request);
+
+        let mut chunks = self
+            .client
+            .stream_generate_content(&self.model_name, generate_request)
+            .await
+            .map_err(|e| LLMError::ProcessingFailed(e.to_string()))?;
+
+        let (tx, rx) = tokio::sync::mpsc::channel(64);
+        tokio::spawn(async move {
+            let mut tokens_used = 0;
+            let mut finish_reason = "STOP".to_string();
+            while let Some(chunk) = chunks.next().await {
+                let chunk = match chunk {
+                    Ok(chunk) => chunk,
+                    Err(e) => {
+                        let _ = tx.send(Err(LLMError::ProcessingFailed(e.to_string()))).await;
+                        return;
+                    }
+                };
+                if let Some(usage) = chunk.usage_metadata {
+                    tokens_used = usage.total_token_count;
+                }
+                if let Some(candidate) = chunk.candidates.into_iter().next() {
+                    if let Some(reason) = candidate.finish_reason {
+                        finish_reason = reason;
+                    }
+                    // Gemini sends each functionCall part complete within one chunk
+                    for call in tool_calls_from_parts(&candidate.content.parts) {
+                        if tx.send(Ok(PromptStreamChunk::ToolCall(call))).await.is_err() {
+                            return;
+                        }
+                    }
+                    let text: String = candidate.content.parts.into_iter().map(|p| p.text).collect();
+                    if !text.is_empty() && tx.send(Ok(PromptStreamChunk::Delta(text))).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            let _ = tx
+                .send(Ok(PromptStreamChunk::Done { tokens_used, finish_reason, safety: Vec::new() }))
+                .await;
+        });
+
+        Ok(Box::pin(tokio_stream::wrappers::ReceiverStream::new(rx)))
     }
 }
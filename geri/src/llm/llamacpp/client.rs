@@ -1,3 +1,6 @@
+use std::pin::Pin;
+
+use futures::Stream;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -11,6 +14,9 @@ pub enum LlamaCppError {
     InvalidConfig(String),
 }
 
+/// Stream of generated text pieces returned by `generate_stream`
+pub type TokenStream = Pin<Box<dyn Stream<Item = Result<String, LlamaCppError>> + Send>>;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LlamaCppConfig {
     /// Path to the GGUF model file
@@ -118,11 +124,68 @@ impl LlamaCppClient {
         ))
     }
     
+    /// Generate text from a prompt, yielding pieces as they are produced
+    /// 
+    /// # Arguments
+    /// 
+    /// * `prompt` - The input prompt
+    /// * `max_tokens` - Maximum number of tokens to generate
+    /// 
+    /// # Note
+    /// 
+    /// Like `generate` this is a stub: the stub output is split into word pieces.
+    /// With the llama.cpp FFI bindings each piece will be one decoded token.
+    pub async fn generate_stream(&self, prompt: &str, max_tokens: u32) -> Result<TokenStream, LlamaCppError> {
+        let text = self.generate(prompt, max_tokens).await?;
+        Ok(Self::pieces(&text))
+    }
+    
+    /// Generate text constrained by a GBNF grammar
+    /// 
+    /// # Arguments
+    /// 
+    /// * `prompt` - The input prompt
+    /// * `max_tokens` - Maximum number of tokens to generate
+    /// * `grammar` - GBNF grammar (e.g. from `ResponseFormat::to_gbnf`); only tokens the grammar
+    ///   allows are sampled
+    /// 
+    /// # Note
+    /// 
+    /// Like `generate` this is a stub: the grammar will be handed to llama.cpp's grammar
+    /// sampler with the FFI bindings.
+    pub async fn generate_with_grammar(&self, prompt: &str, max_tokens: u32, grammar: &str) -> Result<String, LlamaCppError> {
+        if grammar.trim().is_empty() {
+            return Err(LlamaCppError::InvalidConfig("grammar must not be empty".to_string()));
+        }
+        // TODO: pass the grammar to the llama.cpp grammar sampler
+        self.generate(prompt, max_tokens).await
+    }
+    
+    /// Streaming variant of `generate_with_grammar`
+    pub async fn generate_stream_with_grammar(&self, prompt: &str, max_tokens: u32, grammar: &str) -> Result<TokenStream, LlamaCppError> {
+        let text = self.generate_with_grammar(prompt, max_tokens, grammar).await?;
+        Ok(Self::pieces(&text))
+    }
+    
+    /// Splits stub output into word pieces
+    fn pieces(text: &str) -> TokenStream {
+        let pieces: Vec<Result<String, LlamaCppError>> = text
+            .split_inclusive(' ')
+            .map(|piece| Ok(piece.to_string()))
+            .collect();
+        Box::pin(futures::stream::iter(pieces))
+    }
+    
     /// Get the model name
     pub fn model_name(&self) -> &str {
         &self.model_name
     }
     
+    /// Get the model file path
+    pub fn model_path(&self) -> &str {
+        &self.config.model_path
+    }
+    
     /// Get the context size
     pub fn context_size(&self) -> u32 {
         self.config.n_ctx
@@ -0,0 +1,130 @@
+//! llama.cpp embedding mode (`llama-server --embedding`) implementing EmbeddingProvider
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::llm::{EmbeddingProvider, EmbeddingResponse, LLMError};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlamaCppEmbeddingConfig {
+    /// Base URL of a llama.cpp server started with `--embedding`
+    pub server_url: String,
+    pub timeout_secs: u64,
+}
+
+impl Default for LlamaCppEmbeddingConfig {
+    fn default() -> Self {
+        Self {
+            server_url: "http://localhost:8080".to_string(),
+            timeout_secs: 30,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct EmbeddingRequest<'a> {
+    content: &'a str,
+}
+
+/// `/embedding` answers `{"embedding": [...]}` (older servers) or
+/// `[{"index": 0, "embedding": [[...]]}]` (newer servers, one row per token without pooling)
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum EmbeddingResult {
+    Single { embedding: EmbeddingValues },
+    Batch(Vec<EmbeddingItem>),
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingItem {
+    embedding: EmbeddingValues,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum EmbeddingValues {
+    Pooled(Vec<f32>),
+    PerToken(Vec<Vec<f32>>),
+}
+
+impl EmbeddingValues {
+    /// Mean-pools per-token rows into one vector
+    fn pooled(self) -> Vec<f32> {
+        match self {
+            EmbeddingValues::Pooled(vector) => vector,
+            EmbeddingValues::PerToken(rows) => {
+                let count = rows.len().max(1) as f32;
+                let mut pooled = vec![0.0; rows.first().map(Vec::len).unwrap_or(0)];
+                for row in &rows {
+                    for (sum, value) in pooled.iter_mut().zip(row) {
+                        *sum += value;
+                    }
+                }
+                pooled.iter_mut().for_each(|sum| *sum /= count);
+                pooled
+            }
+        }
+    }
+}
+
+pub struct LlamaCppEmbeddingProvider {
+    config: LlamaCppEmbeddingConfig,
+    client: reqwest::Client,
+    model_name: String,
+}
+
+impl LlamaCppEmbeddingProvider {
+    pub fn new(config: LlamaCppEmbeddingConfig, model_name: String) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(config.timeout_secs))
+            .build()
+            .expect("Failed to build HTTP client");
+        Self { config, client, model_name }
+    }
+
+    async fn embed_one(&self, input: &str) -> Result<Vec<f32>, LLMError> {
+        let url = format!("{}/embedding", self.config.server_url.trim_end_matches('/'));
+        let response = self
+            .client
+            .post(&url)
+            .json(&EmbeddingRequest { content: input })
+            .send()
+            .await
+            .map_err(|e| LLMError::ModelNotAvailable(format!("llama.cpp server unreachable: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(LLMError::ProcessingFailed(format!("HTTP {}: {}", status, error_text)));
+        }
+
+        let result: EmbeddingResult = response
+            .json()
+            .await
+            .map_err(|e| LLMError::ProcessingFailed(format!("Failed to parse embedding: {}", e)))?;
+        match result {
+            EmbeddingResult::Single { embedding } => Ok(embedding.pooled()),
+            EmbeddingResult::Batch(items) => items
+                .into_iter()
+                .next()
+                .map(|item| item.embedding.pooled())
+                .ok_or_else(|| LLMError::ProcessingFailed("Empty embedding response".to_string())),
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for LlamaCppEmbeddingProvider {
+    fn model_name(&self) -> &str {
+        &self.model_name
+    }
+
+    async fn embed(&self, inputs: &[String]) -> Result<EmbeddingResponse, LLMError> {
+        // One request per input: older servers accept only a single `content` string
+        let mut embeddings = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            embeddings.push(self.embed_one(input).await?);
+        }
+        Ok(EmbeddingResponse { embeddings, tokens_used: 0 })
+    }
+}
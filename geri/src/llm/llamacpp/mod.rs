@@ -1,5 +1,9 @@
 pub mod client;
+pub mod embedding;
 pub mod provider;
+pub mod vision;
 
-pub use client::{LlamaCppClient, LlamaCppConfig, LlamaCppError};
+pub use client::{LlamaCppClient, LlamaCppConfig, LlamaCppError, TokenStream};
+pub use embedding::{LlamaCppEmbeddingConfig, LlamaCppEmbeddingProvider};
 pub use provider::LlamaCppLLMProvider;
+pub use vision::{discover_vision_models, LlamaCppVisionConfig, LlamaCppVisionProvider, LocalVisionModel};
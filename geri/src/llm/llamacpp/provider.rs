@@ -1,5 +1,8 @@
 use async_trait::async_trait;
-use crate::llm::provider::{LLMProvider, LLMError, PromptRequest, PromptResponse};
+use futures::StreamExt;
+use crate::llm::provider::{LLMProvider, LLMError, PromptRequest, PromptResponse, PromptStream, PromptStreamChunk};
+use crate::llm::tools::parse_tool_calls;
+use crate::prompt::ChatTemplate;
 use super::client::LlamaCppClient;
 
 /// LLM Provider implementation for llama.cpp
@@ -8,6 +11,7 @@ use super::client::LlamaCppClient;
 /// enabling local LLM inference with GGUF models.
 pub struct LlamaCppLLMProvider {
     client: LlamaCppClient,
+    chat_template: ChatTemplate,
 }
 
 impl LlamaCppLLMProvider {
@@ -16,14 +20,41 @@ impl LlamaCppLLMProvider {
     /// # Arguments
     /// 
     /// * `client` - The configured llama.cpp client
+    /// 
+    /// The chat template is detected from the GGUF metadata or the model name.
     pub fn new(client: LlamaCppClient) -> Self {
-        Self { client }
+        let chat_template = ChatTemplate::resolve(None, client.model_path());
+        Self { client, chat_template }
     }
     
     /// Get the underlying client (for advanced usage)
     pub fn client(&self) -> &LlamaCppClient {
         &self.client
     }
+    
+    /// Use the given chat template instead of the auto-detected one
+    pub fn with_chat_template(mut self, chat_template: ChatTemplate) -> Self {
+        self.chat_template = chat_template;
+        self
+    }
+    
+    /// Get the chat template used to format prompts
+    pub fn chat_template(&self) -> ChatTemplate {
+        self.chat_template
+    }
+    
+    /// Build the full model input in the model family's chat template
+    /// 
+    /// llama.cpp has no native tool calling, so tool definitions are rendered as
+    /// JSON-prompted instructions; previous tool calls and results become assistant/tool turns.
+    fn build_prompt(&self, request: &PromptRequest) -> String {
+        self.chat_template.render_request(request)
+    }
+    
+    /// GBNF grammar constraining the answer to the request's JSON Schema
+    fn grammar(request: &PromptRequest) -> Option<String> {
+        request.response_format.as_ref().map(|format| format.to_gbnf())
+    }
 }
 
 #[async_trait]
@@ -33,22 +64,19 @@ impl LLMProvider for LlamaCppLLMProvider {
     }
     
     async fn process_prompt(&self, request: PromptRequest) -> Result<PromptResponse, LLMError> {
-        // Use PromptFormatter for consistent prompting
-        let formatter = crate::prompt::PromptFormatter::default();
-        let full_prompt = formatter.format(
-            request.system_prompt.as_deref().unwrap_or(""),
-            &request.prompt,
-            request.context.as_deref(),
-        );
+        let full_prompt = self.build_prompt(&request);
         
         // Use max_tokens from request or default to 512
         let max_tokens = request.max_tokens.unwrap_or(512);
         
         // Generate text using llama.cpp
-        let generated_text = self.client
-            .generate(&full_prompt, max_tokens)
-            .await
-            .map_err(|e| LLMError::ProcessingFailed(e.to_string()))?;
+        let generated_text = match Self::grammar(&request) {
+            Some(grammar) => self.client.generate_with_grammar(&full_prompt, max_tokens, &grammar).await,
+            None => self.client.generate(&full_prompt, max_tokens).await,
+        }
+        .map_err(|e| LLMError::ProcessingFailed(e.to_string()))?;
+        // Drop anything after the end-of-turn marker (no role markers in the answer)
+        let generated_text = self.chat_template.trim_output(&generated_text).to_string();
         
         // Estimate token usage (simplified - would use actual tokenizer in production)
         // Count input tokens
@@ -57,11 +85,94 @@ impl LLMProvider for LlamaCppLLMProvider {
         let output_tokens = generated_text.split_whitespace().count() as u32;
         let total_tokens = input_tokens + output_tokens;
         
+        // A JSON answer naming a known tool is a tool call, not text for the user
+        if let Some(tool_calls) = parse_tool_calls(&generated_text, &request.tools) {
+            return Ok(PromptResponse {
+                text: String::new(),
+                tokens_used: total_tokens,
+                tool_calls,
+                structured: None,
+                safety: Vec::new(),
+            });
+        }
+        
         Ok(PromptResponse {
             text: generated_text,
             tokens_used: total_tokens,
+            tool_calls: Vec::new(),
+            structured: None,
+            safety: Vec::new(),
         })
     }
+    
+    async fn stream_prompt(&self, request: PromptRequest) -> Result<PromptStream, LLMError> {
+        let full_prompt = self.build_prompt(&request);
+        let max_tokens = request.max_tokens.unwrap_or(512);
+        
+        let mut pieces = match Self::grammar(&request) {
+            Some(grammar) => self.client.generate_stream_with_grammar(&full_prompt, max_tokens, &grammar).await,
+            None => self.client.generate_stream(&full_prompt, max_tokens).await,
+        }
+        .map_err(|e| LLMError::ProcessingFailed(e.to_string()))?;
+        
+        let input_tokens = full_prompt.split_whitespace().count() as u32;
+        let tools = request.tools;
+        let chat_template = self.chat_template;
+        let (tx, rx) = tokio::sync::mpsc::channel(64);
+        tokio::spawn(async move {
+            let mut output_tokens = 0u32;
+            // With tools the answer may be a JSON tool call, so it is buffered until complete
+            let mut buffered = String::new();
+            let mut generated = String::new();
+            while let Some(piece) = pieces.next().await {
+                match piece {
+                    Ok(text) => {
+                        output_tokens += 1;
+                        // Stop at the end-of-turn marker of the chat template
+                        let start = generated.len();
+                        generated.push_str(&text);
+                        let end = chat_template.trim_output(&generated).len();
+                        let stopped = end < generated.len();
+                        let text = generated[start.min(end)..end].to_string();
+                        if !tools.is_empty() {
+                            buffered.push_str(&text);
+                        } else if !text.is_empty()
+                            && tx.send(Ok(PromptStreamChunk::Delta(text))).await.is_err()
+                        {
+                            return;
+                        }
+                        if stopped {
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        let _ = tx.send(Err(LLMError::ProcessingFailed(e.to_string()))).await;
+                        return;
+                    }
+                }
+            }
+            let mut finish_reason = if output_tokens >= max_tokens { "length" } else { "stop" };
+            if let Some(tool_calls) = parse_tool_calls(&buffered, &tools) {
+                finish_reason = "tool_calls";
+                for call in tool_calls {
+                    if tx.send(Ok(PromptStreamChunk::ToolCall(call))).await.is_err() {
+                        return;
+                    }
+                }
+            } else if !buffered.is_empty()
+                && tx.send(Ok(PromptStreamChunk::Delta(buffered))).await.is_err()
+            {
+                return;
+            }
+            let _ = tx.send(Ok(PromptStreamChunk::Done {
+                tokens_used: input_tokens + output_tokens,
+                finish_reason: finish_reason.to_string(),
+                safety: Vec::new(),
+            })).await;
+        });
+        
+        Ok(Box::pin(tokio_stream::wrappers::ReceiverStream::new(rx)))
+    }
 }
 
 #[cfg(test)]
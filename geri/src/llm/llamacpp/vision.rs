@@ -0,0 +1,295 @@
+//! llama.cpp multimodal mode (`llama-server -m <model> --mmproj <projector>`) implementing VisionProvider
+//!
+//! LLaVA/Qwen-VL style models need the vision projector (`mmproj-*.gguf`) next to the language
+//! model; the server receives the image as base64 data URL in an OpenAI-style chat completion,
+//! so camera images never leave the machine.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::model::{ModelInfo, ModelType};
+use crate::vision::{VisionError, VisionProvider, VisionRequest, VisionResponse};
+
+/// Prompt used when the request asks no specific question
+const DEFAULT_VISION_PROMPT: &str = "Describe this image.";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlamaCppVisionConfig {
+    /// Base URL of a llama.cpp server started with the model and its `--mmproj` projector
+    pub server_url: String,
+    /// Path to the GGUF language model
+    pub model_path: String,
+    /// Path to the GGUF vision projector (`mmproj`)
+    pub mmproj_path: String,
+    pub max_tokens: u32,
+    pub timeout_secs: u64,
+}
+
+impl LlamaCppVisionConfig {
+    pub fn validate(&self) -> Result<(), VisionError> {
+        if self.model_path.is_empty() || self.mmproj_path.is_empty() {
+            return Err(VisionError::ProcessingFailed(
+                "model_path and mmproj_path must not be empty".to_string(),
+            ));
+        }
+        if self.model_path == self.mmproj_path {
+            return Err(VisionError::ProcessingFailed(
+                "mmproj_path must point to the vision projector, not the language model".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A vision-capable local model: language model plus projector found in the models directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalVisionModel {
+    /// Model id (file name of the language model without `.gguf`)
+    pub id: String,
+    pub model_path: PathBuf,
+    pub mmproj_path: PathBuf,
+}
+
+impl LocalVisionModel {
+    /// Registry entry (`ModelType::Vision`, local, free)
+    pub fn model_info(&self) -> ModelInfo {
+        ModelInfo {
+            id: self.id.clone(),
+            name: self.id.clone(),
+            provider: "llamacpp".to_string(),
+            model_type: ModelType::Vision,
+            parameter_count: None,
+            hardware_requirements: None,
+            context_window: None,
+            is_local: true,
+            cost_per_token_input: Some(0.0),
+            cost_per_token_output: Some(0.0),
+            embedding_dimension: None,
+        }
+    }
+
+    /// Provider config for this model on the given llama.cpp server
+    pub fn config(&self, server_url: &str) -> LlamaCppVisionConfig {
+        LlamaCppVisionConfig {
+            server_url: server_url.to_string(),
+            model_path: self.model_path.to_string_lossy().into_owned(),
+            mmproj_path: self.mmproj_path.to_string_lossy().into_owned(),
+            max_tokens: 512,
+            timeout_secs: 120,
+        }
+    }
+}
+
+/// Find vision-capable models in `dir` and its direct subdirectories.
+///
+/// A projector is a GGUF file with `general.architecture = "clip"` or `mmproj` in its name. It
+/// belongs to the language model whose name starts with the projector's name (without `mmproj`
+/// and the precision suffix, e.g. `mmproj-Qwen2-VL-7B-Instruct-f16.gguf` and
+/// `Qwen2-VL-7B-Instruct-Q4_K_M.gguf`), or to the only language model in the same directory.
+pub fn discover_vision_models(dir: &Path) -> std::io::Result<Vec<LocalVisionModel>> {
+    let mut models = discover_in(dir)?;
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            models.extend(discover_in(&path)?);
+        }
+    }
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(models)
+}
+
+fn discover_in(dir: &Path) -> std::io::Result<Vec<LocalVisionModel>> {
+    let mut projectors = Vec::new();
+    let mut language_models = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_file() || path.extension().and_then(|e| e.to_str()) != Some("gguf") {
+            continue;
+        }
+        if is_projector(&path) {
+            projectors.push(path);
+        } else {
+            language_models.push(path);
+        }
+    }
+
+    let mut found = Vec::new();
+    for projector in &projectors {
+        let base = projector_base_name(projector);
+        let model = language_models
+            .iter()
+            .find(|model| !base.is_empty() && normalized(&file_stem(model)).join("-").starts_with(&base))
+            .or_else(|| (language_models.len() == 1 && projectors.len() == 1).then(|| &language_models[0]));
+        match model {
+            Some(model) => found.push(LocalVisionModel {
+                id: file_stem(model),
+                model_path: model.clone(),
+                mmproj_path: projector.clone(),
+            }),
+            None => tracing::warn!("No language model found for vision projector {}", projector.display()),
+        }
+    }
+    Ok(found)
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_string()
+}
+
+fn is_projector(path: &Path) -> bool {
+    if file_stem(path).to_lowercase().contains("mmproj") {
+        return true;
+    }
+    matches!(
+        crate::llm::gguf::read_string_metadata(path, "general.architecture"),
+        Ok(Some(architecture)) if architecture == "clip"
+    )
+}
+
+/// Lower-case name parts joined by `-` (`Llava-v1.6_7B` → `llava-v1-6-7b`)
+fn normalized(name: &str) -> Vec<String> {
+    name.to_lowercase()
+        .split(['-', '.', '_'])
+        .filter(|part| !part.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Projector name without `mmproj` and the precision suffix, normalized
+fn projector_base_name(path: &Path) -> String {
+    let mut parts: Vec<String> = normalized(&file_stem(path)).into_iter().filter(|part| part != "mmproj").collect();
+    while parts.last().is_some_and(|last| ["f16", "f32", "bf16", "q8", "0", "model"].contains(&last.as_str())) {
+        parts.pop();
+    }
+    parts.join("-")
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletion {
+    #[serde(default)]
+    choices: Vec<ChatChoice>,
+    #[serde(default)]
+    usage: Option<ChatUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoice {
+    message: ChatChoiceMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatChoiceMessage {
+    #[serde(default)]
+    content: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatUsage {
+    #[serde(default)]
+    total_tokens: u32,
+}
+
+pub struct LlamaCppVisionProvider {
+    config: LlamaCppVisionConfig,
+    client: reqwest::Client,
+    model_name: String,
+}
+
+impl LlamaCppVisionProvider {
+    pub fn new(config: LlamaCppVisionConfig, model_name: String) -> Result<Self, VisionError> {
+        config.validate()?;
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|e| VisionError::ProcessingFailed(format!("Failed to build HTTP client: {}", e)))?;
+        Ok(Self { config, client, model_name })
+    }
+
+    pub fn config(&self) -> &LlamaCppVisionConfig {
+        &self.config
+    }
+
+    /// Chat completion body with the image as data URL (MIME type from the file signature)
+    fn request_body(&self, request: &VisionRequest) -> serde_json::Value {
+        use base64::Engine;
+        let data_url = format!(
+            "data:{};base64,{}",
+            image_mime_type(&request.image_data),
+            base64::engine::general_purpose::STANDARD.encode(&request.image_data)
+        );
+        json!({
+            "model": self.model_name,
+            "max_tokens": self.config.max_tokens,
+            "messages": [{
+                "role": "user",
+                "content": [
+                    { "type": "text", "text": request.prompt.as_deref().unwrap_or(DEFAULT_VISION_PROMPT) },
+                    { "type": "image_url", "image_url": { "url": data_url } }
+                ]
+            }]
+        })
+    }
+
+    async fn analyze(&self, request: VisionRequest) -> Result<VisionResponse, VisionError> {
+        if request.image_data.is_empty() {
+            return Err(VisionError::ProcessingFailed("image_data is empty".to_string()));
+        }
+        let url = format!("{}/v1/chat/completions", self.config.server_url.trim_end_matches('/'));
+        let response = self
+            .client
+            .post(&url)
+            .json(&self.request_body(&request))
+            .send()
+            .await
+            .map_err(|e| VisionError::ProcessingFailed(format!("llama.cpp server unreachable: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(VisionError::ProcessingFailed(format!("HTTP {}: {}", status, error_text)));
+        }
+
+        let completion: ChatCompletion = response
+            .json()
+            .await
+            .map_err(|e| VisionError::ProcessingFailed(format!("Failed to parse vision response: {}", e)))?;
+        let description = completion
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|choice| choice.message.content)
+            .ok_or_else(|| VisionError::ProcessingFailed("Empty vision response".to_string()))?;
+        Ok(VisionResponse {
+            analysis: json!({
+                "model": self.model_name,
+                "provider": "llamacpp",
+                "local": true,
+                "tokens_used": completion.usage.map(|usage| usage.total_tokens).unwrap_or(0),
+            }),
+            description: description.trim().to_string(),
+        })
+    }
+}
+
+impl VisionProvider for LlamaCppVisionProvider {
+    fn model_name(&self) -> &str {
+        &self.model_name
+    }
+
+    fn process(&self, request: VisionRequest) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<VisionResponse, VisionError>> + Send + '_>> {
+        Box::pin(self.analyze(request))
+    }
+}
+
+/// MIME type from the image signature; JPEG if unknown (camera frames)
+fn image_mime_type(data: &[u8]) -> &'static str {
+    match data {
+        [0x89, b'P', b'N', b'G', ..] => "image/png",
+        [b'G', b'I', b'F', b'8', ..] => "image/gif",
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "image/webp",
+        [b'B', b'M', ..] => "image/bmp",
+        _ => "image/jpeg",
+    }
+}
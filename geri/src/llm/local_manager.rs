@@ -2,7 +2,9 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use crate::llm::llamacpp::{LlamaCppClient, LlamaCppConfig, LlamaCppLLMProvider};
 use crate::llm::bitnet::{BitNetClient, BitNetConfig, BitNetLLMProvider};
+use crate::llm::model_catalog::{CatalogEntry, ModelCatalog};
 use crate::llm::provider::LLMProvider;
+use crate::prompt::ChatTemplate;
 
 #[derive(Debug, Error)]
 pub enum LocalManagerError {
@@ -38,6 +40,8 @@ pub struct ProviderSelection {
     pub model_size: String,
     /// Rationale for selection
     pub rationale: String,
+    /// Catalog variant to use (`None` without catalog or if no variant fits)
+    pub variant: Option<CatalogEntry>,
 }
 
 /// Configuration for local model
@@ -47,6 +51,8 @@ pub struct LocalModelConfig {
     pub n_ctx: u32,
     pub n_threads: u32,
     pub n_gpu_layers: u32,
+    /// Chat template override; `None` = auto-detect from GGUF metadata / model name
+    pub chat_template: Option<crate::prompt::ChatTemplate>,
 }
 
 /// Local LLM Manager for automatic provider selection
@@ -58,9 +64,13 @@ pub struct LocalModelConfig {
 /// - GPU availability
 /// - Battery constraints (mobile)
 /// 
-/// It also integrates with ModelDownloader to ensure models are available.
+/// It also integrates with ModelDownloader to ensure models are available; with a
+/// `ModelCatalog` the largest catalog variant that fits the hardware is selected and,
+/// with auto-download enabled, fetched and verified if missing.
 pub struct LocalLLMManager {
     downloader: crate::llm::model_downloader::ModelDownloader,
+    catalog: ModelCatalog,
+    auto_download: bool,
 }
 
 impl LocalLLMManager {
@@ -80,8 +90,36 @@ impl LocalLLMManager {
         
         Ok(Self {
             downloader,
+            catalog: ModelCatalog::default(),
+            auto_download: false,
         })
     }
+
+    /// Select models from a catalog instead of the fixed size heuristic
+    pub fn with_catalog(mut self, catalog: ModelCatalog) -> Self {
+        self.catalog = catalog;
+        self
+    }
+
+    /// Download the selected catalog variant if it is missing
+    pub fn with_auto_download(mut self, auto_download: bool) -> Self {
+        self.auto_download = auto_download;
+        self
+    }
+
+    /// Limit the total size of the models directory (least recently used models are removed)
+    pub fn with_models_quota_mb(mut self, quota_mb: u64) -> Self {
+        self.downloader = self.downloader.with_quota_mb(quota_mb);
+        self
+    }
+
+    pub fn catalog(&self) -> &ModelCatalog {
+        &self.catalog
+    }
+
+    pub fn downloader(&self) -> &crate::llm::model_downloader::ModelDownloader {
+        &self.downloader
+    }
     
     /// Detect hardware profile
     /// 
@@ -149,7 +187,34 @@ impl LocalLLMManager {
     }
     
     /// Select the best provider based on hardware profile
+    ///
+    /// With a catalog, the largest variant of the selected provider that fits into the available
+    /// memory is used (or of the other provider if none fits).
     pub fn select_provider(&self, profile: &HardwareProfile) -> ProviderSelection {
+        let selection = self.select_provider_type(profile);
+        match self.catalog.select(profile, &selection.provider_type) {
+            Some(variant) => ProviderSelection {
+                provider_type: variant.provider_type.clone(),
+                model_size: variant.model_size.clone(),
+                rationale: format!(
+                    "{} Catalog variant {} ({}, needs {}MB).",
+                    selection.rationale, variant.id, variant.quantization, variant.min_ram_mb
+                ),
+                variant: Some(variant.clone()),
+            },
+            None => {
+                if !self.catalog.is_empty() {
+                    tracing::warn!(
+                        "No catalog model fits into {}MB available memory, using size heuristic",
+                        profile.available_memory_mb
+                    );
+                }
+                selection
+            }
+        }
+    }
+
+    fn select_provider_type(&self, profile: &HardwareProfile) -> ProviderSelection {
         // Decision logic:
         // 1. Mobile/Low memory (< 4GB available) -> BitNet for efficiency
         // 2. Medium memory (4-8GB) -> BitNet for balance
@@ -164,6 +229,7 @@ impl LocalLLMManager {
                     "BitNet selected: Low memory system ({}MB available). BitNet provides 90% memory reduction.",
                     profile.available_memory_mb
                 ),
+                variant: None,
             }
         } else if profile.available_memory_mb < 8000 {
             // Medium memory: Still prefer BitNet for safety
@@ -174,6 +240,7 @@ impl LocalLLMManager {
                     "BitNet selected: Medium memory system ({}MB available). BitNet balances quality and efficiency.",
                     profile.available_memory_mb
                 ),
+                variant: None,
             }
         } else {
             // High memory: Use llama.cpp for best quality
@@ -184,6 +251,7 @@ impl LocalLLMManager {
                     "llama.cpp selected: High memory system ({}MB available). llama.cpp provides best quality.",
                     profile.available_memory_mb
                 ),
+                variant: None,
             }
         }
     }
@@ -233,7 +301,9 @@ impl LocalLLMManager {
         };
         
         // Generate model filename and check if exists
-        let model_filename = if selection.provider_type == "bitnet" {
+        let model_filename = if let Some(variant) = &selection.variant {
+            variant.file_name.clone()
+        } else if selection.provider_type == "bitnet" {
             format!("bitnet-{}.bitnet", selection.model_size)
         } else {
             format!("llama-{}.gguf", selection.model_size)
@@ -250,9 +320,15 @@ impl LocalLLMManager {
             );
             
             // Get recommended source
-            if let Some(source) = self.downloader.get_recommended_source(&selection.model_size, &selection.provider_type) {
+            let source = match &selection.variant {
+                Some(variant) => Some(variant.source()),
+                None => self.downloader.get_recommended_source(&selection.model_size, &selection.provider_type),
+            };
+            if let Some(source) = source {
                 tracing::info!("Recommended download: {}", source.get_url());
             }
+        } else if let Err(e) = self.downloader.mark_used(&model_filename) {
+            tracing::debug!("Failed to mark model {} as used: {}", model_filename, e);
         }
         
         LocalModelConfig {
@@ -260,6 +336,7 @@ impl LocalLLMManager {
             n_ctx,
             n_threads,
             n_gpu_layers,
+            chat_template: None,
         }
     }
     
@@ -268,6 +345,12 @@ impl LocalLLMManager {
         self.downloader.list_models().await
             .map_err(|e| LocalManagerError::ProviderCreationFailed(e.to_string()))
     }
+
+    /// Load tokenizer vocabularies from the models directory into the registry
+    pub fn load_tokenizers(&self, registry: &mut runar::TokenizerRegistry) -> Result<usize, LocalManagerError> {
+        self.downloader.load_tokenizers(registry)
+            .map_err(|e| LocalManagerError::ProviderCreationFailed(e.to_string()))
+    }
     
     /// Create provider automatically based on detected hardware
     pub async fn create_provider_auto(&self) -> Result<Box<dyn LLMProvider>, LocalManagerError> {
@@ -283,8 +366,23 @@ impl LocalLLMManager {
             selection.rationale
         );
         
+        if self.auto_download {
+            if let Some(variant) = selection.variant.as_ref().filter(|v| !self.downloader.check_model_exists(&v.file_name)) {
+                self.download_variant(variant).await?;
+            }
+        }
+        
         self.create_provider(&selection.provider_type, config).await
     }
+
+    /// Download and verify a catalog variant into the models directory
+    pub async fn download_variant(&self, variant: &CatalogEntry) -> Result<std::path::PathBuf, LocalManagerError> {
+        tracing::info!("Downloading catalog model {} ({})", variant.id, variant.source().get_url());
+        self.downloader
+            .download_entry(variant)
+            .await
+            .map_err(|e| LocalManagerError::ProviderCreationFailed(e.to_string()))
+    }
     
     /// Create provider with explicit type and config
     pub async fn create_provider(
@@ -294,6 +392,7 @@ impl LocalLLMManager {
     ) -> Result<Box<dyn LLMProvider>, LocalManagerError> {
         match provider_type {
             "llamacpp" => {
+                let chat_template = ChatTemplate::resolve(config.chat_template, &config.model_path);
                 let llamacpp_config = LlamaCppConfig {
                     model_path: config.model_path,
                     n_ctx: config.n_ctx,
@@ -304,10 +403,11 @@ impl LocalLLMManager {
                 let client = LlamaCppClient::new(llamacpp_config)
                     .map_err(|e| LocalManagerError::ProviderCreationFailed(e.to_string()))?;
                 
-                let provider = LlamaCppLLMProvider::new(client);
+                let provider = LlamaCppLLMProvider::new(client).with_chat_template(chat_template);
                 Ok(Box::new(provider))
             }
             "bitnet" => {
+                let chat_template = ChatTemplate::resolve(config.chat_template, &config.model_path);
                 let bitnet_config = BitNetConfig {
                     model_path: config.model_path,
                     n_ctx: config.n_ctx,
@@ -318,7 +418,7 @@ impl LocalLLMManager {
                 let client = BitNetClient::new(bitnet_config)
                     .map_err(|e| LocalManagerError::ProviderCreationFailed(e.to_string()))?;
                 
-                let provider = BitNetLLMProvider::new(client);
+                let provider = BitNetLLMProvider::new(client).with_chat_template(chat_template);
                 Ok(Box::new(provider))
             }
             _ => Err(LocalManagerError::InvalidConfig(
@@ -0,0 +1,97 @@
+//! Multi-turn conversation messages, mapped by each provider to its native chat format.
+
+use serde::{Deserialize, Serialize};
+
+use super::provider::PromptRequest;
+use super::tools::ToolCall;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatRole {
+    System,
+    User,
+    Assistant,
+    /// Result of a tool call; `tool_call_id` references the assistant's [`ToolCall`]
+    Tool,
+}
+
+impl ChatRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChatRole::System => "system",
+            ChatRole::User => "user",
+            ChatRole::Assistant => "assistant",
+            ChatRole::Tool => "tool",
+        }
+    }
+
+    /// Parse a role name; unknown roles are treated as `user`
+    pub fn parse(role: &str) -> Self {
+        match role.to_lowercase().as_str() {
+            "system" => ChatRole::System,
+            "assistant" | "model" => ChatRole::Assistant,
+            "tool" | "function" => ChatRole::Tool,
+            _ => ChatRole::User,
+        }
+    }
+}
+
+/// One turn of a conversation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: ChatRole,
+    pub content: String,
+    /// Speaker name for `user` turns, tool name for `tool` turns
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Set on `tool` turns
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    /// Tool calls made by an `assistant` turn
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+}
+
+impl ChatMessage {
+    pub fn new(role: ChatRole, content: impl Into<String>) -> Self {
+        Self {
+            role,
+            content: content.into(),
+            name: None,
+            tool_call_id: None,
+            tool_calls: Vec::new(),
+        }
+    }
+
+    pub fn system(content: impl Into<String>) -> Self {
+        Self::new(ChatRole::System, content)
+    }
+
+    pub fn user(content: impl Into<String>) -> Self {
+        Self::new(ChatRole::User, content)
+    }
+
+    pub fn assistant(content: impl Into<String>) -> Self {
+        Self::new(ChatRole::Assistant, content)
+    }
+
+    pub fn tool(tool_call_id: impl Into<String>, name: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            tool_call_id: Some(tool_call_id.into()),
+            ..Self::new(ChatRole::Tool, content)
+        }
+    }
+}
+
+impl PromptRequest {
+    /// The conversation to send: `messages` (earlier turns) followed by `prompt` as the
+    /// current user turn. Without `messages` this is the classic single-prompt exchange.
+    pub fn conversation(&self) -> Vec<ChatMessage> {
+        let mut conversation = self.messages.clone();
+        if !self.prompt.is_empty() {
+            conversation.push(ChatMessage::user(self.prompt.clone()));
+        }
+        conversation
+    }
+}
@@ -1,4 +1,8 @@
 pub mod provider;
+pub mod messages;
+pub mod tools;
+pub mod structured;
+pub mod embedding;
 pub mod openai;
 pub mod anthropic;
 pub mod google;
@@ -6,12 +10,20 @@ pub mod llamacpp;
 pub mod bitnet;
 pub mod local_manager;
 pub mod model_downloader;
+pub mod model_catalog;
+pub mod gguf;
 pub mod engine;
 pub mod factory;
+pub mod scheduled;
 
 pub use provider::*;
+pub use messages::{ChatMessage, ChatRole};
+pub use tools::{ToolCall, ToolDefinition, ToolResult};
+pub use structured::ResponseFormat;
+pub use embedding::{EmbeddingProvider, EmbeddingResponse, EmbeddingService, Embeddings};
 pub use engine::GeriEngine;
 pub use factory::ProviderFactory;
+pub use scheduled::ScheduledProvider;
 
 
 
@@ -0,0 +1,171 @@
+//! Catalog of downloadable local models: one entry per GGUF/BitNet file with quantization,
+//! RAM need and SHA-256 checksum. `LocalLLMManager` picks the largest variant that fits the
+//! hardware, `ModelDownloader::download_entry` fetches and verifies it.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::llm::local_manager::HardwareProfile;
+use crate::llm::model_downloader::ModelSource;
+
+#[derive(Debug, Error)]
+pub enum CatalogError {
+    #[error("Failed to read model catalog {path}: {source}")]
+    Io { path: String, source: std::io::Error },
+    #[error("Failed to parse model catalog {path}: {message}")]
+    Parse { path: String, message: String },
+    #[error("Invalid model catalog entry '{id}': {message}")]
+    InvalidEntry { id: String, message: String },
+}
+
+/// One downloadable model file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    pub id: String,
+    /// "llamacpp" or "bitnet"
+    pub provider_type: String,
+    /// Parameter size as used by the size recommendation ("3b", "7b", "8b", ...)
+    pub model_size: String,
+    /// Quantization of the file ("Q4_K_M", "Q8_0", "1bit", ...)
+    pub quantization: String,
+    /// File name in the models directory
+    pub file_name: String,
+    /// HuggingFace repository containing `file_name`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+    /// Direct download URL (instead of `repo`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Expected SHA-256 of the file (hex); downloads without it are not verified
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    /// Expected file size in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+    /// Memory needed to run the model (weights plus context)
+    pub min_ram_mb: u32,
+}
+
+impl CatalogEntry {
+    /// Download source (`url` takes precedence over `repo`)
+    pub fn source(&self) -> ModelSource {
+        match (&self.url, &self.repo) {
+            (Some(url), _) => ModelSource::DirectUrl { url: url.clone() },
+            (None, repo) => ModelSource::HuggingFace {
+                repo: repo.clone().unwrap_or_default(),
+                file: self.file_name.clone(),
+            },
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), CatalogError> {
+        let invalid = |message: &str| CatalogError::InvalidEntry {
+            id: self.id.clone(),
+            message: message.to_string(),
+        };
+        if !matches!(self.provider_type.as_str(), "llamacpp" | "bitnet") {
+            return Err(invalid("provider_type must be 'llamacpp' or 'bitnet'"));
+        }
+        if self.file_name.is_empty() || self.file_name.contains(['/', '\\']) || self.file_name.starts_with('.') {
+            return Err(invalid("file_name must be a plain file name"));
+        }
+        if self.repo.is_none() && self.url.is_none() {
+            return Err(invalid("either repo or url is required"));
+        }
+        if let Some(sha256) = &self.sha256 {
+            if sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(invalid("sha256 must be 64 hex characters"));
+            }
+        }
+        self.source()
+            .validate()
+            .map_err(|e| invalid(&e.to_string()))
+    }
+}
+
+/// Available model variants, loaded from a YAML or JSON file (`models: [...]`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelCatalog {
+    #[serde(default)]
+    pub models: Vec<CatalogEntry>,
+}
+
+impl ModelCatalog {
+    pub fn new(models: Vec<CatalogEntry>) -> Result<Self, CatalogError> {
+        let catalog = Self { models };
+        catalog.validate()?;
+        Ok(catalog)
+    }
+
+    /// Load and validate a catalog; `.json` files are JSON, everything else YAML.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CatalogError> {
+        let path = path.as_ref();
+        let display = path.display().to_string();
+        let content = std::fs::read_to_string(path).map_err(|source| CatalogError::Io {
+            path: display.clone(),
+            source,
+        })?;
+        let catalog: ModelCatalog = match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => serde_json::from_str(&content).map_err(|e| e.to_string()),
+            _ => serde_yaml::from_str(&content).map_err(|e| e.to_string()),
+        }
+        .map_err(|message| CatalogError::Parse { path: display, message })?;
+        catalog.validate()?;
+        Ok(catalog)
+    }
+
+    /// Valid entries with unique ids and file names
+    pub fn validate(&self) -> Result<(), CatalogError> {
+        let mut ids = HashSet::new();
+        let mut files = HashSet::new();
+        for entry in &self.models {
+            entry.validate()?;
+            if !ids.insert(entry.id.as_str()) || !files.insert(entry.file_name.as_str()) {
+                return Err(CatalogError::InvalidEntry {
+                    id: entry.id.clone(),
+                    message: "duplicate id or file_name".to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.models.is_empty()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&CatalogEntry> {
+        self.models.iter().find(|entry| entry.id == id)
+    }
+
+    pub fn by_file_name(&self, file_name: &str) -> Option<&CatalogEntry> {
+        self.models.iter().find(|entry| entry.file_name == file_name)
+    }
+
+    /// Variants of one provider and size, smallest RAM need first
+    pub fn variants(&self, provider_type: &str, model_size: &str) -> Vec<&CatalogEntry> {
+        let mut variants: Vec<&CatalogEntry> = self
+            .models
+            .iter()
+            .filter(|entry| entry.provider_type == provider_type && entry.model_size == model_size)
+            .collect();
+        variants.sort_by_key(|entry| entry.min_ram_mb);
+        variants
+    }
+
+    /// Largest variant that fits into the available memory, preferring `provider_type`;
+    /// another provider's variant is only used if none of the preferred one fits.
+    pub fn select(&self, profile: &HardwareProfile, provider_type: &str) -> Option<&CatalogEntry> {
+        let fitting = |preferred: bool| {
+            self.models
+                .iter()
+                .filter(move |entry| (entry.provider_type == provider_type) == preferred)
+                .filter(|entry| entry.min_ram_mb <= profile.available_memory_mb)
+                .max_by_key(|entry| entry.min_ram_mb)
+        };
+        fitting(true).or_else(|| fitting(false))
+    }
+}
@@ -1,8 +1,15 @@
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
 use thiserror::Error;
+use tokio::io::AsyncWriteExt;
 use tracing::{info, warn, error};
 
+use crate::llm::model_catalog::CatalogEntry;
+
 #[derive(Debug, Error)]
 pub enum DownloadError {
     #[error("Failed to download model: {0}")]
@@ -13,6 +20,16 @@ pub enum DownloadError {
     ModelNotFound(String),
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+    #[error("Tokenizer error: {0}")]
+    TokenizerError(#[from] runar::TokenizerError),
+    #[error("Checksum mismatch for {file}: expected {expected}, got {actual}")]
+    ChecksumMismatch { file: String, expected: String, actual: String },
+    #[error("Not enough disk space: {needed} bytes needed, {available} available")]
+    InsufficientDiskSpace { needed: u64, available: u64 },
+    #[error("Models quota exceeded: {needed} more bytes do not fit into {quota}")]
+    QuotaExceeded { needed: u64, quota: u64 },
+    #[error("Already downloading: {0}")]
+    AlreadyDownloading(String),
 }
 
 /// Model source for downloading
@@ -95,9 +112,56 @@ impl DownloadProgress {
     }
 }
 
+/// Free space kept on the disk after a download
+const DISK_SPACE_MARGIN_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Suffix of incomplete downloads; they are resumed on the next attempt
+const PART_SUFFIX: &str = ".part";
+
+/// Progress callback, called after every received chunk
+pub type ProgressCallback = Arc<dyn Fn(&DownloadProgress) + Send + Sync>;
+
+/// Output paths currently being downloaded in this process
+fn downloads_in_progress() -> &'static Mutex<HashSet<PathBuf>> {
+    static IN_PROGRESS: OnceLock<Mutex<HashSet<PathBuf>>> = OnceLock::new();
+    IN_PROGRESS.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// Marks an output path as being downloaded until dropped
+struct DownloadGuard {
+    path: PathBuf,
+}
+
+impl DownloadGuard {
+    fn acquire(path: &Path) -> Result<Self, DownloadError> {
+        let mut in_progress = downloads_in_progress().lock().unwrap_or_else(|e| e.into_inner());
+        if !in_progress.insert(path.to_path_buf()) {
+            return Err(DownloadError::AlreadyDownloading(path.display().to_string()));
+        }
+        Ok(Self { path: path.to_path_buf() })
+    }
+}
+
+impl Drop for DownloadGuard {
+    fn drop(&mut self) {
+        downloads_in_progress()
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.path);
+    }
+}
+
 /// Model downloader for automatic model downloads
+///
+/// Downloads go to `<name>.part` and are resumed with HTTP range requests after an
+/// interruption. A finished file is checked against the expected size and SHA-256 and only
+/// then renamed to its final name, so a model file in the directory is always complete.
 pub struct ModelDownloader {
     models_dir: PathBuf,
+    client: reqwest::Client,
+    /// Maximum total size of the models directory; least recently used models are removed
+    quota_bytes: Option<u64>,
+    progress: Option<ProgressCallback>,
 }
 
 impl ModelDownloader {
@@ -117,8 +181,22 @@ impl ModelDownloader {
         
         Ok(Self {
             models_dir: path,
+            client: reqwest::Client::new(),
+            quota_bytes: None,
+            progress: None,
         })
     }
+
+    /// Limit the total size of the models directory
+    pub fn with_quota_mb(mut self, quota_mb: u64) -> Self {
+        self.quota_bytes = Some(quota_mb * 1024 * 1024);
+        self
+    }
+
+    pub fn with_progress_callback(mut self, callback: ProgressCallback) -> Self {
+        self.progress = Some(callback);
+        self
+    }
     
     /// Check if a model exists locally
     pub fn check_model_exists(&self, model_name: &str) -> bool {
@@ -130,6 +208,16 @@ impl ModelDownloader {
     pub fn get_model_path(&self, model_name: &str) -> PathBuf {
         self.models_dir.join(model_name)
     }
+
+    /// Get the models directory
+    pub fn models_dir(&self) -> &Path {
+        &self.models_dir
+    }
+
+    /// Load tokenizer vocabularies (`tokenizer.json`) shipped next to the models
+    pub fn load_tokenizers(&self, registry: &mut runar::TokenizerRegistry) -> Result<usize, DownloadError> {
+        Ok(registry.load_dir(&self.models_dir)?)
+    }
     
     /// List all models in the directory
     pub async fn list_models(&self) -> Result<Vec<String>, DownloadError> {
@@ -158,6 +246,15 @@ impl ModelDownloader {
         
         Ok(models)
     }
+
+    /// Record that a model was used (its modification time orders the quota cleanup)
+    pub fn mark_used(&self, model_name: &str) -> Result<(), DownloadError> {
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .open(self.models_dir.join(model_name))?;
+        file.set_modified(SystemTime::now())?;
+        Ok(())
+    }
     
     /// Download a model from a source
     /// 
@@ -166,29 +263,222 @@ impl ModelDownloader {
     /// * `source` - Model source to download from
     /// * `output_name` - Name for the downloaded file
     /// 
-    /// # Note
-    /// 
-    /// This is currently a stub. Real implementation would use reqwest
-    /// with progress tracking.
+    /// The download is not verified; use `download_entry` for catalog models with checksum.
     pub async fn download(
         &self,
         source: ModelSource,
         output_name: &str,
     ) -> Result<PathBuf, DownloadError> {
         source.validate()?;
-        
-        let url = source.get_url();
+        self.fetch(&source.get_url(), output_name, None, None).await
+    }
+
+    /// Download a catalog model and verify its size and SHA-256.
+    ///
+    /// Returns immediately if the file already exists with the expected size.
+    pub async fn download_entry(&self, entry: &CatalogEntry) -> Result<PathBuf, DownloadError> {
+        let source = entry.source();
+        source.validate()?;
+        let output_path = self.get_model_path(&entry.file_name);
+        if let Ok(metadata) = std::fs::metadata(&output_path) {
+            if entry.size_bytes.is_none_or(|size| size == metadata.len()) {
+                info!("Model {} already exists, skipping download", entry.file_name);
+                return Ok(output_path);
+            }
+            warn!(
+                "Model {} has {} bytes instead of {:?}, downloading again",
+                entry.file_name,
+                metadata.len(),
+                entry.size_bytes
+            );
+        }
+        if entry.sha256.is_none() {
+            warn!("Catalog entry {} has no sha256, download is not verified", entry.id);
+        }
+        self.fetch(&source.get_url(), &entry.file_name, entry.sha256.as_deref(), entry.size_bytes)
+            .await
+    }
+
+    async fn fetch(
+        &self,
+        url: &str,
+        output_name: &str,
+        sha256: Option<&str>,
+        expected_size: Option<u64>,
+    ) -> Result<PathBuf, DownloadError> {
+        if output_name.is_empty() || output_name.contains(['/', '\\']) {
+            return Err(DownloadError::InvalidSource(format!("Invalid output name: {}", output_name)));
+        }
         let output_path = self.models_dir.join(output_name);
-        
-        info!("Downloading model from {} to {:?}", url, output_path);
-        
-        // TODO: Implement actual download with reqwest
-        // For now, return an error indicating this is a stub
-        warn!("Model download is not yet implemented (stub)");
-        
-        Err(DownloadError::DownloadFailed(
-            "Download functionality not yet implemented. Please download models manually.".to_string()
-        ))
+        let part_path = self.models_dir.join(format!("{}{}", output_name, PART_SUFFIX));
+        let _guard = DownloadGuard::acquire(&output_path)?;
+
+        let mut offset = std::fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0);
+        if expected_size.is_some_and(|size| offset > size) {
+            warn!("Discarding oversized partial download {:?}", part_path);
+            std::fs::remove_file(&part_path)?;
+            offset = 0;
+        }
+
+        info!("Downloading model from {} to {:?} (resuming at {} bytes)", url, output_path, offset);
+        let mut request = self.client.get(url);
+        if offset > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| DownloadError::DownloadFailed(format!("{}: {}", url, e)))?;
+
+        let status = response.status();
+        let complete = if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && offset > 0 {
+            // Nothing left to fetch; the partial file is verified below
+            true
+        } else if status == reqwest::StatusCode::PARTIAL_CONTENT && offset > 0 {
+            false
+        } else if status.is_success() {
+            if offset > 0 {
+                info!("Server does not support resuming, restarting download of {}", output_name);
+                offset = 0;
+            }
+            false
+        } else {
+            return Err(DownloadError::DownloadFailed(format!("{}: HTTP {}", url, status)));
+        };
+
+        if !complete {
+            let total = expected_size.or_else(|| response.content_length().map(|len| len + offset));
+            let remaining = total.map_or(0, |total| total.saturating_sub(offset));
+            self.ensure_space(output_name, remaining)?;
+
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .write(true)
+                .append(offset > 0)
+                .truncate(offset == 0)
+                .open(&part_path)
+                .await?;
+            let mut progress = DownloadProgress::new(total.unwrap_or(0));
+            let mut downloaded = offset;
+            let mut stream = response.bytes_stream();
+            while let Some(chunk) = stream.next().await {
+                let chunk = chunk.map_err(|e| {
+                    DownloadError::DownloadFailed(format!("{} interrupted at {} bytes: {}", url, downloaded, e))
+                })?;
+                file.write_all(&chunk).await?;
+                downloaded += chunk.len() as u64;
+                progress.update(downloaded);
+                if let Some(callback) = &self.progress {
+                    callback(&progress);
+                }
+            }
+            file.flush().await?;
+            file.sync_all().await?;
+        }
+
+        let size = std::fs::metadata(&part_path)?.len();
+        if let Some(expected) = expected_size {
+            if size < expected {
+                return Err(DownloadError::DownloadFailed(format!(
+                    "{} incomplete: {} of {} bytes, will resume",
+                    output_name, size, expected
+                )));
+            }
+            if size > expected {
+                std::fs::remove_file(&part_path)?;
+                return Err(DownloadError::DownloadFailed(format!(
+                    "{} has {} bytes, expected {}",
+                    output_name, size, expected
+                )));
+            }
+        }
+        if let Some(expected) = sha256 {
+            let actual = file_sha256(&part_path).await?;
+            if !actual.eq_ignore_ascii_case(expected) {
+                std::fs::remove_file(&part_path)?;
+                return Err(DownloadError::ChecksumMismatch {
+                    file: output_name.to_string(),
+                    expected: expected.to_string(),
+                    actual,
+                });
+            }
+        }
+
+        std::fs::rename(&part_path, &output_path)?;
+        info!("Model {} downloaded ({} bytes)", output_name, size);
+        Ok(output_path)
+    }
+
+    /// Free disk space and quota for `needed_bytes` more
+    fn ensure_space(&self, output_name: &str, needed_bytes: u64) -> Result<(), DownloadError> {
+        if self.quota_bytes.is_some() {
+            self.cleanup_unused(&[output_name], needed_bytes)?;
+        }
+        if let Some(available) = self.available_disk_space() {
+            if needed_bytes + DISK_SPACE_MARGIN_BYTES > available {
+                return Err(DownloadError::InsufficientDiskSpace {
+                    needed: needed_bytes,
+                    available,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Free space on the disk holding the models directory (`None` if unknown)
+    fn available_disk_space(&self) -> Option<u64> {
+        let dir = self.models_dir.canonicalize().ok()?;
+        let disks = sysinfo::Disks::new_with_refreshed_list();
+        disks
+            .list()
+            .iter()
+            .filter(|disk| dir.starts_with(disk.mount_point()))
+            .max_by_key(|disk| disk.mount_point().as_os_str().len())
+            .map(|disk| disk.available_space())
+    }
+
+    /// Remove least recently used models until `needed_bytes` more fit into the quota.
+    ///
+    /// Models in `keep` and partial downloads are never removed. Returns the removed files.
+    pub fn cleanup_unused(&self, keep: &[&str], needed_bytes: u64) -> Result<Vec<PathBuf>, DownloadError> {
+        let Some(quota) = self.quota_bytes else {
+            return Ok(Vec::new());
+        };
+        let mut used = 0u64;
+        let mut candidates = Vec::new();
+        for entry in std::fs::read_dir(&self.models_dir)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if !metadata.is_file() {
+                continue;
+            }
+            used += metadata.len();
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let is_model = name.ends_with(".gguf") || name.ends_with(".bitnet");
+            if is_model && !keep.contains(&name.as_str()) {
+                let last_used = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                candidates.push((last_used, entry.path(), metadata.len()));
+            }
+        }
+        candidates.sort_by_key(|(last_used, _, _)| *last_used);
+
+        let mut removed = Vec::new();
+        for (_, path, size) in candidates {
+            if used + needed_bytes <= quota {
+                break;
+            }
+            info!("Removing unused model {:?} to stay within the models quota", path);
+            std::fs::remove_file(&path)?;
+            used -= size;
+            removed.push(path);
+        }
+        if used + needed_bytes > quota {
+            return Err(DownloadError::QuotaExceeded {
+                needed: needed_bytes,
+                quota,
+            });
+        }
+        Ok(removed)
     }
     
     /// Get recommended model source for a given size and provider
@@ -251,16 +541,26 @@ impl ModelDownloader {
                 format!("No recommended source for {} {} model", provider_type, model_size)
             ))?;
         
-        // Download
-        warn!("Model {} not found locally. Automatic download not yet implemented.", filename);
-        warn!("Please download manually from: {}", source.get_url());
-        
-        Err(DownloadError::ModelNotFound(
-            format!("Model {} not found. Please download from: {}", filename, source.get_url())
-        ))
+        warn!("Model {} not found locally, downloading unverified from {}", filename, source.get_url());
+        self.download(source, &filename).await
     }
 }
 
+/// SHA-256 (hex) of a file, hashed off the async runtime
+async fn file_sha256(path: &Path) -> Result<String, DownloadError> {
+    let path = path.to_path_buf();
+    tokio::task::spawn_blocking(move || -> std::io::Result<String> {
+        use sha2::{Digest, Sha256};
+        let mut file = std::fs::File::open(path)?;
+        let mut hasher = Sha256::new();
+        std::io::copy(&mut file, &mut hasher)?;
+        Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+    })
+    .await
+    .map_err(|e| DownloadError::DownloadFailed(format!("Checksum task failed: {}", e)))?
+    .map_err(DownloadError::from)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
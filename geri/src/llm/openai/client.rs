@@ -1,8 +1,15 @@
 //! OpenAI API Client for LLM and Vision requests
 
-use serde::{Deserialize, Serialize};
+use std::pin::Pin;
+
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Deserializer, Serialize};
 use thiserror::Error;
 
+use crate::llm::messages::{ChatMessage as ConversationMessage, ChatRole};
+use crate::llm::structured::ResponseFormat;
+use crate::llm::tools::{parse_arguments, ToolCall, ToolDefinition, ToolResult};
+
 #[derive(Debug, Clone)]
 pub struct OpenAIConfig {
     pub api_key: String,
@@ -23,7 +30,104 @@ pub enum OpenAIError {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
     pub role: String,
+    /// `null` for assistant messages that only carry tool calls
+    #[serde(default, deserialize_with = "null_as_empty")]
     pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<OpenAIToolCall>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+}
+
+impl From<&ConversationMessage> for ChatMessage {
+    fn from(message: &ConversationMessage) -> Self {
+        Self {
+            role: message.role.as_str().to_string(),
+            content: message.content.clone(),
+            // OpenAI takes the tool name from the referenced call, not from `name`
+            name: if message.role == ChatRole::Tool { None } else { message.name.clone() },
+            tool_calls: if message.tool_calls.is_empty() {
+                None
+            } else {
+                Some(message.tool_calls.iter().map(OpenAIToolCall::from).collect())
+            },
+            tool_call_id: message.tool_call_id.clone(),
+        }
+    }
+}
+
+fn null_as_empty<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Entry of the request `tools` array
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAITool {
+    #[serde(rename = "type")]
+    pub tool_type: String, // "function"
+    pub function: OpenAIFunction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAIFunction {
+    pub name: String,
+    pub description: String,
+    pub parameters: serde_json::Value,
+}
+
+/// Request `response_format` for structured output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAIResponseFormat {
+    #[serde(rename = "type")]
+    pub format_type: String, // "json_schema"
+    pub json_schema: OpenAIJsonSchema,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAIJsonSchema {
+    pub name: String,
+    pub schema: serde_json::Value,
+    pub strict: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAIToolCall {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub call_type: String, // "function"
+    pub function: OpenAIFunctionCall,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenAIFunctionCall {
+    pub name: String,
+    /// JSON-encoded arguments
+    pub arguments: String,
+}
+
+impl From<&ToolCall> for OpenAIToolCall {
+    fn from(call: &ToolCall) -> Self {
+        Self {
+            id: call.id.clone(),
+            call_type: "function".to_string(),
+            function: OpenAIFunctionCall {
+                name: call.name.clone(),
+                arguments: call.arguments.to_string(),
+            },
+        }
+    }
+}
+
+impl From<&OpenAIToolCall> for ToolCall {
+    fn from(call: &OpenAIToolCall) -> Self {
+        Self {
+            id: call.id.clone(),
+            name: call.function.name.clone(),
+            arguments: parse_arguments(&call.function.arguments),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,6 +138,19 @@ pub struct ChatRequest {
     pub max_tokens: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<StreamOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<OpenAITool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_format: Option<OpenAIResponseFormat>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamOptions {
+    pub include_usage: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +173,52 @@ pub struct Usage {
     pub total_tokens: u32,
 }
 
+/// One `chat.completion.chunk` event of a streamed chat completion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatStreamChunk {
+    #[serde(default)]
+    pub choices: Vec<ChatStreamChoice>,
+    #[serde(default)]
+    pub usage: Option<Usage>,
+}
+
+/// Stream of parsed chunks returned by [`OpenAIClient::chat_completion_stream`]
+pub type ChatStream = Pin<Box<dyn Stream<Item = Result<ChatStreamChunk, OpenAIError>> + Send>>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatStreamChoice {
+    #[serde(default)]
+    pub delta: ChatDelta,
+    #[serde(default)]
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChatDelta {
+    #[serde(default)]
+    pub content: Option<String>,
+    #[serde(default)]
+    pub tool_calls: Option<Vec<ToolCallDelta>>,
+}
+
+/// Fragment of a streamed tool call; fragments with the same `index` are concatenated
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolCallDelta {
+    pub index: usize,
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub function: Option<FunctionCallDelta>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FunctionCallDelta {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub arguments: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VisionRequest {
     pub model: String,
@@ -84,9 +247,39 @@ pub struct ImageUrl {
     pub url: String,
 }
 
+/// Request body of `/embeddings`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingsRequest {
+    pub model: String,
+    pub input: Vec<String>,
+    /// Shortens the vectors (text-embedding-3 models only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimensions: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmbeddingsResponse {
+    pub data: Vec<EmbeddingData>,
+    #[serde(default)]
+    pub usage: Option<EmbeddingsUsage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmbeddingData {
+    pub index: usize,
+    pub embedding: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmbeddingsUsage {
+    pub prompt_tokens: u32,
+    pub total_tokens: u32,
+}
+
 pub struct OpenAIClient {
     config: OpenAIConfig,
     client: reqwest::Client,
+    live_key: Option<crate::keys::LiveApiKey>,
 }
 
 impl OpenAIClient {
@@ -96,7 +289,21 @@ impl OpenAIClient {
             .build()
             .expect("Failed to build HTTP client");
         
-        Self { config, client }
+        Self { config, client, live_key: None }
+    }
+
+    /// Reads the API key from `live_key` on every request, so rotated keys apply without a restart
+    pub fn with_live_key(mut self, live_key: crate::keys::LiveApiKey) -> Self {
+        self.live_key = Some(live_key);
+        self
+    }
+
+    /// The current API key: the live key if set, otherwise the configured one
+    pub fn api_key(&self) -> String {
+        match &self.live_key {
+            Some(live_key) => live_key.get(),
+            None => self.config.api_key.clone(),
+        }
     }
 
     pub fn base_url(&self) -> &str {
@@ -104,10 +311,11 @@ impl OpenAIClient {
     }
 
     pub async fn validate(&self) -> Result<(), OpenAIError> {
-        if self.config.api_key.is_empty() {
+        let api_key = self.api_key();
+        if api_key.is_empty() {
             return Err(OpenAIError::InvalidConfig("API key is empty".to_string()));
         }
-        if !self.config.api_key.starts_with("sk-") {
+        if !api_key.starts_with("sk-") {
             return Err(OpenAIError::InvalidConfig("API key must start with 'sk-'".to_string()));
         }
         Ok(())
@@ -120,6 +328,24 @@ impl OpenAIClient {
         system_prompt: Option<&str>,
         context: Option<&str>,
         max_tokens: Option<u32>,
+    ) -> ChatRequest {
+        self.build_conversation_request(
+            model,
+            &[ConversationMessage::user(prompt)],
+            system_prompt,
+            context,
+            max_tokens,
+        )
+    }
+
+    /// Chat request for a multi-turn conversation; system prompt and RAG context become the leading system message
+    pub fn build_conversation_request(
+        &self,
+        model: &str,
+        conversation: &[ConversationMessage],
+        system_prompt: Option<&str>,
+        context: Option<&str>,
+        max_tokens: Option<u32>,
     ) -> ChatRequest {
         let mut messages = Vec::new();
 
@@ -139,20 +365,90 @@ impl OpenAIClient {
             messages.push(ChatMessage {
                 role: "system".to_string(),
                 content: system_content,
+                name: None,
+                tool_calls: None,
+                tool_call_id: None,
             });
         }
         
-        messages.push(ChatMessage {
-            role: "user".to_string(),
-            content: prompt.to_string(),
-        });
+        messages.extend(conversation.iter().map(ChatMessage::from));
         
         ChatRequest {
             model: model.to_string(),
             messages,
             max_tokens,
             temperature: None,
+            stream: None,
+            stream_options: None,
+            tools: None,
+            response_format: None,
+        }
+    }
+
+    /// Add tool declarations and the previous tool-call round (assistant `tool_calls`
+    /// message followed by one `tool` message per result) to a chat request
+    pub fn apply_tools(
+        &self,
+        request: &mut ChatRequest,
+        tools: &[ToolDefinition],
+        tool_calls: &[ToolCall],
+        tool_results: &[ToolResult],
+    ) {
+        if !tools.is_empty() {
+            request.tools = Some(
+                tools
+                    .iter()
+                    .map(|tool| OpenAITool {
+                        tool_type: "function".to_string(),
+                        function: OpenAIFunction {
+                            name: tool.name.clone(),
+                            description: tool.description.clone(),
+                            parameters: tool.parameters.clone(),
+                        },
+                    })
+                    .collect(),
+            );
         }
+        if !tool_calls.is_empty() {
+            request.messages.push(ChatMessage {
+                role: "assistant".to_string(),
+                content: String::new(),
+                name: None,
+                tool_calls: Some(tool_calls.iter().map(OpenAIToolCall::from).collect()),
+                tool_call_id: None,
+            });
+        }
+        for result in tool_results {
+            request.messages.push(ChatMessage {
+                role: "tool".to_string(),
+                content: result.content.clone(),
+                name: None,
+                tool_calls: None,
+                tool_call_id: Some(result.tool_call_id.clone()),
+            });
+        }
+    }
+
+    /// Constrain the answer to the JSON Schema (`response_format: json_schema`)
+    pub fn apply_response_format(&self, request: &mut ChatRequest, response_format: Option<&ResponseFormat>) {
+        request.response_format = response_format.map(|format| OpenAIResponseFormat {
+            format_type: "json_schema".to_string(),
+            json_schema: OpenAIJsonSchema {
+                name: format.name.clone(),
+                schema: format.schema.clone(),
+                strict: format.strict,
+            },
+        });
+    }
+
+    /// Parse a single SSE payload of a streamed chat completion; `None` marks the `[DONE]` sentinel.
+    pub fn parse_stream_event(data: &str) -> Result<Option<ChatStreamChunk>, OpenAIError> {
+        if data.trim() == "[DONE]" {
+            return Ok(None);
+        }
+        serde_json::from_str(data)
+            .map(Some)
+            .map_err(|e| OpenAIError::RequestFailed(format!("Failed to parse stream chunk: {}", e)))
     }
 
     pub fn build_vision_request(
@@ -193,7 +489,7 @@ impl OpenAIClient {
         let response = self
             .client
             .post(&url)
-            .header("Authorization", format!("Bearer {}", self.config.api_key))
+            .header("Authorization", format!("Bearer {}", self.api_key()))
             .header("Content-Type", "application/json")
             .json(&request)
             .send()
@@ -212,13 +508,74 @@ impl OpenAIClient {
             .map_err(|e| OpenAIError::RequestFailed(format!("Failed to parse response: {}", e)))
     }
 
+    /// Send a chat request with `stream: true` and yield the completion chunks as they arrive.
+    pub async fn chat_completion_stream(
+        &self,
+        mut request: ChatRequest,
+    ) -> Result<ChatStream, OpenAIError> {
+        request.stream = Some(true);
+        request.stream_options = Some(StreamOptions { include_usage: true });
+        let url = format!("{}/chat/completions", self.config.base_url);
+
+        let response = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", self.api_key()))
+            .header("Content-Type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| OpenAIError::RequestFailed(e.to_string()))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(OpenAIError::APIError(format!("HTTP {}: {}", status, error_text)));
+        }
+
+        let chunks = crate::streaming::sse_events(response)
+            .map(|event| {
+                event
+                    .map_err(|e| OpenAIError::RequestFailed(e.to_string()))
+                    .and_then(|data| Self::parse_stream_event(&data))
+            })
+            .take_while(|chunk| futures::future::ready(!matches!(chunk, Ok(None))))
+            .filter_map(|chunk| futures::future::ready(chunk.transpose()));
+        Ok(Box::pin(chunks))
+    }
+
+    pub async fn embeddings(&self, request: EmbeddingsRequest) -> Result<EmbeddingsResponse, OpenAIError> {
+        let url = format!("{}/embeddings", self.config.base_url);
+
+        let response = self
+            .client
+            .post(&url)
+            .header("Authorization", format!("Bearer {}", self.api_key()))
+            .header("Content-Type", "application/json")
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| OpenAIError::RequestFailed(e.to_string()))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(OpenAIError::APIError(format!("HTTP {}: {}", status, error_text)));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| OpenAIError::RequestFailed(format!("Failed to parse response: {}", e)))
+    }
+
     pub async fn vision_completion(&self, request: VisionRequest) -> Result<ChatResponse, OpenAIError> {
         let url = format!("{}/chat/completions", self.config.base_url);
         
         let response = self
             .client
             .post(&url)
-            .header("Authorization", format!("Bearer {}", self.config.api_key))
+            .header("Authorization", format!("Bearer {}", self.api_key()))
             .header("Content-Type", "application/json")
             .json(&request)
             .send()
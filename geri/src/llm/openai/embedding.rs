@@ -0,0 +1,61 @@
+//! OpenAI embeddings (`/v1/embeddings`) implementing EmbeddingProvider
+
+use async_trait::async_trait;
+
+use super::client::{EmbeddingsRequest, OpenAIClient, OpenAIConfig};
+use crate::llm::{EmbeddingProvider, EmbeddingResponse, LLMError};
+
+pub struct OpenAIEmbeddingProvider {
+    client: OpenAIClient,
+    model_name: String,
+    dimensions: Option<u32>,
+}
+
+impl OpenAIEmbeddingProvider {
+    pub fn new(config: OpenAIConfig, model_name: String) -> Self {
+        Self {
+            client: OpenAIClient::new(config),
+            model_name,
+            dimensions: None,
+        }
+    }
+
+    /// Requests shortened vectors (text-embedding-3 models only)
+    pub fn with_dimensions(mut self, dimensions: u32) -> Self {
+        self.dimensions = Some(dimensions);
+        self
+    }
+
+    /// Uses the live API key, which key rotation swaps without a restart
+    pub fn with_live_key(mut self, live_key: crate::keys::LiveApiKey) -> Self {
+        self.client = self.client.with_live_key(live_key);
+        self
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAIEmbeddingProvider {
+    fn model_name(&self) -> &str {
+        &self.model_name
+    }
+
+    async fn embed(&self, inputs: &[String]) -> Result<EmbeddingResponse, LLMError> {
+        let request = EmbeddingsRequest {
+            model: self.model_name.clone(),
+            input: inputs.to_vec(),
+            dimensions: self.dimensions,
+        };
+        let mut response = self
+            .client
+            .embeddings(request)
+            .await
+            .map_err(|e| LLMError::ProcessingFailed(e.to_string()))?;
+
+        // The API does not guarantee input order in `data`
+        response.data.sort_by_key(|data| data.index);
+        Ok(EmbeddingResponse {
+            embeddings: response.data.into_iter().map(|data| data.embedding).collect(),
+            tokens_used: response.usage.map(|usage| usage.total_tokens).unwrap_or(0),
+        })
+    }
+}
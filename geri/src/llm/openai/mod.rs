@@ -1,5 +1,7 @@
 pub mod client;
+pub mod embedding;
 pub mod provider;
 
-pub use client::{OpenAIClient, OpenAIConfig, OpenAIError, ChatRequest, ChatMessage, VisionRequest};
+pub use client::{OpenAIClient, OpenAIConfig, OpenAIError, ChatRequest, ChatMessage, ChatStream, ChatStreamChunk, VisionRequest, EmbeddingsRequest, EmbeddingsResponse};
+pub use embedding::OpenAIEmbeddingProvider;
 pub use provider::OpenAILLMProvider;
@@ -1,8 +1,12 @@
 //! OpenAI LLM Provider implementing LLMProvider trait
 
+use std::collections::BTreeMap;
+
 use async_trait::async_trait;
-use super::client::{OpenAIClient, OpenAIConfig};
-use crate::llm::{LLMError, LLMProvider, PromptRequest, PromptResponse};
+use futures::StreamExt;
+use super::client::{ChatRequest, OpenAIClient, OpenAIConfig};
+use crate::llm::tools::parse_arguments;
+use crate::llm::{LLMError, LLMProvider, PromptRequest, PromptResponse, PromptStream, PromptStreamChunk, ToolCall};
 
 pub struct OpenAILLMProvider {
     client: OpenAIClient,
@@ -14,6 +18,25 @@ impl OpenAILLMProvider {
         let client = OpenAIClient::new(config);
         Self { client, model_name }
     }
+
+    /// Uses the live API key, which key rotation swaps without a restart
+    pub fn with_live_key(mut self, live_key: crate::keys::LiveApiKey) -> Self {
+        self.client = self.client.with_live_key(live_key);
+        self
+    }
+
+    fn build_request(&self, request: &PromptRequest) -> ChatRequest {
+        let mut chat_request = self.client.build_conversation_request(
+            &self.model_name,
+            &request.conversation(),
+            request.system_prompt.as_deref(),
+            request.context.as_deref(),
+            request.max_tokens,
+        );
+        self.client.apply_tools(&mut chat_request, &request.tools, &request.tool_calls, &request.tool_results);
+        self.client.apply_response_format(&mut chat_request, request.response_format.as_ref());
+        chat_request
+    }
 }
 
 #[async_trait]
@@ -24,13 +47,7 @@ impl LLMProvider for OpenAILLMProvider {
 
     async fn process_prompt(&self, request: PromptRequest) -> Result<PromptResponse, LLMError> {
         // Build OpenAI chat request
-        let chat_request = self.client.build_chat_request(
-            &self.model_name,
-            &request.prompt,
-            request.system_prompt.as_deref(),
-            request.context.as_deref(),
-            request.max_tokens,
-        );
+        let chat_request = self.build_request(&request);
 
         // Send request to OpenAI API
         let response = self
@@ -39,16 +56,87 @@ impl LLMProvider for OpenAILLMProvider {
             .await
             .map_err(|e| LLMError::ProcessingFailed(e.to_string()))?;
 
-        // Extract response text from first choice
-        let text = response
+        // Extract response text and tool calls from first choice
+        let message = response
             .choices
             .first()
-            .map(|choice| choice.message.content.clone())
+            .map(|choice| &choice.message)
             .ok_or_else(|| LLMError::ProcessingFailed("No choices in response".to_string()))?;
 
+        let tool_calls = message
+            .tool_calls
+            .as_ref()
+            .map(|calls| calls.iter().map(ToolCall::from).collect())
+            .unwrap_or_default();
+
         Ok(PromptResponse {
-            text,
+            text: message.content.clone(),
             tokens_used: response.usage.total_tokens,
+            tool_calls,
+            structured: None,
+            safety: Vec::new(),
         })
     }
+
+    async fn stream_prompt(&self, request: PromptRequest) -> Result<PromptStream, LLMError> {
+        let chat_request = self.build_request(&request);
+
+        let mut chunks = self
+            .client
+            .chat_completion_stream(chat_request)
+            .await
+            .map_err(|e| LLMError::ProcessingFailed(e.to_string()))?;
+
+        let (tx, rx) = tokio::sync::mpsc::channel(64);
+        tokio::spawn(async move {
+            let mut tokens_used = 0;
+            let mut finish_reason = "stop".to_string();
+            // Tool calls arrive as fragments keyed by index: (id, name, arguments)
+            let mut pending_calls: BTreeMap<usize, (String, String, String)> = BTreeMap::new();
+            while let Some(chunk) = chunks.next().await {
+                let chunk = match chunk {
+                    Ok(chunk) => chunk,
+                    Err(e) => {
+                        let _ = tx.send(Err(LLMError::ProcessingFailed(e.to_string()))).await;
+                        return;
+                    }
+                };
+                if let Some(usage) = chunk.usage {
+                    tokens_used = usage.total_tokens;
+                }
+                for choice in chunk.choices {
+                    if let Some(reason) = choice.finish_reason {
+                        finish_reason = reason;
+                    }
+                    for fragment in choice.delta.tool_calls.unwrap_or_default() {
+                        let entry = pending_calls.entry(fragment.index).or_default();
+                        if let Some(id) = fragment.id {
+                            entry.0 = id;
+                        }
+                        if let Some(function) = fragment.function {
+                            entry.1.push_str(function.name.as_deref().unwrap_or(""));
+                            entry.2.push_str(function.arguments.as_deref().unwrap_or(""));
+                        }
+                    }
+                    if let Some(text) = choice.delta.content.filter(|t| !t.is_empty()) {
+                        // Receiver gone means the caller cancelled; dropping `chunks` closes the HTTP stream
+                        if tx.send(Ok(PromptStreamChunk::Delta(text))).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+            for (id, name, arguments) in pending_calls.into_values() {
+                let call = ToolCall { id, name, arguments: parse_arguments(&arguments) };
+                if tx.send(Ok(PromptStreamChunk::ToolCall(call))).await.is_err() {
+                    return;
+                }
+            }
+            let _ = tx
+                .send(Ok(PromptStreamChunk::Done { tokens_used, finish_reason, safety: Vec::new() }))
+                .await;
+        });
+
+        Ok(Box::pin(tokio_stream::wrappers::ReceiverStream::new(rx)))
+    }
 }
@@ -1,27 +1,95 @@
+use std::pin::Pin;
+
 use async_trait::async_trait;
+use futures::Stream;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+use super::messages::ChatMessage;
+use super::structured::ResponseFormat;
+use super::tools::{ToolCall, ToolDefinition, ToolResult};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct PromptRequest {
     pub prompt: String,
     pub system_prompt: Option<String>,
     pub context: Option<String>,
     pub max_tokens: Option<u32>,
+    /// Earlier conversation turns, oldest first; `prompt` is the current user turn
+    #[serde(default)]
+    pub messages: Vec<ChatMessage>,
+    /// Tools the model may call; empty disables tool calling
+    #[serde(default)]
+    pub tools: Vec<ToolDefinition>,
+    /// Tool calls the model made in its previous response, answered by `tool_results`
+    #[serde(default)]
+    pub tool_calls: Vec<ToolCall>,
+    #[serde(default)]
+    pub tool_results: Vec<ToolResult>,
+    /// User the request is made for; isolates cached responses, never sent to providers
+    #[serde(default)]
+    pub user_id: Option<String>,
+    /// Device the request comes from; only used for budgets and the usage ledger
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// Scheduling priority in front of local providers (voice before chat before background work)
+    #[serde(default)]
+    pub priority: crate::queue::RequestPriority,
+    /// Bypass the response cache for this request
+    #[serde(default)]
+    pub no_cache: bool,
+    /// JSON Schema the answer must match; see `structured::enforce_response_format`
+    #[serde(default)]
+    pub response_format: Option<ResponseFormat>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PromptResponse {
     pub text: String,
     pub tokens_used: u32,
+    /// Tool calls requested by the model; the caller executes them and sends the results back
+    #[serde(default)]
+    pub tool_calls: Vec<ToolCall>,
+    /// The parsed answer, set once it validated against the request's `response_format`
+    #[serde(default)]
+    pub structured: Option<serde_json::Value>,
+    /// Decisions of the safety filters (redactions, flagged context and tool calls)
+    #[serde(default)]
+    pub safety: Vec<crate::safety::FilterDecision>,
+}
+
+/// A single frame of a streamed completion.
+///
+/// Providers emit any number of `Delta` frames followed by exactly one `Done` frame.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PromptStreamChunk {
+    /// Incremental text generated since the previous frame
+    Delta(String),
+    /// A complete tool call; emitted once its arguments have been fully received
+    ToolCall(ToolCall),
+    /// Final frame carrying usage, the provider's finish reason and, from the engine, the safety
+    /// filter decisions (as in `PromptResponse::safety`)
+    Done {
+        tokens_used: u32,
+        finish_reason: String,
+        #[serde(default)]
+        safety: Vec<crate::safety::FilterDecision>,
+    },
 }
 
+/// Stream of completion frames returned by [`LLMProvider::stream_prompt`].
+pub type PromptStream = Pin<Box<dyn Stream<Item = Result<PromptStreamChunk, LLMError>> + Send>>;
+
 #[derive(Debug, Error)]
 pub enum LLMError {
     #[error("LLM processing failed: {0}")]
     ProcessingFailed(String),
     #[error("Model not available: {0}")]
     ModelNotAvailable(String),
+    #[error("LLM request timed out: {0}")]
+    Timeout(String),
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
 }
 
 #[async_trait]
@@ -29,6 +97,23 @@ pub trait LLMProvider: Send + Sync {
     fn model_name(&self) -> &str;
 
     async fn process_prompt(&self, request: PromptRequest) -> Result<PromptResponse, LLMError>;
+
+    /// Stream the completion as incremental text deltas plus a final usage frame.
+    ///
+    /// The default implementation runs `process_prompt` and emits the whole text as one delta,
+    /// so providers without native streaming still work with streaming callers.
+    async fn stream_prompt(&self, request: PromptRequest) -> Result<PromptStream, LLMError> {
+        let response = self.process_prompt(request).await?;
+        let finish_reason = if response.tool_calls.is_empty() { "stop" } else { "tool_calls" };
+        let mut chunks = vec![Ok(PromptStreamChunk::Delta(response.text))];
+        chunks.extend(response.tool_calls.into_iter().map(|call| Ok(PromptStreamChunk::ToolCall(call))));
+        chunks.push(Ok(PromptStreamChunk::Done {
+            tokens_used: response.tokens_used,
+            finish_reason: finish_reason.to_string(),
+            safety: response.safety,
+        }));
+        Ok(Box::pin(futures::stream::iter(chunks)))
+    }
 }
 
 pub struct LocalLLMProvider {
@@ -71,6 +156,9 @@ impl LLMProvider for LocalLLMProvider {
         Ok(PromptResponse {
             text: format!("[LLM Response from {} for: {}]", self.model_name, request.prompt),
             tokens_used: estimated_tokens,
+            tool_calls: Vec::new(),
+            structured: None,
+            safety: Vec::new(),
         })
     }
 }
@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+
+use super::provider::{LLMError, LLMProvider, PromptRequest, PromptResponse, PromptStream};
+use crate::queue::{RequestScheduler, SchedulerPermit};
+
+/// Admits requests to a local provider through the [`RequestScheduler`].
+///
+/// A slot is held while the provider works on the request; for streams until the stream is
+/// dropped. Dropping the returned future (e.g. because the gRPC client disconnected) leaves the
+/// queue or aborts the provider call and hands the slot to the next request.
+pub struct ScheduledProvider {
+    inner: Arc<dyn LLMProvider>,
+    scheduler: RequestScheduler,
+}
+
+impl ScheduledProvider {
+    pub fn new(inner: Arc<dyn LLMProvider>, scheduler: RequestScheduler) -> Self {
+        Self { inner, scheduler }
+    }
+
+    pub fn scheduler(&self) -> &RequestScheduler {
+        &self.scheduler
+    }
+
+    async fn admit(&self, request: &PromptRequest) -> Result<SchedulerPermit, LLMError> {
+        let model_id = self.inner.model_name();
+        let permit = self
+            .scheduler
+            .acquire(model_id, request.priority, request.user_id.as_deref())
+            .await
+            .map_err(|_| LLMError::ModelNotAvailable(format!("Request queue for {} is full", model_id)))?;
+        if !permit.waited().is_zero() {
+            tracing::debug!(
+                model = %model_id,
+                priority = request.priority.as_str(),
+                waited_ms = permit.waited().as_millis() as u64,
+                "Request waited for a local model slot"
+            );
+        }
+        Ok(permit)
+    }
+}
+
+#[async_trait]
+impl LLMProvider for ScheduledProvider {
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+
+    async fn process_prompt(&self, request: PromptRequest) -> Result<PromptResponse, LLMError> {
+        let _permit = self.admit(&request).await?;
+        self.inner.process_prompt(request).await
+    }
+
+    async fn stream_prompt(&self, request: PromptRequest) -> Result<PromptStream, LLMError> {
+        let permit = self.admit(&request).await?;
+        let stream = self.inner.stream_prompt(request).await?;
+        // The slot is released together with the provider stream
+        Ok(Box::pin(stream.map(move |chunk| {
+            let _ = &permit;
+            chunk
+        })))
+    }
+}
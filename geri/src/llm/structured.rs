@@ -0,0 +1,589 @@
+//! Structured output: JSON Schema response formats, validation of model answers against the
+//! schema with a bounded repair loop, and the provider mappings (Gemini `responseSchema`,
+//! llama.cpp GBNF grammars, prompt instructions for models without native support).
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::messages::ChatMessage;
+use super::provider::{LLMError, LLMProvider, PromptRequest, PromptResponse};
+
+/// Repair rounds after an answer failed validation
+pub const DEFAULT_MAX_REPAIR_ATTEMPTS: u32 = 2;
+
+/// `$ref` nesting followed when inlining schemas (recursive schemas are cut off here)
+const MAX_REF_DEPTH: usize = 16;
+
+/// A JSON Schema the model's answer must validate against
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponseFormat {
+    /// Schema name (OpenAI requires one: letters, digits, `_` and `-`)
+    pub name: String,
+    pub schema: Value,
+    /// Ask OpenAI for strict schema adherence; the schema must then list every property in
+    /// `required` and set `additionalProperties: false`
+    #[serde(default)]
+    pub strict: bool,
+}
+
+impl ResponseFormat {
+    pub fn new(name: impl Into<String>, schema: Value) -> Self {
+        Self {
+            name: name.into(),
+            schema,
+            strict: false,
+        }
+    }
+
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Parse the model's answer and validate it against the schema; the error lists every
+    /// violation (`$.path: message`).
+    pub fn parse(&self, text: &str) -> Result<Value, Vec<String>> {
+        let value = parse_json(text).map_err(|e| vec![format!("answer is not valid JSON: {}", e)])?;
+        self.validate(&value)?;
+        Ok(value)
+    }
+
+    /// Validate `value` against the schema.
+    ///
+    /// Supports `type`, `enum`, `const`, `properties`, `required`, `additionalProperties`,
+    /// `items`, `minItems`/`maxItems`, `minLength`/`maxLength`, `minimum`/`maximum` (also
+    /// exclusive), `anyOf`/`oneOf`/`allOf` and local `$ref`s; other keywords are ignored.
+    pub fn validate(&self, value: &Value) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        check(&self.schema, &self.schema, value, "$", 0, &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Instructions appended to the system prompt for models without native structured output.
+    pub fn render_instructions(&self) -> String {
+        format!(
+            "Reply with ONLY a JSON value that validates against the following JSON Schema, \
+             without explanations or code fences.\n\nSchema ({}):\n{}",
+            self.name, self.schema
+        )
+    }
+
+    /// Gemini `responseSchema`: the OpenAPI subset Gemini accepts, with `$ref`s inlined,
+    /// upper-case types and `nullable` instead of `null` types.
+    pub fn to_gemini_schema(&self) -> Value {
+        gemini_schema(&self.schema, &self.schema, 0)
+    }
+
+    /// GBNF grammar for llama.cpp's grammar sampler. Constructs without a grammar mapping
+    /// (e.g. `allOf`, string lengths) fall back to any JSON value of the right shape; the answer
+    /// is validated against the full schema afterwards.
+    pub fn to_gbnf(&self) -> String {
+        let mut grammar = Grammar::new(&self.schema);
+        let root = grammar.expr(&self.schema, "root", 0);
+        let mut out = format!("root ::= {}\n", root);
+        for (name, body) in &grammar.rules {
+            out.push_str(&format!("{} ::= {}\n", name, body));
+        }
+        out.push_str(GBNF_PRIMITIVES);
+        out
+    }
+}
+
+/// Validate the answer against `request.response_format` and store the parsed value in
+/// `structured`; invalid answers go back to `provider` together with the validation errors, at
+/// most `max_repair_attempts` times. Answers with tool calls are passed through unchecked (the
+/// final answer comes after the tool round). Token usage of all rounds is summed up.
+pub async fn enforce_response_format(
+    provider: &dyn LLMProvider,
+    request: &PromptRequest,
+    mut response: PromptResponse,
+    max_repair_attempts: u32,
+) -> Result<PromptResponse, LLMError> {
+    let Some(format) = &request.response_format else {
+        return Ok(response);
+    };
+    // The repair rounds continue the conversation, so the tool round becomes regular turns
+    let mut conversation = request.conversation();
+    if !request.tool_calls.is_empty() {
+        let mut call_turn = ChatMessage::assistant("");
+        call_turn.tool_calls = request.tool_calls.clone();
+        conversation.push(call_turn);
+    }
+    for result in &request.tool_results {
+        conversation.push(ChatMessage::tool(&result.tool_call_id, &result.name, result.content.clone()));
+    }
+    let mut attempt = 0;
+    loop {
+        if !response.tool_calls.is_empty() {
+            return Ok(response);
+        }
+        let errors = match format.parse(&response.text) {
+            Ok(value) => {
+                response.structured = Some(value);
+                return Ok(response);
+            }
+            Err(errors) => errors,
+        };
+        if attempt >= max_repair_attempts {
+            return Err(LLMError::ProcessingFailed(format!(
+                "answer does not match response schema '{}' after {} repair attempts: {}",
+                format.name,
+                max_repair_attempts,
+                errors.join("; ")
+            )));
+        }
+        attempt += 1;
+        tracing::debug!("Structured output invalid ({}), repair attempt {}", errors.join("; "), attempt);
+
+        conversation.push(ChatMessage::assistant(response.text.clone()));
+        conversation.push(ChatMessage::user(render_repair_prompt(&errors)));
+        let repair = PromptRequest {
+            prompt: String::new(),
+            messages: conversation.clone(),
+            tool_calls: Vec::new(),
+            tool_results: Vec::new(),
+            ..request.clone()
+        };
+        let tokens_used = response.tokens_used;
+        response = provider.process_prompt(repair).await?;
+        response.tokens_used += tokens_used;
+    }
+}
+
+fn render_repair_prompt(errors: &[String]) -> String {
+    let mut out = String::from("Your answer does not match the required JSON Schema:\n");
+    for error in errors {
+        out.push_str(&format!("- {}\n", error));
+    }
+    out.push_str("Reply again with ONLY the corrected JSON.");
+    out
+}
+
+/// Parse a JSON answer; a surrounding code fence or text around a single object/array is tolerated.
+fn parse_json(text: &str) -> Result<Value, serde_json::Error> {
+    let trimmed = text.trim();
+    let unfenced = trimmed
+        .strip_prefix("```json")
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|rest| rest.trim_end().strip_suffix("```"))
+        .unwrap_or(trimmed)
+        .trim();
+    serde_json::from_str(unfenced).or_else(|e| {
+        let start = unfenced.find(['{', '[']).ok_or(e)?;
+        let end = unfenced.rfind(['}', ']']).filter(|end| *end > start);
+        match end {
+            Some(end) => serde_json::from_str(&unfenced[start..=end]),
+            None => serde_json::from_str(&unfenced[start..]),
+        }
+    })
+}
+
+/// Resolve a local `$ref` (`#`, `#/$defs/...`, `#/definitions/...`).
+fn resolve_ref<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    let pointer = reference.strip_prefix('#')?;
+    if pointer.is_empty() {
+        return Some(root);
+    }
+    root.pointer(pointer)
+}
+
+fn type_matches(name: &str, value: &Value) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0),
+        _ => true,
+    }
+}
+
+fn check(root: &Value, schema: &Value, value: &Value, path: &str, depth: usize, errors: &mut Vec<String>) {
+    let schema = match schema {
+        Value::Bool(true) => return,
+        Value::Bool(false) => {
+            errors.push(format!("{}: no value allowed", path));
+            return;
+        }
+        Value::Object(schema) => schema,
+        _ => return,
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        match resolve_ref(root, reference) {
+            Some(target) if depth < MAX_REF_DEPTH => check(root, target, value, path, depth + 1, errors),
+            Some(_) => errors.push(format!("{}: $ref nesting too deep", path)),
+            None => errors.push(format!("{}: unresolvable $ref {}", path, reference)),
+        }
+    }
+
+    if let Some(types) = schema.get("type") {
+        let names: Vec<&str> = match types {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !names.is_empty() && !names.iter().any(|name| type_matches(name, value)) {
+            errors.push(format!("{}: expected {}, got {}", path, names.join(" or "), json_type(value)));
+            return;
+        }
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            errors.push(format!("{}: must be one of {}", path, Value::Array(allowed.clone())));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            errors.push(format!("{}: must be {}", path, expected));
+        }
+    }
+
+    match value {
+        Value::Object(object) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for name in schema.get("required").and_then(Value::as_array).into_iter().flatten() {
+                if let Some(name) = name.as_str().filter(|name| !object.contains_key(*name)) {
+                    errors.push(format!("{}: missing required property '{}'", path, name));
+                }
+            }
+            for (key, item) in object {
+                let item_path = format!("{}.{}", path, key);
+                match properties.and_then(|p| p.get(key)) {
+                    Some(property) => check(root, property, item, &item_path, depth, errors),
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            errors.push(format!("{}: property '{}' is not allowed", path, key))
+                        }
+                        Some(additional) => check(root, additional, item, &item_path, depth, errors),
+                        None => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64).filter(|min| (items.len() as u64) < *min) {
+                errors.push(format!("{}: expected at least {} items", path, min));
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64).filter(|max| (items.len() as u64) > *max) {
+                errors.push(format!("{}: expected at most {} items", path, max));
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    check(root, item_schema, item, &format!("{}[{}]", path, index), depth, errors);
+                }
+            }
+        }
+        Value::String(text) => {
+            let length = text.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64).filter(|min| length < *min) {
+                errors.push(format!("{}: expected at least {} characters", path, min));
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64).filter(|max| length > *max) {
+                errors.push(format!("{}: expected at most {} characters", path, max));
+            }
+        }
+        Value::Number(number) => {
+            let n = number.as_f64().unwrap_or_default();
+            let bound = |key: &str| schema.get(key).and_then(Value::as_f64);
+            if let Some(min) = bound("minimum").filter(|min| n < *min) {
+                errors.push(format!("{}: must be >= {}", path, min));
+            }
+            if let Some(max) = bound("maximum").filter(|max| n > *max) {
+                errors.push(format!("{}: must be <= {}", path, max));
+            }
+            if let Some(min) = bound("exclusiveMinimum").filter(|min| n <= *min) {
+                errors.push(format!("{}: must be > {}", path, min));
+            }
+            if let Some(max) = bound("exclusiveMaximum").filter(|max| n >= *max) {
+                errors.push(format!("{}: must be < {}", path, max));
+            }
+        }
+        _ => {}
+    }
+
+    for sub in schema.get("allOf").and_then(Value::as_array).into_iter().flatten() {
+        check(root, sub, value, path, depth, errors);
+    }
+    for keyword in ["anyOf", "oneOf"] {
+        let Some(options) = schema.get(keyword).and_then(Value::as_array) else {
+            continue;
+        };
+        let matching = options
+            .iter()
+            .filter(|option| {
+                let mut option_errors = Vec::new();
+                check(root, option, value, path, depth, &mut option_errors);
+                option_errors.is_empty()
+            })
+            .count();
+        if matching == 0 || (keyword == "oneOf" && matching > 1) {
+            let expected = if keyword == "oneOf" { "exactly one" } else { "at least one" };
+            errors.push(format!("{}: must match {} of the {} schemas", path, expected, keyword));
+        }
+    }
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn gemini_schema(root: &Value, schema: &Value, depth: usize) -> Value {
+    let Some(schema) = schema.as_object() else {
+        return serde_json::json!({});
+    };
+    if let Some(target) = schema.get("$ref").and_then(Value::as_str).and_then(|r| resolve_ref(root, r)) {
+        if depth < MAX_REF_DEPTH {
+            return gemini_schema(root, target, depth + 1);
+        }
+        return serde_json::json!({});
+    }
+
+    let mut out = serde_json::Map::new();
+    match schema.get("type") {
+        Some(Value::String(name)) => {
+            out.insert("type".to_string(), Value::String(name.to_uppercase()));
+        }
+        Some(Value::Array(names)) => {
+            let names: Vec<&str> = names.iter().filter_map(Value::as_str).collect();
+            if let Some(name) = names.iter().find(|name| **name != "null") {
+                out.insert("type".to_string(), Value::String(name.to_uppercase()));
+            }
+            if names.contains(&"null") {
+                out.insert("nullable".to_string(), Value::Bool(true));
+            }
+        }
+        _ => {}
+    }
+    for key in ["description", "format", "nullable", "minItems", "maxItems", "minimum", "maximum", "required"] {
+        if let Some(value) = schema.get(key) {
+            out.insert(key.to_string(), value.clone());
+        }
+    }
+    // Gemini only knows string enums
+    let strings = |values: &[Value]| values.iter().all(Value::is_string);
+    match (schema.get("enum").and_then(Value::as_array), schema.get("const")) {
+        (Some(values), _) if strings(values) => {
+            out.insert("enum".to_string(), Value::Array(values.clone()));
+        }
+        (None, Some(Value::String(value))) => {
+            out.insert("enum".to_string(), serde_json::json!([value]));
+        }
+        _ => {}
+    }
+    if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+        let properties = properties
+            .iter()
+            .map(|(name, property)| (name.clone(), gemini_schema(root, property, depth)))
+            .collect();
+        out.insert("properties".to_string(), Value::Object(properties));
+    }
+    if let Some(items) = schema.get("items") {
+        out.insert("items".to_string(), gemini_schema(root, items, depth));
+    }
+    let options = schema.get("anyOf").or_else(|| schema.get("oneOf")).and_then(Value::as_array);
+    if let Some(options) = options {
+        let options = options.iter().map(|option| gemini_schema(root, option, depth)).collect();
+        out.insert("anyOf".to_string(), Value::Array(options));
+    }
+    Value::Object(out)
+}
+
+const GBNF_PRIMITIVES: &str = r#"ws ::= [ \t\n]*
+value ::= object | array | string | number | boolean | null
+object ::= "{" ws ( string ":" ws value ( "," ws string ":" ws value )* )? "}" ws
+array ::= "[" ws ( value ( "," ws value )* )? "]" ws
+string ::= "\"" char* "\"" ws
+char ::= [^"\\\x7F\x00-\x1F] | "\\" ( ["\\/bfnrt] | "u" [0-9a-fA-F] [0-9a-fA-F] [0-9a-fA-F] [0-9a-fA-F] )
+number ::= "-"? ( [0-9] | [1-9] [0-9]* ) ( "." [0-9]+ )? ( [eE] [-+]? [0-9]+ )? ws
+integer ::= "-"? ( [0-9] | [1-9] [0-9]* ) ws
+boolean ::= ( "true" | "false" ) ws
+null ::= "null" ws
+"#;
+
+/// Builds GBNF rules for a schema; every object, array and `$ref` target becomes a named rule.
+struct Grammar<'a> {
+    root: &'a Value,
+    rules: Vec<(String, String)>,
+    names: HashSet<String>,
+    refs: HashMap<String, String>,
+}
+
+impl<'a> Grammar<'a> {
+    fn new(root: &'a Value) -> Self {
+        let names = ["root", "ws", "value", "object", "array", "string", "char", "number", "integer", "boolean", "null"];
+        Self {
+            root,
+            rules: Vec::new(),
+            names: names.iter().map(|n| n.to_string()).collect(),
+            refs: HashMap::new(),
+        }
+    }
+
+    /// Unique rule name (GBNF allows `[a-zA-Z0-9-]`)
+    fn name(&mut self, hint: &str) -> String {
+        let base: String = hint
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+            .collect();
+        let base = if base.is_empty() { "rule".to_string() } else { base };
+        let mut name = base.clone();
+        let mut n = 1;
+        while !self.names.insert(name.clone()) {
+            n += 1;
+            name = format!("{}-{}", base, n);
+        }
+        name
+    }
+
+    fn add(&mut self, hint: &str, body: String) -> String {
+        let name = self.name(hint);
+        self.rules.push((name.clone(), body));
+        name
+    }
+
+    /// GBNF expression matching the schema (a rule name or an inline expression)
+    fn expr(&mut self, schema: &Value, hint: &str, depth: usize) -> String {
+        let Some(object) = schema.as_object() else {
+            return "value".to_string();
+        };
+        if let Some(reference) = object.get("$ref").and_then(Value::as_str) {
+            if let Some(name) = self.refs.get(reference) {
+                return name.clone();
+            }
+            let root = self.root;
+            return match resolve_ref(root, reference) {
+                Some(target) if depth < MAX_REF_DEPTH => {
+                    // Register the name first so recursive schemas refer back to it
+                    let name = self.name(reference.rsplit('/').next().unwrap_or("ref"));
+                    self.refs.insert(reference.to_string(), name.clone());
+                    let index = self.rules.len();
+                    self.rules.push((name.clone(), String::new()));
+                    let body = self.expr(target, &name, depth + 1);
+                    self.rules[index].1 = body;
+                    name
+                }
+                _ => "value".to_string(),
+            };
+        }
+        if let Some(expected) = object.get("const") {
+            return literal(expected);
+        }
+        if let Some(values) = object.get("enum").and_then(Value::as_array) {
+            let alternatives: Vec<String> = values.iter().map(literal).collect();
+            return format!("( {} )", alternatives.join(" | "));
+        }
+        if let Some(options) = object.get("anyOf").or_else(|| object.get("oneOf")).and_then(Value::as_array) {
+            let alternatives: Vec<String> = options
+                .iter()
+                .enumerate()
+                .map(|(i, option)| self.expr(option, &format!("{}-{}", hint, i), depth))
+                .collect();
+            return format!("( {} )", alternatives.join(" | "));
+        }
+
+        match object.get("type") {
+            Some(Value::String(name)) => self.typed(object, name, hint, depth),
+            Some(Value::Array(names)) => {
+                let alternatives: Vec<String> = names
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(|name| self.typed(object, name, hint, depth))
+                    .collect();
+                format!("( {} )", alternatives.join(" | "))
+            }
+            _ if object.contains_key("properties") => self.typed(object, "object", hint, depth),
+            _ => "value".to_string(),
+        }
+    }
+
+    fn typed(&mut self, schema: &serde_json::Map<String, Value>, type_name: &str, hint: &str, depth: usize) -> String {
+        match type_name {
+            "object" => self.object(schema, hint, depth),
+            "array" => {
+                let item = match schema.get("items") {
+                    Some(items) => self.expr(items, &format!("{}-item", hint), depth),
+                    None => "value".to_string(),
+                };
+                let min_items = schema.get("minItems").and_then(Value::as_u64).unwrap_or(0);
+                let items = format!("{} ( \",\" ws {} )*", item, item);
+                let body = if min_items > 0 {
+                    format!("\"[\" ws {} \"]\" ws", items)
+                } else {
+                    format!("\"[\" ws ( {} )? \"]\" ws", items)
+                };
+                self.add(hint, body)
+            }
+            "string" | "number" | "integer" | "boolean" | "null" => type_name.to_string(),
+            _ => "value".to_string(),
+        }
+    }
+
+    /// Properties in schema order; required ones always, optional ones each at most once.
+    fn object(&mut self, schema: &serde_json::Map<String, Value>, hint: &str, depth: usize) -> String {
+        let Some(properties) = schema.get("properties").and_then(Value::as_object).filter(|p| !p.is_empty()) else {
+            return "object".to_string();
+        };
+        let required: HashSet<&str> = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|names| names.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+
+        let mut required_pairs = Vec::new();
+        let mut optional_pairs = Vec::new();
+        for (key, property) in properties {
+            let value = self.expr(property, &format!("{}-{}", hint, key), depth);
+            let pair = format!("{} \":\" ws {}", literal(&Value::String(key.clone())), value);
+            if required.contains(key.as_str()) {
+                required_pairs.push(pair);
+            } else {
+                optional_pairs.push(pair);
+            }
+        }
+
+        // Optional pairs as a chain: each rule starts with its own pair or skips to the next one
+        let mut optional = None;
+        for (i, pair) in optional_pairs.iter().enumerate().rev() {
+            let body = match &optional {
+                Some(next) => format!("{} ( \",\" ws {} )? | {}", pair, next, next),
+                None => pair.clone(),
+            };
+            optional = Some(self.add(&format!("{}-opt-{}", hint, i), body));
+        }
+
+        let body = match (required_pairs.is_empty(), optional) {
+            (false, Some(optional)) => format!(
+                "\"{{\" ws {} ( \",\" ws {} )? \"}}\" ws",
+                required_pairs.join(" \",\" ws "),
+                optional
+            ),
+            (false, None) => format!("\"{{\" ws {} \"}}\" ws", required_pairs.join(" \",\" ws ")),
+            (true, Some(optional)) => format!("\"{{\" ws {}? \"}}\" ws", optional),
+            (true, None) => return "object".to_string(),
+        };
+        self.add(hint, body)
+    }
+}
+
+/// GBNF literal for a JSON value (its compact serialization), followed by optional whitespace
+fn literal(value: &Value) -> String {
+    let json = value.to_string();
+    let escaped = json.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n");
+    format!("\"{}\" ws", escaped)
+}
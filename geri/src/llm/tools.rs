@@ -0,0 +1,143 @@
+//! Tool/function calling types shared by all providers, plus the JSON-prompted
+//! fallback used by local models without native tool support (llama.cpp, BitNet).
+
+use serde::{Deserialize, Serialize};
+
+/// A tool the model may call
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolDefinition {
+    pub name: String,
+    pub description: String,
+    /// JSON Schema (`type: object`) describing the arguments
+    pub parameters: serde_json::Value,
+}
+
+/// A tool invocation requested by the model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    /// Provider-assigned id; generated for providers that do not assign one (Gemini, local models)
+    pub id: String,
+    pub name: String,
+    pub arguments: serde_json::Value,
+}
+
+/// The result of executing a [`ToolCall`], sent back to the model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolResult {
+    pub tool_call_id: String,
+    pub name: String,
+    pub content: String,
+    #[serde(default)]
+    pub is_error: bool,
+}
+
+impl ToolCall {
+    /// Create a tool call with a freshly generated id
+    pub fn new(name: impl Into<String>, arguments: serde_json::Value) -> Self {
+        Self {
+            id: format!("call_{}", uuid::Uuid::new_v4().simple()),
+            name: name.into(),
+            arguments,
+        }
+    }
+}
+
+/// Parse JSON-encoded tool arguments as streamed by cloud providers;
+/// malformed JSON is kept as a string value so the caller can report it.
+pub fn parse_arguments(arguments: &str) -> serde_json::Value {
+    if arguments.trim().is_empty() {
+        return serde_json::Value::Object(Default::default());
+    }
+    serde_json::from_str(arguments).unwrap_or_else(|_| serde_json::Value::String(arguments.to_string()))
+}
+
+/// Instructions appended to the system prompt so that a model without native tool calling
+/// answers with a parseable JSON object when it wants to call a tool.
+pub fn render_tool_instructions(tools: &[ToolDefinition]) -> String {
+    let mut out = String::from(
+        "You can call the following tools. To call tools, reply with ONLY a JSON object of the form \
+         {\"tool_calls\": [{\"name\": \"<tool name>\", \"arguments\": {...}}]} and nothing else. \
+         If no tool is needed, answer normally.\n\nTools:\n",
+    );
+    for tool in tools {
+        out.push_str(&format!(
+            "- {}: {}\n  parameters: {}\n",
+            tool.name, tool.description, tool.parameters
+        ));
+    }
+    out
+}
+
+/// Render previous tool calls and their results as plain text for prompt-based models.
+pub fn render_tool_results(calls: &[ToolCall], results: &[ToolResult]) -> String {
+    let mut out = String::new();
+    for call in calls {
+        out.push_str(&format!("Tool call {} ({}): {}\n", call.name, call.id, call.arguments));
+    }
+    for result in results {
+        let label = if result.is_error { "Tool error" } else { "Tool result" };
+        out.push_str(&format!(
+            "{} {} ({}): {}\n",
+            label, result.name, result.tool_call_id, result.content
+        ));
+    }
+    out
+}
+
+/// Extract tool calls from a model answer produced under [`render_tool_instructions`].
+///
+/// Accepts the bare JSON object, a fenced ```json block, or a single call object
+/// (`{"name": ..., "arguments": ...}`). Calls to tools not in `tools` are dropped.
+/// Returns `None` if the text is a normal answer.
+pub fn parse_tool_calls(text: &str, tools: &[ToolDefinition]) -> Option<Vec<ToolCall>> {
+    let json = extract_json_object(text)?;
+    let value: serde_json::Value = serde_json::from_str(json).ok()?;
+
+    let raw_calls = match value.get("tool_calls") {
+        Some(serde_json::Value::Array(calls)) => calls.clone(),
+        Some(_) => return None,
+        None if value.get("name").is_some() => vec![value],
+        None => return None,
+    };
+
+    let calls: Vec<ToolCall> = raw_calls
+        .into_iter()
+        .filter_map(|call| {
+            let name = call.get("name")?.as_str()?.to_string();
+            if !tools.iter().any(|t| t.name == name) {
+                return None;
+            }
+            let arguments = match call.get("arguments") {
+                // Some models emit arguments as a JSON-encoded string
+                Some(serde_json::Value::String(s)) => serde_json::from_str(s).ok()?,
+                Some(args) => args.clone(),
+                None => serde_json::Value::Object(Default::default()),
+            };
+            Some(ToolCall::new(name, arguments))
+        })
+        .collect();
+
+    if calls.is_empty() {
+        None
+    } else {
+        Some(calls)
+    }
+}
+
+/// Locate the JSON object in `text`; only a surrounding code fence is tolerated.
+fn extract_json_object(text: &str) -> Option<&str> {
+    let trimmed = text.trim();
+    let start = trimmed.find('{')?;
+    let end = trimmed.rfind('}')?;
+    if end <= start {
+        return None;
+    }
+    // Only treat the answer as a tool call if nothing but a code fence surrounds the object
+    let before = trimmed[..start].trim();
+    let after = trimmed[end + 1..].trim();
+    let fence_only = |s: &str| s.is_empty() || s == "```" || s == "```json";
+    if !fence_only(before) || !fence_only(after) {
+        return None;
+    }
+    Some(&trimmed[start..=end])
+}
@@ -1,16 +1,20 @@
 //! Logging-Setup (Phase 18.1.1): Structured-Logging (tracing), Default-Log-Level.
 
 use std::io;
-use tracing_subscriber::EnvFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
 
-/// Initialisiert strukturiertes Logging (tracing).
+/// Initialisiert strukturiertes Logging (tracing) und das Distributed Tracing über Vegvisir.
 /// Nutzt `RUST_LOG`; falls nicht gesetzt, wird `default_filter` oder `info` verwendet (LLM-Service-weit).
-pub fn init_logging(default_filter: Option<&str>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// Der zurückgegebene Exporter wird nach dem Laden der Settings mit `trace_export` gestartet.
+pub fn init_logging(default_filter: Option<&str>) -> Result<vegvisir::TraceExporter, Box<dyn std::error::Error + Send + Sync>> {
     let filter = EnvFilter::try_from_default_env()
         .or_else(|_| EnvFilter::try_new(default_filter.unwrap_or("info")))?;
-    tracing_subscriber::fmt()
-        .with_env_filter(filter)
-        .with_writer(io::stderr)
+    let (trace_layer, trace_exporter) = vegvisir::layer("geri");
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_writer(io::stderr).with_filter(filter))
+        .with(trace_layer)
         .try_init()?;
-    Ok(())
+    Ok(trace_exporter)
 }
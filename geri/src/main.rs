@@ -6,7 +6,7 @@ use std::path::PathBuf;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    geri::logging::init_logging(Some("info"))?;
+    let trace_exporter = geri::logging::init_logging(Some("info"))?;
 
     info!("Geri LLM Service starting...");
 
@@ -26,14 +26,33 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     
     let settings = settings_manager.get().await;
     info!("Configuration loaded");
+    trace_exporter.start(&settings.trace_export);
+
+    // Key store maintenance: `geri keys set|rotate|remove|list` edits the encrypted key store file; a
+    // running Geri swaps changed keys in within `keys.refresh_interval_secs`
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("keys") {
+        return run_key_command(&settings, &args[1..]).await;
+    }
+    let live_keys = build_live_keys(&settings).await?;
 
     // Initialize LLM provider using LocalLLMManager
     use geri::llm::local_manager::LocalLLMManager;
     
-    let local_manager = LocalLLMManager::new()
+    let mut local_manager = LocalLLMManager::new()
         .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> {
             Box::new(std::io::Error::new(std::io::ErrorKind::Other, e.to_string()))
-        })?;
+        })?
+        .with_auto_download(settings.local_provider.auto_download);
+    if let Some(catalog_path) = &settings.local_provider.model_catalog_path {
+        let catalog = geri::llm::model_catalog::ModelCatalog::load(catalog_path)
+            .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> { Box::new(e) })?;
+        info!("Loaded model catalog {} ({} models)", catalog_path, catalog.models.len());
+        local_manager = local_manager.with_catalog(catalog);
+    }
+    if let Some(quota_mb) = settings.local_provider.models_quota_mb {
+        local_manager = local_manager.with_models_quota_mb(quota_mb);
+    }
     
     let llm_provider: Arc<dyn geri::llm::LLMProvider> = if settings.local_provider.auto_select {
         info!("Auto-selecting local LLM provider based on hardware...");
@@ -47,7 +66,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         info!("Using configured provider type: {}", settings.local_provider.provider_type);
         
         let hardware = local_manager.detect_hardware();
-        let config = local_manager.generate_model_config(&hardware);
+        let mut config = local_manager.generate_model_config(&hardware);
+        config.chat_template = settings.local_provider.chat_template;
         
         let provider = local_manager.create_provider(
             &settings.local_provider.provider_type,
@@ -61,18 +81,104 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
 
     info!("Local LLM provider initialized: {}", llm_provider.model_name());
 
+    // Offline evaluation: `geri eval <suite>...` runs benchmark suites against the local provider and exits
+    if args.first().map(String::as_str) == Some("eval") {
+        return run_evaluation(&settings, &args[1..], llm_provider).await;
+    }
+
+    // Admission scheduler in front of the local model: per-model slots, voice before chat before
+    // background work, fair share across users (gRPC and HTTP API requests, via the engine)
+    let llm_provider: Arc<dyn geri::llm::LLMProvider> = if settings.scheduler.enabled {
+        let scheduler = geri::queue::RequestScheduler::new(settings.scheduler.clone());
+        Arc::new(geri::llm::ScheduledProvider::new(llm_provider, scheduler))
+    } else {
+        llm_provider
+    };
+
+    // Load model tokenizers (tokenizer.json next to the GGUF models); heuristic as fallback
+    let mut tokenizers = runar::TokenizerRegistry::new();
+    if let Err(e) = local_manager.load_tokenizers(&mut tokenizers) {
+        tracing::warn!("Failed to load tokenizers: {}", e);
+    }
+    for dir in [&settings.local_provider.llamacpp_models_dir, &settings.local_provider.bitnet_models_dir] {
+        if std::path::Path::new(dir).is_dir() {
+            if let Err(e) = tokenizers.load_dir(dir) {
+                tracing::warn!("Failed to load tokenizers from {}: {}", dir, e);
+            }
+        }
+    }
+    let token_counter = geri::prompt::TokenCounter::with_tokenizers(Arc::new(tokenizers));
+
     // Initialize vision processor
     let vision_processor = Arc::new(geri::vision::VisionProcessor::new(
         settings.vision_model.clone(),
     ));
 
+    // Model registry shared by the HTTP API and the embedding models
+    let registry: Arc<dyn geri::model::ModelRegistryTrait> = Arc::new(tokio::sync::RwLock::new(ModelRegistry::new()));
+
+    // Embedding models (optional), shared by the Embed RPC, the HTTP API and the response cache
+    let embeddings = build_embedding_service(&settings, registry.clone(), token_counter.clone(), live_keys.as_ref()).await?;
+
+    // Response cache (optional), used by the engine for gRPC and the HTTP API
+    let response_cache = if settings.cache.enabled {
+        let manager = geri::cache::CacheManager::open(&settings.cache)?;
+        info!("Response cache enabled ({} cached entries)", manager.len());
+        let mut cache = geri::cache::ResponseCache::new(manager);
+        if let Some(embeddings) = &embeddings {
+            cache = cache.with_embedder(embeddings.clone());
+        }
+        Some(Arc::new(cache))
+    } else {
+        None
+    };
+
+    // Budgets and usage ledger, shared by gRPC and the HTTP API
+    let budget_tracker = Arc::new(build_budget_tracker(&settings)?);
+    let reset_tracker = budget_tracker.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60));
+        loop {
+            interval.tick().await;
+            reset_tracker.check_period_reset(chrono::Utc::now()).await;
+        }
+    });
+
+    // GeriEngine (model selection, budget, failover) over the local LLM and the local vision models
+    let engine = build_engine(
+        &settings,
+        registry.clone(),
+        llm_provider,
+        token_counter.clone(),
+        response_cache,
+        budget_tracker.clone(),
+        live_keys,
+    )
+    .await?;
+
+    // Start OpenAI-compatible HTTP server (optional)
+    #[cfg(feature = "openai-api")]
+    if settings.openai_api.enabled {
+        let state = build_openai_api_state(&settings, engine.clone(), registry.clone(), token_counter.clone(), embeddings.clone())?;
+        let addr = std::net::SocketAddr::from(([0, 0, 0, 0], settings.openai_api.port));
+        tokio::spawn(async move {
+            if let Err(e) = geri::http::start_openai_server(addr, state).await {
+                tracing::error!("OpenAI HTTP server error: {}", e);
+            }
+        });
+    }
+
     // Start gRPC server
     let model_registry = Arc::new(ModelRegistry::default());
     let addr = std::net::SocketAddr::from(([0, 0, 0, 0], settings.grpc_port));
     let deps = geri::grpc::GrpcServerDependencies {
         model_registry,
-        llm_provider,
+        engine: engine.clone(),
         vision_processor,
+        token_counter,
+        embeddings,
+        budget_tracker: Some(budget_tracker),
+        vision_engine: settings.local_provider.vision_server_url.is_some().then_some(engine),
     };
     let _server_handle = tokio::spawn(async move {
         if let Err(e) = geri::grpc::start_grpc_server(addr, deps).await {
@@ -87,3 +193,392 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
 
     Ok(())
 }
+
+/// GeriEngine (model selection, budget, performance tracking) over the local provider and, with
+/// `local_provider.vision_server_url`, the vision models found in the llama.cpp models directory.
+async fn build_engine(
+    settings: &geri::utils::config::GeriSettings,
+    registry: Arc<dyn geri::model::ModelRegistryTrait>,
+    llm_provider: Arc<dyn geri::llm::LLMProvider>,
+    token_counter: geri::prompt::TokenCounter,
+    response_cache: Option<Arc<geri::cache::ResponseCache>>,
+    budget_tracker: Arc<geri::cost::BudgetTracker>,
+    live_keys: Option<geri::keys::LiveKeys>,
+) -> Result<Arc<geri::llm::GeriEngine>, Box<dyn std::error::Error + Send + Sync>> {
+    use geri::model::{ModelInfo, ModelType};
+
+    let model_id = llm_provider.model_name().to_string();
+    registry.register(ModelInfo {
+        id: model_id.clone(),
+        name: model_id.clone(),
+        provider: "local".to_string(),
+        model_type: ModelType::Llm,
+        parameter_count: None,
+        hardware_requirements: None,
+        context_window: None,
+        is_local: true,
+        cost_per_token_input: Some(0.0),
+        cost_per_token_output: Some(0.0),
+        embedding_dimension: None,
+    }).await?;
+    let mut factory = geri::llm::ProviderFactory::new();
+    if let Some(live_keys) = live_keys {
+        factory = factory.with_live_keys(live_keys);
+    }
+    let factory = Arc::new(factory);
+    factory.register(&model_id, llm_provider).await;
+    if let Some(server_url) = &settings.local_provider.vision_server_url {
+        register_local_vision_models(&settings.local_provider.llamacpp_models_dir, server_url, registry.as_ref(), &factory).await?;
+    }
+    let performance_tracker = Arc::new(geri::performance::PerformanceTracker::new().map_err(std::io::Error::other)?);
+
+    let failover = &settings.failover;
+    let mut engine = geri::llm::GeriEngine::new(registry.clone(), performance_tracker, factory, budget_tracker)
+        .with_cost_calculator(geri::cost::CostCalculator::new(token_counter.clone()))
+        .with_circuit_breaker(Arc::new(geri::error_handling::CircuitBreakerRegistry::new(failover.circuit_breaker.clone())))
+        .with_retry_manager(geri::error_handling::RetryManager::new(failover.max_retries, failover.retry_base_delay_ms))
+        .with_max_repair_attempts(settings.structured_output.max_repair_attempts)
+        .with_safety_pipeline(settings.safety.pipeline());
+    if let Some(timeout_ms) = failover.request_timeout_ms {
+        engine = engine.with_request_timeout(std::time::Duration::from_millis(timeout_ms));
+    }
+    if let Some(response_cache) = response_cache {
+        engine = engine.with_response_cache(response_cache);
+    }
+    if let Some(path) = &settings.evaluation.results_path {
+        let evaluations = geri::evaluation::EvaluationStore::open(path)?;
+        info!("Evaluation results {} loaded ({} reports)", path, evaluations.reports().len());
+        engine = engine.with_evaluation_store(Arc::new(evaluations));
+    }
+    Ok(Arc::new(engine))
+}
+
+/// Registers the vision models (GGUF model plus `mmproj` projector) found in `models_dir` as local
+/// `ModelType::Vision` models served by the llama.cpp server at `server_url`.
+async fn register_local_vision_models(
+    models_dir: &str,
+    server_url: &str,
+    registry: &dyn geri::model::ModelRegistryTrait,
+    factory: &geri::llm::ProviderFactory,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use geri::llm::llamacpp::{discover_vision_models, LlamaCppVisionProvider};
+
+    let dir = std::path::Path::new(models_dir);
+    if !dir.is_dir() {
+        tracing::warn!("llama.cpp models directory {} not found, no local vision models", models_dir);
+        return Ok(());
+    }
+    for model in discover_vision_models(dir)? {
+        let provider = LlamaCppVisionProvider::new(model.config(server_url), model.id.clone())?;
+        registry.register(model.model_info()).await?;
+        factory.register_vision(&model.id, Arc::new(provider)).await;
+        info!("Local vision model registered: {} (projector {})", model.id, model.mmproj_path.display());
+    }
+    Ok(())
+}
+
+/// OpenAI-compatible HTTP API over the shared engine.
+#[cfg(feature = "openai-api")]
+fn build_openai_api_state(
+    settings: &geri::utils::config::GeriSettings,
+    engine: Arc<geri::llm::GeriEngine>,
+    registry: Arc<dyn geri::model::ModelRegistryTrait>,
+    token_counter: geri::prompt::TokenCounter,
+    embeddings: Option<Arc<geri::llm::EmbeddingService>>,
+) -> Result<geri::http::OpenAiApiState, Box<dyn std::error::Error + Send + Sync>> {
+    let mut state = geri::http::OpenAiApiState::new(engine, registry).with_token_counter(token_counter);
+    if let Some(embeddings) = embeddings {
+        state = state.with_embeddings(embeddings);
+    }
+
+    if settings.openai_api.require_auth {
+        let validator = geri::http::HeimdallTokenValidator::new(&settings.openai_api.heimdall_url)?;
+        state = state.with_validator(Arc::new(validator));
+    } else {
+        tracing::warn!("OpenAI HTTP API runs without authentication (openai_api.require_auth = false)");
+    }
+    Ok(state)
+}
+
+/// Opens the configured key store; the Heimdall backend is returned separately for refreshing.
+async fn open_key_storage(
+    settings: &geri::utils::config::GeriSettings,
+) -> Result<Option<(geri::keys::SecureKeyStorage, Option<geri::keys::HeimdallKeyBackend>)>, Box<dyn std::error::Error + Send + Sync>> {
+    use geri::keys::{EncryptedFileKeyBackend, HeimdallKeyBackend, HeimdallKeySource, RemoteKeySource, SecureKeyStorage, CLOUD_PROVIDER_IDS};
+    use geri::utils::config::KeyBackendType;
+
+    let config = &settings.keys;
+    let heimdall = || {
+        let service_token = std::env::var(&config.service_token_env).unwrap_or_default();
+        HeimdallKeySource::new(&config.heimdall_url, &config.service_id, &service_token)
+    };
+    match config.backend {
+        KeyBackendType::Env => Ok(None),
+        KeyBackendType::EncryptedFile => {
+            let backend = if config.key_from_heimdall {
+                let key = heimdall()?.key_store_key().await?;
+                EncryptedFileKeyBackend::open_with_key(&config.file_path, key)?
+            } else {
+                let passphrase = std::env::var(&config.passphrase_env)
+                    .map_err(|_| format!("{} must hold the key store passphrase", config.passphrase_env))?;
+                EncryptedFileKeyBackend::open_with_passphrase(&config.file_path, &passphrase)?
+            };
+            Ok(Some((SecureKeyStorage::new(Box::new(backend)), None)))
+        }
+        KeyBackendType::Heimdall => {
+            let backend = HeimdallKeyBackend::new(Arc::new(heimdall()?), CLOUD_PROVIDER_IDS);
+            if let Err(e) = backend.refresh().await {
+                tracing::warn!("Failed to fetch provider keys from Heimdall: {}", e);
+            }
+            Ok(Some((SecureKeyStorage::new(Box::new(backend.clone())), Some(backend))))
+        }
+    }
+}
+
+/// Live provider keys from the key store, re-read every `keys.refresh_interval_secs` so rotated
+/// keys reach the running clients; `None` with the `env` backend.
+async fn build_live_keys(
+    settings: &geri::utils::config::GeriSettings,
+) -> Result<Option<geri::keys::LiveKeys>, Box<dyn std::error::Error + Send + Sync>> {
+    use geri::keys::{LiveKeys, CLOUD_PROVIDER_IDS};
+
+    let Some((storage, heimdall)) = open_key_storage(settings).await? else {
+        return Ok(None);
+    };
+    let live_keys = LiveKeys::new();
+    let loaded = live_keys.sync_from(&storage, CLOUD_PROVIDER_IDS)?;
+    info!("API keys loaded from key store: {}", loaded.join(", "));
+
+    let refreshed = live_keys.clone();
+    let period = std::time::Duration::from_secs(settings.keys.refresh_interval_secs);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if let Some(heimdall) = &heimdall {
+                if let Err(e) = heimdall.refresh().await {
+                    tracing::warn!("Failed to refresh provider keys from Heimdall: {}", e);
+                    continue;
+                }
+            }
+            match refreshed.sync_from(&storage, CLOUD_PROVIDER_IDS) {
+                Ok(changed) => {
+                    for provider in changed {
+                        info!("API key for {} rotated", provider);
+                    }
+                }
+                Err(e) => tracing::warn!("Failed to reload API keys: {}", e),
+            }
+        }
+    });
+    Ok(Some(live_keys))
+}
+
+/// `geri keys set|rotate <provider>` (key from stdin), `geri keys remove <provider>`, `geri keys list`
+async fn run_key_command(
+    settings: &geri::utils::config::GeriSettings,
+    args: &[String],
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use geri::keys::{KeyRotationManager, LiveKeys, CLOUD_PROVIDER_IDS};
+    use geri::utils::config::KeyBackendType;
+
+    if settings.keys.backend != KeyBackendType::EncryptedFile {
+        return Err("geri keys needs keys.backend = \"encrypted_file\"; Heimdall keys are managed in Heimdall".into());
+    }
+    let Some((mut storage, _)) = open_key_storage(settings).await? else {
+        return Ok(());
+    };
+    match (args.first().map(String::as_str), args.get(1)) {
+        (Some("set" | "rotate"), Some(provider)) => {
+            let mut api_key = String::new();
+            std::io::stdin().read_line(&mut api_key)?;
+            let api_key = api_key.trim();
+            if api_key.is_empty() {
+                return Err("no API key on stdin".into());
+            }
+            // A running Geri picks the key up on its next key store refresh
+            let live_keys = LiveKeys::new();
+            live_keys.sync_from(&storage, CLOUD_PROVIDER_IDS)?;
+            KeyRotationManager.rotate_live(&mut storage, &live_keys, provider, api_key)?;
+            println!("API key for {} stored in {}", provider, settings.keys.file_path);
+        }
+        (Some("remove"), Some(provider)) => {
+            storage.remove_key(provider)?;
+            println!("API key for {} removed", provider);
+        }
+        (Some("list"), None) => {
+            for provider in CLOUD_PROVIDER_IDS {
+                let state = if storage.load_key(provider)?.is_some() { "set" } else { "-" };
+                println!("{}: {}", provider, state);
+            }
+        }
+        _ => return Err("usage: geri keys set|rotate <provider> | remove <provider> | list".into()),
+    }
+    Ok(())
+}
+
+/// `geri eval <suite>...`: runs each suite against the local provider, prints a summary and stores
+/// the report in `evaluation.results_path`. The local model also serves as `llm_judge`.
+async fn run_evaluation(
+    settings: &geri::utils::config::GeriSettings,
+    suites: &[String],
+    llm_provider: Arc<dyn geri::llm::LLMProvider>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    use geri::evaluation::{EvalRunner, EvalSuite, EvaluationStore};
+
+    if suites.is_empty() {
+        return Err("usage: geri eval <suite.yaml|suite.json|suite.jsonl>...".into());
+    }
+    let config = &settings.evaluation;
+    let model_id = llm_provider.model_name().to_string();
+    let judges = Arc::new(geri::llm::ProviderFactory::new());
+    judges.register(&model_id, llm_provider.clone()).await;
+    let mut runner = EvalRunner::new().with_concurrency(config.concurrency).with_judges(judges);
+    if let Some(timeout_ms) = config.request_timeout_ms {
+        runner = runner.with_request_timeout(std::time::Duration::from_millis(timeout_ms));
+    }
+    let store = config.results_path.as_ref().map(EvaluationStore::open).transpose()?;
+
+    for path in suites {
+        let suite = EvalSuite::load(path)?;
+        let report = runner.run(&suite, &model_id, llm_provider.clone()).await;
+        println!(
+            "{} ({}): {}/{} passed, mean score {:.3}, mean latency {:.0} ms",
+            report.suite,
+            report.model,
+            report.passed(),
+            report.results.len(),
+            report.mean_score(),
+            report.mean_latency_ms()
+        );
+        for result in report.results.iter().filter(|r| !r.grade.passed) {
+            println!("  FAIL {}: {}", result.case_id, result.grade.detail.as_deref().unwrap_or("-"));
+        }
+        if let Some(store) = &store {
+            store.append(report)?;
+        }
+    }
+    Ok(())
+}
+
+/// Logs the start of a new budget period; the engine re-checks budgets on every request, so cloud
+/// models are used again without further action.
+struct LogBudgetReset;
+
+impl geri::fallback::BudgetResetHandler for LogBudgetReset {
+    fn on_budget_reset(&self) {
+        info!("Budget period reset, cloud models are available again");
+    }
+}
+
+/// Budget tracker with the configured limits and the persisted usage ledger; the global
+/// `openai_api.budget_limit` stays as an overall cap.
+fn build_budget_tracker(
+    settings: &geri::utils::config::GeriSettings,
+) -> Result<geri::cost::BudgetTracker, Box<dyn std::error::Error + Send + Sync>> {
+    let config = &settings.budget;
+    let ledger = match &config.ledger_path {
+        Some(path) => {
+            let ledger = geri::cost::UsageLedger::open(path)?;
+            info!("Usage ledger {} loaded ({} records)", path, ledger.records().len());
+            ledger
+        }
+        None => geri::cost::UsageLedger::in_memory(),
+    };
+    Ok(geri::cost::BudgetTracker::new(settings.openai_api.budget_limit)
+        .with_limits(config.limits.clone())
+        .with_ledger(ledger)
+        .with_reset_listener(geri::fallback::BudgetResetListener::new(Box::new(LogBudgetReset))))
+}
+
+/// Embedding providers from `settings.embeddings`, registered in the shared model registry.
+async fn build_embedding_service(
+    settings: &geri::utils::config::GeriSettings,
+    registry: Arc<dyn geri::model::ModelRegistryTrait>,
+    token_counter: geri::prompt::TokenCounter,
+    live_keys: Option<&geri::keys::LiveKeys>,
+) -> Result<Option<Arc<geri::llm::EmbeddingService>>, Box<dyn std::error::Error + Send + Sync>> {
+    use geri::llm::{google, llamacpp, openai, EmbeddingProvider};
+    use geri::model::{ModelInfo, ModelType};
+
+    let config = &settings.embeddings;
+    if config.models.is_empty() {
+        return Ok(None);
+    }
+    let service = geri::llm::EmbeddingService::new(registry).with_token_counter(token_counter);
+    for model in &config.models {
+        let name = model.model.clone().unwrap_or_else(|| model.id.clone());
+        let api_key = model
+            .api_key_env
+            .as_deref()
+            .and_then(|var| std::env::var(var).ok())
+            .unwrap_or_default();
+        // Keys from the key store win over the environment and follow rotations
+        let live_key = live_keys.map(|keys| {
+            let live_key = keys.handle(&model.provider);
+            if live_key.is_empty() && !api_key.is_empty() {
+                live_key.set(&api_key);
+            }
+            live_key
+        });
+        let dimension = model.request_dimension.then_some(model.dimension);
+        let provider: Arc<dyn EmbeddingProvider> = match model.provider.as_str() {
+            "openai" => {
+                let config = openai::OpenAIConfig {
+                    api_key,
+                    base_url: model.base_url.clone().unwrap_or_else(|| "https://api.openai.com/v1".to_string()),
+                    timeout_secs: 30,
+                };
+                let mut provider = openai::OpenAIEmbeddingProvider::new(config, name);
+                if let Some(live_key) = live_key {
+                    provider = provider.with_live_key(live_key);
+                }
+                Arc::new(match dimension {
+                    Some(dimension) => provider.with_dimensions(dimension),
+                    None => provider,
+                })
+            }
+            "google" => {
+                let mut config = google::GoogleConfig::new(api_key);
+                if let Some(base_url) = &model.base_url {
+                    config.base_url = base_url.clone();
+                }
+                let mut provider = google::GoogleEmbeddingProvider::new(config, name);
+                if let Some(live_key) = live_key {
+                    provider = provider.with_live_key(live_key);
+                }
+                Arc::new(match dimension {
+                    Some(dimension) => provider.with_dimensions(dimension),
+                    None => provider,
+                })
+            }
+            _ => {
+                let mut config = llamacpp::LlamaCppEmbeddingConfig::default();
+                if let Some(base_url) = &model.base_url {
+                    config.server_url = base_url.clone();
+                }
+                Arc::new(llamacpp::LlamaCppEmbeddingProvider::new(config, name))
+            }
+        };
+        let info = ModelInfo {
+            id: model.id.clone(),
+            name: model.id.clone(),
+            provider: model.provider.clone(),
+            model_type: ModelType::Embedding,
+            parameter_count: None,
+            hardware_requirements: None,
+            context_window: Some(model.max_input_tokens),
+            is_local: model.provider == "llamacpp",
+            cost_per_token_input: None,
+            cost_per_token_output: None,
+            embedding_dimension: Some(model.dimension),
+        };
+        service.register(info, provider).await?;
+    }
+    if let Some(default_model) = &config.default_model {
+        service.set_default_model(default_model).await?;
+    }
+    info!("Embedding models registered: {}", config.models.len());
+    Ok(Some(Arc::new(service)))
+}
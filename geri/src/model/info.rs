@@ -2,11 +2,13 @@
 
 use serde::{Deserialize, Serialize};
 
-/// Modell-Typ (LLM, Vision, etc.).
+/// Modell-Typ (LLM, Vision, Embedding).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ModelType {
     Llm,
     Vision,
+    /// Text-Embedding-Modell (nur über `Embed`, nie für Prompts ausgewählt).
+    Embedding,
 }
 
 impl std::fmt::Display for ModelType {
@@ -14,6 +16,18 @@ impl std::fmt::Display for ModelType {
         match self {
             ModelType::Llm => write!(f, "Llm"),
             ModelType::Vision => write!(f, "Vision"),
+            ModelType::Embedding => write!(f, "Embedding"),
+        }
+    }
+}
+
+impl ModelType {
+    /// Parst die `Display`-Form; unbekannte Typen gelten als LLM.
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "Vision" => ModelType::Vision,
+            "Embedding" => ModelType::Embedding,
+            _ => ModelType::Llm,
         }
     }
 }
@@ -27,13 +41,13 @@ pub struct ModelInfo {
     pub name: String,
     /// Provider (z. B. openai, anthropic).
     pub provider: String,
-    /// Typ (LLM, Vision).
+    /// Typ (LLM, Vision, Embedding).
     pub model_type: ModelType,
     /// Optionale Parameter-Anzahl.
     pub parameter_count: Option<u64>,
     /// Optionale Hardware-Anforderungen (freitext oder strukturiert).
     pub hardware_requirements: Option<String>,
-    /// Optionale Context-Window-Größe (Tokens).
+    /// Optionale Context-Window-Größe (Tokens); bei Embedding-Modellen die maximale Input-Länge.
     pub context_window: Option<u32>,
     /// Ob das Model lokal oder in der Cloud läuft.
     pub is_local: bool,
@@ -41,4 +55,7 @@ pub struct ModelInfo {
     pub cost_per_token_input: Option<f64>,
     /// Kosten pro output Token (in Dollar).
     pub cost_per_token_output: Option<f64>,
+    /// Dimension der Vektoren (nur Embedding-Modelle).
+    #[serde(default)]
+    pub embedding_dimension: Option<u32>,
 }
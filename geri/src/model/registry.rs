@@ -1,7 +1,9 @@
 //! Model-Registry (Phase 6.1.2): Register/Unregister, Get/List/Filter.
 
 use std::collections::HashMap;
-use crate::model::{ModelInfo, ModelType};
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+use crate::model::{ModelInfo, ModelRegistryTrait, ModelType};
 
 /// In-Memory-Registry für Modelle (Register, Unregister, Get, List, Filter).
 #[derive(Debug, Clone, Default)]
@@ -56,3 +58,35 @@ impl ModelRegistry {
             .collect()
     }
 }
+
+/// Geteilte In-Memory-Registry als `ModelRegistryTrait` (z. B. für `GeriEngine` ohne Datenbank).
+#[async_trait]
+impl ModelRegistryTrait for RwLock<ModelRegistry> {
+    async fn register(&self, model: ModelInfo) -> Result<(), sqlx::Error> {
+        let mut registry = self.write().await;
+        *registry = std::mem::take(&mut *registry).register(model);
+        Ok(())
+    }
+
+    async fn unregister(&self, id: &str) -> Result<(), sqlx::Error> {
+        let mut registry = self.write().await;
+        *registry = std::mem::take(&mut *registry).unregister(id);
+        Ok(())
+    }
+
+    async fn get_by_id(&self, id: &str) -> Result<Option<ModelInfo>, sqlx::Error> {
+        Ok(self.read().await.get_by_id(id).cloned())
+    }
+
+    async fn list_all(&self) -> Result<Vec<ModelInfo>, sqlx::Error> {
+        Ok(self.read().await.list_all().into_iter().cloned().collect())
+    }
+
+    async fn filter_by_type(&self, model_type: ModelType) -> Result<Vec<ModelInfo>, sqlx::Error> {
+        Ok(self.read().await.filter_by_type(model_type).into_iter().cloned().collect())
+    }
+
+    async fn filter_by_provider(&self, provider: &str) -> Result<Vec<ModelInfo>, sqlx::Error> {
+        Ok(self.read().await.filter_by_provider(provider).into_iter().cloned().collect())
+    }
+}
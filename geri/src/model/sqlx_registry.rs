@@ -84,13 +84,14 @@ impl ModelRegistryTrait for SqlxModelRegistry {
             id: r.id,
             name: r.name,
             provider: r.provider,
-            model_type: if r.model_type == "Vision" { ModelType::Vision } else { ModelType::Llm },
+            model_type: ModelType::parse(&r.model_type),
             parameter_count: r.parameter_count.map(|p| p as u64),
             hardware_requirements: None, // We'd need another col if we wanted this
             context_window: Some(r.max_context_tokens as u32),
             is_local: r.is_local,
             cost_per_token_input: Some(r.cost_per_token_input.unwrap_or(0.0)),
             cost_per_token_output: Some(r.cost_per_token_output.unwrap_or(0.0)),
+            embedding_dimension: None,
         }))
     }
 
@@ -109,13 +110,14 @@ impl ModelRegistryTrait for SqlxModelRegistry {
             id: r.id,
             name: r.name,
             provider: r.provider,
-            model_type: if r.model_type == "Vision" { ModelType::Vision } else { ModelType::Llm },
+            model_type: ModelType::parse(&r.model_type),
             parameter_count: r.parameter_count.map(|p| p as u64),
             hardware_requirements: None,
             context_window: Some(r.max_context_tokens as u32),
             is_local: r.is_local,
             cost_per_token_input: Some(r.cost_per_token_input.unwrap_or(0.0)),
             cost_per_token_output: Some(r.cost_per_token_output.unwrap_or(0.0)),
+            embedding_dimension: None,
         }).collect())
     }
 
@@ -136,13 +138,14 @@ impl ModelRegistryTrait for SqlxModelRegistry {
             id: r.id,
             name: r.name,
             provider: r.provider,
-            model_type: if r.model_type == "Vision" { ModelType::Vision } else { ModelType::Llm },
+            model_type: ModelType::parse(&r.model_type),
             parameter_count: r.parameter_count.map(|p| p as u64),
             hardware_requirements: None,
             context_window: Some(r.max_context_tokens as u32),
             is_local: r.is_local,
             cost_per_token_input: Some(r.cost_per_token_input.unwrap_or(0.0)),
             cost_per_token_output: Some(r.cost_per_token_output.unwrap_or(0.0)),
+            embedding_dimension: None,
         }).collect())
     }
 
@@ -162,13 +165,14 @@ impl ModelRegistryTrait for SqlxModelRegistry {
             id: r.id,
             name: r.name,
             provider: r.provider,
-            model_type: if r.model_type == "Vision" { ModelType::Vision } else { ModelType::Llm },
+            model_type: ModelType::parse(&r.model_type),
             parameter_count: r.parameter_count.map(|p| p as u64),
             hardware_requirements: None,
             context_window: Some(r.max_context_tokens as u32),
             is_local: r.is_local,
             cost_per_token_input: Some(r.cost_per_token_input.unwrap_or(0.0)),
             cost_per_token_output: Some(r.cost_per_token_output.unwrap_or(0.0)),
+            embedding_dimension: None,
         }).collect())
     }
 }
@@ -0,0 +1,373 @@
+//! Chat-Templates (Phase 8.1.2): Modellfamilien-spezifische Prompt-Formate für lokale Provider
+//! (llama.cpp, BitNet), die keine Chat-API haben und die Rollen-Marker ihres Trainings erwarten.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::PromptFormatter;
+use crate::llm::tools::render_tool_instructions;
+use crate::llm::{ChatMessage, ChatRole, PromptRequest};
+use crate::model::ModelInfo;
+
+/// Chat-Template einer Modellfamilie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatTemplate {
+    /// Generisches `User:`/`Assistant:`-Format (Fallback für unbekannte Modelle).
+    #[default]
+    Plain,
+    /// `<|im_start|>role … <|im_end|>` (Qwen, Hermes, Yi, OpenChat, …).
+    ChatMl,
+    /// Llama 3 Header-Tokens (`<|start_header_id|>role<|end_header_id|>`).
+    Llama3,
+    /// Llama 2 `[INST] <<SYS>> … <</SYS>> … [/INST]`.
+    Llama2,
+    /// Mistral/Mixtral `[INST] … [/INST]` (System-Prompt im ersten User-Turn).
+    Mistral,
+    /// Gemma `<start_of_turn>user|model` (kein System-Turn).
+    Gemma,
+    /// Phi-3 `<|system|>`/`<|user|>`/`<|assistant|>` mit `<|end|>`.
+    Phi3,
+}
+
+/// Alle registrierten Templates (für Konfiguration und Auto-Detection).
+pub const CHAT_TEMPLATES: &[ChatTemplate] = &[
+    ChatTemplate::Plain,
+    ChatTemplate::ChatMl,
+    ChatTemplate::Llama3,
+    ChatTemplate::Llama2,
+    ChatTemplate::Mistral,
+    ChatTemplate::Gemma,
+    ChatTemplate::Phi3,
+];
+
+impl ChatTemplate {
+    /// Konfigurationsname (z. B. "chatml", "llama3").
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChatTemplate::Plain => "plain",
+            ChatTemplate::ChatMl => "chatml",
+            ChatTemplate::Llama3 => "llama3",
+            ChatTemplate::Llama2 => "llama2",
+            ChatTemplate::Mistral => "mistral",
+            ChatTemplate::Gemma => "gemma",
+            ChatTemplate::Phi3 => "phi3",
+        }
+    }
+
+    /// Parst einen Konfigurationsnamen (case-insensitive, `-`/`_` werden ignoriert).
+    pub fn parse(name: &str) -> Option<Self> {
+        let normalized: String = name
+            .chars()
+            .filter(|c| *c != '-' && *c != '_')
+            .collect::<String>()
+            .to_lowercase();
+        CHAT_TEMPLATES.iter().copied().find(|t| t.name() == normalized)
+    }
+
+    /// Erkennt das Template am Model-Namen oder -Pfad (z. B. `Meta-Llama-3-8B-Instruct.Q4_K_M.gguf`).
+    /// Verglichen wird auf Namens-Token (getrennt an `-`, `_`, `/`, `.`), damit z. B. `codellama-34b`
+    /// nicht als Llama 3 gilt. Unbekannte Modelle liefern `None`.
+    pub fn detect(model_name: &str) -> Option<Self> {
+        let name = model_name.to_lowercase();
+        let tokens: Vec<&str> = name
+            .split(['-', '_', '/', '\\', '.', ' '])
+            .filter(|t| !t.is_empty())
+            .collect();
+        let has = |needle: &str| has_name_tokens(&tokens, needle);
+        if has("llama-3") || has("llama3") {
+            Some(ChatTemplate::Llama3)
+        } else if has("llama-2") || has("llama2") {
+            Some(ChatTemplate::Llama2)
+        } else if has("mistral") || has("mixtral") {
+            Some(ChatTemplate::Mistral)
+        } else if has("gemma") {
+            Some(ChatTemplate::Gemma)
+        } else if has("phi-3") || has("phi3") {
+            Some(ChatTemplate::Phi3)
+        } else if has("qwen") || has("chatml") || has("hermes") || has("openchat") || has("yi") {
+            Some(ChatTemplate::ChatMl)
+        } else {
+            None
+        }
+    }
+
+    /// Erkennt das Template am Jinja-Chat-Template aus den GGUF-Metadaten
+    /// (`tokenizer.chat_template`) anhand der verwendeten Rollen-Marker.
+    pub fn from_jinja(template: &str) -> Option<Self> {
+        if template.contains("<|im_start|>") {
+            Some(ChatTemplate::ChatMl)
+        } else if template.contains("<|start_header_id|>") {
+            Some(ChatTemplate::Llama3)
+        } else if template.contains("<start_of_turn>") {
+            Some(ChatTemplate::Gemma)
+        } else if template.contains("<|user|>") && template.contains("<|end|>") {
+            Some(ChatTemplate::Phi3)
+        } else if template.contains("<<SYS>>") {
+            Some(ChatTemplate::Llama2)
+        } else if template.contains("[INST]") {
+            Some(ChatTemplate::Mistral)
+        } else {
+            None
+        }
+    }
+
+    /// Wählt das Template für ein lokales Model: explizite Konfiguration → GGUF-Metadaten →
+    /// Model-Name → `Plain`.
+    pub fn resolve(configured: Option<ChatTemplate>, model_path: &str) -> Self {
+        if let Some(template) = configured {
+            return template;
+        }
+        let from_metadata = crate::llm::gguf::read_chat_template(Path::new(model_path))
+            .ok()
+            .flatten()
+            .and_then(|jinja| Self::from_jinja(&jinja));
+        from_metadata
+            .or_else(|| Self::detect(model_path))
+            .unwrap_or_default()
+    }
+
+    /// Template für ein registriertes Model (Auswahl über ID, dann Name).
+    pub fn for_model_info(model: &ModelInfo) -> Self {
+        Self::detect(&model.id)
+            .or_else(|| Self::detect(&model.name))
+            .unwrap_or_default()
+    }
+
+    /// Marker, an denen die Generierung endet; Text danach wird verworfen,
+    /// damit keine Rollen-Marker in die Antwort gelangen.
+    pub fn stop_sequences(&self) -> &'static [&'static str] {
+        match self {
+            ChatTemplate::Plain => &[],
+            ChatTemplate::ChatMl => &["<|im_end|>", "<|im_start|>"],
+            ChatTemplate::Llama3 => &["<|eot_id|>", "<|start_header_id|>"],
+            ChatTemplate::Llama2 | ChatTemplate::Mistral => &["</s>", "[INST]"],
+            ChatTemplate::Gemma => &["<end_of_turn>", "<start_of_turn>"],
+            ChatTemplate::Phi3 => &["<|end|>", "<|user|>"],
+        }
+    }
+
+    /// Schneidet generierten Text am ersten Stop-Marker ab.
+    pub fn trim_output<'a>(&self, text: &'a str) -> &'a str {
+        let end = self
+            .stop_sequences()
+            .iter()
+            .filter_map(|stop| text.find(stop))
+            .min()
+            .unwrap_or(text.len());
+        &text[..end]
+    }
+
+    /// Rendert einen kompletten Request: Tool- und JSON-Schema-Instructions im System-Prompt, Verlauf und
+    /// aktueller Prompt als Turns, Tool-Calls/-Ergebnisse der letzten Runde als Assistant-/Tool-Turns.
+    pub fn render_request(&self, request: &PromptRequest) -> String {
+        let mut system_prompt = request.system_prompt.clone().unwrap_or_default();
+        if !request.tools.is_empty() {
+            push_paragraph(&mut system_prompt, &render_tool_instructions(&request.tools));
+        }
+        if let Some(format) = &request.response_format {
+            push_paragraph(&mut system_prompt, &format.render_instructions());
+        }
+        let mut conversation = request.conversation();
+        if !request.tool_calls.is_empty() {
+            let mut call_turn = ChatMessage::assistant("");
+            call_turn.tool_calls = request.tool_calls.clone();
+            conversation.push(call_turn);
+        }
+        for result in &request.tool_results {
+            let content = if result.is_error {
+                format!("Error: {}", result.content)
+            } else {
+                result.content.clone()
+            };
+            conversation.push(ChatMessage::tool(&result.tool_call_id, &result.name, content));
+        }
+        self.render(&system_prompt, &conversation, request.context.as_deref())
+    }
+
+    /// Rendert System-Prompt, optionalen RAG-Context und Verlauf (inkl. Tool-Calls und
+    /// Tool-Ergebnissen) im Format der Modellfamilie. Endet mit dem Assistant-Prefix,
+    /// damit das Modell die nächste Antwort schreibt.
+    pub fn render(&self, system_prompt: &str, messages: &[ChatMessage], context: Option<&str>) -> String {
+        let mut system = system_prompt.trim().to_string();
+        for m in messages.iter().filter(|m| m.role == ChatRole::System) {
+            push_paragraph(&mut system, m.content.trim());
+        }
+        if let Some(ctx) = context.map(str::trim).filter(|c| !c.is_empty()) {
+            push_paragraph(&mut system, &format!("Context:\n{}", ctx));
+        }
+        let turns: Vec<&ChatMessage> = messages.iter().filter(|m| m.role != ChatRole::System).collect();
+
+        match self {
+            ChatTemplate::Plain => render_plain(system_prompt, messages, context),
+            ChatTemplate::ChatMl => {
+                let mut out = String::new();
+                if !system.is_empty() {
+                    out.push_str(&format!("<|im_start|>system\n{}<|im_end|>\n", system));
+                }
+                for m in turns {
+                    let (role, text) = match m.role {
+                        ChatRole::Tool => ("user", format!("<tool_response>\n{}\n</tool_response>", tool_text(m))),
+                        ChatRole::Assistant => ("assistant", assistant_text(m)),
+                        _ => ("user", m.content.trim().to_string()),
+                    };
+                    out.push_str(&format!("<|im_start|>{}\n{}<|im_end|>\n", role, text));
+                }
+                out.push_str("<|im_start|>assistant\n");
+                out
+            }
+            ChatTemplate::Llama3 => {
+                let mut out = String::from("<|begin_of_text|>");
+                let header = |role: &str, text: &str| {
+                    format!("<|start_header_id|>{}<|end_header_id|>\n\n{}<|eot_id|>", role, text)
+                };
+                if !system.is_empty() {
+                    out.push_str(&header("system", &system));
+                }
+                for m in turns {
+                    let turn = match m.role {
+                        ChatRole::Tool => header("ipython", &tool_text(m)),
+                        ChatRole::Assistant => header("assistant", &assistant_text(m)),
+                        _ => header("user", m.content.trim()),
+                    };
+                    out.push_str(&turn);
+                }
+                out.push_str("<|start_header_id|>assistant<|end_header_id|>\n\n");
+                out
+            }
+            ChatTemplate::Llama2 | ChatTemplate::Mistral => {
+                let system_block = if system.is_empty() {
+                    String::new()
+                } else if *self == ChatTemplate::Llama2 {
+                    format!("<<SYS>>\n{}\n<</SYS>>\n\n", system)
+                } else {
+                    format!("{}\n\n", system)
+                };
+                inst_turns(&turns, &system_block, *self == ChatTemplate::Llama2)
+            }
+            ChatTemplate::Gemma => {
+                let mut out = String::new();
+                let mut pending_system = system;
+                for m in turns {
+                    let (role, mut text) = match m.role {
+                        ChatRole::Assistant => ("model", assistant_text(m)),
+                        ChatRole::Tool => ("user", tool_text(m)),
+                        _ => ("user", m.content.trim().to_string()),
+                    };
+                    // Gemma kennt keinen System-Turn: Prefix des ersten User-Turns
+                    if role == "user" && !pending_system.is_empty() {
+                        text = format!("{}\n\n{}", std::mem::take(&mut pending_system), text);
+                    }
+                    out.push_str(&format!("<start_of_turn>{}\n{}<end_of_turn>\n", role, text));
+                }
+                out.push_str("<start_of_turn>model\n");
+                out
+            }
+            ChatTemplate::Phi3 => {
+                let mut out = String::new();
+                if !system.is_empty() {
+                    out.push_str(&format!("<|system|>\n{}<|end|>\n", system));
+                }
+                for m in turns {
+                    let (role, text) = match m.role {
+                        ChatRole::Assistant => ("assistant", assistant_text(m)),
+                        ChatRole::Tool => ("user", tool_text(m)),
+                        _ => ("user", m.content.trim().to_string()),
+                    };
+                    out.push_str(&format!("<|{}|>\n{}<|end|>\n", role, text));
+                }
+                out.push_str("<|assistant|>\n");
+                out
+            }
+        }
+    }
+}
+
+/// `true`, wenn die Token von `needle` (z. B. "llama-3") aufeinanderfolgend im Namen stehen. Ein
+/// Familienname darf direkt eine Versionsnummer tragen ("qwen" passt auf `qwen2`, "gemma" auf `gemma2`).
+fn has_name_tokens(tokens: &[&str], needle: &str) -> bool {
+    let words: Vec<&str> = needle.split('-').collect();
+    let word_matches = |token: &str, word: &str| {
+        token == word
+            || (word.chars().all(|c| c.is_ascii_alphabetic())
+                && token
+                    .strip_prefix(word)
+                    .is_some_and(|version| version.chars().all(|c| c.is_ascii_digit())))
+    };
+    tokens
+        .windows(words.len())
+        .any(|window| window.iter().zip(&words).all(|(token, word)| word_matches(token, word)))
+}
+
+/// Generisches Format: ein einzelner User-Turn wie [`PromptFormatter::format`],
+/// sonst als Transcript wie [`PromptFormatter::format_conversation`].
+fn render_plain(system_prompt: &str, messages: &[ChatMessage], context: Option<&str>) -> String {
+    let formatter = PromptFormatter;
+    match messages {
+        [single] if single.role == ChatRole::User => formatter.format(system_prompt, &single.content, context),
+        _ => formatter.format_conversation(system_prompt, messages, context),
+    }
+}
+
+/// `[INST]`-Format (Llama 2, Mistral): der System-Block steht im ersten User-Turn,
+/// aufeinanderfolgende User-/Tool-Turns teilen sich einen `[INST]`-Block. Mit `bos_per_turn`
+/// (Llama 2) beginnt jeder `[INST]`-Block mit `<s>`, sonst (Mistral) nur der erste:
+/// `<s>[INST] a [/INST] b</s>[INST] c [/INST]`.
+fn inst_turns(turns: &[&ChatMessage], system_block: &str, bos_per_turn: bool) -> String {
+    let mut out = String::new();
+    let mut pending_system = system_block.to_string();
+    let mut user_parts: Vec<String> = Vec::new();
+    for m in turns {
+        match m.role {
+            ChatRole::Assistant => {
+                out.push_str(&inst_block(&mut pending_system, &user_parts, bos_per_turn || out.is_empty()));
+                user_parts.clear();
+                out.push_str(&format!(" {}</s>", assistant_text(m)));
+            }
+            ChatRole::Tool => user_parts.push(format!("[TOOL_RESULTS] {} [/TOOL_RESULTS]", tool_text(m))),
+            _ => user_parts.push(m.content.trim().to_string()),
+        }
+    }
+    let bos = bos_per_turn || out.is_empty();
+    out.push_str(&inst_block(&mut pending_system, &user_parts, bos));
+    out
+}
+
+fn inst_block(pending_system: &mut String, user_parts: &[String], bos: bool) -> String {
+    let bos = if bos { "<s>" } else { "" };
+    format!("{}[INST] {}{} [/INST]", bos, std::mem::take(pending_system), user_parts.join("\n\n"))
+}
+
+/// Assistant-Text inkl. Tool-Calls im JSON-Format der Tool-Instructions.
+fn assistant_text(m: &ChatMessage) -> String {
+    if m.tool_calls.is_empty() {
+        return m.content.trim().to_string();
+    }
+    let calls: Vec<serde_json::Value> = m
+        .tool_calls
+        .iter()
+        .map(|c| serde_json::json!({ "name": c.name, "arguments": c.arguments }))
+        .collect();
+    let json = serde_json::json!({ "tool_calls": calls }).to_string();
+    if m.content.trim().is_empty() {
+        json
+    } else {
+        format!("{}\n{}", m.content.trim(), json)
+    }
+}
+
+/// Tool-Ergebnis als JSON mit Tool-Name.
+fn tool_text(m: &ChatMessage) -> String {
+    serde_json::json!({ "name": m.name.as_deref().unwrap_or("tool"), "content": m.content.trim() }).to_string()
+}
+
+fn push_paragraph(target: &mut String, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    if !target.is_empty() {
+        target.push_str("\n\n");
+    }
+    target.push_str(text);
+}
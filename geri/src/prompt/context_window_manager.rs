@@ -1,6 +1,7 @@
 //! Context-Window-Manager (Phase 8.3.2): Model-Limit prüfen, Response-Reserve 20%, Truncation, Deduplizierung.
 
 use super::{ContextDocument, TokenCounter};
+use crate::llm::{ChatMessage, ChatRole};
 
 const RESPONSE_RESERVE_RATIO: f32 = 0.2;
 
@@ -16,6 +17,11 @@ impl ContextWindowManager {
         Self { token_counter }
     }
 
+    /// Der verwendete Token-Counter (teilt die Tokenizer-Registry mit dem Aufrufer).
+    pub fn token_counter(&self) -> &TokenCounter {
+        &self.token_counter
+    }
+
     /// Response-Reserve in Tokens (20% des Model-Limits).
     pub fn response_reserve_tokens(&self, model_limit: u32) -> u32 {
         ((model_limit as f32) * RESPONSE_RESERVE_RATIO) as u32
@@ -59,6 +65,57 @@ impl ContextWindowManager {
             .collect()
     }
 
+    /// Token-Anzahl eines Gesprächs-Turns (Inhalt plus Tool-Call-Argumente).
+    pub fn count_message(&self, message: &ChatMessage, model_name: &str) -> u32 {
+        let calls: u32 = message
+            .tool_calls
+            .iter()
+            .map(|c| self.token_counter.count_for_model(&format!("{} {}", c.name, c.arguments), model_name))
+            .sum();
+        self.token_counter.count_for_model(&message.content, model_name) + calls
+    }
+
+    /// Kürzt einen Gesprächsverlauf auf `max_tokens`: System-Turns bleiben erhalten, die ältesten
+    /// übrigen Turns fallen zuerst weg. Tool-Turns, deren Assistant-Turn weggefallen ist, werden mit entfernt.
+    pub fn trim_messages(
+        &self,
+        messages: &[ChatMessage],
+        max_tokens: u32,
+        model_name: &str,
+    ) -> Vec<ChatMessage> {
+        let system_t: u32 = messages
+            .iter()
+            .filter(|m| m.role == ChatRole::System)
+            .map(|m| self.count_message(m, model_name))
+            .sum();
+        let budget = max_tokens.saturating_sub(system_t);
+
+        // Von hinten (neueste Turns) auffüllen, bis das Budget erschöpft ist
+        let mut acc: u32 = 0;
+        let mut start = messages.len();
+        for (i, m) in messages.iter().enumerate().rev() {
+            if m.role == ChatRole::System {
+                continue;
+            }
+            let t = self.count_message(m, model_name);
+            if acc + t > budget {
+                break;
+            }
+            acc += t;
+            start = i;
+        }
+        while start < messages.len() && messages[start].role == ChatRole::Tool {
+            start += 1;
+        }
+
+        messages
+            .iter()
+            .enumerate()
+            .filter(|(i, m)| *i >= start || m.role == ChatRole::System)
+            .map(|(_, m)| m.clone())
+            .collect()
+    }
+
     /// Prüft, ob System + User + Context in das Model-Limit passen (inkl. 20% Response-Reserve).
     pub fn fits_in_window(
         &self,
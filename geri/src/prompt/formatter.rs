@@ -1,5 +1,8 @@
 //! Prompt-Formatter (Phase 8.1.1): System-Prompt, User-Prompt, Provider-spezifische Formatierung.
 
+use super::ChatTemplate;
+use crate::llm::{ChatMessage, ChatRole};
+
 /// Formatiert System-Prompt, optionalen RAG-Context und User-Prompt für LLM-Aufrufe.
 #[derive(Debug, Clone, Default)]
 pub struct PromptFormatter;
@@ -31,7 +34,62 @@ impl PromptFormatter {
         parts.join("\n\n")
     }
 
+    /// Formatiert einen Multi-Turn-Verlauf für Modelle ohne Chat-API (llama.cpp, BitNet).
+    ///
+    /// Reihenfolge: System (inkl. System-Turns) → Context → Turns als `User:`/`Assistant:`/`Tool:`-Zeilen.
+    /// Endet mit `Assistant:`, damit das Modell die nächste Antwort schreibt.
+    pub fn format_conversation(
+        &self,
+        system_prompt: &str,
+        messages: &[ChatMessage],
+        context: Option<&str>,
+    ) -> String {
+        let mut system = system_prompt.trim().to_string();
+        for m in messages.iter().filter(|m| m.role == ChatRole::System) {
+            if !system.is_empty() {
+                system.push_str("\n\n");
+            }
+            system.push_str(m.content.trim());
+        }
+        let turns: Vec<String> = messages
+            .iter()
+            .filter(|m| m.role != ChatRole::System)
+            .map(|m| match m.role {
+                ChatRole::Assistant if !m.tool_calls.is_empty() => {
+                    let calls: Vec<String> = m
+                        .tool_calls
+                        .iter()
+                        .map(|c| format!("{}({})", c.name, c.arguments))
+                        .collect();
+                    format!("Assistant: {}\nTool calls: {}", m.content.trim(), calls.join(", "))
+                }
+                ChatRole::Assistant => format!("Assistant: {}", m.content.trim()),
+                ChatRole::Tool => format!(
+                    "Tool ({}): {}",
+                    m.name.as_deref().unwrap_or("tool"),
+                    m.content.trim()
+                ),
+                _ => format!("User: {}", m.content.trim()),
+            })
+            .collect();
+        let mut conversation = turns.join("\n");
+        conversation.push_str("\nAssistant:");
+        self.format(&system, &conversation, context)
+    }
+
+    /// Formatiert einen Multi-Turn-Verlauf im Chat-Template der Modellfamilie (ChatML, Llama 3, Mistral, …).
+    pub fn format_chat(
+        &self,
+        template: ChatTemplate,
+        system_prompt: &str,
+        messages: &[ChatMessage],
+        context: Option<&str>,
+    ) -> String {
+        template.render(system_prompt, messages, context)
+    }
+
     /// Formatiert prompt provider-spezifisch (z. B. Llama [INST], OpenAI Chat).
+    /// Erkannte Modellfamilien nutzen ihr Chat-Template (siehe [`ChatTemplate::detect`]).
     pub fn format_for_provider(
         &self,
         provider_or_model: &str,
@@ -39,6 +97,9 @@ impl PromptFormatter {
         user_prompt: &str,
         context: Option<&str>,
     ) -> String {
+        if let Some(template) = ChatTemplate::detect(provider_or_model) {
+            return self.format_chat(template, system_prompt, &[ChatMessage::user(user_prompt.trim())], context);
+        }
         let base = self.format(system_prompt, user_prompt, context);
         if provider_or_model.to_lowercase().contains("llama")
             || provider_or_model.to_lowercase().contains("llama.cpp")
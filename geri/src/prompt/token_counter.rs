@@ -1,26 +1,58 @@
 //! Token-Counter (Phase 8.3.1): Token-Anzahl für Text berechnen, Model-spezifisches Token-Counting.
 
-/// Schätzt die Token-Anzahl für Text (ohne externes Tokenizer-Modul).
-/// Nutzt eine heuristische Näherung (~4 Zeichen pro Token für Englisch).
+use std::sync::Arc;
+
+use runar::{HeuristicTokenizer, Tokenizer, TokenizerRegistry};
+
+use crate::model::ModelInfo;
+
+/// Zählt Tokens für Text. Mit einer [`TokenizerRegistry`] werden die echten Vokabulare
+/// (BPE/SentencePiece aus `tokenizer.json`) des jeweiligen Models genutzt; ohne Registry
+/// oder für Modelle ohne Vokabular greift die Heuristik (~4 Zeichen pro Token für Englisch).
 #[derive(Debug, Clone, Default)]
-pub struct TokenCounter;
+pub struct TokenCounter {
+    tokenizers: Option<Arc<TokenizerRegistry>>,
+}
 
 impl TokenCounter {
-    /// Schätzt die Token-Anzahl für den gegebenen Text.
+    /// Token-Counter mit model-spezifischen Tokenizern aus der Registry.
+    pub fn with_tokenizers(tokenizers: Arc<TokenizerRegistry>) -> Self {
+        Self { tokenizers: Some(tokenizers) }
+    }
+
+    /// Schätzt die Token-Anzahl für den gegebenen Text (Heuristik).
     /// Leerer oder nur aus Whitespace bestehender Text liefert 0.
     pub fn count(&self, text: &str) -> u32 {
-        let t = text.trim();
-        if t.is_empty() {
-            return 0;
+        Self::clamp(HeuristicTokenizer.count(text))
+    }
+
+    /// Zählt die Token-Anzahl model-spezifisch.
+    /// Nutzt den Tokenizer des Models, falls registriert, sonst die Heuristik.
+    pub fn count_for_model(&self, text: &str, model_name: &str) -> u32 {
+        match self.tokenizers.as_ref().and_then(|r| r.get(model_name)) {
+            Some(tokenizer) => Self::clamp(tokenizer.count(text)),
+            None => self.count(text),
+        }
+    }
+
+    /// Zählt die Token-Anzahl für ein registriertes Model (Auswahl über ID, dann Name).
+    pub fn count_for_model_info(&self, text: &str, model: &ModelInfo) -> u32 {
+        let tokenizer = self
+            .tokenizers
+            .as_ref()
+            .and_then(|r| r.get(&model.id).or_else(|| r.get(&model.name)));
+        match tokenizer {
+            Some(tokenizer) => Self::clamp(tokenizer.count(text)),
+            None => self.count(text),
         }
-        let chars = t.chars().count();
-        ((chars + 3) / 4).min(u32::MAX as usize) as u32
     }
 
-    /// Schätzt die Token-Anzahl model-spezifisch.
-    /// Verschiedene Modelle können unterschiedliche Token-Verhältnisse haben;
-    /// aktuell wird dieselbe Heuristik verwendet, erweiterbar um tiktoken/Model-Vocab.
-    pub fn count_for_model(&self, text: &str, _model_name: &str) -> u32 {
-        self.count(text)
+    /// Ob für das Model ein echter Tokenizer (statt der Heuristik) verfügbar ist.
+    pub fn has_tokenizer(&self, model_name: &str) -> bool {
+        self.tokenizers.as_ref().is_some_and(|r| r.get(model_name).is_some())
+    }
+
+    fn clamp(count: usize) -> u32 {
+        count.min(u32::MAX as usize) as u32
     }
 }
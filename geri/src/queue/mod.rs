@@ -1,6 +1,8 @@
-//! Request-Queuing (Phase 13.1, 13.2): FIFO-Queue, Priority-Queue.
+//! Request-Queuing (Phase 13.1, 13.2): FIFO-Queue, Priority-Queue, Admission-Scheduler für lokale Provider.
 
 mod manager;
 mod priority;
+mod scheduler;
 pub use manager::{QueueFullError, RequestQueueManager};
 pub use priority::PriorityQueueManager;
+pub use scheduler::{QueueStats, RequestPriority, RequestScheduler, SchedulerConfig, SchedulerPermit};
@@ -0,0 +1,353 @@
+//! Admission-Scheduler für lokale Provider: Concurrency-Slots pro Model, Weighted-Fair-Queuing
+//! über Prioritäten und User, Queue-Zeit-Metriken und Abbruch wartender Requests.
+//!
+//! Verfahren: Start-Time Fair Queuing. Jeder Request bekommt beim Einreihen den Start-Tag
+//! `max(virtuelle Zeit, Finish-Tag seines Flows)` (Flow = User + Priorität), der Finish-Tag wächst
+//! um `1 / Gewicht` der Priorität. Freie Slots gehen an den kleinsten Start-Tag; so überholt ein Voice-Request
+//! eine Hintergrund-Zusammenfassung, und ein User mit vielen Requests verdrängt andere nicht.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+use crate::metrics::{MetricsCollector, MetricsSnapshot};
+use crate::queue::QueueFullError;
+
+/// Priorität eines Requests (aus den Request-Metadaten abgeleitet).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestPriority {
+    /// Batch-Arbeit ohne wartenden User (Indexierung, Zusammenfassungen, Evaluation).
+    Background,
+    /// Chat (Ragnarok, Odin ohne Sprachausgabe).
+    #[default]
+    Chat,
+    /// Sprachinteraktion, auf die ein User aktiv wartet.
+    Interactive,
+}
+
+impl RequestPriority {
+    /// Gewicht im Fair-Queuing: Anteil der Slots relativ zu den anderen Prioritäten.
+    pub fn weight(&self) -> f64 {
+        match self {
+            RequestPriority::Background => 1.0,
+            RequestPriority::Chat => 4.0,
+            RequestPriority::Interactive => 16.0,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RequestPriority::Background => "background",
+            RequestPriority::Chat => "chat",
+            RequestPriority::Interactive => "interactive",
+        }
+    }
+
+    /// Liest einen Metadaten-Wert (`voice`/`interactive`, `chat`, `background`/`batch`/`indexing`).
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "interactive" | "voice" => Some(RequestPriority::Interactive),
+            "chat" => Some(RequestPriority::Chat),
+            "background" | "batch" | "indexing" => Some(RequestPriority::Background),
+            _ => None,
+        }
+    }
+}
+
+/// Konfiguration des Schedulers (`scheduler` in der Geri-Config).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SchedulerConfig {
+    /// Scheduler vor den lokalen Providern aktivieren.
+    pub enabled: bool,
+    /// Gleichzeitige Requests pro Model (lokale Models rechnen meist nur einen Request effizient).
+    pub max_concurrent_per_model: usize,
+    /// Abweichende Slot-Anzahl pro Model-ID.
+    pub model_concurrency: HashMap<String, usize>,
+    /// Maximale Anzahl wartender Requests pro Model; weitere werden abgelehnt.
+    pub max_queue_len: usize,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_concurrent_per_model: 1,
+            model_concurrency: HashMap::new(),
+            max_queue_len: 64,
+        }
+    }
+}
+
+impl SchedulerConfig {
+    /// Slot-Anzahl für ein Model (mindestens 1).
+    pub fn concurrency_for(&self, model_id: &str) -> usize {
+        self.model_concurrency
+            .get(model_id)
+            .copied()
+            .unwrap_or(self.max_concurrent_per_model)
+            .max(1)
+    }
+}
+
+/// Momentaufnahme der Queue eines Models.
+#[derive(Debug, Clone)]
+pub struct QueueStats {
+    /// Belegte Slots.
+    pub in_flight: usize,
+    /// Slot-Anzahl.
+    pub slots: usize,
+    /// Wartende Requests.
+    pub waiting: usize,
+    /// Wartende Requests pro Priorität.
+    pub waiting_by_priority: HashMap<RequestPriority, usize>,
+    /// Queue-Zeiten (ms) aller zugelassenen Requests.
+    pub queue_time: MetricsSnapshot,
+}
+
+struct Waiter {
+    id: u64,
+    start_tag: f64,
+    priority: RequestPriority,
+    enqueued_at: Instant,
+    grant: oneshot::Sender<Duration>,
+}
+
+struct ModelQueue {
+    slots: usize,
+    in_flight: usize,
+    /// Start-Tag des zuletzt zugelassenen Requests.
+    virtual_time: f64,
+    /// Finish-Tag pro User und Priorität (anonyme Requests teilen sich einen Eintrag).
+    flow_finish: HashMap<(String, RequestPriority), f64>,
+    waiting: Vec<Waiter>,
+    queue_time: MetricsCollector,
+}
+
+impl ModelQueue {
+    fn new(slots: usize) -> Self {
+        Self {
+            slots,
+            in_flight: 0,
+            virtual_time: 0.0,
+            flow_finish: HashMap::new(),
+            waiting: Vec::new(),
+            queue_time: MetricsCollector::new(),
+        }
+    }
+
+    /// Vergibt den Start-Tag und schreibt den Finish-Tag des Flows (User + Priorität) fort;
+    /// eigene Hintergrund-Requests halten so keinen Voice-Request desselben Users auf.
+    fn tag(&mut self, user: &str, priority: RequestPriority) -> f64 {
+        let flow = (user.to_string(), priority);
+        let finish = self.flow_finish.get(&flow).copied().unwrap_or(0.0);
+        let start = finish.max(self.virtual_time);
+        self.flow_finish.insert(flow, start + 1.0 / priority.weight());
+        start
+    }
+
+    /// Vergibt freie Slots an die Wartenden mit dem kleinsten Start-Tag (bei Gleichstand höhere
+    /// Priorität, dann FIFO).
+    fn dispatch(&mut self) {
+        while self.in_flight < self.slots && !self.waiting.is_empty() {
+            let next = self
+                .waiting
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| {
+                    a.start_tag
+                        .total_cmp(&b.start_tag)
+                        .then(b.priority.weight().total_cmp(&a.priority.weight()))
+                        .then(a.id.cmp(&b.id))
+                })
+                .map(|(index, _)| index)
+                .expect("waiting is not empty");
+            let waiter = self.waiting.remove(next);
+            self.virtual_time = self.virtual_time.max(waiter.start_tag);
+            self.in_flight += 1;
+            let waited = waiter.enqueued_at.elapsed();
+            self.queue_time.record_response(waited.as_millis() as u64);
+            tracing::debug!(priority = waiter.priority.as_str(), waited_ms = waited.as_millis() as u64, "Request admitted from queue");
+            // Ist der Empfänger schon weg, gibt sein WaitGuard den Slot wieder frei
+            let _ = waiter.grant.send(waited);
+        }
+        // Ohne Last beginnt die Fairness-Rechnung neu
+        if self.in_flight == 0 && self.waiting.is_empty() {
+            self.flow_finish.clear();
+            self.virtual_time = 0.0;
+        }
+    }
+
+    fn release(&mut self) {
+        self.in_flight = self.in_flight.saturating_sub(1);
+        self.dispatch();
+    }
+}
+
+#[derive(Default)]
+struct SchedulerState {
+    queues: HashMap<String, ModelQueue>,
+    next_id: u64,
+}
+
+/// Admission-Scheduler; Klone teilen sich die Queues.
+#[derive(Clone)]
+pub struct RequestScheduler {
+    config: SchedulerConfig,
+    state: Arc<Mutex<SchedulerState>>,
+}
+
+impl RequestScheduler {
+    pub fn new(config: SchedulerConfig) -> Self {
+        Self {
+            config,
+            state: Arc::new(Mutex::new(SchedulerState::default())),
+        }
+    }
+
+    pub fn config(&self) -> &SchedulerConfig {
+        &self.config
+    }
+
+    /// Wartet auf einen Slot für `model_id`. Wird das Future verworfen (z. B. weil der
+    /// gRPC-Client die Verbindung getrennt hat), verlässt der Request die Queue.
+    /// Fehler, wenn bereits `max_queue_len` Requests warten.
+    pub async fn acquire(
+        &self,
+        model_id: &str,
+        priority: RequestPriority,
+        user_id: Option<&str>,
+    ) -> Result<SchedulerPermit, QueueFullError> {
+        let (id, granted) = {
+            let mut state = self.state.lock().expect("scheduler lock poisoned");
+            state.next_id += 1;
+            let id = state.next_id;
+            let slots = self.config.concurrency_for(model_id);
+            let queue = state
+                .queues
+                .entry(model_id.to_string())
+                .or_insert_with(|| ModelQueue::new(slots));
+            if queue.in_flight < queue.slots && queue.waiting.is_empty() {
+                let start_tag = queue.tag(user_id.unwrap_or_default(), priority);
+                queue.virtual_time = queue.virtual_time.max(start_tag);
+                queue.in_flight += 1;
+                queue.queue_time.record_response(0);
+                return Ok(self.permit(model_id, Duration::ZERO));
+            }
+            if queue.waiting.len() >= self.config.max_queue_len {
+                return Err(QueueFullError);
+            }
+            let (grant, granted) = oneshot::channel();
+            let start_tag = queue.tag(user_id.unwrap_or_default(), priority);
+            queue.waiting.push(Waiter {
+                id,
+                start_tag,
+                priority,
+                enqueued_at: Instant::now(),
+                grant,
+            });
+            (id, granted)
+        };
+
+        let mut guard = WaitGuard {
+            state: self.state.clone(),
+            model_id: model_id.to_string(),
+            id,
+            admitted: false,
+        };
+        let waited = granted.await.expect("waiter is only dropped after it was granted");
+        guard.admitted = true;
+        Ok(self.permit(model_id, waited))
+    }
+
+    /// Aktuelle Auslastung und Queue-Zeiten eines Models; `None`, wenn noch kein Request kam.
+    pub fn stats(&self, model_id: &str) -> Option<QueueStats> {
+        let state = self.state.lock().expect("scheduler lock poisoned");
+        state.queues.get(model_id).map(|queue| {
+            let mut waiting_by_priority = HashMap::new();
+            for waiter in &queue.waiting {
+                *waiting_by_priority.entry(waiter.priority).or_insert(0) += 1;
+            }
+            QueueStats {
+                in_flight: queue.in_flight,
+                slots: queue.slots,
+                waiting: queue.waiting.len(),
+                waiting_by_priority,
+                queue_time: queue.queue_time.snapshot(),
+            }
+        })
+    }
+
+    fn permit(&self, model_id: &str, waited: Duration) -> SchedulerPermit {
+        SchedulerPermit {
+            state: self.state.clone(),
+            model_id: model_id.to_string(),
+            waited,
+        }
+    }
+}
+
+impl std::fmt::Debug for RequestScheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RequestScheduler").field("config", &self.config).finish()
+    }
+}
+
+fn release(state: &Mutex<SchedulerState>, model_id: &str) {
+    let mut state = state.lock().expect("scheduler lock poisoned");
+    if let Some(queue) = state.queues.get_mut(model_id) {
+        queue.release();
+    }
+}
+
+/// Belegter Slot; wird beim Drop freigegeben und an den nächsten Wartenden vergeben.
+pub struct SchedulerPermit {
+    state: Arc<Mutex<SchedulerState>>,
+    model_id: String,
+    waited: Duration,
+}
+
+impl SchedulerPermit {
+    /// Zeit in der Queue.
+    pub fn waited(&self) -> Duration {
+        self.waited
+    }
+}
+
+impl Drop for SchedulerPermit {
+    fn drop(&mut self) {
+        release(&self.state, &self.model_id);
+    }
+}
+
+/// Entfernt einen abgebrochenen Request aus der Queue; war ihm schon ein Slot zugeteilt,
+/// wird dieser freigegeben.
+struct WaitGuard {
+    state: Arc<Mutex<SchedulerState>>,
+    model_id: String,
+    id: u64,
+    admitted: bool,
+}
+
+impl Drop for WaitGuard {
+    fn drop(&mut self) {
+        if self.admitted {
+            return;
+        }
+        let mut state = self.state.lock().expect("scheduler lock poisoned");
+        let Some(queue) = state.queues.get_mut(&self.model_id) else {
+            return;
+        };
+        match queue.waiting.iter().position(|waiter| waiter.id == self.id) {
+            Some(index) => {
+                queue.waiting.remove(index);
+                tracing::debug!(model = %self.model_id, "Queued request cancelled");
+            }
+            None => queue.release(),
+        }
+    }
+}
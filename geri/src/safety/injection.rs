@@ -0,0 +1,101 @@
+//! Detects instructions aimed at the model inside RAG context and tool results, e.g. a web page
+//! saying "ignore all previous instructions" or smuggling chat-template control tokens.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use super::{FilterAction, FilterDecision, FilterStage, SafetyFilter};
+use crate::llm::{ChatRole, PromptRequest};
+use crate::model::ModelInfo;
+
+pub const INJECTION_FILTER: &str = "prompt_injection";
+
+/// Patterns (case-insensitive) that address the model instead of informing it
+const INJECTION_PATTERNS: &[(&str, &str)] = &[
+    ("override", r"\b(ignore|disregard|forget|override)\b.{0,30}\b(previous|prior|above|earlier|preceding|system|all|your)\b.{0,20}\b(instructions?|prompts?|rules|directions|guidelines)\b"),
+    ("role change", r"\byou are now\b.{0,40}\b(mode|assistant|ai|dan|unrestricted|jailbroken)\b"),
+    ("new instructions", r"\b(new|updated|real|actual|hidden)\s+(system\s+)?(instructions?|prompt)\s*:"),
+    ("prompt extraction", r"\b(reveal|print|show|repeat|output)\b.{0,20}\b(system prompt|your instructions|hidden prompt|initial prompt)\b"),
+    ("concealment", r"\b(do not|don't|never)\s+(tell|inform|alert|mention (this|it) to)\s+the user\b"),
+    ("exfiltration", r"\b(send|post|upload|forward|exfiltrate)\b.{0,40}\b(passwords?|api[ _-]?keys?|tokens?|credentials|secrets?)\b"),
+    ("template tokens", r"(<\|im_start\|>|<\|im_end\|>|<\|system\|>|<\|start_header_id\|>|\[/?INST\]|<</?SYS>>)"),
+];
+
+/// What to do with context containing injected instructions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InjectionMode {
+    /// Keep the content, report it (and let the output check flag resulting actions)
+    #[default]
+    Flag,
+    /// Drop the lines containing the instructions
+    Remove,
+    /// Reject the request
+    Block,
+}
+
+pub struct InjectionDetector {
+    mode: InjectionMode,
+    patterns: Vec<(&'static str, Regex)>,
+}
+
+impl InjectionDetector {
+    pub fn new(mode: InjectionMode) -> Self {
+        let patterns = INJECTION_PATTERNS
+            .iter()
+            .map(|(label, pattern)| (*label, Regex::new(&format!("(?i){}", pattern)).expect("valid injection pattern")))
+            .collect();
+        Self { mode, patterns }
+    }
+
+    /// Labels of the patterns found in `text`
+    pub fn detect(&self, text: &str) -> Vec<&'static str> {
+        self.patterns
+            .iter()
+            .filter(|(_, regex)| regex.is_match(text))
+            .map(|(label, _)| *label)
+            .collect()
+    }
+
+    /// Checks one untrusted text; in `Remove` mode offending lines are dropped
+    fn inspect(&self, source: &str, text: &mut String, decisions: &mut Vec<FilterDecision>) {
+        let found = self.detect(text);
+        if found.is_empty() {
+            return;
+        }
+        let action = match self.mode {
+            InjectionMode::Flag => FilterAction::Flagged,
+            InjectionMode::Remove => {
+                let kept: Vec<&str> = text.lines().filter(|line| self.detect(line).is_empty()).collect();
+                *text = kept.join("\n");
+                FilterAction::Removed
+            }
+            InjectionMode::Block => FilterAction::Blocked,
+        };
+        decisions.push(FilterDecision::new(
+            INJECTION_FILTER,
+            FilterStage::Request,
+            action,
+            format!("instructions in {} ({})", source, found.join(", ")),
+        ));
+    }
+}
+
+impl SafetyFilter for InjectionDetector {
+    fn name(&self) -> &str {
+        INJECTION_FILTER
+    }
+
+    fn filter_request(&self, request: &mut PromptRequest, _target: &ModelInfo, decisions: &mut Vec<FilterDecision>) {
+        if let Some(context) = request.context.as_mut() {
+            self.inspect("context", context, decisions);
+        }
+        for result in request.tool_results.iter_mut() {
+            self.inspect(&format!("tool result {}", result.name), &mut result.content, decisions);
+        }
+        for message in request.messages.iter_mut().filter(|message| message.role == ChatRole::Tool) {
+            let source = format!("tool message {}", message.name.as_deref().unwrap_or("unknown"));
+            self.inspect(&source, &mut message.content, decisions);
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! Safety filters around prompt processing: checks on the request before it reaches a model
+//! (injected instructions in RAG context, secrets and PII for cloud providers) and on the answer
+//! before it reaches Thor (tool calls and shell commands prompted by untrusted context).
+//! Every decision is logged and returned with the response.
+
+mod injection;
+mod output;
+mod redaction;
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::llm::{ChatRole, LLMError, PromptRequest, PromptResponse};
+use crate::model::ModelInfo;
+
+pub use injection::{InjectionDetector, InjectionMode, INJECTION_FILTER};
+pub use output::{UntrustedActionCheck, UNTRUSTED_ACTION_FILTER};
+pub use redaction::{SecretKind, SecretRedactor, SECRET_FILTER};
+
+/// When a filter ran
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterStage {
+    Request,
+    Response,
+}
+
+impl FilterStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FilterStage::Request => "request",
+            FilterStage::Response => "response",
+        }
+    }
+}
+
+/// What a filter did
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterAction {
+    /// Content left unchanged, reported for the caller to decide (e.g. confirm a tool call)
+    Flagged,
+    /// Sensitive values replaced by placeholders
+    Redacted,
+    /// Offending parts dropped (context lines, tool calls)
+    Removed,
+    /// Request rejected
+    Blocked,
+}
+
+impl FilterAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FilterAction::Flagged => "flagged",
+            FilterAction::Redacted => "redacted",
+            FilterAction::Removed => "removed",
+            FilterAction::Blocked => "blocked",
+        }
+    }
+}
+
+/// One filter decision; never contains the sensitive value itself
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilterDecision {
+    pub filter: String,
+    pub stage: FilterStage,
+    pub action: FilterAction,
+    pub detail: String,
+}
+
+impl FilterDecision {
+    pub fn new(filter: &str, stage: FilterStage, action: FilterAction, detail: impl Into<String>) -> Self {
+        Self {
+            filter: filter.to_string(),
+            stage,
+            action,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// A pre/post filter. Filters append their decisions; a `Blocked` request decision stops the request.
+pub trait SafetyFilter: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Inspect or rewrite the request before it is sent to `target`
+    fn filter_request(&self, _request: &mut PromptRequest, _target: &ModelInfo, _decisions: &mut Vec<FilterDecision>) {}
+
+    /// Inspect or rewrite the answer; `decisions` already holds those of the request stage
+    fn filter_response(&self, _request: &PromptRequest, _response: &mut PromptResponse, _decisions: &mut Vec<FilterDecision>) {}
+}
+
+/// Content that did not come from the user: RAG context and tool results (web pages, files)
+pub fn untrusted_content(request: &PromptRequest) -> Vec<&str> {
+    let mut content: Vec<&str> = request.context.iter().map(String::as_str).collect();
+    content.extend(request.tool_results.iter().map(|result| result.content.as_str()));
+    content.extend(
+        request
+            .messages
+            .iter()
+            .filter(|message| message.role == ChatRole::Tool)
+            .map(|message| message.content.as_str()),
+    );
+    content.retain(|text| !text.trim().is_empty());
+    content
+}
+
+/// Filter settings (`safety` in the Geri config)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SafetyConfig {
+    /// Run the filters on every request
+    pub enabled: bool,
+    /// What happens with injected instructions in context and tool results
+    pub injection_mode: InjectionMode,
+    /// Redact API keys, IBANs and email addresses for cloud models
+    pub redact_secrets: bool,
+    /// Also redact for local models
+    pub redact_for_local: bool,
+    /// Drop tool calls prompted by untrusted content instead of flagging them
+    pub remove_untrusted_tool_calls: bool,
+}
+
+impl Default for SafetyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            injection_mode: InjectionMode::Flag,
+            redact_secrets: true,
+            redact_for_local: false,
+            remove_untrusted_tool_calls: false,
+        }
+    }
+}
+
+impl SafetyConfig {
+    pub fn pipeline(&self) -> SafetyPipeline {
+        if !self.enabled {
+            return SafetyPipeline::new();
+        }
+        let mut pipeline = SafetyPipeline::new().with_filter(InjectionDetector::new(self.injection_mode));
+        if self.redact_secrets {
+            pipeline = pipeline.with_filter(SecretRedactor::new().with_redact_for_local(self.redact_for_local));
+        }
+        pipeline.with_filter(UntrustedActionCheck::new().with_remove_tool_calls(self.remove_untrusted_tool_calls))
+    }
+}
+
+/// Ordered filter chain; empty by default (nothing is checked).
+#[derive(Clone, Default)]
+pub struct SafetyPipeline {
+    filters: Vec<Arc<dyn SafetyFilter>>,
+}
+
+impl SafetyPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Injection detection (flagging), secret/PII redaction for cloud models and flagging of
+    /// actions prompted by untrusted context
+    pub fn standard() -> Self {
+        SafetyConfig::default().pipeline()
+    }
+
+    pub fn with_filter(mut self, filter: impl SafetyFilter + 'static) -> Self {
+        self.filters.push(Arc::new(filter));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+
+    /// Filtered copy of the request for `target` and the decisions made; fails if a filter
+    /// blocked the request.
+    pub fn filter_request(&self, request: &PromptRequest, target: &ModelInfo) -> Result<(PromptRequest, Vec<FilterDecision>), LLMError> {
+        let mut filtered = request.clone();
+        let mut decisions = Vec::new();
+        for filter in &self.filters {
+            let before = decisions.len();
+            filter.filter_request(&mut filtered, target, &mut decisions);
+            Self::log(&decisions[before..], &target.id);
+            if let Some(blocked) = decisions[before..].iter().find(|d| d.action == FilterAction::Blocked) {
+                return Err(LLMError::InvalidInput(format!(
+                    "Request blocked by safety filter {}: {}",
+                    blocked.filter, blocked.detail
+                )));
+            }
+        }
+        Ok((filtered, decisions))
+    }
+
+    /// Runs the response filters and attaches all decisions (request and response stage) to the answer
+    pub fn filter_response(&self, request: &PromptRequest, mut response: PromptResponse, mut decisions: Vec<FilterDecision>, model_id: &str) -> PromptResponse {
+        for filter in &self.filters {
+            let before = decisions.len();
+            filter.filter_response(request, &mut response, &mut decisions);
+            Self::log(&decisions[before..], model_id);
+        }
+        response.safety = decisions;
+        response
+    }
+
+    /// Whether the answer to `request` has to be complete before it is checked (streaming
+    /// callers then receive it in one piece)
+    pub fn checks_response(&self, request: &PromptRequest) -> bool {
+        !self.is_empty() && !untrusted_content(request).is_empty()
+    }
+
+    fn log(decisions: &[FilterDecision], model_id: &str) {
+        for decision in decisions {
+            tracing::warn!(
+                filter = %decision.filter,
+                stage = decision.stage.as_str(),
+                action = decision.action.as_str(),
+                model = %model_id,
+                "Safety filter: {}",
+                decision.detail
+            );
+        }
+    }
+}
+
+impl std::fmt::Debug for SafetyPipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.filters.iter().map(|filter| filter.name())).finish()
+    }
+}
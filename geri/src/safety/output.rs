@@ -0,0 +1,115 @@
+//! Flags tool calls and shell commands in answers to requests that carried untrusted content,
+//! so Odin/Thor can ask for confirmation instead of executing what a web page or document asked for.
+
+use regex::Regex;
+
+use super::{untrusted_content, FilterAction, FilterDecision, FilterStage, SafetyFilter, INJECTION_FILTER};
+use crate::llm::{ChatRole, PromptRequest, PromptResponse};
+
+pub const UNTRUSTED_ACTION_FILTER: &str = "untrusted_action";
+
+/// Argument values shorter than this are too generic to attribute to the context
+const MIN_ORIGIN_MATCH_CHARS: usize = 8;
+
+const SHELL_PATTERN: &str = r"(?im)(```\s*(bash|sh|shell|zsh|powershell|cmd)\b|\brm\s+-[a-z]*r[a-z]*f|\b(curl|wget)\b[^\n|]*\|\s*(sudo\s+)?(ba|z)?sh\b|^\s*(\$\s+)?sudo\s+\S|\bmkfs(\.\w+)?\s|\bdd\s+if=|\bchmod\s+(\+x|[0-7]{3,4})\s)";
+
+pub struct UntrustedActionCheck {
+    /// Drop flagged tool calls instead of only reporting them
+    remove_tool_calls: bool,
+    shell: Regex,
+}
+
+impl Default for UntrustedActionCheck {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UntrustedActionCheck {
+    pub fn new() -> Self {
+        Self {
+            remove_tool_calls: false,
+            shell: Regex::new(SHELL_PATTERN).expect("valid shell pattern"),
+        }
+    }
+
+    pub fn with_remove_tool_calls(mut self, remove_tool_calls: bool) -> Self {
+        self.remove_tool_calls = remove_tool_calls;
+        self
+    }
+}
+
+/// All string values of a JSON document
+fn string_values(value: &serde_json::Value, out: &mut Vec<String>) {
+    match value {
+        serde_json::Value::String(s) => out.push(s.clone()),
+        serde_json::Value::Array(items) => items.iter().for_each(|item| string_values(item, out)),
+        serde_json::Value::Object(map) => map.values().for_each(|item| string_values(item, out)),
+        _ => {}
+    }
+}
+
+impl SafetyFilter for UntrustedActionCheck {
+    fn name(&self) -> &str {
+        UNTRUSTED_ACTION_FILTER
+    }
+
+    fn filter_response(&self, request: &PromptRequest, response: &mut PromptResponse, decisions: &mut Vec<FilterDecision>) {
+        let untrusted = untrusted_content(request);
+        if untrusted.is_empty() {
+            return;
+        }
+        let injected = decisions
+            .iter()
+            .any(|d| d.filter == INJECTION_FILTER && d.stage == FilterStage::Request);
+        let from_user: Vec<&str> = std::iter::once(request.prompt.as_str())
+            .chain(
+                request
+                    .messages
+                    .iter()
+                    .filter(|message| message.role == ChatRole::User)
+                    .map(|message| message.content.as_str()),
+            )
+            .collect();
+
+        let mut kept = Vec::new();
+        for call in std::mem::take(&mut response.tool_calls) {
+            let mut values = Vec::new();
+            string_values(&call.arguments, &mut values);
+            // A value the user never wrote but the untrusted content contains
+            let copied = values.iter().find(|value| {
+                value.len() >= MIN_ORIGIN_MATCH_CHARS
+                    && untrusted.iter().any(|text| text.contains(value.as_str()))
+                    && !from_user.iter().any(|text| text.contains(value.as_str()))
+            });
+            let reason = match (injected, copied) {
+                (true, _) => "context contained injected instructions",
+                (false, Some(_)) => "arguments copied from untrusted content",
+                (false, None) => {
+                    kept.push(call);
+                    continue;
+                }
+            };
+            let action = if self.remove_tool_calls { FilterAction::Removed } else { FilterAction::Flagged };
+            decisions.push(FilterDecision::new(
+                UNTRUSTED_ACTION_FILTER,
+                FilterStage::Response,
+                action,
+                format!("tool call {} ({}): {}", call.name, call.id, reason),
+            ));
+            if !self.remove_tool_calls {
+                kept.push(call);
+            }
+        }
+        response.tool_calls = kept;
+
+        if self.shell.is_match(&response.text) {
+            decisions.push(FilterDecision::new(
+                UNTRUSTED_ACTION_FILTER,
+                FilterStage::Response,
+                FilterAction::Flagged,
+                "shell command in answer to a request with untrusted content",
+            ));
+        }
+    }
+}
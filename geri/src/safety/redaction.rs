@@ -0,0 +1,163 @@
+//! Replaces API keys, IBANs and email addresses with placeholders before a request leaves the
+//! device for a cloud provider.
+
+use regex::{Captures, Regex};
+
+use super::{FilterAction, FilterDecision, FilterStage, SafetyFilter};
+use crate::llm::PromptRequest;
+use crate::model::ModelInfo;
+
+pub const SECRET_FILTER: &str = "secret_redaction";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecretKind {
+    ApiKey,
+    Iban,
+    Email,
+}
+
+impl SecretKind {
+    pub fn placeholder(&self) -> &'static str {
+        match self {
+            SecretKind::ApiKey => "[REDACTED_API_KEY]",
+            SecretKind::Iban => "[REDACTED_IBAN]",
+            SecretKind::Email => "[REDACTED_EMAIL]",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            SecretKind::ApiKey => "API key",
+            SecretKind::Iban => "IBAN",
+            SecretKind::Email => "email address",
+        }
+    }
+}
+
+/// OpenAI/Anthropic, AWS, GitHub, Slack, Google and GitLab key formats
+const API_KEY_PATTERN: &str = r"\b(sk-(proj-|ant-)?[A-Za-z0-9_\-]{20,}|AKIA[0-9A-Z]{16}|gh[pousr]_[A-Za-z0-9]{36,}|xox[abposr]-[A-Za-z0-9\-]{10,}|AIza[0-9A-Za-z_\-]{35}|glpat-[A-Za-z0-9_\-]{20,})";
+const IBAN_PATTERN: &str = r"\b[A-Z]{2}[0-9]{2}(?: ?[A-Z0-9]{4}){2,7}(?: ?[A-Z0-9]{1,3})?\b";
+const EMAIL_PATTERN: &str = r"\b[A-Za-z0-9._%+\-]+@[A-Za-z0-9.\-]+\.[A-Za-z]{2,}\b";
+
+pub struct SecretRedactor {
+    patterns: Vec<(SecretKind, Regex)>,
+    /// Also redact for local models (by default only data leaving the device is redacted)
+    redact_for_local: bool,
+}
+
+impl Default for SecretRedactor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SecretRedactor {
+    pub fn new() -> Self {
+        let patterns = [
+            (SecretKind::ApiKey, API_KEY_PATTERN),
+            (SecretKind::Iban, IBAN_PATTERN),
+            (SecretKind::Email, EMAIL_PATTERN),
+        ]
+        .into_iter()
+        .map(|(kind, pattern)| (kind, Regex::new(pattern).expect("valid secret pattern")))
+        .collect();
+        Self {
+            patterns,
+            redact_for_local: false,
+        }
+    }
+
+    pub fn with_redact_for_local(mut self, redact_for_local: bool) -> Self {
+        self.redact_for_local = redact_for_local;
+        self
+    }
+
+    /// Redacted text and the number of replacements per kind
+    pub fn redact(&self, text: &str) -> (String, Vec<(SecretKind, usize)>) {
+        let mut redacted = text.to_string();
+        let mut counts = Vec::new();
+        for (kind, regex) in &self.patterns {
+            let mut count = 0;
+            redacted = regex
+                .replace_all(&redacted, |caps: &Captures| {
+                    let value = &caps[0];
+                    if *kind == SecretKind::Iban && !iban_checksum_valid(value) {
+                        return value.to_string();
+                    }
+                    count += 1;
+                    kind.placeholder().to_string()
+                })
+                .into_owned();
+            if count > 0 {
+                counts.push((*kind, count));
+            }
+        }
+        (redacted, counts)
+    }
+
+    fn redact_field(&self, text: &mut String, totals: &mut Vec<(SecretKind, usize)>) {
+        let (redacted, counts) = self.redact(text);
+        if counts.is_empty() {
+            return;
+        }
+        *text = redacted;
+        for (kind, count) in counts {
+            match totals.iter_mut().find(|(k, _)| *k == kind) {
+                Some((_, total)) => *total += count,
+                None => totals.push((kind, count)),
+            }
+        }
+    }
+}
+
+/// ISO 13616 check: move the first four characters to the end, letters to numbers, mod 97 == 1
+fn iban_checksum_valid(iban: &str) -> bool {
+    let compact: String = iban.chars().filter(|c| !c.is_whitespace()).collect();
+    if !(15..=34).contains(&compact.len()) {
+        return false;
+    }
+    let rearranged = compact[4..].chars().chain(compact[..4].chars());
+    let mut remainder: u32 = 0;
+    for c in rearranged {
+        let value = match c.to_digit(36) {
+            Some(value) => value,
+            None => return false,
+        };
+        remainder = if value < 10 { (remainder * 10 + value) % 97 } else { (remainder * 100 + value) % 97 };
+    }
+    remainder == 1
+}
+
+impl SafetyFilter for SecretRedactor {
+    fn name(&self) -> &str {
+        SECRET_FILTER
+    }
+
+    fn filter_request(&self, request: &mut PromptRequest, target: &ModelInfo, decisions: &mut Vec<FilterDecision>) {
+        if target.is_local && !self.redact_for_local {
+            return;
+        }
+        let mut totals = Vec::new();
+        self.redact_field(&mut request.prompt, &mut totals);
+        if let Some(system_prompt) = request.system_prompt.as_mut() {
+            self.redact_field(system_prompt, &mut totals);
+        }
+        if let Some(context) = request.context.as_mut() {
+            self.redact_field(context, &mut totals);
+        }
+        for message in request.messages.iter_mut() {
+            self.redact_field(&mut message.content, &mut totals);
+        }
+        for result in request.tool_results.iter_mut() {
+            self.redact_field(&mut result.content, &mut totals);
+        }
+        for (kind, count) in totals {
+            decisions.push(FilterDecision::new(
+                SECRET_FILTER,
+                FilterStage::Request,
+                FilterAction::Redacted,
+                format!("{} {}(s) redacted before sending to {}", count, kind.label(), target.id),
+            ));
+        }
+    }
+}
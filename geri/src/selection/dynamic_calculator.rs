@@ -31,6 +31,7 @@ impl DynamicEfficiencyCalculator {
             is_local: model.is_local,
             cost_per_token: model.cost_per_token_input, // Simplification: use input cost
             max_cost_per_token: 0.0001, // Baseline
+            quality_score: None,
         };
 
         // Windowed metrics can be used for more dynamic behavior
@@ -1,14 +1,24 @@
 //! Efficiency-Score-Calculator (Phase 7.1.1): Multi-Faktor-Bewertung für Model-Auswahl.
 
-/// Gewichtungen der sechs Faktoren (Summe = 1.0).
+/// Gewichtungen der sieben Faktoren: die sechs Efficiency-Faktoren (Summe = 1.0) und der
+/// Qualitätsanteil, mit dem der Qualitäts-Score in den Gesamt-Score eingeht.
 #[derive(Debug, Clone)]
 pub struct EfficiencyWeights {
+    /// Gewicht des Size-Scores (kleinere Models bevorzugt).
     pub model_size: f64,
+    /// Gewicht des Hardware-Scores.
     pub hardware: f64,
+    /// Gewicht der Zuverlässigkeit (Uptime, Fehlerrate).
     pub reliability: f64,
+    /// Gewicht des Latency-Scores (Ping).
     pub latency: f64,
+    /// Gewicht des Distance-Scores (lokal = 1.0).
     pub distance: f64,
+    /// Gewicht des Kosten-Scores.
     pub cost: f64,
+    /// Anteil des Qualitäts-Scores (0.0–1.0) am Gesamt-Score; wirkt nur, wenn
+    /// `EfficiencyInput::quality_score` gesetzt ist (die sechs Faktoren teilen sich den Rest).
+    pub quality: f64,
 }
 
 impl Default for EfficiencyWeights {
@@ -20,6 +30,7 @@ impl Default for EfficiencyWeights {
             latency: 0.25,
             distance: 0.10,
             cost: 0.10,
+            quality: 0.25,
         }
     }
 }
@@ -51,6 +62,8 @@ pub struct EfficiencyInput {
     pub cost_per_token: Option<f64>,
     /// Max. Kosten pro Token zur Normalisierung.
     pub max_cost_per_token: f64,
+    /// Qualitäts-Score 0.0–1.0 aus gespeicherten Evaluationsläufen (`EvaluationStore`); fehlt = nicht evaluiert.
+    pub quality_score: Option<f64>,
 }
 
 /// Berechnet den Gesamt-Efficiency-Score aus den sechs gewichteten Teil-Scores.
@@ -70,8 +83,20 @@ impl EfficiencyScoreCalculator {
         &self.weights
     }
 
-    /// Berechnet den Gesamt-Score (0.0–1.0).
+    /// Berechnet den Gesamt-Score (0.0–1.0); mit Qualitäts-Score fließt dieser mit `weights.quality` ein.
     pub fn calculate(&self, input: &EfficiencyInput) -> f64 {
+        let efficiency = self.efficiency_score(input);
+        match input.quality_score {
+            Some(quality) => {
+                let weight = self.weights.quality.clamp(0.0, 1.0);
+                efficiency * (1.0 - weight) + quality.clamp(0.0, 1.0) * weight
+            }
+            None => efficiency,
+        }
+    }
+
+    /// Gewichtete Summe der sechs Effizienz-Faktoren.
+    fn efficiency_score(&self, input: &EfficiencyInput) -> f64 {
         let size = self.model_size_score(input);
         let hw = self.hardware_score(input);
         let rel = self.reliability_score(input);
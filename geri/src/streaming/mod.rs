@@ -1,8 +1,11 @@
-//! Streaming-Support (Phase 12.1, 12.2): LLM-Response-Streaming, Video-Stream-Processing.
+//! Streaming-Support (Phase 12.1, 12.2): LLM-Response-Streaming, Video-Stream-Processing (Frame-Analyse über Vision-Provider).
 
 mod manager;
+mod sse;
 mod video_stream;
 pub use manager::{StreamingError, StreamingManager};
+pub use sse::{sse_events, SseParser};
 pub use video_stream::{
     FrameAnalyzer, VideoAnalysisChunk, VideoStreamChunk, VideoStreamError, VideoStreamProcessor,
+    VisionFrameAnalyzer,
 };
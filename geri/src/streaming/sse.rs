@@ -0,0 +1,99 @@
+//! SSE-Parser (Phase 12.1.2): Server-Sent-Events aus Provider-HTTP-Streams in `data:`-Payloads zerlegen.
+
+use std::collections::VecDeque;
+
+use futures::{Stream, StreamExt};
+
+use super::StreamingError;
+
+/// Inkrementeller Parser für Server-Sent-Events; Bytes können an beliebigen Stellen getrennt ankommen.
+#[derive(Debug, Default)]
+pub struct SseParser {
+    /// Rohbytes bis zum nächsten `\n`; dekodiert wird erst die vollständige Zeile, damit über
+    /// Chunk-Grenzen getrennte UTF-8-Zeichen erhalten bleiben.
+    buffer: Vec<u8>,
+    data_lines: Vec<String>,
+}
+
+impl SseParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Füttert den Parser mit rohen Bytes und liefert alle vollständig empfangenen Event-Payloads.
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(bytes);
+        let mut events = Vec::new();
+        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+            if let Some(event) = self.process_line(line) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    /// Schließt den Stream ab: ein Rest ohne abschließende Leerzeile wird als letztes Event geliefert.
+    pub fn finish(&mut self) -> Vec<String> {
+        let rest = std::mem::take(&mut self.buffer);
+        let rest = String::from_utf8_lossy(&rest);
+        let mut events = Vec::new();
+        if !rest.trim().is_empty() {
+            if let Some(event) = self.process_line(rest.trim_end_matches('\r')) {
+                events.push(event);
+            }
+        }
+        if !self.data_lines.is_empty() {
+            events.push(self.data_lines.drain(..).collect::<Vec<_>>().join("\n"));
+        }
+        events
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<String> {
+        if line.is_empty() {
+            if self.data_lines.is_empty() {
+                return None;
+            }
+            return Some(self.data_lines.drain(..).collect::<Vec<_>>().join("\n"));
+        }
+        if let Some(data) = line.strip_prefix("data:") {
+            self.data_lines.push(data.strip_prefix(' ').unwrap_or(data).to_string());
+        }
+        // `event:`, `id:`, `retry:` und Kommentare (`:`) werden ignoriert – Provider kodieren den Typ im JSON.
+        None
+    }
+}
+
+/// Wandelt eine HTTP-Response mit `text/event-stream` in einen Stream von `data:`-Payloads.
+pub fn sse_events(
+    response: reqwest::Response,
+) -> impl Stream<Item = Result<String, StreamingError>> + Send {
+    let bytes = Box::pin(response.bytes_stream());
+    futures::stream::unfold(
+        (bytes, SseParser::new(), VecDeque::new(), false),
+        |(mut bytes, mut parser, mut pending, mut done)| async move {
+            loop {
+                if let Some(event) = pending.pop_front() {
+                    return Some((Ok(event), (bytes, parser, pending, done)));
+                }
+                if done {
+                    return None;
+                }
+                match bytes.next().await {
+                    Some(Ok(chunk)) => pending.extend(parser.feed(&chunk)),
+                    Some(Err(e)) => {
+                        done = true;
+                        let err = StreamingError::StreamError(e.to_string());
+                        return Some((Err(err), (bytes, parser, pending, done)));
+                    }
+                    None => {
+                        done = true;
+                        pending.extend(parser.finish());
+                    }
+                }
+            }
+        },
+    )
+}
@@ -1,7 +1,11 @@
 //! Video-Stream-Processor (Phase 12.2.1): Chunks verarbeiten, Frame-Extraction, Vision-Analyse, Streaming-Results.
 
+use std::sync::Arc;
+
 use thiserror::Error;
 
+use crate::vision::{VisionProvider, VisionRequest};
+
 /// Ein Chunk Video-Daten (Rohbytes).
 #[derive(Debug, Clone)]
 pub struct VideoStreamChunk {
@@ -29,6 +33,59 @@ pub trait FrameAnalyzer: Send + Sync {
     fn analyze_frame(&self, data: &[u8]) -> Result<String, VideoStreamError>;
 }
 
+/// FrameAnalyzer über einen Vision-Provider (z. B. lokales llama.cpp-Vision-Model oder die
+/// `GeriEngine`); Frames gelten standardmäßig als privacy-sensitive (Kamerabilder bleiben lokal).
+///
+/// `analyze_frame` ist synchron und blockiert auf `runtime`; innerhalb einer Tokio-Runtime nur
+/// mit Multi-Thread-Runtime nutzbar (`block_in_place`).
+pub struct VisionFrameAnalyzer {
+    provider: Arc<dyn VisionProvider>,
+    runtime: tokio::runtime::Handle,
+    prompt: Option<String>,
+    privacy_sensitive: bool,
+}
+
+impl VisionFrameAnalyzer {
+    pub fn new(provider: Arc<dyn VisionProvider>, runtime: tokio::runtime::Handle) -> Self {
+        Self {
+            provider,
+            runtime,
+            prompt: None,
+            privacy_sensitive: true,
+        }
+    }
+
+    /// Frage an das Vision-Model pro Frame (Standard: Bildbeschreibung).
+    pub fn with_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = Some(prompt.into());
+        self
+    }
+
+    pub fn with_privacy_sensitive(mut self, privacy_sensitive: bool) -> Self {
+        self.privacy_sensitive = privacy_sensitive;
+        self
+    }
+}
+
+impl FrameAnalyzer for VisionFrameAnalyzer {
+    fn analyze_frame(&self, data: &[u8]) -> Result<String, VideoStreamError> {
+        let request = VisionRequest {
+            image_data: data.to_vec(),
+            prompt: self.prompt.clone(),
+            privacy_sensitive: self.privacy_sensitive,
+        };
+        let analysis = self.provider.process(request);
+        let result = if tokio::runtime::Handle::try_current().is_ok() {
+            tokio::task::block_in_place(|| self.runtime.block_on(analysis))
+        } else {
+            self.runtime.block_on(analysis)
+        };
+        result
+            .map(|response| response.description)
+            .map_err(|e| VideoStreamError::FrameAnalysisFailed(e.to_string()))
+    }
+}
+
 /// Ein analysierter Frame (Streaming-Result).
 #[derive(Debug, Clone)]
 pub struct VideoAnalysisChunk {
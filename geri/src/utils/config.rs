@@ -16,6 +16,18 @@ pub enum SettingsError {
     EmptyVisionModel,
     #[error("Invalid local provider type: {0}")]
     InvalidLocalProviderType(String),
+    #[error("openai_api.port must be non-zero and differ from grpc_port")]
+    InvalidOpenAiApiPort,
+    #[error("cache.similarity_threshold must be between 0.0 and 1.0")]
+    InvalidCacheSimilarityThreshold,
+    #[error("Invalid embedding model: {0}")]
+    InvalidEmbeddingModel(String),
+    #[error("Invalid budget limit: {0}")]
+    InvalidBudgetLimit(String),
+    #[error("Invalid key store configuration: {0}")]
+    InvalidKeyStore(String),
+    #[error("Invalid trace export configuration: {0}")]
+    InvalidTraceExport(String),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +42,22 @@ pub struct LocalProviderConfig {
     pub auto_select: bool,
     /// Minimum memory (MB) to use llama.cpp (otherwise use BitNet)
     pub llamacpp_min_memory_mb: u32,
+    /// Chat template ("chatml", "llama3", "mistral", ...); auto-detected from GGUF metadata or model name if unset
+    #[serde(default)]
+    pub chat_template: Option<crate::prompt::ChatTemplate>,
+    /// llama.cpp server started with a vision model and its `--mmproj` projector; enables the
+    /// vision models found in `llamacpp_models_dir` for `ProcessVision`
+    #[serde(default)]
+    pub vision_server_url: Option<String>,
+    /// Model catalog (YAML/JSON) with the downloadable variants, their RAM need and SHA-256
+    #[serde(default)]
+    pub model_catalog_path: Option<String>,
+    /// Download and verify the selected catalog model on startup if it is missing
+    #[serde(default)]
+    pub auto_download: bool,
+    /// Maximum size of the models directory; least recently used models are removed for new downloads
+    #[serde(default)]
+    pub models_quota_mb: Option<u64>,
 }
 
 impl Default for LocalProviderConfig {
@@ -40,6 +68,181 @@ impl Default for LocalProviderConfig {
             bitnet_models_dir: "./models/bitnet".to_string(),
             auto_select: true,
             llamacpp_min_memory_mb: 8000, // 8GB minimum for llama.cpp
+            chat_template: None,
+            vision_server_url: None,
+            model_catalog_path: None,
+            auto_download: false,
+            models_quota_mb: None,
+        }
+    }
+}
+
+/// OpenAI-compatible HTTP API (requires the `openai-api` cargo feature)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OpenAiApiConfig {
+    /// Start the HTTP server next to the gRPC server
+    pub enabled: bool,
+    pub port: u16,
+    /// Heimdall gRPC endpoint used to validate bearer tokens
+    pub heimdall_url: String,
+    /// Reject requests without a valid Heimdall token (disable only for local testing)
+    pub require_auth: bool,
+    /// Cost budget (USD) for the GeriEngine behind the API; once exceeded only local models are used
+    pub budget_limit: f64,
+}
+
+impl Default for OpenAiApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 8054,
+            heimdall_url: "http://localhost:50051".to_string(),
+            require_auth: true,
+            budget_limit: 10.0,
+        }
+    }
+}
+
+/// Circuit breaker and failover of the GeriEngine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FailoverConfig {
+    pub circuit_breaker: crate::error_handling::CircuitBreakerConfig,
+    /// Further candidates tried after the first model failed
+    pub max_retries: u32,
+    /// Backoff before the first failover (doubled per attempt)
+    pub retry_base_delay_ms: u64,
+    /// Provider calls exceeding this are treated as failed; unset waits indefinitely
+    pub request_timeout_ms: Option<u64>,
+}
+
+/// Structured output (`response_format` with a JSON Schema)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StructuredOutputConfig {
+    /// Rounds in which an answer that does not match the schema is sent back to the model
+    pub max_repair_attempts: u32,
+}
+
+impl Default for StructuredOutputConfig {
+    fn default() -> Self {
+        Self {
+            max_repair_attempts: crate::llm::structured::DEFAULT_MAX_REPAIR_ATTEMPTS,
+        }
+    }
+}
+
+/// Offline evaluation (`geri eval`) and the quality scores it feeds into model selection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvaluationConfig {
+    /// JSON-lines file with the stored reports; without it, runs are only printed and the
+    /// model selection uses no quality scores
+    pub results_path: Option<String>,
+    /// Cases evaluated at the same time
+    pub concurrency: usize,
+    /// Per-request timeout for evaluated models and judges
+    pub request_timeout_ms: Option<u64>,
+}
+
+impl Default for EvaluationConfig {
+    fn default() -> Self {
+        Self {
+            results_path: None,
+            concurrency: crate::evaluation::DEFAULT_EVAL_CONCURRENCY,
+            request_timeout_ms: None,
+        }
+    }
+}
+
+/// Where the cloud provider API keys (openai, anthropic, google) come from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyBackendType {
+    /// Environment variables only (`api_key_env`)
+    #[default]
+    Env,
+    /// AES-GCM encrypted key store file
+    EncryptedFile,
+    /// Heimdall `SecretService`
+    Heimdall,
+}
+
+/// API key storage and rotation; keys from the store take precedence over `api_key_env`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyStoreConfig {
+    pub backend: KeyBackendType,
+    /// Encrypted key store file (`encrypted_file`)
+    pub file_path: String,
+    /// Environment variable holding the key store passphrase (`encrypted_file`)
+    pub passphrase_env: String,
+    /// Take the key store key from Heimdall instead of deriving it from the passphrase
+    pub key_from_heimdall: bool,
+    pub heimdall_url: String,
+    /// Service identity towards Heimdall
+    pub service_id: String,
+    /// Environment variable holding the service token for Heimdall
+    pub service_token_env: String,
+    /// How often keys are re-read, so keys rotated in Heimdall or the key store file are swapped in
+    pub refresh_interval_secs: u64,
+}
+
+impl Default for KeyStoreConfig {
+    fn default() -> Self {
+        Self {
+            backend: KeyBackendType::Env,
+            file_path: "data/keys.json".to_string(),
+            passphrase_env: "GERI_KEYSTORE_PASSPHRASE".to_string(),
+            key_from_heimdall: false,
+            heimdall_url: "http://localhost:50051".to_string(),
+            service_id: "geri".to_string(),
+            service_token_env: "GERI_SERVICE_TOKEN".to_string(),
+            refresh_interval_secs: 300,
+        }
+    }
+}
+
+/// Embedding models served by the `Embed` RPC and `/v1/embeddings`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingConfig {
+    /// Model used when the caller names none; defaults to the first entry of `models`
+    pub default_model: Option<String>,
+    pub models: Vec<EmbeddingModelConfig>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingModelConfig {
+    /// Model id in the registry and in requests
+    pub id: String,
+    /// "openai", "google" or "llamacpp"
+    pub provider: String,
+    /// Provider-side model name; defaults to `id`
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Vector dimension; every returned vector is checked against it
+    pub dimension: u32,
+    /// Ask the provider to shorten vectors to `dimension` (OpenAI text-embedding-3, Gemini)
+    #[serde(default)]
+    pub request_dimension: bool,
+    pub max_input_tokens: u32,
+    /// API base URL (OpenAI/Google) or llama.cpp server URL; provider default if unset
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// Environment variable holding the API key (OpenAI/Google)
+    #[serde(default)]
+    pub api_key_env: Option<String>,
+}
+
+impl Default for FailoverConfig {
+    fn default() -> Self {
+        Self {
+            circuit_breaker: crate::error_handling::CircuitBreakerConfig::default(),
+            max_retries: 2,
+            retry_base_delay_ms: 50,
+            request_timeout_ms: None,
         }
     }
 }
@@ -51,6 +254,33 @@ pub struct GeriSettings {
     pub vision_model: String,
     #[serde(default)]
     pub local_provider: LocalProviderConfig,
+    #[serde(default)]
+    pub openai_api: OpenAiApiConfig,
+    #[serde(default)]
+    pub failover: FailoverConfig,
+    #[serde(default)]
+    pub cache: crate::cache::CacheConfig,
+    #[serde(default)]
+    pub embeddings: EmbeddingConfig,
+    #[serde(default)]
+    pub structured_output: StructuredOutputConfig,
+    /// Budgets per user/device and period, usage ledger
+    #[serde(default)]
+    pub budget: crate::cost::BudgetConfig,
+    #[serde(default)]
+    pub evaluation: EvaluationConfig,
+    /// Prompt-injection detection, secret redaction and checks of actions in answers
+    #[serde(default)]
+    pub safety: crate::safety::SafetyConfig,
+    /// Admission scheduling in front of the local provider (slots, priorities, fair share)
+    #[serde(default)]
+    pub scheduler: crate::queue::SchedulerConfig,
+    /// API key storage (environment, encrypted file, Heimdall) and hot key rotation
+    #[serde(default)]
+    pub keys: KeyStoreConfig,
+    /// Span export (OTLP collector and/or rolling JSON file), see Vegvisir
+    #[serde(default)]
+    pub trace_export: vegvisir::TraceExportConfig,
 }
 
 impl GeriSettings {
@@ -72,6 +302,43 @@ impl GeriSettings {
                 self.local_provider.provider_type.clone()
             ));
         }
+
+        if self.openai_api.enabled && (self.openai_api.port == 0 || self.openai_api.port == self.grpc_port) {
+            return Err(SettingsError::InvalidOpenAiApiPort);
+        }
+        if self.cache.similarity_threshold.is_some_and(|t| !(0.0..=1.0).contains(&t)) {
+            return Err(SettingsError::InvalidCacheSimilarityThreshold);
+        }
+        for model in &self.embeddings.models {
+            if !["openai", "google", "llamacpp"].contains(&model.provider.as_str()) {
+                return Err(SettingsError::InvalidEmbeddingModel(format!("{}: unknown provider '{}'", model.id, model.provider)));
+            }
+            if model.id.trim().is_empty() || model.dimension == 0 || model.max_input_tokens == 0 {
+                return Err(SettingsError::InvalidEmbeddingModel(format!(
+                    "'{}' needs an id, dimension and max_input_tokens", model.id
+                )));
+            }
+        }
+        if let Some(default) = &self.embeddings.default_model {
+            if !self.embeddings.models.iter().any(|m| &m.id == default) {
+                return Err(SettingsError::InvalidEmbeddingModel(format!("default_model '{}' is not configured", default)));
+            }
+        }
+        for limit in &self.budget.limits {
+            if !limit.limit.is_finite() || limit.limit < 0.0 {
+                return Err(SettingsError::InvalidBudgetLimit(format!(
+                    "{} limit for user {:?}, device {:?} must be a non-negative amount",
+                    limit.period.as_str(), limit.user_id, limit.device_id
+                )));
+            }
+        }
+        if self.keys.backend == KeyBackendType::EncryptedFile && self.keys.file_path.trim().is_empty() {
+            return Err(SettingsError::InvalidKeyStore("file_path must be set for the encrypted_file backend".to_string()));
+        }
+        if self.keys.backend != KeyBackendType::Env && self.keys.refresh_interval_secs == 0 {
+            return Err(SettingsError::InvalidKeyStore("refresh_interval_secs must be non-zero".to_string()));
+        }
+        self.trace_export.validate().map_err(SettingsError::InvalidTraceExport)?;
         
         Ok(())
     }
@@ -84,6 +351,17 @@ impl Default for GeriSettings {
             default_local_llm: "llama3-8b".to_string(),
             vision_model: "gpt-4v".to_string(),
             local_provider: LocalProviderConfig::default(),
+            openai_api: OpenAiApiConfig::default(),
+            failover: FailoverConfig::default(),
+            cache: crate::cache::CacheConfig::default(),
+            embeddings: EmbeddingConfig::default(),
+            structured_output: StructuredOutputConfig::default(),
+            budget: crate::cost::BudgetConfig::default(),
+            evaluation: EvaluationConfig::default(),
+            safety: crate::safety::SafetyConfig::default(),
+            scheduler: crate::queue::SchedulerConfig::default(),
+            keys: KeyStoreConfig::default(),
+            trace_export: vegvisir::TraceExportConfig::default(),
         }
     }
 }
@@ -7,6 +7,9 @@ use thiserror::Error;
 pub struct VisionRequest {
     pub image_data: Vec<u8>,
     pub prompt: Option<String>,
+    /// Bild darf das Gerät nicht verlassen (z. B. Kamerabilder): nur lokale Vision-Models.
+    #[serde(default)]
+    pub privacy_sensitive: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -76,6 +76,7 @@ fn e2e_cloud_limit_fallback_returns_local_model() {
                 is_local: true,
                 cost_per_token: Some(0.0),
                 max_cost_per_token: 0.001,
+                quality_score: None,
             },
         ),
     ];
@@ -114,6 +115,7 @@ fn e2e_model_selection_selects_best_by_efficiency_score() {
                 is_local: false,
                 cost_per_token: Some(0.0001),
                 max_cost_per_token: 0.001,
+                quality_score: None,
             },
         ),
         (
@@ -139,6 +141,7 @@ fn e2e_model_selection_selects_best_by_efficiency_score() {
                 is_local: true,
                 cost_per_token: Some(0.00001),
                 max_cost_per_token: 0.001,
+                quality_score: None,
             },
         ),
     ];
@@ -41,6 +41,7 @@ async fn test_process_vision_returns_description_and_model() {
         image_data: vec![0u8; 10],
         prompt: String::new(),
         model_name: String::new(),
+        privacy_sensitive: false,
     });
 
     let res = GeriService::process_vision(&svc, req).await.expect("process_vision");
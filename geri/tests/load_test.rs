@@ -119,6 +119,7 @@ fn load_balancing_overloaded_provider_receives_lower_effective_score() {
         is_local: true,
         cost_per_token: Some(0.0),
         max_cost_per_token: 0.001,
+        quality_score: None,
     };
     let candidates = vec![
         make_candidate("model-a", "provider-a", input.clone()),
@@ -50,4 +50,20 @@ mod unit {
     pub mod model_downloader_test;
     pub mod performance_tracker_test;
     pub mod engine_test;
+    pub mod sse_parser_test;
+    pub mod prompt_stream_test;
+    pub mod tool_calling_test;
+    pub mod conversation_test;
+    pub mod chat_template_test;
+    pub mod openai_http_test;
+    pub mod circuit_breaker_test;
+    pub mod embedding_test;
+    pub mod structured_output_test;
+    pub mod usage_ledger_test;
+    pub mod evaluation_harness_test;
+    pub mod local_vision_test;
+    pub mod model_catalog_test;
+    pub mod safety_filter_test;
+    pub mod request_scheduler_test;
+    pub mod secret_backend_test;
 }
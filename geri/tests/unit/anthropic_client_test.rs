@@ -1,6 +1,6 @@
 //! Unit tests for Anthropic Client
 
-use geri::llm::anthropic::{AnthropicClient, AnthropicConfig, AnthropicError};
+use geri::llm::anthropic::{AnthropicClient, AnthropicConfig, AnthropicError, ContentBlock};
 
 #[tokio::test]
 async fn test_anthropic_client_new() {
@@ -46,7 +46,7 @@ async fn test_anthropic_client_messages_request_structure() {
     assert_eq!(request.model, "claude-3-opus-20240229");
     assert_eq!(request.messages.len(), 1);
     assert_eq!(request.messages[0].role, "user");
-    assert_eq!(request.messages[0].content[0].text, "Hello");
+    assert!(matches!(&request.messages[0].content[0], ContentBlock::Text { text } if text == "Hello"));
     assert_eq!(request.max_tokens, 100);
 }
 
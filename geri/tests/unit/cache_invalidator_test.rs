@@ -2,46 +2,56 @@
 
 #[cfg(test)]
 mod tests {
-    use geri::cache::{CacheInvalidator, CacheManager, InvalidationEvent};
+    use geri::cache::{CacheInvalidator, CacheKey, CacheManager, InvalidationEvent};
+    use geri::llm::{PromptRequest, PromptResponse};
     use std::time::Duration;
 
+    fn key(prompt: &str) -> CacheKey {
+        let request = PromptRequest { prompt: prompt.to_string(), ..Default::default() };
+        CacheKey::new(&request, "llama-3-8b")
+    }
+
+    fn response(text: &str) -> PromptResponse {
+        PromptResponse { text: text.to_string(), tokens_used: 1, tool_calls: Vec::new(), structured: None, safety: Vec::new() }
+    }
+
     #[test]
     fn invalidate_on_event_clears_cache() {
         let mut cache = CacheManager::new(Duration::from_secs(60));
-        cache.insert("p", "r".to_string());
+        cache.insert(&key("p"), response("r"));
         let mut invalidator = CacheInvalidator::new(Duration::from_secs(300));
         invalidator.invalidate_on_event(&mut cache, InvalidationEvent::ModelUpdate);
-        assert!(cache.get("p").is_none());
+        assert!(cache.get(&key("p")).is_none());
     }
 
     #[test]
     fn invalidate_on_event_provider_status_clears_cache() {
         let mut cache = CacheManager::new(Duration::from_secs(60));
-        cache.insert("x", "y".to_string());
+        cache.insert(&key("x"), response("y"));
         let mut invalidator = CacheInvalidator::new(Duration::from_secs(300));
         invalidator.invalidate_on_event(&mut cache, InvalidationEvent::ProviderStatusChange);
-        assert!(cache.get("x").is_none());
+        assert!(cache.get(&key("x")).is_none());
     }
 
     #[test]
     fn invalidate_on_timeout_clears_cache_when_first_call() {
         let mut cache = CacheManager::new(Duration::from_secs(60));
-        cache.insert("a", "b".to_string());
+        cache.insert(&key("a"), response("b"));
         let mut invalidator = CacheInvalidator::new(Duration::from_secs(300));
         let invalidated = invalidator.invalidate_on_timeout(&mut cache);
         assert!(invalidated);
-        assert!(cache.get("a").is_none());
+        assert!(cache.get(&key("a")).is_none());
     }
 
     #[test]
     fn invalidate_on_timeout_does_not_clear_before_timeout() {
         let mut cache = CacheManager::new(Duration::from_secs(60));
-        cache.insert("a", "b".to_string());
+        cache.insert(&key("a"), response("b"));
         let mut invalidator = CacheInvalidator::new(Duration::from_secs(300));
         invalidator.invalidate_on_timeout(&mut cache);
-        cache.insert("a", "b2".to_string());
+        cache.insert(&key("a"), response("b2"));
         let invalidated = invalidator.invalidate_on_timeout(&mut cache);
         assert!(!invalidated);
-        assert_eq!(cache.get("a"), Some("b2".to_string()));
+        assert_eq!(cache.get(&key("a")).map(|r| r.text), Some("b2".to_string()));
     }
 }
@@ -1,63 +1,257 @@
-//! Tests für Cache-Manager (Phase 11.1.1).
+//! Tests für Cache-Manager, Cache-Key und Response-Cache (Phase 11.1).
 
 #[cfg(test)]
 mod tests {
-    use geri::cache::CacheManager;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
     use std::time::Duration;
 
+    use async_trait::async_trait;
+    use futures::StreamExt;
+    use geri::cache::{CacheConfig, CacheKey, CacheManager, PromptEmbedder, ResponseCache};
+    use geri::cost::BudgetTracker;
+    use geri::llm::{ChatMessage, ChatRole, GeriEngine, LLMError, LLMProvider, PromptRequest, PromptResponse, PromptStreamChunk, ProviderFactory};
+    use geri::model::{ModelInfo, ModelRegistry, ModelRegistryTrait, ModelType};
+    use geri::performance::PerformanceTracker;
+    use geri::selection::SelectionOptions;
+    use tokio::sync::RwLock;
+
+    fn request(prompt: &str) -> PromptRequest {
+        PromptRequest { prompt: prompt.to_string(), ..Default::default() }
+    }
+
+    fn key(prompt: &str) -> CacheKey {
+        CacheKey::new(&request(prompt), "llama-3-8b")
+    }
+
+    fn response(text: &str) -> PromptResponse {
+        PromptResponse { text: text.to_string(), tokens_used: 10, tool_calls: Vec::new(), structured: None, safety: Vec::new() }
+    }
+
+    fn text(response: Option<PromptResponse>) -> Option<String> {
+        response.map(|r| r.text)
+    }
+
     #[test]
     fn get_returns_none_for_unknown_prompt() {
         let mut cache = CacheManager::new(Duration::from_secs(60));
-        assert!(cache.get("unknown prompt").is_none());
+        assert!(cache.get(&key("unknown prompt")).is_none());
     }
 
     #[test]
     fn insert_and_get_returns_cached_response() {
         let mut cache = CacheManager::new(Duration::from_secs(60));
-        cache.insert("Hello", "Hi there!".to_string());
-        assert_eq!(cache.get("Hello"), Some("Hi there!".to_string()));
+        cache.insert(&key("Hello"), response("Hi there!"));
+        assert_eq!(text(cache.get(&key("Hello"))), Some("Hi there!".to_string()));
     }
 
     #[test]
     fn same_prompt_same_key() {
         let mut cache = CacheManager::new(Duration::from_secs(60));
-        cache.insert("same", "first".to_string());
-        assert_eq!(cache.get("same"), Some("first".to_string()));
-        cache.insert("same", "second".to_string());
-        assert_eq!(cache.get("same"), Some("second".to_string()));
+        cache.insert(&key("same"), response("first"));
+        cache.insert(&key("same"), response("second"));
+        assert_eq!(text(cache.get(&key("same"))), Some("second".to_string()));
+        assert_eq!(cache.len(), 1);
     }
 
     #[test]
     fn different_prompts_different_entries() {
         let mut cache = CacheManager::new(Duration::from_secs(60));
-        cache.insert("A", "resp A".to_string());
-        cache.insert("B", "resp B".to_string());
-        assert_eq!(cache.get("A"), Some("resp A".to_string()));
-        assert_eq!(cache.get("B"), Some("resp B".to_string()));
+        cache.insert(&key("A"), response("resp A"));
+        cache.insert(&key("B"), response("resp B"));
+        assert_eq!(text(cache.get(&key("A"))), Some("resp A".to_string()));
+        assert_eq!(text(cache.get(&key("B"))), Some("resp B".to_string()));
+        assert_eq!(cache.len(), 2);
     }
 
     #[test]
     fn expired_entry_returns_none() {
         let mut cache = CacheManager::new(Duration::from_secs(0));
-        cache.insert("x", "y".to_string());
-        assert!(cache.get("x").is_none());
+        cache.insert(&key("x"), response("y"));
+        assert!(cache.get(&key("x")).is_none());
+    }
+
+    #[test]
+    fn cache_key_is_stable_and_whitespace_normalized() {
+        assert_eq!(key("What is  Rust?\n").hash(), key(" What is Rust?").hash());
+        assert_eq!(key("deterministic").hash().len(), 64);
+        // Prompt as current turn or as trailing user message is the same request
+        let mut as_message = request("");
+        as_message.messages.push(ChatMessage::new(ChatRole::User, "deterministic"));
+        assert_eq!(CacheKey::new(&as_message, "llama-3-8b").hash(), key("deterministic").hash());
     }
 
     #[test]
-    fn cache_key_deterministic() {
+    fn cache_key_covers_model_system_prompt_context_and_max_tokens() {
+        let base = request("Hello");
+        let variants = [
+            PromptRequest { system_prompt: Some("Be brief.".to_string()), ..base.clone() },
+            PromptRequest { context: Some("RAG document".to_string()), ..base.clone() },
+            PromptRequest { max_tokens: Some(16), ..base.clone() },
+            PromptRequest { messages: vec![ChatMessage::new(ChatRole::Assistant, "Earlier")], ..base.clone() },
+        ];
+        let base_key = CacheKey::new(&base, "llama-3-8b");
+        assert_ne!(base_key.hash(), CacheKey::new(&base, "gpt-4").hash());
+        for variant in &variants {
+            assert_ne!(base_key.hash(), CacheKey::new(variant, "llama-3-8b").hash());
+        }
+    }
+
+    #[test]
+    fn entries_are_isolated_per_user() {
         let mut cache = CacheManager::new(Duration::from_secs(60));
-        cache.insert("deterministic", "value".to_string());
-        assert_eq!(cache.get("deterministic"), Some("value".to_string()));
-        assert_eq!(cache.get("deterministic"), Some("value".to_string()));
+        let alice = PromptRequest { user_id: Some("alice".to_string()), ..request("My notes?") };
+        let bob = PromptRequest { user_id: Some("bob".to_string()), ..request("My notes?") };
+        cache.insert(&CacheKey::new(&alice, "llama-3-8b"), response("alice's notes"));
+        assert!(cache.get(&CacheKey::new(&bob, "llama-3-8b")).is_none());
+
+        cache.invalidate_user("alice");
+        assert!(cache.is_empty());
     }
 
     #[test]
     fn invalidate_all_clears_cache() {
         let mut cache = CacheManager::new(Duration::from_secs(60));
-        cache.insert("a", "1".to_string());
-        cache.insert("b", "2".to_string());
+        cache.insert(&key("a"), response("1"));
+        cache.insert(&key("b"), response("2"));
         cache.invalidate_all();
-        assert!(cache.get("a").is_none());
-        assert!(cache.get("b").is_none());
+        assert!(cache.get(&key("a")).is_none());
+        assert!(cache.get(&key("b")).is_none());
+    }
+
+    #[test]
+    fn least_recently_used_entry_is_evicted() {
+        let mut cache = CacheManager::open(&CacheConfig { max_entries: 2, ..Default::default() }).unwrap();
+        cache.insert(&key("a"), response("1"));
+        cache.insert(&key("b"), response("2"));
+        assert!(cache.get(&key("a")).is_some());
+        cache.insert(&key("c"), response("3"));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(&key("b")).is_none());
+        assert!(cache.get(&key("a")).is_some());
+    }
+
+    #[test]
+    fn entries_survive_reopen_and_respect_size_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = CacheConfig {
+            enabled: true,
+            dir: Some(dir.path().to_string_lossy().to_string()),
+            ..Default::default()
+        };
+        {
+            let mut cache = CacheManager::open(&config).unwrap();
+            cache.insert(&key("persisted"), response("from disk"));
+            cache.insert(&key("second"), response("also on disk"));
+        }
+        std::fs::write(dir.path().join("broken.json"), "{").unwrap();
+
+        let mut cache = CacheManager::open(&config).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert_eq!(text(cache.get(&key("persisted"))), Some("from disk".to_string()));
+        assert!(!dir.path().join("broken.json").exists());
+
+        // Shrinking the byte limit evicts entries and their files on open
+        let one_entry = cache.size_bytes() / 2 + 1;
+        let cache = CacheManager::open(&CacheConfig { max_bytes: one_entry, ..config }).unwrap();
+        assert_eq!(cache.len(), 1);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn persistent_response_cache_writes_entries_off_the_runtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = CacheConfig { dir: Some(dir.path().to_string_lossy().to_string()), ..Default::default() };
+        let cache = ResponseCache::new(CacheManager::open(&config).unwrap());
+
+        cache.insert(&key("persisted"), &response("from disk")).await;
+        assert_eq!(text(cache.get(&key("persisted")).await), Some("from disk".to_string()));
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        cache.invalidate_all().await;
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    /// Maps prompts containing "Rust" onto the same direction, everything else orthogonal
+    struct KeywordEmbedder;
+
+    #[async_trait]
+    impl PromptEmbedder for KeywordEmbedder {
+        async fn embed(&self, text: &str) -> Result<Vec<f32>, String> {
+            Ok(if text.contains("Rust") { vec![1.0, 0.1] } else { vec![0.0, 1.0] })
+        }
+    }
+
+    #[tokio::test]
+    async fn similar_prompts_hit_above_threshold() {
+        let manager = CacheManager::open(&CacheConfig { similarity_threshold: Some(0.95), ..Default::default() }).unwrap();
+        let cache = ResponseCache::new(manager).with_embedder(Arc::new(KeywordEmbedder));
+
+        let stored = cache.key(&request("What is Rust?"), "llama-3-8b").await.unwrap();
+        cache.insert(&stored, &response("A language")).await;
+
+        let similar = cache.key(&request("Explain Rust please"), "llama-3-8b").await.unwrap();
+        assert_eq!(text(cache.get(&similar).await), Some("A language".to_string()));
+        let unrelated = cache.key(&request("What is Go?"), "llama-3-8b").await.unwrap();
+        assert!(cache.get(&unrelated).await.is_none());
+        // Similarity never crosses models
+        let other_model = cache.key(&request("Explain Rust please"), "gpt-4").await.unwrap();
+        assert!(cache.get(&other_model).await.is_none());
+    }
+
+    struct CountingProvider {
+        calls: AtomicU32,
+    }
+
+    #[async_trait]
+    impl LLMProvider for CountingProvider {
+        fn model_name(&self) -> &str { "llama-3-8b" }
+        async fn process_prompt(&self, request: PromptRequest) -> Result<PromptResponse, LLMError> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(response(&format!("{} #{}", request.prompt, call)))
+        }
+    }
+
+    async fn engine(provider: Arc<CountingProvider>) -> GeriEngine {
+        let registry: Arc<dyn ModelRegistryTrait> = Arc::new(RwLock::new(ModelRegistry::new().register(ModelInfo {
+            id: "llama-3-8b".to_string(),
+            name: "llama-3-8b".to_string(),
+            provider: "local".to_string(),
+            model_type: ModelType::Llm,
+            parameter_count: None,
+            hardware_requirements: None,
+            context_window: Some(4096),
+            is_local: true,
+            cost_per_token_input: Some(0.0),
+            cost_per_token_output: Some(0.0),
+            embedding_dimension: None,
+        })));
+        let factory = Arc::new(ProviderFactory::new());
+        factory.register("llama-3-8b", provider).await;
+        GeriEngine::new(
+            registry,
+            Arc::new(PerformanceTracker::new().unwrap()),
+            factory,
+            Arc::new(BudgetTracker::new(10.0)),
+        )
+        .with_response_cache(Arc::new(ResponseCache::new(CacheManager::new(Duration::from_secs(60)))))
+    }
+
+    #[tokio::test]
+    async fn engine_answers_repeated_requests_from_cache() {
+        let provider = Arc::new(CountingProvider { calls: AtomicU32::new(0) });
+        let engine = engine(provider.clone()).await;
+
+        let first = engine.process(request("Hi"), SelectionOptions::default()).await.unwrap();
+        let second = engine.process(request("Hi"), SelectionOptions::default()).await.unwrap();
+        assert_eq!((first.text.as_str(), second.text.as_str()), ("Hi #1", "Hi #1"));
+
+        let mut stream = engine.process_stream(request("Hi"), SelectionOptions::default()).await.unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap(), PromptStreamChunk::Delta("Hi #1".to_string()));
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 1);
+
+        let bypass = PromptRequest { no_cache: true, ..request("Hi") };
+        let fresh = engine.process(bypass, SelectionOptions::default()).await.unwrap();
+        assert_eq!(fresh.text, "Hi #2");
     }
 }
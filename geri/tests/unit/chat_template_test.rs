@@ -0,0 +1,182 @@
+//! Tests für Chat-Templates (Phase 8.1.2).
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use geri::llm::{ChatMessage, PromptRequest, ToolCall, ToolResult};
+    use geri::prompt::ChatTemplate;
+    use serde_json::json;
+
+    fn history() -> Vec<ChatMessage> {
+        vec![
+            ChatMessage::user("Hi"),
+            ChatMessage::assistant("Hello!"),
+            ChatMessage::user("Weather?"),
+        ]
+    }
+
+    /// Minimale GGUF-v3-Datei mit einem u32-, einem String-Array- und dem Chat-Template-Eintrag.
+    fn write_gguf(chat_template: &str) -> tempfile::NamedTempFile {
+        fn string(out: &mut Vec<u8>, s: &str) {
+            out.extend_from_slice(&(s.len() as u64).to_le_bytes());
+            out.extend_from_slice(s.as_bytes());
+        }
+        let mut out = Vec::new();
+        out.extend_from_slice(b"GGUF");
+        out.extend_from_slice(&3u32.to_le_bytes());
+        out.extend_from_slice(&0u64.to_le_bytes()); // tensors
+        out.extend_from_slice(&3u64.to_le_bytes()); // key/values
+        string(&mut out, "general.quantization_version");
+        out.extend_from_slice(&4u32.to_le_bytes());
+        out.extend_from_slice(&2u32.to_le_bytes());
+        string(&mut out, "tokenizer.ggml.tokens");
+        out.extend_from_slice(&9u32.to_le_bytes());
+        out.extend_from_slice(&8u32.to_le_bytes());
+        out.extend_from_slice(&2u64.to_le_bytes());
+        string(&mut out, "<s>");
+        string(&mut out, "</s>");
+        string(&mut out, "tokenizer.chat_template");
+        out.extend_from_slice(&8u32.to_le_bytes());
+        string(&mut out, chat_template);
+
+        let mut file = tempfile::Builder::new().suffix(".gguf").tempfile().unwrap();
+        file.write_all(&out).unwrap();
+        file
+    }
+
+    #[test]
+    fn parse_and_detect_model_families() {
+        assert_eq!(ChatTemplate::parse("ChatML"), Some(ChatTemplate::ChatMl));
+        assert_eq!(ChatTemplate::parse("llama-3"), Some(ChatTemplate::Llama3));
+        assert_eq!(ChatTemplate::parse("unknown"), None);
+
+        assert_eq!(ChatTemplate::detect("Meta-Llama-3-8B-Instruct.Q4_K_M.gguf"), Some(ChatTemplate::Llama3));
+        assert_eq!(ChatTemplate::detect("mistral-7b-instruct-v0.2"), Some(ChatTemplate::Mistral));
+        assert_eq!(ChatTemplate::detect("qwen2.5-7b-instruct"), Some(ChatTemplate::ChatMl));
+        assert_eq!(ChatTemplate::detect("gemma-2-9b-it"), Some(ChatTemplate::Gemma));
+        assert_eq!(ChatTemplate::detect("Phi-3-mini-4k-instruct"), Some(ChatTemplate::Phi3));
+        assert_eq!(ChatTemplate::detect("bitnet-3b"), None);
+        assert_eq!(ChatTemplate::detect("models/Llama3.1-8B-Instruct.gguf"), Some(ChatTemplate::Llama3));
+        assert_eq!(ChatTemplate::detect("gemma2-2b-it"), Some(ChatTemplate::Gemma));
+    }
+
+    #[test]
+    fn detect_matches_whole_name_tokens_only() {
+        assert_eq!(ChatTemplate::detect("codellama-34b-instruct"), None);
+        assert_eq!(ChatTemplate::detect("tinyllama-3b-chat"), None);
+        assert_eq!(ChatTemplate::detect("llama-30b"), None);
+        assert_eq!(ChatTemplate::detect("phi-35-custom"), None);
+    }
+
+    #[test]
+    fn chatml_renders_multi_turn_history() {
+        let prompt = ChatTemplate::ChatMl.render("Be brief.", &history(), Some("Rain expected"));
+        assert_eq!(
+            prompt,
+            "<|im_start|>system\nBe brief.\n\nContext:\nRain expected<|im_end|>\n\
+             <|im_start|>user\nHi<|im_end|>\n\
+             <|im_start|>assistant\nHello!<|im_end|>\n\
+             <|im_start|>user\nWeather?<|im_end|>\n\
+             <|im_start|>assistant\n"
+        );
+    }
+
+    #[test]
+    fn llama3_renders_header_tokens() {
+        let prompt = ChatTemplate::Llama3.render("Be brief.", &history(), None);
+        assert!(prompt.starts_with("<|begin_of_text|><|start_header_id|>system<|end_header_id|>\n\nBe brief.<|eot_id|>"));
+        assert!(prompt.contains("<|start_header_id|>assistant<|end_header_id|>\n\nHello!<|eot_id|>"));
+        assert!(prompt.ends_with("Weather?<|eot_id|><|start_header_id|>assistant<|end_header_id|>\n\n"));
+    }
+
+    #[test]
+    fn mistral_and_gemma_fold_system_into_first_user_turn() {
+        let mistral = ChatTemplate::Mistral.render("Be brief.", &history(), None);
+        assert_eq!(mistral, "<s>[INST] Be brief.\n\nHi [/INST] Hello!</s>[INST] Weather? [/INST]");
+
+        let llama2 = ChatTemplate::Llama2.render("Be brief.", &[ChatMessage::user("Hi")], None);
+        assert_eq!(llama2, "<s>[INST] <<SYS>>\nBe brief.\n<</SYS>>\n\nHi [/INST]");
+        // Llama 2 opens every round with BOS, Mistral only the first
+        let llama2 = ChatTemplate::Llama2.render("Be brief.", &history(), None);
+        assert!(llama2.ends_with("Hi [/INST] Hello!</s><s>[INST] Weather? [/INST]"));
+
+        let gemma = ChatTemplate::Gemma.render("Be brief.", &history(), None);
+        assert!(gemma.starts_with("<start_of_turn>user\nBe brief.\n\nHi<end_of_turn>\n<start_of_turn>model\nHello!<end_of_turn>\n"));
+        assert!(gemma.ends_with("<start_of_turn>model\n"));
+        assert!(!gemma.contains("system"));
+    }
+
+    #[test]
+    fn render_request_includes_tools_and_tool_results() {
+        let request = PromptRequest {
+            prompt: "Weather in Berlin?".to_string(),
+            system_prompt: Some("Be brief.".to_string()),
+            tools: vec![geri::llm::ToolDefinition {
+                name: "get_weather".to_string(),
+                description: "Current weather".to_string(),
+                parameters: json!({ "type": "object" }),
+            }],
+            tool_calls: vec![ToolCall {
+                id: "call_1".to_string(),
+                name: "get_weather".to_string(),
+                arguments: json!({ "city": "Berlin" }),
+            }],
+            tool_results: vec![ToolResult {
+                tool_call_id: "call_1".to_string(),
+                name: "get_weather".to_string(),
+                content: "12°C".to_string(),
+                is_error: false,
+            }],
+            ..Default::default()
+        };
+        let prompt = ChatTemplate::ChatMl.render_request(&request);
+        assert!(prompt.contains("get_weather"));
+        assert!(prompt.contains(
+            "<|im_start|>assistant\n{\"tool_calls\":[{\"arguments\":{\"city\":\"Berlin\"},\"name\":\"get_weather\"}]}<|im_end|>"
+        ));
+        assert!(prompt.contains(
+            "<|im_start|>user\n<tool_response>\n{\"content\":\"12°C\",\"name\":\"get_weather\"}\n</tool_response><|im_end|>"
+        ));
+        assert!(prompt.ends_with("<|im_start|>assistant\n"));
+    }
+
+    #[test]
+    fn trim_output_stops_at_end_of_turn() {
+        assert_eq!(ChatTemplate::ChatMl.trim_output("Hello<|im_end|>\n<|im_start|>user"), "Hello");
+        assert_eq!(ChatTemplate::Llama3.trim_output("Hi there<|eot_id|>"), "Hi there");
+        assert_eq!(ChatTemplate::Plain.trim_output("User: ok"), "User: ok");
+    }
+
+    #[test]
+    fn resolve_prefers_config_then_gguf_metadata_then_name() {
+        let file = write_gguf("{% for message in messages %}<|im_start|>{{ message.role }}{% endfor %}");
+        let path = file.path().to_str().unwrap();
+        let jinja = geri::llm::gguf::read_chat_template(file.path()).unwrap().unwrap();
+        assert!(jinja.starts_with("{% for message in messages %}"));
+
+        assert_eq!(ChatTemplate::resolve(Some(ChatTemplate::Gemma), path), ChatTemplate::Gemma);
+        assert_eq!(ChatTemplate::resolve(None, path), ChatTemplate::ChatMl);
+        assert_eq!(ChatTemplate::resolve(None, "/missing/llama-3-8b.gguf"), ChatTemplate::Llama3);
+        assert_eq!(ChatTemplate::resolve(None, "/missing/model.gguf"), ChatTemplate::Plain);
+    }
+
+    #[test]
+    fn oversized_gguf_array_is_invalid_data() {
+        let mut out = Vec::new();
+        out.extend_from_slice(b"GGUF");
+        out.extend_from_slice(&3u32.to_le_bytes());
+        out.extend_from_slice(&0u64.to_le_bytes()); // tensors
+        out.extend_from_slice(&1u64.to_le_bytes()); // key/values
+        out.extend_from_slice(&3u64.to_le_bytes());
+        out.extend_from_slice(b"ids");
+        out.extend_from_slice(&9u32.to_le_bytes());
+        out.extend_from_slice(&10u32.to_le_bytes()); // u64-Elemente
+        out.extend_from_slice(&u64::MAX.to_le_bytes());
+        let mut file = tempfile::Builder::new().suffix(".gguf").tempfile().unwrap();
+        file.write_all(&out).unwrap();
+
+        let err = geri::llm::gguf::read_chat_template(file.path()).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
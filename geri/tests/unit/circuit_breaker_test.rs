@@ -0,0 +1,235 @@
+//! Tests für Circuit-Breaker und Failover im GeriEngine (Phase 16.3).
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use futures::StreamExt;
+    use geri::cost::BudgetTracker;
+    use geri::error_handling::{CircuitBreakerConfig, CircuitBreakerRegistry, CircuitState, RetryManager};
+    use geri::llm::{GeriEngine, LLMError, LLMProvider, PromptRequest, PromptResponse, PromptStream, PromptStreamChunk, ProviderFactory};
+    use geri::model::{ModelInfo, ModelRegistry, ModelRegistryTrait, ModelType};
+    use geri::performance::{PerformanceTracker, ProviderMetrics};
+    use geri::selection::SelectionOptions;
+    use tokio::sync::RwLock;
+
+    fn config(failure_threshold: u32, open_duration_ms: u64) -> CircuitBreakerConfig {
+        CircuitBreakerConfig {
+            failure_threshold,
+            open_duration_ms,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_breaker_opens_half_opens_and_closes() {
+        let breakers = CircuitBreakerRegistry::new(config(2, 20));
+        assert!(breakers.allow("openai", "gpt-4").await);
+        breakers.record_failure("openai", "gpt-4", None).await;
+        assert_eq!(breakers.state("openai", "gpt-4").await, CircuitState::Closed);
+        breakers.record_failure("openai", "gpt-4", None).await;
+        assert_eq!(breakers.state("openai", "gpt-4").await, CircuitState::Open);
+        assert!(!breakers.allow("openai", "gpt-4").await);
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        assert!(breakers.allow("openai", "gpt-4").await);
+        assert_eq!(breakers.state("openai", "gpt-4").await, CircuitState::HalfOpen);
+        // Only one probe at a time
+        assert!(!breakers.allow("openai", "gpt-4").await);
+
+        breakers.record_success("openai", "gpt-4").await;
+        assert_eq!(breakers.state("openai", "gpt-4").await, CircuitState::Closed);
+
+        let metrics = breakers.metrics("openai", "gpt-4").await;
+        assert_eq!((metrics.opened, metrics.half_opened, metrics.closed, metrics.rejected), (1, 1, 1, 2));
+    }
+
+    #[tokio::test]
+    async fn test_failed_probe_reopens_breaker() {
+        let breakers = CircuitBreakerRegistry::new(config(1, 0));
+        breakers.record_failure("anthropic", "claude", None).await;
+        assert!(breakers.allow("anthropic", "claude").await);
+        breakers.record_failure("anthropic", "claude", None).await;
+        assert_eq!(breakers.state("anthropic", "claude").await, CircuitState::Open);
+        assert_eq!(breakers.metrics("anthropic", "claude").await.opened, 2);
+    }
+
+    #[tokio::test]
+    async fn test_breaker_trips_on_failure_rate() {
+        let breakers = CircuitBreakerRegistry::new(CircuitBreakerConfig {
+            failure_threshold: 100,
+            failure_rate_threshold: 0.5,
+            min_requests: 4,
+            ..Default::default()
+        });
+        let mut recent = ProviderMetrics {
+            total_requests: 3,
+            successful_requests: 0,
+            failed_requests: 3,
+            ..Default::default()
+        };
+        breakers.record_failure("google", "gemini", Some(&recent)).await;
+        assert_eq!(breakers.state("google", "gemini").await, CircuitState::Closed);
+
+        recent.total_requests = 4;
+        recent.successful_requests = 2;
+        breakers.record_failure("google", "gemini", Some(&recent)).await;
+        assert_eq!(breakers.state("google", "gemini").await, CircuitState::Open);
+        assert_eq!(breakers.snapshot().await[0].state, CircuitState::Open);
+    }
+
+    /// Fails (or hangs) on every call and counts the calls
+    struct FailingProvider {
+        calls: AtomicU32,
+        hang: bool,
+    }
+
+    #[async_trait]
+    impl LLMProvider for FailingProvider {
+        fn model_name(&self) -> &str { "cloud-model" }
+        async fn process_prompt(&self, _request: PromptRequest) -> Result<PromptResponse, LLMError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.hang {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+            Err(LLMError::ProcessingFailed("503 Service Unavailable".to_string()))
+        }
+    }
+
+    /// Streams one delta and ends without a `Done` frame, like a dropped connection
+    struct TruncatedProvider;
+
+    #[async_trait]
+    impl LLMProvider for TruncatedProvider {
+        fn model_name(&self) -> &str { "cloud-model" }
+        async fn process_prompt(&self, _request: PromptRequest) -> Result<PromptResponse, LLMError> {
+            Err(LLMError::ProcessingFailed("streaming only".to_string()))
+        }
+        async fn stream_prompt(&self, _request: PromptRequest) -> Result<PromptStream, LLMError> {
+            Ok(Box::pin(futures::stream::iter(vec![Ok(PromptStreamChunk::Delta("Hal".to_string()))])))
+        }
+    }
+
+    struct LocalProvider;
+
+    #[async_trait]
+    impl LLMProvider for LocalProvider {
+        fn model_name(&self) -> &str { "local-model" }
+        async fn process_prompt(&self, _request: PromptRequest) -> Result<PromptResponse, LLMError> {
+            Ok(PromptResponse {
+                text: "local answer".to_string(),
+                tokens_used: 5,
+                tool_calls: Vec::new(),
+                structured: None,
+                safety: Vec::new(),
+            })
+        }
+    }
+
+    fn model(id: &str, provider: &str, is_local: bool) -> ModelInfo {
+        ModelInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            provider: provider.to_string(),
+            model_type: ModelType::Llm,
+            parameter_count: Some(8_000_000_000),
+            hardware_requirements: None,
+            context_window: Some(8192),
+            is_local,
+            cost_per_token_input: Some(0.0),
+            cost_per_token_output: Some(0.0),
+            embedding_dimension: None,
+        }
+    }
+
+    async fn engine(cloud: Arc<dyn LLMProvider>, breaker: CircuitBreakerConfig) -> GeriEngine {
+        let registry: Arc<dyn ModelRegistryTrait> = Arc::new(RwLock::new(
+            ModelRegistry::new()
+                .register(model("cloud-model", "openai", false))
+                .register(model("local-model", "llamacpp", true)),
+        ));
+        let factory = Arc::new(ProviderFactory::new());
+        factory.register("cloud-model", cloud).await;
+        factory.register("local-model", Arc::new(LocalProvider)).await;
+        GeriEngine::new(
+            registry,
+            Arc::new(PerformanceTracker::new().unwrap()),
+            factory,
+            Arc::new(BudgetTracker::new(10.0)),
+        )
+        .with_circuit_breaker(Arc::new(CircuitBreakerRegistry::new(breaker)))
+        .with_retry_manager(RetryManager::new(2, 1))
+    }
+
+    fn prefer_cloud() -> SelectionOptions {
+        SelectionOptions {
+            user_preferred_model_id: Some("cloud-model".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn request() -> PromptRequest {
+        PromptRequest {
+            prompt: "Hello".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_engine_fails_over_to_next_candidate() {
+        let cloud = Arc::new(FailingProvider { calls: AtomicU32::new(0), hang: false });
+        let engine = engine(cloud.clone(), config(1, 60_000)).await;
+
+        let response = engine.process(request(), prefer_cloud()).await.unwrap();
+        assert_eq!(response.text, "local answer");
+        assert_eq!(engine.circuit_breaker().state("openai", "cloud-model").await, CircuitState::Open);
+
+        // Open breaker: the cloud provider is not called again
+        let response = engine.process(request(), prefer_cloud()).await.unwrap();
+        assert_eq!(response.text, "local answer");
+        assert_eq!(cloud.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(engine.circuit_breaker().metrics("openai", "cloud-model").await.rejected, 1);
+    }
+
+    #[tokio::test]
+    async fn test_engine_fails_over_on_timeout() {
+        let cloud = Arc::new(FailingProvider { calls: AtomicU32::new(0), hang: true });
+        let engine = engine(cloud, config(3, 60_000)).await.with_request_timeout(Duration::from_millis(20));
+
+        let response = engine.process(request(), prefer_cloud()).await.unwrap();
+        assert_eq!(response.text, "local answer");
+    }
+
+    #[tokio::test]
+    async fn test_engine_returns_provider_error_without_retries() {
+        let cloud = Arc::new(FailingProvider { calls: AtomicU32::new(0), hang: false });
+        let engine = engine(cloud, config(3, 60_000)).await.with_retry_manager(RetryManager::new(0, 1));
+
+        let error = engine.process(request(), prefer_cloud()).await.unwrap_err();
+        assert!(error.to_string().contains("503"));
+    }
+
+    #[tokio::test]
+    async fn test_engine_stream_fails_over_before_first_frame() {
+        let cloud = Arc::new(FailingProvider { calls: AtomicU32::new(0), hang: false });
+        let engine = engine(cloud, config(3, 60_000)).await;
+
+        let mut stream = engine.process_stream(request(), prefer_cloud()).await.unwrap();
+        let first = stream.next().await.unwrap().unwrap();
+        assert_eq!(first, PromptStreamChunk::Delta("local answer".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_engine_stream_without_done_counts_as_failure() {
+        let engine = engine(Arc::new(TruncatedProvider), config(1, 60_000)).await;
+
+        let chunks: Vec<_> = engine.process_stream(request(), prefer_cloud()).await.unwrap().collect().await;
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].as_ref().unwrap(), &PromptStreamChunk::Delta("Hal".to_string()));
+        assert!(chunks[1].as_ref().unwrap_err().to_string().contains("without a Done frame"));
+        assert_eq!(engine.circuit_breaker().state("openai", "cloud-model").await, CircuitState::Open);
+    }
+}
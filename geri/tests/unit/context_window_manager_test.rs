@@ -2,6 +2,7 @@
 
 #[cfg(test)]
 mod tests {
+    use geri::llm::{ChatMessage, ToolCall};
     use geri::prompt::{ContextDocument, ContextWindowManager, TokenCounter};
 
     fn doc(id: &str, content: &str, score: f32) -> ContextDocument {
@@ -78,4 +79,43 @@ mod tests {
         let fits = mgr.fits_in_window("System", "User", &docs, 100, "gpt-4");
         assert!(!fits);
     }
+
+    #[test]
+    fn trim_messages_drops_oldest_turns_and_keeps_system() {
+        let mgr = ContextWindowManager::new(TokenCounter::default());
+        let messages = vec![
+            ChatMessage::system("Be brief."),
+            ChatMessage::user("x".repeat(400)),
+            ChatMessage::assistant("y".repeat(400)),
+            ChatMessage::user("Turn on the light."),
+            ChatMessage::assistant("Done."),
+        ];
+        let trimmed = mgr.trim_messages(&messages, 50, "gpt-4");
+        assert_eq!(trimmed.len(), 3);
+        assert_eq!(trimmed[0].content, "Be brief.");
+        assert_eq!(trimmed[1].content, "Turn on the light.");
+        assert_eq!(trimmed[2].content, "Done.");
+    }
+
+    #[test]
+    fn trim_messages_keeps_everything_when_under_limit() {
+        let mgr = ContextWindowManager::new(TokenCounter::default());
+        let messages = vec![ChatMessage::user("Hi"), ChatMessage::assistant("Hello")];
+        assert_eq!(mgr.trim_messages(&messages, 1000, "gpt-4"), messages);
+    }
+
+    #[test]
+    fn trim_messages_drops_orphaned_tool_results() {
+        let mgr = ContextWindowManager::new(TokenCounter::default());
+        let mut call_turn = ChatMessage::assistant("");
+        call_turn.tool_calls = vec![ToolCall::new("get_weather", serde_json::json!({ "city": "x".repeat(200) }))];
+        let messages = vec![
+            ChatMessage::user("Weather?"),
+            call_turn,
+            ChatMessage::tool("call_1", "get_weather", "Sunny"),
+            ChatMessage::assistant("It is sunny."),
+        ];
+        let trimmed = mgr.trim_messages(&messages, 10, "gpt-4");
+        assert_eq!(trimmed, vec![ChatMessage::assistant("It is sunny.")]);
+    }
 }
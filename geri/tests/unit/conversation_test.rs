@@ -0,0 +1,98 @@
+use geri::llm::anthropic::{AnthropicClient, AnthropicConfig};
+use geri::llm::google::{GoogleClient, GoogleConfig};
+use geri::llm::openai::{OpenAIClient, OpenAIConfig};
+use geri::llm::{ChatMessage, ChatRole, PromptRequest, ToolCall};
+use serde_json::json;
+
+fn history() -> Vec<ChatMessage> {
+    let mut call_turn = ChatMessage::assistant("");
+    call_turn.tool_calls = vec![ToolCall {
+        id: "call_1".to_string(),
+        name: "get_weather".to_string(),
+        arguments: json!({ "city": "Berlin" }),
+    }];
+    vec![
+        ChatMessage::user("Weather in Berlin?"),
+        call_turn,
+        ChatMessage::tool("call_1", "get_weather", "12°C"),
+        ChatMessage::assistant("It is 12°C in Berlin."),
+    ]
+}
+
+fn request() -> PromptRequest {
+    PromptRequest {
+        prompt: "And tomorrow?".to_string(),
+        messages: history(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_conversation_appends_prompt_as_user_turn() {
+    let conversation = request().conversation();
+    assert_eq!(conversation.len(), 5);
+    assert_eq!(conversation[4], ChatMessage::user("And tomorrow?"));
+
+    let single = PromptRequest {
+        prompt: "Hi".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(single.conversation(), vec![ChatMessage::user("Hi")]);
+    assert_eq!(ChatRole::parse("model"), ChatRole::Assistant);
+}
+
+#[test]
+fn test_openai_conversation_request() {
+    let client = OpenAIClient::new(OpenAIConfig {
+        api_key: "sk-test123".to_string(),
+        base_url: "https://api.openai.com/v1".to_string(),
+        timeout_secs: 30,
+    });
+    let chat = client.build_conversation_request("gpt-4o", &request().conversation(), Some("Be brief."), None, None);
+
+    let body = serde_json::to_value(&chat).unwrap();
+    let roles: Vec<&str> = body["messages"].as_array().unwrap().iter().map(|m| m["role"].as_str().unwrap()).collect();
+    assert_eq!(roles, vec!["system", "user", "assistant", "tool", "assistant", "user"]);
+    assert_eq!(body["messages"][2]["tool_calls"][0]["id"], "call_1");
+    assert_eq!(body["messages"][3]["tool_call_id"], "call_1");
+    assert_eq!(body["messages"][5]["content"], "And tomorrow?");
+}
+
+#[test]
+fn test_anthropic_conversation_request() {
+    let client = AnthropicClient::new(AnthropicConfig {
+        api_key: "sk-ant-test123".to_string(),
+        base_url: "https://api.anthropic.com/v1".to_string(),
+        timeout_secs: 30,
+        anthropic_version: "2023-06-01".to_string(),
+    });
+    let mut conversation = vec![ChatMessage::system("Speak German.")];
+    conversation.extend(request().conversation());
+    let messages = client.build_conversation_request("claude-3-5-sonnet", &conversation, Some("Be brief."), None, None);
+
+    let body = serde_json::to_value(&messages).unwrap();
+    assert_eq!(body["system"], "Be brief.\n\nSpeak German.");
+    let roles: Vec<&str> = body["messages"].as_array().unwrap().iter().map(|m| m["role"].as_str().unwrap()).collect();
+    assert_eq!(roles, vec!["user", "assistant", "user", "assistant", "user"]);
+    assert_eq!(body["messages"][1]["content"][0]["type"], "tool_use");
+    assert_eq!(body["messages"][2]["content"][0]["type"], "tool_result");
+}
+
+#[test]
+fn test_google_conversation_request() {
+    let client = GoogleClient::new(GoogleConfig::new("test-key".to_string()));
+    let generate = client.build_conversation_request(
+        "gemini-pro",
+        &request().conversation(),
+        Some("Be brief."),
+        Some("Forecast: rain"),
+        None,
+    );
+
+    let body = serde_json::to_value(&generate).unwrap();
+    assert_eq!(body["systemInstruction"]["parts"][0]["text"], "Be brief.");
+    let roles: Vec<&str> = body["contents"].as_array().unwrap().iter().map(|c| c["role"].as_str().unwrap()).collect();
+    assert_eq!(roles, vec!["user", "model", "function", "model", "user"]);
+    assert_eq!(body["contents"][0]["parts"][0]["text"], "Weather in Berlin?");
+    assert_eq!(body["contents"][4]["parts"][0]["text"], "Context: Forecast: rain\n\nPrompt: And tomorrow?");
+}
@@ -3,6 +3,9 @@
 #[cfg(test)]
 mod tests {
     use geri::cost::CostCalculator;
+    use geri::llm::{ChatMessage, PromptRequest};
+    use geri::model::{ModelInfo, ModelType};
+    use geri::prompt::TokenCounter;
 
     #[test]
     fn calculate_total_cost_openai() {
@@ -39,4 +42,31 @@ mod tests {
         let cost = calc.total_cost(100, 50, "unknown", "unknown");
         assert!(cost >= 0.0);
     }
+
+    #[test]
+    fn input_tokens_counts_whole_request() {
+        let calc = CostCalculator::new(TokenCounter::default());
+        let model = ModelInfo {
+            id: "gpt-4".to_string(),
+            name: "GPT-4".to_string(),
+            provider: "openai".to_string(),
+            model_type: ModelType::Llm,
+            parameter_count: None,
+            hardware_requirements: None,
+            context_window: Some(8192),
+            is_local: false,
+            cost_per_token_input: None,
+            cost_per_token_output: None,
+            embedding_dimension: None,
+        };
+        let request = PromptRequest {
+            prompt: "abcdefgh".to_string(),
+            system_prompt: Some("abcd".to_string()),
+            context: Some("abcd".to_string()),
+            messages: vec![ChatMessage::user("abcd"), ChatMessage::assistant("abcd")],
+            ..Default::default()
+        };
+        // 1 (system) + 1 (context) + 2 (history) + 2 (prompt)
+        assert_eq!(calc.input_tokens(&request, &model), 6);
+    }
 }
@@ -18,6 +18,7 @@ mod tests {
             is_local: true,
             cost_per_token: Some(0.000_01),
             max_cost_per_token: 0.001,
+            quality_score: None,
         }
     }
 
@@ -112,6 +113,7 @@ mod tests {
             is_local: false,
             cost_per_token: None,
             max_cost_per_token: 1.0,
+            quality_score: None,
         };
         let score = calc.calculate(&input);
         assert!(score >= 0.0 && score <= 1.0);
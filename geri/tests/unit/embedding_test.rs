@@ -0,0 +1,230 @@
+//! Tests für EmbeddingService und Embedding-Provider (OpenAI, Google, llama.cpp).
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use geri::llm::google::{GoogleConfig, GoogleEmbeddingProvider};
+    use geri::llm::llamacpp::{LlamaCppEmbeddingConfig, LlamaCppEmbeddingProvider};
+    use geri::llm::openai::{OpenAIConfig, OpenAIEmbeddingProvider};
+    use geri::llm::{EmbeddingProvider, EmbeddingResponse, EmbeddingService, LLMError};
+    use geri::model::{ModelInfo, ModelRegistry, ModelRegistryTrait, ModelType};
+    use serde_json::json;
+    use tokio::sync::RwLock;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// Liefert pro Input einen Vektor `[len, 1.0, ...]` der angegebenen Dimension.
+    struct FixedProvider {
+        dimension: usize,
+    }
+
+    #[async_trait]
+    impl EmbeddingProvider for FixedProvider {
+        fn model_name(&self) -> &str { "fixed" }
+        async fn embed(&self, inputs: &[String]) -> Result<EmbeddingResponse, LLMError> {
+            let embeddings = inputs
+                .iter()
+                .map(|input| {
+                    let mut vector = vec![1.0; self.dimension];
+                    vector[0] = input.len() as f32;
+                    vector
+                })
+                .collect();
+            Ok(EmbeddingResponse { embeddings, tokens_used: 0 })
+        }
+    }
+
+    fn embedding_model(id: &str, dimension: u32, max_input_tokens: u32) -> ModelInfo {
+        ModelInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            provider: "local".to_string(),
+            model_type: ModelType::Embedding,
+            parameter_count: None,
+            hardware_requirements: None,
+            context_window: Some(max_input_tokens),
+            is_local: true,
+            cost_per_token_input: None,
+            cost_per_token_output: None,
+            embedding_dimension: Some(dimension),
+        }
+    }
+
+    fn service() -> (EmbeddingService, Arc<dyn ModelRegistryTrait>) {
+        let registry: Arc<dyn ModelRegistryTrait> = Arc::new(RwLock::new(ModelRegistry::new()));
+        (EmbeddingService::new(registry.clone()), registry)
+    }
+
+    fn inputs(texts: &[&str]) -> Vec<String> {
+        texts.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_register_adds_model_to_registry_and_sets_default() {
+        let (service, registry) = service();
+        service
+            .register(embedding_model("nomic", 4, 512), Arc::new(FixedProvider { dimension: 4 }))
+            .await
+            .unwrap();
+        service
+            .register(embedding_model("minilm", 3, 256), Arc::new(FixedProvider { dimension: 3 }))
+            .await
+            .unwrap();
+
+        assert_eq!(service.default_model().await.as_deref(), Some("nomic"));
+        let info = registry.get_by_id("minilm").await.unwrap().unwrap();
+        assert_eq!(info.model_type, ModelType::Embedding);
+        assert_eq!(info.embedding_dimension, Some(3));
+
+        let result = service.embed(None, &inputs(&["hallo", "welten"])).await.unwrap();
+        assert_eq!(result.model, "nomic");
+        assert_eq!(result.dimension, 4);
+        assert_eq!(result.vectors, vec![vec![5.0, 1.0, 1.0, 1.0], vec![6.0, 1.0, 1.0, 1.0]]);
+        // Provider meldet keine Usage: gezählte Tokens
+        assert!(result.tokens_used > 0);
+
+        service.set_default_model("minilm").await.unwrap();
+        assert_eq!(service.embed(None, &inputs(&["a"])).await.unwrap().model, "minilm");
+        assert!(matches!(service.set_default_model("unknown").await, Err(LLMError::ModelNotAvailable(_))));
+    }
+
+    #[tokio::test]
+    async fn test_register_rejects_non_embedding_models() {
+        let (service, _) = service();
+        let mut llm = embedding_model("llama", 4, 512);
+        llm.model_type = ModelType::Llm;
+        let result = service.register(llm, Arc::new(FixedProvider { dimension: 4 })).await;
+        assert!(matches!(result, Err(LLMError::InvalidInput(_))));
+
+        let mut no_dimension = embedding_model("nomic", 4, 512);
+        no_dimension.embedding_dimension = None;
+        let result = service.register(no_dimension, Arc::new(FixedProvider { dimension: 4 })).await;
+        assert!(matches!(result, Err(LLMError::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn test_embed_validates_input_model_and_dimension() {
+        let (service, registry) = service();
+        assert!(matches!(service.embed(None, &inputs(&["a"])).await, Err(LLMError::ModelNotAvailable(_))));
+
+        service
+            .register(embedding_model("short", 4, 5), Arc::new(FixedProvider { dimension: 4 }))
+            .await
+            .unwrap();
+        // Provider liefert 3 statt der registrierten 8 Dimensionen
+        service
+            .register(embedding_model("wrong-dim", 8, 512), Arc::new(FixedProvider { dimension: 3 }))
+            .await
+            .unwrap();
+        registry.register(ModelInfo { model_type: ModelType::Llm, ..embedding_model("llama", 4, 512) }).await.unwrap();
+
+        assert!(matches!(service.embed(None, &[]).await, Err(LLMError::InvalidInput(_))));
+        let too_long = "word ".repeat(100);
+        assert!(matches!(
+            service.embed(Some("short"), &[too_long]).await,
+            Err(LLMError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            service.embed(Some("wrong-dim"), &inputs(&["a"])).await,
+            Err(LLMError::ProcessingFailed(_))
+        ));
+        assert!(matches!(
+            service.embed(Some("llama"), &inputs(&["a"])).await,
+            Err(LLMError::ModelNotAvailable(_))
+        ));
+        assert!(matches!(
+            service.embed(Some("unknown"), &inputs(&["a"])).await,
+            Err(LLMError::ModelNotAvailable(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_openai_provider_orders_by_index() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/embeddings"))
+            .and(header("Authorization", "Bearer test-key"))
+            .and(body_partial_json(json!({
+                "model": "text-embedding-3-small",
+                "input": ["a", "b"],
+                "dimensions": 2
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "object": "list",
+                "data": [
+                    { "object": "embedding", "index": 1, "embedding": [0.3, 0.4] },
+                    { "object": "embedding", "index": 0, "embedding": [0.1, 0.2] }
+                ],
+                "model": "text-embedding-3-small",
+                "usage": { "prompt_tokens": 2, "total_tokens": 2 }
+            })))
+            .mount(&server)
+            .await;
+
+        let config = OpenAIConfig {
+            api_key: "test-key".to_string(),
+            base_url: server.uri(),
+            timeout_secs: 5,
+        };
+        let provider = OpenAIEmbeddingProvider::new(config, "text-embedding-3-small".to_string()).with_dimensions(2);
+        let response = provider.embed(&inputs(&["a", "b"])).await.unwrap();
+        assert_eq!(response.embeddings, vec![vec![0.1, 0.2], vec![0.3, 0.4]]);
+        assert_eq!(response.tokens_used, 2);
+    }
+
+    #[tokio::test]
+    async fn test_google_provider_batch_embeds() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/models/text-embedding-004:batchEmbedContents"))
+            .and(header("x-goog-api-key", "test-key"))
+            .and(body_partial_json(json!({
+                "requests": [
+                    { "model": "models/text-embedding-004", "content": { "parts": [{ "text": "a" }] } },
+                    { "model": "models/text-embedding-004", "content": { "parts": [{ "text": "b" }] } }
+                ]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "embeddings": [{ "values": [0.1, 0.2] }, { "values": [0.3, 0.4] }]
+            })))
+            .mount(&server)
+            .await;
+
+        let config = GoogleConfig {
+            api_key: "test-key".to_string(),
+            base_url: server.uri(),
+        };
+        let provider = GoogleEmbeddingProvider::new(config, "text-embedding-004".to_string());
+        let response = provider.embed(&inputs(&["a", "b"])).await.unwrap();
+        assert_eq!(response.embeddings, vec![vec![0.1, 0.2], vec![0.3, 0.4]]);
+    }
+
+    #[tokio::test]
+    async fn test_llamacpp_provider_pools_per_token_rows() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/embedding"))
+            .and(body_partial_json(json!({ "content": "pooled" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "embedding": [0.5, 0.5] })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/embedding"))
+            .and(body_partial_json(json!({ "content": "tokens" })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+                { "index": 0, "embedding": [[1.0, 0.0], [0.0, 1.0]] }
+            ])))
+            .mount(&server)
+            .await;
+
+        let config = LlamaCppEmbeddingConfig {
+            server_url: server.uri(),
+            timeout_secs: 5,
+        };
+        let provider = LlamaCppEmbeddingProvider::new(config, "nomic-embed-text".to_string());
+        let response = provider.embed(&inputs(&["pooled", "tokens"])).await.unwrap();
+        assert_eq!(response.embeddings, vec![vec![0.5, 0.5], vec![0.5, 0.5]]);
+    }
+}
@@ -31,6 +31,9 @@ impl LLMProvider for MockProvider {
         Ok(PromptResponse {
             text: format!("Mock response from {} for: {}", self.name, request.prompt),
             tokens_used: 10,
+            tool_calls: Vec::new(),
+            structured: None,
+            safety: Vec::new(),
         })
     }
 }
@@ -49,6 +52,7 @@ async fn test_engine_orchestration() {
             is_local: false,
             cost_per_token_input: Some(0.00003),
             cost_per_token_output: Some(0.00006),
+            embedding_dimension: None,
         },
     ];
 
@@ -88,6 +92,7 @@ async fn test_engine_user_preference() {
             is_local: false,
             cost_per_token_input: Some(0.00003),
             cost_per_token_output: Some(0.00006),
+            embedding_dimension: None,
         },
     ];
 
@@ -130,6 +135,7 @@ async fn test_engine_fallback_on_budget_exceeded() {
             is_local: false,
             cost_per_token_input: Some(1.0), // Expensive!
             cost_per_token_output: Some(1.0),
+            embedding_dimension: None,
         },
         ModelInfo {
             id: "local-model".to_string(),
@@ -142,6 +148,7 @@ async fn test_engine_fallback_on_budget_exceeded() {
             is_local: true,
             cost_per_token_input: Some(0.0),
             cost_per_token_output: Some(0.0),
+            embedding_dimension: None,
         },
     ];
 
@@ -0,0 +1,296 @@
+//! Tests für Benchmark-Suites, Grader, Eval-Runner und gespeicherte Evaluationsergebnisse.
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use chrono::Utc;
+    use geri::evaluation::{
+        grade_answer, EvalCase, EvalReport, EvalRunner, EvalSuite, EvaluationError, EvaluationStore, Grader,
+    };
+    use geri::llm::{LLMError, LLMProvider, PromptRequest, PromptResponse, ProviderFactory};
+    use geri::selection::{EfficiencyInput, EfficiencyScoreCalculator, EfficiencyWeights};
+    use serde_json::json;
+
+    const YAML_SUITE: &str = r#"
+name: smoke
+description: Basic offline checks
+cases:
+  - id: capital
+    prompt: "What is the capital of France? Answer with one word."
+    expected: Paris
+  - id: year
+    prompt: "In which year did the Berlin Wall fall?"
+    grader:
+      type: regex
+      pattern: "\\b1989\\b"
+  - id: person
+    prompt: "Return a JSON object with name and age."
+    grader:
+      type: json_schema
+      schema:
+        type: object
+        properties:
+          name: { type: string }
+          age: { type: integer }
+        required: [name, age]
+  - id: explain
+    prompt: "Explain recursion in one sentence."
+    expected: "A function that calls itself."
+    grader:
+      type: llm_judge
+      model: judge
+      rubric: Mentions self-reference.
+"#;
+
+    /// Answers prompts from a fixed table, counting concurrent calls
+    struct ScriptedProvider {
+        answers: HashMap<&'static str, &'static str>,
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+        delay: Duration,
+    }
+
+    impl ScriptedProvider {
+        fn new(answers: &[(&'static str, &'static str)]) -> Self {
+            Self {
+                answers: answers.iter().copied().collect(),
+                in_flight: AtomicUsize::new(0),
+                max_in_flight: AtomicUsize::new(0),
+                delay: Duration::from_millis(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl LLMProvider for ScriptedProvider {
+        fn model_name(&self) -> &str { "scripted" }
+        async fn process_prompt(&self, request: PromptRequest) -> Result<PromptResponse, LLMError> {
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            tokio::time::sleep(self.delay).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            let answer = self
+                .answers
+                .iter()
+                .find(|(prompt, _)| request.prompt.contains(*prompt))
+                .map(|(_, answer)| answer.to_string())
+                .ok_or_else(|| LLMError::ProcessingFailed("no scripted answer".to_string()))?;
+            Ok(PromptResponse {
+                text: answer,
+                tokens_used: 10,
+                tool_calls: Vec::new(),
+                structured: None,
+                safety: Vec::new(),
+            })
+        }
+    }
+
+    fn case(id: &str, expected: Option<&str>, grader: Grader) -> EvalCase {
+        EvalCase {
+            id: id.to_string(),
+            prompt: format!("prompt {}", id),
+            system_prompt: None,
+            max_tokens: None,
+            expected: expected.map(str::to_string),
+            grader,
+        }
+    }
+
+    #[test]
+    fn test_load_yaml_and_jsonl_suites() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = dir.path().join("smoke.yaml");
+        std::fs::write(&yaml, YAML_SUITE).unwrap();
+        let suite = EvalSuite::load(&yaml).unwrap();
+        assert_eq!(suite.name, "smoke");
+        assert_eq!(suite.cases.len(), 4);
+        assert_eq!(suite.cases[0].grader, Grader::ExactMatch { case_sensitive: false });
+        assert!(matches!(&suite.cases[3].grader, Grader::LlmJudge { model, pass_threshold, .. } if model == "judge" && *pass_threshold == 0.5));
+
+        let jsonl = dir.path().join("math.jsonl");
+        std::fs::write(
+            &jsonl,
+            "{\"id\":\"add\",\"prompt\":\"2+2?\",\"expected\":\"4\"}\n\n{\"id\":\"mul\",\"prompt\":\"3*3?\",\"grader\":{\"type\":\"regex\",\"pattern\":\"9\"}}\n",
+        )
+        .unwrap();
+        let suite = EvalSuite::load(&jsonl).unwrap();
+        assert_eq!(suite.name, "math");
+        assert_eq!(suite.cases.len(), 2);
+
+        // Same cases, same fingerprint
+        assert_eq!(suite.fingerprint(), EvalSuite::load(&jsonl).unwrap().fingerprint());
+        assert_ne!(suite.fingerprint(), EvalSuite::load(&yaml).unwrap().fingerprint());
+    }
+
+    #[test]
+    fn test_invalid_suites_are_rejected() {
+        let suite = |cases| EvalSuite { name: "bad".to_string(), description: None, cases };
+        assert!(matches!(suite(vec![]).validate(), Err(EvaluationError::InvalidSuite { .. })));
+        let duplicate = suite(vec![case("a", Some("x"), Grader::default()), case("a", Some("y"), Grader::default())]);
+        assert!(duplicate.validate().unwrap_err().to_string().contains("duplicate case id 'a'"));
+        assert!(suite(vec![case("a", None, Grader::default())]).validate().is_err());
+        let regex = Grader::Regex { pattern: "(".to_string() };
+        assert!(suite(vec![case("a", None, regex)]).validate().is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("broken.jsonl");
+        std::fs::write(&path, "{\"id\":\"a\"}\n").unwrap();
+        let error = EvalSuite::load(&path).unwrap_err();
+        assert!(matches!(error, EvaluationError::Parse { .. }));
+        assert!(error.to_string().contains("line 1"));
+    }
+
+    #[test]
+    fn test_deterministic_graders() {
+        let exact = case("a", Some("Paris"), Grader::default());
+        assert!(grade_answer(&exact, "  paris\n").unwrap().passed);
+        assert!(!grade_answer(&exact, "Lyon").unwrap().passed);
+        let sensitive = case("a", Some("Paris"), Grader::ExactMatch { case_sensitive: true });
+        assert!(!grade_answer(&sensitive, "paris").unwrap().passed);
+
+        let regex = case("b", None, Grader::Regex { pattern: r"\b1989\b".to_string() });
+        assert!(grade_answer(&regex, "It fell in 1989.").unwrap().passed);
+        assert_eq!(grade_answer(&regex, "In 19890").unwrap().score, 0.0);
+
+        let schema = json!({"type": "object", "properties": {"age": {"type": "integer"}}, "required": ["age"]});
+        let json_case = case("c", None, Grader::JsonSchema { schema });
+        assert!(grade_answer(&json_case, "```json\n{\"age\": 3}\n```").unwrap().passed);
+        let failed = grade_answer(&json_case, "{\"age\": \"three\"}").unwrap();
+        assert!(!failed.passed);
+        assert!(failed.detail.unwrap().contains("age"));
+
+        let judge = case("d", None, Grader::LlmJudge { model: "judge".to_string(), rubric: None, pass_threshold: 0.5 });
+        assert!(grade_answer(&judge, "anything").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_runner_grades_suite_with_judge_and_concurrency_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("smoke.yaml");
+        std::fs::write(&path, YAML_SUITE).unwrap();
+        let suite = EvalSuite::load(&path).unwrap();
+
+        let mut model = ScriptedProvider::new(&[
+            ("capital of France", "Paris"),
+            ("Berlin Wall", "It fell in 1989."),
+            ("JSON object", "{\"name\": \"Ada\"}"),
+            ("recursion", "Recursion is when a function calls itself."),
+        ]);
+        model.delay = Duration::from_millis(20);
+        let model = Arc::new(model);
+        let judges = Arc::new(ProviderFactory::new());
+        judges
+            .register("judge", Arc::new(ScriptedProvider::new(&[("Answer to rate", "{\"score\": 0.8, \"reason\": \"correct\"}")])))
+            .await;
+
+        let runner = EvalRunner::new().with_concurrency(2).with_judges(judges);
+        let report = runner.run(&suite, "scripted", model.clone()).await;
+
+        assert_eq!(report.suite, "smoke");
+        assert_eq!(report.model, "scripted");
+        assert_eq!(report.suite_fingerprint, suite.fingerprint());
+        let ids: Vec<&str> = report.results.iter().map(|r| r.case_id.as_str()).collect();
+        assert_eq!(ids, ["capital", "year", "person", "explain"]);
+        let passed: Vec<bool> = report.results.iter().map(|r| r.grade.passed).collect();
+        assert_eq!(passed, [true, true, false, true]);
+        assert_eq!(report.results[3].grade.score, 0.8);
+        assert_eq!(report.results[3].grade.detail.as_deref(), Some("correct"));
+        assert!((report.mean_score() - 0.7).abs() < 1e-9);
+        assert_eq!(report.pass_rate(), 0.75);
+        assert_eq!(model.max_in_flight.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_runner_fails_cases_on_provider_errors_and_missing_judge() {
+        let suite = EvalSuite {
+            name: "errors".to_string(),
+            description: None,
+            cases: vec![
+                case("unknown", Some("x"), Grader::default()),
+                case("judged", None, Grader::LlmJudge { model: "missing".to_string(), rubric: None, pass_threshold: 0.5 }),
+            ],
+        };
+        let model = Arc::new(ScriptedProvider::new(&[("prompt judged", "some answer")]));
+        let report = EvalRunner::new().run(&suite, "scripted", model).await;
+        assert!(report.results[0].answer.is_none());
+        assert!(report.results[0].error.as_deref().unwrap().contains("no scripted answer"));
+        assert!(!report.results[1].grade.passed);
+        assert!(report.results[1].grade.detail.as_deref().unwrap().contains("judge model 'missing' is not registered"));
+        assert_eq!(report.pass_rate(), 0.0);
+    }
+
+    fn report(suite: &str, model: &str, scores: &[f64], age_secs: i64) -> EvalReport {
+        let results = scores
+            .iter()
+            .enumerate()
+            .map(|(i, score)| geri::evaluation::CaseResult {
+                case_id: i.to_string(),
+                answer: Some("answer".to_string()),
+                error: None,
+                grade: geri::evaluation::Grade { score: *score, passed: *score >= 0.5, detail: None },
+                latency_ms: 10,
+                tokens_used: 5,
+            })
+            .collect();
+        EvalReport {
+            suite: suite.to_string(),
+            suite_fingerprint: "fp".to_string(),
+            model: model.to_string(),
+            started_at: Utc::now() - chrono::Duration::seconds(age_secs),
+            duration_ms: 100,
+            results,
+        }
+    }
+
+    #[test]
+    fn test_store_persists_reports_and_uses_latest_run_per_suite() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results").join("evaluations.jsonl");
+        {
+            let store = EvaluationStore::open(&path).unwrap();
+            store.append(report("smoke", "llama", &[0.0, 0.0], 60)).unwrap();
+            store.append(report("smoke", "llama", &[1.0, 0.0], 10)).unwrap();
+            store.append(report("math", "llama", &[1.0], 30)).unwrap();
+            store.append(report("smoke", "gpt", &[1.0], 10)).unwrap();
+        }
+        let store = EvaluationStore::open(&path).unwrap();
+        assert_eq!(store.reports().len(), 4);
+        let latest = store.latest("llama");
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest[0].suite, "math");
+        assert_eq!(latest[1].mean_score(), 0.5);
+        assert_eq!(store.quality_score("llama"), Some(0.75));
+        assert_eq!(store.quality_score("gpt"), Some(1.0));
+        assert_eq!(store.quality_score("unknown"), None);
+    }
+
+    #[test]
+    fn test_quality_score_feeds_efficiency() {
+        let input = EfficiencyInput {
+            parameter_count: Some(8_000_000_000),
+            max_parameter_count: 70_000_000_000,
+            hardware_score: 0.8,
+            uptime_percentage: Some(99.0),
+            error_rate: Some(0.01),
+            ping_ms: Some(100),
+            max_ping_ms: 1000,
+            distance_km: None,
+            max_distance_km: 1000.0,
+            is_local: true,
+            cost_per_token: Some(0.0),
+            max_cost_per_token: 0.001,
+            quality_score: None,
+        };
+        let calc = EfficiencyScoreCalculator::new(EfficiencyWeights::default());
+        let base = calc.calculate(&input);
+        let good = calc.calculate(&EfficiencyInput { quality_score: Some(1.0), ..input.clone() });
+        let bad = calc.calculate(&EfficiencyInput { quality_score: Some(0.0), ..input.clone() });
+        assert!(good > base && base > bad);
+        assert!((bad - base * 0.75).abs() < 1e-9);
+    }
+}
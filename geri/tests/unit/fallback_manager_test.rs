@@ -34,6 +34,7 @@ mod tests {
             is_local: true,
             cost_per_token: Some(0.0),
             max_cost_per_token: 0.001,
+            quality_score: None,
         }
     }
 
@@ -33,6 +33,7 @@ mod tests {
             is_local: true,
             cost_per_token: Some(0.000_001),
             max_cost_per_token: 0.001,
+            quality_score: None,
         }
     }
 
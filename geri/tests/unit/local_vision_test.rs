@@ -0,0 +1,242 @@
+//! Tests für lokale Vision-Models über llama.cpp (Discovery, Provider, Engine-Routing, Frame-Analyse).
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::sync::{Arc, Mutex};
+
+    use geri::cost::BudgetTracker;
+    use geri::grpc::geri::geri_service_server::GeriService;
+    use geri::grpc::GeriServiceImpl;
+    use geri::llm::llamacpp::{discover_vision_models, LlamaCppVisionConfig, LlamaCppVisionProvider};
+    use geri::llm::{GeriEngine, LLMError, ProviderFactory};
+    use geri::model::{ModelInfo, ModelRegistry, ModelRegistryTrait, ModelType};
+    use geri::performance::PerformanceTracker;
+    use geri::selection::SelectionOptions;
+    use geri::streaming::{FrameAnalyzer, VisionFrameAnalyzer};
+    use geri::vision::{VisionError, VisionProcessor, VisionProvider, VisionRequest, VisionResponse};
+    use serde_json::json;
+    use tokio::sync::RwLock;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// Minimal GGUF file with a single string metadata entry
+    fn write_gguf(path: &Path, key: &str, value: &str) {
+        let mut bytes = b"GGUF".to_vec();
+        bytes.extend(3u32.to_le_bytes());
+        bytes.extend(0u64.to_le_bytes());
+        bytes.extend(1u64.to_le_bytes());
+        bytes.extend((key.len() as u64).to_le_bytes());
+        bytes.extend(key.as_bytes());
+        bytes.extend(8u32.to_le_bytes());
+        bytes.extend((value.len() as u64).to_le_bytes());
+        bytes.extend(value.as_bytes());
+        std::fs::write(path, bytes).unwrap();
+    }
+
+    #[test]
+    fn test_discovers_models_with_projectors() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write_gguf(&root.join("Qwen2-VL-7B-Instruct-Q4_K_M.gguf"), "general.architecture", "qwen2vl");
+        write_gguf(&root.join("mmproj-Qwen2-VL-7B-Instruct-f16.gguf"), "general.architecture", "clip");
+        write_gguf(&root.join("llama-3-8b.Q4_K_M.gguf"), "general.architecture", "llama");
+        std::fs::create_dir(root.join("llava")).unwrap();
+        write_gguf(&root.join("llava").join("ggml-model-q4_k.gguf"), "general.architecture", "llama");
+        write_gguf(&root.join("llava").join("mmproj-model-f16.gguf"), "general.architecture", "clip");
+        // Projector only recognisable by its GGUF architecture
+        std::fs::create_dir(root.join("moondream")).unwrap();
+        write_gguf(&root.join("moondream").join("moondream2-text.gguf"), "general.architecture", "phi2");
+        write_gguf(&root.join("moondream").join("moondream2-encoder.gguf"), "general.architecture", "clip");
+
+        let models = discover_vision_models(root).unwrap();
+        let ids: Vec<&str> = models.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["Qwen2-VL-7B-Instruct-Q4_K_M", "ggml-model-q4_k", "moondream2-text"]);
+        assert!(models[0].mmproj_path.ends_with("mmproj-Qwen2-VL-7B-Instruct-f16.gguf"));
+        assert!(models[2].mmproj_path.ends_with("moondream2-encoder.gguf"));
+
+        let info = models[0].model_info();
+        assert_eq!(info.model_type, ModelType::Vision);
+        assert!(info.is_local);
+        assert_eq!(info.provider, "llamacpp");
+    }
+
+    fn vision_config(server_url: &str) -> LlamaCppVisionConfig {
+        LlamaCppVisionConfig {
+            server_url: server_url.to_string(),
+            model_path: "models/qwen2-vl.gguf".to_string(),
+            mmproj_path: "models/mmproj-qwen2-vl.gguf".to_string(),
+            max_tokens: 128,
+            timeout_secs: 5,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_llamacpp_vision_provider_sends_image_as_data_url() {
+        let server = MockServer::start().await;
+        let png = [0x89, b'P', b'N', b'G', 0x0d, 0x0a];
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .and(body_partial_json(json!({"model": "qwen2-vl", "max_tokens": 128})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{"message": {"role": "assistant", "content": " A cat on a sofa. "}}],
+                "usage": {"total_tokens": 42}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let provider = LlamaCppVisionProvider::new(vision_config(&server.uri()), "qwen2-vl".to_string()).unwrap();
+        let response = provider
+            .process(VisionRequest { image_data: png.to_vec(), prompt: Some("What animal?".to_string()), privacy_sensitive: true })
+            .await
+            .unwrap();
+        assert_eq!(response.description, "A cat on a sofa.");
+        assert_eq!(response.analysis["tokens_used"], 42);
+        assert_eq!(response.analysis["local"], true);
+
+        let body: serde_json::Value = serde_json::from_slice(&server.received_requests().await.unwrap()[0].body).unwrap();
+        let content = &body["messages"][0]["content"];
+        assert_eq!(content[0]["text"], "What animal?");
+        assert!(content[1]["image_url"]["url"].as_str().unwrap().starts_with("data:image/png;base64,iVBORw"));
+    }
+
+    #[test]
+    fn test_vision_config_requires_projector() {
+        let mut config = vision_config("http://localhost:8080");
+        config.mmproj_path = config.model_path.clone();
+        assert!(LlamaCppVisionProvider::new(config, "m".to_string()).is_err());
+    }
+
+    /// Answers with its own name and records the privacy flag of each request
+    struct NamedVision {
+        name: &'static str,
+        fail: bool,
+        seen_privacy: Mutex<Vec<bool>>,
+    }
+
+    impl NamedVision {
+        fn new(name: &'static str, fail: bool) -> Arc<Self> {
+            Arc::new(Self { name, fail, seen_privacy: Mutex::new(Vec::new()) })
+        }
+    }
+
+    impl VisionProvider for NamedVision {
+        fn model_name(&self) -> &str {
+            self.name
+        }
+
+        fn process(&self, request: VisionRequest) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<VisionResponse, VisionError>> + Send + '_>> {
+            self.seen_privacy.lock().unwrap().push(request.privacy_sensitive);
+            Box::pin(async move {
+                if self.fail {
+                    return Err(VisionError::ProcessingFailed("model crashed".to_string()));
+                }
+                Ok(VisionResponse { description: format!("seen by {}", self.name), analysis: json!({}) })
+            })
+        }
+    }
+
+    fn vision_model(id: &str, is_local: bool, parameter_count: u64) -> ModelInfo {
+        ModelInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            provider: if is_local { "llamacpp" } else { "openai" }.to_string(),
+            model_type: ModelType::Vision,
+            parameter_count: Some(parameter_count),
+            hardware_requirements: None,
+            context_window: None,
+            is_local,
+            cost_per_token_input: Some(0.0),
+            cost_per_token_output: Some(0.0),
+            embedding_dimension: None,
+        }
+    }
+
+    async fn engine(vision: &[(&str, bool, Arc<NamedVision>)]) -> Arc<GeriEngine> {
+        let mut registry = ModelRegistry::new();
+        let factory = Arc::new(ProviderFactory::new());
+        for (id, is_local, provider) in vision {
+            // The cloud model is the bigger one and wins the automatic selection
+            let parameter_count = if *is_local { 7_000_000_000 } else { 175_000_000_000 };
+            registry = registry.register(vision_model(id, *is_local, parameter_count));
+            factory.register_vision(id, provider.clone()).await;
+        }
+        let registry: Arc<dyn ModelRegistryTrait> = Arc::new(RwLock::new(registry));
+        Arc::new(GeriEngine::new(
+            registry,
+            Arc::new(PerformanceTracker::new().unwrap()),
+            factory,
+            Arc::new(BudgetTracker::new(10.0)),
+        ))
+    }
+
+    fn image(privacy_sensitive: bool) -> VisionRequest {
+        VisionRequest { image_data: vec![0xff, 0xd8, 0xff], prompt: None, privacy_sensitive }
+    }
+
+    #[tokio::test]
+    async fn test_engine_keeps_privacy_sensitive_images_local() {
+        let local = NamedVision::new("qwen2-vl", false);
+        let cloud = NamedVision::new("gpt-4o", false);
+        let engine = engine(&[("qwen2-vl", true, local.clone()), ("gpt-4o", false, cloud.clone())]).await;
+
+        let (response, model) = engine.process_vision(image(false), SelectionOptions::default()).await.unwrap();
+        assert_eq!(model.id, "gpt-4o");
+        assert_eq!(response.description, "seen by gpt-4o");
+
+        let prefer_cloud = SelectionOptions { user_preferred_model_id: Some("gpt-4o".to_string()), ..Default::default() };
+        let (response, model) = engine.process_vision(image(true), prefer_cloud).await.unwrap();
+        assert_eq!(model.id, "qwen2-vl");
+        assert_eq!(response.description, "seen by qwen2-vl");
+        assert_eq!(cloud.seen_privacy.lock().unwrap().as_slice(), [false]);
+    }
+
+    #[tokio::test]
+    async fn test_engine_never_fails_over_to_cloud_for_privacy_sensitive_images() {
+        let local = NamedVision::new("llava", true);
+        let cloud = NamedVision::new("gpt-4o", false);
+        let mixed = engine(&[("llava", true, local.clone()), ("gpt-4o", false, cloud.clone())]).await;
+
+        let error = mixed.process_vision(image(true), SelectionOptions::default()).await.unwrap_err();
+        assert!(error.to_string().contains("model crashed"));
+        assert!(cloud.seen_privacy.lock().unwrap().is_empty());
+
+        let cloud_only = engine(&[("gpt-4o", false, cloud)]).await;
+        let error = cloud_only.process_vision(image(true), SelectionOptions::default()).await.unwrap_err();
+        assert!(matches!(error, LLMError::ModelNotAvailable(msg) if msg.contains("privacy-sensitive")));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_frame_analyzer_uses_vision_provider() {
+        let local = NamedVision::new("qwen2-vl", false);
+        let engine = engine(&[("qwen2-vl", true, local.clone())]).await;
+        let analyzer = VisionFrameAnalyzer::new(engine, tokio::runtime::Handle::current()).with_prompt("Who is at the door?");
+        assert_eq!(analyzer.analyze_frame(&[1, 2, 3]).unwrap(), "seen by qwen2-vl");
+        // Frames are privacy-sensitive unless configured otherwise
+        assert_eq!(local.seen_privacy.lock().unwrap().as_slice(), [true]);
+    }
+
+    #[tokio::test]
+    async fn test_grpc_process_vision_routes_through_engine() {
+        let local = NamedVision::new("qwen2-vl", false);
+        let cloud = NamedVision::new("gpt-4o", false);
+        let engine = engine(&[("qwen2-vl", true, local), ("gpt-4o", false, cloud)]).await;
+        let service = GeriServiceImpl::new(
+            Arc::new(ModelRegistry::new()),
+            engine.clone(),
+            Arc::new(VisionProcessor::new("cloud-vision".to_string())),
+        )
+        .with_vision_engine(engine);
+
+        let request = geri::grpc::geri::ProcessVisionRequest {
+            image_data: vec![0xff, 0xd8],
+            prompt: String::new(),
+            model_name: "gpt-4o".to_string(),
+            privacy_sensitive: true,
+        };
+        let response = service.process_vision(tonic::Request::new(request)).await.unwrap().into_inner();
+        assert_eq!(response.model_used, "qwen2-vl");
+        assert_eq!(response.description, "seen by qwen2-vl");
+    }
+}
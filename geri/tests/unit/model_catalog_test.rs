@@ -0,0 +1,264 @@
+//! Tests für Model-Katalog und verifizierte, fortsetzbare Model-Downloads.
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, SystemTime};
+
+    use geri::llm::local_manager::{HardwareProfile, LocalLLMManager};
+    use geri::llm::model_catalog::{CatalogEntry, ModelCatalog};
+    use geri::llm::model_downloader::{DownloadError, DownloadProgress, ModelDownloader};
+    use sha2::{Digest, Sha256};
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const MODEL_BYTES: &[u8] = b"GGUF fake model weights for download tests";
+
+    fn sha256_hex(data: &[u8]) -> String {
+        Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn entry(id: &str, provider_type: &str, model_size: &str, min_ram_mb: u32) -> CatalogEntry {
+        CatalogEntry {
+            id: id.to_string(),
+            provider_type: provider_type.to_string(),
+            model_size: model_size.to_string(),
+            quantization: "Q4_K_M".to_string(),
+            file_name: format!("{}.gguf", id),
+            repo: Some("example/models".to_string()),
+            url: None,
+            sha256: None,
+            size_bytes: None,
+            min_ram_mb,
+        }
+    }
+
+    fn served_entry(server: &MockServer, data: &[u8]) -> CatalogEntry {
+        CatalogEntry {
+            url: Some(format!("{}/model.gguf", server.uri())),
+            repo: None,
+            sha256: Some(sha256_hex(data)),
+            size_bytes: Some(data.len() as u64),
+            ..entry("tiny-q4", "llamacpp", "1b", 100)
+        }
+    }
+
+    fn profile(available_memory_mb: u32) -> HardwareProfile {
+        HardwareProfile {
+            total_memory_mb: available_memory_mb * 2,
+            available_memory_mb,
+            cpu_cores: 8,
+            has_gpu: false,
+            gpu_memory_mb: 0,
+        }
+    }
+
+    #[test]
+    fn test_catalog_loads_yaml_and_rejects_invalid_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("catalog.yaml");
+        std::fs::write(
+            &path,
+            r#"
+models:
+  - id: llama-3-8b-q4
+    provider_type: llamacpp
+    model_size: 8b
+    quantization: Q4_K_M
+    file_name: llama-3-8b.Q4_K_M.gguf
+    repo: QuantFactory/Meta-Llama-3-8B-Instruct-GGUF
+    size_bytes: 4920733888
+    min_ram_mb: 6500
+  - id: bitnet-3b
+    provider_type: bitnet
+    model_size: 3b
+    quantization: 1bit
+    file_name: bitnet-3b.bitnet
+    url: https://example.com/bitnet-3b.bitnet
+    min_ram_mb: 1200
+"#,
+        )
+        .unwrap();
+        let catalog = ModelCatalog::load(&path).unwrap();
+        assert_eq!(catalog.models.len(), 2);
+        assert!(catalog.get("bitnet-3b").unwrap().source().get_url().starts_with("https://example.com"));
+        assert!(catalog.by_file_name("llama-3-8b.Q4_K_M.gguf").unwrap().source().get_url().contains("huggingface.co"));
+
+        let mut bad = entry("bad", "llamacpp", "7b", 100);
+        bad.sha256 = Some("not-a-hash".to_string());
+        assert!(ModelCatalog::new(vec![bad]).is_err());
+        let mut traversal = entry("bad", "llamacpp", "7b", 100);
+        traversal.file_name = "../escape.gguf".to_string();
+        assert!(ModelCatalog::new(vec![traversal]).is_err());
+        assert!(ModelCatalog::new(vec![entry("a", "llamacpp", "7b", 1), entry("a", "bitnet", "3b", 1)]).is_err());
+
+        let example = ModelCatalog::load(concat!(env!("CARGO_MANIFEST_DIR"), "/config/model-catalog.yaml")).unwrap();
+        assert_eq!(example.select(&profile(8000), "llamacpp").unwrap().quantization, "Q4_K_M");
+    }
+
+    #[test]
+    fn test_catalog_selects_largest_fitting_variant() {
+        let catalog = ModelCatalog::new(vec![
+            entry("llama-7b-q4", "llamacpp", "7b", 6000),
+            entry("llama-7b-q8", "llamacpp", "7b", 9000),
+            entry("llama-13b-q4", "llamacpp", "13b", 12000),
+            entry("bitnet-3b", "bitnet", "3b", 1200),
+        ])
+        .unwrap();
+        assert_eq!(catalog.select(&profile(10000), "llamacpp").unwrap().id, "llama-7b-q8");
+        assert_eq!(catalog.select(&profile(20000), "llamacpp").unwrap().id, "llama-13b-q4");
+        // Preferred provider does not fit: fall back to the other one
+        assert_eq!(catalog.select(&profile(3000), "llamacpp").unwrap().id, "bitnet-3b");
+        assert!(catalog.select(&profile(500), "bitnet").is_none());
+        assert_eq!(catalog.variants("llamacpp", "7b").len(), 2);
+    }
+
+    #[test]
+    fn test_local_manager_uses_catalog_variant() {
+        let dir = tempfile::tempdir().unwrap();
+        let catalog = ModelCatalog::new(vec![
+            entry("llama-8b-q4", "llamacpp", "8b", 7000),
+            entry("bitnet-3b", "bitnet", "3b", 1200),
+        ])
+        .unwrap();
+        let manager = LocalLLMManager::with_models_dir(dir.path().to_string_lossy().to_string())
+            .unwrap()
+            .with_catalog(catalog);
+
+        let selection = manager.select_provider(&profile(12000));
+        assert_eq!(selection.provider_type, "llamacpp");
+        assert_eq!(selection.variant.as_ref().unwrap().id, "llama-8b-q4");
+        assert!(manager.generate_model_config(&profile(12000)).model_path.ends_with("llama-8b-q4.gguf"));
+
+        let selection = manager.select_provider(&profile(2000));
+        assert_eq!(selection.provider_type, "bitnet");
+        assert_eq!(selection.model_size, "3b");
+
+        // Nothing fits: size heuristic without variant
+        let selection = manager.select_provider(&profile(800));
+        assert!(selection.variant.is_none());
+        assert_eq!(selection.model_size, "3b");
+    }
+
+    #[tokio::test]
+    async fn test_download_verifies_checksum_and_renames() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/model.gguf"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(MODEL_BYTES))
+            .mount(&server)
+            .await;
+        let dir = tempfile::tempdir().unwrap();
+        let seen: Arc<Mutex<Vec<DownloadProgress>>> = Arc::new(Mutex::new(Vec::new()));
+        let recorder = seen.clone();
+        let downloader = ModelDownloader::new(dir.path().to_string_lossy().to_string())
+            .unwrap()
+            .with_progress_callback(Arc::new(move |progress| recorder.lock().unwrap().push(progress.clone())));
+
+        let path = downloader.download_entry(&served_entry(&server, MODEL_BYTES)).await.unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), MODEL_BYTES);
+        assert!(!dir.path().join("tiny-q4.gguf.part").exists());
+        assert_eq!(seen.lock().unwrap().last().unwrap().percentage, 100.0);
+
+        // Existing file with the expected size is not downloaded again
+        downloader.download_entry(&served_entry(&server, MODEL_BYTES)).await.unwrap();
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_download_resumes_partial_file_with_range_request() {
+        let server = MockServer::start().await;
+        let (head, tail) = MODEL_BYTES.split_at(10);
+        Mock::given(method("GET"))
+            .and(path("/model.gguf"))
+            .and(header("range", "bytes=10-"))
+            .respond_with(ResponseTemplate::new(206).set_body_bytes(tail))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("tiny-q4.gguf.part"), head).unwrap();
+        let downloader = ModelDownloader::new(dir.path().to_string_lossy().to_string()).unwrap();
+
+        let path = downloader.download_entry(&served_entry(&server, MODEL_BYTES)).await.unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), MODEL_BYTES);
+    }
+
+    #[tokio::test]
+    async fn test_download_restarts_when_server_ignores_range() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/model.gguf"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(MODEL_BYTES))
+            .mount(&server)
+            .await;
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("tiny-q4.gguf.part"), b"stale bytes").unwrap();
+        let downloader = ModelDownloader::new(dir.path().to_string_lossy().to_string()).unwrap();
+
+        let path = downloader.download_entry(&served_entry(&server, MODEL_BYTES)).await.unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), MODEL_BYTES);
+    }
+
+    #[tokio::test]
+    async fn test_checksum_mismatch_discards_download() {
+        let server = MockServer::start().await;
+        let tampered = b"GGUF tampered model weights for the tests!";
+        assert_eq!(tampered.len(), MODEL_BYTES.len());
+        Mock::given(method("GET"))
+            .and(path("/model.gguf"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(tampered.as_slice()))
+            .mount(&server)
+            .await;
+        let dir = tempfile::tempdir().unwrap();
+        let downloader = ModelDownloader::new(dir.path().to_string_lossy().to_string()).unwrap();
+
+        let error = downloader.download_entry(&served_entry(&server, MODEL_BYTES)).await.unwrap_err();
+        assert!(matches!(error, DownloadError::ChecksumMismatch { .. }));
+        assert!(!dir.path().join("tiny-q4.gguf").exists());
+        assert!(!dir.path().join("tiny-q4.gguf.part").exists());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_download_of_same_file_is_rejected() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/model.gguf"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(MODEL_BYTES).set_delay(Duration::from_millis(300)))
+            .mount(&server)
+            .await;
+        let dir = tempfile::tempdir().unwrap();
+        let downloader = ModelDownloader::new(dir.path().to_string_lossy().to_string()).unwrap();
+        let entry = served_entry(&server, MODEL_BYTES);
+
+        let (first, second) = tokio::join!(downloader.download_entry(&entry), async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            downloader.download_entry(&entry).await
+        });
+        assert!(first.is_ok());
+        assert!(matches!(second, Err(DownloadError::AlreadyDownloading(_))));
+    }
+
+    #[test]
+    fn test_quota_cleanup_removes_least_recently_used_models() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        for (name, age_secs) in [("old.gguf", 300), ("recent.gguf", 10), ("active.gguf", 600)] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, vec![0u8; 1024 * 1024]).unwrap();
+            let file = std::fs::OpenOptions::new().write(true).open(&path).unwrap();
+            file.set_modified(now - Duration::from_secs(age_secs)).unwrap();
+        }
+        let downloader = ModelDownloader::new(dir.path().to_string_lossy().to_string())
+            .unwrap()
+            .with_quota_mb(3);
+
+        // 1 MB more fits after removing the oldest model that is not in use
+        let removed = downloader.cleanup_unused(&["active.gguf"], 1024 * 1024).unwrap();
+        assert_eq!(removed, vec![dir.path().join("old.gguf")]);
+        assert!(dir.path().join("active.gguf").exists());
+
+        let error = downloader.cleanup_unused(&["active.gguf", "recent.gguf"], 2 * 1024 * 1024).unwrap_err();
+        assert!(matches!(error, DownloadError::QuotaExceeded { .. }));
+    }
+}
@@ -0,0 +1,304 @@
+//! Tests für die OpenAI-kompatible HTTP-API (Feature `openai-api`).
+
+#![cfg(feature = "openai-api")]
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use geri::cost::BudgetTracker;
+    use geri::http::{router, AuthError, AuthenticatedUser, OpenAiApiState, TokenValidator};
+    use geri::llm::{ChatRole, GeriEngine, LLMError, LLMProvider, PromptRequest, PromptResponse, ProviderFactory, ToolCall};
+    use geri::model::{ModelInfo, ModelRegistry, ModelRegistryTrait, ModelType};
+    use geri::performance::PerformanceTracker;
+    use serde_json::{json, Value};
+    use tokio::sync::RwLock;
+
+    /// Antwortet mit der Anzahl der Turns und dem letzten User-Text; `weather` löst einen Tool-Call aus,
+    /// eine Repair-Aufforderung (Structured Output) wird mit gültigem JSON beantwortet.
+    struct EchoProvider;
+
+    #[async_trait]
+    impl LLMProvider for EchoProvider {
+        fn model_name(&self) -> &str { "llama-3-8b" }
+        async fn process_prompt(&self, request: PromptRequest) -> Result<PromptResponse, LLMError> {
+            let conversation = request.conversation();
+            let last = conversation.last().map(|m| m.content.clone()).unwrap_or_default();
+            let tool_calls = if last.contains("weather") {
+                vec![ToolCall::new("get_weather", json!({ "city": "Berlin" }))]
+            } else {
+                Vec::new()
+            };
+            let system = request.system_prompt.unwrap_or_default();
+            let tool_turns = conversation.iter().filter(|m| m.role == ChatRole::Tool).count();
+            let text = if request.response_format.is_some() && last.contains("JSON Schema") {
+                r#"{"status": "ok"}"#.to_string()
+            } else {
+                format!("{}|{}|{}|{}", system, conversation.len(), tool_turns, last)
+            };
+            Ok(PromptResponse {
+                text,
+                tokens_used: 40,
+                tool_calls,
+                structured: None,
+                safety: Vec::new(),
+            })
+        }
+    }
+
+    struct StaticValidator;
+
+    #[async_trait]
+    impl TokenValidator for StaticValidator {
+        async fn validate(&self, token: &str, device_id: Option<&str>) -> Result<AuthenticatedUser, AuthError> {
+            if token != "valid-token" {
+                return Err(AuthError::InvalidToken("unknown token".to_string()));
+            }
+            Ok(AuthenticatedUser {
+                user_id: "user-1".to_string(),
+                device_id: device_id.unwrap_or_default().to_string(),
+                permissions: Vec::new(),
+            })
+        }
+    }
+
+    fn local_model(id: &str) -> ModelInfo {
+        ModelInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            provider: "local".to_string(),
+            model_type: ModelType::Llm,
+            parameter_count: None,
+            hardware_requirements: None,
+            context_window: Some(4096),
+            is_local: true,
+            cost_per_token_input: Some(0.0),
+            cost_per_token_output: Some(0.0),
+            embedding_dimension: None,
+        }
+    }
+
+    /// Startet den Server auf einem freien Port und liefert die Basis-URL.
+    async fn spawn_server(with_auth: bool) -> String {
+        let registry: Arc<dyn ModelRegistryTrait> =
+            Arc::new(RwLock::new(ModelRegistry::new().register(local_model("llama-3-8b"))));
+        let factory = Arc::new(ProviderFactory::new());
+        factory.register("llama-3-8b", Arc::new(EchoProvider)).await;
+        let engine = GeriEngine::new(
+            registry.clone(),
+            Arc::new(PerformanceTracker::new().unwrap()),
+            factory,
+            Arc::new(BudgetTracker::new(10.0)),
+        );
+        let mut state = OpenAiApiState::new(Arc::new(engine), registry);
+        if with_auth {
+            state = state.with_validator(Arc::new(StaticValidator));
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router(state)).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_models_lists_registry() {
+        let base = spawn_server(false).await;
+        let body: Value = reqwest::get(format!("{}/v1/models", base)).await.unwrap().json().await.unwrap();
+        assert_eq!(body["object"], "list");
+        assert_eq!(body["data"][0]["id"], "llama-3-8b");
+        assert_eq!(body["data"][0]["owned_by"], "local");
+
+        let missing = reqwest::get(format!("{}/v1/models/gpt-9", base)).await.unwrap();
+        assert_eq!(missing.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_chat_completion_maps_messages() {
+        let base = spawn_server(false).await;
+        let body: Value = reqwest::Client::new()
+            .post(format!("{}/v1/chat/completions", base))
+            .json(&json!({
+                "model": "llama-3-8b",
+                "messages": [
+                    { "role": "system", "content": "Be brief." },
+                    { "role": "user", "content": "Hi" },
+                    { "role": "assistant", "content": "Hello!" },
+                    { "role": "user", "content": [{ "type": "text", "text": "How are you?" }] }
+                ]
+            }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+
+        assert_eq!(body["object"], "chat.completion");
+        assert_eq!(body["model"], "llama-3-8b");
+        assert_eq!(body["choices"][0]["message"]["content"], "Be brief.|3|0|How are you?");
+        assert_eq!(body["choices"][0]["finish_reason"], "stop");
+        assert_eq!(body["usage"]["total_tokens"], 40);
+        let prompt = body["usage"]["prompt_tokens"].as_u64().unwrap();
+        assert_eq!(prompt + body["usage"]["completion_tokens"].as_u64().unwrap(), 40);
+    }
+
+    #[tokio::test]
+    async fn test_chat_completion_response_format_repairs_answer() {
+        let base = spawn_server(false).await;
+        let response = reqwest::Client::new()
+            .post(format!("{}/v1/chat/completions", base))
+            .json(&json!({
+                "model": "llama-3-8b",
+                "messages": [{ "role": "user", "content": "Status?" }],
+                "response_format": {
+                    "type": "json_schema",
+                    "json_schema": {
+                        "name": "status",
+                        "schema": {
+                            "type": "object",
+                            "properties": { "status": { "type": "string" } },
+                            "required": ["status"]
+                        }
+                    }
+                }
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let body: Value = response.json().await.unwrap();
+        let content = body["choices"][0]["message"]["content"].as_str().unwrap();
+        assert_eq!(serde_json::from_str::<Value>(content).unwrap(), json!({ "status": "ok" }));
+        // Erste Antwort plus eine Reparatur
+        assert_eq!(body["usage"]["total_tokens"], 80);
+    }
+
+    #[tokio::test]
+    async fn test_chat_completion_tool_round_trip() {
+        let base = spawn_server(false).await;
+        let client = reqwest::Client::new();
+        let first: Value = client
+            .post(format!("{}/v1/chat/completions", base))
+            .json(&json!({
+                "messages": [{ "role": "user", "content": "weather in Berlin?" }],
+                "tools": [{ "type": "function", "function": { "name": "get_weather", "parameters": { "type": "object" } } }]
+            }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(first["choices"][0]["finish_reason"], "tool_calls");
+        let call = &first["choices"][0]["message"]["tool_calls"][0];
+        assert_eq!(call["type"], "function");
+        assert_eq!(call["function"]["name"], "get_weather");
+        let arguments: Value = serde_json::from_str(call["function"]["arguments"].as_str().unwrap()).unwrap();
+        assert_eq!(arguments["city"], "Berlin");
+
+        let second: Value = client
+            .post(format!("{}/v1/chat/completions", base))
+            .json(&json!({
+                "model": "auto",
+                "messages": [
+                    { "role": "user", "content": "weather in Berlin?" },
+                    { "role": "assistant", "content": null, "tool_calls": [call] },
+                    { "role": "tool", "tool_call_id": call["id"], "content": "sunny" }
+                ]
+            }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(second["choices"][0]["message"]["content"], "|3|1|sunny");
+    }
+
+    #[tokio::test]
+    async fn test_chat_completion_stream_sends_sse_chunks() {
+        let base = spawn_server(false).await;
+        let body = reqwest::Client::new()
+            .post(format!("{}/v1/chat/completions", base))
+            .json(&json!({
+                "model": "llama-3-8b",
+                "stream": true,
+                "stream_options": { "include_usage": true },
+                "messages": [{ "role": "user", "content": "Hi" }]
+            }))
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+
+        let events: Vec<&str> = body
+            .lines()
+            .filter_map(|line| line.strip_prefix("data: "))
+            .collect();
+        assert_eq!(events.last(), Some(&"[DONE]"));
+        let chunks: Vec<Value> = events[..events.len() - 1]
+            .iter()
+            .map(|e| serde_json::from_str(e).unwrap())
+            .collect();
+        assert_eq!(chunks[0]["object"], "chat.completion.chunk");
+        assert_eq!(chunks[0]["choices"][0]["delta"]["role"], "assistant");
+        let text: String = chunks
+            .iter()
+            .filter_map(|c| c["choices"][0]["delta"]["content"].as_str())
+            .collect();
+        assert_eq!(text, "|1|0|Hi");
+        assert!(chunks.iter().any(|c| c["choices"][0]["finish_reason"] == "stop"));
+        assert_eq!(chunks.last().unwrap()["usage"]["total_tokens"], 40);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_model_and_embeddings_return_openai_errors() {
+        let base = spawn_server(false).await;
+        let client = reqwest::Client::new();
+        let unknown = client
+            .post(format!("{}/v1/chat/completions", base))
+            .json(&json!({ "model": "gpt-9", "messages": [{ "role": "user", "content": "Hi" }] }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(unknown.status(), 404);
+        let body: Value = unknown.json().await.unwrap();
+        assert_eq!(body["error"]["code"], "model_not_found");
+
+        let embeddings = client
+            .post(format!("{}/v1/embeddings", base))
+            .json(&json!({ "model": "llama-3-8b", "input": "Hi" }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(embeddings.status(), 501);
+    }
+
+    #[tokio::test]
+    async fn test_requests_require_heimdall_token() {
+        let base = spawn_server(true).await;
+        let client = reqwest::Client::new();
+        let url = format!("{}/v1/models", base);
+
+        let missing = client.get(&url).send().await.unwrap();
+        assert_eq!(missing.status(), 401);
+        let body: Value = missing.json().await.unwrap();
+        assert_eq!(body["error"]["code"], "invalid_api_key");
+
+        let invalid = client.get(&url).bearer_auth("forged").send().await.unwrap();
+        assert_eq!(invalid.status(), 401);
+
+        let valid = client
+            .get(&url)
+            .bearer_auth("valid-token")
+            .header("X-Device-Id", "laptop")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(valid.status(), 200);
+    }
+}
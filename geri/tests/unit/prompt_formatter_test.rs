@@ -2,6 +2,7 @@
 
 #[cfg(test)]
 mod tests {
+    use geri::llm::ChatMessage;
     use geri::prompt::PromptFormatter;
 
     #[test]
@@ -93,4 +94,21 @@ mod tests {
         let double_injected = inject_xml_protocol(&injected);
         assert_eq!(injected, double_injected);
     }
+
+    #[test]
+    fn format_conversation_renders_turns_in_order() {
+        let formatter = PromptFormatter::default();
+        let messages = vec![
+            ChatMessage::user("Turn on the kitchen light."),
+            ChatMessage::assistant("The kitchen light is on."),
+            ChatMessage::user("And dim it."),
+        ];
+        let full = formatter.format_conversation("You are Odin.", &messages, None);
+        assert!(full.starts_with("You are Odin."));
+        let first = full.find("User: Turn on the kitchen light.").unwrap();
+        let answer = full.find("Assistant: The kitchen light is on.").unwrap();
+        let second = full.find("User: And dim it.").unwrap();
+        assert!(first < answer && answer < second);
+        assert!(full.ends_with("Assistant:"));
+    }
 }
@@ -0,0 +1,165 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use geri::llm::llamacpp::{LlamaCppClient, LlamaCppConfig, LlamaCppLLMProvider};
+use geri::llm::openai::OpenAIClient;
+use geri::llm::anthropic::{AnthropicClient, StreamEvent};
+use geri::llm::{GeriEngine, LLMError, LLMProvider, PromptRequest, PromptResponse, PromptStreamChunk, ProviderFactory};
+use geri::model::{ModelInfo, ModelRegistryTrait, ModelType};
+use geri::performance::PerformanceTracker;
+use geri::selection::SelectionOptions;
+
+struct MockRegistry {
+    models: Vec<ModelInfo>,
+}
+
+#[async_trait]
+impl ModelRegistryTrait for MockRegistry {
+    async fn register(&self, _model: ModelInfo) -> Result<(), sqlx::Error> { Ok(()) }
+    async fn unregister(&self, _id: &str) -> Result<(), sqlx::Error> { Ok(()) }
+    async fn get_by_id(&self, id: &str) -> Result<Option<ModelInfo>, sqlx::Error> {
+        Ok(self.models.iter().find(|m| m.id == id).cloned())
+    }
+    async fn list_all(&self) -> Result<Vec<ModelInfo>, sqlx::Error> { Ok(self.models.clone()) }
+    async fn filter_by_type(&self, _type: ModelType) -> Result<Vec<ModelInfo>, sqlx::Error> { Ok(self.models.clone()) }
+    async fn filter_by_provider(&self, _provider: &str) -> Result<Vec<ModelInfo>, sqlx::Error> { Ok(self.models.clone()) }
+}
+
+struct MockProvider;
+
+#[async_trait]
+impl LLMProvider for MockProvider {
+    fn model_name(&self) -> &str { "mock" }
+    async fn process_prompt(&self, request: PromptRequest) -> Result<PromptResponse, LLMError> {
+        Ok(PromptResponse {
+            text: format!("echo: {}", request.prompt),
+            tokens_used: 7,
+            tool_calls: Vec::new(),
+            structured: None,
+            safety: Vec::new(),
+        })
+    }
+}
+
+fn request(prompt: &str) -> PromptRequest {
+    PromptRequest {
+        prompt: prompt.to_string(),
+        system_prompt: None,
+        context: None,
+        max_tokens: Some(100),
+        ..Default::default()
+    }
+}
+
+async fn collect(mut stream: geri::llm::PromptStream) -> Vec<PromptStreamChunk> {
+    let mut chunks = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        chunks.push(chunk.expect("stream error"));
+    }
+    chunks
+}
+
+#[tokio::test]
+async fn test_default_stream_prompt_emits_single_delta_and_done() {
+    let chunks = collect(MockProvider.stream_prompt(request("hi")).await.unwrap()).await;
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks[0], PromptStreamChunk::Delta("echo: hi".to_string()));
+    assert!(matches!(chunks[1], PromptStreamChunk::Done { tokens_used: 7, .. }));
+}
+
+#[tokio::test]
+async fn test_llamacpp_stream_prompt_reassembles_generated_text() {
+    let config = LlamaCppConfig {
+        model_path: "/path/to/llama-3-8b.gguf".to_string(),
+        n_ctx: 2048,
+        n_threads: 4,
+        n_gpu_layers: 0,
+    };
+    let provider = LlamaCppLLMProvider::new(LlamaCppClient::new(config).unwrap());
+
+    let full = provider.process_prompt(request("Hello there")).await.unwrap();
+    let chunks = collect(provider.stream_prompt(request("Hello there")).await.unwrap()).await;
+
+    let text: String = chunks
+        .iter()
+        .filter_map(|c| match c {
+            PromptStreamChunk::Delta(t) => Some(t.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(text, full.text);
+    assert!(chunks.len() > 2);
+    assert!(matches!(chunks.last(), Some(PromptStreamChunk::Done { .. })));
+}
+
+#[test]
+fn test_openai_parse_stream_event() {
+    let chunk = OpenAIClient::parse_stream_event(
+        r#"{"id":"c1","choices":[{"index":0,"delta":{"content":"Hel"},"finish_reason":null}]}"#,
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(chunk.choices[0].delta.content.as_deref(), Some("Hel"));
+    assert!(chunk.usage.is_none());
+
+    let usage = OpenAIClient::parse_stream_event(
+        r#"{"id":"c1","choices":[],"usage":{"prompt_tokens":3,"completion_tokens":5,"total_tokens":8}}"#,
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(usage.usage.unwrap().total_tokens, 8);
+
+    assert!(OpenAIClient::parse_stream_event("[DONE]").unwrap().is_none());
+}
+
+#[test]
+fn test_anthropic_parse_stream_event() {
+    let delta = AnthropicClient::parse_stream_event(
+        r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hi"}}"#,
+    )
+    .unwrap();
+    assert!(matches!(delta, StreamEvent::ContentBlockDelta { ref delta, .. } if delta.text.as_deref() == Some("Hi")));
+
+    let ping = AnthropicClient::parse_stream_event(r#"{"type":"ping"}"#).unwrap();
+    assert!(matches!(ping, StreamEvent::Other));
+
+    let stop = AnthropicClient::parse_stream_event(
+        r#"{"type":"message_delta","delta":{"stop_reason":"end_turn"},"usage":{"output_tokens":12}}"#,
+    )
+    .unwrap();
+    assert!(matches!(stop, StreamEvent::MessageDelta { .. }));
+}
+
+#[tokio::test]
+async fn test_engine_process_stream_tracks_performance_and_budget() {
+    let models = vec![ModelInfo {
+        id: "gpt-4".to_string(),
+        name: "GPT-4".to_string(),
+        provider: "openai".to_string(),
+        model_type: ModelType::Llm,
+        parameter_count: None,
+        hardware_requirements: None,
+        context_window: Some(8192),
+        is_local: false,
+        cost_per_token_input: Some(0.00003),
+        cost_per_token_output: Some(0.00006),
+        embedding_dimension: None,
+    }];
+
+    let registry = Arc::new(MockRegistry { models });
+    let performance_tracker = Arc::new(PerformanceTracker::new().unwrap());
+    let factory = Arc::new(ProviderFactory::new());
+    let budget_tracker = Arc::new(geri::cost::BudgetTracker::new(10.0));
+    factory.register("gpt-4", Arc::new(MockProvider)).await;
+
+    let engine = GeriEngine::new(registry, performance_tracker.clone(), factory, budget_tracker.clone());
+    let stream = engine.process_stream(request("Hello"), SelectionOptions::default()).await.unwrap();
+    let chunks = collect(stream).await;
+
+    assert_eq!(chunks[0], PromptStreamChunk::Delta("echo: Hello".to_string()));
+    let metrics = performance_tracker.get_metrics("openai", "gpt-4").await.unwrap();
+    assert_eq!(metrics.success_rate(), 1.0);
+    let (usage, _, _) = budget_tracker.get_usage_info().await;
+    assert!(usage > 0.0);
+}
@@ -0,0 +1,242 @@
+//! Tests für den Admission-Scheduler (Weighted-Fair-Queuing, Slots, Abbruch) und ScheduledProvider.
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use geri::grpc::geri::geri_service_server::GeriService;
+    use geri::cost::BudgetTracker;
+    use geri::grpc::GeriServiceImpl;
+    use geri::llm::{GeriEngine, LLMError, LLMProvider, PromptRequest, PromptResponse, ProviderFactory, ScheduledProvider};
+    use geri::model::{ModelInfo, ModelRegistry, ModelRegistryTrait, ModelType};
+    use geri::performance::PerformanceTracker;
+    use geri::queue::{QueueFullError, RequestPriority, RequestScheduler, SchedulerConfig};
+
+    const MODEL: &str = "llama-3-8b";
+
+    fn scheduler(slots: usize) -> RequestScheduler {
+        RequestScheduler::new(SchedulerConfig { max_concurrent_per_model: slots, ..Default::default() })
+    }
+
+    async fn wait_for_queue(scheduler: &RequestScheduler, waiting: usize) {
+        for _ in 0..200 {
+            if scheduler.stats(MODEL).map(|s| s.waiting) == Some(waiting) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        panic!("queue never reached {} waiting requests", waiting);
+    }
+
+    /// Queues `requests` behind a held slot and returns the order in which they were admitted
+    async fn admission_order(scheduler: &RequestScheduler, requests: &[(&'static str, RequestPriority, Option<&'static str>)]) -> Vec<&'static str> {
+        let holder = scheduler.acquire(MODEL, RequestPriority::Chat, None).await.unwrap();
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut tasks = Vec::new();
+        for (index, (name, priority, user)) in requests.iter().copied().enumerate() {
+            let (queued, order) = (scheduler.clone(), order.clone());
+            tasks.push(tokio::spawn(async move {
+                let _permit = queued.acquire(MODEL, priority, user).await.unwrap();
+                order.lock().unwrap().push(name);
+            }));
+            wait_for_queue(scheduler, index + 1).await;
+        }
+        drop(holder);
+        for task in tasks {
+            task.await.unwrap();
+        }
+        let order = order.lock().unwrap().clone();
+        order
+    }
+
+    #[test]
+    fn test_priority_from_metadata_values() {
+        assert_eq!(RequestPriority::parse("voice"), Some(RequestPriority::Interactive));
+        assert_eq!(RequestPriority::parse(" Background "), Some(RequestPriority::Background));
+        assert_eq!(RequestPriority::parse("indexing"), Some(RequestPriority::Background));
+        assert_eq!(RequestPriority::parse("urgent"), None);
+        assert_eq!(RequestPriority::default(), RequestPriority::Chat);
+        assert!(RequestPriority::Interactive.weight() > RequestPriority::Chat.weight());
+    }
+
+    #[tokio::test]
+    async fn test_voice_request_overtakes_background_work() {
+        let scheduler = scheduler(1);
+        let order = admission_order(
+            &scheduler,
+            &[
+                ("summary-1", RequestPriority::Background, Some("alice")),
+                ("summary-2", RequestPriority::Background, Some("alice")),
+                ("voice", RequestPriority::Interactive, Some("alice")),
+            ],
+        )
+        .await;
+        assert_eq!(order, ["voice", "summary-1", "summary-2"]);
+
+        let stats = scheduler.stats(MODEL).unwrap();
+        assert_eq!((stats.in_flight, stats.waiting, stats.slots), (0, 0, 1));
+        assert_eq!(stats.queue_time.request_count(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_fair_share_across_users() {
+        let scheduler = scheduler(1);
+        let order = admission_order(
+            &scheduler,
+            &[
+                ("alice-1", RequestPriority::Chat, Some("alice")),
+                ("alice-2", RequestPriority::Chat, Some("alice")),
+                ("alice-3", RequestPriority::Chat, Some("alice")),
+                ("bob-1", RequestPriority::Chat, Some("bob")),
+            ],
+        )
+        .await;
+        assert_eq!(order, ["alice-1", "bob-1", "alice-2", "alice-3"]);
+    }
+
+    #[tokio::test]
+    async fn test_slots_per_model_and_queue_limit() {
+        let mut config = SchedulerConfig { max_queue_len: 1, ..Default::default() };
+        config.model_concurrency.insert(MODEL.to_string(), 2);
+        let scheduler = RequestScheduler::new(config);
+
+        let first = scheduler.acquire(MODEL, RequestPriority::Chat, None).await.unwrap();
+        let _second = scheduler.acquire(MODEL, RequestPriority::Chat, None).await.unwrap();
+        assert_eq!(first.waited(), Duration::ZERO);
+        // Other models have their own slots
+        let _other = scheduler.acquire("phi-3", RequestPriority::Chat, None).await.unwrap();
+
+        let queued = tokio::spawn({
+            let scheduler = scheduler.clone();
+            async move { scheduler.acquire(MODEL, RequestPriority::Chat, None).await.map(|permit| permit.waited()) }
+        });
+        wait_for_queue(&scheduler, 1).await;
+        assert!(matches!(scheduler.acquire(MODEL, RequestPriority::Interactive, None).await, Err(QueueFullError)));
+
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        drop(first);
+        assert!(queued.await.unwrap().unwrap() >= Duration::from_millis(5));
+        assert_eq!(scheduler.stats(MODEL).unwrap().in_flight, 1);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_request_leaves_queue() {
+        let scheduler = scheduler(1);
+        let holder = scheduler.acquire(MODEL, RequestPriority::Chat, None).await.unwrap();
+        let cancelled = tokio::spawn({
+            let scheduler = scheduler.clone();
+            async move {
+                let _permit = scheduler.acquire(MODEL, RequestPriority::Chat, Some("alice")).await;
+            }
+        });
+        wait_for_queue(&scheduler, 1).await;
+        cancelled.abort();
+        wait_for_queue(&scheduler, 0).await;
+
+        drop(holder);
+        let stats = scheduler.stats(MODEL).unwrap();
+        assert_eq!((stats.in_flight, stats.waiting), (0, 0));
+        assert!(scheduler.acquire(MODEL, RequestPriority::Chat, None).await.is_ok());
+    }
+
+    /// Slow local model that records concurrency and the priorities it saw
+    struct SlowProvider {
+        running: AtomicUsize,
+        max_running: AtomicUsize,
+        priorities: Mutex<Vec<RequestPriority>>,
+    }
+
+    impl SlowProvider {
+        fn new() -> Arc<Self> {
+            Arc::new(Self { running: AtomicUsize::new(0), max_running: AtomicUsize::new(0), priorities: Mutex::new(Vec::new()) })
+        }
+    }
+
+    #[async_trait]
+    impl LLMProvider for SlowProvider {
+        fn model_name(&self) -> &str {
+            MODEL
+        }
+
+        async fn process_prompt(&self, request: PromptRequest) -> Result<PromptResponse, LLMError> {
+            self.priorities.lock().unwrap().push(request.priority);
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_running.fetch_max(running, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(PromptResponse { text: "ok".to_string(), tokens_used: 1, tool_calls: Vec::new(), structured: None, safety: Vec::new() })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scheduled_provider_serializes_and_aborts() {
+        let inner = SlowProvider::new();
+        let provider = Arc::new(ScheduledProvider::new(inner.clone(), scheduler(1)));
+
+        let calls: Vec<_> = (0..3)
+            .map(|_| {
+                let provider = provider.clone();
+                tokio::spawn(async move { provider.process_prompt(PromptRequest::default()).await })
+            })
+            .collect();
+        for call in calls {
+            assert!(call.await.unwrap().is_ok());
+        }
+        assert_eq!(inner.max_running.load(Ordering::SeqCst), 1);
+
+        // Dropping a running call (client disconnect) frees the slot at once
+        let aborted = tokio::spawn({
+            let provider = provider.clone();
+            async move { provider.process_prompt(PromptRequest::default()).await }
+        });
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        assert_eq!(provider.scheduler().stats(MODEL).unwrap().in_flight, 1);
+        aborted.abort();
+        let _ = aborted.await;
+        assert_eq!(provider.scheduler().stats(MODEL).unwrap().in_flight, 0);
+
+        // A stream holds its slot until it is dropped
+        let stream = provider.stream_prompt(PromptRequest::default()).await.unwrap();
+        assert_eq!(provider.scheduler().stats(MODEL).unwrap().in_flight, 1);
+        drop(stream);
+        assert_eq!(provider.scheduler().stats(MODEL).unwrap().in_flight, 0);
+    }
+
+    #[tokio::test]
+    async fn test_grpc_priority_from_request_metadata() {
+        let inner = SlowProvider::new();
+        let model = ModelInfo {
+            id: MODEL.to_string(),
+            name: MODEL.to_string(),
+            provider: "llamacpp".to_string(),
+            model_type: ModelType::Llm,
+            parameter_count: None,
+            hardware_requirements: None,
+            context_window: Some(4096),
+            is_local: true,
+            cost_per_token_input: Some(0.0),
+            cost_per_token_output: Some(0.0),
+            embedding_dimension: None,
+        };
+        let registry: Arc<dyn ModelRegistryTrait> = Arc::new(tokio::sync::RwLock::new(ModelRegistry::new().register(model)));
+        let factory = Arc::new(ProviderFactory::new());
+        factory.register(MODEL, Arc::new(ScheduledProvider::new(inner.clone(), scheduler(1)))).await;
+        let engine = GeriEngine::new(registry, Arc::new(PerformanceTracker::new().unwrap()), factory, Arc::new(BudgetTracker::new(10.0)));
+        let service = GeriServiceImpl::new(
+            Arc::new(ModelRegistry::new()),
+            Arc::new(engine),
+            Arc::new(geri::vision::VisionProcessor::new("vision".to_string())),
+        );
+
+        let mut request = tonic::Request::new(geri::grpc::geri::ProcessPromptRequest { prompt: "Licht an".to_string(), ..Default::default() });
+        request.metadata_mut().insert(geri::grpc::PRIORITY_METADATA_KEY, "voice".parse().unwrap());
+        service.process_prompt(request).await.unwrap();
+        let request = tonic::Request::new(geri::grpc::geri::ProcessPromptRequest { prompt: "Hallo".to_string(), ..Default::default() });
+        service.process_prompt(request).await.unwrap();
+
+        assert_eq!(*inner.priorities.lock().unwrap(), [RequestPriority::Interactive, RequestPriority::Chat]);
+    }
+}
@@ -0,0 +1,173 @@
+//! Tests für die Secret-Backends (verschlüsselte Datei, Heimdall) und die Key-Rotation ohne Neustart.
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+    use geri::keys::{
+        EncryptedFileKeyBackend, HeimdallKeyBackend, KeyRotationManager, KeyStorageError, LiveKeys, RemoteKeySource,
+        SecureKeyBackend, SecureKeyStorage, CLOUD_PROVIDER_IDS,
+    };
+    use geri::llm::openai::{OpenAIConfig, OpenAIEmbeddingProvider};
+    use geri::llm::{EmbeddingProvider, PromptRequest, ProviderFactory};
+    use serde_json::json;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_encrypted_file_roundtrip_and_wrong_passphrase() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keys.json");
+
+        let mut backend = EncryptedFileKeyBackend::open_with_passphrase(&path, "correct horse").unwrap();
+        backend.store("openai", b"sk-secret-1").unwrap();
+        backend.store("anthropic", b"sk-ant-1").unwrap();
+        backend.delete("anthropic").unwrap();
+
+        // Nothing readable on disk
+        let raw = std::fs::read_to_string(&path).unwrap();
+        assert!(!raw.contains("sk-secret-1"));
+
+        // A second process with the same passphrase sees the keys
+        let reopened = EncryptedFileKeyBackend::open_with_passphrase(&path, "correct horse").unwrap();
+        assert_eq!(reopened.load("openai").unwrap(), Some(b"sk-secret-1".to_vec()));
+        assert_eq!(reopened.load("anthropic").unwrap(), None);
+        assert_eq!(reopened.key_ids().unwrap(), ["openai"]);
+
+        assert!(matches!(
+            EncryptedFileKeyBackend::open_with_passphrase(&path, "wrong"),
+            Err(KeyStorageError::Crypto(_))
+        ));
+        assert!(matches!(EncryptedFileKeyBackend::open_with_key(&path, [7; 32]), Err(KeyStorageError::Crypto(_))));
+    }
+
+    #[test]
+    fn test_encrypted_file_detects_tampering() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("keys.json");
+        let mut backend = EncryptedFileKeyBackend::open_with_key(&path, [1; 32]).unwrap();
+        backend.store("openai", b"sk-openai").unwrap();
+        backend.store("google", b"google-key").unwrap();
+
+        // Swapping two entries is caught by the key ID in the associated data
+        let mut file: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let openai = file["entries"]["openai"].clone();
+        file["entries"]["openai"] = file["entries"]["google"].clone();
+        file["entries"]["google"] = openai;
+        std::fs::write(&path, file.to_string()).unwrap();
+
+        assert!(matches!(backend.load("openai"), Err(KeyStorageError::Crypto(_))));
+        assert!(matches!(EncryptedFileKeyBackend::open_with_key(&path, [2; 32]), Err(KeyStorageError::Crypto(_))));
+    }
+
+    /// Heimdall fake whose keys can be rotated by the test
+    struct FakeHeimdall {
+        keys: Mutex<HashMap<String, String>>,
+    }
+
+    #[async_trait]
+    impl RemoteKeySource for FakeHeimdall {
+        async fn provider_keys(&self, provider_ids: &[String]) -> Result<HashMap<String, String>, KeyStorageError> {
+            let keys = self.keys.lock().unwrap();
+            Ok(provider_ids.iter().filter_map(|id| keys.get(id).map(|key| (id.clone(), key.clone()))).collect())
+        }
+
+        async fn key_store_key(&self) -> Result<[u8; 32], KeyStorageError> {
+            Ok([9; 32])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_heimdall_backend_refresh_reports_rotations() {
+        let heimdall = Arc::new(FakeHeimdall {
+            keys: Mutex::new(HashMap::from([
+                ("openai".to_string(), "sk-1".to_string()),
+                ("google".to_string(), "g-1".to_string()),
+            ])),
+        });
+        let backend = HeimdallKeyBackend::new(heimdall.clone(), CLOUD_PROVIDER_IDS);
+        assert_eq!(backend.refresh().await.unwrap(), ["google", "openai"]);
+
+        let storage = SecureKeyStorage::new(Box::new(backend.clone()));
+        let live_keys = LiveKeys::new();
+        let handle = live_keys.handle("openai");
+        live_keys.sync_from(&storage, CLOUD_PROVIDER_IDS).unwrap();
+        assert_eq!(handle.get(), "sk-1");
+        assert!(live_keys.get("anthropic").is_none());
+
+        heimdall.keys.lock().unwrap().insert("openai".to_string(), "sk-2".to_string());
+        assert_eq!(backend.refresh().await.unwrap(), ["openai"]);
+        assert!(backend.refresh().await.unwrap().is_empty());
+        assert_eq!(live_keys.sync_from(&storage, CLOUD_PROVIDER_IDS).unwrap(), ["openai"]);
+        assert_eq!(handle.get(), "sk-2");
+    }
+
+    #[tokio::test]
+    async fn test_rotation_reaches_running_client_without_restart() {
+        let server = MockServer::start().await;
+        for key in ["sk-old", "sk-new"] {
+            Mock::given(method("POST"))
+                .and(path("/embeddings"))
+                .and(header("Authorization", format!("Bearer {}", key).as_str()))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "object": "list",
+                    "data": [{ "object": "embedding", "index": 0, "embedding": [0.1] }],
+                    "model": "text-embedding-3-small",
+                    "usage": { "prompt_tokens": 1, "total_tokens": 1 }
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut storage = SecureKeyStorage::new(Box::new(EncryptedFileKeyBackend::open_with_key(dir.path().join("keys.json"), [3; 32]).unwrap()));
+        storage.store_key("openai", "sk-old").unwrap();
+        let live_keys = LiveKeys::new();
+        live_keys.sync_from(&storage, CLOUD_PROVIDER_IDS).unwrap();
+
+        let config = OpenAIConfig { api_key: "sk-from-env".to_string(), base_url: server.uri(), timeout_secs: 5 };
+        let provider = OpenAIEmbeddingProvider::new(config, "text-embedding-3-small".to_string()).with_live_key(live_keys.handle("openai"));
+        provider.embed(&["a".to_string()]).await.unwrap();
+
+        KeyRotationManager.rotate_live(&mut storage, &live_keys, "openai", "sk-new").unwrap();
+        provider.embed(&["b".to_string()]).await.unwrap();
+        assert_eq!(storage.load_key("openai").unwrap().as_deref(), Some("sk-new"));
+    }
+
+    #[tokio::test]
+    async fn test_rotation_reaches_chat_client_from_factory() {
+        let server = MockServer::start().await;
+        for key in ["sk-old", "sk-new"] {
+            Mock::given(method("POST"))
+                .and(path("/chat/completions"))
+                .and(header("Authorization", format!("Bearer {}", key).as_str()))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "id": "chatcmpl-1",
+                    "choices": [{ "message": { "role": "assistant", "content": "ok" }, "finish_reason": "stop" }],
+                    "usage": { "prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2 }
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut storage = SecureKeyStorage::new(Box::new(EncryptedFileKeyBackend::open_with_key(dir.path().join("keys.json"), [4; 32]).unwrap()));
+        storage.store_key("openai", "sk-old").unwrap();
+        let live_keys = LiveKeys::new();
+        live_keys.sync_from(&storage, CLOUD_PROVIDER_IDS).unwrap();
+
+        let factory = ProviderFactory::new().with_live_keys(live_keys.clone());
+        let config = OpenAIConfig { api_key: "sk-from-env".to_string(), base_url: server.uri(), timeout_secs: 5 };
+        factory.add_openai("gpt-4o-mini", config).await;
+        let provider = factory.get("gpt-4o-mini").await.unwrap();
+        let request = || PromptRequest { prompt: "hi".to_string(), ..Default::default() };
+        provider.process_prompt(request()).await.unwrap();
+
+        KeyRotationManager.rotate_live(&mut storage, &live_keys, "openai", "sk-new").unwrap();
+        provider.process_prompt(request()).await.unwrap();
+    }
+}
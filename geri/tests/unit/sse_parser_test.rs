@@ -0,0 +1,52 @@
+//! Tests für SSE-Parser (Phase 12.1.2).
+
+#[cfg(test)]
+mod tests {
+    use geri::streaming::SseParser;
+
+    #[test]
+    fn feed_yields_complete_events() {
+        let mut parser = SseParser::new();
+        let events = parser.feed(b"data: {\"a\":1}\n\ndata: {\"b\":2}\n\n");
+        assert_eq!(events, vec!["{\"a\":1}".to_string(), "{\"b\":2}".to_string()]);
+    }
+
+    #[test]
+    fn feed_handles_events_split_across_chunks() {
+        let mut parser = SseParser::new();
+        assert!(parser.feed(b"data: {\"te").is_empty());
+        assert!(parser.feed(b"xt\":\"hi\"}\r\n").is_empty());
+        let events = parser.feed(b"\r\n");
+        assert_eq!(events, vec!["{\"text\":\"hi\"}".to_string()]);
+    }
+
+    #[test]
+    fn feed_keeps_multibyte_characters_split_across_chunks() {
+        let mut parser = SseParser::new();
+        let umlaut = "ä".as_bytes();
+        assert!(parser.feed(&[b"data: ".as_slice(), &umlaut[..1]].concat()).is_empty());
+        let events = parser.feed(&[&umlaut[1..], b"\n\n".as_slice()].concat());
+        assert_eq!(events, vec!["ä".to_string()]);
+    }
+
+    #[test]
+    fn event_and_comment_lines_are_ignored() {
+        let mut parser = SseParser::new();
+        let events = parser.feed(b": keep-alive\nevent: content_block_delta\ndata: x\n\n");
+        assert_eq!(events, vec!["x".to_string()]);
+    }
+
+    #[test]
+    fn multi_line_data_is_joined() {
+        let mut parser = SseParser::new();
+        let events = parser.feed(b"data: line1\ndata: line2\n\n");
+        assert_eq!(events, vec!["line1\nline2".to_string()]);
+    }
+
+    #[test]
+    fn finish_flushes_trailing_event_without_blank_line() {
+        let mut parser = SseParser::new();
+        assert!(parser.feed(b"data: [DONE]").is_empty());
+        assert_eq!(parser.finish(), vec!["[DONE]".to_string()]);
+    }
+}
@@ -0,0 +1,272 @@
+//! Tests für Structured Output (JSON-Schema-Validierung, Provider-Mapping, GBNF, Repair-Loop).
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use geri::llm::google::{GoogleClient, GoogleConfig};
+    use geri::llm::openai::{OpenAIClient, OpenAIConfig};
+    use geri::llm::structured::enforce_response_format;
+    use geri::llm::{LLMError, LLMProvider, PromptRequest, PromptResponse, ResponseFormat, ToolCall};
+    use serde_json::json;
+
+    fn plan_format() -> ResponseFormat {
+        ResponseFormat::new(
+            "action_plan",
+            json!({
+                "type": "object",
+                "properties": {
+                    "intent": { "type": "string", "enum": ["search", "reply"] },
+                    "steps": { "type": "array", "items": { "$ref": "#/$defs/step" }, "minItems": 1 },
+                    "confidence": { "type": ["number", "null"], "minimum": 0, "maximum": 1 }
+                },
+                "required": ["intent", "steps"],
+                "additionalProperties": false,
+                "$defs": {
+                    "step": {
+                        "type": "object",
+                        "properties": { "action": { "type": "string", "minLength": 1 } },
+                        "required": ["action"]
+                    }
+                }
+            }),
+        )
+    }
+
+    fn response(text: &str, tokens_used: u32) -> PromptResponse {
+        PromptResponse {
+            text: text.to_string(),
+            tokens_used,
+            tool_calls: vec![],
+            structured: None,
+            safety: Vec::new(),
+        }
+    }
+
+    /// Liefert die vorgegebenen Antworten der Reihe nach und merkt sich die Requests.
+    struct ScriptedProvider {
+        answers: Mutex<Vec<PromptResponse>>,
+        requests: Mutex<Vec<PromptRequest>>,
+    }
+
+    impl ScriptedProvider {
+        fn new(answers: Vec<PromptResponse>) -> Self {
+            Self {
+                answers: Mutex::new(answers),
+                requests: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl LLMProvider for ScriptedProvider {
+        fn model_name(&self) -> &str { "scripted" }
+        async fn process_prompt(&self, request: PromptRequest) -> Result<PromptResponse, LLMError> {
+            self.requests.lock().unwrap().push(request);
+            let mut answers = self.answers.lock().unwrap();
+            if answers.is_empty() {
+                return Err(LLMError::ProcessingFailed("no scripted answer left".to_string()));
+            }
+            Ok(answers.remove(0))
+        }
+    }
+
+    fn structured_request() -> PromptRequest {
+        PromptRequest {
+            prompt: "Plan the search".to_string(),
+            response_format: Some(plan_format()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_and_reports_paths() {
+        let format = plan_format();
+        assert!(format
+            .validate(&json!({ "intent": "search", "steps": [{ "action": "query" }], "confidence": null }))
+            .is_ok());
+
+        let errors = format
+            .validate(&json!({ "intent": "dance", "steps": [{ "action": "" }, {}], "extra": 1, "confidence": 2 }))
+            .unwrap_err();
+        let joined = errors.join("\n");
+        assert!(joined.contains("$.intent"), "{}", joined);
+        assert!(joined.contains("$.steps[0].action"), "{}", joined);
+        assert!(joined.contains("$.steps[1]"), "{}", joined);
+        assert!(joined.contains("extra"), "{}", joined);
+        assert!(joined.contains("$.confidence"), "{}", joined);
+
+        let errors = format.validate(&json!({ "intent": "reply", "steps": [] })).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(format.validate(&json!(["not", "an", "object"])).is_err());
+    }
+
+    #[test]
+    fn test_validate_any_of_and_const() {
+        let format = ResponseFormat::new(
+            "decision",
+            json!({ "anyOf": [{ "const": "skip" }, { "type": "integer", "exclusiveMinimum": 0 }] }),
+        );
+        assert!(format.validate(&json!("skip")).is_ok());
+        assert!(format.validate(&json!(3)).is_ok());
+        assert!(format.validate(&json!(0)).is_err());
+        assert!(format.validate(&json!(1.5)).is_err());
+        assert!(format.validate(&json!("run")).is_err());
+    }
+
+    #[test]
+    fn test_parse_tolerates_code_fences_and_surrounding_text() {
+        let format = plan_format();
+        let fenced = "```json\n{\"intent\": \"reply\", \"steps\": [{\"action\": \"answer\"}]}\n```";
+        assert_eq!(format.parse(fenced).unwrap()["intent"], "reply");
+        let chatty = "Here is the plan: {\"intent\": \"search\", \"steps\": [{\"action\": \"q\"}]} Done.";
+        assert_eq!(format.parse(chatty).unwrap()["steps"][0]["action"], "q");
+
+        let errors = format.parse("I cannot do that.").unwrap_err();
+        assert!(errors[0].contains("not valid JSON"));
+    }
+
+    #[test]
+    fn test_to_gemini_schema_inlines_refs_and_nullable() {
+        let schema = plan_format().to_gemini_schema();
+        assert_eq!(schema["type"], "OBJECT");
+        assert_eq!(schema["required"], json!(["intent", "steps"]));
+        assert_eq!(schema["properties"]["intent"]["enum"], json!(["search", "reply"]));
+        assert_eq!(schema["properties"]["steps"]["type"], "ARRAY");
+        assert_eq!(schema["properties"]["steps"]["items"]["type"], "OBJECT");
+        assert_eq!(schema["properties"]["steps"]["items"]["properties"]["action"]["type"], "STRING");
+        assert_eq!(schema["properties"]["confidence"]["type"], "NUMBER");
+        assert_eq!(schema["properties"]["confidence"]["nullable"], true);
+        assert!(schema.get("additionalProperties").is_none());
+        assert!(schema.get("$defs").is_none());
+    }
+
+    #[test]
+    fn test_to_gbnf_generates_rules() {
+        let grammar = plan_format().to_gbnf();
+        assert!(grammar.starts_with("root ::= "));
+        assert!(grammar.contains(r#"\"intent\""#), "{}", grammar);
+        assert!(grammar.contains(r#"\"search\""#), "{}", grammar);
+        assert!(grammar.contains(r#"\"steps\""#), "{}", grammar);
+        assert!(grammar.contains("string ::="));
+        assert!(grammar.contains("number ::="));
+        assert!(grammar.contains("ws ::="));
+        // Jede Regel ist genau einmal definiert
+        let mut names: Vec<&str> = grammar.lines().filter_map(|line| line.split(" ::= ").next()).collect();
+        let count = names.len();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), count, "{}", grammar);
+    }
+
+    #[test]
+    fn test_openai_apply_response_format() {
+        let client = OpenAIClient::new(OpenAIConfig {
+            api_key: "sk-test123".to_string(),
+            base_url: "https://api.openai.com/v1".to_string(),
+            timeout_secs: 30,
+        });
+        let mut request = client.build_chat_request("gpt-4o", "Plan", None, None, None);
+        client.apply_response_format(&mut request, Some(&plan_format().with_strict(true)));
+
+        let body = serde_json::to_value(&request).unwrap();
+        assert_eq!(body["response_format"]["type"], "json_schema");
+        assert_eq!(body["response_format"]["json_schema"]["name"], "action_plan");
+        assert_eq!(body["response_format"]["json_schema"]["strict"], true);
+        assert_eq!(body["response_format"]["json_schema"]["schema"]["required"], json!(["intent", "steps"]));
+
+        let mut plain = client.build_chat_request("gpt-4o", "Plan", None, None, None);
+        client.apply_response_format(&mut plain, None);
+        assert!(serde_json::to_value(&plain).unwrap().get("response_format").is_none());
+    }
+
+    #[test]
+    fn test_google_apply_response_format() {
+        let client = GoogleClient::new(GoogleConfig {
+            api_key: "test-key".to_string(),
+            base_url: "https://generativelanguage.googleapis.com/v1beta".to_string(),
+        });
+        let mut request = client.build_generate_content_request("gemini-1.5-pro", "Plan", None, Some(256));
+        client.apply_response_format(&mut request, Some(&plan_format()));
+
+        let body = serde_json::to_value(&request).unwrap();
+        assert_eq!(body["generation_config"]["responseMimeType"], "application/json");
+        assert_eq!(body["generation_config"]["responseSchema"]["type"], "OBJECT");
+        assert_eq!(body["generation_config"]["max_output_tokens"], 256);
+    }
+
+    #[tokio::test]
+    async fn test_enforce_accepts_valid_answer() {
+        let provider = ScriptedProvider::new(vec![]);
+        let answer = response(r#"{"intent": "search", "steps": [{"action": "query"}]}"#, 10);
+        let result = enforce_response_format(&provider, &structured_request(), answer, 2).await.unwrap();
+        assert_eq!(result.structured.unwrap()["intent"], "search");
+        assert!(provider.requests.lock().unwrap().is_empty());
+
+        // Ohne response_format bleibt die Antwort unverändert
+        let plain = PromptRequest { prompt: "Hi".to_string(), ..Default::default() };
+        let result = enforce_response_format(&provider, &plain, response("Hallo!", 3), 2).await.unwrap();
+        assert_eq!(result.text, "Hallo!");
+        assert!(result.structured.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_enforce_repairs_invalid_answer() {
+        let provider = ScriptedProvider::new(vec![
+            response(r#"{"intent": "search"}"#, 7),
+            response(r#"{"intent": "search", "steps": [{"action": "query"}]}"#, 5),
+        ]);
+        let result = enforce_response_format(&provider, &structured_request(), response("Sure, let me plan.", 10), 2)
+            .await
+            .unwrap();
+        assert_eq!(result.structured.unwrap()["steps"][0]["action"], "query");
+        assert_eq!(result.tokens_used, 22);
+
+        let requests = provider.requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        // Erste Reparatur: Ursprungsfrage, fehlerhafte Antwort, Fehlerliste
+        let turns = &requests[0].messages;
+        assert_eq!(turns.len(), 3);
+        assert_eq!(turns[0].content, "Plan the search");
+        assert_eq!(turns[1].content, "Sure, let me plan.");
+        assert!(turns[2].content.contains("not valid JSON"));
+        assert!(requests[0].prompt.is_empty());
+        assert!(requests[0].response_format.is_some());
+        // Zweite Reparatur setzt das Gespräch fort
+        assert_eq!(requests[1].messages.len(), 5);
+        assert!(requests[1].messages[4].content.contains("missing required property 'steps'"));
+    }
+
+    #[tokio::test]
+    async fn test_enforce_gives_up_after_max_attempts() {
+        let provider = ScriptedProvider::new(vec![response("still prose", 1), response("more prose", 1)]);
+        let result = enforce_response_format(&provider, &structured_request(), response("prose", 1), 2).await;
+        match result {
+            Err(LLMError::ProcessingFailed(message)) => {
+                assert!(message.contains("action_plan"));
+                assert!(message.contains("2 repair attempts"));
+            }
+            other => panic!("expected ProcessingFailed, got {:?}", other),
+        }
+        assert_eq!(provider.requests.lock().unwrap().len(), 2);
+
+        let provider = ScriptedProvider::new(vec![]);
+        assert!(enforce_response_format(&provider, &structured_request(), response("prose", 1), 0).await.is_err());
+        assert!(provider.requests.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_enforce_passes_tool_calls_through() {
+        let provider = ScriptedProvider::new(vec![]);
+        let mut answer = response("", 4);
+        answer.tool_calls = vec![ToolCall {
+            id: "call_1".to_string(),
+            name: "search".to_string(),
+            arguments: json!({ "query": "rust" }),
+        }];
+        let result = enforce_response_format(&provider, &structured_request(), answer, 2).await.unwrap();
+        assert_eq!(result.tool_calls.len(), 1);
+        assert!(result.structured.is_none());
+    }
+}
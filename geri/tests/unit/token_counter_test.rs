@@ -2,7 +2,30 @@
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
+    use geri::model::{ModelInfo, ModelType};
     use geri::prompt::TokenCounter;
+    use runar::{HuggingFaceTokenizer, TokenizerRegistry};
+
+    /// BPE-Vokabular, in dem "hello" ein einzelnes Token ist.
+    const BPE_TOKENIZER: &str = r#"{
+        "version": "1.0", "truncation": null, "padding": null, "added_tokens": [],
+        "normalizer": null, "pre_tokenizer": { "type": "Whitespace" }, "post_processor": null, "decoder": null,
+        "model": {
+            "type": "BPE", "dropout": null, "unk_token": "[UNK]", "continuing_subword_prefix": null,
+            "end_of_word_suffix": null, "fuse_unk": false, "byte_fallback": false,
+            "vocab": { "[UNK]": 0, "h": 1, "e": 2, "l": 3, "o": 4, "he": 5, "ll": 6, "hell": 7, "hello": 8 },
+            "merges": ["h e", "l l", "he ll", "hell o"]
+        }
+    }"#;
+
+    fn counter_with_llama_tokenizer() -> TokenCounter {
+        let mut registry = TokenizerRegistry::new();
+        let tokenizer = HuggingFaceTokenizer::from_bytes("llama3-8b", BPE_TOKENIZER).unwrap();
+        registry.register("llama3-8b", Arc::new(tokenizer));
+        TokenCounter::with_tokenizers(Arc::new(registry))
+    }
 
     #[test]
     fn count_empty_returns_zero() {
@@ -52,4 +75,35 @@ mod tests {
         let counter = TokenCounter::default();
         assert_eq!(counter.count_for_model("", "gpt-4"), 0);
     }
+
+    #[test]
+    fn count_for_model_uses_registered_tokenizer() {
+        let counter = counter_with_llama_tokenizer();
+        let text = "hello hello hello hello";
+        assert!(counter.has_tokenizer("llama3-8b-instruct.Q4_K_M"));
+        assert_eq!(counter.count_for_model(text, "llama3-8b-instruct.Q4_K_M"), 4);
+        // Ohne Vokabular: Heuristik
+        assert!(!counter.has_tokenizer("gpt-4"));
+        assert_eq!(counter.count_for_model(text, "gpt-4"), counter.count(text));
+        assert_eq!(counter.count_for_model("", "llama3-8b"), 0);
+    }
+
+    #[test]
+    fn count_for_model_info_selects_by_id_then_name() {
+        let counter = counter_with_llama_tokenizer();
+        let model = ModelInfo {
+            id: "local-1".to_string(),
+            name: "llama3-8b".to_string(),
+            provider: "llamacpp".to_string(),
+            model_type: ModelType::Llm,
+            parameter_count: None,
+            hardware_requirements: None,
+            context_window: Some(8192),
+            is_local: true,
+            cost_per_token_input: None,
+            cost_per_token_output: None,
+            embedding_dimension: None,
+        };
+        assert_eq!(counter.count_for_model_info("hello hello", &model), 2);
+    }
 }
@@ -0,0 +1,162 @@
+use async_trait::async_trait;
+use futures::StreamExt;
+use geri::llm::anthropic::{AnthropicClient, AnthropicConfig};
+use geri::llm::google::{GoogleClient, GoogleConfig};
+use geri::llm::openai::{OpenAIClient, OpenAIConfig};
+use geri::llm::tools::{parse_tool_calls, render_tool_instructions};
+use geri::llm::{LLMError, LLMProvider, PromptRequest, PromptResponse, PromptStreamChunk, ToolCall, ToolDefinition, ToolResult};
+use serde_json::json;
+
+fn weather_tool() -> ToolDefinition {
+    ToolDefinition {
+        name: "get_weather".to_string(),
+        description: "Current weather for a city".to_string(),
+        parameters: json!({
+            "type": "object",
+            "properties": { "city": { "type": "string" } },
+            "required": ["city"]
+        }),
+    }
+}
+
+fn previous_round() -> (Vec<ToolCall>, Vec<ToolResult>) {
+    let call = ToolCall {
+        id: "call_1".to_string(),
+        name: "get_weather".to_string(),
+        arguments: json!({ "city": "Berlin" }),
+    };
+    let result = ToolResult {
+        tool_call_id: "call_1".to_string(),
+        name: "get_weather".to_string(),
+        content: "12°C, cloudy".to_string(),
+        is_error: false,
+    };
+    (vec![call], vec![result])
+}
+
+#[test]
+fn test_parse_tool_calls_json_prompted() {
+    let tools = vec![weather_tool()];
+
+    let calls = parse_tool_calls(
+        r#"{"tool_calls": [{"name": "get_weather", "arguments": {"city": "Berlin"}}]}"#,
+        &tools,
+    )
+    .expect("tool call expected");
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].name, "get_weather");
+    assert_eq!(calls[0].arguments, json!({ "city": "Berlin" }));
+    assert!(calls[0].id.starts_with("call_"));
+
+    // Fenced single call with string-encoded arguments
+    let calls = parse_tool_calls(
+        "```json\n{\"name\": \"get_weather\", \"arguments\": \"{\\\"city\\\": \\\"Oslo\\\"}\"}\n```",
+        &tools,
+    )
+    .expect("tool call expected");
+    assert_eq!(calls[0].arguments, json!({ "city": "Oslo" }));
+}
+
+#[test]
+fn test_parse_tool_calls_ignores_normal_answers_and_unknown_tools() {
+    let tools = vec![weather_tool()];
+    assert!(parse_tool_calls("It is sunny in Berlin.", &tools).is_none());
+    assert!(parse_tool_calls(r#"Use {"name": "get_weather"} like this"#, &tools).is_none());
+    assert!(parse_tool_calls(r#"{"name": "delete_everything", "arguments": {}}"#, &tools).is_none());
+    assert!(render_tool_instructions(&tools).contains("get_weather"));
+}
+
+#[test]
+fn test_openai_apply_tools() {
+    let client = OpenAIClient::new(OpenAIConfig {
+        api_key: "sk-test123".to_string(),
+        base_url: "https://api.openai.com/v1".to_string(),
+        timeout_secs: 30,
+    });
+    let mut request = client.build_chat_request("gpt-4o", "Weather in Berlin?", None, None, None);
+    let (calls, results) = previous_round();
+    client.apply_tools(&mut request, &[weather_tool()], &calls, &results);
+
+    let body = serde_json::to_value(&request).unwrap();
+    assert_eq!(body["tools"][0]["type"], "function");
+    assert_eq!(body["tools"][0]["function"]["name"], "get_weather");
+    assert_eq!(body["messages"][1]["role"], "assistant");
+    assert_eq!(body["messages"][1]["tool_calls"][0]["id"], "call_1");
+    assert_eq!(body["messages"][1]["tool_calls"][0]["function"]["arguments"], r#"{"city":"Berlin"}"#);
+    assert_eq!(body["messages"][2]["role"], "tool");
+    assert_eq!(body["messages"][2]["tool_call_id"], "call_1");
+}
+
+#[test]
+fn test_anthropic_apply_tools() {
+    let client = AnthropicClient::new(AnthropicConfig {
+        api_key: "sk-ant-test123".to_string(),
+        base_url: "https://api.anthropic.com/v1".to_string(),
+        timeout_secs: 30,
+        anthropic_version: "2023-06-01".to_string(),
+    });
+    let mut request = client.build_messages_request("claude-3-5-sonnet", "Weather in Berlin?", None, None, None);
+    let (calls, results) = previous_round();
+    client.apply_tools(&mut request, &[weather_tool()], &calls, &results);
+
+    let body = serde_json::to_value(&request).unwrap();
+    assert_eq!(body["tools"][0]["name"], "get_weather");
+    assert_eq!(body["tools"][0]["input_schema"]["type"], "object");
+    assert_eq!(body["messages"][1]["role"], "assistant");
+    assert_eq!(body["messages"][1]["content"][0]["type"], "tool_use");
+    assert_eq!(body["messages"][1]["content"][0]["input"]["city"], "Berlin");
+    assert_eq!(body["messages"][2]["role"], "user");
+    assert_eq!(body["messages"][2]["content"][0]["type"], "tool_result");
+    assert_eq!(body["messages"][2]["content"][0]["tool_use_id"], "call_1");
+}
+
+#[test]
+fn test_google_apply_tools() {
+    let client = GoogleClient::new(GoogleConfig::new("test-key".to_string()));
+    let mut request = client.build_generate_content_request("gemini-pro", "Weather in Berlin?", None, None);
+    let (calls, results) = previous_round();
+    client.apply_tools(&mut request, &[weather_tool()], &calls, &results);
+
+    let body = serde_json::to_value(&request).unwrap();
+    assert_eq!(body["tools"][0]["functionDeclarations"][0]["name"], "get_weather");
+    assert_eq!(body["contents"][1]["role"], "model");
+    assert_eq!(body["contents"][1]["parts"][0]["functionCall"]["args"]["city"], "Berlin");
+    assert_eq!(body["contents"][2]["role"], "function");
+    assert_eq!(body["contents"][2]["parts"][0]["functionResponse"]["response"]["result"], "12°C, cloudy");
+}
+
+struct ToolCallingProvider;
+
+#[async_trait]
+impl LLMProvider for ToolCallingProvider {
+    fn model_name(&self) -> &str { "mock" }
+    async fn process_prompt(&self, _request: PromptRequest) -> Result<PromptResponse, LLMError> {
+        Ok(PromptResponse {
+            text: String::new(),
+            tokens_used: 5,
+            tool_calls: vec![ToolCall::new("get_weather", json!({ "city": "Berlin" }))],
+            structured: None,
+            safety: Vec::new(),
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_default_stream_emits_tool_calls() {
+    let request = PromptRequest {
+        prompt: "Weather in Berlin?".to_string(),
+        tools: vec![weather_tool()],
+        ..Default::default()
+    };
+    let mut stream = ToolCallingProvider.stream_prompt(request).await.unwrap();
+    let mut chunks = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        chunks.push(chunk.unwrap());
+    }
+
+    assert!(chunks.iter().any(|c| matches!(c, PromptStreamChunk::ToolCall(call) if call.name == "get_weather")));
+    match chunks.last() {
+        Some(PromptStreamChunk::Done { finish_reason, .. }) => assert_eq!(finish_reason, "tool_calls"),
+        other => panic!("expected Done, got {:?}", other),
+    }
+}
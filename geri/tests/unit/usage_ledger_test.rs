@@ -0,0 +1,226 @@
+//! Tests für Usage-Ledger, Budgets pro User/Device/Periode und Budget-Reset (Phase 9.4.1).
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use geri::cost::{BudgetLimit, BudgetPeriod, BudgetTracker, UsageFilter, UsageLedger, UsageRecord};
+    use geri::fallback::{BudgetResetHandler, BudgetResetListener};
+    use geri::llm::{GeriEngine, LLMError, LLMProvider, PromptRequest, PromptResponse, ProviderFactory};
+    use geri::model::{ModelInfo, ModelRegistry, ModelRegistryTrait, ModelType};
+    use geri::performance::PerformanceTracker;
+    use geri::selection::SelectionOptions;
+    use tokio::sync::RwLock;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, 0, 0).unwrap()
+    }
+
+    fn record(timestamp: DateTime<Utc>, user: &str, device: &str, model: &str, cost: f64) -> UsageRecord {
+        UsageRecord {
+            timestamp,
+            user_id: Some(user.to_string()),
+            device_id: Some(device.to_string()),
+            provider: "openai".to_string(),
+            model: model.to_string(),
+            input_tokens: 100,
+            output_tokens: 50,
+            cost,
+        }
+    }
+
+    fn limit(user: Option<&str>, device: Option<&str>, period: BudgetPeriod, amount: f64) -> BudgetLimit {
+        BudgetLimit {
+            user_id: user.map(str::to_string),
+            device_id: device.map(str::to_string),
+            period,
+            limit: amount,
+        }
+    }
+
+    #[test]
+    fn test_period_boundaries() {
+        let now = at(2026, 12, 31, 15);
+        assert_eq!(BudgetPeriod::Daily.start(now), Some(at(2026, 12, 31, 0)));
+        assert_eq!(BudgetPeriod::Daily.next_start(now), Some(at(2027, 1, 1, 0)));
+        assert_eq!(BudgetPeriod::Monthly.start(now), Some(at(2026, 12, 1, 0)));
+        assert_eq!(BudgetPeriod::Monthly.next_start(now), Some(at(2027, 1, 1, 0)));
+        assert_eq!(BudgetPeriod::Monthly.next_start(at(2026, 1, 31, 23)), Some(at(2026, 2, 1, 0)));
+        assert_eq!(BudgetPeriod::Total.start(now), None);
+        assert_eq!(BudgetPeriod::parse("Month"), Some(BudgetPeriod::Monthly));
+        assert_eq!(BudgetPeriod::parse("weekly"), None);
+    }
+
+    #[test]
+    fn test_limit_scopes() {
+        let global = limit(None, None, BudgetPeriod::Monthly, 10.0);
+        let alice = limit(Some("alice"), None, BudgetPeriod::Monthly, 1.0);
+        let kitchen = limit(None, Some("kitchen"), BudgetPeriod::Daily, 0.5);
+        assert!(global.applies_to(None, None));
+        assert!(alice.applies_to(Some("alice"), Some("kitchen")));
+        assert!(!alice.applies_to(Some("bob"), None));
+        assert!(!alice.applies_to(None, None));
+        assert!(kitchen.applies_to(Some("bob"), Some("kitchen")));
+        assert!(!kitchen.applies_to(Some("bob"), None));
+    }
+
+    #[test]
+    fn test_ledger_persists_and_summarizes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("usage").join("ledger.jsonl");
+        {
+            let mut ledger = UsageLedger::open(&path).unwrap();
+            ledger.append(record(at(2026, 9, 30, 12), "alice", "phone", "gpt-4o", 0.5));
+            ledger.append(record(at(2026, 10, 2, 12), "alice", "phone", "gpt-4o", 0.25));
+            ledger.append(record(at(2026, 10, 3, 12), "alice", "kitchen", "claude-3-5-sonnet", 1.0));
+            ledger.append(record(at(2026, 10, 3, 13), "bob", "kitchen", "gpt-4o", 2.0));
+        }
+        // Kaputte Zeilen werden beim Öffnen übersprungen
+        let mut content = std::fs::read_to_string(&path).unwrap();
+        content.push_str("not json\n");
+        std::fs::write(&path, content).unwrap();
+
+        let ledger = UsageLedger::open(&path).unwrap();
+        assert_eq!(ledger.records().len(), 4);
+
+        let october_alice = UsageFilter {
+            user_id: Some("alice".to_string()),
+            since: Some(at(2026, 10, 1, 0)),
+            ..Default::default()
+        };
+        let summary = ledger.summarize(&october_alice);
+        assert_eq!(summary.requests, 2);
+        assert_eq!(summary.input_tokens, 200);
+        assert_eq!(summary.output_tokens, 100);
+        assert!((summary.cost - 1.25).abs() < 1e-9);
+        assert_eq!(summary.models.len(), 2);
+        assert_eq!(summary.models[0].model, "claude-3-5-sonnet");
+        assert_eq!(summary.models[1].model, "gpt-4o");
+        assert_eq!(summary.models[1].requests, 1);
+
+        let kitchen = UsageFilter { device_id: Some("kitchen".to_string()), ..Default::default() };
+        assert!((ledger.cost(&kitchen) - 3.0).abs() < 1e-9);
+        assert_eq!(ledger.summarize(&UsageFilter::default()).requests, 4);
+    }
+
+    #[tokio::test]
+    async fn test_tracker_checks_limits_per_user_and_device() {
+        let now = Utc::now();
+        let tracker = BudgetTracker::new(100.0).with_limits(vec![
+            limit(Some("alice"), None, BudgetPeriod::Daily, 1.0),
+            limit(None, Some("kitchen"), BudgetPeriod::Monthly, 5.0),
+        ]);
+        tracker.record(record(now, "alice", "phone", "gpt-4o", 0.8)).await;
+        assert!(!tracker.is_over_limit_for(Some("alice"), Some("phone")).await);
+
+        tracker.record(record(now, "alice", "phone", "gpt-4o", 0.4)).await;
+        assert!(tracker.is_over_limit_for(Some("alice"), Some("phone")).await);
+        assert!(!tracker.is_over_limit_for(Some("bob"), Some("phone")).await);
+        // Gestrige Nutzung zählt nicht auf das Tageslimit
+        let yesterday = BudgetPeriod::Daily.start(now).unwrap() - Duration::hours(1);
+        tracker.record(record(yesterday, "bob", "kitchen", "gpt-4o", 10.0)).await;
+        assert!(!tracker.is_over_limit_for(Some("bob"), Some("phone")).await);
+
+        let statuses = tracker.statuses_at(Some("alice"), Some("kitchen"), now).await;
+        assert_eq!(statuses.len(), 2);
+        assert!((statuses[0].used - 1.2).abs() < 1e-9);
+        assert!(statuses[0].is_over_limit());
+        assert_eq!(statuses[0].resets_at, BudgetPeriod::Daily.next_start(now));
+
+        // Globales Limit (`BudgetManager`) gilt zusätzlich für alle
+        let (usage, _, _) = tracker.get_usage_info().await;
+        assert!((usage - 11.2).abs() < 1e-9);
+    }
+
+    struct CountHandler(Arc<AtomicUsize>);
+
+    impl BudgetResetHandler for CountHandler {
+        fn on_budget_reset(&self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_period_change_notifies_reset_listener() {
+        let resets = Arc::new(AtomicUsize::new(0));
+        let tracker = BudgetTracker::new(100.0)
+            .with_limits(vec![limit(Some("alice"), None, BudgetPeriod::Daily, 1.0)])
+            .with_reset_listener(BudgetResetListener::new(Box::new(CountHandler(resets.clone()))));
+
+        let now = Utc::now();
+        assert!(!tracker.check_period_reset(now).await);
+        assert!(tracker.check_period_reset(now + Duration::days(1)).await);
+        assert!(!tracker.check_period_reset(now + Duration::days(1)).await);
+        assert_eq!(resets.load(Ordering::SeqCst), 1);
+    }
+
+    struct FixedProvider(&'static str);
+
+    #[async_trait]
+    impl LLMProvider for FixedProvider {
+        fn model_name(&self) -> &str { self.0 }
+        async fn process_prompt(&self, _request: PromptRequest) -> Result<PromptResponse, LLMError> {
+            Ok(PromptResponse {
+                text: format!("answer from {}", self.0),
+                tokens_used: 30,
+                tool_calls: Vec::new(),
+                structured: None,
+                safety: Vec::new(),
+            })
+        }
+    }
+
+    fn model(id: &str, provider: &str, is_local: bool) -> ModelInfo {
+        ModelInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            provider: provider.to_string(),
+            model_type: ModelType::Llm,
+            parameter_count: Some(8_000_000_000),
+            hardware_requirements: None,
+            context_window: Some(8192),
+            is_local,
+            cost_per_token_input: None,
+            cost_per_token_output: None,
+            embedding_dimension: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_engine_records_usage_and_keeps_user_over_budget_local() {
+        let registry: Arc<dyn ModelRegistryTrait> = Arc::new(RwLock::new(
+            ModelRegistry::new()
+                .register(model("gpt-4o", "openai", false))
+                .register(model("llama-3-8b", "llamacpp", true)),
+        ));
+        let factory = Arc::new(ProviderFactory::new());
+        factory.register("gpt-4o", Arc::new(FixedProvider("gpt-4o"))).await;
+        factory.register("llama-3-8b", Arc::new(FixedProvider("llama-3-8b"))).await;
+        let tracker = Arc::new(
+            BudgetTracker::new(100.0).with_limits(vec![limit(Some("alice"), None, BudgetPeriod::Monthly, 1.0)]),
+        );
+        tracker.record(record(Utc::now(), "alice", "phone", "gpt-4o", 1.5)).await;
+        let engine = GeriEngine::new(registry, Arc::new(PerformanceTracker::new().unwrap()), factory, tracker.clone());
+
+        let request = PromptRequest {
+            prompt: "Hello".to_string(),
+            user_id: Some("alice".to_string()),
+            device_id: Some("kitchen".to_string()),
+            ..Default::default()
+        };
+        let response = engine.process(request, SelectionOptions::default()).await.unwrap();
+        assert_eq!(response.text, "answer from llama-3-8b");
+
+        let summary = tracker
+            .usage(&UsageFilter { device_id: Some("kitchen".to_string()), ..Default::default() })
+            .await;
+        assert_eq!(summary.requests, 1);
+        assert_eq!(summary.models[0].model, "llama-3-8b");
+        assert_eq!(summary.models[0].provider, "llamacpp");
+        assert_eq!(summary.input_tokens + summary.output_tokens, 30);
+        assert!(summary.input_tokens > 0);
+    }
+}
@@ -0,0 +1,15 @@
+//! Gjallarhorn: Risiko-Klassifikation von Aktionen für Edda.
+//!
+//! Bewertet eine Aktion anhand von Service, Action-Type und JSON-Parametern: destruktive
+//! Datei-Operationen, Shell-Commands, Aktionen auf anderen Devices und Käufe sind riskant.
+//! Odin entscheidet damit, welche Pläne der User bestätigen muss; Thor verweigert riskante
+//! Aktionen ohne Bestätigung. Beide nutzen dieselben Regeln.
+//!
+//! Die Bestätigung reist als Token, das Odin mit einem gemeinsamen Secret für genau eine Aktion
+//! signiert und das nach Ablauf ungültig wird; Thor prüft es, bevor er die Aktion ausführt.
+
+mod risk;
+mod token;
+
+pub use risk::{classify_action, is_dangerous_command, RiskAssessment, RiskLevel};
+pub use token::{token_expiry, ConfirmationSigner, TokenError};
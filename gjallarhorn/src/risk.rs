@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Risiko einer Aktion, von harmlos bis gefährlich geordnet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    #[default]
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl RiskLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RiskLevel::Low => "low",
+            RiskLevel::Medium => "medium",
+            RiskLevel::High => "high",
+            RiskLevel::Critical => "critical",
+        }
+    }
+
+    /// Level zu seinem Namen, wie er in den Settings steht (`"low"` … `"critical"`).
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "low" => Some(RiskLevel::Low),
+            "medium" => Some(RiskLevel::Medium),
+            "high" => Some(RiskLevel::High),
+            "critical" => Some(RiskLevel::Critical),
+            _ => None,
+        }
+    }
+}
+
+/// Risiko einer Aktion und die Gründe dafür.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RiskAssessment {
+    pub level: RiskLevel,
+    pub reasons: Vec<String>,
+}
+
+impl RiskAssessment {
+    /// Hebt das Risiko auf mindestens `level` an und merkt sich den Grund.
+    pub fn raise(&mut self, level: RiskLevel, reason: String) {
+        self.level = self.level.max(level);
+        self.reasons.push(reason);
+    }
+}
+
+/// Shell-Fragmente, die Daten zerstören oder das System herunterfahren.
+const DANGEROUS_COMMANDS: &[&str] = &[
+    "rm -rf", "rm -fr", "rm -r", "mkfs", "dd if=", "shutdown", "reboot", "poweroff", "halt", ":(){",
+    "chmod -r 777", "chown -r", "> /dev/sd", "sudo ", "format ", "del /s", "rd /s", "remove-item",
+];
+
+/// Wörter in Funktions-, Tool- oder Skriptnamen, die auf Datenverlust hindeuten.
+const DESTRUCTIVE_WORDS: &[&str] = &["delete", "remove", "wipe", "erase", "format", "destroy", "kill", "shutdown", "reset"];
+
+/// Wörter in Action-Types, Funktions-, Tool- oder Skriptnamen und URLs, die auf Geldausgaben hindeuten.
+const PURCHASE_WORDS: &[&str] = &["purchase", "buy", "checkout", "payment", "pay", "order"];
+
+/// Bewertet eine Aktion von `service` (`"thor"`, `"loki"`, …) anhand von Action-Type und Parametern.
+pub fn classify_action(service: &str, action_type: &str, params: &Value) -> RiskAssessment {
+    let mut risk = RiskAssessment::default();
+
+    match (service, action_type) {
+        ("thor", "XML_TASK" | "XML_CALL") => classify_xml(str_param(params, "xml"), &mut risk),
+        ("thor", "FILE_OPERATION") => {
+            let path = str_param(params, "path");
+            match str_param(params, "operation") {
+                "Delete" => risk.raise(RiskLevel::High, format!("deletes {}", path)),
+                "Move" => risk.raise(
+                    RiskLevel::High,
+                    format!("moves {} to {}", str_param(params, "from"), str_param(params, "to")),
+                ),
+                "Write" => risk.raise(RiskLevel::Medium, format!("writes {}", path)),
+                _ => {}
+            }
+        }
+        ("thor", "SYSTEM_COMMAND" | "TERMINAL_OPERATION") => {
+            let command = command_line(params);
+            risk.raise(RiskLevel::High, format!("runs shell command `{}`", command));
+            if is_dangerous_command(&command) {
+                risk.raise(RiskLevel::Critical, "command can destroy data or stop the system".to_string());
+            }
+        }
+        ("thor", "SANDBOX_COMMAND") => {
+            let command = command_line(params);
+            risk.raise(RiskLevel::Medium, format!("runs `{}` in the sandbox", command));
+            if is_dangerous_command(&command) {
+                risk.raise(RiskLevel::High, "command can destroy data inside the sandbox".to_string());
+            }
+        }
+        ("thor", "NETWORK_OPERATION") => match str_param(params, "method").to_uppercase().as_str() {
+            "DELETE" => risk.raise(RiskLevel::High, format!("deletes remote resource {}", str_param(params, "url"))),
+            method @ ("POST" | "PUT") => {
+                risk.raise(RiskLevel::Medium, format!("sends {} to {}", method, str_param(params, "url")))
+            }
+            _ => {}
+        },
+        ("thor", "APP_CONTROL") if str_param(params, "operation") == "stop" => {
+            risk.raise(RiskLevel::Medium, format!("stops {}", str_param(params, "app_path")));
+        }
+        ("thor", "JOTUNHEIM_OPERATION") => {
+            risk.raise(RiskLevel::Medium, format!("controls device {}", str_param(params, "device_id")));
+        }
+        ("loki", "EXECUTE_SCRIPT") => {
+            risk.raise(RiskLevel::Medium, format!("runs Loki script {}", str_param(params, "script_id")))
+        }
+        ("loki", _) => risk.raise(
+            RiskLevel::Medium,
+            format!("calls {} on device {}", str_param(params, "tool_name"), str_param(params, "device_id")),
+        ),
+        _ => {}
+    }
+
+    // Funktionen, Tools und Skripte, die nach dem benannt sind, was sie tun
+    for name in [action_type, str_param(params, "tool_name"), str_param(params, "script_id")] {
+        if has_word(name, DESTRUCTIVE_WORDS) {
+            risk.raise(RiskLevel::High, format!("{} is destructive", name));
+        }
+        if has_word(name, PURCHASE_WORDS) {
+            risk.raise(RiskLevel::High, format!("{} may make a purchase", name));
+        }
+    }
+    if has_word(str_param(params, "url"), PURCHASE_WORDS) {
+        risk.raise(RiskLevel::High, format!("may make a purchase via {}", str_param(params, "url")));
+    }
+    let target = str_param(params, "target_device_id");
+    if !target.is_empty() || action_type.to_uppercase().contains("CROSS_DEVICE") {
+        risk.raise(RiskLevel::High, format!("runs on another device ({})", target));
+    }
+    risk
+}
+
+/// Ob eine Shell-Befehlszeile eines der [`DANGEROUS_COMMANDS`] enthält.
+pub fn is_dangerous_command(command: &str) -> bool {
+    let command = command.to_lowercase();
+    DANGEROUS_COMMANDS.iter().any(|pattern| command.contains(pattern))
+}
+
+/// Thor-XML-Protokoll: `<instruction>`-Tasks, die einen Befehl ausführen, und `<call>`s riskanter Typen.
+fn classify_xml(xml: &str, risk: &mut RiskAssessment) {
+    if let Some(instruction) = between(xml, "<instruction>", "</instruction>") {
+        if let Some(command) = instruction.split("command:").nth(1).filter(|_| instruction.contains("Execute")) {
+            let command = command.trim();
+            risk.raise(RiskLevel::High, format!("runs shell command `{}`", command));
+            if is_dangerous_command(command) {
+                risk.raise(RiskLevel::Critical, "command can destroy data or stop the system".to_string());
+            }
+        }
+    }
+    if let Some(call_type) = between(xml, "<call type=\"", "\"") {
+        if matches!(call_type, "SYSTEM_COMMAND" | "TERMINAL_OPERATION") {
+            risk.raise(RiskLevel::High, format!("runs a shell command ({})", call_type));
+        }
+        if has_word(call_type, DESTRUCTIVE_WORDS) {
+            risk.raise(RiskLevel::High, format!("{} is destructive", call_type));
+        }
+    }
+}
+
+fn between<'a>(text: &'a str, start: &str, end: &str) -> Option<&'a str> {
+    let from = text.find(start)? + start.len();
+    let len = text[from..].find(end)?;
+    Some(&text[from..from + len])
+}
+
+fn has_word(text: &str, words: &[&str]) -> bool {
+    text.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .any(|word| words.contains(&word))
+}
+
+fn command_line(params: &Value) -> String {
+    let mut parts = vec![str_param(params, "command").to_string()];
+    if let Some(args) = params["args"].as_array() {
+        parts.extend(args.iter().filter_map(Value::as_str).map(str::to_string));
+    }
+    parts.join(" ").trim().to_string()
+}
+
+fn str_param<'a>(params: &'a Value, key: &str) -> &'a str {
+    params.get(key).and_then(Value::as_str).unwrap_or_default()
+}
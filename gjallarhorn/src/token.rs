@@ -0,0 +1,87 @@
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Warum ein Bestätigungs-Token abgelehnt wurde.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TokenError {
+    #[error("malformed confirmation token")]
+    Malformed,
+    #[error("confirmation expired")]
+    Expired,
+    #[error("confirmation was not issued for this action")]
+    InvalidSignature,
+}
+
+/// Signiert Bestätigungen (Odin) und prüft sie (Thor) mit einem gemeinsamen Secret.
+///
+/// Ein Token gilt für genau eine Aktion – Action-Type und Daten, wie Thor sie empfängt – und
+/// bis zu seinem Ablauf: `<confirmation_id>.<expires_at_ms>.<hmac>`, HMAC-SHA256 über
+/// Bestätigungs-ID, Ablauf, Action-Type und SHA-256 der Daten.
+#[derive(Clone)]
+pub struct ConfirmationSigner {
+    key: Vec<u8>,
+}
+
+impl std::fmt::Debug for ConfirmationSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConfirmationSigner").finish_non_exhaustive()
+    }
+}
+
+impl ConfirmationSigner {
+    pub fn new(secret: &str) -> Self {
+        Self { key: secret.as_bytes().to_vec() }
+    }
+
+    /// Token für die Bestätigung `confirmation_id` der Aktion, gültig bis `expires_at_ms` (Unix ms).
+    pub fn sign(&self, confirmation_id: &str, action_type: &str, action_data: &[u8], expires_at_ms: i64) -> String {
+        let signature = self.mac(confirmation_id, expires_at_ms, action_type, action_data).finalize().into_bytes();
+        format!("{}.{}.{}", confirmation_id, expires_at_ms, hex::encode(signature))
+    }
+
+    /// Prüft das Token für die Aktion zum Zeitpunkt `now_ms`; liefert die Bestätigungs-ID.
+    ///
+    /// Ein gültiges Token bleibt bis zu seinem Ablauf gültig; dass es nur einmal eingelöst wird,
+    /// stellt der Empfänger sicher (siehe [`token_expiry`]).
+    pub fn verify(&self, token: &str, action_type: &str, action_data: &[u8], now_ms: i64) -> Result<String, TokenError> {
+        let (confirmation_id, expires_at_ms, signature) = split(token)?;
+        let signature = hex::decode(signature).map_err(|_| TokenError::Malformed)?;
+        self.mac(confirmation_id, expires_at_ms, action_type, action_data)
+            .verify_slice(&signature)
+            .map_err(|_| TokenError::InvalidSignature)?;
+        if expires_at_ms <= now_ms {
+            return Err(TokenError::Expired);
+        }
+        Ok(confirmation_id.to_string())
+    }
+
+    fn mac(&self, confirmation_id: &str, expires_at_ms: i64, action_type: &str, action_data: &[u8]) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        // Längenpräfixe: kein Feld kann in das nächste übergehen
+        let data_digest = Sha256::digest(action_data);
+        for field in [confirmation_id.as_bytes(), &expires_at_ms.to_be_bytes(), action_type.as_bytes(), data_digest.as_slice()] {
+            mac.update(&(field.len() as u64).to_be_bytes());
+            mac.update(field);
+        }
+        mac
+    }
+}
+
+/// Ablaufzeitpunkt (Unix ms) eines Tokens, ohne es zu prüfen; bis dahin muss sich der Empfänger
+/// eingelöste Tokens merken.
+pub fn token_expiry(token: &str) -> Option<i64> {
+    split(token).ok().map(|(_, expires_at_ms, _)| expires_at_ms)
+}
+
+/// `<confirmation_id>.<expires_at_ms>.<hmac>` – die ID darf selbst Punkte enthalten.
+fn split(token: &str) -> Result<(&str, i64, &str), TokenError> {
+    let mut parts = token.rsplitn(3, '.');
+    let (Some(signature), Some(expires_at_ms), Some(confirmation_id)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(TokenError::Malformed);
+    };
+    let expires_at_ms = expires_at_ms.parse().map_err(|_| TokenError::Malformed)?;
+    Ok((confirmation_id, expires_at_ms, signature))
+}
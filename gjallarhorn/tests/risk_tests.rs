@@ -0,0 +1,43 @@
+use gjallarhorn::{classify_action, is_dangerous_command, RiskLevel};
+use serde_json::json;
+
+#[test]
+fn shell_commands_are_rated_by_where_they_run() {
+    let host = classify_action("thor", "SYSTEM_COMMAND", &json!({ "command": "rm", "args": ["-rf", "/home"] }));
+    assert_eq!(host.level, RiskLevel::Critical);
+
+    // Inside the sandbox the same command cannot reach the host
+    let sandbox = classify_action("thor", "SANDBOX_COMMAND", &json!({ "command": "rm", "args": ["-rf", "/work"] }));
+    assert_eq!(sandbox.level, RiskLevel::High);
+
+    let xml = json!({ "xml": "<task><instruction>Execute the following command: sudo reboot</instruction></task>" });
+    assert_eq!(classify_action("thor", "XML_TASK", &xml).level, RiskLevel::Critical);
+
+    assert!(is_dangerous_command("Remove-Item C:\\Users -Recurse"));
+    assert!(!is_dangerous_command("ls -la"));
+}
+
+#[test]
+fn tools_and_scripts_are_rated_by_their_names() {
+    let script = classify_action("loki", "EXECUTE_SCRIPT", &json!({ "script_id": "evening_lights" }));
+    assert_eq!(script.level, RiskLevel::Medium);
+
+    let wipe = classify_action("loki", "CALL_TOOL", &json!({ "tool_name": "wipe_storage", "device_id": "cam-1" }));
+    assert_eq!(wipe.level, RiskLevel::High);
+    assert!(wipe.reasons.contains(&"wipe_storage is destructive".to_string()));
+
+    let order = classify_action("thor", "JOTUNHEIM_OPERATION", &json!({ "tool_name": "order_coffee", "device_id": "kitchen" }));
+    assert_eq!(order.level, RiskLevel::High);
+
+    let status = classify_action("thor", "APP_CONTROL", &json!({ "operation": "status", "app_path": "vlc" }));
+    assert_eq!(status.level, RiskLevel::Low);
+    assert!(status.reasons.is_empty());
+}
+
+#[test]
+fn levels_parse_from_settings_names() {
+    assert_eq!(RiskLevel::parse("medium"), Some(RiskLevel::Medium));
+    assert_eq!(RiskLevel::parse("severe"), None);
+    assert_eq!(RiskLevel::Critical.as_str(), "critical");
+    assert!(RiskLevel::High > RiskLevel::Medium);
+}
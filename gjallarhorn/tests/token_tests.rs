@@ -0,0 +1,39 @@
+use gjallarhorn::{token_expiry, ConfirmationSigner, TokenError};
+
+const DATA: &[u8] = br#"{"command":"rm","args":["-rf","/tmp/build"]}"#;
+
+#[test]
+fn token_is_valid_for_its_action_until_it_expires() {
+    let signer = ConfirmationSigner::new("shared-secret");
+    let token = signer.sign("c-1", "SYSTEM_COMMAND", DATA, 2_000);
+
+    assert_eq!(signer.verify(&token, "SYSTEM_COMMAND", DATA, 1_000), Ok("c-1".to_string()));
+    assert_eq!(signer.verify(&token, "SYSTEM_COMMAND", DATA, 2_000), Err(TokenError::Expired));
+    assert_eq!(token_expiry(&token), Some(2_000));
+}
+
+#[test]
+fn token_is_bound_to_action_and_secret() {
+    let signer = ConfirmationSigner::new("shared-secret");
+    let token = signer.sign("c-1", "SYSTEM_COMMAND", DATA, 2_000);
+
+    let other_data = br#"{"command":"rm","args":["-rf","/home"]}"#;
+    assert_eq!(signer.verify(&token, "SYSTEM_COMMAND", other_data, 1_000), Err(TokenError::InvalidSignature));
+    assert_eq!(signer.verify(&token, "TERMINAL_OPERATION", DATA, 1_000), Err(TokenError::InvalidSignature));
+    assert_eq!(
+        ConfirmationSigner::new("other-secret").verify(&token, "SYSTEM_COMMAND", DATA, 1_000),
+        Err(TokenError::InvalidSignature)
+    );
+
+    // A later expiry cannot be patched into a signed token
+    let extended = token.replacen(".2000.", ".9000.", 1);
+    assert_eq!(signer.verify(&extended, "SYSTEM_COMMAND", DATA, 1_000), Err(TokenError::InvalidSignature));
+}
+
+#[test]
+fn made_up_ids_are_rejected() {
+    let signer = ConfirmationSigner::new("shared-secret");
+    assert_eq!(signer.verify("c-1", "SYSTEM_COMMAND", DATA, 1_000), Err(TokenError::Malformed));
+    assert_eq!(signer.verify("c-1.2000.zz", "SYSTEM_COMMAND", DATA, 1_000), Err(TokenError::Malformed));
+    assert_eq!(signer.verify("c-1.2000.00ff", "SYSTEM_COMMAND", DATA, 1_000), Err(TokenError::InvalidSignature));
+}
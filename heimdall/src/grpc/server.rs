@@ -638,6 +638,7 @@ pub async fn start_grpc_server(
         .await;
 
     Server::builder()
+        .layer(vegvisir::ServerTraceLayer)
         .add_service(health_service)
         .add_service(AuthenticationServiceServer::new(auth_service))
         .add_service(AuthorizationServiceServer::new(authz_service))
@@ -4,12 +4,17 @@ use heimdall::grpc::start_grpc_server;
 use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+    // Initialize tracing; spans go to Vegvisir and are exported once the settings are loaded
+    let (trace_layer, trace_exporter) = vegvisir::layer("heimdall");
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(tracing_subscriber::EnvFilter::from_default_env()))
+        .with(trace_layer)
         .init();
 
     info!("Heimdall Security Service starting...");
@@ -22,6 +27,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     let settings = settings_manager.get().await;
     info!("Configuration loaded");
+    trace_exporter.start(&settings.trace_export);
 
     // Initialize database
     let db_manager = heimdall::utils::database::DatabaseManager::new(&settings.database_url).await?;
@@ -54,6 +54,9 @@ pub struct HeimdallSettings {
     pub oauth: Option<OAuthSettings>,
     pub grpc_port: u16,
     pub database_url: String,
+    /// Span export (OTLP collector and/or JSON file), see Vegvisir
+    #[serde(default)]
+    pub trace_export: vegvisir::TraceExportConfig,
 }
 
 impl Default for HeimdallSettings {
@@ -80,6 +83,7 @@ impl Default for HeimdallSettings {
             oauth: None,
             grpc_port: 50051,
             database_url: "postgres://localhost/heimdall".to_string(),
+            trace_export: vegvisir::TraceExportConfig::default(),
         }
     }
 }
@@ -162,7 +166,7 @@ impl SettingsManager {
 
 }
 
-/// Validates settings (token expirations, port, database_url). Used by load() and hot-reload.
+/// Validates settings (token expirations, port, database_url, trace_export). Used by load() and hot-reload.
 pub fn validate_settings(settings: &HeimdallSettings) -> Result<(), Box<dyn std::error::Error>> {
     if settings.token_configuration.heimdall_token_expiration_hours == 0 {
         return Err("heimdall_token_expiration_hours must be > 0".into());
@@ -179,5 +183,6 @@ pub fn validate_settings(settings: &HeimdallSettings) -> Result<(), Box<dyn std:
     if settings.database_url.is_empty() {
         return Err("database_url cannot be empty".into());
     }
+    settings.trace_export.validate()?;
     Ok(())
 }
@@ -23,6 +23,25 @@ pub struct GeneratedToolDef {
     pub return_type: String, // "string" | "number" | "boolean" | "void"
 }
 
+impl GeneratedToolDef {
+    /// JSON Schema (`type: object`) of the parameters, for native LLM tool calling.
+    pub fn parameters_schema(&self) -> serde_json::Value {
+        let mut properties = serde_json::Map::new();
+        let mut required = Vec::new();
+        for p in &self.parameters {
+            let mut prop = serde_json::json!({ "type": p.param_type });
+            if let Some(d) = &p.description {
+                prop["description"] = serde_json::Value::String(d.clone());
+            }
+            properties.insert(p.name.clone(), prop);
+            if p.required {
+                required.push(serde_json::Value::String(p.name.clone()));
+            }
+        }
+        serde_json::json!({ "type": "object", "properties": properties, "required": required })
+    }
+}
+
 /// Registry: sensor/actuator type → list of tool definitions.
 fn builtin_sensor_tools() -> HashMap<String, Vec<GeneratedToolDef>> {
     let mut m = HashMap::new();
@@ -99,6 +99,40 @@ impl ToolDefinition {
             script: ScriptSource::Inline(String::new()),
         }
     }
+    
+    /// JSON Schema (`type: object`) of the parameters, as expected by LLM tool calling (Geri `ToolDefinition.parameters`)
+    pub fn parameters_schema(&self) -> serde_json::Value {
+        let mut properties = serde_json::Map::new();
+        let mut required = Vec::new();
+        for param in &self.parameters {
+            let mut property = serde_json::json!({ "type": param.param_type.json_schema_type() });
+            if let Some(description) = &param.description {
+                property["description"] = serde_json::Value::String(description.clone());
+            }
+            properties.insert(param.name.clone(), property);
+            if param.required {
+                required.push(serde_json::Value::String(param.name.clone()));
+            }
+        }
+        serde_json::json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        })
+    }
+}
+
+impl ParameterType {
+    /// JSON Schema type name
+    pub fn json_schema_type(&self) -> &'static str {
+        match self {
+            ParameterType::String => "string",
+            ParameterType::Number => "number",
+            ParameterType::Boolean => "boolean",
+            ParameterType::Object => "object",
+            ParameterType::Array => "array",
+        }
+    }
 }
 
 impl ToolParameter {
@@ -195,4 +229,20 @@ mod tests {
         let tool = ToolDefinition::new("test".to_string(), "desc".to_string());
         assert_eq!(tool.return_type, ReturnType::String);
     }
+    
+    #[test]
+    fn test_parameters_schema() {
+        let mut tool = ToolDefinition::new("set_led".to_string(), "desc".to_string());
+        let mut pin = ToolParameter::new("pin".to_string(), ParameterType::Number, true);
+        pin.description = Some("GPIO pin".to_string());
+        tool.parameters.push(pin);
+        tool.parameters.push(ToolParameter::new("on".to_string(), ParameterType::Boolean, false));
+        
+        let schema = tool.parameters_schema();
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["properties"]["pin"]["type"], "number");
+        assert_eq!(schema["properties"]["pin"]["description"], "GPIO pin");
+        assert_eq!(schema["properties"]["on"]["type"], "boolean");
+        assert_eq!(schema["required"], serde_json::json!(["pin"]));
+    }
 }
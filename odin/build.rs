@@ -11,9 +11,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .build_client(true)
         .compile(&["proto/einherjar.proto"], &["proto"])?;
     
-    // Build Responsibility Service proto
+    // Build Responsibility Service proto (server: owners return or reject responsibility to Odin)
     tonic_build::configure()
-        .build_server(false)
+        .build_server(true)
         .build_client(true)
         .compile(&["proto/responsibility.proto"], &["proto"])?;
     
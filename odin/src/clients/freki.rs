@@ -1,4 +1,4 @@
-use tonic::transport::Channel;
+use vegvisir::TracedChannel;
 use anyhow::Result;
 use std::time::Duration;
 use super::ServiceClientConfig;
@@ -12,7 +12,7 @@ use freki::{RetrieveContextRequest, RetrieveContextResponse, IndexDocumentReques
 
 /// Client for Freki service
 pub struct FrekiClient {
-    client: FrekiServiceClient<Channel>,
+    client: FrekiServiceClient<TracedChannel>,
 }
 
 impl FrekiClient {
@@ -23,7 +23,7 @@ impl FrekiClient {
             .connect_timeout(Duration::from_secs(5));
         
         let channel = endpoint.connect().await?;
-        let client = FrekiServiceClient::new(channel);
+        let client = FrekiServiceClient::new(vegvisir::traced(channel));
         
         Ok(Self { client })
     }
@@ -1,4 +1,4 @@
-use tonic::transport::Channel;
+use vegvisir::TracedChannel;
 use anyhow::Result;
 use std::time::Duration;
 use super::ServiceClientConfig;
@@ -8,11 +8,11 @@ pub mod geri {
 }
 
 use geri::geri_service_client::GeriServiceClient;
-use geri::{ProcessPromptRequest, ProcessPromptResponse, ProcessVisionRequest, ProcessVisionResponse};
+use geri::{ProcessPromptRequest, ProcessPromptResponse, ProcessPromptStreamChunk, ProcessVisionRequest, ProcessVisionResponse};
 
 /// Client for Geri service
 pub struct GeriClient {
-    client: GeriServiceClient<Channel>,
+    client: GeriServiceClient<TracedChannel>,
 }
 
 impl GeriClient {
@@ -23,7 +23,7 @@ impl GeriClient {
             .connect_timeout(Duration::from_secs(5));
         
         let channel = endpoint.connect().await?;
-        let client = GeriServiceClient::new(channel);
+        let client = GeriServiceClient::new(vegvisir::traced(channel));
         
         Ok(Self { client })
     }
@@ -35,6 +35,13 @@ impl GeriClient {
         Ok(response.into_inner())
     }
 
+    /// Process a prompt via Geri, receiving the answer as it is generated
+    pub async fn process_prompt_stream(&mut self, request: ProcessPromptRequest) -> Result<tonic::Streaming<ProcessPromptStreamChunk>> {
+        let req = tonic::Request::new(request);
+        let response = self.client.process_prompt_stream(req).await?;
+        Ok(response.into_inner())
+    }
+
     /// Process a vision request via Geri
     pub async fn process_vision(&mut self, request: ProcessVisionRequest) -> Result<ProcessVisionResponse> {
         let req = tonic::Request::new(request);
@@ -1,4 +1,4 @@
-use tonic::transport::Channel;
+use vegvisir::TracedChannel;
 use anyhow::Result;
 use std::time::Duration;
 use super::ServiceClientConfig;
@@ -22,10 +22,10 @@ use heimdall::{
 
 /// Client for Heimdall service (Security)
 pub struct HeimdallClient {
-    auth_client: AuthenticationServiceClient<Channel>,
-    authz_client: AuthorizationServiceClient<Channel>,
-    token_client: TokenServiceClient<Channel>,
-    bifrost_client: BifrostValidationServiceClient<Channel>,
+    auth_client: AuthenticationServiceClient<TracedChannel>,
+    authz_client: AuthorizationServiceClient<TracedChannel>,
+    token_client: TokenServiceClient<TracedChannel>,
+    bifrost_client: BifrostValidationServiceClient<TracedChannel>,
 }
 
 impl HeimdallClient {
@@ -38,10 +38,10 @@ impl HeimdallClient {
         let channel = endpoint.connect().await?;
         
         Ok(Self {
-            auth_client: AuthenticationServiceClient::new(channel.clone()),
-            authz_client: AuthorizationServiceClient::new(channel.clone()),
-            token_client: TokenServiceClient::new(channel.clone()),
-            bifrost_client: BifrostValidationServiceClient::new(channel),
+            auth_client: AuthenticationServiceClient::new(vegvisir::traced(channel.clone())),
+            authz_client: AuthorizationServiceClient::new(vegvisir::traced(channel.clone())),
+            token_client: TokenServiceClient::new(vegvisir::traced(channel.clone())),
+            bifrost_client: BifrostValidationServiceClient::new(vegvisir::traced(channel)),
         })
     }
 
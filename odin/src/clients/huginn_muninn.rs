@@ -1,4 +1,4 @@
-use tonic::transport::Channel;
+use vegvisir::TracedChannel;
 use anyhow::Result;
 use std::time::Duration;
 use super::ServiceClientConfig;
@@ -12,7 +12,7 @@ use huginn_muninn::{TranscribeRequest, TranscribeResponse, SynthesizeRequest, Sy
 
 /// Client for Huginn-Muninn service (STT/TTS)
 pub struct HuginnMuninnClient {
-    client: HuginnMuninnServiceClient<Channel>,
+    client: HuginnMuninnServiceClient<TracedChannel>,
 }
 
 impl HuginnMuninnClient {
@@ -23,7 +23,7 @@ impl HuginnMuninnClient {
             .connect_timeout(Duration::from_secs(5));
         
         let channel = endpoint.connect().await?;
-        let client = HuginnMuninnServiceClient::new(channel);
+        let client = HuginnMuninnServiceClient::new(vegvisir::traced(channel));
         
         Ok(Self { client })
     }
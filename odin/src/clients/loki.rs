@@ -1,4 +1,4 @@
-use tonic::transport::Channel;
+use vegvisir::TracedChannel;
 use anyhow::Result;
 use std::time::Duration;
 use super::ServiceClientConfig;
@@ -8,11 +8,11 @@ pub mod loki {
 }
 
 use loki::loki_service_client::LokiServiceClient;
-use loki::{ExecuteScriptRequest, ExecuteScriptResponse, CallToolRequest, CallToolResponse};
+use loki::{ExecuteScriptRequest, ExecuteScriptResponse, CallToolRequest, CallToolResponse, GetCapabilitiesRequest, GetCapabilitiesResponse};
 
 /// Client for Loki service (Script Execution & IoT Tool Calling)
 pub struct LokiClient {
-    client: LokiServiceClient<Channel>,
+    client: LokiServiceClient<TracedChannel>,
 }
 
 impl LokiClient {
@@ -23,11 +23,18 @@ impl LokiClient {
             .connect_timeout(Duration::from_secs(5));
         
         let channel = endpoint.connect().await?;
-        let client = LokiServiceClient::new(channel);
+        let client = LokiServiceClient::new(vegvisir::traced(channel));
         
         Ok(Self { client })
     }
 
+    /// Get the scripts Loki offers (`ScriptCapability`)
+    pub async fn get_capabilities(&mut self) -> Result<GetCapabilitiesResponse> {
+        let req = tonic::Request::new(GetCapabilitiesRequest {});
+        let response = self.client.get_capabilities(req).await?;
+        Ok(response.into_inner())
+    }
+
     /// Execute a script via Loki
     pub async fn execute_script(&mut self, request: ExecuteScriptRequest) -> Result<ExecuteScriptResponse> {
         let req = tonic::Request::new(request);
@@ -198,6 +198,61 @@ impl ClientManager {
         }
     }
 
+    /// Stream a prompt's answer from Geri (convenience method); the client is only locked until the stream starts
+    pub async fn stream_geri_prompt(&self, request: crate::clients::geri::geri::ProcessPromptRequest) -> Result<tonic::Streaming<crate::clients::geri::geri::ProcessPromptStreamChunk>, String> {
+        let mut client_guard = self.geri_client.write().await;
+        if let Some(ref mut client) = *client_guard {
+            client.process_prompt_stream(request).await
+                .map_err(|e| format!("Failed to stream prompt: {}", e))
+        } else {
+            Err("Geri client not initialized".to_string())
+        }
+    }
+
+    /// Call a tool on an IoT device via Loki (convenience method)
+    pub async fn call_loki_tool(&self, request: crate::clients::loki::loki::CallToolRequest) -> Result<crate::clients::loki::loki::CallToolResponse, String> {
+        let mut client_guard = self.loki_client.write().await;
+        if let Some(ref mut client) = *client_guard {
+            client.call_tool(request).await
+                .map_err(|e| format!("Failed to call tool: {}", e))
+        } else {
+            Err("Loki client not initialized".to_string())
+        }
+    }
+
+    /// Execute a script via Loki (convenience method)
+    pub async fn execute_loki_script(&self, request: crate::clients::loki::loki::ExecuteScriptRequest) -> Result<crate::clients::loki::loki::ExecuteScriptResponse, String> {
+        let mut client_guard = self.loki_client.write().await;
+        if let Some(ref mut client) = *client_guard {
+            client.execute_script(request).await
+                .map_err(|e| format!("Failed to execute script: {}", e))
+        } else {
+            Err("Loki client not initialized".to_string())
+        }
+    }
+
+    /// Get Loki's script capabilities (convenience method)
+    pub async fn get_loki_capabilities(&self) -> Result<crate::clients::loki::loki::GetCapabilitiesResponse, String> {
+        let mut client_guard = self.loki_client.write().await;
+        if let Some(ref mut client) = *client_guard {
+            client.get_capabilities().await
+                .map_err(|e| format!("Failed to get capabilities: {}", e))
+        } else {
+            Err("Loki client not initialized".to_string())
+        }
+    }
+
+    /// Check a permission via Heimdall (convenience method)
+    pub async fn check_heimdall_permission(&self, request: crate::clients::heimdall::heimdall::PermissionCheckRequest) -> Result<crate::clients::heimdall::heimdall::PermissionCheckResponse, String> {
+        let mut client_guard = self.heimdall_client.write().await;
+        if let Some(ref mut client) = *client_guard {
+            client.check_permission(request).await
+                .map_err(|e| format!("Failed to check permission: {}", e))
+        } else {
+            Err("Heimdall client not initialized".to_string())
+        }
+    }
+
     /// Select model via Skuld (convenience method)
     pub async fn select_skuld_model(&self, request: crate::clients::skuld::skuld::SelectModelRequest) -> Result<crate::clients::skuld::skuld::SelectModelResponse, String> {
         let mut client_guard = self.skuld_client.write().await;
@@ -1,4 +1,4 @@
-use tonic::transport::Channel;
+use vegvisir::TracedChannel;
 use anyhow::Result;
 use std::time::Duration;
 use super::ServiceClientConfig;
@@ -12,7 +12,7 @@ use skuld::{SelectModelRequest, SelectModelResponse};
 
 /// Client for Skuld service (LLM Model Selection)
 pub struct SkuldClient {
-    client: SkuldServiceClient<Channel>,
+    client: SkuldServiceClient<TracedChannel>,
 }
 
 impl SkuldClient {
@@ -23,7 +23,7 @@ impl SkuldClient {
             .connect_timeout(Duration::from_secs(5));
         
         let channel = endpoint.connect().await?;
-        let client = SkuldServiceClient::new(channel);
+        let client = SkuldServiceClient::new(vegvisir::traced(channel));
         
         Ok(Self { client })
     }
@@ -1,4 +1,4 @@
-use tonic::transport::Channel;
+use vegvisir::TracedChannel;
 use anyhow::Result;
 use std::time::Duration;
 use super::ServiceClientConfig;
@@ -12,7 +12,7 @@ use thor::{ThorAction, ThorResult};
 
 /// Client for Thor service
 pub struct ThorClient {
-    client: ThorServiceClient<Channel>,
+    client: ThorServiceClient<TracedChannel>,
 }
 
 impl ThorClient {
@@ -23,7 +23,7 @@ impl ThorClient {
             .connect_timeout(Duration::from_secs(5));
         
         let channel = endpoint.connect().await?;
-        let client = ThorServiceClient::new(channel);
+        let client = ThorServiceClient::new(vegvisir::traced(channel));
         
         Ok(Self { client })
     }
@@ -1,6 +1,8 @@
 pub mod odin {
     tonic::include_proto!("odin");
 }
+pub mod responsibility;
 pub mod server;
 
+pub use responsibility::*;
 pub use server::*;
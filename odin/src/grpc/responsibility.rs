@@ -0,0 +1,80 @@
+//! Odin's side of the Responsibility protocol: services and plugins that own a conversation give
+//! it back here (`ReturnResponsibility`, or `RejectResponsibility` if they cannot continue).
+
+use std::sync::Arc;
+
+use tonic::{Request, Response, Status};
+
+use crate::orchestration::HandoffStore;
+use crate::protocols::responsibility::responsibility::responsibility_service_server::ResponsibilityService;
+use crate::protocols::responsibility::responsibility::{
+    RejectResponsibilityRequest, RejectResponsibilityResponse, ReturnResponsibilityRequest, ReturnResponsibilityResponse,
+    TakeResponsibilityRequest, TakeResponsibilityResponse,
+};
+
+pub struct OdinResponsibilityService {
+    handoffs: Arc<HandoffStore>,
+}
+
+impl OdinResponsibilityService {
+    pub fn new(handoffs: Arc<HandoffStore>) -> Self {
+        Self { handoffs }
+    }
+}
+
+fn service_filter(service_name: &str) -> Option<&str> {
+    if service_name.is_empty() { None } else { Some(service_name) }
+}
+
+#[tonic::async_trait]
+impl ResponsibilityService for OdinResponsibilityService {
+    async fn take_responsibility(
+        &self,
+        _request: Request<TakeResponsibilityRequest>,
+    ) -> Result<Response<TakeResponsibilityResponse>, Status> {
+        // Odin hands out responsibility; nobody takes it from Odin
+        Ok(Response::new(TakeResponsibilityResponse {
+            accepted: false,
+            message: "Odin assigns responsibility; it cannot be taken".to_string(),
+            one_shot: false,
+        }))
+    }
+
+    async fn return_responsibility(
+        &self,
+        request: Request<ReturnResponsibilityRequest>,
+    ) -> Result<Response<ReturnResponsibilityResponse>, Status> {
+        let req = request.into_inner();
+        let released = self.handoffs.release(&req.request_id, service_filter(&req.service_name), &req.reason).await;
+        Ok(Response::new(match released {
+            Some(lease) => ReturnResponsibilityResponse {
+                acknowledged: true,
+                message: format!("{} no longer owns the conversation", lease.owner),
+            },
+            None => ReturnResponsibilityResponse {
+                acknowledged: false,
+                message: format!("No conversation owned for request {}", req.request_id),
+            },
+        }))
+    }
+
+    async fn reject_responsibility(
+        &self,
+        request: Request<RejectResponsibilityRequest>,
+    ) -> Result<Response<RejectResponsibilityResponse>, Status> {
+        // An owner that cannot continue gives the conversation back; the next turn is routed again
+        let req = request.into_inner();
+        let mut reason = format!("rejected: {}", req.reason);
+        if !req.suggested_alternatives.is_empty() {
+            reason.push_str(&format!(" (suggested: {})", req.suggested_alternatives.join(", ")));
+        }
+        let released = self.handoffs.release(&req.request_id, service_filter(&req.service_name), &reason).await;
+        Ok(Response::new(RejectResponsibilityResponse {
+            acknowledged: released.is_some(),
+            message: match released {
+                Some(lease) => format!("{} no longer owns the conversation", lease.owner),
+                None => format!("No conversation owned for request {}", req.request_id),
+            },
+        }))
+    }
+}
@@ -1,14 +1,31 @@
-use tonic::{transport::Server, Request, Response, Status};
-use tracing::info;
+use tonic::{transport::Server, Request, Response, Status, Streaming};
+use tracing::{info, Instrument, Span};
 use std::net::SocketAddr;
 use std::sync::Arc;
 
 use crate::grpc::odin;
+use crate::orchestration::{ProgressEvent, ProgressReporter, RoutineReply, RoutineRequestHandler};
 use odin::odin_service_server::{OdinService, OdinServiceServer};
+use odin::process_event::Event;
+use odin::process_stream_request::Message;
+use crate::protocols::responsibility::responsibility::responsibility_service_server::ResponsibilityServiceServer;
+
+/// Events buffered per `ProcessStream` before Odin waits for the client to read.
+const STREAM_BUFFER: usize = 64;
 
 pub struct OdinServiceImpl {
     request_processor: Arc<crate::orchestration::RequestProcessor>,
     action_orchestrator: Arc<crate::orchestration::ActionOrchestrator>,
+    agent: Option<AgentMode>,
+    handoffs: Option<Arc<crate::orchestration::HandoffStore>>,
+    confirmation: Option<Arc<crate::orchestration::ConfirmationGate>>,
+    routines: Option<Arc<crate::orchestration::RoutineEngine>>,
+}
+
+/// Agent loop and the catalog of tools it may call.
+pub struct AgentMode {
+    pub agent: Arc<crate::orchestration::AgentLoop>,
+    pub tools: Arc<crate::orchestration::ToolCatalog>,
 }
 
 impl OdinServiceImpl {
@@ -19,8 +36,224 @@ impl OdinServiceImpl {
         Self {
             request_processor,
             action_orchestrator,
+            agent: None,
+            handoffs: None,
+            confirmation: None,
+            routines: None,
         }
     }
+
+    /// Enable the routine RPCs, `ReportDeviceEvent` and `WatchNotifications`.
+    pub fn with_routine_engine(mut self, routines: Arc<crate::orchestration::RoutineEngine>) -> Self {
+        self.routines = Some(routines);
+        self
+    }
+
+    /// Hold risky actions until the user confirms them; enables `ConfirmAction`.
+    pub fn with_confirmation_gate(mut self, gate: Arc<crate::orchestration::ConfirmationGate>) -> Self {
+        self.confirmation = Some(gate);
+        self
+    }
+
+    /// Enable `GetHandoffState`.
+    pub fn with_handoff_store(mut self, handoffs: Arc<crate::orchestration::HandoffStore>) -> Self {
+        self.handoffs = Some(handoffs);
+        self
+    }
+
+    /// Enable `RunAgent`.
+    pub fn with_agent(mut self, agent: AgentMode) -> Self {
+        self.agent = Some(agent);
+        self
+    }
+}
+
+fn user_request(req: odin::ProcessRequest) -> crate::orchestration::UserRequest {
+    crate::orchestration::UserRequest {
+        request_id: req.request_id,
+        user_id: req.user_id,
+        device_id: req.device_id,
+        input: req.input,
+        input_type: req.input_type,
+        session_id: req.session_id,
+    }
+}
+
+/// Makes Odin's request id the root of the trace, so the spans of every service the request
+/// reaches end up in one trace.
+fn trace_request(span: &Span, request: &crate::orchestration::UserRequest) {
+    if !request.request_id.is_empty() {
+        vegvisir::set_trace_root(span, &request.request_id);
+    }
+    vegvisir::record_request_ids(span, &request.request_id, &request.user_id, &request.device_id);
+}
+
+/// Shared by `Process` and `ProcessStream`: route the request, then run (or hold for confirmation)
+/// the actions in its answer.
+async fn process_request(
+    request_processor: &crate::orchestration::RequestProcessor,
+    action_orchestrator: &crate::orchestration::ActionOrchestrator,
+    confirmation: Option<&crate::orchestration::ConfirmationGate>,
+    user_request: crate::orchestration::UserRequest,
+    progress: &ProgressReporter,
+) -> Result<odin::ProcessResponse, Status> {
+    let conversation = crate::orchestration::ConversationKey::from_request(&user_request);
+    let request_id = user_request.request_id.clone();
+
+    // Process request
+    let routed = request_processor.process_resolved(user_request.clone(), progress).await
+        .map_err(|e| Status::internal(format!("Request processing failed: {}", e)))?;
+    let mut response = routed.response;
+
+    // The request itself was held for confirmation; its question is not a plan to execute
+    if let Some(gate) = confirmation {
+        if let Some(pending) = gate.pending(&conversation).await.filter(|p| p.request_id == request_id) {
+            return Ok(odin::ProcessResponse {
+                response,
+                actions_taken: Vec::new(),
+                confirmation_id: pending.confirmation_id,
+                confirmation_expires_at: pending.expires_at_ms,
+            });
+        }
+    }
+
+    // A classified intent already ran its actions during routing; only an unclassified answer
+    // is scanned for actions by keyword
+    if routed.intent.is_some() {
+        return Ok(odin::ProcessResponse { response, ..Default::default() });
+    }
+    let action_plan = action_orchestrator.plan_actions(&response).await
+        .map_err(|e| Status::internal(format!("Action planning failed: {}", e)))?;
+
+    if let Some(gate) = confirmation {
+        if let Some(pending) = gate.check(&user_request, &action_plan, action_orchestrator).await {
+            progress.emit(ProgressEvent::confirmation_requested(&pending));
+            response.push_str("\n\n");
+            response.push_str(&pending.prompt());
+            return Ok(odin::ProcessResponse {
+                response,
+                actions_taken: Vec::new(),
+                confirmation_id: pending.confirmation_id,
+                confirmation_expires_at: pending.expires_at_ms,
+            });
+        }
+    }
+
+    // Execute actions
+    let actions_taken = action_orchestrator.execute_actions_with_progress(action_plan, progress).await
+        .map_err(|e| Status::internal(format!("Action execution failed: {}", e)))?;
+
+    Ok(odin::ProcessResponse {
+        response,
+        actions_taken,
+        ..Default::default()
+    })
+}
+
+/// The `Process` path for requests Odin makes on a user's behalf, e.g. the steps of routines.
+pub struct ProcessPipeline {
+    request_processor: Arc<crate::orchestration::RequestProcessor>,
+    action_orchestrator: Arc<crate::orchestration::ActionOrchestrator>,
+    confirmation: Option<Arc<crate::orchestration::ConfirmationGate>>,
+}
+
+impl ProcessPipeline {
+    pub fn new(
+        request_processor: Arc<crate::orchestration::RequestProcessor>,
+        action_orchestrator: Arc<crate::orchestration::ActionOrchestrator>,
+        confirmation: Option<Arc<crate::orchestration::ConfirmationGate>>,
+    ) -> Self {
+        Self { request_processor, action_orchestrator, confirmation }
+    }
+}
+
+#[tonic::async_trait]
+impl RoutineRequestHandler for ProcessPipeline {
+    async fn handle(&self, request: crate::orchestration::UserRequest) -> Result<RoutineReply, String> {
+        let span = tracing::info_span!("routine_step");
+        trace_request(&span, &request);
+        let response = process_request(
+            &self.request_processor,
+            &self.action_orchestrator,
+            self.confirmation.as_deref(),
+            request,
+            &ProgressReporter::disabled(),
+        )
+        .instrument(span)
+        .await
+        .map_err(|status| status.message().to_string())?;
+        Ok(RoutineReply {
+            response: response.response,
+            confirmation_id: Some(response.confirmation_id).filter(|id| !id.is_empty()),
+        })
+    }
+}
+
+fn process_event(request_id: &str, event: Event) -> odin::ProcessEvent {
+    odin::ProcessEvent { request_id: request_id.to_string(), event: Some(event) }
+}
+
+fn progress_event(request_id: &str, event: ProgressEvent) -> odin::ProcessEvent {
+    let event = match event {
+        ProgressEvent::Routing { service, reason } => Event::Routing(odin::RoutingDecision { service, reason }),
+        ProgressEvent::Sources { sources } => Event::Sources(odin::RetrievedSources {
+            sources: sources
+                .into_iter()
+                .map(|source| odin::RetrievedSource { id: source.id, score: source.score, excerpt: source.excerpt })
+                .collect(),
+        }),
+        ProgressEvent::TokenDelta { text } => Event::Token(odin::TokenDelta { text }),
+        ProgressEvent::ActionStarted { action_id, action_type, service } => {
+            Event::ActionStarted(odin::ActionStarted { action_id, action_type, service })
+        }
+        ProgressEvent::ActionFinished { action_id, status, error } => Event::ActionFinished(odin::ActionFinished {
+            action_id,
+            status: status.as_str().to_string(),
+            error: error.unwrap_or_default(),
+        }),
+        ProgressEvent::ConfirmationRequested { confirmation_id, prompt, expires_at_ms } => {
+            Event::ConfirmationRequested(odin::ConfirmationRequested { confirmation_id, prompt, expires_at: expires_at_ms })
+        }
+    };
+    process_event(request_id, event)
+}
+
+/// Resolves when the client asks to cancel, with its reason. Closing the sending side only ends
+/// the client's input; the request keeps running.
+async fn cancel_requested(mut inbound: Streaming<odin::ProcessStreamRequest>) -> String {
+    loop {
+        match inbound.message().await {
+            Ok(Some(odin::ProcessStreamRequest { message: Some(Message::Cancel(cancel)) })) => {
+                return if cancel.reason.is_empty() { "cancelled by client".to_string() } else { cancel.reason };
+            }
+            Ok(Some(_)) => continue,
+            Ok(None) => std::future::pending::<()>().await,
+            Err(e) => return format!("client stream failed: {}", e),
+        }
+    }
+}
+
+fn routine_error(error: crate::orchestration::OrchestrationError) -> Status {
+    match error {
+        crate::orchestration::OrchestrationError::InvalidRoutine(msg) => Status::invalid_argument(msg),
+        other => Status::internal(other.to_string()),
+    }
+}
+
+fn device_event(report: odin::DeviceEventReport) -> Result<crate::orchestration::DeviceEvent, String> {
+    let payload = if report.payload_json.is_empty() {
+        serde_json::Map::new()
+    } else {
+        serde_json::from_str(&report.payload_json).map_err(|e| format!("payload_json is not a JSON object: {}", e))?
+    };
+    let occurred_at_ms = if report.occurred_at > 0 { report.occurred_at } else { chrono::Utc::now().timestamp_millis() };
+    Ok(crate::orchestration::DeviceEvent {
+        device_id: report.device_id,
+        event_type: report.event_type,
+        source: report.source,
+        payload,
+        occurred_at_ms,
+    })
 }
 
 #[tonic::async_trait]
@@ -29,38 +262,318 @@ impl OdinService for OdinServiceImpl {
         &self,
         request: Request<odin::ProcessRequest>,
     ) -> Result<Response<odin::ProcessResponse>, Status> {
-        let req = request.into_inner();
-        
-        let user_request = crate::orchestration::UserRequest {
-            request_id: req.request_id.clone(),
-            user_id: req.user_id,
-            device_id: req.device_id,
-            input: req.input,
-            input_type: req.input_type,
+        let user_request = user_request(request.into_inner());
+        trace_request(&Span::current(), &user_request);
+        let response = process_request(
+            &self.request_processor,
+            &self.action_orchestrator,
+            self.confirmation.as_deref(),
+            user_request,
+            &ProgressReporter::disabled(),
+        )
+        .await?;
+        Ok(Response::new(response))
+    }
+
+    type ProcessStreamStream = tokio_stream::wrappers::ReceiverStream<Result<odin::ProcessEvent, Status>>;
+
+    async fn process_stream(
+        &self,
+        request: Request<Streaming<odin::ProcessStreamRequest>>,
+    ) -> Result<Response<Self::ProcessStreamStream>, Status> {
+        let mut inbound = request.into_inner();
+        let start = match inbound.message().await? {
+            Some(odin::ProcessStreamRequest { message: Some(Message::Start(start)) }) => start,
+            _ => return Err(Status::invalid_argument("ProcessStream must begin with a start message")),
         };
+        let user_request = user_request(start);
+        trace_request(&Span::current(), &user_request);
+        let request_id = user_request.request_id.clone();
+        let request_processor = self.request_processor.clone();
+        let action_orchestrator = self.action_orchestrator.clone();
+        let confirmation = self.confirmation.clone();
+        let (tx, rx) = tokio::sync::mpsc::channel(STREAM_BUFFER);
+
+        tokio::spawn(async move {
+            let (progress, mut events) = ProgressReporter::channel();
+            let mut work = Box::pin(process_request(
+                &request_processor,
+                &action_orchestrator,
+                confirmation.as_deref(),
+                user_request,
+                &progress,
+            ));
+            let cancel = cancel_requested(inbound);
+            tokio::pin!(cancel);
 
-        // Process request
-        let response = self.request_processor.process(user_request).await
-            .map_err(|e| Status::internal(format!("Request processing failed: {}", e)))?;
+            let last = loop {
+                tokio::select! {
+                    biased;
+                    Some(event) = events.recv() => {
+                        if tx.send(Ok(progress_event(&request_id, event))).await.is_err() {
+                            break None;
+                        }
+                    }
+                    result = &mut work => {
+                        // Events emitted before the result come first
+                        while let Ok(event) = events.try_recv() {
+                            let _ = tx.send(Ok(progress_event(&request_id, event))).await;
+                        }
+                        break Some(match result {
+                            Ok(response) => Event::Completed(response),
+                            Err(status) => Event::Failed(odin::ProcessFailed { message: status.message().to_string() }),
+                        });
+                    }
+                    reason = &mut cancel => {
+                        info!("Request {} cancelled: {}", request_id, reason);
+                        break Some(Event::Cancelled(odin::ProcessCancelled { reason }));
+                    }
+                    _ = tx.closed() => break None,
+                }
+            };
+            // Dropping the unfinished work cancels its in-flight downstream calls and plan steps
+            drop(work);
+            match last {
+                Some(event) => {
+                    let _ = tx.send(Ok(process_event(&request_id, event))).await;
+                }
+                None => info!("Client of request {} disconnected; processing stopped", request_id),
+            }
+        }.instrument(Span::current()));
 
-        // Generate action plan
-        let action_plan = self.action_orchestrator.plan_actions(&response).await
-            .map_err(|e| Status::internal(format!("Action planning failed: {}", e)))?;
+        Ok(Response::new(tokio_stream::wrappers::ReceiverStream::new(rx)))
+    }
 
-        // Execute actions
-        let actions_taken = self.action_orchestrator.execute_actions(action_plan).await
+    async fn confirm_action(
+        &self,
+        request: Request<odin::ConfirmActionRequest>,
+    ) -> Result<Response<odin::ProcessResponse>, Status> {
+        let Some(ref gate) = self.confirmation else {
+            return Err(Status::failed_precondition("Confirmation of risky actions is disabled"));
+        };
+        let req = request.into_inner();
+        let outcome = gate.decide(&req.confirmation_id, &req.user_id, &req.device_id, req.approved).await
+            .ok_or_else(|| Status::not_found(format!("No pending confirmation {}", req.confirmation_id)))?;
+        // The approved plan continues the trace of the request that was held
+        let span = Span::current();
+        vegvisir::set_trace_root(&span, &outcome.pending.request_id);
+        vegvisir::record_request_ids(&span, &outcome.pending.request_id, &req.user_id, &req.device_id);
+        let response = outcome.execute(&self.action_orchestrator).await
             .map_err(|e| Status::internal(format!("Action execution failed: {}", e)))?;
+        let actions_taken = if outcome.decision == crate::orchestration::ConfirmationDecision::Approved {
+            outcome.pending.plan.actions.iter().map(|action| action.action_type.clone()).collect()
+        } else {
+            Vec::new()
+        };
 
         Ok(Response::new(odin::ProcessResponse {
             response,
             actions_taken,
+            ..Default::default()
         }))
     }
+
+    async fn run_agent(
+        &self,
+        request: Request<odin::ProcessRequest>,
+    ) -> Result<Response<odin::AgentResponse>, Status> {
+        let Some(ref mode) = self.agent else {
+            return Err(Status::failed_precondition("Agent mode is disabled"));
+        };
+        let user_request = user_request(request.into_inner());
+        trace_request(&Span::current(), &user_request);
+        let tools = mode.tools.tools().await;
+        let outcome = mode.agent.run(&user_request, &tools).await;
+        let trace_json = serde_json::to_string(&outcome.trace)
+            .map_err(|e| Status::internal(format!("Agent trace serialization failed: {}", e)))?;
+
+        Ok(Response::new(odin::AgentResponse {
+            response: outcome.answer,
+            stop_reason: outcome.trace.stop_reason.as_str().to_string(),
+            trace_json,
+            steps: outcome.trace.steps,
+            tokens_used: outcome.trace.tokens_used,
+        }))
+    }
+
+    async fn upsert_routine(
+        &self,
+        request: Request<odin::UpsertRoutineRequest>,
+    ) -> Result<Response<odin::RoutineResponse>, Status> {
+        let Some(ref engine) = self.routines else {
+            return Err(Status::failed_precondition("Routines are disabled"));
+        };
+        let req = request.into_inner();
+        let mut routine: crate::orchestration::Routine = serde_json::from_str(&req.routine_json)
+            .map_err(|e| Status::invalid_argument(format!("routine_json is not a routine: {}", e)))?;
+        routine.owner = crate::orchestration::RoutineOwner { user_id: req.user_id, device_id: req.device_id };
+        let routine = engine.store().upsert(routine).await.map_err(routine_error)?;
+        let routine_json = serde_json::to_string(&routine)
+            .map_err(|e| Status::internal(format!("Routine serialization failed: {}", e)))?;
+        Ok(Response::new(odin::RoutineResponse { routine_json }))
+    }
+
+    async fn list_routines(
+        &self,
+        request: Request<odin::ListRoutinesRequest>,
+    ) -> Result<Response<odin::ListRoutinesResponse>, Status> {
+        let Some(ref engine) = self.routines else {
+            return Err(Status::failed_precondition("Routines are disabled"));
+        };
+        let routines = engine.store().list(&request.into_inner().user_id).await;
+        let routines_json = serde_json::to_string(&routines)
+            .map_err(|e| Status::internal(format!("Routine serialization failed: {}", e)))?;
+        Ok(Response::new(odin::ListRoutinesResponse { routines_json }))
+    }
+
+    async fn delete_routine(
+        &self,
+        request: Request<odin::RoutineRef>,
+    ) -> Result<Response<odin::DeleteRoutineResponse>, Status> {
+        let Some(ref engine) = self.routines else {
+            return Err(Status::failed_precondition("Routines are disabled"));
+        };
+        let req = request.into_inner();
+        let deleted = engine.store().remove(&req.user_id, &req.routine_id).await;
+        Ok(Response::new(odin::DeleteRoutineResponse { deleted }))
+    }
+
+    async fn set_routine_enabled(
+        &self,
+        request: Request<odin::SetRoutineEnabledRequest>,
+    ) -> Result<Response<odin::RoutineResponse>, Status> {
+        let Some(ref engine) = self.routines else {
+            return Err(Status::failed_precondition("Routines are disabled"));
+        };
+        let req = request.into_inner();
+        let routine = engine.store().set_enabled(&req.user_id, &req.routine_id, req.enabled).await
+            .ok_or_else(|| Status::not_found(format!("No routine {}", req.routine_id)))?;
+        let routine_json = serde_json::to_string(&routine)
+            .map_err(|e| Status::internal(format!("Routine serialization failed: {}", e)))?;
+        Ok(Response::new(odin::RoutineResponse { routine_json }))
+    }
+
+    async fn get_routine_history(
+        &self,
+        request: Request<odin::RoutineRef>,
+    ) -> Result<Response<odin::RoutineHistoryResponse>, Status> {
+        let Some(ref engine) = self.routines else {
+            return Err(Status::failed_precondition("Routines are disabled"));
+        };
+        let req = request.into_inner();
+        let runs = engine.store().history(&req.user_id, &req.routine_id).await
+            .ok_or_else(|| Status::not_found(format!("No routine {}", req.routine_id)))?;
+        let runs_json = serde_json::to_string(&runs)
+            .map_err(|e| Status::internal(format!("Routine history serialization failed: {}", e)))?;
+        Ok(Response::new(odin::RoutineHistoryResponse { runs_json }))
+    }
+
+    async fn test_fire_routine(
+        &self,
+        request: Request<odin::TestFireRoutineRequest>,
+    ) -> Result<Response<odin::RoutineRunResponse>, Status> {
+        let Some(ref engine) = self.routines else {
+            return Err(Status::failed_precondition("Routines are disabled"));
+        };
+        let req = request.into_inner();
+        let event = if req.event_json.is_empty() {
+            None
+        } else {
+            Some(serde_json::from_str(&req.event_json).map_err(|e| Status::invalid_argument(format!("event_json is not a device event: {}", e)))?)
+        };
+        let run = engine.test_fire(&req.user_id, &req.routine_id, event, req.check_conditions).await
+            .ok_or_else(|| Status::not_found(format!("No routine {}", req.routine_id)))?;
+        let run_json = serde_json::to_string(&run)
+            .map_err(|e| Status::internal(format!("Routine run serialization failed: {}", e)))?;
+        Ok(Response::new(odin::RoutineRunResponse { run_json }))
+    }
+
+    async fn report_device_event(
+        &self,
+        request: Request<odin::DeviceEventReport>,
+    ) -> Result<Response<odin::DeviceEventResponse>, Status> {
+        let Some(ref engine) = self.routines else {
+            return Err(Status::failed_precondition("Routines are disabled"));
+        };
+        let engine = engine.clone();
+        let event = device_event(request.into_inner()).map_err(Status::invalid_argument)?;
+        let triggered_routine_ids = engine.triggered_by(&event).await.into_iter().map(|routine| routine.id).collect();
+        // The reporting device does not wait for the routines' steps
+        tokio::spawn(async move {
+            engine.handle_event(event).await;
+        }.instrument(Span::current()));
+        Ok(Response::new(odin::DeviceEventResponse { triggered_routine_ids }))
+    }
+
+    type WatchNotificationsStream = tokio_stream::wrappers::ReceiverStream<Result<odin::RoutineNotification, Status>>;
+
+    async fn watch_notifications(
+        &self,
+        request: Request<odin::WatchNotificationsRequest>,
+    ) -> Result<Response<Self::WatchNotificationsStream>, Status> {
+        let Some(ref engine) = self.routines else {
+            return Err(Status::failed_precondition("Routines are disabled"));
+        };
+        let user_id = request.into_inner().user_id;
+        let mut notifications = engine.subscribe();
+        let (tx, rx) = tokio::sync::mpsc::channel(STREAM_BUFFER);
+
+        tokio::spawn(async move {
+            loop {
+                let notification = tokio::select! {
+                    received = notifications.recv() => match received {
+                        Ok(notification) => notification,
+                        Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                            tracing::warn!("Notification watcher of user {} skipped {} notification(s)", user_id, skipped);
+                            continue;
+                        }
+                        Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                    },
+                    _ = tx.closed() => break,
+                };
+                if notification.user_id != user_id {
+                    continue;
+                }
+                let message = odin::RoutineNotification {
+                    routine_id: notification.routine_id,
+                    routine_name: notification.routine_name,
+                    message: notification.message,
+                    confirmation_id: notification.confirmation_id.unwrap_or_default(),
+                    created_at: notification.created_at_ms,
+                };
+                if tx.send(Ok(message)).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Response::new(tokio_stream::wrappers::ReceiverStream::new(rx)))
+    }
+
+    async fn get_handoff_state(
+        &self,
+        _request: Request<odin::HandoffStateRequest>,
+    ) -> Result<Response<odin::HandoffStateResponse>, Status> {
+        let Some(ref handoffs) = self.handoffs else {
+            return Err(Status::failed_precondition("Responsibility handoff is not enabled"));
+        };
+        let state_json = serde_json::to_string(&handoffs.snapshot().await)
+            .map_err(|e| Status::internal(format!("Handoff state serialization failed: {}", e)))?;
+        Ok(Response::new(odin::HandoffStateResponse { state_json }))
+    }
 }
 
 pub struct GrpcServerDependencies {
     pub request_processor: Arc<crate::orchestration::RequestProcessor>,
     pub action_orchestrator: Arc<crate::orchestration::ActionOrchestrator>,
+    /// Set when the agent mode is enabled.
+    pub agent: Option<AgentMode>,
+    /// Conversation ownership; enables the Responsibility service for owners returning it.
+    pub handoffs: Option<Arc<crate::orchestration::HandoffStore>>,
+    /// Set when risky actions need the user's confirmation.
+    pub confirmation: Option<Arc<crate::orchestration::ConfirmationGate>>,
+    /// Set when routines are enabled.
+    pub routines: Option<Arc<crate::orchestration::RoutineEngine>>,
 }
 
 pub async fn start_grpc_server(
@@ -69,13 +582,30 @@ pub async fn start_grpc_server(
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     info!("Starting Odin gRPC server on {}", addr);
 
-    let odin_service = OdinServiceImpl::new(
+    let mut odin_service = OdinServiceImpl::new(
         deps.request_processor,
         deps.action_orchestrator,
     );
+    if let Some(agent) = deps.agent {
+        odin_service = odin_service.with_agent(agent);
+    }
+    if let Some(ref handoffs) = deps.handoffs {
+        odin_service = odin_service.with_handoff_store(handoffs.clone());
+    }
+    if let Some(gate) = deps.confirmation {
+        odin_service = odin_service.with_confirmation_gate(gate);
+    }
+    if let Some(routines) = deps.routines {
+        odin_service = odin_service.with_routine_engine(routines);
+    }
+    let responsibility_service = deps
+        .handoffs
+        .map(|handoffs| ResponsibilityServiceServer::new(super::OdinResponsibilityService::new(handoffs)));
 
     Server::builder()
+        .layer(vegvisir::ServerTraceLayer)
         .add_service(OdinServiceServer::new(odin_service))
+        .add_optional_service(responsibility_service)
         .serve(addr)
         .await?;
 
@@ -1,11 +1,17 @@
 use tracing::info;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
 use std::path::PathBuf;
 use std::sync::Arc;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+    // Spans go to Vegvisir; they are exported once the settings are loaded
+    let (trace_layer, trace_exporter) = vegvisir::layer("odin");
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(tracing_subscriber::EnvFilter::from_default_env()))
+        .with(trace_layer)
         .init();
 
     info!("Odin Main Orchestrator starting...");
@@ -30,6 +36,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let settings = settings_manager.get().await;
     let grpc_port = settings.grpc_port;
     info!("Settings loaded, gRPC port: {}", grpc_port);
+    trace_exporter.start(&settings.trace_export);
     
     // Wrap settings in Arc<RwLock> for sharing
     let settings_arc = Arc::new(tokio::sync::RwLock::new(settings));
@@ -46,11 +53,54 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Initialize responsibility manager
     let capability_cache = protocol_manager.get_cache();
-    let responsibility_manager = Arc::new(odin::orchestration::responsibility::ResponsibilityManager::new(
+    let conversation_store = Arc::new(odin::orchestration::ConversationStore::from_config(
+        &settings_arc.read().await.conversation,
+    ));
+    // Conversation ownership (Take/Return/Reject) survives restarts in a state file next to the settings
+    let handoff_default_path = config_path
+        .parent()
+        .map(|dir| dir.join("handoff_state.json"))
+        .unwrap_or_else(|| PathBuf::from("handoff_state.json"));
+    let handoff_config = settings_arc.read().await.handoff.clone();
+    let handoffs = Arc::new(
+        odin::orchestration::HandoffStore::from_config(&handoff_config, &handoff_default_path).unwrap_or_else(|e| {
+            tracing::warn!("Failed to load handoff state, starting without persisted ownership: {}", e);
+            odin::orchestration::HandoffStore::new(
+                std::time::Duration::from_secs(handoff_config.lease_secs),
+                handoff_config.max_history,
+            )
+        }),
+    );
+    let plugin_manager = Arc::new(odin::plugins::PluginManager::new());
+    let mut responsibility_manager = odin::orchestration::responsibility::ResponsibilityManager::new(
         capability_cache,
         protocol_manager.clone(),
         client_manager.clone(),
-    ));
+    )
+    .with_conversation_store(conversation_store)
+    .with_handoff_store(handoffs.clone())
+    .with_plugin_manager(plugin_manager.clone());
+    // Intent recognition via Geri; keyword routing remains the fallback when Geri is unreachable
+    let intent_config = settings_arc.read().await.intent.clone();
+    if intent_config.enabled {
+        let classifier = odin::orchestration::IntentClassifier::new(client_manager.clone(), intent_config);
+        responsibility_manager = responsibility_manager.with_intent_classifier(Arc::new(classifier));
+    }
+    // Risky actions (deleting, shell commands, other devices, purchases) wait for the user's yes/no
+    let confirmation_config = settings_arc.read().await.confirmation.clone();
+    let confirmation_gate = if confirmation_config.enabled {
+        if confirmation_config.signing_secret.is_empty() {
+            tracing::warn!("confirmation.signing_secret is empty: Thor refuses confirmed risky actions without a signed token");
+        }
+        let gate = odin::orchestration::ConfirmationGate::from_config(&confirmation_config)
+            .with_audit_logger(Arc::new(odin::orchestration::TracingAuditLogger));
+        let gate = Arc::new(gate);
+        responsibility_manager = responsibility_manager.with_confirmation_gate(gate.clone());
+        Some(gate)
+    } else {
+        None
+    };
+    let responsibility_manager = Arc::new(responsibility_manager);
     
     // Discover capabilities from all services and enabled plugins (Frigg, Valkyries)
     protocol_manager.discover_all_capabilities().await?;
@@ -66,7 +116,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     device_scheduler.start();
 
     // Bootstrap Frigg/Valkyries as remote plugins when enabled and URL set
-    let plugin_manager = Arc::new(odin::plugins::PluginManager::new());
     odin::bootstrap::bootstrap_frigg_valkyries_plugins(plugin_manager.as_ref(), protocol_manager.as_ref(), &settings_arc).await?;
     
     // Initialize request processor with responsibility manager
@@ -79,11 +128,59 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         client_manager.clone(),
     ));
     
+    // Agent mode: Geri calls Thor actions, Loki scripts and device tools (checked by Heimdall)
+    let agent_config = settings_arc.read().await.agent.clone();
+    let agent = if agent_config.enabled {
+        let agent_loop = odin::orchestration::AgentLoop::new(
+            client_manager.clone(),
+            Arc::new(odin::orchestration::ServiceActionExecutor::new(client_manager.clone())),
+            client_manager.clone(),
+            agent_config,
+        )
+        .with_audit_logger(Arc::new(odin::orchestration::TracingAuditLogger));
+        let tools = odin::orchestration::ToolCatalog::new(protocol_manager.get_cache())
+            .with_client_manager(client_manager.clone());
+        Some(odin::grpc::AgentMode { agent: Arc::new(agent_loop), tools: Arc::new(tools) })
+    } else {
+        None
+    };
+
+    // Routines: cron- and device-event-triggered automations, run through the Process path as their owner
+    let routines_config = settings_arc.read().await.routines.clone();
+    let routines = if routines_config.enabled {
+        let routines_default_path = config_path
+            .parent()
+            .map(|dir| dir.join("routines.json"))
+            .unwrap_or_else(|| PathBuf::from("routines.json"));
+        let store = odin::orchestration::RoutineStore::from_config(&routines_config, &routines_default_path).unwrap_or_else(|e| {
+            tracing::warn!("Failed to load routines, starting without stored routines: {}", e);
+            odin::orchestration::RoutineStore::new(routines_config.max_history)
+        });
+        let pipeline = odin::grpc::ProcessPipeline::new(
+            request_processor.clone(),
+            action_orchestrator.clone(),
+            confirmation_gate.clone(),
+        );
+        let engine = Arc::new(odin::orchestration::RoutineEngine::from_config(
+            &routines_config,
+            Arc::new(store),
+            Arc::new(pipeline),
+        ));
+        engine.start();
+        Some(engine)
+    } else {
+        None
+    };
+
     // Start gRPC server
     let addr = std::net::SocketAddr::from(([0, 0, 0, 0], grpc_port));
     let deps = odin::grpc::GrpcServerDependencies {
         request_processor,
         action_orchestrator,
+        agent,
+        handoffs: Some(handoffs),
+        confirmation: confirmation_gate,
+        routines,
     };
     let server_handle = tokio::spawn(async move {
         if let Err(e) = odin::grpc::start_grpc_server(addr, deps).await {
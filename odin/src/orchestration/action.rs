@@ -1,26 +1,179 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use uuid::Uuid;
 
-/// Ordered list of actions produced by [`ActionOrchestrator::plan_actions`].
+use super::error::OrchestrationError;
+use super::executor::{parameter_references, ActionExecutor, ExecutionReport, PlanExecutor, ServiceActionExecutor, StepStatus};
+use super::progress::{ProgressEvent, ProgressReporter};
+
+/// Actions produced by [`ActionOrchestrator::plan_actions`], forming a DAG via [`Action::depends_on`].
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ActionPlan {
     pub actions: Vec<Action>,
 }
 
-/// Single action to be executed by a service (e.g. Thor).
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl ActionPlan {
+    /// Checks that ids are unique, dependencies exist and form no cycle, and that parameter
+    /// references (`${a1.result.path}`) only point to steps this step (transitively) depends on.
+    pub fn validate(&self) -> Result<(), OrchestrationError> {
+        let invalid = |msg: String| Err(OrchestrationError::InvalidPlan(msg));
+        let mut ids = HashSet::new();
+        for action in &self.actions {
+            if action.action_id.is_empty() {
+                return invalid("action without action_id".to_string());
+            }
+            if !ids.insert(action.action_id.as_str()) {
+                return invalid(format!("duplicate action_id '{}'", action.action_id));
+            }
+        }
+        for action in &self.actions {
+            for dep in &action.depends_on {
+                if !ids.contains(dep.as_str()) {
+                    return invalid(format!("'{}' depends on unknown action '{}'", action.action_id, dep));
+                }
+            }
+        }
+        let order = self.topological_order().ok_or_else(|| OrchestrationError::InvalidPlan("dependency cycle".to_string()))?;
+
+        // Ancestors in topological order, so every dependency is complete before its dependents
+        let by_id: HashMap<&str, &Action> = self.actions.iter().map(|a| (a.action_id.as_str(), a)).collect();
+        let mut ancestors: HashMap<&str, HashSet<&str>> = HashMap::new();
+        for id in order {
+            let action = by_id[id];
+            let mut own = HashSet::new();
+            for dep in &action.depends_on {
+                own.insert(dep.as_str());
+                own.extend(ancestors[dep.as_str()].iter().copied());
+            }
+            let mut referenced = parameter_references(&action.parameters);
+            if let Some(compensation) = &action.compensation {
+                referenced.extend(parameter_references(&compensation.parameters));
+            }
+            for reference in referenced {
+                // A compensation may use the output of its own step
+                let own_output = reference == action.action_id && action.compensation.is_some();
+                if !own.contains(reference.as_str()) && !own_output {
+                    return invalid(format!("'{}' references '{}' without depending on it", action.action_id, reference));
+                }
+            }
+            ancestors.insert(id, own);
+        }
+        Ok(())
+    }
+
+    /// Action ids in an order where every action comes after its dependencies; `None` on a cycle.
+    pub fn topological_order(&self) -> Option<Vec<&str>> {
+        let mut remaining: HashMap<&str, usize> = self.actions.iter().map(|a| (a.action_id.as_str(), a.depends_on.len())).collect();
+        let mut order = Vec::with_capacity(self.actions.len());
+        while order.len() < self.actions.len() {
+            let ready: Vec<&str> = self
+                .actions
+                .iter()
+                .map(|a| a.action_id.as_str())
+                .filter(|id| remaining.get(id) == Some(&0))
+                .collect();
+            if ready.is_empty() {
+                return None;
+            }
+            for id in ready {
+                remaining.remove(id);
+                for action in &self.actions {
+                    if action.depends_on.iter().any(|dep| dep == id) {
+                        if let Some(count) = remaining.get_mut(action.action_id.as_str()) {
+                            *count -= 1;
+                        }
+                    }
+                }
+                order.push(id);
+            }
+        }
+        Some(order)
+    }
+}
+
+/// Single action (plan step) to be executed by a service (e.g. Thor).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Action {
     pub action_id: String,
     pub action_type: String,
-    /// Target service: `"thor"`, `"freki"`, `"geri"`, etc.
+    /// Target service: `"thor"`, `"loki"`, `"freki"`, `"geri"`.
     pub service: String,
+    /// Parameters; string values may reference earlier outputs, e.g. `"${a1.result.path}"`.
     pub parameters: serde_json::Value,
+    /// Ids of actions that must succeed before this one starts.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Timeout, retries and failure handling for this step.
+    #[serde(default)]
+    pub policy: StepPolicy,
+    /// Undo step, run when a later failure compensates the plan (see [`FailureMode::Compensate`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compensation: Option<Box<Action>>,
+    /// Set once the user confirmed the step. Thor steps are sent with a token signed for the
+    /// resolved action instead (see
+    /// [`ConfirmationGate::with_signing_secret`](super::ConfirmationGate::with_signing_secret));
+    /// Thor refuses risky actions without a valid one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirmation_id: Option<String>,
+}
+
+/// Timeout and retry policy of a plan step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepPolicy {
+    /// Timeout per attempt; `None` waits for the service's own timeout.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Additional attempts after a failure or timeout.
+    #[serde(default)]
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled for each further retry.
+    #[serde(default = "default_retry_delay_ms")]
+    pub retry_delay_ms: u64,
+    /// Upper bound for the doubled retry delay.
+    #[serde(default = "default_max_retry_delay_ms")]
+    pub max_retry_delay_ms: u64,
+    #[serde(default)]
+    pub on_failure: FailureMode,
+}
+
+fn default_retry_delay_ms() -> u64 {
+    500
+}
+
+fn default_max_retry_delay_ms() -> u64 {
+    30_000
+}
+
+impl Default for StepPolicy {
+    fn default() -> Self {
+        Self {
+            timeout_ms: None,
+            max_retries: 0,
+            retry_delay_ms: default_retry_delay_ms(),
+            max_retry_delay_ms: default_max_retry_delay_ms(),
+            on_failure: FailureMode::default(),
+        }
+    }
+}
+
+/// What happens to the rest of the plan when a step finally fails.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureMode {
+    /// Start no further steps; running steps finish.
+    #[default]
+    Abort,
+    /// Abort and run the compensations of all succeeded steps, newest first.
+    Compensate,
+    /// Skip only the steps depending on this one.
+    Continue,
 }
 
 /// Plans and executes actions via Thor; maps request text to actions.
 pub struct ActionOrchestrator {
     client_manager: Option<Arc<crate::clients::manager::ClientManager>>,
+    plan_executor: Option<PlanExecutor>,
 }
 
 impl ActionOrchestrator {
@@ -28,17 +181,81 @@ impl ActionOrchestrator {
     pub fn new() -> Self {
         Self {
             client_manager: None,
+            plan_executor: None,
         }
     }
 
     /// Orchestrator with Thor client for executing actions.
     pub fn new_with_client(client_manager: Arc<crate::clients::manager::ClientManager>) -> Self {
+        let executor = Arc::new(ServiceActionExecutor::new(client_manager.clone()));
         Self {
             client_manager: Some(client_manager),
+            plan_executor: Some(PlanExecutor::new(executor)),
         }
     }
 
-    /// Generate action plan from request text (XML Task-based).
+    /// Execute plans with a custom step executor (e.g. a mock in tests).
+    pub fn with_executor(mut self, executor: Arc<dyn ActionExecutor>) -> Self {
+        self.plan_executor = Some(PlanExecutor::new(executor));
+        self
+    }
+
+    /// Limit how many independent steps of a plan run at the same time.
+    pub fn with_max_parallel(mut self, max_parallel: usize) -> Self {
+        self.plan_executor = self.plan_executor.map(|executor| executor.with_max_parallel(max_parallel));
+        self
+    }
+
+    /// Whether [`execute_plan`](Self::execute_plan) can run steps.
+    pub fn can_execute(&self) -> bool {
+        self.plan_executor.is_some()
+    }
+
+    /// Execute the plan as a DAG: independent steps run concurrently across services, steps
+    /// wait for their dependencies, and failures abort, compensate or skip dependents.
+    pub async fn execute_plan(&self, plan: &ActionPlan) -> Result<ExecutionReport, OrchestrationError> {
+        self.execute_plan_with_progress(plan, &ProgressReporter::disabled()).await
+    }
+
+    /// Like [`execute_plan`](Self::execute_plan), reporting when each step starts and finishes.
+    pub async fn execute_plan_with_progress(
+        &self,
+        plan: &ActionPlan,
+        progress: &ProgressReporter,
+    ) -> Result<ExecutionReport, OrchestrationError> {
+        let executor = self
+            .plan_executor
+            .as_ref()
+            .ok_or_else(|| OrchestrationError::ActionFailed("Client manager not available".to_string()))?;
+        executor.execute_with_progress(plan, progress).await
+    }
+
+    /// Like [`execute_plan_with_progress`](Self::execute_plan_with_progress), with every resolved
+    /// step run through the executor `wrap` builds around the orchestrator's.
+    pub async fn execute_plan_wrapped(
+        &self,
+        plan: &ActionPlan,
+        progress: &ProgressReporter,
+        wrap: impl FnOnce(Arc<dyn ActionExecutor>) -> Arc<dyn ActionExecutor>,
+    ) -> Result<ExecutionReport, OrchestrationError> {
+        let executor = self
+            .plan_executor
+            .as_ref()
+            .ok_or_else(|| OrchestrationError::ActionFailed("Client manager not available".to_string()))?;
+        executor.wrap(wrap).execute_with_progress(plan, progress).await
+    }
+
+    /// Run the plan as a dry run: every step reports what it would do (Thor also its risk), nothing is executed.
+    pub async fn dry_run_plan(&self, plan: &ActionPlan) -> Result<ExecutionReport, OrchestrationError> {
+        let executor = self
+            .plan_executor
+            .as_ref()
+            .ok_or_else(|| OrchestrationError::ActionFailed("Client manager not available".to_string()))?;
+        executor.dry_run().execute(plan).await
+    }
+
+    /// Generate action plan from request text (XML Task-based). Keyword matching only: used when
+    /// no intent recognition via Geri is available (see [`IntentClassifier`](super::IntentClassifier)).
     pub async fn plan_actions(&self, request: &str) -> Result<ActionPlan, Box<dyn std::error::Error + Send + Sync>> {
         let request_lower = request.to_lowercase();
         let mut actions = Vec::new();
@@ -60,6 +277,7 @@ impl ActionOrchestrator {
                 parameters: serde_json::json!({
                     "xml": xml_task
                 }),
+                ..Default::default()
             });
         }
 
@@ -71,6 +289,7 @@ impl ActionOrchestrator {
                 action_type: "XML_TASK".to_string(),
                 service: "thor".to_string(),
                 parameters: serde_json::json!({ "xml": xml_task }),
+                ..Default::default()
             });
         }
 
@@ -82,6 +301,7 @@ impl ActionOrchestrator {
                 action_type: "XML_TASK".to_string(),
                 service: "thor".to_string(),
                 parameters: serde_json::json!({ "xml": xml_task }),
+                ..Default::default()
             });
         }
 
@@ -93,6 +313,7 @@ impl ActionOrchestrator {
                 action_type: "XML_TASK".to_string(),
                 service: "thor".to_string(),
                 parameters: serde_json::json!({ "xml": xml_task }),
+                ..Default::default()
             });
         }
 
@@ -111,6 +332,7 @@ impl ActionOrchestrator {
                 parameters: serde_json::json!({
                     "xml": xml_task
                 }),
+                ..Default::default()
             });
         }
 
@@ -122,6 +344,7 @@ impl ActionOrchestrator {
                 action_type: "XML_TASK".to_string(),
                 service: "thor".to_string(),
                 parameters: serde_json::json!({ "xml": xml_task }),
+                ..Default::default()
             });
         }
 
@@ -133,6 +356,7 @@ impl ActionOrchestrator {
                 action_type: "XML_TASK".to_string(),
                 service: "thor".to_string(),
                 parameters: serde_json::json!({ "xml": xml_task }),
+                ..Default::default()
             });
         }
 
@@ -144,6 +368,7 @@ impl ActionOrchestrator {
                 action_type: "XML_TASK".to_string(),
                 service: "thor".to_string(),
                 parameters: serde_json::json!({ "xml": xml_task }),
+                ..Default::default()
             });
         }
 
@@ -160,6 +385,7 @@ impl ActionOrchestrator {
                 parameters: serde_json::json!({
                     "xml": xml_task
                 }),
+                ..Default::default()
             });
         }
 
@@ -222,6 +448,15 @@ impl ActionOrchestrator {
 
     /// Execute actions via Thor; returns one string per action (success/error/skip).
     pub async fn execute_actions(&self, plan: ActionPlan) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+        self.execute_actions_with_progress(plan, &ProgressReporter::disabled()).await
+    }
+
+    /// Like [`execute_actions`](Self::execute_actions), reporting when each Thor action starts and finishes.
+    pub async fn execute_actions_with_progress(
+        &self,
+        plan: ActionPlan,
+        progress: &ProgressReporter,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
         let mut results = Vec::new();
 
         for action in plan.actions {
@@ -240,12 +475,26 @@ impl ActionOrchestrator {
                 }
             };
 
+            let action_id = action.action_id.clone();
+            progress.emit(ProgressEvent::ActionStarted {
+                action_id: action_id.clone(),
+                action_type: action.action_type.clone(),
+                service: action.service.clone(),
+            });
+            let finished = |error: Option<String>| ProgressEvent::ActionFinished {
+                action_id: action_id.clone(),
+                status: if error.is_none() { StepStatus::Succeeded } else { StepStatus::Failed },
+                error,
+            };
+
             // Convert Action to ThorAction
-            let thor_action = self.convert_to_thor_action(action)?;
+            let thor_action = convert_to_thor_action(action)?;
 
             // Execute via Thor
             match client_manager.execute_thor_action(thor_action).await {
                 Ok(thor_result) => {
+                    let error = if thor_result.success { None } else { Some(thor_result.error_message.clone()) };
+                    progress.emit(finished(error));
                     if thor_result.success {
                         let result_data = if !thor_result.result_data.is_empty() {
                             String::from_utf8_lossy(&thor_result.result_data).to_string()
@@ -255,7 +504,7 @@ impl ActionOrchestrator {
 
                         // Post-process XML response if present
                         if result_data.starts_with("<response") {
-                            results.push(self.parse_xml_response(&result_data));
+                            results.push(parse_xml_response(&result_data));
                         } else {
                             results.push(result_data);
                         }
@@ -264,6 +513,7 @@ impl ActionOrchestrator {
                     }
                 }
                 Err(e) => {
+                    progress.emit(finished(Some(e.clone())));
                     results.push(format!("Failed to execute action: {}", e));
                 }
             }
@@ -271,41 +521,54 @@ impl ActionOrchestrator {
 
         Ok(results)
     }
+}
 
-    /// Parses a standard XML response and returns the payload or error.
-    fn parse_xml_response(&self, xml: &str) -> String {
-        // Simple extraction logic for demonstration
-        if let Some(payload_start) = xml.find("<payload>") {
-            if let Some(payload_end) = xml.find("</payload>") {
-                return xml[payload_start + 9..payload_end].to_string();
-            }
-        }
-        
-        if xml.contains("status=\"error\"") {
-             return format!("Error in XML response: {}", xml);
+/// Parses a standard XML response and returns the payload or error.
+pub(crate) fn parse_xml_response(xml: &str) -> String {
+    // Simple extraction logic for demonstration
+    if let Some(payload_start) = xml.find("<payload>") {
+        if let Some(payload_end) = xml.find("</payload>") {
+            return xml[payload_start + 9..payload_end].to_string();
         }
+    }
+    
+    if xml.contains("status=\"error\"") {
+         return format!("Error in XML response: {}", xml);
+    }
+
+    xml.to_string()
+}
 
-        xml.to_string()
+/// ThorAction metadata key: run the action as a dry run.
+pub(crate) const THOR_DRY_RUN_KEY: &str = "dry_run";
+/// ThorAction metadata key: user confirmation of a risky action.
+pub(crate) const THOR_CONFIRMATION_KEY: &str = "confirmation_id";
+
+/// `action_data` of the ThorAction for an action, as Thor receives it.
+pub(crate) fn thor_action_data(action: &Action) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+    // For XML types, we want the raw XML string as action_data
+    if action.action_type == "XML_TASK" || action.action_type == "XML_CALL" {
+        return Ok(action.parameters["xml"].as_str().unwrap_or_default().as_bytes().to_vec());
     }
+    // Serialize parameters to JSON bytes for standard actions
+    Ok(serde_json::to_vec(&action.parameters).map_err(|e| format!("Failed to serialize action parameters: {}", e))?)
+}
 
-    /// Convert internal Action to ThorAction proto
-    fn convert_to_thor_action(&self, action: Action) -> Result<crate::clients::thor::thor::ThorAction, Box<dyn std::error::Error + Send + Sync>> {
-        // For XML types, we want the raw XML string as action_data
-        let action_data = if action.action_type == "XML_TASK" || action.action_type == "XML_CALL" {
-            action.parameters["xml"].as_str().unwrap_or_default().as_bytes().to_vec()
-        } else {
-            // Serialize parameters to JSON bytes for standard actions
-            serde_json::to_vec(&action.parameters)
-                .map_err(|e| format!("Failed to serialize action parameters: {}", e))?
-        };
-
-        Ok(crate::clients::thor::thor::ThorAction {
-            action_id: action.action_id,
-            action_type: action.action_type,
-            device_id: String::new(), // Will be set by caller if needed
-            user_id: String::new(),    // Will be set by caller if needed
-            action_data,
-            metadata: std::collections::HashMap::new(),
-        })
+/// Convert internal Action to ThorAction proto
+pub(crate) fn convert_to_thor_action(action: Action) -> Result<crate::clients::thor::thor::ThorAction, Box<dyn std::error::Error + Send + Sync>> {
+    let action_data = thor_action_data(&action)?;
+
+    let mut metadata = std::collections::HashMap::new();
+    if let Some(confirmation_id) = action.confirmation_id {
+        metadata.insert(THOR_CONFIRMATION_KEY.to_string(), confirmation_id);
     }
+
+    Ok(crate::clients::thor::thor::ThorAction {
+        action_id: action.action_id,
+        action_type: action.action_type,
+        device_id: String::new(), // Will be set by caller if needed
+        user_id: String::new(),    // Will be set by caller if needed
+        action_data,
+        metadata,
+    })
 }
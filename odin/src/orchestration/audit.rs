@@ -1,9 +1,14 @@
 //! Audit logging for orchestration (Phase 9): security- and compliance-relevant events.
 //!
 //! Use [`AuditLogger`] with [`RequestProcessor::with_audit_logger`](crate::orchestration::RequestProcessor::with_audit_logger) to record [`AuditEvent`]s (e.g. `RequestReceived`).
+//! [`AgentLoop::with_audit_logger`](crate::orchestration::AgentLoop::with_audit_logger) records the agent trace.
+//! [`ConfirmationGate::with_audit_logger`](crate::orchestration::ConfirmationGate::with_audit_logger) records confirmations of risky actions.
 
 use serde::Serialize;
 
+use super::agent::{AgentStopReason, AgentTraceEntry};
+use super::confirmation::{ConfirmationDecision, RiskLevel};
+
 /// Audit event for compliance (who/what/when).
 #[derive(Debug, Clone, Serialize)]
 pub enum AuditEvent {
@@ -14,6 +19,39 @@ pub enum AuditEvent {
         device_id: String,
         input_type: String,
     },
+    /// A model turn or tool call of an agent run.
+    AgentStep {
+        request_id: String,
+        user_id: String,
+        entry: AgentTraceEntry,
+    },
+    /// An agent run ended.
+    AgentFinished {
+        request_id: String,
+        user_id: String,
+        stop_reason: AgentStopReason,
+        steps: u32,
+        tokens_used: u32,
+        duration_ms: u64,
+    },
+    /// A plan with risky actions was held; the user was asked to confirm it.
+    ConfirmationRequested {
+        confirmation_id: String,
+        request_id: String,
+        user_id: String,
+        device_id: String,
+        risk: RiskLevel,
+        reasons: Vec<String>,
+        expires_at_ms: i64,
+    },
+    /// A held plan was approved, denied, expired or replaced by a new request.
+    ConfirmationResolved {
+        confirmation_id: String,
+        request_id: String,
+        user_id: String,
+        device_id: String,
+        decision: ConfirmationDecision,
+    },
 }
 
 /// Logger for audit events (injectable, e.g. for tests or file sink).
@@ -21,3 +59,15 @@ pub trait AuditLogger: Send + Sync {
     /// Record an audit event.
     fn log(&self, event: &AuditEvent);
 }
+
+/// Writes audit events as JSON to the `odin::audit` tracing target.
+pub struct TracingAuditLogger;
+
+impl AuditLogger for TracingAuditLogger {
+    fn log(&self, event: &AuditEvent) {
+        match serde_json::to_string(event) {
+            Ok(json) => tracing::info!(target: "odin::audit", "{}", json),
+            Err(e) => tracing::warn!(target: "odin::audit", "Unserializable audit event: {}", e),
+        }
+    }
+}
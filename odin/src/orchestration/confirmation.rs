@@ -0,0 +1,438 @@
+//! Human-in-the-loop confirmation of risky actions.
+//!
+//! [`classify_action`] rates every step of an [`ActionPlan`]: destructive file operations, shell
+//! commands, actions on other devices and purchases are risky. A plan with steps at or above the
+//! configured level is not executed right away: the [`ConfirmationGate`] holds it and Odin asks the
+//! originating device for a yes/no answer (text in Ragnarok/Midgard, voice via Huginn/Muninn). The
+//! answer arrives as the next turn of the same conversation or via `ConfirmAction`; unanswered
+//! confirmations expire and nothing is executed. Requests and decisions are audit-logged.
+//!
+//! Approved Thor steps carry a token signed with the secret Odin shares with Thor, bound to the
+//! step as dispatched (after `${...}` references are resolved) and valid for the confirmation
+//! timeout; Thor verifies it before running the step.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use super::action::{thor_action_data, Action, ActionOrchestrator, ActionPlan};
+use super::audit::{AuditEvent, AuditLogger};
+use super::conversation::ConversationKey;
+use super::error::OrchestrationError;
+use super::executor::ActionExecutor;
+use super::progress::ProgressReporter;
+use super::processor::UserRequest;
+use crate::utils::config::ConfirmationConfig;
+
+use gjallarhorn::ConfirmationSigner;
+
+pub use gjallarhorn::{RiskAssessment, RiskLevel};
+
+/// Rates a plan step by service, action type and parameters (the rules Thor enforces, see Gjallarhorn).
+pub fn classify_action(action: &Action) -> RiskAssessment {
+    gjallarhorn::classify_action(&action.service, &action.action_type, &action.parameters)
+}
+
+/// Risk of one plan step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepRisk {
+    pub action_id: String,
+    pub action_type: String,
+    pub risk: RiskAssessment,
+}
+
+/// A plan held until the user answers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingConfirmation {
+    pub confirmation_id: String,
+    pub conversation: ConversationKey,
+    pub request_id: String,
+    /// Once approved, every step carries the confirmation (see [`Action::confirmation_id`]).
+    pub plan: ActionPlan,
+    /// Steps at or above the gate's level.
+    pub risky_steps: Vec<StepRisk>,
+    /// Dry-run output of the plan, if available.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<String>,
+    /// Unix ms.
+    pub expires_at_ms: i64,
+}
+
+impl PendingConfirmation {
+    /// Highest risk of the held plan.
+    pub fn risk(&self) -> RiskLevel {
+        self.risky_steps.iter().map(|step| step.risk.level).max().unwrap_or_default()
+    }
+
+    /// Question sent back to the originating device (shown as text or spoken by Muninn).
+    pub fn prompt(&self) -> String {
+        let mut prompt = "This needs your confirmation:".to_string();
+        for step in &self.risky_steps {
+            prompt.push_str(&format!(
+                "\n- {} ({} risk): {}",
+                step.action_type,
+                step.risk.level.as_str(),
+                step.risk.reasons.join("; ")
+            ));
+        }
+        if let Some(ref preview) = self.preview {
+            prompt.push_str(&format!("\nDry run:\n{}", preview));
+        }
+        let seconds = (self.expires_at_ms - now_ms()).max(0) / 1000;
+        prompt.push_str(&format!("\nReply \"yes\" to run it or \"no\" to cancel (expires in {} s).", seconds));
+        prompt
+    }
+}
+
+/// How a held plan ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfirmationDecision {
+    Approved,
+    Denied,
+    /// Not answered in time; nothing was executed.
+    Expired,
+    /// The conversation moved on to another request; nothing was executed.
+    Superseded,
+}
+
+/// A decision together with the plan it applies to.
+#[derive(Debug, Clone)]
+pub struct ConfirmationOutcome {
+    pub decision: ConfirmationDecision,
+    pub pending: PendingConfirmation,
+    /// Signs the approved Thor steps when they are dispatched.
+    signing: Option<StepSigning>,
+}
+
+impl ConfirmationOutcome {
+    /// Run the plan if it was approved; otherwise the answer that nothing was executed.
+    pub async fn execute(&self, orchestrator: &ActionOrchestrator) -> Result<String, OrchestrationError> {
+        self.execute_with_progress(orchestrator, &ProgressReporter::disabled()).await
+    }
+
+    /// Like [`execute`](Self::execute), reporting when each step starts and finishes.
+    pub async fn execute_with_progress(
+        &self,
+        orchestrator: &ActionOrchestrator,
+        progress: &ProgressReporter,
+    ) -> Result<String, OrchestrationError> {
+        match self.decision {
+            ConfirmationDecision::Approved => {
+                let report = match self.signing.clone() {
+                    Some(signing) => {
+                        orchestrator
+                            .execute_plan_wrapped(&self.pending.plan, progress, |executor| {
+                                Arc::new(SigningExecutor { executor, signing })
+                            })
+                            .await?
+                    }
+                    None => orchestrator.execute_plan_with_progress(&self.pending.plan, progress).await?,
+                };
+                Ok(report.response_text())
+            }
+            ConfirmationDecision::Denied => Ok("Cancelled; nothing was executed.".to_string()),
+            ConfirmationDecision::Expired => {
+                Ok("The confirmation expired; nothing was executed. Please repeat the request.".to_string())
+            }
+            ConfirmationDecision::Superseded => Ok("Replaced by a newer request; nothing was executed.".to_string()),
+        }
+    }
+}
+
+/// Signer and expiry of an approval's Thor tokens.
+#[derive(Debug, Clone)]
+struct StepSigning {
+    signer: ConfirmationSigner,
+    expires_at_ms: i64,
+}
+
+/// Replaces the confirmation id of approved Thor steps with a token signed for the step as
+/// dispatched, i.e. with the outputs of earlier steps already filled in.
+struct SigningExecutor {
+    executor: Arc<dyn ActionExecutor>,
+    signing: StepSigning,
+}
+
+impl SigningExecutor {
+    fn sign(&self, action: &Action) -> Action {
+        let mut action = action.clone();
+        let Some(ref confirmation_id) = action.confirmation_id else {
+            return action;
+        };
+        if action.service != "thor" {
+            return action;
+        }
+        match thor_action_data(&action) {
+            Ok(data) => {
+                let token = self.signing.signer.sign(confirmation_id, &action.action_type, &data, self.signing.expires_at_ms);
+                action.confirmation_id = Some(token);
+            }
+            Err(e) => tracing::warn!("Cannot sign confirmation for step {}: {}", action.action_id, e),
+        }
+        action
+    }
+}
+
+#[async_trait]
+impl ActionExecutor for SigningExecutor {
+    async fn execute(&self, action: &Action) -> Result<Value, String> {
+        self.executor.execute(&self.sign(action)).await
+    }
+
+    async fn dry_run(&self, action: &Action) -> Result<Value, String> {
+        self.executor.dry_run(action).await
+    }
+}
+
+const YES: &[&str] = &[
+    "yes", "y", "yeah", "yep", "ok", "okay", "sure", "confirm", "confirmed", "do it", "go ahead", "ja", "jawohl",
+    "klar", "bestätigen", "bestätigt", "mach es", "mach das", "los",
+];
+const NO: &[&str] = &[
+    "no", "n", "nope", "cancel", "stop", "abort", "don't", "do not", "nein", "ne", "nö", "abbrechen", "stopp", "nicht",
+];
+/// Words that turn any yes into a no ("mach das nicht", "ok no").
+const NEGATIONS: &[&str] = &["no", "n", "not", "don't", "dont", "nope", "nein", "ne", "nö", "nicht", "kein", "keine", "never", "nie"];
+
+/// Reads a short yes/no answer (English or German, typed or transcribed); `None` for anything else.
+pub fn parse_confirmation_reply(input: &str) -> Option<bool> {
+    let normalized: String = input
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '\'' { c } else { ' ' })
+        .collect();
+    let words: Vec<&str> = normalized.split_whitespace().collect();
+    // Longer utterances are new requests, not answers
+    if words.is_empty() || words.len() > 4 {
+        return None;
+    }
+    let phrase = words.join(" ");
+    let starts_with = |answers: &[&str]| {
+        answers.iter().any(|answer| phrase == *answer || phrase.starts_with(&format!("{} ", answer)))
+    };
+    let negated = words.iter().any(|word| NEGATIONS.contains(word));
+    match (starts_with(YES) && !negated, starts_with(NO)) {
+        (true, false) => Some(true),
+        (false, true) => Some(false),
+        _ => None,
+    }
+}
+
+/// Holds plans with risky steps until the originating device confirms them.
+pub struct ConfirmationGate {
+    min_risk: RiskLevel,
+    timeout: Duration,
+    dry_run_preview: bool,
+    signer: Option<ConfirmationSigner>,
+    pending: RwLock<HashMap<ConversationKey, PendingConfirmation>>,
+    audit_logger: Option<Arc<dyn AuditLogger>>,
+}
+
+impl ConfirmationGate {
+    /// Plans with a step of at least `min_risk` need a confirmation within `timeout`.
+    pub fn new(min_risk: RiskLevel, timeout: Duration) -> Self {
+        Self {
+            min_risk,
+            timeout,
+            dry_run_preview: false,
+            signer: None,
+            pending: RwLock::new(HashMap::new()),
+            audit_logger: None,
+        }
+    }
+
+    pub fn from_config(config: &ConfirmationConfig) -> Self {
+        Self::new(
+            RiskLevel::parse(&config.min_risk).unwrap_or(RiskLevel::High),
+            Duration::from_secs(config.timeout_secs),
+        )
+        .with_dry_run_preview(config.dry_run_preview)
+        .with_signing_secret(&config.signing_secret)
+    }
+
+    /// Dry-run held plans and show the result in the question.
+    pub fn with_dry_run_preview(mut self, enabled: bool) -> Self {
+        self.dry_run_preview = enabled;
+        self
+    }
+
+    /// Sign the confirmation of approved Thor steps with the secret shared with Thor; without one
+    /// (empty), steps carry the bare confirmation id, which a Thor that enforces confirmations refuses.
+    pub fn with_signing_secret(mut self, secret: &str) -> Self {
+        self.signer = Some(secret).filter(|s| !s.is_empty()).map(ConfirmationSigner::new);
+        self
+    }
+
+    /// Record requests and decisions as [`AuditEvent`]s.
+    pub fn with_audit_logger(mut self, logger: Arc<dyn AuditLogger>) -> Self {
+        self.audit_logger = Some(logger);
+        self
+    }
+
+    /// Steps of the plan that need a confirmation; empty if the plan can run right away.
+    pub fn risky_steps(&self, plan: &ActionPlan) -> Vec<StepRisk> {
+        plan.actions
+            .iter()
+            .map(|action| StepRisk {
+                action_id: action.action_id.clone(),
+                action_type: action.action_type.clone(),
+                risk: classify_action(action),
+            })
+            .filter(|step| step.risk.level >= self.min_risk)
+            .collect()
+    }
+
+    /// Hold `plan` if it has risky steps, with a dry run via `orchestrator` as preview if enabled.
+    /// `None` if the plan may run right away.
+    pub async fn check(
+        &self,
+        request: &UserRequest,
+        plan: &ActionPlan,
+        orchestrator: &ActionOrchestrator,
+    ) -> Option<PendingConfirmation> {
+        let risky_steps = self.risky_steps(plan);
+        if risky_steps.is_empty() {
+            return None;
+        }
+        let preview = if self.dry_run_preview {
+            match orchestrator.dry_run_plan(plan).await {
+                Ok(report) => Some(report.response_text()),
+                Err(e) => {
+                    tracing::warn!("Dry run for confirmation failed: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        Some(self.hold(request, plan.clone(), risky_steps, preview).await)
+    }
+
+    /// Hold `plan` for the request's conversation; an earlier held plan of it is superseded.
+    pub async fn hold(
+        &self,
+        request: &UserRequest,
+        plan: ActionPlan,
+        risky_steps: Vec<StepRisk>,
+        preview: Option<String>,
+    ) -> PendingConfirmation {
+        let pending = PendingConfirmation {
+            confirmation_id: Uuid::new_v4().to_string(),
+            conversation: ConversationKey::from_request(request),
+            request_id: request.request_id.clone(),
+            plan,
+            risky_steps,
+            preview,
+            expires_at_ms: now_ms() + self.timeout.as_millis() as i64,
+        };
+        let replaced = self.pending.write().await.insert(pending.conversation.clone(), pending.clone());
+        if let Some(replaced) = replaced {
+            self.log_decision(&replaced, ConfirmationDecision::Superseded);
+        }
+        if let Some(ref logger) = self.audit_logger {
+            logger.log(&AuditEvent::ConfirmationRequested {
+                confirmation_id: pending.confirmation_id.clone(),
+                request_id: pending.request_id.clone(),
+                user_id: request.user_id.clone(),
+                device_id: request.device_id.clone(),
+                risk: pending.risk(),
+                reasons: pending.risky_steps.iter().flat_map(|step| step.risk.reasons.clone()).collect(),
+                expires_at_ms: pending.expires_at_ms,
+            });
+        }
+        tracing::info!("Holding plan of request {} until the user confirms it", pending.request_id);
+        pending
+    }
+
+    /// Held plan of the conversation that is still open.
+    pub async fn pending(&self, conversation: &ConversationKey) -> Option<PendingConfirmation> {
+        let pending = self.pending.read().await;
+        pending.get(conversation).filter(|p| p.expires_at_ms > now_ms()).cloned()
+    }
+
+    /// Treat the request as the answer to the conversation's held plan. `None` if nothing is held
+    /// or the request is not a yes/no answer; a held plan is then dropped (superseded or expired)
+    /// and the request is processed normally.
+    pub async fn answer(&self, request: &UserRequest) -> Option<ConfirmationOutcome> {
+        let conversation = ConversationKey::from_request(request);
+        let mut pending = self.pending.write().await.remove(&conversation)?;
+        let reply = parse_confirmation_reply(&request.input);
+        let decision = match reply {
+            _ if pending.expires_at_ms <= now_ms() => ConfirmationDecision::Expired,
+            Some(true) => ConfirmationDecision::Approved,
+            Some(false) => ConfirmationDecision::Denied,
+            None => ConfirmationDecision::Superseded,
+        };
+        self.log_decision(&pending, decision);
+        let signing = (decision == ConfirmationDecision::Approved).then(|| self.confirm(&mut pending)).flatten();
+        match (decision, reply) {
+            (ConfirmationDecision::Superseded, _) | (ConfirmationDecision::Expired, None) => None,
+            _ => Some(ConfirmationOutcome { decision, pending, signing }),
+        }
+    }
+
+    /// Explicit answer (e.g. a button in Ragnarok/Midgard); only the originating user and device
+    /// may decide. `None` if no such confirmation is held.
+    pub async fn decide(
+        &self,
+        confirmation_id: &str,
+        user_id: &str,
+        device_id: &str,
+        approved: bool,
+    ) -> Option<ConfirmationOutcome> {
+        let mut pending = {
+            let mut held = self.pending.write().await;
+            let conversation = held
+                .iter()
+                .find(|(key, p)| p.confirmation_id == confirmation_id && key.user_id == user_id && key.device_id == device_id)
+                .map(|(key, _)| key.clone())?;
+            held.remove(&conversation)?
+        };
+        let decision = if pending.expires_at_ms <= now_ms() {
+            ConfirmationDecision::Expired
+        } else if approved {
+            ConfirmationDecision::Approved
+        } else {
+            ConfirmationDecision::Denied
+        };
+        self.log_decision(&pending, decision);
+        let signing = (decision == ConfirmationDecision::Approved).then(|| self.confirm(&mut pending)).flatten();
+        Some(ConfirmationOutcome { decision, pending, signing })
+    }
+
+    /// Attach the confirmation id to every step; with a signing secret, Thor steps are signed
+    /// when dispatched, with tokens valid for the gate's timeout.
+    fn confirm(&self, pending: &mut PendingConfirmation) -> Option<StepSigning> {
+        for action in &mut pending.plan.actions {
+            action.confirmation_id = Some(pending.confirmation_id.clone());
+        }
+        self.signer.clone().map(|signer| StepSigning {
+            signer,
+            expires_at_ms: now_ms() + self.timeout.as_millis() as i64,
+        })
+    }
+
+    fn log_decision(&self, pending: &PendingConfirmation, decision: ConfirmationDecision) {
+        tracing::info!("Confirmation {} of request {}: {:?}", pending.confirmation_id, pending.request_id, decision);
+        if let Some(ref logger) = self.audit_logger {
+            logger.log(&AuditEvent::ConfirmationResolved {
+                confirmation_id: pending.confirmation_id.clone(),
+                request_id: pending.request_id.clone(),
+                user_id: pending.conversation.user_id.clone(),
+                device_id: pending.conversation.device_id.clone(),
+                decision,
+            });
+        }
+    }
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
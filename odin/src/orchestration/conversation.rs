@@ -0,0 +1,134 @@
+//! Conversation store: keeps the turns of each user/device/session so that Geri sees the
+//! previous exchange. Geri trims the history to the model's context window
+//! (`ContextWindowManager`) and reports how many of the oldest turns it dropped; the store
+//! drops them as well, so it never grows beyond what the model can use.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+use crate::clients::geri::geri::ChatMessage;
+use crate::orchestration::UserRequest;
+use crate::utils::config::ConversationConfig;
+
+/// Identifies one conversation. An empty `session_id` means one running conversation per user and device.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ConversationKey {
+    pub user_id: String,
+    pub device_id: String,
+    pub session_id: String,
+}
+
+impl ConversationKey {
+    pub fn from_request(request: &UserRequest) -> Self {
+        Self {
+            user_id: request.user_id.clone(),
+            device_id: request.device_id.clone(),
+            session_id: request.session_id.clone(),
+        }
+    }
+}
+
+struct Conversation {
+    messages: Vec<ChatMessage>,
+    last_active: Instant,
+}
+
+/// In-memory conversation history, bounded per conversation and evicted when idle.
+pub struct ConversationStore {
+    conversations: RwLock<HashMap<ConversationKey, Conversation>>,
+    max_messages: usize,
+    idle_timeout: Duration,
+}
+
+impl ConversationStore {
+    pub fn new(max_messages: usize, idle_timeout: Duration) -> Self {
+        Self {
+            conversations: RwLock::new(HashMap::new()),
+            max_messages,
+            idle_timeout,
+        }
+    }
+
+    pub fn from_config(config: &ConversationConfig) -> Self {
+        Self::new(config.max_messages, Duration::from_secs(config.idle_timeout_secs))
+    }
+
+    /// Turns of the conversation, oldest first; empty for unknown or expired conversations.
+    pub async fn history(&self, key: &ConversationKey) -> Vec<ChatMessage> {
+        let conversations = self.conversations.read().await;
+        match conversations.get(key) {
+            Some(c) if c.last_active.elapsed() < self.idle_timeout => c.messages.clone(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Append turns; the oldest turns beyond `max_messages` are dropped.
+    pub async fn append(&self, key: &ConversationKey, messages: Vec<ChatMessage>) {
+        let mut conversations = self.conversations.write().await;
+        let idle_timeout = self.idle_timeout;
+        let conversation = conversations.entry(key.clone()).or_insert_with(|| Conversation {
+            messages: Vec::new(),
+            last_active: Instant::now(),
+        });
+        if conversation.last_active.elapsed() >= idle_timeout {
+            conversation.messages.clear();
+        }
+        conversation.messages.extend(messages);
+        if conversation.messages.len() > self.max_messages {
+            let excess = conversation.messages.len() - self.max_messages;
+            conversation.messages.drain(..excess);
+        }
+        conversation.last_active = Instant::now();
+    }
+
+    /// Drop the `count` oldest turns (as reported by Geri's `trimmed_messages`).
+    pub async fn trim_oldest(&self, key: &ConversationKey, count: usize) {
+        if count == 0 {
+            return;
+        }
+        let mut conversations = self.conversations.write().await;
+        if let Some(conversation) = conversations.get_mut(key) {
+            let count = count.min(conversation.messages.len());
+            conversation.messages.drain(..count);
+        }
+    }
+
+    /// Forget a conversation (e.g. "start over").
+    pub async fn clear(&self, key: &ConversationKey) {
+        self.conversations.write().await.remove(key);
+    }
+
+    /// Remove all idle conversations; returns how many were removed.
+    pub async fn evict_idle(&self) -> usize {
+        let mut conversations = self.conversations.write().await;
+        let before = conversations.len();
+        conversations.retain(|_, c| c.last_active.elapsed() < self.idle_timeout);
+        before - conversations.len()
+    }
+
+    /// Number of stored conversations.
+    pub async fn len(&self) -> usize {
+        self.conversations.read().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.len().await == 0
+    }
+}
+
+impl Default for ConversationStore {
+    fn default() -> Self {
+        Self::from_config(&ConversationConfig::default())
+    }
+}
+
+/// A conversation turn in Geri's wire format.
+pub fn chat_message(role: &str, content: impl Into<String>) -> ChatMessage {
+    ChatMessage {
+        role: role.to_string(),
+        content: content.into(),
+        ..Default::default()
+    }
+}
@@ -21,6 +21,14 @@ pub enum OrchestrationError {
     #[error("action failed: {0}")]
     ActionFailed(String),
 
+    /// Action plan is not a valid DAG (unknown dependency, cycle, dangling reference).
+    #[error("invalid action plan: {0}")]
+    InvalidPlan(String),
+
+    /// Routine cannot be stored (missing steps, bad cron expression or time zone, unknown id).
+    #[error("invalid routine: {0}")]
+    InvalidRoutine(String),
+
     /// Service is not implemented for direct routing (e.g. Freki standalone).
     #[error("service not implemented for direct routing: {0}")]
     ServiceNotImplemented(String),
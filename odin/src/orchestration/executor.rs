@@ -0,0 +1,633 @@
+//! Action-plan execution: runs the steps of an [`ActionPlan`] as a dependency DAG.
+//!
+//! Steps whose dependencies succeeded start concurrently (bounded by `max_parallel`), each with
+//! its own timeout/retry policy. String parameters may reference outputs of earlier steps as
+//! `${<action_id>.result.<path>}`. On a final failure the plan is aborted, compensated or
+//! continued without the dependents, see [`FailureMode`]. The outcome is an [`ExecutionReport`].
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::task::JoinSet;
+use tracing::Instrument;
+
+use super::action::{convert_to_thor_action, parse_xml_response, Action, ActionPlan, FailureMode, THOR_DRY_RUN_KEY};
+use super::error::OrchestrationError;
+use super::progress::{ProgressEvent, ProgressReporter};
+use crate::clients::manager::ClientManager;
+
+/// Default number of steps running at the same time.
+pub const DEFAULT_MAX_PARALLEL: usize = 4;
+
+/// Runs a single, already resolved plan step on its service and returns the step output.
+#[async_trait]
+pub trait ActionExecutor: Send + Sync {
+    async fn execute(&self, action: &Action) -> Result<Value, String>;
+
+    /// What [`execute`](Self::execute) would do, without doing it. Defaults to the step itself.
+    async fn dry_run(&self, action: &Action) -> Result<Value, String> {
+        Ok(describe_step(action))
+    }
+}
+
+fn describe_step(action: &Action) -> Value {
+    serde_json::json!({
+        "dry_run": true,
+        "service": action.service,
+        "action_type": action.action_type,
+        "parameters": action.parameters,
+    })
+}
+
+/// Runs every step as a dry run of the wrapped executor.
+struct DryRunExecutor(Arc<dyn ActionExecutor>);
+
+#[async_trait]
+impl ActionExecutor for DryRunExecutor {
+    async fn execute(&self, action: &Action) -> Result<Value, String> {
+        self.0.dry_run(action).await
+    }
+}
+
+/// Executes steps on Thor, Loki, Freki and Geri via the [`ClientManager`].
+pub struct ServiceActionExecutor {
+    client_manager: Arc<ClientManager>,
+}
+
+impl ServiceActionExecutor {
+    pub fn new(client_manager: Arc<ClientManager>) -> Self {
+        Self { client_manager }
+    }
+
+    async fn execute_thor(&self, action: &Action) -> Result<Value, String> {
+        let thor_action = convert_to_thor_action(action.clone()).map_err(|e| e.to_string())?;
+        self.run_thor(thor_action).await
+    }
+
+    async fn run_thor(&self, thor_action: crate::clients::thor::thor::ThorAction) -> Result<Value, String> {
+        let result = self.client_manager.execute_thor_action(thor_action).await?;
+        if !result.success {
+            return Err(result.error_message);
+        }
+        let data = String::from_utf8_lossy(&result.result_data).to_string();
+        let data = if data.starts_with("<response") { parse_xml_response(&data) } else { data };
+        // ThorResult.result_data is JSON-encoded; keep plain text as a string
+        Ok(serde_json::from_str(&data).unwrap_or(Value::String(data)))
+    }
+
+    async fn execute_loki(&self, action: &Action) -> Result<Value, String> {
+        use crate::clients::loki::loki::{CallToolRequest, ExecuteScriptRequest};
+
+        let params = &action.parameters;
+        if action.action_type == "EXECUTE_SCRIPT" {
+            let request = ExecuteScriptRequest {
+                script_id: str_param(params, "script_id"),
+                script_content: str_param(params, "script_content"),
+                script_type: str_param(params, "script_type"),
+                parameters: params["parameters"]
+                    .as_object()
+                    .map(|map| map.iter().map(|(k, v)| (k.clone(), value_to_string(v))).collect())
+                    .unwrap_or_default(),
+            };
+            let response = self.client_manager.execute_loki_script(request).await?;
+            if !response.success {
+                return Err(response.error);
+            }
+            return Ok(serde_json::from_str(&response.output).unwrap_or(Value::String(response.output)));
+        }
+        let request = CallToolRequest {
+            device_id: str_param(params, "device_id"),
+            tool_name: str_param(params, "tool_name"),
+            parameters_json: params.get("parameters").map(Value::to_string).unwrap_or_else(|| "{}".to_string()),
+        };
+        let response = self.client_manager.call_loki_tool(request).await?;
+        if !response.success {
+            return Err(response.error);
+        }
+        Ok(serde_json::from_str(&response.result_json).unwrap_or(Value::String(response.result_json)))
+    }
+
+    async fn execute_freki(&self, action: &Action) -> Result<Value, String> {
+        let params = &action.parameters;
+        let embedding: Vec<f32> = params["query_embedding"]
+            .as_array()
+            .map(|values| values.iter().filter_map(Value::as_f64).map(|v| v as f32).collect())
+            .unwrap_or_default();
+        let request = crate::clients::freki::freki::RetrieveContextRequest {
+            query_embedding: encode_embedding(&embedding),
+            limit: params["limit"].as_u64().unwrap_or(5),
+            collection_name: str_param(params, "collection_name"),
+        };
+        let response = self.client_manager.retrieve_freki_context(request).await?;
+        let documents: Vec<Value> = response
+            .documents
+            .into_iter()
+            .map(|doc| serde_json::json!({ "id": doc.id, "content": doc.content, "score": doc.score, "metadata": doc.metadata }))
+            .collect();
+        Ok(serde_json::json!({ "documents": documents }))
+    }
+
+    async fn execute_geri(&self, action: &Action) -> Result<Value, String> {
+        let params = &action.parameters;
+        let request = crate::clients::geri::geri::ProcessPromptRequest {
+            prompt: str_param(params, "prompt"),
+            context: str_param(params, "context"),
+            model_name: str_param(params, "model_name"),
+            max_tokens: params["max_tokens"].as_u64().unwrap_or(0) as u32,
+            system_prompt: str_param(params, "system_prompt"),
+            user_id: str_param(params, "user_id"),
+            ..Default::default()
+        };
+        let response = self.client_manager.process_geri_prompt(request).await?;
+        let structured: Value = serde_json::from_str(&response.structured_json).unwrap_or(Value::Null);
+        Ok(serde_json::json!({
+            "text": response.text,
+            "model_used": response.model_used,
+            "tokens_used": response.tokens_used,
+            "structured": structured,
+        }))
+    }
+}
+
+#[async_trait]
+impl ActionExecutor for ServiceActionExecutor {
+    async fn execute(&self, action: &Action) -> Result<Value, String> {
+        match action.service.as_str() {
+            "thor" => self.execute_thor(action).await,
+            "loki" => self.execute_loki(action).await,
+            "freki" => self.execute_freki(action).await,
+            "geri" => self.execute_geri(action).await,
+            other => Err(format!("Service '{}' cannot execute actions", other)),
+        }
+    }
+
+    /// Thor reports what it would do (and the action's risk); other services only describe the step.
+    async fn dry_run(&self, action: &Action) -> Result<Value, String> {
+        if action.service != "thor" {
+            return Ok(describe_step(action));
+        }
+        let mut thor_action = convert_to_thor_action(action.clone()).map_err(|e| e.to_string())?;
+        thor_action.metadata.insert(THOR_DRY_RUN_KEY.to_string(), "true".to_string());
+        self.run_thor(thor_action).await
+    }
+}
+
+fn str_param(params: &Value, key: &str) -> String {
+    params.get(key).map(value_to_string).unwrap_or_default()
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Freki expects a bincode-encoded `Vec<f32>`: u64 length followed by the values, little endian.
+fn encode_embedding(embedding: &[f32]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(8 + embedding.len() * 4);
+    bytes.extend_from_slice(&(embedding.len() as u64).to_le_bytes());
+    for value in embedding {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    bytes
+}
+
+/// Final state of a plan step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Succeeded,
+    Failed,
+    TimedOut,
+    /// Not started: a dependency did not succeed or the plan was aborted.
+    Skipped,
+}
+
+impl StepStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StepStatus::Succeeded => "succeeded",
+            StepStatus::Failed => "failed",
+            StepStatus::TimedOut => "timed_out",
+            StepStatus::Skipped => "skipped",
+        }
+    }
+}
+
+/// Outcome of one step (or compensation).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepReport {
+    pub action_id: String,
+    pub service: String,
+    pub action_type: String,
+    pub status: StepStatus,
+    /// Attempts made, including retries; 0 if skipped.
+    pub attempts: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+impl StepReport {
+    fn skipped(action: &Action, reason: String) -> Self {
+        Self {
+            action_id: action.action_id.clone(),
+            service: action.service.clone(),
+            action_type: action.action_type.clone(),
+            status: StepStatus::Skipped,
+            attempts: 0,
+            output: None,
+            error: Some(reason),
+            duration_ms: 0,
+        }
+    }
+}
+
+/// Overall result of a plan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanStatus {
+    Succeeded,
+    /// Some steps failed with [`FailureMode::Continue`]; the others completed.
+    PartiallySucceeded,
+    Failed,
+    /// A step failed and the succeeded steps were compensated.
+    Compensated,
+}
+
+/// Structured result of [`PlanExecutor::execute`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionReport {
+    pub status: PlanStatus,
+    /// One report per step, in plan order.
+    pub steps: Vec<StepReport>,
+    /// Compensations in the order they ran.
+    #[serde(default)]
+    pub compensations: Vec<StepReport>,
+    pub duration_ms: u64,
+}
+
+impl ExecutionReport {
+    pub fn step(&self, action_id: &str) -> Option<&StepReport> {
+        self.steps.iter().find(|step| step.action_id == action_id)
+    }
+
+    /// Short human-readable summary, e.g. for the response to the user.
+    pub fn summary(&self) -> String {
+        let succeeded = self.steps.iter().filter(|s| s.status == StepStatus::Succeeded).count();
+        let status = match self.status {
+            PlanStatus::Succeeded => "succeeded",
+            PlanStatus::PartiallySucceeded => "partially succeeded",
+            PlanStatus::Failed => "failed",
+            PlanStatus::Compensated => "failed and was compensated",
+        };
+        let mut summary = format!("Plan {}: {}/{} step(s) succeeded", status, succeeded, self.steps.len());
+        for step in self.steps.iter().filter(|s| matches!(s.status, StepStatus::Failed | StepStatus::TimedOut)) {
+            summary.push_str(&format!("\n- {} ({}) failed: {}", step.action_id, step.service, step.error.as_deref().unwrap_or("unknown error")));
+        }
+        summary
+    }
+
+    /// Outputs of all steps as the answer to the user; the [`summary`](Self::summary) if the plan did not succeed.
+    pub fn response_text(&self) -> String {
+        if self.status != PlanStatus::Succeeded {
+            return self.summary();
+        }
+        let outputs: Vec<String> = self
+            .steps
+            .iter()
+            .filter_map(|step| step.output.as_ref())
+            .filter(|output| !output.is_null())
+            .map(value_to_string)
+            .collect();
+        if outputs.is_empty() {
+            self.summary()
+        } else {
+            outputs.join("\n")
+        }
+    }
+}
+
+/// Executes [`ActionPlan`]s as a DAG via an [`ActionExecutor`].
+#[derive(Clone)]
+pub struct PlanExecutor {
+    executor: Arc<dyn ActionExecutor>,
+    max_parallel: usize,
+}
+
+impl PlanExecutor {
+    pub fn new(executor: Arc<dyn ActionExecutor>) -> Self {
+        Self {
+            executor,
+            max_parallel: DEFAULT_MAX_PARALLEL,
+        }
+    }
+
+    pub fn with_max_parallel(mut self, max_parallel: usize) -> Self {
+        self.max_parallel = max_parallel.max(1);
+        self
+    }
+
+    /// Executor for the same plans that only dry-runs every step (see [`ActionExecutor::dry_run`]).
+    pub fn dry_run(&self) -> Self {
+        self.wrap(|executor| Arc::new(DryRunExecutor(executor)))
+    }
+
+    /// Executor for the same plans whose steps, once resolved, run through the executor `wrap` builds.
+    pub fn wrap(&self, wrap: impl FnOnce(Arc<dyn ActionExecutor>) -> Arc<dyn ActionExecutor>) -> Self {
+        Self {
+            executor: wrap(self.executor.clone()),
+            max_parallel: self.max_parallel,
+        }
+    }
+
+    /// Validate and run the plan; errors only for invalid plans, step failures go into the report.
+    pub async fn execute(&self, plan: &ActionPlan) -> Result<ExecutionReport, OrchestrationError> {
+        self.execute_with_progress(plan, &ProgressReporter::disabled()).await
+    }
+
+    /// Like [`execute`](Self::execute), reporting when each step starts and finishes. Dropping the
+    /// returned future aborts the running steps.
+    pub async fn execute_with_progress(
+        &self,
+        plan: &ActionPlan,
+        progress: &ProgressReporter,
+    ) -> Result<ExecutionReport, OrchestrationError> {
+        plan.validate()?;
+        let started = Instant::now();
+        let by_id: HashMap<&str, &Action> = plan.actions.iter().map(|a| (a.action_id.as_str(), a)).collect();
+        let mut reports: HashMap<String, StepReport> = HashMap::new();
+        // Outputs visible to references: `{ "<id>": { "result": <output> } }`
+        let mut outputs = serde_json::Map::new();
+        let mut completed: Vec<String> = Vec::new();
+        let mut started_ids: HashSet<String> = HashSet::new();
+        let mut running = JoinSet::new();
+        let mut abort: Option<(String, FailureMode)> = None;
+
+        loop {
+            // Repeat until no step changes state, so skips propagate along dependency chains
+            let mut progressed = abort.is_none();
+            while progressed && abort.is_none() {
+                progressed = false;
+                for action in &plan.actions {
+                    if running.len() >= self.max_parallel {
+                        break;
+                    }
+                    if started_ids.contains(&action.action_id) || reports.contains_key(&action.action_id) {
+                        continue;
+                    }
+                    // A dependency that finished without success skips this step
+                    if let Some(dep) = action.depends_on.iter().find(|dep| reports.get(*dep).is_some_and(|r| r.status != StepStatus::Succeeded)) {
+                        reports.insert(action.action_id.clone(), StepReport::skipped(action, format!("dependency '{}' did not succeed", dep)));
+                        progressed = true;
+                        continue;
+                    }
+                    if !action.depends_on.iter().all(|dep| reports.contains_key(dep)) {
+                        continue;
+                    }
+                    let mut step = action.clone();
+                    match resolve_parameters(&action.parameters, &outputs) {
+                        Ok(parameters) => step.parameters = parameters,
+                        Err(e) => {
+                            let mut report = StepReport::skipped(action, e);
+                            report.status = StepStatus::Failed;
+                            if action.policy.on_failure != FailureMode::Continue {
+                                abort = Some((action.action_id.clone(), action.policy.on_failure));
+                            }
+                            reports.insert(action.action_id.clone(), report);
+                            progressed = true;
+                            continue;
+                        }
+                    }
+                    started_ids.insert(action.action_id.clone());
+                    progress.emit(step_started(&step));
+                    let executor = self.executor.clone();
+                    // Child of the request's span, so the step's downstream calls stay in its trace
+                    let span = tracing::info_span!("plan_step", action_id = %step.action_id, action_type = %step.action_type);
+                    running.spawn(async move { run_step(executor.as_ref(), &step).await }.instrument(span));
+                }
+            }
+
+            let Some(joined) = running.join_next().await else {
+                break;
+            };
+            let report = joined.map_err(|e| OrchestrationError::ActionFailed(format!("plan step panicked: {}", e)))?;
+            started_ids.remove(&report.action_id);
+            progress.emit(step_finished(&report));
+            match report.status {
+                StepStatus::Succeeded => {
+                    outputs.insert(report.action_id.clone(), serde_json::json!({ "result": report.output.clone().unwrap_or(Value::Null) }));
+                    completed.push(report.action_id.clone());
+                }
+                _ => {
+                    let mode = by_id[report.action_id.as_str()].policy.on_failure;
+                    tracing::warn!("Plan step {} failed: {}", report.action_id, report.error.as_deref().unwrap_or_default());
+                    if mode != FailureMode::Continue && abort.is_none() {
+                        abort = Some((report.action_id.clone(), mode));
+                    }
+                }
+            }
+            reports.insert(report.action_id.clone(), report);
+        }
+
+        // Steps never started because the plan was aborted
+        for action in &plan.actions {
+            if !reports.contains_key(&action.action_id) {
+                let reason = match &abort {
+                    Some((failed, _)) => format!("plan aborted after '{}' failed", failed),
+                    None => "not started".to_string(),
+                };
+                reports.insert(action.action_id.clone(), StepReport::skipped(action, reason));
+            }
+        }
+
+        let mut compensations = Vec::new();
+        if let Some((_, FailureMode::Compensate)) = &abort {
+            for id in completed.iter().rev() {
+                let Some(compensation) = &by_id[id.as_str()].compensation else {
+                    continue;
+                };
+                let mut step = (**compensation).clone();
+                if step.action_id.is_empty() {
+                    step.action_id = format!("{}-compensation", id);
+                }
+                let report = match resolve_parameters(&compensation.parameters, &outputs) {
+                    Ok(parameters) => {
+                        step.parameters = parameters;
+                        progress.emit(step_started(&step));
+                        let report = run_step(self.executor.as_ref(), &step).await;
+                        progress.emit(step_finished(&report));
+                        report
+                    }
+                    Err(e) => {
+                        let mut report = StepReport::skipped(&step, e);
+                        report.status = StepStatus::Failed;
+                        report
+                    }
+                };
+                if report.status != StepStatus::Succeeded {
+                    tracing::warn!("Compensation of plan step {} failed: {}", id, report.error.as_deref().unwrap_or_default());
+                }
+                compensations.push(report);
+            }
+        }
+
+        let steps: Vec<StepReport> = plan.actions.iter().filter_map(|a| reports.remove(&a.action_id)).collect();
+        let status = match &abort {
+            Some((_, FailureMode::Compensate)) => PlanStatus::Compensated,
+            Some(_) => PlanStatus::Failed,
+            None if steps.iter().all(|s| s.status == StepStatus::Succeeded) => PlanStatus::Succeeded,
+            None => PlanStatus::PartiallySucceeded,
+        };
+        Ok(ExecutionReport {
+            status,
+            steps,
+            compensations,
+            duration_ms: started.elapsed().as_millis() as u64,
+        })
+    }
+}
+
+fn step_started(action: &Action) -> ProgressEvent {
+    ProgressEvent::ActionStarted {
+        action_id: action.action_id.clone(),
+        action_type: action.action_type.clone(),
+        service: action.service.clone(),
+    }
+}
+
+fn step_finished(report: &StepReport) -> ProgressEvent {
+    ProgressEvent::ActionFinished {
+        action_id: report.action_id.clone(),
+        status: report.status,
+        error: report.error.clone(),
+    }
+}
+
+/// Runs one step with its timeout and retry policy.
+async fn run_step(executor: &dyn ActionExecutor, action: &Action) -> StepReport {
+    let policy = &action.policy;
+    let started = Instant::now();
+    let mut attempts = 0;
+    let max_delay = Duration::from_millis(policy.max_retry_delay_ms);
+    let mut delay = Duration::from_millis(policy.retry_delay_ms).min(max_delay);
+    loop {
+        attempts += 1;
+        let result = match policy.timeout_ms {
+            Some(ms) => match tokio::time::timeout(Duration::from_millis(ms), executor.execute(action)).await {
+                Ok(result) => result.map_err(|e| (StepStatus::Failed, e)),
+                Err(_) => Err((StepStatus::TimedOut, format!("timed out after {} ms", ms))),
+            },
+            None => executor.execute(action).await.map_err(|e| (StepStatus::Failed, e)),
+        };
+        let (status, output, error) = match result {
+            Ok(output) => (StepStatus::Succeeded, Some(output), None),
+            Err((status, error)) if attempts > policy.max_retries => (status, None, Some(error)),
+            Err((_, error)) => {
+                tracing::debug!("Plan step {} attempt {} failed, retrying: {}", action.action_id, attempts, error);
+                tokio::time::sleep(delay).await;
+                delay = delay.saturating_mul(2).min(max_delay);
+                continue;
+            }
+        };
+        return StepReport {
+            action_id: action.action_id.clone(),
+            service: action.service.clone(),
+            action_type: action.action_type.clone(),
+            status,
+            attempts,
+            output,
+            error,
+            duration_ms: started.elapsed().as_millis() as u64,
+        };
+    }
+}
+
+/// Action ids referenced as `${<id>...}` anywhere in the parameters.
+pub fn parameter_references(parameters: &Value) -> Vec<String> {
+    let mut ids = Vec::new();
+    visit_strings(parameters, &mut |s| {
+        for reference in references_in(s) {
+            let id = reference.split('.').next().unwrap_or_default().to_string();
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    });
+    ids
+}
+
+fn visit_strings(value: &Value, f: &mut impl FnMut(&str)) {
+    match value {
+        Value::String(s) => f(s),
+        Value::Array(items) => items.iter().for_each(|item| visit_strings(item, f)),
+        Value::Object(map) => map.values().for_each(|item| visit_strings(item, f)),
+        _ => {}
+    }
+}
+
+/// Contents of all `${...}` placeholders in `s`.
+fn references_in(s: &str) -> Vec<&str> {
+    let mut references = Vec::new();
+    let mut rest = s;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+        references.push(&rest[start + 2..start + 2 + len]);
+        rest = &rest[start + 3 + len..];
+    }
+    references
+}
+
+/// Replaces `${<id>.result.<path>}` references with earlier step outputs. A string that is
+/// exactly one reference takes the referenced JSON value; otherwise the value is inserted as text.
+pub fn resolve_parameters(parameters: &Value, outputs: &serde_json::Map<String, Value>) -> Result<Value, String> {
+    Ok(match parameters {
+        Value::String(s) => {
+            let references = references_in(s);
+            if references.is_empty() {
+                return Ok(parameters.clone());
+            }
+            if references.len() == 1 && s.len() == references[0].len() + 3 {
+                return lookup(outputs, references[0]).cloned();
+            }
+            let mut resolved = s.clone();
+            for reference in references {
+                let value = lookup(outputs, reference)?;
+                resolved = resolved.replace(&format!("${{{}}}", reference), &value_to_string(value));
+            }
+            Value::String(resolved)
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|item| resolve_parameters(item, outputs)).collect::<Result<_, _>>()?),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| Ok((key.clone(), resolve_parameters(value, outputs)?)))
+                .collect::<Result<_, String>>()?,
+        ),
+        other => other.clone(),
+    })
+}
+
+fn lookup<'a>(outputs: &'a serde_json::Map<String, Value>, reference: &str) -> Result<&'a Value, String> {
+    let mut segments = reference.split('.');
+    let id = segments.next().unwrap_or_default();
+    let mut value = outputs.get(id).ok_or_else(|| format!("unresolved reference ${{{}}}: no output of '{}'", reference, id))?;
+    for segment in segments {
+        let next = match value {
+            Value::Object(map) => map.get(segment),
+            Value::Array(items) => segment.parse::<usize>().ok().and_then(|index| items.get(index)),
+            _ => None,
+        };
+        value = next.ok_or_else(|| format!("unresolved reference ${{{}}}: no field '{}'", reference, segment))?;
+    }
+    Ok(value)
+}
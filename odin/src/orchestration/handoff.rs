@@ -0,0 +1,268 @@
+//! Responsibility handoff: the service or plugin that accepts `TakeResponsibility` owns the
+//! conversation until it returns (or rejects) it, or until its lease runs out. Following turns of
+//! the conversation go straight to the owner and renew the lease.
+//!
+//! Leases and a bounded history of transitions are kept in the [`HandoffStore`], which writes them
+//! to a JSON file after every change so that ownership survives an Odin restart and can be
+//! inspected for debugging.
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use super::conversation::ConversationKey;
+use crate::utils::config::HandoffConfig;
+
+/// Ownership of one conversation by a service or plugin.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponsibilityLease {
+    pub conversation: ConversationKey,
+    pub owner: String,
+    /// Why the owner was chosen (score or intent).
+    pub reason: String,
+    /// Requests handed to the owner, oldest first; `ReturnResponsibility` may name any of them.
+    pub request_ids: Vec<String>,
+    /// Unix ms.
+    pub taken_at_ms: i64,
+    /// Unix ms; renewed by every turn routed to the owner.
+    pub expires_at_ms: i64,
+}
+
+impl ResponsibilityLease {
+    fn is_expired(&self, now_ms: i64) -> bool {
+        now_ms >= self.expires_at_ms
+    }
+}
+
+/// Kind of a handoff transition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HandoffTransition {
+    /// The service accepted and owns the conversation.
+    Taken,
+    /// The service declined; the next candidate is asked.
+    Rejected,
+    /// A following turn went straight to the owner.
+    Routed,
+    /// The owner gave the conversation back.
+    Returned,
+    /// The lease ran out.
+    Expired,
+}
+
+/// One entry of the handoff history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HandoffRecord {
+    pub at_ms: i64,
+    pub conversation: ConversationKey,
+    pub service: String,
+    pub request_id: String,
+    pub transition: HandoffTransition,
+    /// Reason or message given with the transition.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub detail: String,
+}
+
+/// Persisted handoff state.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HandoffState {
+    pub leases: Vec<ResponsibilityLease>,
+    /// Latest transitions, oldest first.
+    pub history: VecDeque<HandoffRecord>,
+}
+
+/// Leases per conversation and their history, optionally persisted to a JSON file.
+pub struct HandoffStore {
+    state: RwLock<HandoffState>,
+    lease: Duration,
+    max_history: usize,
+    path: Option<PathBuf>,
+}
+
+impl HandoffStore {
+    /// In-memory store (lost on restart).
+    pub fn new(lease: Duration, max_history: usize) -> Self {
+        Self {
+            state: RwLock::new(HandoffState::default()),
+            lease,
+            max_history,
+            path: None,
+        }
+    }
+
+    /// Store persisted at `path`; loads the state written by a previous run if the file exists.
+    pub fn open(path: impl Into<PathBuf>, lease: Duration, max_history: usize) -> std::io::Result<Self> {
+        let path = path.into();
+        let state = if path.exists() {
+            let content = std::fs::read_to_string(&path)?;
+            serde_json::from_str(&content).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+        } else {
+            HandoffState::default()
+        };
+        tracing::info!("Loaded {} responsibility lease(s) from {:?}", state.leases.len(), path);
+        Ok(Self {
+            state: RwLock::new(state),
+            lease,
+            max_history,
+            path: Some(path),
+        })
+    }
+
+    /// Store from settings; an empty `state_path` means `default_path`.
+    pub fn from_config(config: &HandoffConfig, default_path: &Path) -> std::io::Result<Self> {
+        let path = if config.state_path.is_empty() { default_path.to_path_buf() } else { PathBuf::from(&config.state_path) };
+        Self::open(path, Duration::from_secs(config.lease_secs), config.max_history)
+    }
+
+    /// Current owner of the conversation; an expired lease is ended here.
+    pub async fn owner(&self, conversation: &ConversationKey) -> Option<ResponsibilityLease> {
+        let mut state = self.state.write().await;
+        let now = now_ms();
+        if self.expire(&mut state, conversation, now) {
+            self.save(&state).await;
+        }
+        state.leases.iter().find(|l| l.conversation == *conversation).cloned()
+    }
+
+    /// Hand the turn `request_id` to the current owner and renew its lease; `None` without owner.
+    pub async fn route_to_owner(&self, conversation: &ConversationKey, request_id: &str) -> Option<ResponsibilityLease> {
+        let mut state = self.state.write().await;
+        let now = now_ms();
+        let expired = self.expire(&mut state, conversation, now);
+        let renewed = state.leases.iter_mut().find(|l| l.conversation == *conversation).map(|lease| {
+            lease.request_ids.push(request_id.to_string());
+            lease.expires_at_ms = now + self.lease_ms();
+            lease.clone()
+        });
+        if let Some(ref lease) = renewed {
+            let record = record(now, conversation, &lease.owner, request_id, HandoffTransition::Routed, "");
+            self.push_history(&mut state, record);
+        }
+        if expired || renewed.is_some() {
+            self.save(&state).await;
+        }
+        renewed
+    }
+
+    /// Lease end for a conversation taken now, as sent in `TakeResponsibility`.
+    pub fn lease_expires_at(&self) -> i64 {
+        now_ms() + self.lease_ms()
+    }
+
+    /// `service` accepted `request_id` and now owns the conversation (replacing a previous owner).
+    pub async fn take(&self, conversation: &ConversationKey, service: &str, request_id: &str, reason: &str) -> ResponsibilityLease {
+        let mut state = self.state.write().await;
+        let now = now_ms();
+        state.leases.retain(|l| l.conversation != *conversation);
+        let lease = ResponsibilityLease {
+            conversation: conversation.clone(),
+            owner: service.to_string(),
+            reason: reason.to_string(),
+            request_ids: vec![request_id.to_string()],
+            taken_at_ms: now,
+            expires_at_ms: now + self.lease_ms(),
+        };
+        state.leases.push(lease.clone());
+        self.push_history(&mut state, record(now, conversation, service, request_id, HandoffTransition::Taken, reason));
+        self.save(&state).await;
+        tracing::info!("{} owns conversation of {}/{} until it returns it", service, conversation.user_id, conversation.device_id);
+        lease
+    }
+
+    /// `service` declined `request_id` (history only; nobody owns the conversation yet).
+    pub async fn reject(&self, conversation: &ConversationKey, service: &str, request_id: &str, message: &str) {
+        let mut state = self.state.write().await;
+        self.push_history(&mut state, record(now_ms(), conversation, service, request_id, HandoffTransition::Rejected, message));
+        self.save(&state).await;
+    }
+
+    /// The owner gives the conversation of `request_id` back. With `service` set, only that owner
+    /// may do so. Returns the ended lease, or `None` if no matching lease exists.
+    pub async fn release(&self, request_id: &str, service: Option<&str>, reason: &str) -> Option<ResponsibilityLease> {
+        let mut state = self.state.write().await;
+        let index = state
+            .leases
+            .iter()
+            .position(|l| l.request_ids.iter().any(|id| id == request_id) && service.is_none_or(|s| s == l.owner))?;
+        let lease = state.leases.remove(index);
+        let record = record(now_ms(), &lease.conversation, &lease.owner, request_id, HandoffTransition::Returned, reason);
+        self.push_history(&mut state, record);
+        self.save(&state).await;
+        tracing::info!("{} returned responsibility for request {}: {}", lease.owner, request_id, reason);
+        Some(lease)
+    }
+
+    /// Copy of the current state for debugging; expired leases are left out.
+    pub async fn snapshot(&self) -> HandoffState {
+        let mut state = self.state.read().await.clone();
+        let now = now_ms();
+        state.leases.retain(|l| !l.is_expired(now));
+        state
+    }
+
+    fn lease_ms(&self) -> i64 {
+        self.lease.as_millis() as i64
+    }
+
+    /// End the conversation's lease if it ran out; `true` if the state changed.
+    fn expire(&self, state: &mut HandoffState, conversation: &ConversationKey, now: i64) -> bool {
+        let Some(index) = state.leases.iter().position(|l| l.conversation == *conversation && l.is_expired(now)) else {
+            return false;
+        };
+        let lease = state.leases.remove(index);
+        let last_request = lease.request_ids.last().cloned().unwrap_or_default();
+        tracing::info!("Responsibility lease of {} expired", lease.owner);
+        self.push_history(state, record(now, conversation, &lease.owner, &last_request, HandoffTransition::Expired, ""));
+        true
+    }
+
+    fn push_history(&self, state: &mut HandoffState, record: HandoffRecord) {
+        state.history.push_back(record);
+        while state.history.len() > self.max_history {
+            state.history.pop_front();
+        }
+    }
+
+    /// Write the state (temporary file, then rename); failures are logged and the state stays in memory.
+    async fn save(&self, state: &HandoffState) {
+        let Some(ref path) = self.path else {
+            return;
+        };
+        let json = match serde_json::to_string_pretty(state) {
+            Ok(json) => json,
+            Err(e) => {
+                tracing::warn!("Failed to serialize handoff state: {}", e);
+                return;
+            }
+        };
+        if let Some(parent) = path.parent() {
+            let _ = tokio::fs::create_dir_all(parent).await;
+        }
+        let tmp = path.with_extension("json.tmp");
+        let result = match tokio::fs::write(&tmp, json).await {
+            Ok(()) => tokio::fs::rename(&tmp, path).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            tracing::warn!("Failed to persist handoff state to {:?}: {}", path, e);
+        }
+    }
+}
+
+fn record(at_ms: i64, conversation: &ConversationKey, service: &str, request_id: &str, transition: HandoffTransition, detail: &str) -> HandoffRecord {
+    HandoffRecord {
+        at_ms,
+        conversation: conversation.clone(),
+        service: service.to_string(),
+        request_id: request_id.to_string(),
+        transition,
+        detail: detail.to_string(),
+    }
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
@@ -0,0 +1,290 @@
+//! Intent layer: Geri classifies a request against the Einherjar capabilities of all services
+//! (structured output) and extracts typed arguments for the chosen functions.
+//!
+//! Low confidence or missing arguments lead to a clarification question instead of an action.
+//! If Geri is unreachable or answers with something unusable, [`IntentClassifier::classify`]
+//! returns `None` and the caller falls back to keyword routing.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use super::action::{Action, ActionPlan};
+use super::UserRequest;
+use crate::clients::geri::geri::{ProcessPromptRequest, ProcessPromptResponse, ResponseFormat};
+use crate::clients::manager::ClientManager;
+use crate::protocols::einherjar::einherjar::FunctionDefinition;
+use crate::protocols::einherjar::AggregatedCapabilities;
+use crate::utils::config::IntentConfig;
+
+/// Asked when the model gives no clarification question of its own.
+const DEFAULT_CLARIFICATION: &str = "Could you say more precisely what you would like me to do?";
+
+/// LLM used for intent classification and the agent loop (Geri; a fake in tests).
+#[async_trait]
+pub trait IntentModel: Send + Sync {
+    async fn complete(&self, request: ProcessPromptRequest) -> Result<ProcessPromptResponse, String>;
+}
+
+#[async_trait]
+impl IntentModel for ClientManager {
+    async fn complete(&self, request: ProcessPromptRequest) -> Result<ProcessPromptResponse, String> {
+        self.process_geri_prompt(request).await
+    }
+}
+
+/// A function call with arguments converted to the declared parameter types.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionCall {
+    pub function: String,
+    pub arguments: Map<String, Value>,
+}
+
+/// What Odin should do with a request.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntentResolution {
+    /// Call functions of one service, in order.
+    Execute {
+        service: String,
+        calls: Vec<FunctionCall>,
+        confidence: f64,
+    },
+    /// Question or conversation without an action; answered via Geri.
+    Answer { confidence: f64 },
+    /// Ambiguous request or missing arguments; the question goes back to the user.
+    Clarify { question: String },
+}
+
+impl IntentResolution {
+    /// Action plan for [`Execute`](Self::Execute): one step per call, each after the previous one.
+    pub fn action_plan(&self) -> Option<ActionPlan> {
+        let IntentResolution::Execute { service, calls, .. } = self else {
+            return None;
+        };
+        let actions = calls
+            .iter()
+            .enumerate()
+            .map(|(index, call)| Action {
+                action_id: format!("a{}", index + 1),
+                action_type: call.function.clone(),
+                service: service.clone(),
+                parameters: Value::Object(call.arguments.clone()),
+                depends_on: if index == 0 { Vec::new() } else { vec![format!("a{}", index)] },
+                ..Default::default()
+            })
+            .collect();
+        Some(ActionPlan { actions })
+    }
+}
+
+/// Structured answer of the model, see [`intent_schema`].
+#[derive(Debug, Deserialize)]
+struct ModelIntent {
+    intent: String,
+    #[serde(default)]
+    service: String,
+    #[serde(default)]
+    calls: Vec<ModelCall>,
+    confidence: f64,
+    #[serde(default)]
+    clarification_question: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelCall {
+    function: String,
+    #[serde(default)]
+    arguments: Value,
+}
+
+/// Classifies requests via an [`IntentModel`].
+pub struct IntentClassifier {
+    model: Arc<dyn IntentModel>,
+    config: IntentConfig,
+}
+
+impl IntentClassifier {
+    pub fn new(model: Arc<dyn IntentModel>, config: IntentConfig) -> Self {
+        Self { model, config }
+    }
+
+    /// Classify the request; `None` if the model is unavailable or its answer unusable.
+    pub async fn classify(&self, request: &UserRequest, capabilities: &AggregatedCapabilities) -> Option<IntentResolution> {
+        let prompt_request = ProcessPromptRequest {
+            prompt: request.input.clone(),
+            model_name: self.config.model_name.clone(),
+            max_tokens: self.config.max_tokens,
+            system_prompt: system_prompt(capabilities),
+            user_id: request.user_id.clone(),
+            response_format: Some(ResponseFormat {
+                name: "intent".to_string(),
+                schema_json: intent_schema().to_string(),
+                strict: false,
+            }),
+            ..Default::default()
+        };
+        let response = match self.model.complete(prompt_request).await {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::warn!("Intent classification unavailable, using keyword routing: {}", e);
+                return None;
+            }
+        };
+        let json = if response.structured_json.is_empty() { response.text.as_str() } else { response.structured_json.as_str() };
+        let intent: ModelIntent = match serde_json::from_str(json) {
+            Ok(intent) => intent,
+            Err(e) => {
+                tracing::warn!("Unusable intent classification ({}), using keyword routing", e);
+                return None;
+            }
+        };
+        let resolution = self.resolve(intent, capabilities);
+        tracing::debug!("Intent for request {}: {:?}", request.request_id, resolution);
+        Some(resolution)
+    }
+
+    fn resolve(&self, intent: ModelIntent, capabilities: &AggregatedCapabilities) -> IntentResolution {
+        let clarify = |question: &str| IntentResolution::Clarify {
+            question: if question.trim().is_empty() { DEFAULT_CLARIFICATION.to_string() } else { question.trim().to_string() },
+        };
+        match intent.intent.as_str() {
+            "question" => return IntentResolution::Answer { confidence: intent.confidence },
+            "action" if intent.confidence >= self.config.min_confidence => {}
+            _ => return clarify(&intent.clarification_question),
+        }
+        if intent.calls.is_empty() {
+            return clarify(&intent.clarification_question);
+        }
+        let mut calls = Vec::with_capacity(intent.calls.len());
+        for call in intent.calls {
+            let Some(definition) = capabilities.function(&intent.service, &call.function) else {
+                tracing::warn!("Intent names unknown function {}.{}", intent.service, call.function);
+                return clarify(&intent.clarification_question);
+            };
+            match typed_arguments(definition, &call.arguments) {
+                Ok(arguments) => calls.push(FunctionCall { function: call.function, arguments }),
+                Err(question) => return IntentResolution::Clarify { question },
+            }
+        }
+        IntentResolution::Execute {
+            service: intent.service,
+            calls,
+            confidence: intent.confidence,
+        }
+    }
+}
+
+/// JSON Schema for the model's answer.
+pub fn intent_schema() -> Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "intent": { "type": "string", "enum": ["action", "question", "unclear"] },
+            "service": { "type": "string" },
+            "calls": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "function": { "type": "string" },
+                        "arguments": { "type": "object" }
+                    },
+                    "required": ["function", "arguments"]
+                }
+            },
+            "confidence": { "type": "number", "minimum": 0, "maximum": 1 },
+            "clarification_question": { "type": "string" }
+        },
+        "required": ["intent", "service", "calls", "confidence", "clarification_question"]
+    })
+}
+
+fn system_prompt(capabilities: &AggregatedCapabilities) -> String {
+    let mut prompt = String::from(
+        "You route requests of a personal assistant to services. Available services and functions:\n",
+    );
+    for service in capabilities.service_names() {
+        prompt.push_str(&format!("- {} ({})\n", service, capabilities.purposes[service]));
+        for f in capabilities.functions.iter().filter(|f| f.service_name == service) {
+            let params: Vec<String> = f
+                .function
+                .parameters
+                .iter()
+                .map(|p| {
+                    let mut param = format!("{}: {}{}", p.name, p.r#type, if p.required { "" } else { "?" });
+                    if !p.enum_values.is_empty() {
+                        param.push_str(&format!(" (one of {})", p.enum_values.join(", ")));
+                    }
+                    param
+                })
+                .collect();
+            prompt.push_str(&format!("  - {}({}): {}\n", f.function.name, params.join(", "), f.function.description));
+        }
+    }
+    prompt.push_str(
+        "\nClassify the user's request:\n\
+         - \"action\": the user wants something done that the listed functions do. Choose one service, \
+         the functions to call in order and their arguments taken from the request.\n\
+         - \"question\": questions, conversation and everything no function should do. A request that \
+         forbids an action (e.g. \"don't run anything\") is never an action.\n\
+         - \"unclear\": the request is ambiguous; ask a short question in clarification_question.\n\
+         Set confidence between 0 and 1. Use empty service and calls unless the intent is \"action\".",
+    );
+    prompt
+}
+
+/// Converts the arguments to the declared parameter types; `Err` holds a question for the user.
+fn typed_arguments(definition: &FunctionDefinition, arguments: &Value) -> Result<Map<String, Value>, String> {
+    let empty = Map::new();
+    let arguments = arguments.as_object().unwrap_or(&empty);
+    if definition.parameters.is_empty() {
+        return Ok(arguments.clone());
+    }
+    let mut typed = Map::new();
+    for param in &definition.parameters {
+        let label = if param.description.is_empty() { param.name.as_str() } else { param.description.as_str() };
+        let value = match arguments.get(&param.name) {
+            Some(Value::Null) | None if param.required => {
+                return Err(format!("Which {} should I use for {}?", label, definition.name));
+            }
+            Some(Value::Null) | None => continue,
+            Some(value) => value,
+        };
+        let converted = convert(value, &param.r#type)
+            .ok_or_else(|| format!("I understood \"{}\" for {}, but expected a {}. Could you rephrase?", value_text(value), label, param.r#type))?;
+        if !param.enum_values.is_empty() && !param.enum_values.iter().any(|v| *v == value_text(&converted)) {
+            return Err(format!("Which {} do you mean: {}?", label, param.enum_values.join(", ")));
+        }
+        typed.insert(param.name.clone(), converted);
+    }
+    Ok(typed)
+}
+
+fn convert(value: &Value, json_type: &str) -> Option<Value> {
+    match (json_type, value) {
+        ("string", Value::String(_)) => Some(value.clone()),
+        ("string", Value::Number(_) | Value::Bool(_)) => Some(Value::String(value.to_string())),
+        ("integer", Value::Number(n)) => n
+            .as_i64()
+            .or_else(|| n.as_f64().filter(|f| f.fract() == 0.0).map(|f| f as i64))
+            .map(Value::from),
+        ("integer", Value::String(s)) => s.trim().parse::<i64>().ok().map(Value::from),
+        ("number", Value::Number(_)) => Some(value.clone()),
+        ("number", Value::String(s)) => s.trim().parse::<f64>().ok().map(Value::from),
+        ("boolean", Value::Bool(_)) => Some(value.clone()),
+        ("boolean", Value::String(s)) => s.trim().parse::<bool>().ok().map(Value::Bool),
+        ("array", Value::Array(_)) | ("object", Value::Object(_)) => Some(value.clone()),
+        ("string" | "integer" | "number" | "boolean" | "array" | "object", _) => None,
+        // Unknown or missing type: take the value as it is
+        _ => Some(value.clone()),
+    }
+}
+
+fn value_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
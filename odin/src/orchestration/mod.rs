@@ -2,17 +2,41 @@
 //!
 //! - [`RequestProcessor`]: Parse → Route → Coordinate (via ResponsibilityManager or fallback).
 //! - [`ActionOrchestrator`]: Plan and execute actions via Thor.
+//! - [`PlanExecutor`]: Run an [`ActionPlan`] as a dependency DAG across Thor, Loki, Freki and Geri.
 //! - [`ResponsibilityManager`]: Determine and route requests to services (Geri, Thor, etc.).
+//! - [`IntentClassifier`]: Geri-based intent recognition with typed arguments; keywords are the offline fallback.
+//! - [`AgentLoop`]: Tool-use loop in which Geri calls Thor, Loki and Jotunheim tools within a step/time/token budget.
+//! - [`HandoffStore`]: Take/Return/Reject ownership of conversations with leases, persisted across restarts.
+//! - [`ConfirmationGate`]: Risk classification of plan steps; risky plans run only after the user confirms them.
+//! - [`RoutineEngine`]: User routines fired by cron schedules and device events, run as their owner.
+//! - [`ProgressReporter`]: Routing, retrieval, token and action events of a request for `ProcessStream`.
+//! - [`ConversationStore`]: Per user/device/session conversation history sent to Geri.
 //! - [`OrchestrationError`]: Structured errors for orchestration flows.
 
+pub mod agent;
 pub mod audit;
+pub mod confirmation;
+pub mod conversation;
 pub mod error;
+pub mod executor;
+pub mod handoff;
+pub mod intent;
 pub mod processor;
+pub mod progress;
+pub mod routine;
 pub mod action;
 pub mod responsibility;
 
+pub use agent::*;
 pub use audit::*;
+pub use confirmation::*;
+pub use conversation::*;
 pub use error::*;
+pub use executor::*;
+pub use handoff::*;
+pub use intent::*;
 pub use processor::*;
+pub use progress::*;
+pub use routine::*;
 pub use action::*;
 pub use responsibility::*;
\ No newline at end of file
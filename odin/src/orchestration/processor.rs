@@ -1,8 +1,11 @@
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use super::audit::{AuditEvent, AuditLogger};
-use super::responsibility;
+use super::intent::{IntentClassifier, IntentResolution};
+use super::progress::ProgressReporter;
+use super::responsibility::{self, RoutedResponse};
 use super::ActionOrchestrator;
+use crate::protocols::einherjar::CapabilityCache;
 use crate::utils::{MonitoringService, ParallelProcessor, QueuedRequest, RequestQueue, ResponseCache};
 
 /// User input as received by the orchestrator (from platform or Huginn).
@@ -14,6 +17,9 @@ pub struct UserRequest {
     pub input: String,
     /// Input kind: `"text"`, `"audio"`, `"image"`, `"video"`.
     pub input_type: String,
+    /// Conversation id; empty means one running conversation per user and device.
+    #[serde(default)]
+    pub session_id: String,
 }
 
 impl From<QueuedRequest> for UserRequest {
@@ -24,6 +30,7 @@ impl From<QueuedRequest> for UserRequest {
             device_id: q.device_id,
             input: q.input,
             input_type: q.input_type,
+            session_id: String::new(),
         }
     }
 }
@@ -32,6 +39,7 @@ impl From<QueuedRequest> for UserRequest {
 pub struct RequestProcessor {
     responsibility_manager: Option<Arc<responsibility::ResponsibilityManager>>,
     action_orchestrator: Option<Arc<ActionOrchestrator>>,
+    intent_classifier: Option<(Arc<IntentClassifier>, Arc<CapabilityCache>)>,
     audit_logger: Option<Arc<dyn AuditLogger>>,
     monitoring: Option<Arc<MonitoringService>>,
     response_cache: Option<Arc<ResponseCache>>,
@@ -43,6 +51,7 @@ impl RequestProcessor {
         Self {
             responsibility_manager: None,
             action_orchestrator: None,
+            intent_classifier: None,
             audit_logger: None,
             monitoring: None,
             response_cache: None,
@@ -54,6 +63,7 @@ impl RequestProcessor {
         Self {
             responsibility_manager: Some(responsibility_manager),
             action_orchestrator: None,
+            intent_classifier: None,
             audit_logger: None,
             monitoring: None,
             response_cache: None,
@@ -65,12 +75,19 @@ impl RequestProcessor {
         Self {
             responsibility_manager: None,
             action_orchestrator: Some(Arc::new(action_orchestrator)),
+            intent_classifier: None,
             audit_logger: None,
             monitoring: None,
             response_cache: None,
         }
     }
 
+    /// Fallback path: classify text via Geri against the cached capabilities before planning by keywords.
+    pub fn with_intent_classifier(mut self, classifier: Arc<IntentClassifier>, capabilities: Arc<CapabilityCache>) -> Self {
+        self.intent_classifier = Some((classifier, capabilities));
+        self
+    }
+
     /// Attach an audit logger; call after construction (e.g. `processor.with_audit_logger(logger)`).
     pub fn with_audit_logger(mut self, logger: Arc<dyn AuditLogger>) -> Self {
         self.audit_logger = Some(logger);
@@ -91,6 +108,25 @@ impl RequestProcessor {
 
     /// Process a user request; returns response string or error (e.g. [`OrchestrationError`](crate::orchestration::OrchestrationError)).
     pub async fn process(&self, request: UserRequest) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        self.process_with_progress(request, &ProgressReporter::disabled()).await
+    }
+
+    /// Like [`process`](Self::process), reporting routing, sources, Geri's tokens and actions as they happen.
+    pub async fn process_with_progress(
+        &self,
+        request: UserRequest,
+        progress: &ProgressReporter,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.process_resolved(request, progress).await?.response)
+    }
+
+    /// Like [`process_with_progress`](Self::process_with_progress), also returning the classified
+    /// intent (`None` for cached responses and keyword routing).
+    pub async fn process_resolved(
+        &self,
+        request: UserRequest,
+        progress: &ProgressReporter,
+    ) -> Result<RoutedResponse, Box<dyn std::error::Error + Send + Sync>> {
         if let Some(ref m) = self.monitoring {
             m.update_active_requests(1).await;
         }
@@ -99,12 +135,12 @@ impl RequestProcessor {
                 if let Some(ref m) = self.monitoring {
                     m.update_active_requests(0).await;
                 }
-                return Ok(cached);
+                return Ok(RoutedResponse { response: cached, intent: None });
             }
         }
-        let result = self.process_inner(request.clone()).await;
-        if let (Some(ref c), Ok(ref s)) = (self.response_cache.as_ref(), &result) {
-            c.set(request.request_id.clone(), s.clone()).await;
+        let result = self.process_inner(request.clone(), progress).await;
+        if let (Some(ref c), Ok(ref routed)) = (self.response_cache.as_ref(), &result) {
+            c.set(request.request_id.clone(), routed.response.clone()).await;
         }
         if let Some(ref m) = self.monitoring {
             m.update_active_requests(0).await;
@@ -133,7 +169,7 @@ impl RequestProcessor {
         .await
     }
 
-    async fn process_inner(&self, request: UserRequest) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    async fn process_inner(&self, request: UserRequest, progress: &ProgressReporter) -> Result<RoutedResponse, Box<dyn std::error::Error + Send + Sync>> {
         if let Some(ref log) = self.audit_logger {
             log.log(&AuditEvent::RequestReceived {
                 request_id: request.request_id.clone(),
@@ -143,27 +179,46 @@ impl RequestProcessor {
             });
         }
         if let Some(ref resp_manager) = self.responsibility_manager {
-            return resp_manager.route_request(&request).await;
+            return resp_manager.route_request_resolved(&request, progress).await;
         }
+        let unresolved = |response: String| RoutedResponse { response, intent: None };
 
         let input_lower = request.input.to_lowercase();
         if request.input_type == "audio" || input_lower.contains("transcribe") || input_lower.contains("speech") {
-            return Ok("Audio processing requires Huginn-Muninn service (not available without responsibility manager)".to_string());
+            return Ok(unresolved("Audio processing requires Huginn-Muninn service (not available without responsibility manager)".to_string()));
         }
         if request.input_type == "image" || request.input_type == "video" {
-            return Ok("Vision processing requires Geri service (not available without responsibility manager)".to_string());
+            return Ok(unresolved("Vision processing requires Geri service (not available without responsibility manager)".to_string()));
         }
         if request.input_type == "text" {
+            let intent = match self.intent_classifier {
+                Some((ref classifier, ref capabilities)) => classifier.classify(&request, &capabilities.get_aggregated().await).await,
+                None => None,
+            };
+            if let Some(IntentResolution::Clarify { ref question }) = intent {
+                return Ok(RoutedResponse { response: question.clone(), intent });
+            }
             if let Some(ref ao) = self.action_orchestrator {
-                if let Ok(plan) = ao.plan_actions(&request.input).await {
+                // A classified intent runs only its own calls; keywords plan only unclassified requests
+                let plan = match intent {
+                    Some(ref resolution) => resolution.action_plan(),
+                    None => ao.plan_actions(&request.input).await.ok(),
+                };
+                if let Some(plan) = plan {
+                    if !plan.actions.is_empty() && ao.can_execute() {
+                        let report = ao.execute_plan_with_progress(&plan, progress).await?;
+                        return Ok(RoutedResponse { response: report.summary(), intent });
+                    }
                     if !plan.actions.is_empty() {
                         let first_type = &plan.actions[0].action_type;
-                        return Ok(format!("Planned {} action(s): {}", plan.actions.len(), first_type));
+                        let response = format!("Planned {} action(s): {}", plan.actions.len(), first_type);
+                        return Ok(RoutedResponse { response, intent });
                     }
                 }
             }
-            return Ok("Text processing requires Geri service (not available without responsibility manager)".to_string());
+            let response = "Text processing requires Geri service (not available without responsibility manager)".to_string();
+            return Ok(RoutedResponse { response, intent });
         }
-        Ok(format!("Request received: {} (type: {}). Responsibility manager required for full processing.", request.input, request.input_type))
+        Ok(unresolved(format!("Request received: {} (type: {}). Responsibility manager required for full processing.", request.input, request.input_type)))
     }
 }
@@ -0,0 +1,86 @@
+//! Progress of a request while Odin waits on Freki, Geri, Thor and the plugins.
+//!
+//! Routing, retrieval, Geri's answer and plan execution report [`ProgressEvent`]s to a
+//! [`ProgressReporter`]. Unary calls use [`ProgressReporter::disabled`]; `ProcessStream` forwards
+//! the events to the client as they happen.
+
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+use super::confirmation::PendingConfirmation;
+use super::executor::StepStatus;
+
+/// Longest excerpt of a retrieved document sent with [`ProgressEvent::Sources`].
+const EXCERPT_CHARS: usize = 200;
+
+/// A document Freki retrieved as context for Geri.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RetrievedSource {
+    pub id: String,
+    pub score: f32,
+    /// Beginning of the document.
+    pub excerpt: String,
+}
+
+impl RetrievedSource {
+    pub fn new(id: String, score: f32, content: &str) -> Self {
+        Self { id, score, excerpt: content.chars().take(EXCERPT_CHARS).collect() }
+    }
+}
+
+/// Something that happened while processing a request.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    /// The service or plugin that handles the request, and why.
+    Routing { service: String, reason: String },
+    /// Context retrieved from Freki.
+    Sources { sources: Vec<RetrievedSource> },
+    /// Text Geri generated since the previous delta.
+    TokenDelta { text: String },
+    ActionStarted { action_id: String, action_type: String, service: String },
+    ActionFinished { action_id: String, status: StepStatus, error: Option<String> },
+    /// Risky actions are held until the user answers (see [`ConfirmationGate`](super::ConfirmationGate)).
+    ConfirmationRequested { confirmation_id: String, prompt: String, expires_at_ms: i64 },
+}
+
+impl ProgressEvent {
+    pub fn confirmation_requested(pending: &PendingConfirmation) -> Self {
+        ProgressEvent::ConfirmationRequested {
+            confirmation_id: pending.confirmation_id.clone(),
+            prompt: pending.prompt(),
+            expires_at_ms: pending.expires_at_ms,
+        }
+    }
+}
+
+/// Where the progress events of one request go.
+#[derive(Debug, Clone, Default)]
+pub struct ProgressReporter {
+    sender: Option<mpsc::UnboundedSender<ProgressEvent>>,
+}
+
+impl ProgressReporter {
+    /// Reporter whose events arrive at the returned receiver, in the order they were emitted.
+    pub fn channel() -> (Self, mpsc::UnboundedReceiver<ProgressEvent>) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        (Self { sender: Some(sender) }, receiver)
+    }
+
+    /// Reporter that drops every event.
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Whether anyone listens; e.g. Geri's answer is only streamed then.
+    pub fn is_enabled(&self) -> bool {
+        self.sender.as_ref().is_some_and(|sender| !sender.is_closed())
+    }
+
+    pub fn emit(&self, event: ProgressEvent) {
+        if let Some(ref sender) = self.sender {
+            // The listener may be gone (client disconnected); processing goes on regardless
+            let _ = sender.send(event);
+        }
+    }
+}
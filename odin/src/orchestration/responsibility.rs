@@ -4,12 +4,36 @@ use crate::protocols::manager::ProtocolManager;
 use crate::orchestration::UserRequest;
 use crate::orchestration::error::OrchestrationError;
 use crate::clients::manager::ClientManager;
+use crate::orchestration::confirmation::ConfirmationGate;
+use crate::orchestration::conversation::{chat_message, ConversationKey, ConversationStore};
+use crate::orchestration::handoff::HandoffStore;
+use crate::orchestration::intent::{IntentClassifier, IntentResolution};
+use crate::orchestration::progress::{ProgressEvent, ProgressReporter, RetrievedSource};
+use crate::plugins::PluginManager;
+use crate::utils::config::HandoffConfig;
+
+/// Answer of a routed request and the intent Geri classified it as; `intent` is `None` when
+/// routing fell back to keyword scoring. The actions of a resolved intent already ran during routing.
+#[derive(Debug, Clone)]
+pub struct RoutedResponse {
+    pub response: String,
+    pub intent: Option<IntentResolution>,
+}
 
 /// Determines which service/plugin handles a request and routes it (Einherjar + Responsibility protocol).
+///
+/// The service that accepts a request owns the conversation (see [`HandoffStore`]): following turns
+/// go straight to it until it returns responsibility or its lease expires. Rejections cascade to
+/// the next candidate in score order.
 pub struct ResponsibilityManager {
     capability_cache: Arc<CapabilityCache>,
     protocol_manager: Arc<ProtocolManager>,
     client_manager: Arc<ClientManager>,
+    conversation_store: Option<Arc<ConversationStore>>,
+    intent_classifier: Option<Arc<IntentClassifier>>,
+    handoffs: Arc<HandoffStore>,
+    plugin_manager: Option<Arc<PluginManager>>,
+    confirmation: Option<Arc<ConfirmationGate>>,
 }
 
 impl ResponsibilityManager {
@@ -22,26 +46,80 @@ impl ResponsibilityManager {
             capability_cache,
             protocol_manager,
             client_manager,
+            conversation_store: None,
+            intent_classifier: None,
+            handoffs: Arc::new(HandoffStore::new(
+                std::time::Duration::from_secs(HandoffConfig::default().lease_secs),
+                HandoffConfig::default().max_history,
+            )),
+            plugin_manager: None,
+            confirmation: None,
         }
     }
 
+    /// Keep conversation ownership in `store` (e.g. persisted, so it survives restarts).
+    pub fn with_handoff_store(mut self, store: Arc<HandoffStore>) -> Self {
+        self.handoffs = store;
+        self
+    }
+
+    /// Route to registered plugins (Frigg, Valkyries) that take responsibility.
+    pub fn with_plugin_manager(mut self, plugin_manager: Arc<PluginManager>) -> Self {
+        self.plugin_manager = Some(plugin_manager);
+        self
+    }
+
+    /// Hold plans with risky actions until the originating device confirms them.
+    pub fn with_confirmation_gate(mut self, gate: Arc<ConfirmationGate>) -> Self {
+        self.confirmation = Some(gate);
+        self
+    }
+
+    /// Ownership state (leases and transitions), e.g. for the Responsibility service and debugging.
+    pub fn handoff_store(&self) -> Arc<HandoffStore> {
+        self.handoffs.clone()
+    }
+
+    /// Route via Geri-based intent recognition; keyword scoring stays as fallback when Geri is unavailable.
+    pub fn with_intent_classifier(mut self, classifier: Arc<IntentClassifier>) -> Self {
+        self.intent_classifier = Some(classifier);
+        self
+    }
+
+    /// Keep conversation history per user/device/session and send it to Geri with each prompt.
+    pub fn with_conversation_store(mut self, store: Arc<ConversationStore>) -> Self {
+        self.conversation_store = Some(store);
+        self
+    }
+
     /// Determine which service should handle the request
     /// Returns service name and relevance score
     pub async fn determine_responsibility(
         &self,
         request: &UserRequest,
     ) -> Result<Option<(String, f64)>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.ranked_services(request).await?.into_iter().next())
+    }
+
+    /// All services with a positive relevance score, highest first (the handoff order).
+    pub async fn ranked_services(
+        &self,
+        request: &UserRequest,
+    ) -> Result<Vec<(String, f64)>, Box<dyn std::error::Error + Send + Sync>> {
         // Get all cached capabilities
         let all_capabilities = self.capability_cache.get_all().await;
         
-        if all_capabilities.is_empty() {
+        let all_capabilities = if all_capabilities.is_empty() {
             // No capabilities discovered yet - try to discover
             self.protocol_manager.discover_all_capabilities().await?;
             let all_capabilities = self.capability_cache.get_all().await;
             if all_capabilities.is_empty() {
-                return Ok(None);
+                return Ok(Vec::new());
             }
-        }
+            all_capabilities
+        } else {
+            all_capabilities
+        };
 
         // Score each service based on relevance
         let mut scored_services: Vec<(String, f64)> = Vec::new();
@@ -55,12 +133,10 @@ impl ResponsibilityManager {
 
         // Sort by score (highest first)
         scored_services.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-
-        // Return the highest scoring service
-        Ok(scored_services.first().map(|(name, score)| (name.clone(), *score)))
+        Ok(scored_services)
     }
 
-    /// Calculate relevance score for a service based on request
+    /// Calculate relevance score for a service based on request (keyword fallback without intent recognition)
     pub async fn calculate_relevance_score(
         &self,
         request: &UserRequest,
@@ -129,52 +205,181 @@ impl ResponsibilityManager {
         &self,
         request: &UserRequest,
     ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        // Determine responsible service
-        let (service_name, score) = match self.determine_responsibility(request).await? {
-            Some((name, score)) => (name, score),
-            None => {
-                // No service found - try to discover capabilities first
+        self.route_request_with_progress(request, &ProgressReporter::disabled()).await
+    }
+
+    /// Like [`route_request`](Self::route_request), reporting the routing decision, retrieved
+    /// sources, Geri's answer as it is generated and the executed actions.
+    pub async fn route_request_with_progress(
+        &self,
+        request: &UserRequest,
+        progress: &ProgressReporter,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(self.route_request_resolved(request, progress).await?.response)
+    }
+
+    /// Like [`route_request_with_progress`](Self::route_request_with_progress), also returning the
+    /// classified intent so callers do not plan actions from the answer text on top of it.
+    pub async fn route_request_resolved(
+        &self,
+        request: &UserRequest,
+        progress: &ProgressReporter,
+    ) -> Result<RoutedResponse, Box<dyn std::error::Error + Send + Sync>> {
+        let unresolved = |response: String| RoutedResponse { response, intent: None };
+
+        // A yes/no to a held plan (typed, or spoken and transcribed by Huginn) decides it
+        if let Some(ref gate) = self.confirmation {
+            if let Some(outcome) = gate.answer(request).await {
+                let action_orchestrator = crate::orchestration::ActionOrchestrator::new_with_client(self.client_manager.clone());
+                return Ok(unresolved(outcome.execute_with_progress(&action_orchestrator, progress).await?));
+            }
+        }
+
+        // A service that took over the conversation gets every turn until it returns it
+        let conversation = ConversationKey::from_request(request);
+        if let Some(lease) = self.handoffs.route_to_owner(&conversation, &request.request_id).await {
+            tracing::info!("Routing request to {} (owns the conversation)", lease.owner);
+            progress.emit(ProgressEvent::Routing {
+                service: lease.owner.clone(),
+                reason: "Owns the conversation".to_string(),
+            });
+            return self.execute_service_request(&lease.owner, request, None, progress).await.map(unresolved);
+        }
+
+        if let Some(ref classifier) = self.intent_classifier {
+            if self.capability_cache.get_all().await.is_empty() {
                 self.protocol_manager.discover_all_capabilities().await?;
-                
-                // Try again after discovery
-                match self.determine_responsibility(request).await? {
-                    Some((name, score)) => (name, score),
-                    None => {
-                        return Err(Box::new(OrchestrationError::NoServiceFound));
-                    }
-                }
             }
-        };
+            let capabilities = self.capability_cache.get_aggregated().await;
+            if let Some(resolution) = classifier.classify(request, &capabilities).await {
+                let response = self.route_intent(request, &resolution, progress).await?;
+                return Ok(RoutedResponse { response, intent: Some(resolution) });
+            }
+        }
+
+        // Determine responsible services, best first
+        let mut ranked = self.ranked_services(request).await?;
+        if ranked.is_empty() {
+            // No service found - try to discover capabilities first
+            self.protocol_manager.discover_all_capabilities().await?;
+            ranked = self.ranked_services(request).await?;
+        }
+        if ranked.is_empty() {
+            return Err(Box::new(OrchestrationError::NoServiceFound));
+        }
 
-        tracing::info!("Routing request to {} (score: {})", service_name, score);
+        let candidates = ranked
+            .into_iter()
+            .map(|(name, score)| (name, format!("Relevance score: {}", score)))
+            .collect();
+        let service_name = self.hand_off(request, candidates, progress).await?;
+        self.execute_service_request(&service_name, request, None, progress).await.map(unresolved)
+    }
 
-        // Try to take responsibility
-        let take_request = crate::protocols::responsibility::responsibility::TakeResponsibilityRequest {
-            request_id: request.request_id.clone(),
-            user_id: request.user_id.clone(),
-            device_id: request.device_id.clone(),
-            input: request.input.clone(),
-            input_type: request.input_type.clone(),
-            reason: format!("Relevance score: {}", score),
+    /// Act on a classified intent: ask back, answer via Geri, or hand the function calls to the service.
+    async fn route_intent(
+        &self,
+        request: &UserRequest,
+        resolution: &IntentResolution,
+        progress: &ProgressReporter,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let (intent_service, reason) = match resolution {
+            IntentResolution::Clarify { question } => return Ok(question.clone()),
+            IntentResolution::Answer { .. } => {
+                progress.emit(ProgressEvent::Routing { service: "geri".to_string(), reason: "Intent: answer".to_string() });
+                return self.execute_service_request("geri", request, Some(resolution), progress).await;
+            }
+            IntentResolution::Execute { service, calls, confidence } => {
+                let functions: Vec<&str> = calls.iter().map(|c| c.function.as_str()).collect();
+                (service.clone(), format!("Intent: {} (confidence: {:.2})", functions.join(", "), confidence))
+            }
         };
 
-        match self.protocol_manager.take_responsibility(&service_name, take_request).await {
-            Ok(response) => {
-                if response.accepted {
+        // If the intended service declines, the keyword ranking provides the next candidates
+        let mut candidates = vec![(intent_service.clone(), reason)];
+        for (name, score) in self.ranked_services(request).await? {
+            if name != intent_service {
+                candidates.push((name.clone(), format!("Fallback after {} (score: {})", intent_service, score)));
+            }
+        }
+        let service_name = self.hand_off(request, candidates, progress).await?;
+        match (service_name.as_str(), resolution.action_plan()) {
+            // Executable services get the extracted function calls as an action plan
+            ("thor" | "loki", Some(plan)) if service_name == intent_service => {
+                let action_orchestrator = crate::orchestration::ActionOrchestrator::new_with_client(self.client_manager.clone());
+                if let Some(question) = self.hold_for_confirmation(request, &plan, &action_orchestrator, progress).await {
+                    return Ok(question);
+                }
+                let report = action_orchestrator.execute_plan_with_progress(&plan, progress).await?;
+                Ok(report.response_text())
+            }
+            _ => self.execute_service_request(&service_name, request, Some(resolution), progress).await,
+        }
+    }
+
+    /// Hold a plan with risky steps for confirmation; returns the question for the user, or `None`
+    /// if the plan may run right away.
+    async fn hold_for_confirmation(
+        &self,
+        request: &UserRequest,
+        plan: &crate::orchestration::ActionPlan,
+        action_orchestrator: &crate::orchestration::ActionOrchestrator,
+        progress: &ProgressReporter,
+    ) -> Option<String> {
+        let gate = self.confirmation.as_ref()?;
+        let pending = gate.check(request, plan, action_orchestrator).await?;
+        progress.emit(ProgressEvent::confirmation_requested(&pending));
+        Some(pending.prompt())
+    }
+
+    /// Ask the candidates in order to take responsibility; the first to accept is returned and,
+    /// unless it only handles this request, owns the conversation. Rejections and unreachable
+    /// services cascade to the next candidate.
+    async fn hand_off(
+        &self,
+        request: &UserRequest,
+        candidates: Vec<(String, String)>,
+        progress: &ProgressReporter,
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        let conversation = ConversationKey::from_request(request);
+        let mut rejections = Vec::new();
+        for (service_name, reason) in candidates {
+            tracing::info!("Routing request to {} ({})", service_name, reason);
+            let take_request = crate::protocols::responsibility::responsibility::TakeResponsibilityRequest {
+                request_id: request.request_id.clone(),
+                user_id: request.user_id.clone(),
+                device_id: request.device_id.clone(),
+                input: request.input.clone(),
+                input_type: request.input_type.clone(),
+                reason: reason.clone(),
+                session_id: request.session_id.clone(),
+                lease_expires_at: self.handoffs.lease_expires_at(),
+            };
+            let message = match self.protocol_manager.take_responsibility(&service_name, take_request).await {
+                Ok(response) if response.accepted => {
                     tracing::info!("Service {} accepted responsibility", service_name);
-                    // Route to service based on service type
-                    self.execute_service_request(&service_name, request).await
-                } else {
-                    // Service rejected - try next best service from cache
+                    if !response.one_shot {
+                        self.handoffs.take(&conversation, &service_name, &request.request_id, &reason).await;
+                    }
+                    progress.emit(ProgressEvent::Routing { service: service_name.clone(), reason });
+                    return Ok(service_name);
+                }
+                Ok(response) => {
                     tracing::warn!("Service {} rejected responsibility: {}", service_name, response.message);
-                    self.try_fallback_service(request, &service_name).await
+                    response.message
                 }
-            }
-            Err(e) => {
-                tracing::error!("Failed to take responsibility: {}", e);
-                Err(e.into())
-            }
+                Err(e) => {
+                    tracing::warn!("Failed to take responsibility with {}: {}", service_name, e);
+                    e
+                }
+            };
+            self.handoffs.reject(&conversation, &service_name, &request.request_id, &message).await;
+            rejections.push(format!("{}: {}", service_name, message));
+        }
+        if rejections.is_empty() {
+            return Err(Box::new(OrchestrationError::NoServiceFound));
         }
+        Err(Box::new(OrchestrationError::ServiceRejected(rejections.join("; "))))
     }
 
     /// Select model via Skuld
@@ -196,7 +401,7 @@ impl ResponsibilityManager {
     }
 
     /// Get RAG context from Freki
-    async fn get_rag_context(&self, query: &str) -> Result<String, Box<dyn std::error::Error>> {
+    async fn get_rag_context(&self, query: &str, progress: &ProgressReporter) -> Result<String, Box<dyn std::error::Error>> {
         // Create embedding request for Freki
         // Note: In real implementation, we'd need to generate embeddings first
         // For now, we'll use a placeholder
@@ -208,6 +413,15 @@ impl ResponsibilityManager {
         
         match self.client_manager.retrieve_freki_context(freki_request).await {
             Ok(response) => {
+                if !response.documents.is_empty() {
+                    progress.emit(ProgressEvent::Sources {
+                        sources: response
+                            .documents
+                            .iter()
+                            .map(|doc| RetrievedSource::new(doc.id.clone(), doc.score, &doc.content))
+                            .collect(),
+                    });
+                }
                 // Combine retrieved documents into context
                 let context: Vec<String> = response.documents
                     .iter()
@@ -222,85 +436,94 @@ impl ResponsibilityManager {
         }
     }
 
-    /// Try fallback service if primary service rejected
-    async fn try_fallback_service(
+    /// Geri's answer as a stream of token deltas, collected into the unary response. Falls back to
+    /// `ProcessPrompt` if Geri cannot stream; an interrupted stream is an error (deltas were sent).
+    async fn stream_geri_prompt(
         &self,
-        request: &UserRequest,
-        rejected_service: &str,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        // Get all capabilities and find next best match
-        let all_capabilities = self.capability_cache.get_all().await;
-        
-        let mut scored_services: Vec<(String, f64)> = Vec::new();
-        for cached in &all_capabilities {
-            if cached.service_name != rejected_service {
-                let score = self.calculate_relevance_score(request, &cached.capability).await;
-                if score > 0.0 {
-                    scored_services.push((cached.service_name.clone(), score));
-                }
+        geri_request: crate::clients::geri::geri::ProcessPromptRequest,
+        progress: &ProgressReporter,
+    ) -> Result<crate::clients::geri::geri::ProcessPromptResponse, String> {
+        use crate::clients::geri::geri::process_prompt_stream_chunk::Chunk;
+
+        let mut stream = match self.client_manager.stream_geri_prompt(geri_request.clone()).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                tracing::debug!("Geri streaming unavailable, using ProcessPrompt: {}", e);
+                return self.client_manager.process_geri_prompt(geri_request).await;
             }
-        }
-        
-        scored_services.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
-        
-        if let Some((service_name, score)) = scored_services.first() {
-            tracing::info!("Trying fallback service {} (score: {})", service_name, score);
-            
-            // Try to take responsibility with fallback service
-            let take_request = crate::protocols::responsibility::responsibility::TakeResponsibilityRequest {
-                request_id: request.request_id.clone(),
-                user_id: request.user_id.clone(),
-                device_id: request.device_id.clone(),
-                input: request.input.clone(),
-                input_type: request.input_type.clone(),
-                reason: format!("Fallback after {} rejected (score: {})", rejected_service, score),
-            };
-            
-            match self.protocol_manager.take_responsibility(service_name, take_request).await {
-                Ok(response) => {
-                    if response.accepted {
-                        tracing::info!("Fallback service {} accepted responsibility", service_name);
-                        self.execute_service_request(service_name, request).await
-                    } else {
-                        Err(Box::new(OrchestrationError::ServiceRejected(
-                            format!("Fallback service {} also rejected: {}", service_name, response.message),
-                        )))
-                    }
+        };
+        let mut response = crate::clients::geri::geri::ProcessPromptResponse::default();
+        while let Some(chunk) = stream.message().await.map_err(|e| format!("Geri stream interrupted: {}", e))? {
+            match chunk.chunk {
+                Some(Chunk::Delta(delta)) => {
+                    response.text.push_str(&delta.text);
+                    progress.emit(ProgressEvent::TokenDelta { text: delta.text });
                 }
-                Err(e) => {
-                    Err(format!("Failed to take responsibility with fallback service: {}", e).into())
+                Some(Chunk::ToolCall(tool_call)) => response.tool_calls.push(tool_call),
+                Some(Chunk::Done(done)) => {
+                    response.tokens_used = done.tokens_used;
+                    response.model_used = done.model_used;
+                    response.trimmed_messages = done.trimmed_messages;
+                    response.structured_json = done.structured_json;
+                    response.safety_decisions = done.safety_decisions;
                 }
+                None => {}
             }
-        } else {
-            Err(Box::new(OrchestrationError::NoFallbackService))
         }
+        Ok(response)
     }
 
-    /// Execute request on a specific service
+    /// Execute request on a specific service; `intent` is the classified intent, if any.
     async fn execute_service_request(
         &self,
         service_name: &str,
         request: &UserRequest,
+        intent: Option<&IntentResolution>,
+        progress: &ProgressReporter,
     ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
         match service_name {
             "geri" => {
                 // Route to Geri for LLM processing
                 // First, try to get context from Freki (RAG)
-                let context = self.get_rag_context(&request.input).await.unwrap_or_default();
+                let context = self.get_rag_context(&request.input, progress).await.unwrap_or_default();
                 
                 // Use Skuld for model selection
                 let model_name = self.select_model(&request.input).await.unwrap_or_default();
                 
+                // Earlier turns of this conversation; Geri trims them to the model's context window
+                let conversation_key = ConversationKey::from_request(request);
+                let messages = match self.conversation_store {
+                    Some(ref store) => store.history(&conversation_key).await,
+                    None => Vec::new(),
+                };
+                
                 let geri_request = crate::clients::geri::geri::ProcessPromptRequest {
                     prompt: request.input.clone(),
                     context,
                     model_name,
                     max_tokens: 1000,
                     system_prompt: String::new(), // Can be extended if Odin has specific system instructions
+                    messages,
+                    user_id: request.user_id.clone(),
+                    ..Default::default()
                 };
                 
-                match self.client_manager.process_geri_prompt(geri_request).await {
-                    Ok(response) => Ok(response.text),
+                let response = if progress.is_enabled() {
+                    self.stream_geri_prompt(geri_request, progress).await
+                } else {
+                    self.client_manager.process_geri_prompt(geri_request).await
+                };
+                match response {
+                    Ok(response) => {
+                        if let Some(ref store) = self.conversation_store {
+                            store.trim_oldest(&conversation_key, response.trimmed_messages as usize).await;
+                            store.append(&conversation_key, vec![
+                                chat_message("user", request.input.clone()),
+                                chat_message("assistant", response.text.clone()),
+                            ]).await;
+                        }
+                        Ok(response.text)
+                    }
                     Err(e) => Err(Box::new(OrchestrationError::ActionFailed(format!("Geri: {}", e)))),
                 }
             }
@@ -313,21 +536,36 @@ impl ResponsibilityManager {
             }
             "thor" => {
                 // Route to Thor for action execution via ActionOrchestrator
-                // Create a simple action plan from the request
                 let action_orchestrator = crate::orchestration::ActionOrchestrator::new_with_client(
                     self.client_manager.clone()
                 );
                 
-                let action_plan = action_orchestrator.plan_actions(&request.input).await
-                    .map_err(|e| Box::new(OrchestrationError::ActionFailed(format!("plan: {}", e))) as Box<dyn std::error::Error + Send + Sync>)?;
+                // A classified intent runs only its own calls; keywords plan only unclassified requests
+                let action_plan = match intent {
+                    Some(resolution) => resolution.action_plan().unwrap_or(crate::orchestration::ActionPlan { actions: Vec::new() }),
+                    None => action_orchestrator.plan_actions(&request.input).await
+                        .map_err(|e| Box::new(OrchestrationError::ActionFailed(format!("plan: {}", e))) as Box<dyn std::error::Error + Send + Sync>)?,
+                };
+                if let Some(question) = self.hold_for_confirmation(request, &action_plan, &action_orchestrator, progress).await {
+                    return Ok(question);
+                }
                 
-                let results = action_orchestrator.execute_actions(action_plan).await
+                let results = action_orchestrator.execute_actions_with_progress(action_plan, progress).await
                     .map_err(|e| Box::new(OrchestrationError::ActionFailed(format!("execute: {}", e))) as Box<dyn std::error::Error + Send + Sync>)?;
                 
                 // Combine results into response
                 Ok(results.join("\n"))
             }
             _ => {
+                // Plugins (Frigg, Valkyries) handle the request themselves
+                if let Some(ref plugins) = self.plugin_manager {
+                    if let Some(plugin) = plugins.get(service_name).await {
+                        let result = plugin.process_request(&request.input).await;
+                        return result.map_err(|e| {
+                            Box::new(OrchestrationError::ActionFailed(format!("{}: {}", service_name, e))) as Box<dyn std::error::Error + Send + Sync>
+                        });
+                    }
+                }
                 Err(Box::new(OrchestrationError::ServiceNotImplemented(
                     service_name.to_string(),
                 )))
@@ -0,0 +1,852 @@
+//! Routines: automations a user defines and Odin runs on its own, e.g. "every weekday at 7:00 read
+//! my calendar summary" or "when the living-room sensor reports more than 28 °C, turn on the fan
+//! and tell me".
+//!
+//! A [`Routine`] fires on a [`RoutineTrigger`] – a cron schedule in the routine's time zone, or a
+//! device/sensor event reported by Jotunheim devices or Loki (`ReportDeviceEvent`). If its
+//! [`RoutineCondition`]s hold, its steps run in order: request steps take the same path as the
+//! owner's own requests (routing, Heimdall permissions, confirmation of risky actions), notify
+//! steps reach the owner's devices through `WatchNotifications`.
+//!
+//! Routines and the latest runs of each routine are kept in the [`RoutineStore`], which writes
+//! them to a JSON file after every change.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, NaiveTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::{broadcast, RwLock};
+
+use super::error::OrchestrationError;
+use super::processor::{RequestProcessor, UserRequest};
+use crate::utils::config::RoutinesConfig;
+
+/// Notifications buffered for slow `WatchNotifications` clients before the oldest are dropped.
+const NOTIFICATION_BUFFER: usize = 64;
+
+/// Scheduled minutes looked at by one [`RoutineEngine::run_due`] call (one day).
+const MAX_DUE_MINUTES: i64 = 24 * 60;
+
+/// A user's automation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Routine {
+    /// Assigned when the routine is first stored.
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    /// Requests of the routine are made as this user and device; set from the storing request.
+    #[serde(default)]
+    pub owner: RoutineOwner,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    pub trigger: RoutineTrigger,
+    /// All must hold for the routine to run.
+    #[serde(default)]
+    pub conditions: Vec<RoutineCondition>,
+    pub steps: Vec<RoutineStep>,
+    /// Minimum time between two runs, e.g. for sensors that keep reporting the same reading.
+    #[serde(default)]
+    pub cooldown_secs: u64,
+    /// IANA time zone for the schedule and time windows; empty = `routines.timezone`.
+    #[serde(default)]
+    pub timezone: String,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoutineOwner {
+    pub user_id: String,
+    pub device_id: String,
+}
+
+/// When a routine fires.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RoutineTrigger {
+    /// Five-field cron expression, e.g. `0 7 * * 1-5` for weekdays at 7:00.
+    Schedule { cron: String },
+    /// An event reported for the device; an empty `event_type` matches every event of the device.
+    DeviceEvent {
+        device_id: String,
+        #[serde(default)]
+        event_type: String,
+    },
+}
+
+/// Comparison of an event field with a value; numbers (also numeric strings) compare numerically,
+/// everything else as case-insensitive text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Contains,
+}
+
+impl CompareOp {
+    fn apply(self, actual: &Value, expected: &Value) -> bool {
+        if self != CompareOp::Contains {
+            if let (Some(a), Some(b)) = (as_number(actual), as_number(expected)) {
+                return match self {
+                    CompareOp::Eq => a == b,
+                    CompareOp::Ne => a != b,
+                    CompareOp::Gt => a > b,
+                    CompareOp::Ge => a >= b,
+                    CompareOp::Lt => a < b,
+                    CompareOp::Le => a <= b,
+                    CompareOp::Contains => false,
+                };
+            }
+        }
+        let (a, b) = (as_text(actual).to_lowercase(), as_text(expected).to_lowercase());
+        match self {
+            CompareOp::Eq => a == b,
+            CompareOp::Ne => a != b,
+            CompareOp::Contains => a.contains(&b),
+            _ => false,
+        }
+    }
+}
+
+/// Must hold for a routine to run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RoutineCondition {
+    /// Field of the triggering event (`value`, `unit`, any payload field; `a.b` for nested ones).
+    /// Never holds without an event, e.g. for scheduled runs.
+    Event { field: String, op: CompareOp, value: Value },
+    /// Local time of day in `[from, to)`, as `HH:MM`; `from` after `to` spans midnight.
+    TimeWindow { from: String, to: String },
+}
+
+impl RoutineCondition {
+    pub fn holds(&self, event: Option<&DeviceEvent>, local_time: NaiveTime) -> bool {
+        match self {
+            RoutineCondition::Event { field, op, value } => {
+                event.and_then(|event| event.field(field)).is_some_and(|actual| op.apply(&actual, value))
+            }
+            RoutineCondition::TimeWindow { from, to } => match (parse_time(from), parse_time(to)) {
+                (Some(from), Some(to)) if from <= to => from <= local_time && local_time < to,
+                (Some(from), Some(to)) => local_time >= from || local_time < to,
+                _ => false,
+            },
+        }
+    }
+}
+
+/// One step of a routine. `${event.<field>}` in the text is replaced with the triggering event's field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RoutineStep {
+    /// Processed like the owner saying it, e.g. "turn on the living room fan".
+    Request { input: String },
+    /// Sent to the owner's devices.
+    Notify { message: String },
+}
+
+impl Routine {
+    /// Checks what the engine relies on: name, owner, steps, a parseable trigger, conditions and time zone.
+    pub fn validate(&self) -> Result<(), OrchestrationError> {
+        let invalid = |msg: String| Err(OrchestrationError::InvalidRoutine(msg));
+        if self.name.trim().is_empty() {
+            return invalid("name is empty".to_string());
+        }
+        if self.owner.user_id.is_empty() {
+            return invalid("owner has no user id".to_string());
+        }
+        if self.steps.is_empty() {
+            return invalid("routine has no steps".to_string());
+        }
+        for (index, step) in self.steps.iter().enumerate() {
+            let text = match step {
+                RoutineStep::Request { input } => input,
+                RoutineStep::Notify { message } => message,
+            };
+            if text.trim().is_empty() {
+                return invalid(format!("step {} is empty", index + 1));
+            }
+        }
+        match self.trigger {
+            RoutineTrigger::Schedule { ref cron } => {
+                CronSchedule::parse(cron).map_err(|e| OrchestrationError::InvalidRoutine(format!("cron '{}': {}", cron, e)))?;
+            }
+            RoutineTrigger::DeviceEvent { ref device_id, .. } if device_id.is_empty() => {
+                return invalid("device event trigger has no device id".to_string());
+            }
+            RoutineTrigger::DeviceEvent { .. } => {}
+        }
+        for condition in &self.conditions {
+            if let RoutineCondition::TimeWindow { from, to } = condition {
+                if parse_time(from).is_none() || parse_time(to).is_none() {
+                    return invalid(format!("time window {}-{} is not HH:MM", from, to));
+                }
+            }
+        }
+        if !self.timezone.is_empty() && self.timezone.parse::<Tz>().is_err() {
+            return invalid(format!("unknown time zone '{}'", self.timezone));
+        }
+        Ok(())
+    }
+}
+
+/// Five-field cron expression: minute, hour, day of month, month, day of week (0–7, Sunday is 0 or 7).
+/// Fields take `*`, values, ranges (`1-5`), lists (`1,15`) and steps (`*/15`, `8-18/2`). As in cron,
+/// a day matches either day field when both are restricted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!("expected 5 fields (minute hour day month weekday), got {}", fields.len()));
+        }
+        let mut weekdays = parse_cron_field(fields[4], 0, 7, "weekday")?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: parse_cron_field(fields[0], 0, 59, "minute")?,
+            hours: parse_cron_field(fields[1], 0, 23, "hour")?,
+            days: parse_cron_field(fields[2], 1, 31, "day")?,
+            months: parse_cron_field(fields[3], 1, 12, "month")?,
+            weekdays,
+            days_restricted: !fields[2].starts_with('*'),
+            weekdays_restricted: !fields[4].starts_with('*'),
+        })
+    }
+
+    /// Whether the schedule fires in the minute of `time` (seconds are ignored).
+    pub fn matches<Z: TimeZone>(&self, time: &DateTime<Z>) -> bool {
+        let has = |set: u64, value: u32| set & (1 << value) != 0;
+        let day = has(self.days, time.day());
+        let weekday = has(self.weekdays, time.weekday().num_days_from_sunday());
+        let day_matches = if self.days_restricted && self.weekdays_restricted { day || weekday } else { day && weekday };
+        has(self.minutes, time.minute()) && has(self.hours, time.hour()) && has(self.months, time.month()) && day_matches
+    }
+}
+
+fn parse_cron_field(field: &str, min: u32, max: u32, name: &str) -> Result<u64, String> {
+    let number = |s: &str| s.parse::<u32>().map_err(|_| format!("invalid {} '{}'", name, s));
+    let mut set = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, number(step)?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            return Err(format!("step 0 in {} '{}'", name, part));
+        }
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (number(start)?, number(end)?),
+            // `5/15` means from 5 on, every 15
+            None if part.contains('/') => (number(range)?, max),
+            None => (number(range)?, number(range)?),
+        };
+        if start < min || end > max || start > end {
+            return Err(format!("{} '{}' is outside {}-{}", name, part, min, max));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            set |= 1 << value;
+        }
+    }
+    Ok(set)
+}
+
+fn parse_time(s: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(s.trim(), "%H:%M").ok()
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    value.as_f64().or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+}
+
+fn as_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// A device or sensor event, reported by Jotunheim devices, Loki or a platform.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceEvent {
+    pub device_id: String,
+    /// e.g. `temperature`, `motion`, `door_opened`.
+    pub event_type: String,
+    /// Who reported it: `jotunheim`, `loki` or the platform.
+    #[serde(default)]
+    pub source: String,
+    /// Reading and details, e.g. `{"value": 28.5, "unit": "celsius"}`.
+    #[serde(default)]
+    pub payload: serde_json::Map<String, Value>,
+    /// Unix ms.
+    #[serde(default)]
+    pub occurred_at_ms: i64,
+}
+
+impl DeviceEvent {
+    /// `device_id`, `event_type`, `source` or a payload field (`a.b` for nested fields).
+    pub fn field(&self, name: &str) -> Option<Value> {
+        match name {
+            "device_id" => Some(Value::String(self.device_id.clone())),
+            "event_type" => Some(Value::String(self.event_type.clone())),
+            "source" => Some(Value::String(self.source.clone())),
+            _ => {
+                let (first, rest) = name.split_once('.').map_or((name, None), |(first, rest)| (first, Some(rest)));
+                let value = self.payload.get(first)?;
+                match rest {
+                    Some(rest) => value.pointer(&format!("/{}", rest.replace('.', "/"))).cloned(),
+                    None => Some(value.clone()),
+                }
+            }
+        }
+    }
+}
+
+/// Replace `${event.<field>}` with the event's field; without the field (or event) it becomes empty.
+fn render(text: &str, event: Option<&DeviceEvent>) -> String {
+    const OPEN: &str = "${event.";
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(OPEN) {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let field = &rest[start + OPEN.len()..start + len];
+        if let Some(value) = event.and_then(|event| event.field(field)) {
+            out.push_str(&as_text(&value));
+        }
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// What started a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunTrigger {
+    Schedule,
+    DeviceEvent,
+    /// `TestFireRoutine`.
+    Test,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Succeeded,
+    /// A step failed; the following steps were skipped.
+    Failed,
+    /// A request step's actions wait for the owner's confirmation; the following steps were skipped.
+    AwaitingConfirmation,
+    /// Test fire whose conditions did not hold; nothing ran.
+    ConditionsNotMet,
+}
+
+/// One run of a routine.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoutineRun {
+    pub run_id: String,
+    pub routine_id: String,
+    pub trigger: RunTrigger,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event: Option<DeviceEvent>,
+    /// Unix ms.
+    pub started_at_ms: i64,
+    /// Unix ms.
+    pub finished_at_ms: i64,
+    pub status: RunStatus,
+    /// Answer or message of each step that ran.
+    pub outputs: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A routine with its latest run, as listed for the owner.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoutineSummary {
+    pub routine: Routine,
+    pub last_run: Option<RoutineRun>,
+}
+
+/// Persisted routines.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RoutineState {
+    pub routines: Vec<Routine>,
+    /// Latest runs per routine id, oldest first.
+    pub runs: HashMap<String, VecDeque<RoutineRun>>,
+}
+
+/// Routines of all users and their run history, optionally persisted to a JSON file.
+pub struct RoutineStore {
+    state: RwLock<RoutineState>,
+    max_history: usize,
+    path: Option<PathBuf>,
+}
+
+impl RoutineStore {
+    /// In-memory store (lost on restart).
+    pub fn new(max_history: usize) -> Self {
+        Self {
+            state: RwLock::new(RoutineState::default()),
+            max_history,
+            path: None,
+        }
+    }
+
+    /// Store persisted at `path`; loads the routines written by a previous run if the file exists.
+    pub fn open(path: impl Into<PathBuf>, max_history: usize) -> std::io::Result<Self> {
+        let path = path.into();
+        let state: RoutineState = if path.exists() {
+            let content = std::fs::read_to_string(&path)?;
+            serde_json::from_str(&content).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+        } else {
+            RoutineState::default()
+        };
+        tracing::info!("Loaded {} routine(s) from {:?}", state.routines.len(), path);
+        Ok(Self {
+            state: RwLock::new(state),
+            max_history,
+            path: Some(path),
+        })
+    }
+
+    /// Store from settings; an empty `state_path` means `default_path`.
+    pub fn from_config(config: &RoutinesConfig, default_path: &Path) -> std::io::Result<Self> {
+        let path = if config.state_path.is_empty() { default_path.to_path_buf() } else { PathBuf::from(&config.state_path) };
+        Self::open(path, config.max_history)
+    }
+
+    /// Add a routine (without id) or replace one of the same owner; returns it with its id.
+    pub async fn upsert(&self, mut routine: Routine) -> Result<Routine, OrchestrationError> {
+        routine.validate()?;
+        let mut state = self.state.write().await;
+        if routine.id.is_empty() {
+            routine.id = uuid::Uuid::new_v4().to_string();
+            state.routines.push(routine.clone());
+        } else {
+            let existing = state
+                .routines
+                .iter_mut()
+                .find(|r| r.id == routine.id && r.owner.user_id == routine.owner.user_id)
+                .ok_or_else(|| OrchestrationError::InvalidRoutine(format!("unknown routine {}", routine.id)))?;
+            *existing = routine.clone();
+        }
+        self.save(&state).await;
+        Ok(routine)
+    }
+
+    pub async fn get(&self, user_id: &str, routine_id: &str) -> Option<Routine> {
+        let state = self.state.read().await;
+        state.routines.iter().find(|r| r.id == routine_id && r.owner.user_id == user_id).cloned()
+    }
+
+    /// The user's routines with their latest run.
+    pub async fn list(&self, user_id: &str) -> Vec<RoutineSummary> {
+        let state = self.state.read().await;
+        state
+            .routines
+            .iter()
+            .filter(|r| r.owner.user_id == user_id)
+            .map(|routine| RoutineSummary {
+                routine: routine.clone(),
+                last_run: state.runs.get(&routine.id).and_then(|runs| runs.back().cloned()),
+            })
+            .collect()
+    }
+
+    /// Enabled routines of all users.
+    pub async fn enabled(&self) -> Vec<Routine> {
+        self.state.read().await.routines.iter().filter(|r| r.enabled).cloned().collect()
+    }
+
+    /// Remove the routine and its history; `false` if the user has no such routine.
+    pub async fn remove(&self, user_id: &str, routine_id: &str) -> bool {
+        let mut state = self.state.write().await;
+        let before = state.routines.len();
+        state.routines.retain(|r| !(r.id == routine_id && r.owner.user_id == user_id));
+        if state.routines.len() == before {
+            return false;
+        }
+        state.runs.remove(routine_id);
+        self.save(&state).await;
+        true
+    }
+
+    pub async fn set_enabled(&self, user_id: &str, routine_id: &str, enabled: bool) -> Option<Routine> {
+        let mut state = self.state.write().await;
+        let routine = state.routines.iter_mut().find(|r| r.id == routine_id && r.owner.user_id == user_id)?;
+        routine.enabled = enabled;
+        let routine = routine.clone();
+        self.save(&state).await;
+        Some(routine)
+    }
+
+    /// Latest runs of the user's routine, oldest first; `None` if the user has no such routine.
+    pub async fn history(&self, user_id: &str, routine_id: &str) -> Option<Vec<RoutineRun>> {
+        let state = self.state.read().await;
+        state.routines.iter().find(|r| r.id == routine_id && r.owner.user_id == user_id)?;
+        Some(state.runs.get(routine_id).map(|runs| runs.iter().cloned().collect()).unwrap_or_default())
+    }
+
+    pub async fn last_run(&self, routine_id: &str) -> Option<RoutineRun> {
+        self.state.read().await.runs.get(routine_id).and_then(|runs| runs.back().cloned())
+    }
+
+    pub async fn record_run(&self, run: RoutineRun) {
+        let mut state = self.state.write().await;
+        let runs = state.runs.entry(run.routine_id.clone()).or_default();
+        runs.push_back(run);
+        while runs.len() > self.max_history {
+            runs.pop_front();
+        }
+        self.save(&state).await;
+    }
+
+    /// Write the state (temporary file, then rename); failures are logged and the state stays in memory.
+    async fn save(&self, state: &RoutineState) {
+        let Some(ref path) = self.path else {
+            return;
+        };
+        let json = match serde_json::to_string_pretty(state) {
+            Ok(json) => json,
+            Err(e) => {
+                tracing::warn!("Failed to serialize routines: {}", e);
+                return;
+            }
+        };
+        if let Some(parent) = path.parent() {
+            let _ = tokio::fs::create_dir_all(parent).await;
+        }
+        let tmp = path.with_extension("json.tmp");
+        let result = match tokio::fs::write(&tmp, json).await {
+            Ok(()) => tokio::fs::rename(&tmp, path).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            tracing::warn!("Failed to persist routines to {:?}: {}", path, e);
+        }
+    }
+}
+
+/// Answer to a request step.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RoutineReply {
+    pub response: String,
+    /// Set when risky actions of the request wait for the owner's confirmation.
+    pub confirmation_id: Option<String>,
+}
+
+/// Processes the request steps of routines; in Odin the same path as the `Process` RPC.
+#[async_trait]
+pub trait RoutineRequestHandler: Send + Sync {
+    async fn handle(&self, request: UserRequest) -> Result<RoutineReply, String>;
+}
+
+#[async_trait]
+impl RoutineRequestHandler for RequestProcessor {
+    async fn handle(&self, request: UserRequest) -> Result<RoutineReply, String> {
+        self.process(request)
+            .await
+            .map(|response| RoutineReply { response, confirmation_id: None })
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Message of a notify step (or a held confirmation) for the routine owner's devices.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoutineNotification {
+    pub user_id: String,
+    pub routine_id: String,
+    pub routine_name: String,
+    pub message: String,
+    /// Set when the message asks the owner to confirm risky actions (`ConfirmAction`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirmation_id: Option<String>,
+    /// Unix ms.
+    pub created_at_ms: i64,
+}
+
+/// Fires routines on their schedule and on device events and runs their steps as the owner.
+pub struct RoutineEngine {
+    store: Arc<RoutineStore>,
+    handler: Arc<dyn RoutineRequestHandler>,
+    notifications: broadcast::Sender<RoutineNotification>,
+    timezone: Tz,
+    tick: Duration,
+    /// Start of the latest run per routine, for the cooldown (also of runs not yet recorded).
+    last_fired: Mutex<HashMap<String, i64>>,
+}
+
+impl RoutineEngine {
+    /// Engine with UTC as default time zone, checking schedules every 30 seconds.
+    pub fn new(store: Arc<RoutineStore>, handler: Arc<dyn RoutineRequestHandler>) -> Self {
+        Self {
+            store,
+            handler,
+            notifications: broadcast::channel(NOTIFICATION_BUFFER).0,
+            timezone: Tz::UTC,
+            tick: Duration::from_secs(30),
+            last_fired: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn from_config(config: &RoutinesConfig, store: Arc<RoutineStore>, handler: Arc<dyn RoutineRequestHandler>) -> Self {
+        let timezone = config.timezone.parse().unwrap_or_else(|_| {
+            tracing::warn!("Unknown routines.timezone '{}', using UTC", config.timezone);
+            Tz::UTC
+        });
+        Self::new(store, handler)
+            .with_timezone(timezone)
+            .with_tick(Duration::from_secs(config.tick_secs))
+    }
+
+    /// Time zone of routines that do not name one.
+    pub fn with_timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = timezone;
+        self
+    }
+
+    /// How often schedules are checked.
+    pub fn with_tick(mut self, tick: Duration) -> Self {
+        self.tick = tick;
+        self
+    }
+
+    pub fn store(&self) -> &Arc<RoutineStore> {
+        &self.store
+    }
+
+    /// Notifications of all users from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<RoutineNotification> {
+        self.notifications.subscribe()
+    }
+
+    /// Check schedules in the background every tick.
+    pub fn start(self: &Arc<Self>) {
+        let engine = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(engine.tick);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            let mut last = Utc::now();
+            loop {
+                interval.tick().await;
+                let now = Utc::now();
+                let due = engine.clone();
+                tokio::spawn(async move {
+                    due.run_due(last, now).await;
+                });
+                last = now;
+            }
+        });
+    }
+
+    /// Run scheduled routines due in a minute after `after` up to `until` (at most one run each).
+    pub async fn run_due(&self, after: DateTime<Utc>, until: DateTime<Utc>) -> Vec<RoutineRun> {
+        let after = after.max(until - chrono::Duration::minutes(MAX_DUE_MINUTES));
+        let first = after.with_second(0).and_then(|t| t.with_nanosecond(0)).unwrap_or(after) + chrono::Duration::minutes(1);
+        let mut runs = Vec::new();
+        for routine in self.store.enabled().await {
+            let RoutineTrigger::Schedule { ref cron } = routine.trigger else {
+                continue;
+            };
+            let Ok(schedule) = CronSchedule::parse(cron) else {
+                continue;
+            };
+            let timezone = self.timezone_of(&routine);
+            let mut minute = first;
+            while minute <= until {
+                let local = minute.with_timezone(&timezone);
+                if schedule.matches(&local) {
+                    if self.conditions_hold(&routine, None, local.time()) && self.claim(&routine, minute.timestamp_millis()).await {
+                        runs.push(self.run(&routine, RunTrigger::Schedule, None).await);
+                    }
+                    break;
+                }
+                minute += chrono::Duration::minutes(1);
+            }
+        }
+        runs
+    }
+
+    /// Enabled routines the event triggers whose conditions hold, ignoring cooldowns.
+    pub async fn triggered_by(&self, event: &DeviceEvent) -> Vec<Routine> {
+        let mut triggered = Vec::new();
+        for routine in self.store.enabled().await {
+            let RoutineTrigger::DeviceEvent { ref device_id, ref event_type } = routine.trigger else {
+                continue;
+            };
+            let matches = *device_id == event.device_id && (event_type.is_empty() || event_type.eq_ignore_ascii_case(&event.event_type));
+            let local_time = Utc::now().with_timezone(&self.timezone_of(&routine)).time();
+            if matches && self.conditions_hold(&routine, Some(event), local_time) {
+                triggered.push(routine);
+            }
+        }
+        triggered
+    }
+
+    /// Run the routines the event triggers, unless they are cooling down.
+    pub async fn handle_event(&self, event: DeviceEvent) -> Vec<RoutineRun> {
+        let mut runs = Vec::new();
+        for routine in self.triggered_by(&event).await {
+            if self.claim(&routine, now_ms()).await {
+                runs.push(self.run(&routine, RunTrigger::DeviceEvent, Some(&event)).await);
+            }
+        }
+        runs
+    }
+
+    /// Run the user's routine now, e.g. to try it out; `event` stands in for a triggering event.
+    /// Conditions are only evaluated with `check_conditions`; the cooldown is ignored.
+    pub async fn test_fire(&self, user_id: &str, routine_id: &str, event: Option<DeviceEvent>, check_conditions: bool) -> Option<RoutineRun> {
+        let routine = self.store.get(user_id, routine_id).await?;
+        let local_time = Utc::now().with_timezone(&self.timezone_of(&routine)).time();
+        if check_conditions && !self.conditions_hold(&routine, event.as_ref(), local_time) {
+            let now = now_ms();
+            let run = RoutineRun {
+                run_id: uuid::Uuid::new_v4().to_string(),
+                routine_id: routine.id.clone(),
+                trigger: RunTrigger::Test,
+                event,
+                started_at_ms: now,
+                finished_at_ms: now,
+                status: RunStatus::ConditionsNotMet,
+                outputs: Vec::new(),
+                error: None,
+            };
+            self.store.record_run(run.clone()).await;
+            return Some(run);
+        }
+        self.last_fired.lock().unwrap().insert(routine.id.clone(), now_ms());
+        Some(self.run(&routine, RunTrigger::Test, event.as_ref()).await)
+    }
+
+    fn timezone_of(&self, routine: &Routine) -> Tz {
+        routine.timezone.parse().unwrap_or(self.timezone)
+    }
+
+    fn conditions_hold(&self, routine: &Routine, event: Option<&DeviceEvent>, local_time: NaiveTime) -> bool {
+        routine.conditions.iter().all(|condition| condition.holds(event, local_time))
+    }
+
+    /// Whether the routine may start at `at_ms` given its cooldown; if so, the start is noted.
+    async fn claim(&self, routine: &Routine, at_ms: i64) -> bool {
+        let recorded = self
+            .store
+            .last_run(&routine.id)
+            .await
+            .filter(|run| run.status != RunStatus::ConditionsNotMet)
+            .map(|run| run.started_at_ms);
+        let mut last_fired = self.last_fired.lock().unwrap();
+        let last = last_fired.get(&routine.id).copied().max(recorded);
+        let cooldown_ms = (routine.cooldown_secs * 1000) as i64;
+        if cooldown_ms > 0 && last.is_some_and(|last| at_ms < last + cooldown_ms) {
+            return false;
+        }
+        last_fired.insert(routine.id.clone(), at_ms);
+        true
+    }
+
+    /// Run the steps in order as the owner; stops at the first failed or held step.
+    async fn run(&self, routine: &Routine, trigger: RunTrigger, event: Option<&DeviceEvent>) -> RoutineRun {
+        let run_id = uuid::Uuid::new_v4().to_string();
+        let started_at_ms = now_ms();
+        tracing::info!("Running routine {} ({}) of user {}", routine.name, routine.id, routine.owner.user_id);
+        let mut outputs = Vec::new();
+        let mut status = RunStatus::Succeeded;
+        let mut error = None;
+        for (index, step) in routine.steps.iter().enumerate() {
+            match step {
+                RoutineStep::Request { input } => {
+                    let request = UserRequest {
+                        request_id: format!("routine-{}-{}", run_id, index + 1),
+                        user_id: routine.owner.user_id.clone(),
+                        device_id: routine.owner.device_id.clone(),
+                        input: render(input, event),
+                        input_type: "text".to_string(),
+                        // Own conversation, so routine turns do not mix into the user's chat
+                        session_id: format!("routine-{}", routine.id),
+                    };
+                    match self.handler.handle(request).await {
+                        Ok(reply) => {
+                            outputs.push(reply.response.clone());
+                            if let Some(confirmation_id) = reply.confirmation_id {
+                                self.notify(routine, reply.response, Some(confirmation_id));
+                                status = RunStatus::AwaitingConfirmation;
+                                break;
+                            }
+                        }
+                        Err(e) => {
+                            tracing::warn!("Routine {} step {} failed: {}", routine.id, index + 1, e);
+                            status = RunStatus::Failed;
+                            error = Some(format!("step {}: {}", index + 1, e));
+                            break;
+                        }
+                    }
+                }
+                RoutineStep::Notify { message } => {
+                    let message = render(message, event);
+                    self.notify(routine, message.clone(), None);
+                    outputs.push(message);
+                }
+            }
+        }
+        let run = RoutineRun {
+            run_id,
+            routine_id: routine.id.clone(),
+            trigger,
+            event: event.cloned(),
+            started_at_ms,
+            finished_at_ms: now_ms(),
+            status,
+            outputs,
+            error,
+        };
+        self.store.record_run(run.clone()).await;
+        run
+    }
+
+    fn notify(&self, routine: &Routine, message: String, confirmation_id: Option<String>) {
+        let notification = RoutineNotification {
+            user_id: routine.owner.user_id.clone(),
+            routine_id: routine.id.clone(),
+            routine_name: routine.name.clone(),
+            message,
+            confirmation_id,
+            created_at_ms: now_ms(),
+        };
+        if self.notifications.send(notification).is_err() {
+            tracing::info!("No device watches notifications of user {} (routine {})", routine.owner.user_id, routine.id);
+        }
+    }
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
@@ -4,7 +4,7 @@ use async_trait::async_trait;
 use std::sync::Arc;
 use std::time::Duration;
 
-use tonic::transport::Channel;
+use vegvisir::TracedChannel;
 use crate::grpc::odin::odin_service_client::OdinServiceClient;
 use crate::grpc::odin::{ProcessRequest, ProcessResponse};
 
@@ -55,6 +55,7 @@ impl OdinPlugin for GrpcPluginProxy {
             device_id: String::new(),
             input: request.to_string(),
             input_type: "text".to_string(),
+            session_id: String::new(),
         };
         let res = self.client.process(req).await?;
         Ok(res.response)
@@ -63,7 +64,7 @@ impl OdinPlugin for GrpcPluginProxy {
 
 /// Production ProcessClient that calls the OdinService Process RPC at a given URL.
 pub struct OdinGrpcProcessClient {
-    client: tokio::sync::Mutex<OdinServiceClient<Channel>>,
+    client: tokio::sync::Mutex<OdinServiceClient<TracedChannel>>,
 }
 
 impl OdinGrpcProcessClient {
@@ -73,7 +74,7 @@ impl OdinGrpcProcessClient {
             .timeout(Duration::from_secs(30))
             .connect_timeout(Duration::from_secs(5));
         let channel = endpoint.connect().await?;
-        let client = OdinServiceClient::new(channel);
+        let client = OdinServiceClient::new(vegvisir::traced(channel));
         Ok(Self {
             client: tokio::sync::Mutex::new(client),
         })
@@ -1,4 +1,4 @@
-use tonic::transport::Channel;
+use vegvisir::TracedChannel;
 use anyhow::Result;
 use std::time::Duration;
 use crate::clients::ServiceClientConfig;
@@ -8,12 +8,12 @@ pub mod einherjar {
 }
 
 use einherjar::einherjar_protocol_client::EinherjarProtocolClient;
-use einherjar::{CapabilityRequest, CapabilityResponse};
+use einherjar::{CapabilityRequest, CapabilityResponse, FunctionDefinition};
 
 /// Client for Einherjar Protocol
 /// Used to discover capabilities of services and plugins
 pub struct EinherjarClient {
-    client: EinherjarProtocolClient<Channel>,
+    client: EinherjarProtocolClient<TracedChannel>,
 }
 
 impl EinherjarClient {
@@ -24,7 +24,7 @@ impl EinherjarClient {
             .connect_timeout(Duration::from_secs(5));
         
         let channel = endpoint.connect().await?;
-        let client = EinherjarProtocolClient::new(channel);
+        let client = EinherjarProtocolClient::new(vegvisir::traced(channel));
         
         Ok(Self { client })
     }
@@ -53,9 +53,35 @@ pub struct AggregatedCapabilities {
     pub by_domain: std::collections::HashMap<String, Vec<String>>,
     /// For each responsibility_keyword, service names that declare it.
     pub by_keyword: std::collections::HashMap<String, Vec<String>>,
+    /// Purpose per service name (e.g. `"thor"` → `"Action Execution"`).
+    pub purposes: std::collections::HashMap<String, String>,
+    /// All functions of all services, sorted by service name.
+    pub functions: Vec<ServiceFunction>,
+}
+
+/// A function offered by a service.
+#[derive(Debug, Clone)]
+pub struct ServiceFunction {
+    pub service_name: String,
+    pub function: FunctionDefinition,
 }
 
 impl AggregatedCapabilities {
+    /// Service names with a known capability, sorted.
+    pub fn service_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.purposes.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+
+    /// Definition of `function` offered by `service_name`.
+    pub fn function(&self, service_name: &str, function: &str) -> Option<&FunctionDefinition> {
+        self.functions
+            .iter()
+            .find(|f| f.service_name == service_name && f.function.name == function)
+            .map(|f| &f.function)
+    }
+
     /// Service names that handle the given domain.
     pub fn services_for_domain(&self, domain: &str) -> Option<&Vec<String>> {
         self.by_domain.get(domain)
@@ -109,7 +135,16 @@ impl CapabilityCache {
             std::collections::HashMap::new();
         let mut by_keyword: std::collections::HashMap<String, Vec<String>> =
             std::collections::HashMap::new();
+        let mut purposes = std::collections::HashMap::new();
+        let mut functions = Vec::new();
         for c in all {
+            purposes.insert(c.service_name.clone(), c.capability.purpose.clone());
+            for f in &c.capability.functions {
+                functions.push(ServiceFunction {
+                    service_name: c.service_name.clone(),
+                    function: f.clone(),
+                });
+            }
             for d in &c.capability.responsibility_domains {
                 by_domain
                     .entry(d.clone())
@@ -123,9 +158,12 @@ impl CapabilityCache {
                     .push(c.service_name.clone());
             }
         }
+        functions.sort_by(|a, b| a.service_name.cmp(&b.service_name));
         AggregatedCapabilities {
             by_domain,
             by_keyword,
+            purposes,
+            functions,
         }
     }
 
@@ -1,4 +1,4 @@
-use tonic::transport::Channel;
+use vegvisir::TracedChannel;
 use anyhow::Result;
 use std::time::Duration;
 use crate::clients::ServiceClientConfig;
@@ -17,7 +17,7 @@ use responsibility::{
 /// Client for Responsibility Service
 /// Used to manage responsibility for requests
 pub struct ResponsibilityClient {
-    client: ResponsibilityServiceClient<Channel>,
+    client: ResponsibilityServiceClient<TracedChannel>,
 }
 
 impl ResponsibilityClient {
@@ -28,7 +28,7 @@ impl ResponsibilityClient {
             .connect_timeout(Duration::from_secs(5));
         
         let channel = endpoint.connect().await?;
-        let client = ResponsibilityServiceClient::new(channel);
+        let client = ResponsibilityServiceClient::new(vegvisir::traced(channel));
         
         Ok(Self { client })
     }
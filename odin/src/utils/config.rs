@@ -165,6 +165,154 @@ impl Default for ChatFlags {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationConfig {
+    /// Maximal gespeicherte Turns pro Konversation (Geri kürzt zusätzlich auf das Context-Window).
+    pub max_messages: usize,
+    /// Konversationen ohne neuen Turn werden nach dieser Zeit verworfen.
+    pub idle_timeout_secs: u64,
+}
+
+impl Default for ConversationConfig {
+    fn default() -> Self {
+        Self {
+            max_messages: 50,
+            idle_timeout_secs: 1800,
+        }
+    }
+}
+
+/// Intent-Erkennung über Geri (Structured Output); ohne Geri greift das Keyword-Routing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntentConfig {
+    pub enabled: bool,
+    /// Unterhalb dieser Confidence (0.0–1.0) wird bei Aktionen nachgefragt statt ausgeführt.
+    pub min_confidence: f64,
+    /// Geri-Model für die Klassifikation; leer = Auswahl durch Geri.
+    #[serde(default)]
+    pub model_name: String,
+    pub max_tokens: u32,
+}
+
+impl Default for IntentConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_confidence: 0.6,
+            model_name: String::new(),
+            max_tokens: 512,
+        }
+    }
+}
+
+/// Responsibility-Handoff: Ein Service/Plugin, das eine Anfrage übernimmt, besitzt die Konversation,
+/// bis es sie zurückgibt oder die Lease abläuft.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandoffConfig {
+    /// Dauer der Lease in Sekunden; jeder weitere Turn an den Owner verlängert sie.
+    pub lease_secs: u64,
+    /// Datei für den Handoff-Zustand (übersteht Neustarts); leer = `handoff_state.json` neben der settings.json.
+    #[serde(default)]
+    pub state_path: String,
+    /// Anzahl gespeicherter Übergänge (Take/Reject/Return/…) für das Debugging.
+    pub max_history: usize,
+}
+
+impl Default for HandoffConfig {
+    fn default() -> Self {
+        Self {
+            lease_secs: 900,
+            state_path: String::new(),
+            max_history: 200,
+        }
+    }
+}
+
+/// Agent-Modus: Geri ruft Thor-Aktionen, Loki-Skripte und Jotunheim-Device-Tools auf, bis eine
+/// Antwort vorliegt oder ein Budget erschöpft ist.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentConfig {
+    pub enabled: bool,
+    /// Maximale Anzahl Model-Runden (jede Runde kann mehrere Tool-Calls enthalten).
+    pub max_steps: u32,
+    /// Gesamtzeit für einen Agent-Lauf in Millisekunden.
+    pub max_duration_ms: u64,
+    /// Token-Budget über alle Model-Runden.
+    pub max_tokens: u32,
+    /// Geri-Model für den Agent; leer = Auswahl durch Geri.
+    #[serde(default)]
+    pub model_name: String,
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_steps: 8,
+            max_duration_ms: 120_000,
+            max_tokens: 16_000,
+            model_name: String::new(),
+        }
+    }
+}
+
+/// Bestätigung riskanter Aktionen: Pläne mit Aktionen ab `min_risk` (z.B. Löschen, Shell-Commands,
+/// Aktionen auf anderen Devices, Käufe) werden erst nach einem „Ja“ vom auslösenden Device ausgeführt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfirmationConfig {
+    pub enabled: bool,
+    /// Ab diesem Risiko ist eine Bestätigung nötig: "low" | "medium" | "high" | "critical".
+    pub min_risk: String,
+    /// Gültigkeit einer offenen Bestätigung in Sekunden; danach wird nichts ausgeführt.
+    pub timeout_secs: u64,
+    /// Vorschau per Dry-Run (Thor meldet, was er tun würde) in der Rückfrage.
+    pub dry_run_preview: bool,
+    /// Mit Thor geteiltes Secret: freigegebene Thor-Schritte tragen ein damit signiertes, an den
+    /// Schritt gebundenes Token. Leer = unsigniert; Thor lehnt riskante Actions dann ab.
+    #[serde(default)]
+    pub signing_secret: String,
+}
+
+impl Default for ConfirmationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_risk: "high".to_string(),
+            timeout_secs: 120,
+            dry_run_preview: true,
+            signing_secret: String::new(),
+        }
+    }
+}
+
+/// Routinen: vom User definierte Automationen (Zeitplan per Cron oder Device-/Sensor-Events), die
+/// Odin selbst auslöst und mit den Rechten des Besitzers ausführt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutinesConfig {
+    pub enabled: bool,
+    /// Datei für Routinen und ihre Läufe; leer = `routines.json` neben der settings.json.
+    #[serde(default)]
+    pub state_path: String,
+    /// Intervall in Sekunden, in dem fällige Zeitpläne geprüft werden.
+    pub tick_secs: u64,
+    /// Anzahl gespeicherter Läufe pro Routine.
+    pub max_history: usize,
+    /// Zeitzone (IANA, z.B. "Europe/Berlin") für Routinen ohne eigene Zeitzone.
+    pub timezone: String,
+}
+
+impl Default for RoutinesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            state_path: String::new(),
+            tick_secs: 30,
+            max_history: 20,
+            timezone: "UTC".to_string(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OdinSettings {
     #[serde(default)]
@@ -181,6 +329,21 @@ pub struct OdinSettings {
     pub scheduler: SchedulerConfig,
     #[serde(default)]
     pub chat_flags: ChatFlags,
+    #[serde(default)]
+    pub conversation: ConversationConfig,
+    #[serde(default)]
+    pub intent: IntentConfig,
+    #[serde(default)]
+    pub agent: AgentConfig,
+    #[serde(default)]
+    pub handoff: HandoffConfig,
+    #[serde(default)]
+    pub confirmation: ConfirmationConfig,
+    #[serde(default)]
+    pub routines: RoutinesConfig,
+    /// Span-Export (OTLP-Collector und/oder JSON-Datei), siehe Vegvisir.
+    #[serde(default)]
+    pub trace_export: vegvisir::TraceExportConfig,
     pub grpc_port: u16,
     #[serde(default)]
     pub service_urls: ServiceUrls,
@@ -242,6 +405,13 @@ impl Default for OdinSettings {
             state_sync: StateSyncConfig::default(),
             scheduler: SchedulerConfig::default(),
             chat_flags: ChatFlags::default(),
+            conversation: ConversationConfig::default(),
+            intent: IntentConfig::default(),
+            agent: AgentConfig::default(),
+            handoff: HandoffConfig::default(),
+            confirmation: ConfirmationConfig::default(),
+            routines: RoutinesConfig::default(),
+            trace_export: vegvisir::TraceExportConfig::default(),
             grpc_port: 50050,
             service_urls: ServiceUrls::default(),
         }
@@ -378,6 +548,42 @@ impl SettingsManager {
             }
         }
         
+        // Validate intent confidence threshold
+        if !(0.0..=1.0).contains(&settings.intent.min_confidence) {
+            return Err("intent.min_confidence must be between 0.0 and 1.0".into());
+        }
+
+        // Validate handoff lease
+        if settings.handoff.lease_secs == 0 {
+            return Err("handoff.lease_secs must be > 0".into());
+        }
+
+        // Validate confirmation of risky actions
+        let valid_risk_levels = ["low", "medium", "high", "critical"];
+        if !valid_risk_levels.contains(&settings.confirmation.min_risk.as_str()) {
+            return Err(format!("confirmation.min_risk must be one of: {:?}", valid_risk_levels).into());
+        }
+        if settings.confirmation.enabled && settings.confirmation.timeout_secs == 0 {
+            return Err("confirmation.timeout_secs must be > 0".into());
+        }
+
+        // Validate routines
+        if settings.routines.enabled {
+            if settings.routines.tick_secs == 0 {
+                return Err("routines.tick_secs must be > 0".into());
+            }
+            if settings.routines.timezone.parse::<chrono_tz::Tz>().is_err() {
+                return Err(format!("routines.timezone '{}' is not a known time zone", settings.routines.timezone).into());
+            }
+        }
+
+        settings.trace_export.validate()?;
+
+        // Validate agent budgets
+        if settings.agent.enabled && (settings.agent.max_steps == 0 || settings.agent.max_duration_ms == 0 || settings.agent.max_tokens == 0) {
+            return Err("agent budgets (max_steps, max_duration_ms, max_tokens) must be greater than 0".into());
+        }
+        
         // Validate port
         if settings.grpc_port == 0 {
             return Err("grpc_port must be > 0".into());
@@ -51,6 +51,7 @@ mod tests {
             context: "Test context".to_string(),
             model_name: "test-model".to_string(),
             max_tokens: 100,
+            ..Default::default()
         };
         
         let result = client.process_prompt(request).await;
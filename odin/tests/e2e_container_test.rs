@@ -80,6 +80,7 @@ async fn e2e_container_request_flow_uses_mock_urls() {
         device_id: "d1".to_string(),
         input: "Can you explain how this works?".to_string(),
         input_type: "text".to_string(),
+        session_id: String::new(),
     };
 
     let result = processor.process(req).await;
@@ -1,4 +1,4 @@
-//! Minimal gRPC mock for Geri: Einherjar GetCapabilities, Responsibility TakeResponsibility, Geri ProcessPrompt.
+//! Minimal gRPC mock for Geri: Einherjar GetCapabilities, Responsibility TakeResponsibility, Geri ProcessPrompt(Stream).
 //! Used so Odin container E2E can get Ok(response) when routing to Geri.
 
 use std::net::SocketAddr;
@@ -49,6 +49,7 @@ impl ResponsibilityService for ResponsibilityImpl {
         Ok(Response::new(TakeResponsibilityResponse {
             accepted: true,
             message: "Accepted".to_string(),
+            one_shot: false,
         }))
     }
     async fn return_responsibility(
@@ -85,6 +86,29 @@ impl GeriService for GeriImpl {
             model_used: "mock".to_string(),
         }))
     }
+    type ProcessPromptStreamStream = tokio_stream::wrappers::ReceiverStream<Result<geri::ProcessPromptStreamChunk, Status>>;
+
+    async fn process_prompt_stream(
+        &self,
+        req: Request<ProcessPromptRequest>,
+    ) -> Result<Response<Self::ProcessPromptStreamStream>, Status> {
+        let p = req.into_inner();
+        let (tx, rx) = tokio::sync::mpsc::channel(4);
+        let chunks = vec![
+            geri::process_prompt_stream_chunk::Chunk::Delta(geri::PromptDelta { text: "[mock-geri] ".to_string() }),
+            geri::process_prompt_stream_chunk::Chunk::Delta(geri::PromptDelta { text: p.prompt }),
+            geri::process_prompt_stream_chunk::Chunk::Done(geri::PromptStreamDone {
+                model_used: "mock".to_string(),
+                finish_reason: "stop".to_string(),
+                ..Default::default()
+            }),
+        ];
+        for chunk in chunks {
+            let _ = tx.send(Ok(geri::ProcessPromptStreamChunk { chunk: Some(chunk) })).await;
+        }
+        Ok(Response::new(tokio_stream::wrappers::ReceiverStream::new(rx)))
+    }
+
     async fn process_vision(
         &self,
         _req: Request<geri::ProcessVisionRequest>,
@@ -88,6 +88,7 @@ mod tests {
                 action_type: "FILE_OPERATION".to_string(),
                 service: "thor".to_string(),
                 parameters: serde_json::json!({}),
+                ..Default::default()
             }],
         };
         let result = orchestrator.execute_actions(plan).await;
@@ -106,6 +107,7 @@ mod tests {
                 action_type: "LLM_CALL".to_string(),
                 service: "geri".to_string(),
                 parameters: serde_json::json!({}),
+                ..Default::default()
             }],
         };
         let result = orchestrator.execute_actions(plan).await;
@@ -142,6 +144,7 @@ mod tests {
                     parameters: serde_json::json!({
                         "command": "echo 'test'"
                     }),
+                    ..Default::default()
                 }
             ],
         };
@@ -205,6 +208,7 @@ mod tests {
                     parameters: serde_json::json!({
                         "command": "echo 'test1'"
                     }),
+                    ..Default::default()
                 },
                 Action {
                     action_id: "test-action-2".to_string(),
@@ -213,6 +217,7 @@ mod tests {
                     parameters: serde_json::json!({
                         "command": "echo 'test2'"
                     }),
+                    ..Default::default()
                 },
             ],
         };
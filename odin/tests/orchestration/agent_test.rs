@@ -0,0 +1,281 @@
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use odin::clients::geri::geri::{ProcessPromptRequest, ProcessPromptResponse, ToolCall};
+    use odin::clients::heimdall::heimdall::{PermissionCheckRequest, PermissionCheckResponse};
+    use odin::clients::loki::loki::{ParameterDefinition as ScriptParameter, ScriptCapability};
+    use odin::orchestration::{
+        Action, ActionExecutor, AgentLoop, AgentStopReason, AgentTool, AgentTraceEntry, AuditEvent, AuditLogger, IntentModel,
+        PermissionChecker, ToolCallStatus, ToolCatalog, ToolTarget, UserRequest,
+    };
+    use odin::protocols::einherjar::einherjar::{CapabilityResponse, FunctionDefinition, ParameterDefinition};
+    use odin::protocols::einherjar::CapabilityCache;
+    use odin::utils::config::AgentConfig;
+    use serde_json::{json, Value};
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// Geri stand-in: answers from a script, repeating the last answer when it runs out
+    struct ScriptedGeri {
+        answers: Mutex<VecDeque<ProcessPromptResponse>>,
+        requests: Mutex<Vec<ProcessPromptRequest>>,
+    }
+
+    impl ScriptedGeri {
+        fn new(answers: Vec<ProcessPromptResponse>) -> Arc<Self> {
+            Arc::new(Self { answers: Mutex::new(answers.into()), requests: Mutex::new(Vec::new()) })
+        }
+    }
+
+    #[async_trait]
+    impl IntentModel for ScriptedGeri {
+        async fn complete(&self, request: ProcessPromptRequest) -> Result<ProcessPromptResponse, String> {
+            self.requests.lock().unwrap().push(request);
+            let mut answers = self.answers.lock().unwrap();
+            if answers.len() > 1 {
+                Ok(answers.pop_front().unwrap())
+            } else {
+                answers.front().cloned().ok_or_else(|| "no answer".to_string())
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct FakeServices {
+        actions: Mutex<Vec<Action>>,
+        delay_ms: u64,
+    }
+
+    #[async_trait]
+    impl ActionExecutor for FakeServices {
+        async fn execute(&self, action: &Action) -> Result<Value, String> {
+            self.actions.lock().unwrap().push(action.clone());
+            tokio::time::sleep(Duration::from_millis(self.delay_ms)).await;
+            Ok(json!({ "ok": true, "action_type": action.action_type }))
+        }
+    }
+
+    /// Heimdall stand-in: denies the listed resources, fails entirely if `offline`
+    #[derive(Default)]
+    struct FakeHeimdall {
+        denied: Vec<String>,
+        offline: bool,
+        checks: Mutex<Vec<PermissionCheckRequest>>,
+    }
+
+    #[async_trait]
+    impl PermissionChecker for FakeHeimdall {
+        async fn check(&self, request: PermissionCheckRequest) -> Result<PermissionCheckResponse, String> {
+            self.checks.lock().unwrap().push(request.clone());
+            if self.offline {
+                return Err("Heimdall client not initialized".to_string());
+            }
+            let allowed = !self.denied.contains(&request.resource_id);
+            Ok(PermissionCheckResponse {
+                allowed,
+                reason: if allowed { String::new() } else { "user may not control this device".to_string() },
+                ..Default::default()
+            })
+        }
+    }
+
+    #[derive(Default)]
+    struct CaptureAuditLogger(Mutex<Vec<AuditEvent>>);
+
+    impl AuditLogger for CaptureAuditLogger {
+        fn log(&self, event: &AuditEvent) {
+            self.0.lock().unwrap().push(event.clone());
+        }
+    }
+
+    fn tool_call(id: &str, name: &str, arguments: Value) -> ToolCall {
+        ToolCall { id: id.to_string(), name: name.to_string(), arguments_json: arguments.to_string() }
+    }
+
+    fn calls(tool_calls: Vec<ToolCall>, tokens_used: u32) -> ProcessPromptResponse {
+        ProcessPromptResponse { tool_calls, tokens_used, ..Default::default() }
+    }
+
+    fn answer(text: &str) -> ProcessPromptResponse {
+        ProcessPromptResponse { text: text.to_string(), tokens_used: 50, ..Default::default() }
+    }
+
+    fn function(name: &str, description: &str, parameters: Vec<ParameterDefinition>) -> FunctionDefinition {
+        FunctionDefinition { name: name.to_string(), description: description.to_string(), parameters, ..Default::default() }
+    }
+
+    fn param(name: &str, json_type: &str, required: bool) -> ParameterDefinition {
+        ParameterDefinition { name: name.to_string(), r#type: json_type.to_string(), required, ..Default::default() }
+    }
+
+    fn tools() -> Vec<AgentTool> {
+        vec![
+            AgentTool::thor_action(&function("FILE_OPERATION", "Read or write files", vec![param("path", "string", true)])),
+            AgentTool::device_tool("kitchen-esp32", &function("set_relay", "Switch the relay", vec![param("on", "boolean", true)])),
+        ]
+    }
+
+    fn request() -> UserRequest {
+        UserRequest {
+            request_id: "agent-1".to_string(),
+            user_id: "u1".to_string(),
+            device_id: "d1".to_string(),
+            input: "Turn on the kitchen relay and tell me what is in notes.txt".to_string(),
+            input_type: "text".to_string(),
+            session_id: String::new(),
+        }
+    }
+
+    fn agent(geri: &Arc<ScriptedGeri>, services: &Arc<FakeServices>, heimdall: &Arc<FakeHeimdall>, config: AgentConfig) -> AgentLoop {
+        AgentLoop::new(geri.clone(), services.clone(), heimdall.clone(), config)
+    }
+
+    #[tokio::test]
+    async fn tool_results_are_fed_back_until_final_answer() {
+        let geri = ScriptedGeri::new(vec![
+            calls(
+                vec![
+                    tool_call("c1", "thor_file_operation", json!({ "path": "notes.txt" })),
+                    tool_call("c2", "device_kitchen-esp32_set_relay", json!({ "on": true })),
+                ],
+                100,
+            ),
+            answer("The relay is on; notes.txt is a shopping list."),
+        ]);
+        let services = Arc::new(FakeServices::default());
+        let heimdall = Arc::new(FakeHeimdall::default());
+        let audit = Arc::new(CaptureAuditLogger::default());
+        let agent = agent(&geri, &services, &heimdall, AgentConfig::default()).with_audit_logger(audit.clone());
+
+        let outcome = agent.run(&request(), &tools()).await;
+        assert_eq!(outcome.answer, "The relay is on; notes.txt is a shopping list.");
+        assert_eq!(outcome.trace.stop_reason, AgentStopReason::FinalAnswer);
+        assert_eq!((outcome.trace.steps, outcome.trace.tokens_used), (2, 150));
+
+        // Thor gets the arguments as parameters, device tools go through Loki
+        let actions = services.actions.lock().unwrap().clone();
+        assert_eq!((actions[0].service.as_str(), actions[0].action_type.as_str()), ("thor", "FILE_OPERATION"));
+        assert_eq!(actions[0].parameters["path"], "notes.txt");
+        assert_eq!((actions[1].service.as_str(), actions[1].action_type.as_str()), ("loki", "CALL_TOOL"));
+        assert_eq!(actions[1].parameters, json!({ "device_id": "kitchen-esp32", "tool_name": "set_relay", "parameters": { "on": true } }));
+
+        let checks = heimdall.checks.lock().unwrap();
+        assert_eq!((checks[1].resource_type.as_str(), checks[1].resource_id.as_str()), ("device_tool", "kitchen-esp32/set_relay"));
+
+        // Second round: user turn, assistant tool calls, one tool turn per call
+        let requests = geri.requests.lock().unwrap();
+        assert_eq!(requests[0].tools.len(), 2);
+        let roles: Vec<&str> = requests[1].messages.iter().map(|m| m.role.as_str()).collect();
+        assert_eq!(roles, ["user", "assistant", "tool", "tool"]);
+        assert_eq!(requests[1].messages[2].tool_call_id, "c1");
+        assert!(requests[1].messages[2].content.contains("FILE_OPERATION"));
+
+        // Every trace entry is audited, plus the end of the run
+        let events = audit.0.lock().unwrap();
+        assert_eq!(events.len(), outcome.trace.entries.len() + 1);
+        assert!(matches!(events.last(), Some(AuditEvent::AgentFinished { stop_reason: AgentStopReason::FinalAnswer, .. })));
+        let trace_json = serde_json::to_value(&outcome.trace).unwrap();
+        assert_eq!(trace_json["entries"][1]["kind"], "tool_call");
+        assert_eq!(trace_json["entries"][1]["status"], "succeeded");
+    }
+
+    #[tokio::test]
+    async fn denied_and_unknown_calls_are_not_executed() {
+        let geri = ScriptedGeri::new(vec![
+            calls(
+                vec![
+                    tool_call("c1", "device_kitchen-esp32_set_relay", json!({ "on": true })),
+                    tool_call("c2", "thor_format_disk", json!({})),
+                ],
+                10,
+            ),
+            answer("I am not allowed to switch the relay."),
+        ]);
+        let services = Arc::new(FakeServices::default());
+        let heimdall = Arc::new(FakeHeimdall { denied: vec!["kitchen-esp32/set_relay".to_string()], ..Default::default() });
+
+        let outcome = agent(&geri, &services, &heimdall, AgentConfig::default()).run(&request(), &tools()).await;
+        assert!(services.actions.lock().unwrap().is_empty());
+        let statuses: Vec<ToolCallStatus> = outcome
+            .trace
+            .tool_calls()
+            .map(|e| match e {
+                AgentTraceEntry::ToolCall { status, .. } => *status,
+                _ => unreachable!(),
+            })
+            .collect();
+        assert_eq!(statuses, [ToolCallStatus::Denied, ToolCallStatus::Failed]);
+        let tool_turn = &geri.requests.lock().unwrap()[1].messages[2];
+        assert_eq!(tool_turn.content, "Error: permission denied: user may not control this device");
+
+        // Without an answer from Heimdall nothing runs either
+        let geri = ScriptedGeri::new(vec![calls(vec![tool_call("c1", "thor_file_operation", json!({ "path": "a" }))], 10), answer("done")]);
+        let heimdall = Arc::new(FakeHeimdall { offline: true, ..Default::default() });
+        let outcome = agent(&geri, &services, &heimdall, AgentConfig::default()).run(&request(), &tools()).await;
+        assert!(matches!(outcome.trace.tool_calls().next(), Some(AgentTraceEntry::ToolCall { status: ToolCallStatus::Denied, .. })));
+        assert!(services.actions.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn budgets_stop_the_loop() {
+        let looping = || ScriptedGeri::new(vec![calls(vec![tool_call("", "thor_file_operation", json!({ "path": "a" }))], 400)]);
+        let heimdall = Arc::new(FakeHeimdall::default());
+
+        let services = Arc::new(FakeServices::default());
+        let config = AgentConfig { max_steps: 3, ..Default::default() };
+        let outcome = agent(&looping(), &services, &heimdall, config).run(&request(), &tools()).await;
+        assert_eq!((outcome.trace.stop_reason, outcome.trace.steps), (AgentStopReason::StepLimit, 3));
+        assert!(outcome.answer.starts_with("Stopped after 3 steps"));
+        assert_eq!(services.actions.lock().unwrap()[1].action_id, "call_2_1");
+
+        let config = AgentConfig { max_tokens: 1000, ..Default::default() };
+        let outcome = agent(&looping(), &services, &heimdall, config).run(&request(), &tools()).await;
+        assert_eq!((outcome.trace.stop_reason, outcome.trace.steps), (AgentStopReason::TokenLimit, 3));
+
+        let slow = Arc::new(FakeServices { delay_ms: 200, ..Default::default() });
+        let config = AgentConfig { max_duration_ms: 50, ..Default::default() };
+        let outcome = agent(&looping(), &slow, &heimdall, config).run(&request(), &tools()).await;
+        assert_eq!((outcome.trace.stop_reason, outcome.trace.steps), (AgentStopReason::TimeLimit, 1));
+        assert!(matches!(outcome.trace.tool_calls().next(), Some(AgentTraceEntry::ToolCall { status: ToolCallStatus::TimedOut, .. })));
+
+        let offline = ScriptedGeri::new(Vec::new());
+        let outcome = agent(&offline, &services, &heimdall, AgentConfig::default()).run(&request(), &tools()).await;
+        assert_eq!(outcome.trace.stop_reason, AgentStopReason::ModelError);
+    }
+
+    #[tokio::test]
+    async fn catalog_offers_thor_actions_and_device_tools() {
+        let cache = Arc::new(CapabilityCache::new());
+        let thor = CapabilityResponse {
+            god_name: "Thor".to_string(),
+            purpose: "Action Execution".to_string(),
+            functions: vec![function("APP_CONTROL", "Start or stop apps", vec![param("app", "string", true)])],
+            ..Default::default()
+        };
+        let geri = CapabilityResponse { god_name: "Geri".to_string(), functions: vec![function("prompt", "", vec![])], ..Default::default() };
+        cache.update("thor".to_string(), "http://localhost:50052".to_string(), thor).await;
+        cache.update("geri".to_string(), "http://localhost:50053".to_string(), geri).await;
+
+        let catalog = ToolCatalog::new(cache);
+        catalog
+            .register_device_tools("garage door", vec![function("open", "", vec![]), function("close", "Close the door", vec![])])
+            .await;
+        let names: Vec<String> = catalog.tools().await.into_iter().map(|t| t.name).collect();
+        assert_eq!(names, ["thor_app_control", "device_garage_door_open", "device_garage_door_close"]);
+
+        catalog.remove_device("garage door").await;
+        let tools = catalog.tools().await;
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].parameters, json!({ "type": "object", "properties": { "app": { "type": "string" } }, "required": ["app"] }));
+
+        let script = AgentTool::loki_script(&ScriptCapability {
+            script_name: "backup_photos".to_string(),
+            description: "Copy photos to the NAS".to_string(),
+            parameters: vec![ScriptParameter { name: "album".to_string(), r#type: "String".to_string(), required: false, description: "Album name".to_string() }],
+            ..Default::default()
+        });
+        assert_eq!(script.target, ToolTarget::LokiScript { script_name: "backup_photos".to_string() });
+        assert_eq!(script.parameters["properties"]["album"], json!({ "type": "string", "description": "Album name" }));
+    }
+}
@@ -0,0 +1,214 @@
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use odin::orchestration::{
+        classify_action, parse_confirmation_reply, Action, ActionExecutor, ActionOrchestrator, ActionPlan, AuditEvent, AuditLogger,
+        ConfirmationDecision, ConfirmationGate, ConversationKey, RiskLevel, UserRequest,
+    };
+    use serde_json::{json, Value};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// Fake services: records executed steps; dry runs use the trait default
+    #[derive(Default)]
+    struct FakeServices {
+        executed: Mutex<Vec<Action>>,
+    }
+
+    #[async_trait]
+    impl ActionExecutor for FakeServices {
+        async fn execute(&self, action: &Action) -> Result<Value, String> {
+            self.executed.lock().unwrap().push(action.clone());
+            Ok(json!({ "done": action.action_id }))
+        }
+    }
+
+    #[derive(Default)]
+    struct CaptureAuditLogger(Mutex<Vec<AuditEvent>>);
+
+    impl AuditLogger for CaptureAuditLogger {
+        fn log(&self, event: &AuditEvent) {
+            self.0.lock().unwrap().push(event.clone());
+        }
+    }
+
+    fn thor(id: &str, action_type: &str, parameters: Value) -> Action {
+        Action {
+            action_id: id.to_string(),
+            action_type: action_type.to_string(),
+            service: "thor".to_string(),
+            parameters,
+            ..Default::default()
+        }
+    }
+
+    fn delete_plan() -> ActionPlan {
+        ActionPlan {
+            actions: vec![
+                thor("a1", "FILE_OPERATION", json!({ "operation": "Read", "path": "/tmp/notes.txt" })),
+                thor("a2", "FILE_OPERATION", json!({ "operation": "Delete", "path": "/tmp/notes.txt" })),
+            ],
+        }
+    }
+
+    fn request(request_id: &str, device_id: &str, input: &str) -> UserRequest {
+        UserRequest {
+            request_id: request_id.to_string(),
+            user_id: "u1".to_string(),
+            device_id: device_id.to_string(),
+            input: input.to_string(),
+            input_type: "text".to_string(),
+            session_id: String::new(),
+        }
+    }
+
+    #[test]
+    fn actions_are_classified_by_what_they_do() {
+        let shell = thor(
+            "a1",
+            "XML_TASK",
+            json!({ "xml": "<task><instruction>Execute the following command: rm -rf /tmp/build</instruction></task>" }),
+        );
+        assert_eq!(classify_action(&shell).level, RiskLevel::Critical);
+
+        let processes = thor("a1", "XML_TASK", json!({ "xml": "<task><instruction>List the running processes</instruction></task>" }));
+        assert_eq!(classify_action(&processes).level, RiskLevel::Low);
+
+        let purchase = thor("a1", "NETWORK_OPERATION", json!({ "method": "POST", "url": "https://shop.example/checkout" }));
+        assert_eq!(classify_action(&purchase).level, RiskLevel::High);
+
+        let remote = thor("a1", "APP_CONTROL", json!({ "operation": "start", "target_device_id": "tv" }));
+        assert_eq!(classify_action(&remote).level, RiskLevel::High);
+        assert_eq!(classify_action(&remote).reasons, vec!["runs on another device (tv)".to_string()]);
+    }
+
+    #[test]
+    fn replies_are_parsed_in_english_and_german() {
+        assert_eq!(parse_confirmation_reply("Yes"), Some(true));
+        assert_eq!(parse_confirmation_reply("Ja, mach es"), Some(true));
+        assert_eq!(parse_confirmation_reply("nein danke"), Some(false));
+        assert_eq!(parse_confirmation_reply("no."), Some(false));
+        assert_eq!(parse_confirmation_reply("yes, and also open the browser afterwards"), None);
+        assert_eq!(parse_confirmation_reply("open the browser"), None);
+        // A negation anywhere never approves
+        for reply in ["mach das nicht", "mach es nicht", "ok no", "sure not", "yes no", "ja nein"] {
+            assert_ne!(parse_confirmation_reply(reply), Some(true), "{}", reply);
+        }
+        assert_eq!(parse_confirmation_reply("no, don't"), Some(false));
+    }
+
+    #[tokio::test]
+    async fn approved_plan_runs_with_confirmation_after_dry_run() {
+        let services = Arc::new(FakeServices::default());
+        let orchestrator = ActionOrchestrator::new().with_executor(services.clone());
+        let audit = Arc::new(CaptureAuditLogger::default());
+        let gate = ConfirmationGate::new(RiskLevel::High, Duration::from_secs(60))
+            .with_dry_run_preview(true)
+            .with_audit_logger(audit.clone());
+
+        let harmless = ActionPlan { actions: vec![delete_plan().actions[0].clone()] };
+        assert!(gate.check(&request("r0", "d1", "read notes"), &harmless, &orchestrator).await.is_none());
+
+        let pending = gate.check(&request("r1", "d1", "delete my notes"), &delete_plan(), &orchestrator).await.unwrap();
+        assert_eq!(pending.risk(), RiskLevel::High);
+        assert_eq!(pending.risky_steps.len(), 1);
+        assert_eq!(pending.risky_steps[0].action_id, "a2");
+        assert!(pending.preview.as_deref().unwrap().contains("/tmp/notes.txt"));
+        assert!(pending.prompt().contains("deletes /tmp/notes.txt"));
+        // The dry run did not execute anything
+        assert!(services.executed.lock().unwrap().is_empty());
+
+        let outcome = gate.answer(&request("r2", "d1", "yes")).await.unwrap();
+        assert_eq!(outcome.decision, ConfirmationDecision::Approved);
+        outcome.execute(&orchestrator).await.unwrap();
+        let executed = services.executed.lock().unwrap().clone();
+        assert_eq!(executed.len(), 2);
+        assert!(executed.iter().all(|a| a.confirmation_id.as_deref() == Some(pending.confirmation_id.as_str())));
+        assert!(gate.pending(&ConversationKey::from_request(&request("r3", "d1", ""))).await.is_none());
+
+        let events = audit.0.lock().unwrap().clone();
+        assert!(matches!(events[0], AuditEvent::ConfirmationRequested { ref risk, .. } if *risk == RiskLevel::High));
+        assert!(matches!(events[1], AuditEvent::ConfirmationResolved { decision: ConfirmationDecision::Approved, .. }));
+    }
+
+    #[tokio::test]
+    async fn approved_thor_steps_carry_a_token_signed_for_the_step() {
+        let services = Arc::new(FakeServices::default());
+        let orchestrator = ActionOrchestrator::new().with_executor(services.clone());
+        let gate = ConfirmationGate::new(RiskLevel::High, Duration::from_secs(60)).with_signing_secret("shared-secret");
+
+        let pending = gate.check(&request("r1", "d1", "delete my notes"), &delete_plan(), &orchestrator).await.unwrap();
+        let outcome = gate.decide(&pending.confirmation_id, "u1", "d1", true).await.unwrap();
+        outcome.execute(&orchestrator).await.unwrap();
+
+        // Thor verifies each token against the action data it receives (the JSON parameters)
+        let thor = gjallarhorn::ConfirmationSigner::new("shared-secret");
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let executed = services.executed.lock().unwrap().clone();
+        assert_eq!(executed.len(), 2);
+        for action in &executed {
+            let token = action.confirmation_id.as_deref().unwrap();
+            let data = serde_json::to_vec(&action.parameters).unwrap();
+            assert_eq!(thor.verify(token, &action.action_type, &data, now_ms), Ok(pending.confirmation_id.clone()));
+        }
+        // A token cannot be moved to another step
+        let delete_token = executed[1].confirmation_id.as_deref().unwrap();
+        let read_data = serde_json::to_vec(&executed[0].parameters).unwrap();
+        assert!(thor.verify(delete_token, "FILE_OPERATION", &read_data, now_ms).is_err());
+    }
+
+    #[tokio::test]
+    async fn tokens_are_signed_for_the_step_with_resolved_references() {
+        let services = Arc::new(FakeServices::default());
+        let orchestrator = ActionOrchestrator::new().with_executor(services.clone());
+        let gate = ConfirmationGate::new(RiskLevel::High, Duration::from_secs(60)).with_signing_secret("shared-secret");
+        let mut plan = delete_plan();
+        plan.actions[1].parameters = json!({ "operation": "Delete", "path": "/tmp/${a1.result.done}.bak" });
+        plan.actions[1].depends_on = vec!["a1".to_string()];
+
+        let pending = gate.check(&request("r1", "d1", "delete the backup"), &plan, &orchestrator).await.unwrap();
+        let outcome = gate.decide(&pending.confirmation_id, "u1", "d1", true).await.unwrap();
+        outcome.execute(&orchestrator).await.unwrap();
+
+        // Thor receives the resolved path, and the token was signed for exactly that
+        let thor = gjallarhorn::ConfirmationSigner::new("shared-secret");
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let executed = services.executed.lock().unwrap().clone();
+        assert_eq!(executed[1].parameters["path"], "/tmp/a1.bak");
+        let data = serde_json::to_vec(&executed[1].parameters).unwrap();
+        let token = executed[1].confirmation_id.as_deref().unwrap();
+        assert_eq!(thor.verify(token, "FILE_OPERATION", &data, now_ms), Ok(pending.confirmation_id.clone()));
+    }
+
+    #[tokio::test]
+    async fn unrelated_input_supersedes_and_other_devices_cannot_decide() {
+        let orchestrator = ActionOrchestrator::new().with_executor(Arc::new(FakeServices::default()));
+        let gate = ConfirmationGate::new(RiskLevel::High, Duration::from_secs(60));
+
+        gate.check(&request("r1", "d1", "delete my notes"), &delete_plan(), &orchestrator).await.unwrap();
+        assert!(gate.answer(&request("r2", "d1", "what's the weather tomorrow")).await.is_none());
+        assert!(gate.pending(&ConversationKey::from_request(&request("r3", "d1", ""))).await.is_none());
+
+        let pending = gate.check(&request("r4", "d1", "delete my notes"), &delete_plan(), &orchestrator).await.unwrap();
+        assert!(gate.decide(&pending.confirmation_id, "u1", "d2", true).await.is_none());
+        let outcome = gate.decide(&pending.confirmation_id, "u1", "d1", false).await.unwrap();
+        assert_eq!(outcome.decision, ConfirmationDecision::Denied);
+        assert_eq!(outcome.execute(&orchestrator).await.unwrap(), "Cancelled; nothing was executed.");
+    }
+
+    #[tokio::test]
+    async fn unanswered_confirmation_expires_without_executing() {
+        let services = Arc::new(FakeServices::default());
+        let orchestrator = ActionOrchestrator::new().with_executor(services.clone());
+        let gate = ConfirmationGate::new(RiskLevel::Medium, Duration::from_millis(30));
+
+        gate.check(&request("r1", "d1", "delete my notes"), &delete_plan(), &orchestrator).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(gate.pending(&ConversationKey::from_request(&request("r2", "d1", ""))).await.is_none());
+
+        let outcome = gate.answer(&request("r2", "d1", "ja")).await.unwrap();
+        assert_eq!(outcome.decision, ConfirmationDecision::Expired);
+        outcome.execute(&orchestrator).await.unwrap();
+        assert!(services.executed.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+    use odin::orchestration::{chat_message, ConversationKey, ConversationStore};
+    use std::time::Duration;
+
+    fn key(session_id: &str) -> ConversationKey {
+        ConversationKey {
+            user_id: "u1".to_string(),
+            device_id: "d1".to_string(),
+            session_id: session_id.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn append_and_history_keep_turn_order() {
+        let store = ConversationStore::new(10, Duration::from_secs(60));
+        store.append(&key(""), vec![chat_message("user", "Turn on the light"), chat_message("assistant", "Done")]).await;
+        store.append(&key(""), vec![chat_message("user", "Dim it")]).await;
+
+        let history = store.history(&key("")).await;
+        let contents: Vec<&str> = history.iter().map(|m| m.content.as_str()).collect();
+        assert_eq!(contents, vec!["Turn on the light", "Done", "Dim it"]);
+        assert_eq!(history[1].role, "assistant");
+    }
+
+    #[tokio::test]
+    async fn sessions_are_isolated() {
+        let store = ConversationStore::new(10, Duration::from_secs(60));
+        store.append(&key("kitchen"), vec![chat_message("user", "Hello")]).await;
+        assert!(store.history(&key("car")).await.is_empty());
+        assert_eq!(store.history(&key("kitchen")).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn max_messages_drops_oldest() {
+        let store = ConversationStore::new(2, Duration::from_secs(60));
+        store.append(&key(""), vec![chat_message("user", "1"), chat_message("assistant", "2"), chat_message("user", "3")]).await;
+        let contents: Vec<String> = store.history(&key("")).await.into_iter().map(|m| m.content).collect();
+        assert_eq!(contents, vec!["2", "3"]);
+    }
+
+    #[tokio::test]
+    async fn trim_oldest_applies_geri_trimming() {
+        let store = ConversationStore::new(10, Duration::from_secs(60));
+        store.append(&key(""), vec![chat_message("user", "1"), chat_message("assistant", "2"), chat_message("user", "3")]).await;
+        store.trim_oldest(&key(""), 2).await;
+        assert_eq!(store.history(&key("")).await.len(), 1);
+        store.trim_oldest(&key(""), 5).await;
+        assert!(store.history(&key("")).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn idle_conversations_expire() {
+        let store = ConversationStore::new(10, Duration::from_millis(20));
+        store.append(&key(""), vec![chat_message("user", "Hello")]).await;
+        tokio::time::sleep(Duration::from_millis(40)).await;
+        assert!(store.history(&key("")).await.is_empty());
+        assert_eq!(store.evict_idle().await, 1);
+        assert!(store.is_empty().await);
+    }
+}
@@ -0,0 +1,199 @@
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use odin::clients::manager::ClientManager;
+    use odin::grpc::OdinResponsibilityService;
+    use odin::orchestration::responsibility::ResponsibilityManager;
+    use odin::orchestration::{ConversationKey, HandoffStore, HandoffTransition, UserRequest};
+    use odin::plugins::{OdinPlugin, PluginManager};
+    use odin::protocols::einherjar::einherjar::CapabilityResponse;
+    use odin::protocols::einherjar::CapabilityCache;
+    use odin::protocols::manager::ProtocolManager;
+    use odin::protocols::responsibility::responsibility::responsibility_service_server::{ResponsibilityService, ResponsibilityServiceServer};
+    use odin::protocols::responsibility::responsibility::*;
+    use odin::utils::config::OdinSettings;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tempfile::TempDir;
+    use tonic::{Request, Response, Status};
+
+    fn key(session_id: &str) -> ConversationKey {
+        ConversationKey { user_id: "u1".to_string(), device_id: "d1".to_string(), session_id: session_id.to_string() }
+    }
+
+    fn transitions(history: &std::collections::VecDeque<odin::orchestration::HandoffRecord>) -> Vec<(String, HandoffTransition)> {
+        history.iter().map(|r| (r.service.clone(), r.transition)).collect()
+    }
+
+    #[tokio::test]
+    async fn owner_keeps_conversation_until_it_returns_it() {
+        let store = HandoffStore::new(Duration::from_secs(60), 10);
+        let lease = store.take(&key("s1"), "valkyries", "r1", "Relevance score: 15").await;
+        assert_eq!(lease.owner, "valkyries");
+        assert!(store.owner(&key("s2")).await.is_none());
+
+        let routed = store.route_to_owner(&key("s1"), "r2").await.unwrap();
+        assert_eq!(routed.request_ids, ["r1", "r2"]);
+        assert!(routed.expires_at_ms >= lease.expires_at_ms);
+
+        // Only the owner can give it back, naming any request it received
+        assert!(store.release("r2", Some("thor"), "done").await.is_none());
+        assert!(store.release("r2", Some("valkyries"), "task finished").await.is_some());
+        assert!(store.route_to_owner(&key("s1"), "r3").await.is_none());
+
+        let state = store.snapshot().await;
+        assert!(state.leases.is_empty());
+        assert_eq!(
+            transitions(&state.history),
+            [
+                ("valkyries".to_string(), HandoffTransition::Taken),
+                ("valkyries".to_string(), HandoffTransition::Routed),
+                ("valkyries".to_string(), HandoffTransition::Returned),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn lease_expires_and_state_survives_restart() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("handoff_state.json");
+        {
+            let store = HandoffStore::open(&path, Duration::from_secs(60), 10).unwrap();
+            store.reject(&key(""), "thor", "r1", "busy").await;
+            store.take(&key(""), "valkyries", "r1", "Relevance score: 10").await;
+        }
+        let restarted = HandoffStore::open(&path, Duration::from_millis(30), 10).unwrap();
+        assert_eq!(restarted.owner(&key("")).await.unwrap().owner, "valkyries");
+        let history = restarted.snapshot().await.history;
+        assert_eq!(history[0].detail, "busy");
+
+        // Renewed with the short lease, then left alone until it runs out
+        restarted.route_to_owner(&key(""), "r2").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(restarted.owner(&key("")).await.is_none());
+        let reloaded = HandoffStore::open(&path, Duration::from_secs(60), 2).unwrap();
+        let state = reloaded.snapshot().await;
+        assert!(state.leases.is_empty());
+        assert_eq!(state.history.back().unwrap().transition, HandoffTransition::Expired);
+    }
+
+    /// Responsibility endpoint of a service: accepts or rejects every request and counts them
+    struct FakeService {
+        accept: bool,
+        takes: Arc<AtomicUsize>,
+    }
+
+    #[tonic::async_trait]
+    impl ResponsibilityService for FakeService {
+        async fn take_responsibility(&self, _request: Request<TakeResponsibilityRequest>) -> Result<Response<TakeResponsibilityResponse>, Status> {
+            self.takes.fetch_add(1, Ordering::SeqCst);
+            Ok(Response::new(TakeResponsibilityResponse {
+                accepted: self.accept,
+                message: if self.accept { String::new() } else { "busy".to_string() },
+                one_shot: false,
+            }))
+        }
+
+        async fn return_responsibility(&self, _request: Request<ReturnResponsibilityRequest>) -> Result<Response<ReturnResponsibilityResponse>, Status> {
+            Err(Status::unimplemented("not used"))
+        }
+
+        async fn reject_responsibility(&self, _request: Request<RejectResponsibilityRequest>) -> Result<Response<RejectResponsibilityResponse>, Status> {
+            Err(Status::unimplemented("not used"))
+        }
+    }
+
+    async fn start_service(accept: bool) -> (String, Arc<AtomicUsize>) {
+        let takes = Arc::new(AtomicUsize::new(0));
+        let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let service = FakeService { accept, takes: takes.clone() };
+        tokio::spawn(tonic::transport::Server::builder().add_service(ResponsibilityServiceServer::new(service)).serve(addr));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        (format!("http://{}", addr), takes)
+    }
+
+    struct Valkyries;
+
+    #[async_trait]
+    impl OdinPlugin for Valkyries {
+        fn name(&self) -> &str {
+            "valkyries"
+        }
+
+        fn capabilities(&self) -> Vec<String> {
+            vec!["coding".to_string()]
+        }
+
+        async fn process_request(&self, request: &str) -> Result<String, Box<dyn std::error::Error>> {
+            Ok(format!("valkyries: {}", request))
+        }
+    }
+
+    fn request(request_id: &str, input: &str) -> UserRequest {
+        UserRequest {
+            request_id: request_id.to_string(),
+            user_id: "u1".to_string(),
+            device_id: "d1".to_string(),
+            input: input.to_string(),
+            input_type: "text".to_string(),
+            session_id: "coding".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn rejection_cascades_and_following_turns_go_to_owner() {
+        let (thor_url, thor_takes) = start_service(false).await;
+        let (valkyries_url, valkyries_takes) = start_service(true).await;
+        let mut settings = OdinSettings::default();
+        settings.service_urls.thor = Some(thor_url.clone());
+        settings.service_urls.valkyries = Some(valkyries_url.clone());
+        let settings = Arc::new(tokio::sync::RwLock::new(settings));
+
+        let cache = Arc::new(CapabilityCache::new());
+        let capability = |name: &str, keywords: &[&str]| CapabilityResponse {
+            god_name: name.to_string(),
+            responsibility_keywords: keywords.iter().map(|k| k.to_string()).collect(),
+            ..Default::default()
+        };
+        cache.update("thor".to_string(), thor_url, capability("Thor", &["refactor", "file"])).await;
+        cache.update("valkyries".to_string(), valkyries_url, capability("Valkyries", &["refactor"])).await;
+
+        let plugins = Arc::new(PluginManager::new());
+        plugins.register(Arc::new(Valkyries)).await;
+        let handoffs = Arc::new(HandoffStore::new(Duration::from_secs(60), 20));
+        let manager = ResponsibilityManager::new(cache, Arc::new(ProtocolManager::new(settings.clone())), Arc::new(ClientManager::new(settings)))
+            .with_handoff_store(handoffs.clone())
+            .with_plugin_manager(plugins);
+
+        // Thor scores higher but rejects; Valkyries accepts and owns the session
+        let response = manager.route_request(&request("r1", "refactor the file parser")).await.unwrap();
+        assert_eq!(response, "valkyries: refactor the file parser");
+        let response = manager.route_request(&request("r2", "now add tests")).await.unwrap();
+        assert_eq!(response, "valkyries: now add tests");
+        assert_eq!((thor_takes.load(Ordering::SeqCst), valkyries_takes.load(Ordering::SeqCst)), (1, 1));
+        assert_eq!(
+            transitions(&handoffs.snapshot().await.history),
+            [
+                ("thor".to_string(), HandoffTransition::Rejected),
+                ("valkyries".to_string(), HandoffTransition::Taken),
+                ("valkyries".to_string(), HandoffTransition::Routed),
+            ]
+        );
+
+        // Valkyries returns the conversation via Odin's Responsibility service; the next turn is routed again
+        let odin = OdinResponsibilityService::new(handoffs.clone());
+        let returned = odin
+            .return_responsibility(Request::new(ReturnResponsibilityRequest {
+                request_id: "r2".to_string(),
+                reason: "refactoring done".to_string(),
+                service_name: "valkyries".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(returned.acknowledged);
+        manager.route_request(&request("r3", "refactor the next file")).await.unwrap();
+        assert_eq!((thor_takes.load(Ordering::SeqCst), valkyries_takes.load(Ordering::SeqCst)), (2, 2));
+    }
+}
@@ -0,0 +1,274 @@
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use odin::clients::geri::geri::{ProcessPromptRequest, ProcessPromptResponse};
+    use odin::clients::manager::ClientManager;
+    use odin::grpc::odin::odin_service_client::OdinServiceClient;
+    use odin::grpc::odin::odin_service_server::OdinServiceServer;
+    use odin::grpc::odin::ProcessRequest;
+    use odin::grpc::OdinServiceImpl;
+    use odin::orchestration::responsibility::ResponsibilityManager;
+    use odin::orchestration::{
+        Action, ActionExecutor, ActionOrchestrator, FunctionCall, IntentClassifier, IntentModel, IntentResolution,
+        RequestProcessor, UserRequest,
+    };
+    use odin::protocols::einherjar::einherjar::{CapabilityResponse, FunctionDefinition, ParameterDefinition};
+    use odin::protocols::einherjar::{AggregatedCapabilities, CapabilityCache};
+    use odin::protocols::manager::ProtocolManager;
+    use odin::utils::config::{IntentConfig, OdinSettings};
+    use serde_json::{json, Value};
+    use std::sync::{Arc, Mutex};
+
+    /// Geri stand-in: answers with a fixed structured output and keeps the last request
+    struct FakeGeri {
+        answer: Result<String, String>,
+        last_request: Mutex<Option<ProcessPromptRequest>>,
+    }
+
+    impl FakeGeri {
+        fn answering(answer: serde_json::Value) -> Arc<Self> {
+            Arc::new(Self { answer: Ok(answer.to_string()), last_request: Mutex::new(None) })
+        }
+    }
+
+    #[async_trait]
+    impl IntentModel for FakeGeri {
+        async fn complete(&self, request: ProcessPromptRequest) -> Result<ProcessPromptResponse, String> {
+            *self.last_request.lock().unwrap() = Some(request);
+            let structured_json = self.answer.clone()?;
+            Ok(ProcessPromptResponse { structured_json, ..Default::default() })
+        }
+    }
+
+    fn param(name: &str, json_type: &str, description: &str, required: bool, enum_values: &[&str]) -> ParameterDefinition {
+        ParameterDefinition {
+            name: name.to_string(),
+            r#type: json_type.to_string(),
+            description: description.to_string(),
+            required,
+            enum_values: enum_values.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+
+    async fn capability_cache() -> Arc<CapabilityCache> {
+        let cache = Arc::new(CapabilityCache::new());
+        let thor = CapabilityResponse {
+            god_name: "Thor".to_string(),
+            purpose: "Action Execution".to_string(),
+            functions: vec![FunctionDefinition {
+                name: "run_command".to_string(),
+                description: "Run a shell command".to_string(),
+                parameters: vec![
+                    param("command", "string", "shell command", true, &[]),
+                    param("timeout_secs", "integer", "timeout in seconds", false, &[]),
+                ],
+                ..Default::default()
+            }],
+            responsibility_domains: vec!["system".to_string()],
+            responsibility_keywords: vec!["run".to_string()],
+        };
+        let loki = CapabilityResponse {
+            god_name: "Loki".to_string(),
+            purpose: "IoT Tool Calling".to_string(),
+            functions: vec![FunctionDefinition {
+                name: "set_light".to_string(),
+                description: "Switch a light".to_string(),
+                parameters: vec![param("room", "string", "room", true, &[]), param("state", "string", "light state", true, &["on", "off"])],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        cache.update("thor".to_string(), "http://localhost:50052".to_string(), thor).await;
+        cache.update("loki".to_string(), "http://localhost:50057".to_string(), loki).await;
+        cache
+    }
+
+    async fn capabilities() -> AggregatedCapabilities {
+        capability_cache().await.get_aggregated().await
+    }
+
+    fn request(input: &str) -> UserRequest {
+        UserRequest {
+            request_id: "r1".to_string(),
+            user_id: "u1".to_string(),
+            device_id: "d1".to_string(),
+            input: input.to_string(),
+            input_type: "text".to_string(),
+            session_id: String::new(),
+        }
+    }
+
+    fn classifier(geri: &Arc<FakeGeri>) -> IntentClassifier {
+        IntentClassifier::new(geri.clone(), IntentConfig::default())
+    }
+
+    #[tokio::test]
+    async fn question_with_action_words_is_answered_not_executed() {
+        let geri = FakeGeri::answering(json!({
+            "intent": "question", "service": "", "calls": [], "confidence": 0.95, "clarification_question": ""
+        }));
+        let resolution = classifier(&geri).classify(&request("What's the weather? Don't run anything."), &capabilities().await).await;
+        assert_eq!(resolution, Some(IntentResolution::Answer { confidence: 0.95 }));
+
+        // Geri got the capabilities and the schema for structured output
+        let sent = geri.last_request.lock().unwrap().clone().unwrap();
+        assert!(sent.system_prompt.contains("run_command(command: string, timeout_secs: integer?): Run a shell command"));
+        assert!(sent.system_prompt.contains("set_light(room: string, state: string (one of on, off))"));
+        assert_eq!(sent.response_format.unwrap().name, "intent");
+    }
+
+    #[tokio::test]
+    async fn action_arguments_are_typed_and_planned() {
+        let geri = FakeGeri::answering(json!({
+            "intent": "action", "service": "thor", "confidence": 0.9, "clarification_question": "",
+            "calls": [{ "function": "run_command", "arguments": { "command": "df -h", "timeout_secs": "30", "sudo": true } }]
+        }));
+        let resolution = classifier(&geri).classify(&request("Run df -h with a 30 second timeout"), &capabilities().await).await.unwrap();
+
+        let mut arguments = serde_json::Map::new();
+        arguments.insert("command".to_string(), json!("df -h"));
+        arguments.insert("timeout_secs".to_string(), json!(30));
+        assert_eq!(
+            resolution,
+            IntentResolution::Execute {
+                service: "thor".to_string(),
+                calls: vec![FunctionCall { function: "run_command".to_string(), arguments }],
+                confidence: 0.9,
+            }
+        );
+        let plan = resolution.action_plan().unwrap();
+        assert_eq!(plan.actions.len(), 1);
+        assert_eq!((plan.actions[0].service.as_str(), plan.actions[0].action_type.as_str()), ("thor", "run_command"));
+        assert_eq!(plan.actions[0].parameters["timeout_secs"], 30);
+    }
+
+    #[tokio::test]
+    async fn ambiguous_or_incomplete_requests_ask_back() {
+        let caps = capabilities().await;
+        let low_confidence = FakeGeri::answering(json!({
+            "intent": "action", "service": "loki", "confidence": 0.4, "clarification_question": "Which room do you mean?",
+            "calls": [{ "function": "set_light", "arguments": { "room": "kitchen", "state": "on" } }]
+        }));
+        assert_eq!(
+            classifier(&low_confidence).classify(&request("light"), &caps).await,
+            Some(IntentResolution::Clarify { question: "Which room do you mean?".to_string() })
+        );
+
+        let missing_room = FakeGeri::answering(json!({
+            "intent": "action", "service": "loki", "confidence": 0.8, "clarification_question": "",
+            "calls": [{ "function": "set_light", "arguments": { "state": "on" } }]
+        }));
+        let Some(IntentResolution::Clarify { question }) = classifier(&missing_room).classify(&request("Turn the light on"), &caps).await else {
+            panic!("expected a clarification question");
+        };
+        assert_eq!(question, "Which room should I use for set_light?");
+
+        let bad_state = FakeGeri::answering(json!({
+            "intent": "action", "service": "loki", "confidence": 0.8, "clarification_question": "",
+            "calls": [{ "function": "set_light", "arguments": { "room": "kitchen", "state": "dimmed" } }]
+        }));
+        assert!(matches!(
+            classifier(&bad_state).classify(&request("Dim the kitchen light"), &caps).await,
+            Some(IntentResolution::Clarify { question }) if question.contains("on, off")
+        ));
+    }
+
+    #[tokio::test]
+    async fn unavailable_or_unusable_model_falls_back() {
+        let caps = capabilities().await;
+        let offline = Arc::new(FakeGeri { answer: Err("Geri client not initialized".to_string()), last_request: Mutex::new(None) });
+        assert_eq!(classifier(&offline).classify(&request("Run ls"), &caps).await, None);
+
+        let garbage = Arc::new(FakeGeri { answer: Ok("I think thor".to_string()), last_request: Mutex::new(None) });
+        assert_eq!(classifier(&garbage).classify(&request("Run ls"), &caps).await, None);
+    }
+
+    #[tokio::test]
+    async fn responsibility_manager_returns_clarification_question() {
+        let settings = Arc::new(tokio::sync::RwLock::new(OdinSettings::default()));
+        let geri = FakeGeri::answering(json!({
+            "intent": "unclear", "service": "", "calls": [], "confidence": 0.3,
+            "clarification_question": "Do you want to restart the server or just check it?"
+        }));
+        let manager = ResponsibilityManager::new(
+            capability_cache().await,
+            Arc::new(ProtocolManager::new(settings.clone())),
+            Arc::new(ClientManager::new(settings)),
+        )
+        .with_intent_classifier(Arc::new(classifier(&geri)));
+
+        let response = manager.route_request(&request("do the server thing")).await.unwrap();
+        assert_eq!(response, "Do you want to restart the server or just check it?");
+    }
+
+    /// Thor stand-in that records every action it is asked to run
+    #[derive(Default)]
+    struct RecordingThor {
+        executed: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl ActionExecutor for RecordingThor {
+        async fn execute(&self, action: &Action) -> Result<Value, String> {
+            self.executed.lock().unwrap().push(action.action_type.clone());
+            Ok(json!({ "done": action.action_id }))
+        }
+    }
+
+    #[tokio::test]
+    async fn classified_answer_mentioning_run_dispatches_no_action() {
+        let settings = Arc::new(tokio::sync::RwLock::new(OdinSettings::default()));
+        let geri = FakeGeri::answering(json!({
+            "intent": "unclear", "service": "", "calls": [], "confidence": 0.3,
+            "clarification_question": "Should I run the backup now or tonight?"
+        }));
+        let manager = ResponsibilityManager::new(
+            capability_cache().await,
+            Arc::new(ProtocolManager::new(settings.clone())),
+            Arc::new(ClientManager::new(settings)),
+        )
+        .with_intent_classifier(Arc::new(classifier(&geri)));
+        let processor = RequestProcessor::new_with_responsibility(Arc::new(manager));
+        let service = OdinServiceImpl::new(Arc::new(processor), Arc::new(ActionOrchestrator::new()));
+        let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        tokio::spawn(tonic::transport::Server::builder().add_service(OdinServiceServer::new(service)).serve(addr));
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let mut client = OdinServiceClient::connect(format!("http://{}", addr)).await.unwrap();
+
+        let response = client
+            .process(ProcessRequest {
+                request_id: "r1".to_string(),
+                user_id: "u1".to_string(),
+                device_id: "d1".to_string(),
+                input: "back up my files".to_string(),
+                input_type: "text".to_string(),
+                session_id: String::new(),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.response, "Should I run the backup now or tonight?");
+        assert!(response.actions_taken.is_empty(), "keyword planning ran on the answer: {:?}", response.actions_taken);
+    }
+
+    #[tokio::test]
+    async fn fallback_processor_runs_only_the_classified_calls() {
+        let thor = Arc::new(RecordingThor::default());
+        let answer = FakeGeri::answering(json!({
+            "intent": "question", "service": "", "calls": [], "confidence": 0.9, "clarification_question": ""
+        }));
+        let processor = RequestProcessor::new_with_action_fallback(ActionOrchestrator::new().with_executor(thor.clone()))
+            .with_intent_classifier(Arc::new(classifier(&answer)), capability_cache().await);
+        processor.process(request("How do I run a backup?")).await.unwrap();
+        assert!(thor.executed.lock().unwrap().is_empty());
+
+        let execute = FakeGeri::answering(json!({
+            "intent": "action", "service": "thor", "confidence": 0.9, "clarification_question": "",
+            "calls": [{ "function": "run_command", "arguments": { "command": "df -h" } }]
+        }));
+        let processor = RequestProcessor::new_with_action_fallback(ActionOrchestrator::new().with_executor(thor.clone()))
+            .with_intent_classifier(Arc::new(classifier(&execute)), capability_cache().await);
+        processor.process(request("Run df -h and list the log files")).await.unwrap();
+        assert_eq!(*thor.executed.lock().unwrap(), vec!["run_command".to_string()]);
+    }
+}
@@ -1,3 +1,12 @@
 pub mod responsibility_test;
 pub mod processor_test;
 pub mod action_test;
+pub mod conversation_test;
+pub mod plan_executor_test;
+pub mod intent_test;
+pub mod agent_test;
+pub mod handoff_test;
+pub mod confirmation_test;
+pub mod progress_test;
+pub mod routine_test;
+pub mod trace_test;
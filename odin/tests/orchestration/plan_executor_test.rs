@@ -0,0 +1,188 @@
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use odin::orchestration::{
+        Action, ActionExecutor, ActionOrchestrator, ActionPlan, FailureMode, OrchestrationError, PlanStatus, RequestProcessor, StepPolicy, StepStatus,
+        UserRequest,
+    };
+    use serde_json::{json, Value};
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// Fake services: records calls and parameters, answers from a script per action id
+    #[derive(Default)]
+    struct FakeServices {
+        calls: Mutex<Vec<(String, Value)>>,
+        failures: Mutex<HashMap<String, usize>>,
+        running: AtomicUsize,
+        max_running: AtomicUsize,
+    }
+
+    impl FakeServices {
+        /// `action_id` fails its first `times` attempts
+        fn fail(self: &Arc<Self>, action_id: &str, times: usize) {
+            self.failures.lock().unwrap().insert(action_id.to_string(), times);
+        }
+
+        fn called(&self) -> Vec<String> {
+            self.calls.lock().unwrap().iter().map(|(id, _)| id.clone()).collect()
+        }
+
+        fn parameters(&self, action_id: &str) -> Value {
+            self.calls.lock().unwrap().iter().find(|(id, _)| id == action_id).map(|(_, p)| p.clone()).unwrap()
+        }
+    }
+
+    #[async_trait]
+    impl ActionExecutor for FakeServices {
+        async fn execute(&self, action: &Action) -> Result<Value, String> {
+            self.calls.lock().unwrap().push((action.action_id.clone(), action.parameters.clone()));
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_running.fetch_max(running, Ordering::SeqCst);
+            let delay = action.parameters["delay_ms"].as_u64().unwrap_or(10);
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            self.running.fetch_sub(1, Ordering::SeqCst);
+
+            if let Some(remaining) = self.failures.lock().unwrap().get_mut(&action.action_id) {
+                if *remaining > 0 {
+                    *remaining -= 1;
+                    return Err(format!("{} unavailable", action.service));
+                }
+            }
+            Ok(json!({ "service": action.service, "path": format!("/tmp/{}.txt", action.action_id) }))
+        }
+    }
+
+    fn step(id: &str, service: &str, depends_on: &[&str], parameters: Value) -> Action {
+        Action {
+            action_id: id.to_string(),
+            action_type: "TEST".to_string(),
+            service: service.to_string(),
+            parameters,
+            depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn orchestrator(services: &Arc<FakeServices>) -> ActionOrchestrator {
+        ActionOrchestrator::new().with_executor(services.clone())
+    }
+
+    #[tokio::test]
+    async fn independent_steps_run_concurrently_and_references_resolve() {
+        let services = Arc::new(FakeServices::default());
+        let plan = ActionPlan {
+            actions: vec![
+                step("a1", "thor", &[], json!({ "delay_ms": 50 })),
+                step("a2", "freki", &[], json!({ "delay_ms": 50 })),
+                step(
+                    "a3",
+                    "geri",
+                    &["a1", "a2"],
+                    json!({ "file": "${a1.result.path}", "prompt": "Summarize ${a1.result.path} from ${a2.result.service}", "raw": "${a2.result}" }),
+                ),
+            ],
+        };
+
+        let report = orchestrator(&services).execute_plan(&plan).await.unwrap();
+        assert_eq!(report.status, PlanStatus::Succeeded);
+        assert_eq!(services.max_running.load(Ordering::SeqCst), 2);
+        assert_eq!(services.called().last().map(String::as_str), Some("a3"));
+
+        let params = services.parameters("a3");
+        assert_eq!(params["file"], "/tmp/a1.txt");
+        assert_eq!(params["prompt"], "Summarize /tmp/a1.txt from freki");
+        assert_eq!(params["raw"]["service"], "freki");
+        assert_eq!(report.step("a3").unwrap().output.as_ref().unwrap()["service"], "geri");
+    }
+
+    #[tokio::test]
+    async fn invalid_plans_are_rejected_before_execution() {
+        let services = Arc::new(FakeServices::default());
+        let orchestrator = orchestrator(&services);
+        let cycle = ActionPlan { actions: vec![step("a1", "thor", &["a2"], json!({})), step("a2", "thor", &["a1"], json!({}))] };
+        let unknown = ActionPlan { actions: vec![step("a1", "thor", &["missing"], json!({}))] };
+        let dangling = ActionPlan {
+            actions: vec![step("a1", "thor", &[], json!({})), step("a2", "loki", &[], json!({ "device": "${a1.result.path}" }))],
+        };
+
+        for plan in [cycle, unknown, dangling] {
+            assert!(matches!(orchestrator.execute_plan(&plan).await, Err(OrchestrationError::InvalidPlan(_))));
+        }
+        assert!(services.called().is_empty());
+    }
+
+    #[tokio::test]
+    async fn retries_and_timeouts_follow_step_policy() {
+        let services = Arc::new(FakeServices::default());
+        services.fail("flaky", 2);
+        let mut flaky = step("flaky", "loki", &[], json!({}));
+        flaky.policy = StepPolicy { max_retries: 2, retry_delay_ms: 1, ..Default::default() };
+        // The doubled delay saturates and is capped instead of overflowing
+        services.fail("capped", 2);
+        let mut capped = step("capped", "loki", &[], json!({}));
+        capped.policy = StepPolicy { max_retries: 2, retry_delay_ms: u64::MAX, max_retry_delay_ms: 1, ..Default::default() };
+        let mut slow = step("slow", "thor", &[], json!({ "delay_ms": 500 }));
+        slow.policy = StepPolicy { timeout_ms: Some(20), on_failure: FailureMode::Continue, ..Default::default() };
+        let after_slow = step("after-slow", "geri", &["slow"], json!({}));
+
+        let report = orchestrator(&services).execute_plan(&ActionPlan { actions: vec![flaky, capped, slow, after_slow] }).await.unwrap();
+        assert_eq!(report.status, PlanStatus::PartiallySucceeded);
+        let flaky = report.step("flaky").unwrap();
+        assert_eq!((flaky.status, flaky.attempts), (StepStatus::Succeeded, 3));
+        let capped = report.step("capped").unwrap();
+        assert_eq!((capped.status, capped.attempts), (StepStatus::Succeeded, 3));
+        assert_eq!(report.step("slow").unwrap().status, StepStatus::TimedOut);
+        assert_eq!(report.step("after-slow").unwrap().status, StepStatus::Skipped);
+        assert!(report.summary().contains("slow (thor) failed: timed out"));
+    }
+
+    #[tokio::test]
+    async fn failure_aborts_or_compensates_the_plan() {
+        let services = Arc::new(FakeServices::default());
+        services.fail("notify", 1);
+        let mut download = step("download", "thor", &[], json!({}));
+        download.compensation = Some(Box::new(step("", "thor", &[], json!({ "delete": "${download.result.path}" }))));
+        let mut notify = step("notify", "loki", &["download"], json!({}));
+        notify.policy.on_failure = FailureMode::Compensate;
+        let summarize = step("summarize", "geri", &["notify"], json!({}));
+        let plan = ActionPlan { actions: vec![download, notify, summarize] };
+
+        let report = orchestrator(&services).execute_plan(&plan).await.unwrap();
+        assert_eq!(report.status, PlanStatus::Compensated);
+        assert_eq!(report.step("summarize").unwrap().status, StepStatus::Skipped);
+        assert_eq!(report.compensations.len(), 1);
+        assert_eq!(report.compensations[0].action_id, "download-compensation");
+        assert_eq!(services.parameters("download-compensation")["delete"], "/tmp/download.txt");
+
+        // Without compensation the plan stops at the failure
+        let services = Arc::new(FakeServices::default());
+        services.fail("notify", 1);
+        let mut plan = plan;
+        plan.actions[1].policy.on_failure = FailureMode::Abort;
+        let report = orchestrator(&services).execute_plan(&plan).await.unwrap();
+        assert_eq!(report.status, PlanStatus::Failed);
+        assert!(report.compensations.is_empty());
+        assert_eq!(services.called(), ["download", "notify"]);
+    }
+
+    #[tokio::test]
+    async fn processor_executes_planned_actions() {
+        let services = Arc::new(FakeServices::default());
+        let processor = RequestProcessor::new_with_action_fallback(orchestrator(&services));
+        let request = UserRequest {
+            request_id: "r1".to_string(),
+            user_id: "u1".to_string(),
+            device_id: "d1".to_string(),
+            input: "Show network connections".to_string(),
+            input_type: "text".to_string(),
+            session_id: String::new(),
+        };
+
+        let response = processor.process(request).await.unwrap();
+        assert!(response.starts_with("Plan succeeded: 1/1 step(s) succeeded"), "{}", response);
+        assert_eq!(services.called().len(), 1);
+    }
+}
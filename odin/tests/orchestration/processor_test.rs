@@ -16,6 +16,7 @@ mod tests {
             device_id: "d1".to_string(),
             input: input.to_string(),
             input_type: input_type.to_string(),
+            session_id: String::new(),
         }
     }
 
@@ -308,6 +309,7 @@ mod tests {
             device_id: "device-1".to_string(),
             input: "What is the weather?".to_string(),
             input_type: "text".to_string(),
+            session_id: String::new(),
         };
         
         let result = processor.process(request).await;
@@ -0,0 +1,122 @@
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use odin::grpc::odin::odin_service_client::OdinServiceClient;
+    use odin::grpc::odin::odin_service_server::OdinServiceServer;
+    use odin::grpc::odin::process_event::Event;
+    use odin::grpc::odin::process_stream_request::Message;
+    use odin::grpc::odin::{CancelProcess, ProcessEvent, ProcessRequest, ProcessStreamRequest};
+    use odin::grpc::OdinServiceImpl;
+    use odin::orchestration::{Action, ActionExecutor, ActionOrchestrator, RequestProcessor};
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// Thor stand-in: answers after `delay`; records whether a running call was dropped
+    struct SlowThor {
+        delay: Duration,
+        dropped: Arc<AtomicBool>,
+    }
+
+    struct DropFlag(Arc<AtomicBool>);
+
+    impl Drop for DropFlag {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[async_trait]
+    impl ActionExecutor for SlowThor {
+        async fn execute(&self, action: &Action) -> Result<Value, String> {
+            let flag = DropFlag(self.dropped.clone());
+            tokio::time::sleep(self.delay).await;
+            std::mem::forget(flag);
+            Ok(json!({ "done": action.action_id }))
+        }
+    }
+
+    async fn start_odin(delay: Duration) -> (OdinServiceClient<tonic::transport::Channel>, Arc<AtomicBool>) {
+        let dropped = Arc::new(AtomicBool::new(false));
+        let thor = Arc::new(SlowThor { delay, dropped: dropped.clone() });
+        let processor = RequestProcessor::new_with_action_fallback(ActionOrchestrator::new().with_executor(thor));
+        let service = OdinServiceImpl::new(Arc::new(processor), Arc::new(ActionOrchestrator::new()));
+        let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        tokio::spawn(tonic::transport::Server::builder().add_service(OdinServiceServer::new(service)).serve(addr));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        (OdinServiceClient::connect(format!("http://{}", addr)).await.unwrap(), dropped)
+    }
+
+    fn start(request_id: &str, input: &str) -> ProcessStreamRequest {
+        ProcessStreamRequest {
+            message: Some(Message::Start(ProcessRequest {
+                request_id: request_id.to_string(),
+                user_id: "u1".to_string(),
+                device_id: "d1".to_string(),
+                input: input.to_string(),
+                input_type: "text".to_string(),
+                session_id: String::new(),
+            })),
+        }
+    }
+
+    async fn next_event(events: &mut tonic::Streaming<ProcessEvent>) -> Event {
+        let event = tokio::time::timeout(Duration::from_secs(5), events.message()).await.unwrap().unwrap().unwrap();
+        assert_eq!(event.request_id, "r1");
+        event.event.unwrap()
+    }
+
+    #[tokio::test]
+    async fn stream_reports_actions_before_the_response() {
+        let (mut client, _) = start_odin(Duration::from_millis(10)).await;
+        let (tx, rx) = tokio::sync::mpsc::channel(4);
+        tx.send(start("r1", "list running processes")).await.unwrap();
+        let mut events = client.process_stream(tokio_stream::wrappers::ReceiverStream::new(rx)).await.unwrap().into_inner();
+
+        let (mut started, mut finished) = (Vec::new(), Vec::new());
+        let response = loop {
+            match next_event(&mut events).await {
+                Event::ActionStarted(action) => {
+                    assert_eq!((action.action_type.as_str(), action.service.as_str()), ("XML_TASK", "thor"));
+                    started.push(action.action_id);
+                }
+                Event::ActionFinished(action) => {
+                    assert!(started.contains(&action.action_id));
+                    assert_eq!(action.status, "succeeded");
+                    finished.push(action.action_id);
+                }
+                Event::Completed(response) => break response,
+                other => panic!("unexpected event {:?}", other),
+            }
+        };
+        assert!(!started.is_empty());
+        started.sort();
+        finished.sort();
+        assert_eq!(started, finished);
+        assert!(!response.response.is_empty());
+        assert!(events.message().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn cancel_stops_running_actions() {
+        let (mut client, dropped) = start_odin(Duration::from_secs(30)).await;
+        let (tx, rx) = tokio::sync::mpsc::channel(4);
+        tx.send(start("r1", "list running processes")).await.unwrap();
+        let mut events = client.process_stream(tokio_stream::wrappers::ReceiverStream::new(rx)).await.unwrap().into_inner();
+
+        assert!(matches!(next_event(&mut events).await, Event::ActionStarted(_)));
+        tx.send(ProcessStreamRequest { message: Some(Message::Cancel(CancelProcess { reason: "user pressed stop".to_string() })) })
+            .await
+            .unwrap();
+        let cancelled = loop {
+            match next_event(&mut events).await {
+                Event::ActionStarted(_) => continue,
+                Event::Cancelled(cancelled) => break cancelled,
+                other => panic!("unexpected event {:?}", other),
+            }
+        };
+        assert_eq!(cancelled.reason, "user pressed stop");
+        assert!(dropped.load(Ordering::SeqCst));
+    }
+}
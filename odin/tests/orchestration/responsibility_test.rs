@@ -76,6 +76,7 @@ mod tests {
             device_id: "device-1".to_string(),
             input: "Can you explain how this works?".to_string(),
             input_type: "text".to_string(),
+            session_id: String::new(),
         };
         
         let result = responsibility_manager.determine_responsibility(&request).await;
@@ -120,6 +121,7 @@ mod tests {
             device_id: "device-1".to_string(),
             input: "Execute file operation".to_string(),
             input_type: "text".to_string(),
+            session_id: String::new(),
         };
         
         let capability = odin::protocols::einherjar::einherjar::CapabilityResponse {
@@ -0,0 +1,258 @@
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use chrono::{NaiveTime, TimeZone, Utc};
+    use odin::orchestration::{
+        Action, ActionExecutor, ActionOrchestrator, CompareOp, CronSchedule, DeviceEvent, RequestProcessor, Routine, RoutineCondition,
+        RoutineEngine, RoutineOwner, RoutineReply, RoutineRequestHandler, RoutineStep, RoutineStore, RoutineTrigger, RunStatus, RunTrigger,
+        UserRequest,
+    };
+    use serde_json::{json, Value};
+    use std::sync::{Arc, Mutex};
+    use tempfile::TempDir;
+
+    /// Stand-in for the Process path: records requests; inputs mentioning "delete" wait for confirmation
+    #[derive(Default)]
+    struct FakePipeline {
+        requests: Mutex<Vec<UserRequest>>,
+    }
+
+    #[async_trait]
+    impl RoutineRequestHandler for FakePipeline {
+        async fn handle(&self, request: UserRequest) -> Result<RoutineReply, String> {
+            self.requests.lock().unwrap().push(request.clone());
+            if request.input.contains("delete") {
+                return Ok(RoutineReply { response: "Delete the files? (yes/no)".to_string(), confirmation_id: Some("c1".to_string()) });
+            }
+            Ok(RoutineReply { response: format!("done: {}", request.input), confirmation_id: None })
+        }
+    }
+
+    #[derive(Default)]
+    struct FakeThor {
+        executed: Mutex<Vec<Action>>,
+    }
+
+    #[async_trait]
+    impl ActionExecutor for FakeThor {
+        async fn execute(&self, action: &Action) -> Result<Value, String> {
+            self.executed.lock().unwrap().push(action.clone());
+            Ok(json!({ "done": action.action_id }))
+        }
+    }
+
+    fn routine(name: &str, trigger: RoutineTrigger, steps: Vec<RoutineStep>) -> Routine {
+        Routine {
+            id: String::new(),
+            name: name.to_string(),
+            owner: RoutineOwner { user_id: "u1".to_string(), device_id: "phone".to_string() },
+            enabled: true,
+            trigger,
+            conditions: Vec::new(),
+            steps,
+            cooldown_secs: 0,
+            timezone: String::new(),
+        }
+    }
+
+    fn fan_routine() -> Routine {
+        let mut routine = routine(
+            "Fan when hot",
+            RoutineTrigger::DeviceEvent { device_id: "living-room-sensor".to_string(), event_type: "temperature".to_string() },
+            vec![
+                RoutineStep::Request { input: "turn on the living room fan".to_string() },
+                RoutineStep::Notify { message: "It is ${event.value} °C, the fan is on".to_string() },
+            ],
+        );
+        routine.conditions = vec![RoutineCondition::Event { field: "value".to_string(), op: CompareOp::Gt, value: json!(28) }];
+        routine.cooldown_secs = 600;
+        routine
+    }
+
+    fn temperature(value: f64) -> DeviceEvent {
+        DeviceEvent {
+            device_id: "living-room-sensor".to_string(),
+            event_type: "temperature".to_string(),
+            source: "jotunheim".to_string(),
+            payload: json!({ "value": value, "unit": "celsius" }).as_object().unwrap().clone(),
+            occurred_at_ms: 0,
+        }
+    }
+
+    #[test]
+    fn cron_expressions_match_their_minutes() {
+        let weekdays = CronSchedule::parse("0 7 * * 1-5").unwrap();
+        // 2026-10-19 is a Monday
+        assert!(weekdays.matches(&Utc.with_ymd_and_hms(2026, 10, 19, 7, 0, 30).unwrap()));
+        assert!(!weekdays.matches(&Utc.with_ymd_and_hms(2026, 10, 19, 7, 1, 0).unwrap()));
+        assert!(!weekdays.matches(&Utc.with_ymd_and_hms(2026, 10, 18, 7, 0, 0).unwrap()));
+
+        let steps = CronSchedule::parse("*/15 8-18/2 * * 0,7").unwrap();
+        assert!(steps.matches(&Utc.with_ymd_and_hms(2026, 10, 18, 10, 45, 0).unwrap()));
+        assert!(!steps.matches(&Utc.with_ymd_and_hms(2026, 10, 18, 9, 45, 0).unwrap()));
+
+        // Both day fields restricted: either matches
+        let either = CronSchedule::parse("0 0 1 * 1").unwrap();
+        assert!(either.matches(&Utc.with_ymd_and_hms(2026, 10, 1, 0, 0, 0).unwrap()));
+        assert!(either.matches(&Utc.with_ymd_and_hms(2026, 10, 19, 0, 0, 0).unwrap()));
+
+        assert!(CronSchedule::parse("61 * * * *").is_err());
+        assert!(CronSchedule::parse("* * *").is_err());
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+    }
+
+    #[test]
+    fn conditions_compare_event_fields_and_local_time() {
+        let hot = RoutineCondition::Event { field: "value".to_string(), op: CompareOp::Gt, value: json!(28) };
+        let noon = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+        assert!(hot.holds(Some(&temperature(28.5)), noon));
+        assert!(!hot.holds(Some(&temperature(27.0)), noon));
+        assert!(!hot.holds(None, noon));
+
+        let unit = RoutineCondition::Event { field: "unit".to_string(), op: CompareOp::Eq, value: json!("Celsius") };
+        assert!(unit.holds(Some(&temperature(20.0)), noon));
+
+        let night = RoutineCondition::TimeWindow { from: "22:00".to_string(), to: "06:30".to_string() };
+        assert!(night.holds(None, NaiveTime::from_hms_opt(23, 15, 0).unwrap()));
+        assert!(night.holds(None, NaiveTime::from_hms_opt(6, 0, 0).unwrap()));
+        assert!(!night.holds(None, noon));
+    }
+
+    #[tokio::test]
+    async fn invalid_routines_and_foreign_ids_are_rejected() {
+        let store = RoutineStore::new(10);
+        let bad_cron = routine("Bad", RoutineTrigger::Schedule { cron: "every morning".to_string() }, vec![RoutineStep::Notify { message: "hi".to_string() }]);
+        assert!(store.upsert(bad_cron).await.is_err());
+        assert!(store.upsert(routine("Empty", RoutineTrigger::Schedule { cron: "0 7 * * *".to_string() }, Vec::new())).await.is_err());
+
+        let stored = store.upsert(fan_routine()).await.unwrap();
+        let mut foreign = stored.clone();
+        foreign.owner.user_id = "u2".to_string();
+        assert!(store.upsert(foreign).await.is_err());
+        assert!(store.get("u2", &stored.id).await.is_none());
+        assert!(!store.remove("u2", &stored.id).await);
+    }
+
+    #[tokio::test]
+    async fn sensor_event_runs_steps_as_owner_with_cooldown() {
+        let store = Arc::new(RoutineStore::new(10));
+        let pipeline = Arc::new(FakePipeline::default());
+        let engine = RoutineEngine::new(store.clone(), pipeline.clone());
+        let mut notifications = engine.subscribe();
+        let fan = store.upsert(fan_routine()).await.unwrap();
+
+        assert!(engine.handle_event(temperature(26.0)).await.is_empty());
+        let runs = engine.handle_event(temperature(29.5)).await;
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].status, RunStatus::Succeeded);
+        assert_eq!(runs[0].trigger, RunTrigger::DeviceEvent);
+        assert_eq!(runs[0].outputs[1], "It is 29.5 °C, the fan is on");
+
+        let requests = pipeline.requests.lock().unwrap().clone();
+        assert_eq!(requests.len(), 1);
+        assert_eq!((requests[0].user_id.as_str(), requests[0].device_id.as_str()), ("u1", "phone"));
+        assert_eq!(requests[0].session_id, format!("routine-{}", fan.id));
+
+        let notification = notifications.try_recv().unwrap();
+        assert_eq!((notification.user_id.as_str(), notification.routine_id.as_str()), ("u1", fan.id.as_str()));
+
+        // Still cooling down; disabled routines do not fire at all
+        assert!(engine.handle_event(temperature(30.0)).await.is_empty());
+        store.set_enabled("u1", &fan.id, false).await.unwrap();
+        assert!(engine.triggered_by(&temperature(30.0)).await.is_empty());
+
+        let history = store.history("u1", &fan.id).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(store.list("u1").await[0].last_run.as_ref().unwrap().run_id, runs[0].run_id);
+    }
+
+    #[tokio::test]
+    async fn schedule_fires_in_the_routine_time_zone() {
+        let store = Arc::new(RoutineStore::new(10));
+        let engine = RoutineEngine::new(store.clone(), Arc::new(FakePipeline::default()));
+        let mut morning = routine(
+            "Calendar summary",
+            RoutineTrigger::Schedule { cron: "0 7 * * 1-5".to_string() },
+            vec![RoutineStep::Request { input: "read my calendar summary".to_string() }],
+        );
+        morning.timezone = "Europe/Berlin".to_string();
+        store.upsert(morning).await.unwrap();
+
+        // 07:00 in Berlin (CEST) is 05:00 UTC
+        let before = Utc.with_ymd_and_hms(2026, 10, 19, 4, 30, 0).unwrap();
+        assert!(engine.run_due(before, Utc.with_ymd_and_hms(2026, 10, 19, 4, 59, 59).unwrap()).await.is_empty());
+        let runs = engine.run_due(Utc.with_ymd_and_hms(2026, 10, 19, 4, 59, 59).unwrap(), Utc.with_ymd_and_hms(2026, 10, 19, 5, 0, 20).unwrap()).await;
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].trigger, RunTrigger::Schedule);
+        assert_eq!(runs[0].outputs, vec!["done: read my calendar summary".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn held_confirmation_stops_the_run_and_asks_the_owner() {
+        let store = Arc::new(RoutineStore::new(10));
+        let pipeline = Arc::new(FakePipeline::default());
+        let engine = RoutineEngine::new(store.clone(), pipeline.clone());
+        let mut notifications = engine.subscribe();
+        let cleanup = store
+            .upsert(routine(
+                "Cleanup",
+                RoutineTrigger::Schedule { cron: "0 3 * * *".to_string() },
+                vec![
+                    RoutineStep::Request { input: "delete the old downloads".to_string() },
+                    RoutineStep::Notify { message: "Downloads cleaned up".to_string() },
+                ],
+            ))
+            .await
+            .unwrap();
+
+        let run = engine.test_fire("u1", &cleanup.id, None, false).await.unwrap();
+        assert_eq!(run.status, RunStatus::AwaitingConfirmation);
+        assert_eq!(run.trigger, RunTrigger::Test);
+        assert_eq!(run.outputs.len(), 1);
+        assert_eq!(notifications.try_recv().unwrap().confirmation_id.as_deref(), Some("c1"));
+        assert!(notifications.try_recv().is_err());
+        assert!(engine.test_fire("u2", &cleanup.id, None, false).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fire_checks_conditions_on_request_and_uses_the_orchestration_path() {
+        let store = Arc::new(RoutineStore::new(10));
+        let thor = Arc::new(FakeThor::default());
+        let processor = RequestProcessor::new_with_action_fallback(ActionOrchestrator::new().with_executor(thor.clone()));
+        let engine = RoutineEngine::new(store.clone(), Arc::new(processor));
+        let mut check = fan_routine();
+        check.steps = vec![RoutineStep::Request { input: "list running processes".to_string() }];
+        let check = store.upsert(check).await.unwrap();
+
+        let skipped = engine.test_fire("u1", &check.id, Some(temperature(20.0)), true).await.unwrap();
+        assert_eq!(skipped.status, RunStatus::ConditionsNotMet);
+        assert!(thor.executed.lock().unwrap().is_empty());
+
+        let run = engine.test_fire("u1", &check.id, Some(temperature(20.0)), false).await.unwrap();
+        assert_eq!(run.status, RunStatus::Succeeded);
+        assert!(!thor.executed.lock().unwrap().is_empty());
+        assert_eq!(store.history("u1", &check.id).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn routines_and_runs_survive_a_restart() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("routines.json");
+        let store = Arc::new(RoutineStore::open(&path, 2).unwrap());
+        let engine = RoutineEngine::new(store.clone(), Arc::new(FakePipeline::default()));
+        let mut fan = fan_routine();
+        fan.cooldown_secs = 0;
+        let fan = store.upsert(fan).await.unwrap();
+        for value in [29.0, 30.0, 31.0] {
+            assert_eq!(engine.handle_event(temperature(value)).await.len(), 1);
+        }
+
+        let reopened = RoutineStore::open(&path, 2).unwrap();
+        let summaries = reopened.list("u1").await;
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].routine, fan);
+        let history = reopened.history("u1", &fan.id).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].event.as_ref().unwrap().field("value"), Some(json!(31.0)));
+    }
+}
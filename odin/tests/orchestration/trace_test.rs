@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use odin::grpc::odin::odin_service_client::OdinServiceClient;
+    use odin::grpc::odin::odin_service_server::OdinServiceServer;
+    use odin::grpc::odin::ProcessRequest;
+    use odin::grpc::OdinServiceImpl;
+    use odin::orchestration::{Action, ActionExecutor, ActionOrchestrator, RequestProcessor};
+    use serde_json::{json, Value};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tracing::Instrument;
+    use tracing_subscriber::layer::SubscriberExt;
+    use vegvisir::{AttributeValue, Baggage, FinishedSpan, SpanContext, SpanKind, TraceId};
+
+    /// Thor stand-in: records the trace context its action runs in
+    #[derive(Default)]
+    struct TracedThor {
+        seen: Mutex<Vec<(Option<SpanContext>, Baggage)>>,
+    }
+
+    #[async_trait]
+    impl ActionExecutor for TracedThor {
+        async fn execute(&self, action: &Action) -> Result<Value, String> {
+            let span = tracing::Span::current();
+            self.seen.lock().unwrap().push((vegvisir::span_context(&span), vegvisir::baggage(&span)));
+            Ok(json!({ "done": action.action_id }))
+        }
+    }
+
+    fn text(value: &str) -> AttributeValue {
+        AttributeValue::String(value.to_string())
+    }
+
+    #[tokio::test]
+    async fn request_id_roots_the_trace_across_client_server_and_actions() {
+        let (layer, exporter) = vegvisir::layer("odin");
+        let _subscriber = tracing::subscriber::set_default(tracing_subscriber::registry().with(layer));
+
+        let thor = Arc::new(TracedThor::default());
+        let processor = RequestProcessor::new_with_action_fallback(ActionOrchestrator::new().with_executor(thor.clone()));
+        let service = OdinServiceImpl::new(Arc::new(processor), Arc::new(ActionOrchestrator::new()));
+        let addr = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .layer(vegvisir::ServerTraceLayer)
+                .add_service(OdinServiceServer::new(service))
+                .serve(addr),
+        );
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let channel = tonic::transport::Endpoint::from_shared(format!("http://{}", addr)).unwrap().connect().await.unwrap();
+        let mut client = OdinServiceClient::new(vegvisir::traced(channel));
+
+        let request_id = "6f1c8a52-3b1e-4a7d-9c4e-2f0a1b3c4d5e";
+        let request = ProcessRequest {
+            request_id: request_id.to_string(),
+            user_id: "u1".to_string(),
+            device_id: "d1".to_string(),
+            input: "list running processes".to_string(),
+            input_type: "text".to_string(),
+            session_id: String::new(),
+        };
+        let platform = tracing::info_span!("voice_command");
+        client.process(request).instrument(platform.clone()).await.unwrap();
+        let caller = vegvisir::span_context(&platform).unwrap();
+        drop(platform);
+
+        let mut receiver = exporter.take_receiver().unwrap();
+        let mut spans: Vec<FinishedSpan> = Vec::new();
+        while spans.iter().all(|span| span.kind != SpanKind::Server) {
+            spans.push(tokio::time::timeout(Duration::from_secs(5), receiver.recv()).await.unwrap().unwrap());
+        }
+        while let Ok(span) = receiver.try_recv() {
+            spans.push(span);
+        }
+
+        let root = TraceId::from_request_id(request_id);
+        let server = spans.iter().find(|span| span.name == "odin.OdinService/Process").unwrap();
+        assert_eq!(server.kind, SpanKind::Server);
+        assert_eq!(server.context.trace_id, root);
+        assert_eq!(server.parent_span_id, None);
+        assert_eq!(server.attribute("edda.request_id"), Some(&text(request_id)));
+        assert_eq!(server.attribute("edda.user_id"), Some(&text("u1")));
+        assert_eq!(server.attribute("edda.device_id"), Some(&text("d1")));
+        // The platform's own trace stays linked to Odin's
+        let client_span = spans.iter().find(|span| span.kind == SpanKind::Client).unwrap();
+        assert_eq!(client_span.context.trace_id, caller.trace_id);
+        let caller_traceparent = SpanContext { span_id: client_span.context.span_id, ..caller }.to_traceparent();
+        assert_eq!(server.attribute("edda.caller_traceparent"), Some(&text(&caller_traceparent)));
+
+        // Downstream calls made for the request carry its trace and ids
+        let seen = thor.seen.lock().unwrap();
+        assert!(!seen.is_empty());
+        for (context, baggage) in seen.iter() {
+            assert_eq!(context.unwrap().trace_id, root);
+            assert_eq!(baggage.get("request_id"), Some(request_id));
+            assert_eq!(baggage.get("device_id"), Some("d1"));
+        }
+    }
+}
@@ -108,6 +108,7 @@ impl ProcessClient for MockProcessClient {
         Ok(ProcessResponse {
             response: self.response.clone(),
             actions_taken: vec![],
+            ..Default::default()
         })
     }
 }
@@ -53,6 +53,7 @@ mod tests {
             input: "Test input".to_string(),
             input_type: "text".to_string(),
             reason: "Test reason".to_string(),
+            ..Default::default()
         };
         
         let result = client.take_responsibility(request).await;
@@ -28,11 +28,23 @@ pub enum Commands {
     },
     /// List models from Geri (when configured)
     Models,
-    /// Retrieve RAG context from Freki (when configured)
+    /// Retrieve RAG context from Freki (when configured); the query is embedded via Geri
     Retrieve {
-        /// Query (note: Freki API uses embeddings; query may be used for future text-based API)
+        /// Query text
         query: String,
     },
+    /// Show LLM spending per model and budget status from Geri (when configured)
+    Usage {
+        /// Period: daily, monthly or total
+        #[arg(long, default_value = "monthly")]
+        period: String,
+        /// Only requests of this user
+        #[arg(long)]
+        user: Option<String>,
+        /// Only requests from this device
+        #[arg(long)]
+        device: Option<String>,
+    },
     /// Transcribe audio file via Huginn STT (when configured)
     Transcribe {
         /// Path to audio file (wav, mp3, opus, etc.)
@@ -1,4 +1,4 @@
-//! Geri gRPC client (optional direct LLM prompt / model list).
+//! Geri gRPC client (optional direct LLM prompt / model list / embeddings / usage).
 //! Phase 2: optional Geri-Client.
 
 use tonic::transport::Channel;
@@ -40,6 +40,18 @@ impl GeriClient {
         Ok(response.into_inner())
     }
 
+    /// Streams the completion; yields deltas followed by one final `done` chunk.
+    pub async fn process_prompt_stream(
+        &mut self,
+        request: geri::ProcessPromptRequest,
+    ) -> Result<tonic::Streaming<geri::ProcessPromptStreamChunk>, GeriClientError> {
+        let response = self
+            .client
+            .process_prompt_stream(tonic::Request::new(request))
+            .await?;
+        Ok(response.into_inner())
+    }
+
     pub async fn list_models(
         &mut self,
         request: geri::ListModelsRequest,
@@ -50,6 +62,30 @@ impl GeriClient {
             .await?;
         Ok(response.into_inner())
     }
+
+    /// Embeds the inputs with Geri's embedding model (empty `model_name` = Geri's default).
+    pub async fn embed(
+        &mut self,
+        request: geri::EmbedRequest,
+    ) -> Result<geri::EmbedResponse, GeriClientError> {
+        let response = self
+            .client
+            .embed(tonic::Request::new(request))
+            .await?;
+        Ok(response.into_inner())
+    }
+
+    /// Spending per model and budget status for the current period.
+    pub async fn get_usage(
+        &mut self,
+        request: geri::GetUsageRequest,
+    ) -> Result<geri::GetUsageResponse, GeriClientError> {
+        let response = self
+            .client
+            .get_usage(tonic::Request::new(request))
+            .await?;
+        Ok(response.into_inner())
+    }
 }
 
 pub mod geri {
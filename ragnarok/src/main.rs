@@ -149,9 +149,32 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                 context: String::new(),
                                 model_name: String::new(),
                                 max_tokens: 1024,
+                                system_prompt: String::new(),
                             };
-                            match geri_client.process_prompt(request).await {
-                                Ok(resp) => println!("{}", resp.text),
+                            match geri_client.process_prompt_stream(request).await {
+                                Ok(mut stream) => {
+                                    use std::io::Write;
+                                    use ragnarok::grpc_client::geri::process_prompt_stream_chunk::Chunk;
+                                    loop {
+                                        match stream.message().await {
+                                            Ok(Some(msg)) => match msg.chunk {
+                                                Some(Chunk::Delta(delta)) => {
+                                                    print!("{}", delta.text);
+                                                    let _ = std::io::stdout().flush();
+                                                }
+                                                Some(Chunk::Done(_)) | None => {}
+                                            },
+                                            Ok(None) => {
+                                                println!();
+                                                break;
+                                            }
+                                            Err(e) => {
+                                                eprintln!("\nGeri error: {}", e);
+                                                break;
+                                            }
+                                        }
+                                    }
+                                }
                                 Err(e) => eprintln!("Geri error: {}", e),
                             }
                         }
@@ -256,12 +279,95 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     println!("Muninn not configured; add 'muninn' (port) to config to generate speech.");
                 }
             }
-            ragnarok::cli::Commands::Retrieve { query: _query } => {
-                if let Some(ref freki_cfg) = settings.freki {
+            ragnarok::cli::Commands::Usage { period, user, device } => {
+                if let Some(ref geri_cfg) = settings.geri {
+                    match GeriClient::new(geri_cfg.port).await {
+                        Ok(mut geri_client) => {
+                            let request = ragnarok::grpc_client::geri::GetUsageRequest {
+                                user_id: user.unwrap_or_default(),
+                                device_id: device.unwrap_or_default(),
+                                period,
+                            };
+                            match geri_client.get_usage(request).await {
+                                Ok(resp) => {
+                                    let date = |unix: i64| {
+                                        chrono::DateTime::from_timestamp(unix, 0)
+                                            .map(|at| at.format("%Y-%m-%d").to_string())
+                                            .unwrap_or_default()
+                                    };
+                                    let since = if resp.period_start_unix > 0 {
+                                        format!(" since {}", date(resp.period_start_unix))
+                                    } else {
+                                        String::new()
+                                    };
+                                    println!(
+                                        "Usage ({}{}): {} requests, {} input / {} output tokens, ${:.4}",
+                                        resp.period, since, resp.request_count, resp.input_tokens, resp.output_tokens, resp.total_cost
+                                    );
+                                    for m in &resp.models {
+                                        println!(
+                                            "  {} ({}): {} requests, {} / {} tokens, ${:.4}",
+                                            m.model, m.provider, m.request_count, m.input_tokens, m.output_tokens, m.cost
+                                        );
+                                    }
+                                    for b in &resp.budgets {
+                                        let scope = match (b.user_id.as_str(), b.device_id.as_str()) {
+                                            ("", "") => "global".to_string(),
+                                            (user, "") => format!("user {}", user),
+                                            ("", device) => format!("device {}", device),
+                                            (user, device) => format!("user {} on {}", user, device),
+                                        };
+                                        let resets = if b.resets_at_unix > 0 {
+                                            format!(", resets {}", date(b.resets_at_unix))
+                                        } else {
+                                            String::new()
+                                        };
+                                        println!(
+                                            "Budget {} ({}): ${:.2} of ${:.2}{}{}",
+                                            b.period, scope, b.used, b.limit,
+                                            if b.over_limit { " - exceeded, local models only" } else { "" },
+                                            resets
+                                        );
+                                    }
+                                }
+                                Err(e) => eprintln!("Geri error: {}", e),
+                            }
+                        }
+                        Err(e) => eprintln!("Geri not reachable (port {}): {}", geri_cfg.port, e),
+                    }
+                } else {
+                    println!("Geri not configured; add 'geri' to config to show usage.");
+                }
+            }
+            ragnarok::cli::Commands::Retrieve { query } => {
+                if let (Some(ref freki_cfg), Some(ref geri_cfg)) = (&settings.freki, &settings.geri) {
+                    // Freki searches by vector; Geri embeds the query with the same model used for indexing
+                    let query_embedding = match GeriClient::new(geri_cfg.port).await {
+                        Ok(mut geri_client) => {
+                            let request = ragnarok::grpc_client::geri::EmbedRequest {
+                                inputs: vec![query],
+                                model_name: String::new(),
+                            };
+                            match geri_client.embed(request).await {
+                                Ok(resp) => resp.embeddings.into_iter().next().map(|e| e.values),
+                                Err(e) => {
+                                    eprintln!("Geri error: {}", e);
+                                    None
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            eprintln!("Geri not reachable (port {}): {}", geri_cfg.port, e);
+                            None
+                        }
+                    };
+                    let Some(query_embedding) = query_embedding else {
+                        return Ok(());
+                    };
                     match FrekiClient::new(freki_cfg.port).await {
                         Ok(mut freki_client) => {
                             let request = ragnarok::grpc_client::freki::RetrieveContextRequest {
-                                query_embedding: vec![],
+                                query_embedding: bincode::serialize(&query_embedding)?,
                                 limit: 5,
                                 collection_name: String::new(),
                             };
@@ -272,7 +378,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                         println!("{}", doc.content);
                                     }
                                     if resp.documents.is_empty() {
-                                        println!("No documents returned (Freki may require query embeddings).");
+                                        println!("No documents returned.");
                                     }
                                 }
                                 Err(e) => eprintln!("Freki error: {}", e),
@@ -281,7 +387,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         Err(e) => eprintln!("Freki not reachable (port {}): {}", freki_cfg.port, e),
                     }
                 } else {
-                    println!("Freki not configured; add 'freki' to config to retrieve RAG context.");
+                    println!("Freki and Geri must be configured; add 'freki' and 'geri' to config to retrieve RAG context.");
                 }
             }
             ragnarok::cli::Commands::Status => {
@@ -72,6 +72,28 @@ fn test_parse_retrieve_command() {
     }
 }
 
+#[test]
+fn test_parse_usage_command() {
+    let cli = parse(&["usage"]);
+    match &cli.command {
+        Some(Commands::Usage { period, user, device }) => {
+            assert_eq!(period, "monthly");
+            assert!(user.is_none());
+            assert!(device.is_none());
+        }
+        _ => panic!("expected Usage command"),
+    }
+
+    let cli = parse(&["usage", "--period", "daily", "--user", "alice"]);
+    match &cli.command {
+        Some(Commands::Usage { period, user, .. }) => {
+            assert_eq!(period, "daily");
+            assert_eq!(user.as_deref(), Some("alice"));
+        }
+        _ => panic!("expected Usage command"),
+    }
+}
+
 #[test]
 fn test_parse_no_subcommand() {
     let cli = parse(&[]);
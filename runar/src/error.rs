@@ -0,0 +1,10 @@
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum TokenizerError {
+    #[error("Failed to load tokenizer from {path}: {message}")]
+    LoadError { path: PathBuf, message: String },
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
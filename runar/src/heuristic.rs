@@ -0,0 +1,19 @@
+use crate::Tokenizer;
+
+/// Heuristischer Fallback ohne Vokabular (~4 Zeichen pro Token für Englisch).
+#[derive(Debug, Clone, Default)]
+pub struct HeuristicTokenizer;
+
+impl Tokenizer for HeuristicTokenizer {
+    fn name(&self) -> &str {
+        "heuristic"
+    }
+
+    fn count(&self, text: &str) -> usize {
+        let t = text.trim();
+        if t.is_empty() {
+            return 0;
+        }
+        t.chars().count().div_ceil(4)
+    }
+}
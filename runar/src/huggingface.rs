@@ -0,0 +1,58 @@
+use std::path::Path;
+
+use tracing::warn;
+
+use crate::{HeuristicTokenizer, Tokenizer, TokenizerError};
+
+/// Tokenizer aus einer Hugging-Face-`tokenizer.json` (BPE, SentencePiece/Unigram, WordPiece).
+pub struct HuggingFaceTokenizer {
+    name: String,
+    inner: tokenizers::Tokenizer,
+}
+
+impl HuggingFaceTokenizer {
+    /// Lädt den Tokenizer aus der angegebenen `tokenizer.json`.
+    pub fn from_file(name: impl Into<String>, path: impl AsRef<Path>) -> Result<Self, TokenizerError> {
+        let path = path.as_ref();
+        let inner = tokenizers::Tokenizer::from_file(path).map_err(|e| TokenizerError::LoadError {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })?;
+        Ok(Self { name: name.into(), inner })
+    }
+
+    /// Lädt den Tokenizer aus dem JSON-Inhalt einer `tokenizer.json`.
+    pub fn from_bytes(name: impl Into<String>, bytes: impl AsRef<[u8]>) -> Result<Self, TokenizerError> {
+        let name = name.into();
+        let inner = tokenizers::Tokenizer::from_bytes(bytes).map_err(|e| TokenizerError::LoadError {
+            path: name.clone().into(),
+            message: e.to_string(),
+        })?;
+        Ok(Self { name, inner })
+    }
+}
+
+impl std::fmt::Debug for HuggingFaceTokenizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HuggingFaceTokenizer").field("name", &self.name).finish()
+    }
+}
+
+impl Tokenizer for HuggingFaceTokenizer {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn count(&self, text: &str) -> usize {
+        if text.trim().is_empty() {
+            return 0;
+        }
+        match self.inner.encode(text, false) {
+            Ok(encoding) => encoding.len(),
+            Err(e) => {
+                warn!("Tokenizer {} failed, using heuristic: {}", self.name, e);
+                HeuristicTokenizer.count(text)
+            }
+        }
+    }
+}
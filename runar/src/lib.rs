@@ -0,0 +1,25 @@
+//! Runar: Tokenizer-Subsystem für Edda.
+//!
+//! Lädt BPE-/SentencePiece-Vokabulare aus lokalen `tokenizer.json`-Dateien (z. B. neben
+//! GGUF-Modellen) und wählt pro Model den passenden Tokenizer. Ohne Vokabular greift die
+//! Heuristik (~4 Zeichen pro Token). Wird von Geri (Context-Window, Kosten) und Freki
+//! (Chunking) gemeinsam genutzt.
+
+mod error;
+mod heuristic;
+mod huggingface;
+mod registry;
+
+pub use error::TokenizerError;
+pub use heuristic::HeuristicTokenizer;
+pub use huggingface::HuggingFaceTokenizer;
+pub use registry::TokenizerRegistry;
+
+/// Zählt Tokens für Text.
+pub trait Tokenizer: Send + Sync + std::fmt::Debug {
+    /// Name des Tokenizers (z. B. Model-Name oder "heuristic").
+    fn name(&self) -> &str;
+
+    /// Token-Anzahl für den Text; leerer Text liefert 0.
+    fn count(&self, text: &str) -> usize;
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use tracing::{info, warn};
+
+use crate::{HeuristicTokenizer, HuggingFaceTokenizer, Tokenizer, TokenizerError};
+
+const TOKENIZER_FILE: &str = "tokenizer.json";
+const TOKENIZER_SUFFIX: &str = ".tokenizer.json";
+
+/// Wählt pro Model den passenden Tokenizer; unbekannte Modelle nutzen den Fallback.
+#[derive(Debug)]
+pub struct TokenizerRegistry {
+    tokenizers: HashMap<String, Arc<dyn Tokenizer>>,
+    fallback: Arc<dyn Tokenizer>,
+}
+
+impl Default for TokenizerRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TokenizerRegistry {
+    /// Leere Registry mit heuristischem Fallback.
+    pub fn new() -> Self {
+        Self {
+            tokenizers: HashMap::new(),
+            fallback: Arc::new(HeuristicTokenizer),
+        }
+    }
+
+    /// Registriert einen Tokenizer unter dem Model-Namen (case-insensitive).
+    pub fn register(&mut self, model: &str, tokenizer: Arc<dyn Tokenizer>) {
+        self.tokenizers.insert(model.to_lowercase(), tokenizer);
+    }
+
+    /// Lädt eine `tokenizer.json` und registriert sie unter dem Model-Namen.
+    pub fn load_file(&mut self, model: &str, path: impl AsRef<Path>) -> Result<(), TokenizerError> {
+        let tokenizer = HuggingFaceTokenizer::from_file(model, path)?;
+        self.register(model, Arc::new(tokenizer));
+        Ok(())
+    }
+
+    /// Durchsucht ein Models-Verzeichnis (z. B. das des `ModelDownloader`) nach Vokabularen:
+    /// - `<model>.tokenizer.json` wird unter `<model>` registriert,
+    /// - `tokenizer.json` unter dem Verzeichnisnamen und jedem GGUF-Dateinamen daneben.
+    ///
+    /// Unterverzeichnisse werden eine Ebene tief berücksichtigt. Nicht ladbare Dateien werden
+    /// übersprungen. Liefert die Anzahl registrierter Model-Namen.
+    pub fn load_dir(&mut self, dir: impl AsRef<Path>) -> Result<usize, TokenizerError> {
+        let dir = dir.as_ref();
+        let mut loaded = self.load_single_dir(dir)?;
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                loaded += self.load_single_dir(&path)?;
+            }
+        }
+        info!("Loaded {} tokenizer(s) from {:?}", loaded, dir);
+        Ok(loaded)
+    }
+
+    fn load_single_dir(&mut self, dir: &Path) -> Result<usize, TokenizerError> {
+        let mut loaded = 0;
+        let mut gguf_models = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if let Some(model) = file_name.strip_suffix(TOKENIZER_SUFFIX) {
+                loaded += self.try_load(&[model.to_string()], &path);
+            } else if file_name.to_lowercase().ends_with(".gguf") {
+                gguf_models.push(file_name[..file_name.len() - ".gguf".len()].to_string());
+            }
+        }
+
+        let shared = dir.join(TOKENIZER_FILE);
+        if shared.is_file() {
+            if let Some(dir_name) = dir.file_name().and_then(|n| n.to_str()) {
+                gguf_models.push(dir_name.to_string());
+            }
+            loaded += self.try_load(&gguf_models, &shared);
+        }
+        Ok(loaded)
+    }
+
+    fn try_load(&mut self, models: &[String], path: &Path) -> usize {
+        let Some(first) = models.first() else {
+            return 0;
+        };
+        match HuggingFaceTokenizer::from_file(first.as_str(), path) {
+            Ok(tokenizer) => {
+                let tokenizer: Arc<dyn Tokenizer> = Arc::new(tokenizer);
+                for model in models {
+                    self.register(model, tokenizer.clone());
+                }
+                models.len()
+            }
+            Err(e) => {
+                warn!("Skipping tokenizer {:?}: {}", path, e);
+                0
+            }
+        }
+    }
+
+    /// Tokenizer für das Model: exakter Treffer, sonst der längste registrierte Name,
+    /// der im Model-Namen enthalten ist (z. B. `llama-3-8b` für `llama-3-8b.Q4_K_M`).
+    pub fn get(&self, model: &str) -> Option<Arc<dyn Tokenizer>> {
+        let model = model.to_lowercase();
+        if let Some(tokenizer) = self.tokenizers.get(&model) {
+            return Some(tokenizer.clone());
+        }
+        self.tokenizers
+            .iter()
+            .filter(|(name, _)| model.contains(name.as_str()))
+            .max_by_key(|(name, _)| name.len())
+            .map(|(_, tokenizer)| tokenizer.clone())
+    }
+
+    /// Tokenizer für das Model oder der Fallback.
+    pub fn for_model(&self, model: &str) -> Arc<dyn Tokenizer> {
+        self.get(model).unwrap_or_else(|| self.fallback.clone())
+    }
+
+    /// Zählt Tokens mit dem Tokenizer des Models.
+    pub fn count(&self, text: &str, model: &str) -> usize {
+        self.for_model(model).count(text)
+    }
+
+    /// Fallback-Tokenizer für Modelle ohne Vokabular.
+    pub fn fallback(&self) -> Arc<dyn Tokenizer> {
+        self.fallback.clone()
+    }
+
+    /// Anzahl registrierter Model-Namen.
+    pub fn len(&self) -> usize {
+        self.tokenizers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tokenizers.is_empty()
+    }
+}
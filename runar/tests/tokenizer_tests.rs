@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use runar::{HeuristicTokenizer, HuggingFaceTokenizer, Tokenizer, TokenizerRegistry};
+
+/// Minimales BPE-Vokabular: "hello" ist ein Token, die Heuristik schätzt 2.
+const BPE_TOKENIZER: &str = r#"{
+  "version": "1.0",
+  "truncation": null,
+  "padding": null,
+  "added_tokens": [],
+  "normalizer": null,
+  "pre_tokenizer": { "type": "Whitespace" },
+  "post_processor": null,
+  "decoder": null,
+  "model": {
+    "type": "BPE",
+    "dropout": null,
+    "unk_token": "[UNK]",
+    "continuing_subword_prefix": null,
+    "end_of_word_suffix": null,
+    "fuse_unk": false,
+    "byte_fallback": false,
+    "vocab": { "[UNK]": 0, "h": 1, "e": 2, "l": 3, "o": 4, "he": 5, "ll": 6, "hell": 7, "hello": 8 },
+    "merges": ["h e", "l l", "he ll", "hell o"]
+  }
+}"#;
+
+#[test]
+fn test_heuristic_tokenizer() {
+    assert_eq!(HeuristicTokenizer.count(""), 0);
+    assert_eq!(HeuristicTokenizer.count("   "), 0);
+    assert_eq!(HeuristicTokenizer.count("abcd"), 1);
+    assert_eq!(HeuristicTokenizer.count("abcde"), 2);
+}
+
+#[test]
+fn test_bpe_tokenizer_from_bytes() {
+    let tokenizer = HuggingFaceTokenizer::from_bytes("test-bpe", BPE_TOKENIZER).unwrap();
+    assert_eq!(tokenizer.name(), "test-bpe");
+    assert_eq!(tokenizer.count("hello hello hello"), 3);
+    assert_eq!(tokenizer.count("hel"), 2);
+    assert_eq!(tokenizer.count(""), 0);
+    assert!(HuggingFaceTokenizer::from_bytes("broken", "{}").is_err());
+}
+
+#[test]
+fn test_registry_selects_tokenizer_per_model() {
+    let mut registry = TokenizerRegistry::new();
+    let tokenizer = HuggingFaceTokenizer::from_bytes("llama-3-8b", BPE_TOKENIZER).unwrap();
+    registry.register("Llama-3-8B", Arc::new(tokenizer));
+
+    assert_eq!(registry.count("hello hello hello", "llama-3-8b"), 3);
+    assert_eq!(registry.count("hello hello hello", "llama-3-8b-instruct.Q4_K_M"), 3);
+    // Unbekanntes Model: Heuristik
+    assert_eq!(registry.count("hello hello hello", "gpt-4"), 5);
+    assert!(registry.get("gpt-4").is_none());
+    assert_eq!(registry.for_model("gpt-4").name(), "heuristic");
+}
+
+#[test]
+fn test_registry_load_dir_next_to_gguf() {
+    let dir = tempfile::tempdir().unwrap();
+    // tokenizer.json neben einem GGUF in einem Unterverzeichnis
+    let model_dir = dir.path().join("mistral");
+    std::fs::create_dir(&model_dir).unwrap();
+    std::fs::write(model_dir.join("mistral-7b-instruct.Q4_K_M.gguf"), b"").unwrap();
+    std::fs::write(model_dir.join("tokenizer.json"), BPE_TOKENIZER).unwrap();
+    // <model>.tokenizer.json im Wurzelverzeichnis, plus eine defekte Datei
+    std::fs::write(dir.path().join("phi-3.tokenizer.json"), BPE_TOKENIZER).unwrap();
+    std::fs::write(dir.path().join("broken.tokenizer.json"), "not json").unwrap();
+
+    let mut registry = TokenizerRegistry::new();
+    let loaded = registry.load_dir(dir.path()).unwrap();
+    assert_eq!(loaded, 3);
+    assert!(registry.get("mistral-7b-instruct.Q4_K_M").is_some());
+    assert!(registry.get("mistral").is_some());
+    assert!(registry.get("phi-3-mini").is_some());
+    assert!(registry.get("broken").is_none());
+}
@@ -1,13 +1,57 @@
-use crate::actions::{ActionContext, ActionError, ActionRegistry};
+use crate::actions::{classify_risk, ActionContext, ActionError, ActionRegistry, RiskLevel};
 use crate::audit::AuditLogger;
 use crate::permissions::PermissionChecker;
-use std::sync::Arc;
+use gjallarhorn::ConfirmationSigner;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Metadata key: `"true"` runs the action as a dry run.
+pub const DRY_RUN_METADATA_KEY: &str = "dry_run";
+/// Metadata key: token of the user confirmation Odin obtained and signed for the action.
+pub const CONFIRMATION_METADATA_KEY: &str = "confirmation_id";
+
+/// How a single action is dispatched; set by the caller via the action metadata.
+#[derive(Debug, Clone, Default)]
+pub struct DispatchOptions {
+    /// Only report what would be done (and its risk); nothing is executed.
+    pub dry_run: bool,
+    /// Confirmation of the user for a risky action: the token Odin signed for it.
+    pub confirmation_id: Option<String>,
+    /// Action type and data the confirmation was signed for, if not the dispatched action itself
+    /// (the XML task a call comes from).
+    pub confirmed_action: Option<(String, Vec<u8>)>,
+    /// Identifies the action Odin sent; the actions it expands to share it and may use its
+    /// confirmation, any other dispatch may not. Empty: this dispatch alone.
+    pub dispatch_id: String,
+}
+
+impl DispatchOptions {
+    pub fn from_metadata(metadata: &HashMap<String, String>) -> Self {
+        Self {
+            dry_run: metadata.get(DRY_RUN_METADATA_KEY).is_some_and(|v| v == "true"),
+            confirmation_id: metadata.get(CONFIRMATION_METADATA_KEY).filter(|id| !id.is_empty()).cloned(),
+            confirmed_action: None,
+            dispatch_id: String::new(),
+        }
+    }
+
+    /// Check the confirmation against the action Odin sent, also for the actions it expands to.
+    pub fn for_action(mut self, action_type: &str, action_data: &[u8]) -> Self {
+        self.confirmed_action = Some((action_type.to_string(), action_data.to_vec()));
+        self.dispatch_id = uuid::Uuid::new_v4().to_string();
+        self
+    }
+}
 
 pub struct ActionDispatcher {
     registry: Arc<ActionRegistry>,
     permission_checker: Arc<PermissionChecker>,
     audit_logger: Option<Arc<dyn AuditLogger>>,
     strict_sandboxing: bool,
+    confirmation_level: Option<RiskLevel>,
+    confirmation_signer: Option<ConfirmationSigner>,
+    /// Redeemed confirmation tokens until they expire: token -> (expires at ms, dispatch id).
+    used_confirmations: Mutex<HashMap<String, (i64, String)>>,
 }
 
 impl ActionDispatcher {
@@ -21,6 +65,9 @@ impl ActionDispatcher {
             permission_checker,
             audit_logger: None,
             strict_sandboxing,
+            confirmation_level: None,
+            confirmation_signer: None,
+            used_confirmations: Mutex::new(HashMap::new()),
         }
     }
 
@@ -35,14 +82,40 @@ impl ActionDispatcher {
             permission_checker,
             audit_logger: Some(audit_logger),
             strict_sandboxing,
+            confirmation_level: None,
+            confirmation_signer: None,
+            used_confirmations: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Refuse actions of at least `level` risk (see [`classify_risk`]) that were not confirmed by the user.
+    pub fn with_confirmation_level(mut self, level: RiskLevel) -> Self {
+        self.confirmation_level = Some(level);
+        self
+    }
+
+    /// Secret shared with Odin to verify confirmation tokens; without it no confirmation is accepted.
+    pub fn with_confirmation_secret(mut self, secret: &str) -> Self {
+        self.confirmation_signer = Some(secret).filter(|s| !s.is_empty()).map(ConfirmationSigner::new);
+        self
+    }
+
     pub async fn dispatch(
         &self,
         action_type: &str,
         context: &ActionContext,
         action_data: &[u8],
+    ) -> Result<Vec<u8>, ActionError> {
+        self.dispatch_with_options(action_type, context, action_data, &DispatchOptions::default())
+            .await
+    }
+
+    pub async fn dispatch_with_options(
+        &self,
+        action_type: &str,
+        context: &ActionContext,
+        action_data: &[u8],
+        options: &DispatchOptions,
     ) -> Result<Vec<u8>, ActionError> {
         if let Some(logger) = &self.audit_logger {
             logger.log_dispatch(context, action_type).await;
@@ -81,6 +154,42 @@ impl ActionDispatcher {
             return Err(ActionError::PermissionDenied(err_msg));
         }
 
+        let risk = classify_risk(action_type, action_data);
+        if options.dry_run {
+            let would_execute = executor.dry_run(context, action_data).await?;
+            let would_execute: serde_json::Value = serde_json::from_slice(&would_execute)
+                .unwrap_or_else(|_| serde_json::Value::String(String::from_utf8_lossy(&would_execute).to_string()));
+            let preview = serde_json::json!({
+                "dry_run": true,
+                "action_type": action_type,
+                "risk": risk,
+                "would_execute": would_execute,
+            });
+            return Ok(serde_json::to_vec(&preview).unwrap());
+        }
+
+        if self.confirmation_level.is_some_and(|level| risk.level >= level) {
+            let confirmation_id = match self.verify_confirmation(action_type, action_data, options) {
+                Ok(confirmation_id) => confirmation_id,
+                Err(reason) => {
+                    let err_msg = format!(
+                        "{} is {} risk ({}) and needs a user confirmation: {}",
+                        action_type,
+                        risk.level.as_str(),
+                        risk.reasons.join("; "),
+                        reason
+                    );
+                    if let Some(logger) = &self.audit_logger {
+                        logger.log_result(context, action_type, false, Some(&err_msg)).await;
+                    }
+                    return Err(ActionError::ConfirmationRequired(err_msg));
+                }
+            };
+            if let Some(logger) = &self.audit_logger {
+                logger.log_confirmation(context, action_type, &risk, &confirmation_id).await;
+            }
+        }
+
         let result = executor.execute(context, action_data).await;
         if let Some(logger) = &self.audit_logger {
             let err_msg = result.as_ref().err().map(|e| e.to_string());
@@ -92,4 +201,28 @@ impl ActionDispatcher {
         }
         result
     }
+
+    /// The confirmation id, if the token was signed by Odin for this action, has not expired and
+    /// was not redeemed by another dispatch; redeems it.
+    fn verify_confirmation(&self, action_type: &str, action_data: &[u8], options: &DispatchOptions) -> Result<String, String> {
+        let token = options.confirmation_id.as_deref().ok_or("none given")?;
+        let signer = self.confirmation_signer.as_ref().ok_or("no confirmation secret configured")?;
+        let (signed_type, signed_data) = match &options.confirmed_action {
+            Some((signed_type, signed_data)) => (signed_type.as_str(), signed_data.as_slice()),
+            None => (action_type, action_data),
+        };
+        let now_ms = chrono::Utc::now().timestamp_millis();
+        let confirmation_id = signer.verify(token, signed_type, signed_data, now_ms).map_err(|e| e.to_string())?;
+
+        let mut used = self.used_confirmations.lock().unwrap();
+        used.retain(|_, (expires_at_ms, _)| *expires_at_ms > now_ms);
+        if let Some((_, dispatch_id)) = used.get(token) {
+            if dispatch_id.is_empty() || *dispatch_id != options.dispatch_id {
+                return Err("confirmation was already used".to_string());
+            }
+        }
+        let expires_at_ms = gjallarhorn::token_expiry(token).unwrap_or(now_ms);
+        used.insert(token.to_string(), (expires_at_ms, options.dispatch_id.clone()));
+        Ok(confirmation_id)
+    }
 }
@@ -19,6 +19,8 @@ pub enum ActionError {
     InvalidAction(String),
     #[error("Action timeout")]
     Timeout,
+    #[error("Confirmation required: {0}")]
+    ConfirmationRequired(String),
 }
 
 #[async_trait]
@@ -35,4 +37,13 @@ pub trait ActionExecutor: Send + Sync {
         context: &ActionContext,
         action_data: &[u8],
     ) -> Result<Vec<u8>, ActionError>;
+
+    /// Describes what `execute` would do without doing it; defaults to the action parameters.
+    async fn dry_run(
+        &self,
+        _context: &ActionContext,
+        action_data: &[u8],
+    ) -> Result<Vec<u8>, ActionError> {
+        Ok(action_data.to_vec())
+    }
 }
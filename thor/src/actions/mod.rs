@@ -2,8 +2,10 @@ pub mod executor;
 pub mod registry;
 pub mod dispatcher;
 pub mod xml_dispatcher;
+pub mod risk;
 
 pub use executor::*;
 pub use registry::*;
 pub use dispatcher::*;
 pub use xml_dispatcher::*;
+pub use risk::*;
@@ -0,0 +1,35 @@
+//! Risk classification of actions: which actions must be confirmed by the user before they run.
+//!
+//! The rules are shared with Odin (see Gjallarhorn); Thor adds what only it can see, such as
+//! whether a write would overwrite an existing file.
+
+use serde_json::Value;
+
+pub use gjallarhorn::{is_dangerous_command, RiskAssessment, RiskLevel};
+
+/// Classifies an action by its type and JSON parameters: destructive file operations, shell
+/// commands, actions on other devices and purchases are risky.
+pub fn classify_risk(action_type: &str, action_data: &[u8]) -> RiskAssessment {
+    let params: Value = serde_json::from_slice(action_data).unwrap_or(Value::Null);
+    let mut risk = gjallarhorn::classify_action("thor", action_type, &params);
+    if action_type == "FILE_OPERATION" {
+        classify_overwrite(&params, &mut risk);
+    }
+    risk
+}
+
+/// Writes and copies onto files that exist on this device replace their content.
+fn classify_overwrite(params: &Value, risk: &mut RiskAssessment) {
+    let target = match str_field(params, "operation") {
+        "Write" => str_field(params, "path"),
+        "Copy" => str_field(params, "to"),
+        _ => return,
+    };
+    if !target.is_empty() && std::path::Path::new(target).exists() {
+        risk.raise(RiskLevel::High, format!("overwrites {}", target));
+    }
+}
+
+fn str_field<'a>(params: &'a Value, key: &str) -> &'a str {
+    params.get(key).and_then(Value::as_str).unwrap_or_default()
+}
@@ -1,6 +1,6 @@
 use quick_xml::events::Event;
 use quick_xml::reader::Reader;
-use crate::actions::{ActionContext, ActionError, ActionDispatcher};
+use crate::actions::{ActionContext, ActionError, ActionDispatcher, DispatchOptions};
 use std::collections::HashMap;
 use serde_json::Value;
 
@@ -16,6 +16,11 @@ impl XmlDispatcher {
 
     /// Execute an action from an XML protocol blob (supports <task> and <call>)
     pub async fn execute_xml(&self, context: &ActionContext, xml_data: &str) -> Result<Vec<u8>, ActionError> {
+        self.execute_xml_with_options(context, xml_data, &DispatchOptions::default()).await
+    }
+
+    /// Like [`execute_xml`](Self::execute_xml); the options (dry run, confirmation) apply to every resulting action
+    pub async fn execute_xml_with_options(&self, context: &ActionContext, xml_data: &str, options: &DispatchOptions) -> Result<Vec<u8>, ActionError> {
         let mut reader = Reader::from_str(xml_data);
         reader.trim_text(true);
 
@@ -127,11 +132,11 @@ impl XmlDispatcher {
             if task_type.is_none() && is_task {
                  // Fallback to instruction if we found content
             }
-            self.handle_task(context, task_type, task_args).await
+            self.handle_task(context, task_type, task_args, options).await
         } else if let Some(a_type) = action_type {
             let payload = serde_json::to_vec(&call_args)
                 .map_err(|e| ActionError::InvalidAction(format!("Failed to serialize call arguments: {}", e)))?;
-            self.dispatcher.dispatch(&a_type, context, &payload).await
+            self.dispatcher.dispatch_with_options(&a_type, context, &payload, options).await
         } else {
             return Err(ActionError::InvalidAction("No <task> or <call> found in XML".to_string()));
         };
@@ -156,7 +161,7 @@ impl XmlDispatcher {
     }
 
     /// Translates a high-level <task> into one or more low-level <call> operations
-    async fn handle_task(&self, context: &ActionContext, task_type: Option<String>, args: HashMap<String, String>) -> Result<Vec<u8>, ActionError> {
+    async fn handle_task(&self, context: &ActionContext, task_type: Option<String>, args: HashMap<String, String>, options: &DispatchOptions) -> Result<Vec<u8>, ActionError> {
         let t_type = task_type.ok_or_else(|| ActionError::InvalidAction("Task missing type descriptor".to_string()))?;
 
         match t_type.as_str() {
@@ -176,7 +181,7 @@ impl XmlDispatcher {
                             "workdir": location
                         });
                         let data = serde_json::to_vec(&payload).unwrap();
-                        self.dispatcher.dispatch("SANDBOX_COMMAND", context, &data).await
+                        self.dispatcher.dispatch_with_options("SANDBOX_COMMAND", context, &data, options).await
                     }
                     "process" => {
                         let payload = serde_json::json!({
@@ -184,7 +189,7 @@ impl XmlDispatcher {
                             "args": ["aux"]
                         });
                         let data = serde_json::to_vec(&payload).unwrap();
-                        self.dispatcher.dispatch("SANDBOX_COMMAND", context, &data).await
+                        self.dispatcher.dispatch_with_options("SANDBOX_COMMAND", context, &data, options).await
                     }
                     "network" => {
                         let payload = serde_json::json!({
@@ -192,7 +197,7 @@ impl XmlDispatcher {
                             "args": ["addr"]
                         });
                         let data = serde_json::to_vec(&payload).unwrap();
-                        self.dispatcher.dispatch("SANDBOX_COMMAND", context, &data).await
+                        self.dispatcher.dispatch_with_options("SANDBOX_COMMAND", context, &data, options).await
                     }
                     "logs" => {
                         let payload = serde_json::json!({
@@ -201,7 +206,7 @@ impl XmlDispatcher {
                             "workdir": location
                         });
                         let data = serde_json::to_vec(&payload).unwrap();
-                        self.dispatcher.dispatch("SANDBOX_COMMAND", context, &data).await
+                        self.dispatcher.dispatch_with_options("SANDBOX_COMMAND", context, &data, options).await
                     }
                     _ => Err(ActionError::InvalidAction(format!("Unsupported collection type: {}", c_type)))
                 }
@@ -217,7 +222,7 @@ impl XmlDispatcher {
                             "args": ["-b", "-n", "1"]
                         });
                         let data = serde_json::to_vec(&payload).unwrap();
-                        self.dispatcher.dispatch("SANDBOX_COMMAND", context, &data).await
+                        self.dispatcher.dispatch_with_options("SANDBOX_COMMAND", context, &data, options).await
                     }
                     "security" => {
                         let payload = serde_json::json!({
@@ -225,7 +230,7 @@ impl XmlDispatcher {
                             "args": ["-tuln"]
                         });
                         let data = serde_json::to_vec(&payload).unwrap();
-                        self.dispatcher.dispatch("SANDBOX_COMMAND", context, &data).await
+                        self.dispatcher.dispatch_with_options("SANDBOX_COMMAND", context, &data, options).await
                     }
                     "hardware" => {
                         let payload = serde_json::json!({
@@ -233,7 +238,7 @@ impl XmlDispatcher {
                             "args": []
                         });
                         let data = serde_json::to_vec(&payload).unwrap();
-                        self.dispatcher.dispatch("SANDBOX_COMMAND", context, &data).await
+                        self.dispatcher.dispatch_with_options("SANDBOX_COMMAND", context, &data, options).await
                     }
                     _ => Err(ActionError::InvalidAction(format!("Unsupported analysis type: {}", a_type)))
                 }
@@ -250,7 +255,7 @@ impl XmlDispatcher {
                         "args": []
                     });
                     let data = serde_json::to_vec(&payload).unwrap();
-                    self.dispatcher.dispatch("SANDBOX_COMMAND", context, &data).await
+                    self.dispatcher.dispatch_with_options("SANDBOX_COMMAND", context, &data, options).await
                 } else {
                     // Fallback or generic handling
                     Ok(format!("Instruction acknowledged (Sandboxed): {}", instruction).into_bytes())
@@ -1,7 +1,7 @@
 //! Audit logging for action execution (Phase 10).
 //! Trait allows swapping in different backends (tracing, external service).
 
-use crate::actions::ActionContext;
+use crate::actions::{ActionContext, RiskAssessment};
 use async_trait::async_trait;
 use std::sync::Arc;
 
@@ -17,6 +17,15 @@ pub trait AuditLogger: Send + Sync {
         success: bool,
         error_message: Option<&str>,
     );
+    /// Log that a risky action runs with the user's confirmation.
+    async fn log_confirmation(
+        &self,
+        _context: &ActionContext,
+        _action_type: &str,
+        _risk: &RiskAssessment,
+        _confirmation_id: &str,
+    ) {
+    }
 }
 
 /// Audit logger that writes to tracing (info level).
@@ -68,4 +77,22 @@ impl AuditLogger for TracingAuditLogger {
             );
         }
     }
+
+    async fn log_confirmation(
+        &self,
+        context: &ActionContext,
+        action_type: &str,
+        risk: &RiskAssessment,
+        confirmation_id: &str,
+    ) {
+        tracing::info!(
+            device_id = %context.device_id,
+            user_id = %context.user_id,
+            action_id = %context.action_id,
+            action_type = %action_type,
+            risk = %risk.level.as_str(),
+            confirmation_id = %confirmation_id,
+            "action_confirmed"
+        );
+    }
 }
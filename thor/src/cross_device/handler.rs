@@ -1,7 +1,7 @@
 use crate::actions::{ActionExecutor, ActionContext};
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tonic::transport::Channel;
+use vegvisir::TracedChannel;
 use anyhow::Result;
 use std::time::Duration;
 
@@ -16,7 +16,7 @@ use cross_device::{CrossDeviceActionRequest, CrossDeviceActionResult};
 /// Receives actions via gRPC from other devices, executes them, and sends results back
 pub struct CrossDeviceActionHandler {
     // gRPC client for sending results back to source device
-    clients: Arc<RwLock<std::collections::HashMap<String, CrossDeviceServiceClient<Channel>>>>,
+    clients: Arc<RwLock<std::collections::HashMap<String, CrossDeviceServiceClient<TracedChannel>>>>,
     local_executor: Arc<dyn ActionExecutor>,
 }
 
@@ -76,7 +76,7 @@ impl CrossDeviceActionHandler {
                 .connect_timeout(Duration::from_secs(5));
             
             let channel = endpoint.connect().await?;
-            let new_client = CrossDeviceServiceClient::new(channel);
+            let new_client = CrossDeviceServiceClient::new(vegvisir::traced(channel));
             
             // Store client
             let mut clients = self.clients.write().await;
@@ -97,7 +97,7 @@ impl CrossDeviceActionHandler {
             .connect_timeout(Duration::from_secs(5));
         
         let channel = endpoint.connect().await?;
-        let client = CrossDeviceServiceClient::new(channel);
+        let client = CrossDeviceServiceClient::new(vegvisir::traced(channel));
         
         let mut clients = self.clients.write().await;
         clients.insert(device_url, client);
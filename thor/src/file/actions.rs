@@ -79,4 +79,39 @@ impl ActionExecutor for FileActionExecutor {
             }
         }
     }
+
+    async fn dry_run(
+        &self,
+        _context: &ActionContext,
+        action_data: &[u8],
+    ) -> Result<Vec<u8>, ActionError> {
+        let operation: FileOperation = serde_json::from_slice(action_data)
+            .map_err(|e| ActionError::InvalidAction(format!("Invalid file operation: {}", e)))?;
+        let exists = |path: &str| std::path::Path::new(path).exists();
+
+        let preview = match operation {
+            FileOperation::Read(params) => {
+                serde_json::json!({ "operation": "read", "path": params.path, "exists": exists(&params.path) })
+            }
+            FileOperation::Write(params) => serde_json::json!({
+                "operation": "write",
+                "overwrites": exists(&params.path),
+                "path": params.path,
+                "bytes": params.content.len(),
+            }),
+            FileOperation::Delete(params) => {
+                serde_json::json!({ "operation": "delete", "path": params.path, "exists": exists(&params.path) })
+            }
+            FileOperation::Move(params) => serde_json::json!({
+                "operation": "move",
+                "overwrites": exists(&params.to),
+                "from": params.from,
+                "to": params.to,
+            }),
+            FileOperation::Copy { from, to } => {
+                serde_json::json!({ "operation": "copy", "overwrites": exists(&to), "from": from, "to": to })
+            }
+        };
+        Ok(serde_json::to_vec(&preview).unwrap())
+    }
 }
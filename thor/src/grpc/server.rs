@@ -29,6 +29,8 @@ impl ThorService for ThorServiceImpl {
         request: Request<thor::ThorAction>,
     ) -> Result<Response<thor::ThorResult>, Status> {
         let action = request.into_inner();
+        // The request id arrives with the trace context from Odin
+        vegvisir::record_request_ids(&tracing::Span::current(), "", &action.user_id, &action.device_id);
         
         let context = crate::actions::ActionContext {
             device_id: action.device_id.clone(),
@@ -36,22 +38,33 @@ impl ThorService for ThorServiceImpl {
             action_id: action.action_id.clone(),
         };
 
+        // Dry run and user confirmation are passed by Odin in the metadata
+        let options = crate::actions::DispatchOptions::from_metadata(&action.metadata)
+            .for_action(&action.action_type, &action.action_data);
+
         // Dispatch action (check for structural XML protocol)
         let result = if action.action_type == "XML_CALL" || action.action_type == "XML_TASK" {
             let xml_str = String::from_utf8_lossy(&action.action_data);
-            self.xml_dispatcher.execute_xml(&context, &xml_str).await
+            self.xml_dispatcher.execute_xml_with_options(&context, &xml_str, &options).await
         } else {
             self.dispatcher
-                .dispatch(&action.action_type, &context, &action.action_data)
+                .dispatch_with_options(&action.action_type, &context, &action.action_data, &options)
                 .await
-        }.map_err(|e| Status::internal(format!("Action execution failed: {}", e)))?;
+        }.map_err(|e| match e {
+            crate::actions::ActionError::ConfirmationRequired(_) => Status::failed_precondition(e.to_string()),
+            _ => Status::internal(format!("Action execution failed: {}", e)),
+        })?;
 
+        let mut metadata = std::collections::HashMap::new();
+        if options.dry_run {
+            metadata.insert(crate::actions::DRY_RUN_METADATA_KEY.to_string(), "true".to_string());
+        }
         let response = thor::ThorResult {
             action_id: action.action_id,
             success: true,
             error_message: String::new(),
             result_data: result,
-            metadata: std::collections::HashMap::new(),
+            metadata,
         };
 
         Ok(Response::new(response))
@@ -81,6 +94,7 @@ pub async fn start_grpc_server(
     let thor_service = ThorServiceImpl::new(deps.dispatcher);
 
     Server::builder()
+        .layer(vegvisir::ServerTraceLayer)
         .add_service(ThorServiceServer::new(thor_service))
         .serve(addr)
         .await?;
@@ -1,4 +1,4 @@
-use tonic::transport::Channel;
+use vegvisir::TracedChannel;
 use anyhow::Result;
 use std::time::Duration;
 
@@ -11,7 +11,7 @@ use jotunheim::{DeviceCommandRequest, DeviceCommandResponse, DeviceStatusRequest
 
 /// Client for Jotunheim service (IoT Platform)
 pub struct JotunheimClient {
-    client: JotunheimServiceClient<Channel>,
+    client: JotunheimServiceClient<TracedChannel>,
 }
 
 impl JotunheimClient {
@@ -22,7 +22,7 @@ impl JotunheimClient {
             .connect_timeout(Duration::from_secs(5));
         
         let channel = endpoint.connect().await?;
-        let client = JotunheimServiceClient::new(channel);
+        let client = JotunheimServiceClient::new(vegvisir::traced(channel));
         
         Ok(Self { client })
     }
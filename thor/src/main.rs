@@ -1,15 +1,20 @@
-use tracing::{info, error};
+use tracing::{info, warn, error};
 use thor::utils::config::SettingsManager;
 use thor::grpc::start_grpc_server;
 use std::path::PathBuf;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize tracing
-    tracing_subscriber::fmt()
-        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+    // Initialize tracing; spans go to Vegvisir and are exported once the settings are loaded
+    let (trace_layer, trace_exporter) = vegvisir::layer("thor");
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(tracing_subscriber::EnvFilter::from_default_env()))
+        .with(trace_layer)
         .init();
 
     info!("Thor Action Executor Service starting...");
@@ -22,6 +27,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     let settings = settings_manager.get().await;
     info!("Configuration loaded");
+    trace_exporter.start(&settings.trace_export);
 
     // Initialize permission checker
     let permission_checker = Arc::new(thor::permissions::PermissionChecker::new(
@@ -52,19 +58,29 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Initialize action dispatcher (with optional audit logging)
     let dispatcher = if settings.enable_audit_logging {
-        Arc::new(thor::actions::ActionDispatcher::new_with_audit(
+        thor::actions::ActionDispatcher::new_with_audit(
             registry.clone(),
             permission_checker.clone(),
             thor::audit::TracingAuditLogger::new(),
             settings.enable_sandboxing,
-        ))
+        )
     } else {
-        Arc::new(thor::actions::ActionDispatcher::new(
+        thor::actions::ActionDispatcher::new(
             registry.clone(),
             permission_checker.clone(),
             settings.enable_sandboxing,
-        ))
+        )
     };
+    // Risky actions need a user confirmation obtained and signed by Odin
+    let dispatcher = Arc::new(match settings.confirmation_risk_level {
+        Some(level) => {
+            if settings.confirmation_secret.is_empty() {
+                warn!("confirmation_secret is empty: actions of {} risk and above are refused", level.as_str());
+            }
+            dispatcher.with_confirmation_level(level).with_confirmation_secret(&settings.confirmation_secret)
+        }
+        None => dispatcher,
+    });
 
     // Start gRPC server
     let addr = SocketAddr::from(([0, 0, 0, 0], settings.grpc_port));
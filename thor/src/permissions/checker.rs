@@ -85,7 +85,7 @@ impl PermissionChecker {
             Err(e) if self.allow_on_connection_error => return Ok(true),
             Err(e) => return Err(e),
         };
-        let mut client = AuthorizationServiceClient::new(channel);
+        let mut client = AuthorizationServiceClient::new(vegvisir::traced(channel));
         let request = PermissionCheckRequest {
             device_id: device_id.to_string(),
             user_id: user_id.to_string(),
@@ -4,6 +4,7 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 use notify::{Watcher, RecommendedWatcher, RecursiveMode, Event, EventKind};
 use tracing::{info, error};
+use crate::actions::RiskLevel;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ThorSettings {
@@ -14,6 +15,20 @@ pub struct ThorSettings {
     pub action_timeout_seconds: u64,
     pub enable_sandboxing: bool,
     pub enable_audit_logging: bool,
+    /// Actions of at least this risk run only with a user confirmation from Odin; `null` disables the check.
+    #[serde(default = "default_confirmation_risk_level")]
+    pub confirmation_risk_level: Option<RiskLevel>,
+    /// Secret shared with Odin (`confirmation.signing_secret`) to verify its confirmation tokens;
+    /// empty accepts no confirmation, so risky actions are refused.
+    #[serde(default)]
+    pub confirmation_secret: String,
+    /// Span export (OTLP collector and/or JSON file), see Vegvisir.
+    #[serde(default)]
+    pub trace_export: vegvisir::TraceExportConfig,
+}
+
+fn default_confirmation_risk_level() -> Option<RiskLevel> {
+    Some(RiskLevel::High)
 }
 
 impl Default for ThorSettings {
@@ -26,6 +41,9 @@ impl Default for ThorSettings {
             action_timeout_seconds: 300,
             enable_sandboxing: false,
             enable_audit_logging: true,
+            confirmation_risk_level: default_confirmation_risk_level(),
+            confirmation_secret: String::new(),
+            trace_export: vegvisir::TraceExportConfig::default(),
         }
     }
 }
@@ -47,6 +65,7 @@ impl SettingsManager {
         if self.config_path.exists() {
             let content = tokio::fs::read_to_string(&self.config_path).await?;
             let settings: ThorSettings = serde_json::from_str(&content)?;
+            settings.trace_export.validate()?;
             *self.settings.write().await = settings;
             info!("Configuration loaded from {}", self.config_path.display());
         } else {
@@ -11,6 +11,7 @@ mod unit {
     pub mod ui_automation_test;
     pub mod scheduler_test;
     pub mod jotunheim_test;
+    pub mod risk_test;
 }
 
 #[cfg(test)]
@@ -0,0 +1,151 @@
+//! Unit tests for risk classification, confirmation enforcement and dry runs in the ActionDispatcher.
+
+use async_trait::async_trait;
+use gjallarhorn::ConfirmationSigner;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use thor::actions::{
+    classify_risk, ActionContext, ActionDispatcher, ActionError, ActionExecutor, ActionRegistry, DispatchOptions,
+    RiskLevel,
+};
+use thor::permissions::PermissionChecker;
+
+/// Secret Thor shares with Odin in these tests.
+const SECRET: &str = "shared-secret";
+
+struct CountingExecutor {
+    runs: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl ActionExecutor for CountingExecutor {
+    fn action_type(&self) -> &str {
+        "SYSTEM_COMMAND"
+    }
+
+    async fn execute(&self, _context: &ActionContext, _action_data: &[u8]) -> Result<Vec<u8>, ActionError> {
+        self.runs.fetch_add(1, Ordering::SeqCst);
+        Ok(b"{\"exit_code\":0}".to_vec())
+    }
+}
+
+fn context() -> ActionContext {
+    ActionContext {
+        device_id: "dev-1".to_string(),
+        user_id: "user-1".to_string(),
+        action_id: "act-1".to_string(),
+    }
+}
+
+async fn dispatcher(runs: Arc<AtomicUsize>) -> ActionDispatcher {
+    let registry = Arc::new(ActionRegistry::new());
+    registry.register(Arc::new(CountingExecutor { runs })).await;
+    let permissions = Arc::new(PermissionChecker::new_allow_on_connection_error("http://127.0.0.1:17997".to_string()));
+    ActionDispatcher::new(registry, permissions, false)
+        .with_confirmation_level(RiskLevel::High)
+        .with_confirmation_secret(SECRET)
+}
+
+/// Confirmation as Odin sends it: a token signed for the action, valid for a minute.
+fn confirmed(action_type: &str, data: &[u8]) -> DispatchOptions {
+    let expires_at_ms = chrono::Utc::now().timestamp_millis() + 60_000;
+    let token = ConfirmationSigner::new(SECRET).sign("c-1", action_type, data, expires_at_ms);
+    DispatchOptions { confirmation_id: Some(token), ..Default::default() }
+}
+
+#[test]
+fn test_classify_risk_levels() {
+    let read = classify_risk("FILE_OPERATION", br#"{"operation":"Read","path":"/tmp/a.txt"}"#);
+    assert_eq!(read.level, RiskLevel::Low);
+
+    let delete = classify_risk("FILE_OPERATION", br#"{"operation":"Delete","path":"/tmp/a.txt"}"#);
+    assert_eq!(delete.level, RiskLevel::High);
+    assert_eq!(delete.reasons, vec!["deletes /tmp/a.txt".to_string()]);
+
+    let wipe = classify_risk("SYSTEM_COMMAND", br#"{"command":"rm","args":["-rf","/home"]}"#);
+    assert_eq!(wipe.level, RiskLevel::Critical);
+
+    let purchase = classify_risk("NETWORK_OPERATION", br#"{"method":"POST","url":"https://shop.example/api/checkout"}"#);
+    assert_eq!(purchase.level, RiskLevel::High);
+
+    let remote = classify_risk("APP_CONTROL", br#"{"operation":"start","app_path":"vlc","target_device_id":"tv"}"#);
+    assert_eq!(remote.level, RiskLevel::High);
+
+    let listing = classify_risk("SANDBOX_COMMAND", br#"{"command":"ls","args":["-la"]}"#);
+    assert_eq!(listing.level, RiskLevel::Medium);
+}
+
+#[tokio::test]
+async fn test_dispatch_requires_confirmation_for_risky_action() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let dispatcher = dispatcher(runs.clone()).await;
+    let data = br#"{"command":"rm","args":["-rf","/tmp/build"]}"#;
+
+    let result = dispatcher.dispatch("SYSTEM_COMMAND", &context(), data).await;
+    assert!(matches!(result, Err(ActionError::ConfirmationRequired(_))));
+    assert_eq!(runs.load(Ordering::SeqCst), 0);
+
+    let result = dispatcher.dispatch_with_options("SYSTEM_COMMAND", &context(), data, &confirmed("SYSTEM_COMMAND", data)).await;
+    assert!(result.is_ok());
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_dispatch_rejects_made_up_or_misused_confirmation() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let dispatcher = dispatcher(runs.clone()).await;
+    let data = br#"{"command":"rm","args":["-rf","/tmp/build"]}"#;
+
+    let made_up = DispatchOptions { confirmation_id: Some("c-1".to_string()), ..Default::default() };
+    let result = dispatcher.dispatch_with_options("SYSTEM_COMMAND", &context(), data, &made_up).await;
+    assert!(matches!(result, Err(ActionError::ConfirmationRequired(_))));
+
+    // Confirmed for a different command
+    let other = confirmed("SYSTEM_COMMAND", br#"{"command":"rm","args":["-rf","/tmp/cache"]}"#);
+    let result = dispatcher.dispatch_with_options("SYSTEM_COMMAND", &context(), data, &other).await;
+    assert!(matches!(result, Err(ActionError::ConfirmationRequired(_))));
+
+    let expired_token = ConfirmationSigner::new(SECRET).sign("c-1", "SYSTEM_COMMAND", data, 1);
+    let expired = DispatchOptions { confirmation_id: Some(expired_token), ..Default::default() };
+    let result = dispatcher.dispatch_with_options("SYSTEM_COMMAND", &context(), data, &expired).await;
+    assert!(matches!(result, Err(ActionError::ConfirmationRequired(_))));
+    assert_eq!(runs.load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn test_dispatch_rejects_replayed_confirmation() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let dispatcher = dispatcher(runs.clone()).await;
+    let data = br#"{"command":"rm","args":["-rf","/tmp/build"]}"#;
+
+    // The actions one request expands to share its confirmation
+    let request = confirmed("SYSTEM_COMMAND", data).for_action("SYSTEM_COMMAND", data);
+    for _ in 0..2 {
+        let result = dispatcher.dispatch_with_options("SYSTEM_COMMAND", &context(), data, &request).await;
+        assert!(result.is_ok());
+    }
+
+    // The same token sent again is refused
+    let replayed = DispatchOptions { confirmation_id: request.confirmation_id.clone(), ..Default::default() }
+        .for_action("SYSTEM_COMMAND", data);
+    let result = dispatcher.dispatch_with_options("SYSTEM_COMMAND", &context(), data, &replayed).await;
+    assert!(matches!(result, Err(ActionError::ConfirmationRequired(ref e)) if e.contains("already used")));
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_dry_run_reports_without_executing() {
+    let runs = Arc::new(AtomicUsize::new(0));
+    let dispatcher = dispatcher(runs.clone()).await;
+    let dry_run = DispatchOptions { dry_run: true, ..Default::default() };
+
+    let preview = dispatcher
+        .dispatch_with_options("SYSTEM_COMMAND", &context(), br#"{"command":"reboot","args":[]}"#, &dry_run)
+        .await
+        .unwrap();
+    let preview: serde_json::Value = serde_json::from_slice(&preview).unwrap();
+    assert_eq!(preview["dry_run"], true);
+    assert_eq!(preview["risk"]["level"], "critical");
+    assert_eq!(preview["would_execute"]["command"], "reboot");
+    assert_eq!(runs.load(Ordering::SeqCst), 0);
+}
@@ -0,0 +1,203 @@
+//! W3C Trace Context: `traceparent` (Trace- und Span-ID des Aufrufers) und `baggage`
+//! (Request-, User- und Device-ID, die mit dem Trace durch alle Services wandern).
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Header mit Trace-ID, Span-ID des Aufrufers und Flags (`00-<trace>-<span>-<flags>`).
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+/// Header mit den weitergegebenen IDs (`request_id=...,user_id=...`).
+pub const BAGGAGE_HEADER: &str = "baggage";
+
+/// Baggage-Keys der Edda-IDs.
+pub const REQUEST_ID: &str = "request_id";
+pub const USER_ID: &str = "user_id";
+pub const DEVICE_ID: &str = "device_id";
+
+/// 16-Byte Trace-ID; alle Spans eines Requests über alle Services teilen sie.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TraceId(pub [u8; 16]);
+
+impl TraceId {
+    pub fn random() -> Self {
+        TraceId(*uuid::Uuid::new_v4().as_bytes())
+    }
+
+    /// Trace-ID zu einer Request-ID: UUIDs werden übernommen (Trace-ID = Request-ID ohne
+    /// Bindestriche), andere IDs per FNV-1a (128 Bit) abgeleitet – gleiche ID, gleicher Trace.
+    pub fn from_request_id(request_id: &str) -> Self {
+        if let Ok(uuid) = uuid::Uuid::parse_str(request_id) {
+            if !uuid.is_nil() {
+                return TraceId(*uuid.as_bytes());
+            }
+        }
+        const OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+        const PRIME: u128 = 0x0000000001000000000000000000013b;
+        let hash = request_id.bytes().fold(OFFSET, |hash, byte| (hash ^ byte as u128).wrapping_mul(PRIME));
+        TraceId(hash.to_be_bytes())
+    }
+
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let mut bytes = [0u8; 16];
+        decode_hex(hex, &mut bytes)?;
+        Some(TraceId(bytes)).filter(|id| id.0 != [0; 16])
+    }
+}
+
+impl fmt::Display for TraceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_hex(f, &self.0)
+    }
+}
+
+/// 8-Byte Span-ID, eindeutig pro Span.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SpanId(pub [u8; 8]);
+
+impl SpanId {
+    pub fn random() -> Self {
+        loop {
+            let bytes = uuid::Uuid::new_v4().into_bytes();
+            let id: [u8; 8] = bytes[..8].try_into().expect("uuid has 16 bytes");
+            if id != [0; 8] {
+                return SpanId(id);
+            }
+        }
+    }
+
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let mut bytes = [0u8; 8];
+        decode_hex(hex, &mut bytes)?;
+        Some(SpanId(bytes)).filter(|id| id.0 != [0; 8])
+    }
+}
+
+impl fmt::Display for SpanId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_hex(f, &self.0)
+    }
+}
+
+/// Position eines Spans im Trace, wie sie per `traceparent` weitergegeben wird.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpanContext {
+    pub trace_id: TraceId,
+    pub span_id: SpanId,
+    /// Flag `01`: der Aufrufer zeichnet den Trace auf.
+    pub sampled: bool,
+}
+
+impl SpanContext {
+    pub fn to_traceparent(&self) -> String {
+        format!("00-{}-{}-{:02x}", self.trace_id, self.span_id, self.sampled as u8)
+    }
+
+    /// Liest einen `traceparent`-Header; ungültige Header (falsche Länge, Null-IDs, Version `ff`)
+    /// ergeben `None`. Spätere Versionen werden gelesen, soweit sie Version 00 entsprechen.
+    pub fn from_traceparent(value: &str) -> Option<Self> {
+        let parts: Vec<&str> = value.trim().split('-').collect();
+        let version = *parts.first()?;
+        if version.len() != 2 || version == "ff" || parts.len() < 4 || (version == "00" && parts.len() != 4) {
+            return None;
+        }
+        let mut flags = [0u8; 1];
+        decode_hex(version, &mut [0u8; 1])?;
+        decode_hex(parts[3], &mut flags)?;
+        Some(SpanContext {
+            trace_id: TraceId::from_hex(parts[1])?,
+            span_id: SpanId::from_hex(parts[2])?,
+            sampled: flags[0] & 1 == 1,
+        })
+    }
+}
+
+/// Mit dem Trace weitergegebene Key-Value-Paare (W3C `baggage`), z.B. die Request-ID.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Baggage(BTreeMap<String, String>);
+
+impl Baggage {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    /// Leere Werte werden nicht gesetzt.
+    pub fn set(&mut self, key: &str, value: &str) {
+        if !value.is_empty() {
+            self.0.insert(key.to_string(), value.to_string());
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    pub fn to_header(&self) -> String {
+        self.0.iter().map(|(key, value)| format!("{}={}", key, percent_encode(value))).collect::<Vec<_>>().join(",")
+    }
+
+    /// Liest einen `baggage`-Header; Einträge ohne `=` und Properties (`;...`) werden ignoriert.
+    pub fn from_header(value: &str) -> Self {
+        let mut baggage = Baggage::default();
+        for member in value.split(',') {
+            let member = member.split(';').next().unwrap_or_default();
+            if let Some((key, value)) = member.split_once('=') {
+                baggage.set(key.trim(), &percent_decode(value.trim()));
+            }
+        }
+        baggage
+    }
+}
+
+fn write_hex(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    bytes.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+}
+
+/// Kleinbuchstaben-Hex genau der Länge von `out`.
+fn decode_hex(hex: &str, out: &mut [u8]) -> Option<()> {
+    if hex.len() != out.len() * 2 || !hex.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b)) {
+        return None;
+    }
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(())
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~:/@".contains(&byte) {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    out
+}
+
+pub(crate) fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let decoded = (bytes[i] == b'%')
+            .then(|| value.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match decoded {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
@@ -0,0 +1,285 @@
+//! Export beendeter Spans als OTLP/JSON: per HTTP an einen lokalen Collector
+//! (`POST <endpoint>/v1/traces`) und/oder zeilenweise in eine rollierende Datei.
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use crate::layer::{AttributeValue, FinishedSpan, SpanKind, SpanStatus};
+
+/// Einstellungen für den Span-Export (Feld `trace_export` in den Settings jedes Services).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TraceExportConfig {
+    /// OTLP/HTTP-Collector, z.B. "http://localhost:4318" (nur http://); leer = kein Collector.
+    pub otlp_endpoint: String,
+    /// JSON-Datei (eine OTLP-Nachricht pro Zeile); leer = keine Datei.
+    pub file_path: String,
+    /// Größe, ab der die Datei rolliert wird (`spans.json` → `spans.json.1` → ...).
+    pub max_file_bytes: u64,
+    /// Anzahl aufbewahrter Dateien inklusive der aktuellen.
+    pub max_files: usize,
+    /// Spans pro Export.
+    pub batch_size: usize,
+    /// Spätestens nach diesem Intervall (ms) werden gesammelte Spans exportiert.
+    pub flush_interval_ms: u64,
+}
+
+impl Default for TraceExportConfig {
+    fn default() -> Self {
+        Self {
+            otlp_endpoint: String::new(),
+            file_path: String::new(),
+            max_file_bytes: 10 * 1024 * 1024,
+            max_files: 5,
+            batch_size: 512,
+            flush_interval_ms: 2000,
+        }
+    }
+}
+
+impl TraceExportConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.otlp_endpoint.is_empty() || !self.file_path.is_empty()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.otlp_endpoint.is_empty() && !self.otlp_endpoint.starts_with("http://") {
+            return Err(format!("trace_export.otlp_endpoint '{}' must be an http:// URL", self.otlp_endpoint));
+        }
+        if !self.file_path.is_empty() && (self.max_file_bytes == 0 || self.max_files == 0) {
+            return Err("trace_export.max_file_bytes and trace_export.max_files must be > 0".to_string());
+        }
+        if self.batch_size == 0 || self.flush_interval_ms == 0 {
+            return Err("trace_export.batch_size and trace_export.flush_interval_ms must be > 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Gegenstück zum Layer aus [`layer`](crate::layer): nimmt dessen beendete Spans entgegen.
+pub struct TraceExporter {
+    service_name: String,
+    receiver: Mutex<Option<mpsc::Receiver<FinishedSpan>>>,
+}
+
+impl TraceExporter {
+    pub(crate) fn new(service_name: &str, receiver: mpsc::Receiver<FinishedSpan>) -> Self {
+        Self {
+            service_name: service_name.to_string(),
+            receiver: Mutex::new(Some(receiver)),
+        }
+    }
+
+    /// Startet den Export im Hintergrund (Tokio-Runtime erforderlich). Ist kein Ziel
+    /// konfiguriert, werden Spans ab jetzt verworfen und `None` zurückgegeben.
+    pub fn start(&self, config: &TraceExportConfig) -> Option<JoinHandle<()>> {
+        let receiver = self.take_receiver()?;
+        if !config.is_enabled() {
+            return None;
+        }
+        let exporter = BatchExporter {
+            service_name: self.service_name.clone(),
+            file: (!config.file_path.is_empty())
+                .then(|| RollingFile::new(&config.file_path, config.max_file_bytes, config.max_files)),
+            collector: (!config.otlp_endpoint.is_empty()).then(|| OtlpCollector::new(&config.otlp_endpoint)),
+        };
+        let batch_size = config.batch_size.max(1);
+        let interval = Duration::from_millis(config.flush_interval_ms.max(1));
+        Some(tokio::spawn(exporter.run(receiver, batch_size, interval)))
+    }
+
+    /// Übernimmt die Spans selbst (z.B. in Tests); danach hat [`start`](Self::start) keine Wirkung.
+    pub fn take_receiver(&self) -> Option<mpsc::Receiver<FinishedSpan>> {
+        self.receiver.lock().ok()?.take()
+    }
+}
+
+struct BatchExporter {
+    service_name: String,
+    file: Option<RollingFile>,
+    collector: Option<OtlpCollector>,
+}
+
+impl BatchExporter {
+    async fn run(mut self, mut receiver: mpsc::Receiver<FinishedSpan>, batch_size: usize, interval: Duration) {
+        let mut batch = Vec::with_capacity(batch_size);
+        loop {
+            let deadline = Instant::now() + interval;
+            let closed = loop {
+                match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                    Ok(Some(span)) => {
+                        batch.push(span);
+                        if batch.len() >= batch_size {
+                            break false;
+                        }
+                    }
+                    Ok(None) => break true,
+                    Err(_) => break false,
+                }
+            };
+            if !batch.is_empty() {
+                self.export(&batch).await;
+                batch.clear();
+            }
+            if closed {
+                return;
+            }
+        }
+    }
+
+    async fn export(&mut self, spans: &[FinishedSpan]) {
+        let body = to_otlp_json(&self.service_name, spans).to_string();
+        if let Some(ref mut file) = self.file {
+            if let Err(e) = file.append_line(&body).await {
+                tracing::warn!("Failed to write spans to {}: {}", file.path.display(), e);
+            }
+        }
+        if let Some(ref collector) = self.collector {
+            if let Err(e) = collector.send(body).await {
+                tracing::warn!("Failed to export spans to {}: {}", collector.uri, e);
+            }
+        }
+    }
+}
+
+/// Datei, die bei Erreichen von `max_bytes` rolliert wird; die älteste Datei entfällt.
+pub struct RollingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    max_files: usize,
+}
+
+impl RollingFile {
+    pub fn new(path: impl AsRef<Path>, max_bytes: u64, max_files: usize) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            max_bytes,
+            max_files: max_files.max(1),
+        }
+    }
+
+    pub async fn append_line(&mut self, line: &str) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let size = tokio::fs::metadata(&self.path).await.map(|m| m.len()).unwrap_or(0);
+        if size > 0 && size + line.len() as u64 + 1 > self.max_bytes {
+            self.rotate().await?;
+        }
+        let mut file = tokio::fs::OpenOptions::new().create(true).append(true).open(&self.path).await?;
+        file.write_all(line.as_bytes()).await?;
+        file.write_all(b"\n").await?;
+        file.flush().await
+    }
+
+    async fn rotate(&self) -> std::io::Result<()> {
+        if self.max_files == 1 {
+            return tokio::fs::remove_file(&self.path).await;
+        }
+        for index in (1..self.max_files).rev() {
+            let from = if index == 1 { self.path.clone() } else { self.rotated(index - 1) };
+            if tokio::fs::metadata(&from).await.is_ok() {
+                tokio::fs::rename(&from, self.rotated(index)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    fn rotated(&self, index: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+}
+
+/// OTLP/HTTP-Collector mit JSON-Encoding.
+struct OtlpCollector {
+    uri: String,
+    client: hyper::Client<hyper::client::HttpConnector>,
+}
+
+impl OtlpCollector {
+    fn new(endpoint: &str) -> Self {
+        let endpoint = endpoint.trim_end_matches('/');
+        let uri = if endpoint.ends_with("/v1/traces") { endpoint.to_string() } else { format!("{}/v1/traces", endpoint) };
+        Self { uri, client: hyper::Client::new() }
+    }
+
+    async fn send(&self, body: String) -> Result<(), String> {
+        let request = hyper::Request::post(&self.uri)
+            .header(http::header::CONTENT_TYPE, "application/json")
+            .body(hyper::Body::from(body))
+            .map_err(|e| e.to_string())?;
+        let response = self.client.request(request).await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("collector returned {}", response.status()));
+        }
+        Ok(())
+    }
+}
+
+/// Spans als OTLP `ExportTraceServiceRequest` im JSON-Encoding (IDs hex, Zeiten in ns).
+pub fn to_otlp_json(service_name: &str, spans: &[FinishedSpan]) -> Value {
+    json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [attribute_json("service.name", &AttributeValue::String(service_name.to_string()))]
+            },
+            "scopeSpans": [{
+                "scope": { "name": "vegvisir", "version": env!("CARGO_PKG_VERSION") },
+                "spans": spans.iter().map(span_json).collect::<Vec<_>>()
+            }]
+        }]
+    })
+}
+
+fn span_json(span: &FinishedSpan) -> Value {
+    let (code, message) = match span.status {
+        SpanStatus::Unset => (0, ""),
+        SpanStatus::Ok => (1, ""),
+        SpanStatus::Error(ref message) => (2, message.as_str()),
+    };
+    json!({
+        "traceId": span.context.trace_id.to_string(),
+        "spanId": span.context.span_id.to_string(),
+        "parentSpanId": span.parent_span_id.map(|id| id.to_string()).unwrap_or_default(),
+        "name": span.name,
+        "kind": match span.kind {
+            SpanKind::Internal => 1,
+            SpanKind::Server => 2,
+            SpanKind::Client => 3,
+        },
+        "startTimeUnixNano": unix_nanos(span.start),
+        "endTimeUnixNano": unix_nanos(span.end),
+        "attributes": span.attributes.iter().map(|(key, value)| attribute_json(key, value)).collect::<Vec<_>>(),
+        "events": span.events.iter().map(|event| json!({
+            "timeUnixNano": unix_nanos(event.time),
+            "name": event.name,
+            "attributes": event.attributes.iter().map(|(key, value)| attribute_json(key, value)).collect::<Vec<_>>(),
+        })).collect::<Vec<_>>(),
+        "status": { "code": code, "message": message },
+    })
+}
+
+fn attribute_json(key: &str, value: &AttributeValue) -> Value {
+    // int64 values are strings in OTLP/JSON
+    let value = match value {
+        AttributeValue::String(s) => json!({ "stringValue": s }),
+        AttributeValue::Bool(b) => json!({ "boolValue": b }),
+        AttributeValue::Int(i) => json!({ "intValue": i.to_string() }),
+        AttributeValue::Double(d) => json!({ "doubleValue": d }),
+    };
+    json!({ "key": key, "value": value })
+}
+
+fn unix_nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or_default().to_string()
+}
@@ -0,0 +1,197 @@
+//! Tower-Layer für tonic: jeder ausgehende und eingehende RPC bekommt einen Span; Clients
+//! senden `traceparent` und `baggage` mit, Server übernehmen sie als Parent.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tower_layer::Layer;
+use tower_service::Service;
+use tracing::{Instrument, Span};
+
+use crate::context::{percent_decode, Baggage, SpanContext, BAGGAGE_HEADER, TRACEPARENT_HEADER};
+use crate::layer::{add_baggage, baggage, set_remote_parent, span_context};
+
+/// tonic-Channel, dessen RPCs getraced werden: `FooServiceClient::new(vegvisir::traced(channel))`.
+pub type TracedChannel = ClientTrace<tonic::transport::Channel>;
+
+pub fn traced(channel: tonic::transport::Channel) -> TracedChannel {
+    ClientTrace::new(channel)
+}
+
+type BoxFuture<T, E> = Pin<Box<dyn Future<Output = Result<T, E>> + Send>>;
+
+/// Client-Seite: Span pro RPC (Kind des aktuellen Spans) und Trace-Header im Request.
+#[derive(Debug, Clone)]
+pub struct ClientTrace<S> {
+    inner: S,
+}
+
+impl<S> ClientTrace<S> {
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+}
+
+impl<S, B, ResBody> Service<http::Request<B>> for ClientTrace<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<ResBody>>,
+    S::Future: Send + 'static,
+    S::Error: std::fmt::Display + 'static,
+    ResBody: 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<S::Response, S::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: http::Request<B>) -> Self::Future {
+        let (service, method) = rpc_name(request.uri().path());
+        let span = tracing::info_span!(
+            "grpc.client",
+            otel.name = %format!("{}/{}", service, method),
+            otel.kind = "client",
+            rpc.system = "grpc",
+            rpc.service = %service,
+            rpc.method = %method,
+            rpc.grpc.status_code = tracing::field::Empty,
+            otel.status_code = tracing::field::Empty,
+            otel.status_message = tracing::field::Empty,
+        );
+        inject(&span, request.headers_mut());
+        let future = span.in_scope(|| self.inner.call(request));
+        Box::pin(record_status(future, span))
+    }
+}
+
+/// Server-Seite für `Server::builder().layer(vegvisir::ServerTraceLayer)`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ServerTraceLayer;
+
+impl<S> Layer<S> for ServerTraceLayer {
+    type Service = ServerTrace<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ServerTrace { inner }
+    }
+}
+
+/// Server-Seite: Span pro RPC mit dem Aufrufer aus `traceparent` als Parent; IDs aus dem
+/// `baggage` werden Attribute (`edda.request_id`, `edda.user_id`, `edda.device_id`).
+#[derive(Debug, Clone)]
+pub struct ServerTrace<S> {
+    inner: S,
+}
+
+impl<S, B, ResBody> Service<http::Request<B>> for ServerTrace<S>
+where
+    S: Service<http::Request<B>, Response = http::Response<ResBody>>,
+    S::Future: Send + 'static,
+    S::Error: std::fmt::Display + 'static,
+    ResBody: 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<S::Response, S::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let (service, method) = rpc_name(request.uri().path());
+        // Not a child of whatever span the server task runs in; the caller is the parent
+        let span = tracing::info_span!(
+            parent: None,
+            "grpc.server",
+            otel.name = %format!("{}/{}", service, method),
+            otel.kind = "server",
+            rpc.system = "grpc",
+            rpc.service = %service,
+            rpc.method = %method,
+            rpc.grpc.status_code = tracing::field::Empty,
+            otel.status_code = tracing::field::Empty,
+            otel.status_message = tracing::field::Empty,
+        );
+        let (parent, baggage) = extract(request.headers());
+        match parent {
+            Some(parent) => set_remote_parent(&span, parent, baggage),
+            None => add_baggage(&span, baggage),
+        }
+        let future = span.in_scope(|| self.inner.call(request));
+        Box::pin(record_status(future, span))
+    }
+}
+
+/// Schreibt `traceparent` und `baggage` des Spans in die Header.
+pub fn inject(span: &Span, headers: &mut http::HeaderMap) {
+    if let Some(context) = span_context(span) {
+        if let Ok(value) = http::HeaderValue::from_str(&context.to_traceparent()) {
+            headers.insert(TRACEPARENT_HEADER, value);
+        }
+        let baggage = baggage(span);
+        if !baggage.is_empty() {
+            if let Ok(value) = http::HeaderValue::from_str(&baggage.to_header()) {
+                headers.insert(BAGGAGE_HEADER, value);
+            }
+        }
+    }
+}
+
+/// Liest `traceparent` und `baggage`; ungültige Header werden ignoriert.
+pub fn extract(headers: &http::HeaderMap) -> (Option<SpanContext>, Baggage) {
+    let parent = headers
+        .get(TRACEPARENT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(SpanContext::from_traceparent);
+    let baggage = headers
+        .get(BAGGAGE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(Baggage::from_header)
+        .unwrap_or_default();
+    (parent, baggage)
+}
+
+/// `/thor.ThorService/ExecuteAction` → (`thor.ThorService`, `ExecuteAction`).
+fn rpc_name(path: &str) -> (String, String) {
+    let path = path.trim_start_matches('/');
+    match path.split_once('/') {
+        Some((service, method)) => (service.to_string(), method.to_string()),
+        None => (path.to_string(), String::new()),
+    }
+}
+
+/// Läuft `future` im Span und hält den gRPC-Status fest. Fehler-Status stehen bei tonic in den
+/// Headern (Trailers-Only-Antwort); fehlt `grpc-status`, war der Aufruf erfolgreich.
+async fn record_status<F, ResBody, E>(future: F, span: Span) -> Result<http::Response<ResBody>, E>
+where
+    F: Future<Output = Result<http::Response<ResBody>, E>>,
+    E: std::fmt::Display,
+{
+    let result = future.instrument(span.clone()).await;
+    match result {
+        Ok(ref response) => {
+            let code = response
+                .headers()
+                .get("grpc-status")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<i64>().ok())
+                .unwrap_or(0);
+            span.record("rpc.grpc.status_code", code);
+            if code != 0 {
+                let message = response.headers().get("grpc-message").and_then(|value| value.to_str().ok()).unwrap_or_default();
+                span.record("otel.status_code", "error");
+                // grpc-message is percent-encoded
+                span.record("otel.status_message", percent_decode(message).as_str());
+            }
+        }
+        Err(ref e) => {
+            span.record("otel.status_code", "error");
+            span.record("otel.status_message", e.to_string().as_str());
+        }
+    }
+    result
+}
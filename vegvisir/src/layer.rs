@@ -0,0 +1,380 @@
+//! Tracing-Layer: führt Trace-Kontext, Attribute und Events jedes Spans mit und übergibt
+//! beendete Spans an den [`TraceExporter`](crate::TraceExporter).
+//!
+//! Die Felder `otel.name`, `otel.kind` (`server`, `client`, `internal`), `otel.status_code`
+//! (`ok`, `error`) und `otel.status_message` steuern Name, Art und Status des Spans; alle anderen
+//! Felder werden Attribute, Events innerhalb des Spans werden Span-Events. Request-, User- und
+//! Device-ID aus dem Baggage trägt jeder Span als `edda.request_id`, `edda.user_id`, `edda.device_id`.
+
+use std::time::SystemTime;
+
+use tokio::sync::mpsc;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Span, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{Layer, Registry};
+
+use crate::context::{Baggage, SpanContext, SpanId, TraceId, DEVICE_ID, REQUEST_ID, USER_ID};
+
+/// Events pro Span; weitere werden nicht aufgezeichnet.
+const MAX_EVENTS: usize = 64;
+
+/// Art des Spans (OTLP `SpanKind`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpanKind {
+    Internal,
+    /// Eingehender RPC.
+    Server,
+    /// Ausgehender RPC.
+    Client,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    String(String),
+    Bool(bool),
+    Int(i64),
+    Double(f64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpanStatus {
+    Unset,
+    Ok,
+    Error(String),
+}
+
+/// Log-Event innerhalb eines Spans.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanEvent {
+    pub time: SystemTime,
+    pub name: String,
+    pub attributes: Vec<(String, AttributeValue)>,
+}
+
+/// Beendeter Span, wie er exportiert wird.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FinishedSpan {
+    pub context: SpanContext,
+    pub parent_span_id: Option<SpanId>,
+    pub name: String,
+    pub kind: SpanKind,
+    pub start: SystemTime,
+    pub end: SystemTime,
+    pub attributes: Vec<(String, AttributeValue)>,
+    pub events: Vec<SpanEvent>,
+    pub status: SpanStatus,
+}
+
+impl FinishedSpan {
+    pub fn attribute(&self, key: &str) -> Option<&AttributeValue> {
+        self.attributes.iter().find(|(k, _)| k == key).map(|(_, value)| value)
+    }
+}
+
+/// Zustand eines offenen Spans, als Extension am Registry-Span.
+struct SpanState {
+    context: SpanContext,
+    parent_span_id: Option<SpanId>,
+    name: String,
+    kind: SpanKind,
+    start: SystemTime,
+    attributes: Vec<(String, AttributeValue)>,
+    events: Vec<SpanEvent>,
+    status: SpanStatus,
+    /// Wird an Kind-Spans und ausgehende RPCs weitergegeben.
+    baggage: Baggage,
+}
+
+impl SpanState {
+    fn set_attribute(&mut self, key: &str, value: AttributeValue) {
+        match self.attributes.iter_mut().find(|(k, _)| k == key) {
+            Some((_, existing)) => *existing = value,
+            None => self.attributes.push((key.to_string(), value)),
+        }
+    }
+
+    /// Request-, User- und Device-ID aus dem Baggage als Attribute `edda.*`.
+    fn apply_baggage_ids(&mut self) {
+        for key in [REQUEST_ID, USER_ID, DEVICE_ID] {
+            if let Some(value) = self.baggage.get(key).map(str::to_string) {
+                self.set_attribute(&format!("edda.{}", key), AttributeValue::String(value));
+            }
+        }
+    }
+}
+
+/// Schreibt Felder in einen Span (Attribute bzw. `otel.*`-Steuerfelder).
+struct SpanFields<'a>(&'a mut SpanState);
+
+impl SpanFields<'_> {
+    fn record(&mut self, field: &Field, value: AttributeValue) {
+        let state = &mut *self.0;
+        let text = || match value {
+            AttributeValue::String(ref s) => s.clone(),
+            ref other => format!("{:?}", other),
+        };
+        match field.name() {
+            "otel.name" => state.name = text(),
+            "otel.kind" => {
+                state.kind = match text().as_str() {
+                    "server" => SpanKind::Server,
+                    "client" => SpanKind::Client,
+                    _ => SpanKind::Internal,
+                }
+            }
+            "otel.status_code" => {
+                state.status = match text().as_str() {
+                    "ok" => SpanStatus::Ok,
+                    "error" => SpanStatus::Error(match state.status {
+                        SpanStatus::Error(ref message) => message.clone(),
+                        _ => String::new(),
+                    }),
+                    _ => SpanStatus::Unset,
+                }
+            }
+            "otel.status_message" => state.status = SpanStatus::Error(text()),
+            name => state.set_attribute(name, value),
+        }
+    }
+}
+
+impl Visit for SpanFields<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, AttributeValue::String(value.to_string()));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.record(field, AttributeValue::Bool(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.record(field, AttributeValue::Int(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        match i64::try_from(value) {
+            Ok(value) => self.record(field, AttributeValue::Int(value)),
+            Err(_) => self.record(field, AttributeValue::String(value.to_string())),
+        }
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.record(field, AttributeValue::Double(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.record(field, AttributeValue::String(format!("{:?}", value)));
+    }
+}
+
+/// Felder eines Events; `message` wird der Name des Span-Events.
+#[derive(Default)]
+struct EventFields {
+    message: Option<String>,
+    attributes: Vec<(String, AttributeValue)>,
+}
+
+impl Visit for EventFields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.attributes.push((field.name().to_string(), AttributeValue::String(value.to_string())));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.attributes.push((field.name().to_string(), AttributeValue::Bool(value)));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.attributes.push((field.name().to_string(), AttributeValue::Int(value)));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.attributes.push((field.name().to_string(), AttributeValue::Double(value)));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = Some(format!("{:?}", value));
+        } else {
+            self.attributes.push((field.name().to_string(), AttributeValue::String(format!("{:?}", value))));
+        }
+    }
+}
+
+/// Layer, der Spans mit W3C-Trace-Kontext versieht; siehe [`layer`](crate::layer).
+pub struct TraceLayer {
+    sender: mpsc::Sender<FinishedSpan>,
+}
+
+impl TraceLayer {
+    pub(crate) fn new(sender: mpsc::Sender<FinishedSpan>) -> Self {
+        Self { sender }
+    }
+}
+
+impl<S> Layer<S> for TraceLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let parent = span
+            .scope()
+            .skip(1)
+            .find_map(|ancestor| ancestor.extensions().get::<SpanState>().map(|state| (state.context, state.baggage.clone())));
+        let (context, parent_span_id, baggage) = match parent {
+            Some((parent, baggage)) => (
+                SpanContext { trace_id: parent.trace_id, span_id: SpanId::random(), sampled: parent.sampled },
+                Some(parent.span_id),
+                baggage,
+            ),
+            None => (SpanContext { trace_id: TraceId::random(), span_id: SpanId::random(), sampled: true }, None, Baggage::default()),
+        };
+        let mut state = SpanState {
+            context,
+            parent_span_id,
+            name: attrs.metadata().name().to_string(),
+            kind: SpanKind::Internal,
+            start: SystemTime::now(),
+            attributes: Vec::new(),
+            events: Vec::new(),
+            status: SpanStatus::Unset,
+            baggage,
+        };
+        state.apply_baggage_ids();
+        attrs.record(&mut SpanFields(&mut state));
+        span.extensions_mut().insert(state);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(state) = span.extensions_mut().get_mut::<SpanState>() {
+                values.record(&mut SpanFields(state));
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.event_span(event) else {
+            return;
+        };
+        let span = span.scope().find(|span| span.extensions().get::<SpanState>().is_some());
+        let Some(span) = span else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        let Some(state) = extensions.get_mut::<SpanState>() else {
+            return;
+        };
+        if state.events.len() >= MAX_EVENTS {
+            return;
+        }
+        let mut fields = EventFields::default();
+        event.record(&mut fields);
+        let mut attributes = vec![("level".to_string(), AttributeValue::String(event.metadata().level().to_string()))];
+        attributes.append(&mut fields.attributes);
+        state.events.push(SpanEvent {
+            time: SystemTime::now(),
+            name: fields.message.unwrap_or_else(|| event.metadata().name().to_string()),
+            attributes,
+        });
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(state) = span.extensions_mut().remove::<SpanState>() else {
+            return;
+        };
+        if !state.context.sampled {
+            return;
+        }
+        // A full queue (exporter stalled or not started) drops the span instead of blocking the service
+        let _ = self.sender.try_send(FinishedSpan {
+            context: state.context,
+            parent_span_id: state.parent_span_id,
+            name: state.name,
+            kind: state.kind,
+            start: state.start,
+            end: SystemTime::now(),
+            attributes: state.attributes,
+            events: state.events,
+            status: state.status,
+        });
+    }
+}
+
+/// Zugriff auf den Zustand eines Spans; `None`, wenn der Span nicht aufgezeichnet wird
+/// (kein [`TraceLayer`] installiert oder vom Filter ausgeschlossen).
+fn with_state<R>(span: &Span, f: impl FnOnce(&mut SpanState) -> R) -> Option<R> {
+    span.with_subscriber(|(id, dispatch)| {
+        let registry = dispatch.downcast_ref::<Registry>()?;
+        let span = registry.span(id)?;
+        let mut extensions = span.extensions_mut();
+        extensions.get_mut::<SpanState>().map(f)
+    })
+    .flatten()
+}
+
+/// Trace- und Span-ID des Spans, z.B. für den `traceparent` eines ausgehenden RPCs.
+pub fn span_context(span: &Span) -> Option<SpanContext> {
+    with_state(span, |state| state.context)
+}
+
+/// Die mit dem Trace weitergegebenen IDs.
+pub fn baggage(span: &Span) -> Baggage {
+    with_state(span, |state| state.baggage.clone()).unwrap_or_default()
+}
+
+/// Setzt den per `traceparent` empfangenen Aufrufer als Parent; muss vor dem Anlegen von
+/// Kind-Spans geschehen.
+pub fn set_remote_parent(span: &Span, parent: SpanContext, baggage: Baggage) {
+    with_state(span, |state| {
+        state.context.trace_id = parent.trace_id;
+        state.context.sampled = parent.sampled;
+        state.parent_span_id = Some(parent.span_id);
+    });
+    add_baggage(span, baggage);
+}
+
+/// Übernimmt empfangenes Baggage (auch ohne `traceparent`).
+pub(crate) fn add_baggage(span: &Span, baggage: Baggage) {
+    if baggage.is_empty() {
+        return;
+    }
+    with_state(span, |state| {
+        for (key, value) in baggage.iter() {
+            state.baggage.set(key, value);
+        }
+        state.apply_baggage_ids();
+    });
+}
+
+/// Macht den Span zur Wurzel des Traces der Request-ID (Trace-ID siehe
+/// [`TraceId::from_request_id`]); ein bisheriger Aufrufer bleibt als Attribut
+/// `edda.caller_traceparent` erhalten. Muss vor dem Anlegen von Kind-Spans geschehen.
+pub fn set_trace_root(span: &Span, request_id: &str) {
+    with_state(span, |state| {
+        if let Some(parent_span_id) = state.parent_span_id.take() {
+            let caller = SpanContext { trace_id: state.context.trace_id, span_id: parent_span_id, sampled: state.context.sampled };
+            state.set_attribute("edda.caller_traceparent", AttributeValue::String(caller.to_traceparent()));
+        }
+        state.context.trace_id = TraceId::from_request_id(request_id);
+    });
+}
+
+/// Request-, User- und Device-ID als Attribute des Spans und als Baggage für alle folgenden
+/// Spans und RPCs; leere IDs werden übergangen.
+pub fn record_request_ids(span: &Span, request_id: &str, user_id: &str, device_id: &str) {
+    with_state(span, |state| {
+        state.baggage.set(REQUEST_ID, request_id);
+        state.baggage.set(USER_ID, user_id);
+        state.baggage.set(DEVICE_ID, device_id);
+        state.apply_baggage_ids();
+    });
+}
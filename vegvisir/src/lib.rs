@@ -0,0 +1,48 @@
+//! Vegvisir: Distributed Tracing für Edda.
+//!
+//! Gibt den W3C Trace Context (`traceparent`, `baggage`) über alle tonic-Clients und -Server
+//! weiter, legt pro RPC einen Span mit Request-, User- und Device-ID an und exportiert die
+//! Spans als OTLP/JSON an einen lokalen Collector oder in eine rollierende Datei. Odins
+//! `request_id` ist die Wurzel des Traces: ein Sprachbefehl lässt sich über alle Services
+//! hinweg zusammensetzen.
+
+mod context;
+mod export;
+mod grpc;
+mod layer;
+
+pub use context::{
+    Baggage, SpanContext, SpanId, TraceId, BAGGAGE_HEADER, DEVICE_ID, REQUEST_ID, TRACEPARENT_HEADER, USER_ID,
+};
+pub use export::{to_otlp_json, RollingFile, TraceExportConfig, TraceExporter};
+pub use grpc::{extract, inject, traced, ClientTrace, ServerTrace, ServerTraceLayer, TracedChannel};
+pub use layer::{
+    baggage, record_request_ids, set_remote_parent, set_trace_root, span_context, AttributeValue, FinishedSpan,
+    SpanEvent, SpanKind, SpanStatus, TraceLayer,
+};
+
+use tracing::Subscriber;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// Beendete Spans, die auf den Export warten; bei vollem Puffer werden Spans verworfen.
+const SPAN_QUEUE: usize = 8192;
+
+/// Layer für `tracing_subscriber::registry()` und der zugehörige Exporter, der nach dem Laden
+/// der Settings gestartet wird:
+///
+/// ```ignore
+/// let (trace_layer, exporter) = vegvisir::layer("odin");
+/// tracing_subscriber::registry().with(fmt_layer).with(trace_layer).init();
+/// exporter.start(&settings.trace_export);
+/// ```
+///
+/// Aufgezeichnet werden Spans ab Level INFO.
+pub fn layer<S>(service_name: &str) -> (impl Layer<S>, TraceExporter)
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let (sender, receiver) = tokio::sync::mpsc::channel(SPAN_QUEUE);
+    (TraceLayer::new(sender).with_filter(LevelFilter::INFO), TraceExporter::new(service_name, receiver))
+}
@@ -0,0 +1,316 @@
+use std::future::Ready;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime};
+
+use tower_layer::Layer;
+use tower_service::Service;
+use tracing_subscriber::layer::SubscriberExt;
+use vegvisir::{
+    AttributeValue, Baggage, ClientTrace, FinishedSpan, RollingFile, ServerTraceLayer, SpanContext, SpanId, SpanKind,
+    SpanStatus, TraceExportConfig, TraceId,
+};
+
+const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+/// Führt `f` mit einem Subscriber aus, der nur den Vegvisir-Layer hat, und liefert die beendeten Spans.
+fn record_spans(f: impl FnOnce()) -> Vec<FinishedSpan> {
+    let (layer, exporter) = vegvisir::layer("test");
+    tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), f);
+    let mut receiver = exporter.take_receiver().unwrap();
+    let mut spans = Vec::new();
+    while let Ok(span) = receiver.try_recv() {
+        spans.push(span);
+    }
+    spans
+}
+
+fn find<'a>(spans: &'a [FinishedSpan], name: &str) -> &'a FinishedSpan {
+    spans.iter().find(|span| span.name == name).unwrap_or_else(|| panic!("no span {}", name))
+}
+
+fn text(value: &str) -> AttributeValue {
+    AttributeValue::String(value.to_string())
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(future)
+}
+
+/// Innerer gRPC-Service: merkt sich die Request-Header, legt einen Span an und antwortet
+/// optional mit einem Fehler-Status.
+#[derive(Clone, Default)]
+struct FakeService {
+    headers: Arc<Mutex<Option<http::HeaderMap>>>,
+    grpc_status: Option<(&'static str, &'static str)>,
+}
+
+impl Service<http::Request<()>> for FakeService {
+    type Response = http::Response<()>;
+    type Error = String;
+    type Future = Ready<Result<http::Response<()>, String>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), String>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<()>) -> Self::Future {
+        *self.headers.lock().unwrap() = Some(request.headers().clone());
+        tracing::info_span!("handler").in_scope(|| tracing::info!("handling"));
+        let mut response = http::Response::builder();
+        if let Some((code, message)) = self.grpc_status {
+            response = response.header("grpc-status", code).header("grpc-message", message);
+        }
+        std::future::ready(Ok(response.body(()).unwrap()))
+    }
+}
+
+fn grpc_request(path: &str) -> http::Request<()> {
+    http::Request::builder().uri(format!("http://localhost{}", path)).body(()).unwrap()
+}
+
+#[test]
+fn test_traceparent_round_trip() {
+    let context = SpanContext::from_traceparent(TRACEPARENT).unwrap();
+    assert_eq!(context.trace_id.to_string(), "4bf92f3577b34da6a3ce929d0e0e4736");
+    assert_eq!(context.span_id.to_string(), "00f067aa0ba902b7");
+    assert!(context.sampled);
+    assert_eq!(context.to_traceparent(), TRACEPARENT);
+
+    let unsampled = SpanContext::from_traceparent("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00").unwrap();
+    assert!(!unsampled.sampled);
+}
+
+#[test]
+fn test_invalid_traceparent_is_ignored() {
+    for value in [
+        "",
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+        "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+        "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+        "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+        "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+    ] {
+        assert_eq!(SpanContext::from_traceparent(value), None, "{}", value);
+    }
+}
+
+#[test]
+fn test_baggage_round_trip() {
+    let mut baggage = Baggage::default();
+    baggage.set("request_id", "req-1");
+    baggage.set("user_id", "Anna Müller, Küche");
+    baggage.set("device_id", "");
+    assert_eq!(baggage.get("device_id"), None);
+
+    let parsed = Baggage::from_header(&baggage.to_header());
+    assert_eq!(parsed, baggage);
+    assert_eq!(parsed.get("user_id"), Some("Anna Müller, Küche"));
+
+    let with_properties = Baggage::from_header("request_id=req-2;prop=1, broken, user_id = u1");
+    assert_eq!(with_properties.get("request_id"), Some("req-2"));
+    assert_eq!(with_properties.get("user_id"), Some("u1"));
+}
+
+#[test]
+fn test_trace_id_from_request_id() {
+    let uuid = "6f1c8a52-3b1e-4a7d-9c4e-2f0a1b3c4d5e";
+    assert_eq!(TraceId::from_request_id(uuid).to_string(), "6f1c8a523b1e4a7d9c4e2f0a1b3c4d5e");
+    assert_eq!(TraceId::from_request_id("req-42"), TraceId::from_request_id("req-42"));
+    assert_ne!(TraceId::from_request_id("req-42"), TraceId::from_request_id("req-43"));
+}
+
+#[test]
+fn test_child_spans_share_trace() {
+    let spans = record_spans(|| {
+        let root = tracing::info_span!("root", user = "u1");
+        root.in_scope(|| {
+            tracing::info_span!("child").in_scope(|| tracing::info!(step = 1, "working"));
+        });
+        // Below INFO spans are not recorded
+        tracing::debug_span!("debug").in_scope(|| {});
+    });
+
+    assert_eq!(spans.len(), 2);
+    let root = find(&spans, "root");
+    let child = find(&spans, "child");
+    assert_eq!(root.parent_span_id, None);
+    assert_eq!(child.parent_span_id, Some(root.context.span_id));
+    assert_eq!(child.context.trace_id, root.context.trace_id);
+    assert_ne!(child.context.span_id, root.context.span_id);
+    assert_eq!(root.kind, SpanKind::Internal);
+    assert_eq!(root.attribute("user"), Some(&text("u1")));
+    assert_eq!(child.events.len(), 1);
+    assert_eq!(child.events[0].name, "working");
+}
+
+#[test]
+fn test_client_injects_trace_headers() {
+    let inner = FakeService { grpc_status: Some(("5", "device%20not%20found")), ..Default::default() };
+    let headers = inner.headers.clone();
+    let spans = record_spans(|| {
+        let span = tracing::info_span!("request");
+        vegvisir::record_request_ids(&span, "req-1", "u1", "dev-1");
+        let _entered = span.enter();
+        let response = block_on(ClientTrace::new(inner).call(grpc_request("/thor.ThorService/ExecuteAction")));
+        assert!(response.is_ok());
+    });
+
+    let request = find(&spans, "request");
+    let client = find(&spans, "thor.ThorService/ExecuteAction");
+    assert_eq!(client.kind, SpanKind::Client);
+    assert_eq!(client.parent_span_id, Some(request.context.span_id));
+    assert_eq!(client.context.trace_id, request.context.trace_id);
+    assert_eq!(client.attribute("rpc.method"), Some(&text("ExecuteAction")));
+    assert_eq!(client.attribute("rpc.grpc.status_code"), Some(&AttributeValue::Int(5)));
+    assert_eq!(client.attribute("edda.device_id"), Some(&text("dev-1")));
+    assert_eq!(client.status, SpanStatus::Error("device not found".to_string()));
+
+    let headers = headers.lock().unwrap().clone().unwrap();
+    let (parent, baggage) = vegvisir::extract(&headers);
+    assert_eq!(parent.unwrap().span_id, client.context.span_id);
+    assert_eq!(baggage.get("request_id"), Some("req-1"));
+    assert_eq!(baggage.get("user_id"), Some("u1"));
+}
+
+#[test]
+fn test_server_adopts_remote_parent() {
+    let mut service = ServerTraceLayer.layer(FakeService::default());
+    let spans = record_spans(|| {
+        // The server span must not become a child of the span the connection is served in
+        let _connection = tracing::info_span!("connection").entered();
+        let mut request = grpc_request("/geri.GeriService/ProcessPrompt");
+        request.headers_mut().insert("traceparent", TRACEPARENT.parse().unwrap());
+        request.headers_mut().insert("baggage", "request_id=req-1,user_id=u%201".parse().unwrap());
+        block_on(service.call(request)).unwrap();
+    });
+
+    let server = find(&spans, "geri.GeriService/ProcessPrompt");
+    let handler = find(&spans, "handler");
+    assert_eq!(server.kind, SpanKind::Server);
+    assert_eq!(server.context.trace_id.to_string(), "4bf92f3577b34da6a3ce929d0e0e4736");
+    assert_eq!(server.parent_span_id, SpanId::from_hex("00f067aa0ba902b7"));
+    assert_eq!(server.attribute("edda.request_id"), Some(&text("req-1")));
+    assert_eq!(server.attribute("edda.user_id"), Some(&text("u 1")));
+    assert_eq!(server.attribute("rpc.grpc.status_code"), Some(&AttributeValue::Int(0)));
+    assert_eq!(handler.context.trace_id, server.context.trace_id);
+    assert_eq!(handler.parent_span_id, Some(server.context.span_id));
+}
+
+#[test]
+fn test_unsampled_trace_is_not_exported() {
+    let mut service = ServerTraceLayer.layer(FakeService::default());
+    let spans = record_spans(|| {
+        let mut request = grpc_request("/geri.GeriService/ProcessPrompt");
+        request
+            .headers_mut()
+            .insert("traceparent", "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00".parse().unwrap());
+        block_on(service.call(request)).unwrap();
+    });
+    assert!(spans.is_empty());
+}
+
+#[test]
+fn test_request_id_becomes_trace_root() {
+    let request_id = "6f1c8a52-3b1e-4a7d-9c4e-2f0a1b3c4d5e";
+    let spans = record_spans(|| {
+        let span = tracing::info_span!("process");
+        vegvisir::set_remote_parent(&span, SpanContext::from_traceparent(TRACEPARENT).unwrap(), Baggage::default());
+        vegvisir::set_trace_root(&span, request_id);
+        vegvisir::record_request_ids(&span, request_id, "u1", "");
+        span.in_scope(|| tracing::info_span!("step").in_scope(|| {}));
+    });
+
+    let process = find(&spans, "process");
+    let step = find(&spans, "step");
+    assert_eq!(process.context.trace_id, TraceId::from_request_id(request_id));
+    assert_eq!(process.parent_span_id, None);
+    assert_eq!(process.attribute("edda.caller_traceparent"), Some(&text(TRACEPARENT)));
+    assert_eq!(process.attribute("edda.device_id"), None);
+    assert_eq!(step.context.trace_id, process.context.trace_id);
+}
+
+#[test]
+fn test_otlp_json_shape() {
+    let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1);
+    let span = FinishedSpan {
+        context: SpanContext::from_traceparent(TRACEPARENT).unwrap(),
+        parent_span_id: None,
+        name: "odin.OdinService/Process".to_string(),
+        kind: SpanKind::Server,
+        start,
+        end: start + Duration::from_millis(5),
+        attributes: vec![
+            ("edda.request_id".to_string(), AttributeValue::String("req-1".to_string())),
+            ("rpc.grpc.status_code".to_string(), AttributeValue::Int(0)),
+        ],
+        events: Vec::new(),
+        status: SpanStatus::Error("failed".to_string()),
+    };
+
+    let json = vegvisir::to_otlp_json("odin", &[span]);
+    let resource = &json["resourceSpans"][0];
+    assert_eq!(resource["resource"]["attributes"][0]["key"], "service.name");
+    assert_eq!(resource["resource"]["attributes"][0]["value"]["stringValue"], "odin");
+    let span = &resource["scopeSpans"][0]["spans"][0];
+    assert_eq!(span["traceId"], "4bf92f3577b34da6a3ce929d0e0e4736");
+    assert_eq!(span["spanId"], "00f067aa0ba902b7");
+    assert_eq!(span["parentSpanId"], "");
+    assert_eq!(span["kind"], 2);
+    assert_eq!(span["startTimeUnixNano"], "1000000000");
+    assert_eq!(span["endTimeUnixNano"], "1005000000");
+    assert_eq!(span["attributes"][1]["value"]["intValue"], "0");
+    assert_eq!(span["status"]["code"], 2);
+    assert_eq!(span["status"]["message"], "failed");
+}
+
+#[tokio::test]
+async fn test_rolling_file_rotates() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("traces").join("spans.json");
+    let mut file = RollingFile::new(&path, 20, 3);
+    for line in ["first-line-xx", "second-line-x", "third-line-xx", "fourth-line-x"] {
+        file.append_line(line).await.unwrap();
+    }
+
+    let read = |suffix: &str| std::fs::read_to_string(format!("{}{}", path.display(), suffix)).unwrap();
+    assert_eq!(read(""), "fourth-line-x\n");
+    assert_eq!(read(".1"), "third-line-xx\n");
+    assert_eq!(read(".2"), "second-line-x\n");
+    assert!(!dir.path().join("traces").join("spans.json.3").exists());
+}
+
+#[tokio::test]
+async fn test_exporter_writes_json_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("spans.json");
+    let (layer, exporter) = vegvisir::layer("freki");
+    let config = TraceExportConfig {
+        file_path: path.display().to_string(),
+        flush_interval_ms: 10,
+        ..Default::default()
+    };
+    assert!(config.is_enabled());
+    let handle = exporter.start(&config).unwrap();
+    assert!(exporter.take_receiver().is_none());
+
+    tracing::subscriber::with_default(tracing_subscriber::registry().with(layer), || {
+        tracing::info_span!("index_document", document_id = "doc-1").in_scope(|| {});
+    });
+    // The subscriber is gone, so the exporter flushes and stops
+    tokio::time::timeout(Duration::from_secs(5), handle).await.unwrap().unwrap();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    let json: serde_json::Value = serde_json::from_str(content.lines().next().unwrap()).unwrap();
+    assert_eq!(json["resourceSpans"][0]["resource"]["attributes"][0]["value"]["stringValue"], "freki");
+    assert_eq!(json["resourceSpans"][0]["scopeSpans"][0]["spans"][0]["name"], "index_document");
+}
+
+#[test]
+fn test_disabled_exporter_drops_spans() {
+    let (_layer, exporter) = vegvisir::layer::<tracing_subscriber::Registry>("thor");
+    assert!(!TraceExportConfig::default().is_enabled());
+    assert!(exporter.start(&TraceExportConfig::default()).is_none());
+    assert!(exporter.take_receiver().is_none());
+}